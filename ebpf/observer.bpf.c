@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * File and network observer for sandboxed servers, loaded by supermcp
+ * built with the `ebpf` feature when `[ebpf] enabled = true`.
+ *
+ * Build against the running kernel's BTF:
+ *
+ *   bpftool btf dump file /sys/kernel/btf/vmlinux format c > vmlinux.h
+ *   clang -O2 -g -target bpf -c observer.bpf.c -o observer.bpf.o
+ *
+ * supermcp puts each server's process in WATCHED; processes they fork are
+ * added here and dropped when they exit. Paths opened with openat(2) and
+ * addresses passed to connect(2) by watched processes go out on EVENTS.
+ */
+#include "vmlinux.h"
+#include <bpf/bpf_helpers.h>
+#include <bpf/bpf_tracing.h>
+
+#define DATA_LEN 256
+
+#define EVENT_OPEN 1
+#define EVENT_CONNECT 2
+
+/* Must match `RawEvent` in src/sandbox/observer.rs */
+struct event {
+	__u32 pid;
+	__u32 server;
+	__u32 kind;
+	__u32 flags;
+	__s32 dirfd;
+	__u32 len;
+	__u8 data[DATA_LEN];
+};
+
+/* Watched process (tgid) -> server id */
+struct {
+	__uint(type, BPF_MAP_TYPE_HASH);
+	__uint(max_entries, 16384);
+	__type(key, __u32);
+	__type(value, __u32);
+} WATCHED SEC(".maps");
+
+struct {
+	__uint(type, BPF_MAP_TYPE_RINGBUF);
+	__uint(max_entries, 1 << 22);
+} EVENTS SEC(".maps");
+
+static __always_inline struct event *reserve(__u32 kind)
+{
+	__u32 pid = bpf_get_current_pid_tgid() >> 32;
+	__u32 *server = bpf_map_lookup_elem(&WATCHED, &pid);
+	struct event *e;
+
+	if (!server)
+		return NULL;
+	e = bpf_ringbuf_reserve(&EVENTS, sizeof(*e), 0);
+	if (!e)
+		return NULL;
+	e->pid = pid;
+	e->server = *server;
+	e->kind = kind;
+	e->flags = 0;
+	e->dirfd = 0;
+	e->len = 0;
+	return e;
+}
+
+SEC("tracepoint/syscalls/sys_enter_openat")
+int on_openat(struct trace_event_raw_sys_enter *ctx)
+{
+	struct event *e = reserve(EVENT_OPEN);
+	long len;
+
+	if (!e)
+		return 0;
+	e->dirfd = (__s32)ctx->args[0];
+	e->flags = (__u32)ctx->args[2];
+	len = bpf_probe_read_user_str(e->data, sizeof(e->data), (void *)ctx->args[1]);
+	if (len <= 0) {
+		bpf_ringbuf_discard(e, 0);
+		return 0;
+	}
+	e->len = len - 1;
+	bpf_ringbuf_submit(e, 0);
+	return 0;
+}
+
+SEC("tracepoint/syscalls/sys_enter_connect")
+int on_connect(struct trace_event_raw_sys_enter *ctx)
+{
+	struct event *e = reserve(EVENT_CONNECT);
+	__u32 len;
+
+	if (!e)
+		return 0;
+	len = (__u32)ctx->args[2];
+	if (len > sizeof(e->data))
+		len = sizeof(e->data);
+	if (bpf_probe_read_user(e->data, len, (void *)ctx->args[1])) {
+		bpf_ringbuf_discard(e, 0);
+		return 0;
+	}
+	e->len = len;
+	bpf_ringbuf_submit(e, 0);
+	return 0;
+}
+
+SEC("tp_btf/sched_process_fork")
+int BPF_PROG(on_fork, struct task_struct *parent, struct task_struct *child)
+{
+	__u32 parent_pid = parent->tgid;
+	__u32 child_pid = child->tgid;
+	__u32 *server;
+
+	/* Threads share their process's tgid, already watched */
+	if (child_pid == parent_pid)
+		return 0;
+	server = bpf_map_lookup_elem(&WATCHED, &parent_pid);
+	if (server)
+		bpf_map_update_elem(&WATCHED, &child_pid, server, BPF_ANY);
+	return 0;
+}
+
+SEC("tp_btf/sched_process_exit")
+int BPF_PROG(on_exit, struct task_struct *task)
+{
+	__u32 pid = task->tgid;
+
+	/* Only when the whole process goes, not one of its threads */
+	if (task->pid == task->tgid)
+		bpf_map_delete_elem(&WATCHED, &pid);
+	return 0;
+}
+
+char LICENSE[] SEC("license") = "GPL";
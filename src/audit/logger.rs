@@ -1,7 +1,12 @@
 //! Structured audit logging for security events
 
+use crate::cloud::tenant_keys::TenantKeys;
+use crate::utils::errors::{McpError, McpResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::fs::{File, OpenOptions};
@@ -43,6 +48,32 @@ pub enum AuditEventType {
     RateLimitHit,
     /// Suspicious activity detected
     SuspiciousActivity,
+    /// Upstream server restarted after an unexpected exit
+    ServerRestart,
+    /// Upstream server gave up restarting and is degraded
+    ServerDegraded,
+    /// Script executed through a runtime tool
+    RuntimeExec,
+    /// Server registered from an editor config by the import watcher
+    ServerImported,
+    /// Canary server version lost its traffic to elevated errors
+    VersionRollback,
+    /// Upstream tool catalog drifted from the approved one
+    ToolDrift,
+    /// Operator approved a server's drifted tool catalog
+    ToolDriftApproved,
+    /// A user's consent to a tool was remembered
+    ConsentGranted,
+    /// A remembered consent was withdrawn
+    ConsentRevoked,
+    /// A sandboxed server first opened a path or connected to an endpoint
+    SandboxObserved,
+    /// A file was read or written through the file broker
+    FileBrokerAccess,
+    /// A server was given temporary sandbox elevation
+    SandboxElevated,
+    /// A server's sandbox elevation expired or was revoked
+    SandboxElevationReverted,
 }
 
 /// Audit event structure
@@ -162,11 +193,93 @@ pub enum LogFormat {
     Pretty,
 }
 
+static GLOBAL_LOGGER: OnceCell<Arc<AuditLogger>> = OnceCell::new();
+
+/// Install the process-wide audit logger used by [`record`]
+pub fn install_global(logger: Arc<AuditLogger>) -> bool {
+    GLOBAL_LOGGER.set(logger).is_ok()
+}
+
+/// Record an event with the process-wide audit logger, if one is installed
+///
+/// Events are written in the background so callers never wait on disk I/O.
+/// Events recorded while serving a request carry its correlation ID.
+pub fn record(mut event: AuditEvent) {
+    if event.request_id.is_none() {
+        event.request_id = crate::core::request_id::current_correlation_id().map(|id| id.to_string());
+    }
+    info!(target: "audit", event_type = ?event.event_type, server = ?event.server_name, success = event.success, "audit event");
+
+    if let Some(logger) = GLOBAL_LOGGER.get() {
+        let logger = logger.clone();
+        tokio::spawn(async move { logger.log(event).await });
+    }
+}
+
+/// Associated data of sealed audit records
+const SEALED_AAD: &[u8] = b"supermcp audit";
+
+/// A record sealed with its tenant's key; only the time and tenant are
+/// readable without the key
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedRecord {
+    timestamp: DateTime<Utc>,
+    tenant: String,
+    sealed: String,
+}
+
+/// Written in place of a record its tenant's key could not seal, so the
+/// event is on record at once; the sealed record follows once sealing
+/// succeeds
+#[derive(Debug, Serialize, Deserialize)]
+struct UnsealedMarker {
+    timestamp: DateTime<Utc>,
+    tenant: String,
+    event_type: AuditEventType,
+    unsealed: String,
+}
+
+/// A record kept in the clear, in memory only, until it can be sealed
+struct PendingRecord {
+    timestamp: DateTime<Utc>,
+    tenant: String,
+    line: String,
+}
+
+/// Records kept waiting to be sealed; past this the oldest is given up,
+/// leaving only its marker
+const MAX_PENDING: usize = 1000;
+
+/// The tenant an audit log line was sealed for, or could not be sealed
+/// for, `None` if it is not a tenant's record
+pub fn sealed_tenant(line: &str) -> Option<String> {
+    serde_json::from_str::<SealedRecord>(line)
+        .map(|record| record.tenant)
+        .or_else(|_| serde_json::from_str::<UnsealedMarker>(line).map(|marker| marker.tenant))
+        .ok()
+}
+
+/// An audit log line as written, with sealed records opened
+pub async fn open_line(keys: &TenantKeys, line: &str) -> McpResult<String> {
+    let Ok(record) = serde_json::from_str::<SealedRecord>(line) else {
+        return Ok(line.to_string());
+    };
+    let sealed = STANDARD
+        .decode(&record.sealed)
+        .map_err(|e| McpError::InvalidRequest(format!("Invalid sealed audit record: {}", e)))?;
+    let opened = keys.open(&record.tenant, SEALED_AAD, &sealed).await?;
+    Ok(String::from_utf8_lossy(&opened).trim_end().to_string())
+}
+
 /// Async audit logger
 pub struct AuditLogger {
     config: AuditConfig,
     file: Arc<Mutex<File>>,
     current_size: Arc<Mutex<u64>>,
+    /// Seals records of authenticated users with their tenant key
+    tenant_keys: Option<Arc<TenantKeys>>,
+    /// Records whose sealing failed, retried in order on the next event
+    pending: Mutex<VecDeque<PendingRecord>>,
 }
 
 impl AuditLogger {
@@ -193,9 +306,72 @@ impl AuditLogger {
             config,
             file: Arc::new(Mutex::new(file)),
             current_size: Arc::new(Mutex::new(current_size)),
+            tenant_keys: None,
+            pending: Mutex::new(VecDeque::new()),
         })
     }
 
+    /// Encrypt the records of each tenant with its own key
+    pub fn with_tenant_keys(mut self, keys: Arc<TenantKeys>) -> Self {
+        self.tenant_keys = Some(keys);
+        self
+    }
+
+    async fn seal(&self, keys: &TenantKeys, timestamp: DateTime<Utc>, tenant: &str, line: &str) -> McpResult<String> {
+        let sealed = keys.seal(tenant, SEALED_AAD, line.as_bytes()).await?;
+        let record = SealedRecord {
+            timestamp,
+            tenant: tenant.to_string(),
+            sealed: STANDARD.encode(sealed),
+        };
+        Ok(format!("{}\n", serde_json::to_string(&record)?))
+    }
+
+    /// Write a marker for a record that could not be sealed and keep the
+    /// record to seal later
+    async fn defer(&self, event: &AuditEvent, tenant: &str, line: String, e: McpError) {
+        error!("Failed to encrypt audit event for {}, will retry: {}", tenant, e);
+        let marker = UnsealedMarker {
+            timestamp: event.timestamp,
+            tenant: tenant.to_string(),
+            event_type: event.event_type.clone(),
+            unsealed: e.to_string(),
+        };
+        match serde_json::to_string(&marker) {
+            Ok(marker) => self.write(&format!("{}\n", marker)).await,
+            Err(e) => error!("Failed to serialize audit marker: {}", e),
+        }
+
+        let mut pending = self.pending.lock().await;
+        pending.push_back(PendingRecord {
+            timestamp: event.timestamp,
+            tenant: tenant.to_string(),
+            line,
+        });
+        if pending.len() > MAX_PENDING {
+            if let Some(dropped) = pending.pop_front() {
+                error!(
+                    "Gave up sealing the audit event for {} at {}; only its marker was written",
+                    dropped.tenant, dropped.timestamp
+                );
+            }
+        }
+    }
+
+    /// Seal and write deferred records, oldest first, until one still fails
+    async fn retry_pending(&self, keys: &TenantKeys) {
+        let mut pending = self.pending.lock().await;
+        while let Some(record) = pending.front() {
+            match self.seal(keys, record.timestamp, &record.tenant, &record.line).await {
+                Ok(sealed) => {
+                    self.write(&sealed).await;
+                    pending.pop_front();
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
     /// Log an audit event
     pub async fn log(&self, event: AuditEvent) {
         let log_line = match self.config.format {
@@ -208,7 +384,25 @@ impl AuditLogger {
             },
             LogFormat::Pretty => self.format_pretty(&event),
         };
+        let log_line = match (&self.tenant_keys, &event.user_id) {
+            (Some(keys), tenant) => {
+                self.retry_pending(keys).await;
+                match tenant {
+                    Some(tenant) => match self.seal(keys, event.timestamp, tenant, &log_line).await {
+                        Ok(sealed) => sealed,
+                        Err(e) => return self.defer(&event, tenant, log_line, e).await,
+                    },
+                    None => log_line,
+                }
+            }
+            (None, _) => log_line,
+        };
 
+        self.write(&log_line).await;
+    }
+
+    /// Append a line to the log, rotating it first if it is full
+    async fn write(&self, log_line: &str) {
         let bytes = log_line.as_bytes();
         let len = bytes.len() as u64;
 
@@ -421,6 +615,100 @@ mod tests {
         assert!(content.contains("127.0.0.1"));
     }
 
+    #[tokio::test]
+    async fn test_tenant_records_are_sealed() {
+        use crate::cloud::tenant_keys::LocalKms;
+        use crate::cloud::InMemoryBackend;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = AuditConfig {
+            path: temp_dir.path().join("audit.log"),
+            ..Default::default()
+        };
+        let keys = Arc::new(TenantKeys::new(
+            Arc::new(LocalKms::new([1u8; 32])),
+            Arc::new(InMemoryBackend::new()),
+        ));
+        let logger = AuditLogger::new(config).await.unwrap().with_tenant_keys(keys.clone());
+
+        logger
+            .log(AuditEvent::new(AuditEventType::Request).with_user_id("alice").with_server_name("files"))
+            .await;
+        logger.log(AuditEvent::new(AuditEventType::ServerStart)).await;
+
+        let content = tokio::fs::read_to_string(temp_dir.path().join("audit.log"))
+            .await
+            .unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert!(lines[0].contains("\"tenant\":\"alice\""));
+        assert!(!lines[0].contains("files"));
+        assert!(lines[1].contains("server_start"));
+
+        let opened = open_line(&keys, lines[0]).await.unwrap();
+        assert!(opened.contains("\"server_name\":\"files\""));
+        assert_eq!(open_line(&keys, lines[1]).await.unwrap(), lines[1]);
+    }
+
+    #[tokio::test]
+    async fn test_unsealable_records_are_kept_and_retried() {
+        use crate::cloud::tenant_keys::{KeyWrapper, LocalKms};
+        use crate::cloud::InMemoryBackend;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// KMS that is unreachable until `up` is set
+        struct Flaky {
+            up: AtomicBool,
+            kms: LocalKms,
+        }
+
+        #[async_trait::async_trait]
+        impl KeyWrapper for Flaky {
+            async fn wrap(&self, tenant: &str, key: &[u8; 32]) -> McpResult<String> {
+                if !self.up.load(Ordering::SeqCst) {
+                    return Err(McpError::TransportError("KMS unreachable".to_string()));
+                }
+                self.kms.wrap(tenant, key).await
+            }
+
+            async fn unwrap(&self, tenant: &str, wrapped: &str) -> McpResult<[u8; 32]> {
+                self.kms.unwrap(tenant, wrapped).await
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = AuditConfig {
+            path: temp_dir.path().join("audit.log"),
+            ..Default::default()
+        };
+        let kms = Arc::new(Flaky {
+            up: AtomicBool::new(false),
+            kms: LocalKms::new([1u8; 32]),
+        });
+        let keys = Arc::new(TenantKeys::new(kms.clone(), Arc::new(InMemoryBackend::new())));
+        let logger = AuditLogger::new(config).await.unwrap().with_tenant_keys(keys.clone());
+
+        logger
+            .log(AuditEvent::new(AuditEventType::Request).with_user_id("alice").with_server_name("files"))
+            .await;
+        let read = || tokio::fs::read_to_string(temp_dir.path().join("audit.log"));
+        let content = read().await.unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("\"unsealed\""));
+        assert!(!lines[0].contains("files"));
+        assert_eq!(sealed_tenant(lines[0]).as_deref(), Some("alice"));
+
+        // The next event once the KMS is back writes the kept record first
+        kms.up.store(true, Ordering::SeqCst);
+        logger.log(AuditEvent::new(AuditEventType::ServerStart)).await;
+        let content = read().await.unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(open_line(&keys, lines[1]).await.unwrap().contains("\"server_name\":\"files\""));
+        assert!(lines[2].contains("server_start"));
+        assert!(logger.pending.lock().await.is_empty());
+    }
+
     #[test]
     fn test_audit_event_builder() {
         let event = AuditEvent::new(AuditEventType::AuthAttempt)
@@ -2,4 +2,4 @@
 
 pub mod logger;
 
-pub use logger::{AuditEvent, AuditEventType, AuditLogger};
+pub use logger::{install_global, record, AuditConfig, AuditEvent, AuditEventType, AuditLogger};
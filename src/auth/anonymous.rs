@@ -0,0 +1,66 @@
+//! Anonymous read-only authentication provider
+use crate::auth::provider::{AuthProvider, Session, Tokens};
+use crate::auth::static_token::StaticTokenAuth;
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+
+/// Provider for `anonymous_readonly` mode
+///
+/// Requests without a token carry no session and are held to read-only
+/// methods by the HTTP layer. An optional operator token still gets a
+/// full-access session, so the proxy can be managed without switching modes.
+pub struct AnonymousReadonlyAuth {
+    operator: Option<StaticTokenAuth>,
+}
+
+impl AnonymousReadonlyAuth {
+    pub fn new() -> Self {
+        Self { operator: None }
+    }
+
+    pub fn with_operator_token(mut self, token: impl Into<String>) -> Self {
+        let token = token.into();
+        self.operator = (!token.is_empty()).then(|| StaticTokenAuth::new(token));
+        self
+    }
+}
+
+impl Default for AnonymousReadonlyAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for AnonymousReadonlyAuth {
+    async fn validate_token(
+        &self,
+        token: &str,
+    ) -> McpResult<Session> {
+        match &self.operator {
+            Some(operator) => operator.validate_token(token).await,
+            None => Err(McpError::AuthError(
+                "This server only serves anonymous read-only requests".to_string(),
+            )),
+        }
+    }
+
+    async fn refresh_token(
+        &self,
+        _refresh_token: &str,
+    ) -> McpResult<Tokens> {
+        Err(McpError::AuthError("Token refresh not supported".to_string()))
+    }
+
+    async fn generate_token(
+        &self,
+        _user_id: &str,
+        _scopes: Vec<String>,
+    ) -> McpResult<Tokens> {
+        Err(McpError::AuthError("Token generation not supported".to_string()))
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+}
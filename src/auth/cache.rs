@@ -2,13 +2,33 @@
 //!
 //! Implements an LRU cache with TTL for validated tokens to reduce
 //! redundant token validation overhead.
-
-use crate::auth::provider::Session;
+//!
+//! With `persist_path` set, validated sessions and OAuth tokens are also
+//! saved to that file, sealed with ChaCha20-Poly1305, every cleanup
+//! interval and on [`TokenCache::save`], and restored on startup. The key
+//! is kept in the OS keyring, or in a `.key` file readable only by the
+//! user when there is no keyring.
+
+use crate::auth::keyring;
+use crate::auth::provider::{AuthProvider, Session, Tokens};
+use crate::config::AuthCacheConfig;
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+/// First bytes of a persisted cache file
+const FILE_MAGIC: &[u8] = b"SMTC1";
 
 /// Cached session with metadata
 #[derive(Debug, Clone)]
@@ -54,6 +74,10 @@ pub struct TokenCacheConfig {
     pub max_size: usize,
     /// Cleanup interval
     pub cleanup_interval: Duration,
+    /// Encrypted file the cache is saved to; `None` keeps it in memory only
+    pub persist_path: Option<PathBuf>,
+    /// Keep the file's key in the OS keyring rather than a `.key` file
+    pub use_keyring: bool,
 }
 
 impl Default for TokenCacheConfig {
@@ -62,25 +86,270 @@ impl Default for TokenCacheConfig {
             default_ttl: Duration::from_secs(300), // 5 minutes
             max_size: 10000,
             cleanup_interval: Duration::from_secs(60),
+            persist_path: None,
+            use_keyring: true,
+        }
+    }
+}
+
+impl TokenCacheConfig {
+    /// Per-user location for a persisted cache
+    pub fn default_persist_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("supermcp/token-cache.bin")
+    }
+
+    /// Settings for `[auth.cache]`
+    pub fn from_config(config: &AuthCacheConfig) -> Self {
+        let persist_path = config.persist.then(|| match &config.persist_path {
+            Some(path) => PathBuf::from(shellexpand::tilde(path).as_ref()),
+            None => Self::default_persist_path(),
+        });
+        Self {
+            default_ttl: Duration::from_secs(config.ttl_seconds),
+            max_size: config.max_size,
+            persist_path,
+            use_keyring: config.use_keyring,
+            ..Default::default()
+        }
+    }
+}
+
+/// OAuth tokens and when they were stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredTokens {
+    tokens: Tokens,
+    saved_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedSession {
+    key: String,
+    session: Session,
+    expires_at: DateTime<Utc>,
+    access_count: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedCache {
+    sessions: Vec<PersistedSession>,
+    tokens: Vec<(String, StoredTokens)>,
+}
+
+/// The encrypted file a cache is saved to
+struct CacheStore {
+    path: PathBuf,
+    key: LessSafeKey,
+    key_source: &'static str,
+    dirty: AtomicBool,
+    loaded: AtomicUsize,
+    saves: AtomicU64,
+    save_failures: AtomicU64,
+}
+
+impl CacheStore {
+    fn open(path: PathBuf, use_keyring: bool) -> McpResult<Self> {
+        let (key, key_source) = data_key(&path, use_keyring)?;
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+            .map_err(|_| McpError::InternalError("Invalid token cache key".to_string()))?;
+        Ok(Self {
+            path,
+            key: LessSafeKey::new(key),
+            key_source,
+            dirty: AtomicBool::new(false),
+            loaded: AtomicUsize::new(0),
+            saves: AtomicU64::new(0),
+            save_failures: AtomicU64::new(0),
+        })
+    }
+
+    fn read(&self) -> McpResult<Option<PersistedCache>> {
+        let sealed = match std::fs::read(&self.path) {
+            Ok(sealed) => sealed,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(McpError::Io(e)),
+        };
+        let invalid = || McpError::AuthError("Token cache file is corrupt or sealed with another key".to_string());
+        let sealed = sealed.strip_prefix(FILE_MAGIC).ok_or_else(invalid)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(FILE_MAGIC), &mut buffer)
+            .map_err(|_| invalid())?;
+        Ok(Some(serde_json::from_slice(plaintext)?))
+    }
+
+    async fn write(&self, cache: &PersistedCache) -> McpResult<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| McpError::InternalError("Failed to generate nonce".to_string()))?;
+        let mut sealed = serde_json::to_vec(cache)?;
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(FILE_MAGIC), &mut sealed)
+            .map_err(|_| McpError::InternalError("Encryption failed".to_string()))?;
+
+        let mut contents = FILE_MAGIC.to_vec();
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&sealed);
+
+        // Write beside the file and rename, so a crash never leaves half of it
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, &contents)?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+/// Create (or truncate) a file only the current user can read
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> McpResult<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)?;
+    Ok(())
+}
+
+fn random_key() -> McpResult<[u8; 32]> {
+    let mut key = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| McpError::InternalError("Failed to generate token cache key".to_string()))?;
+    Ok(key)
+}
+
+fn decode_key(encoded: &str) -> McpResult<[u8; 32]> {
+    STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| McpError::AuthError("Stored token cache key is invalid".to_string()))
+}
+
+/// The key a cache file is sealed with, and where it is kept
+///
+/// An existing key wins: the keyring's, then a `.key` file. A new key goes
+/// to the keyring if possible, otherwise to the `.key` file.
+fn data_key(path: &Path, use_keyring: bool) -> McpResult<([u8; 32], &'static str)> {
+    let account = format!("token-cache:{}", path.display());
+    let key_path = path.with_extension("key");
+
+    if use_keyring {
+        match keyring::get(&account) {
+            Ok(Some(encoded)) => return Ok((decode_key(&encoded)?, "keyring")),
+            Ok(None) if !key_path.exists() => {
+                let key = random_key()?;
+                match keyring::set(&account, &STANDARD.encode(key)) {
+                    Ok(()) => return Ok((key, "keyring")),
+                    Err(e) => warn!("{}; keeping the token cache key in {}", e, key_path.display()),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("{}; keeping the token cache key in {}", e, key_path.display()),
+        }
+    }
+
+    match std::fs::read_to_string(&key_path) {
+        Ok(encoded) => Ok((decode_key(&encoded)?, "file")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = random_key()?;
+            write_private(&key_path, STANDARD.encode(key).as_bytes())?;
+            Ok((key, "file"))
+        }
+        Err(e) => Err(McpError::Io(e)),
+    }
+}
+
+type SessionMap = DashMap<String, Arc<RwLock<CachedSession>>>;
+
+/// Copy of the live entries for saving
+async fn snapshot(sessions: &SessionMap, tokens: &DashMap<String, StoredTokens>) -> PersistedCache {
+    let entries: Vec<(String, Arc<RwLock<CachedSession>>)> = sessions
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().clone()))
+        .collect();
+    let mut persisted = Vec::with_capacity(entries.len());
+    for (key, entry) in entries {
+        let entry = entry.read().await;
+        let Some(remaining) = entry.ttl.checked_sub(entry.cached_at.elapsed()) else {
+            continue;
+        };
+        persisted.push(PersistedSession {
+            key,
+            session: entry.session.clone(),
+            expires_at: Utc::now() + chrono::Duration::from_std(remaining).unwrap_or_default(),
+            access_count: entry.access_count,
+        });
+    }
+    PersistedCache {
+        sessions: persisted,
+        tokens: tokens
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect(),
+    }
+}
+
+async fn save_to(store: &CacheStore, sessions: &SessionMap, tokens: &DashMap<String, StoredTokens>) -> McpResult<()> {
+    store.dirty.store(false, Ordering::Relaxed);
+    let result = store.write(&snapshot(sessions, tokens).await).await;
+    match &result {
+        Ok(()) => {
+            store.saves.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(_) => {
+            store.save_failures.fetch_add(1, Ordering::Relaxed);
+            store.dirty.store(true, Ordering::Relaxed);
         }
     }
+    result
 }
 
 /// LRU cache with TTL for validated tokens
 pub struct TokenCache {
     /// Cache storage: token hash -> cached session
-    cache: DashMap<String, Arc<RwLock<CachedSession>>>,
+    cache: Arc<SessionMap>,
+    /// OAuth tokens by caller-chosen key
+    tokens: Arc<DashMap<String, StoredTokens>>,
     /// Configuration
     config: TokenCacheConfig,
+    /// Where the cache is persisted, if anywhere
+    store: Option<Arc<CacheStore>>,
 }
 
 impl TokenCache {
-    /// Create a new token cache
+    /// Create a new token cache, restoring a persisted one
+    ///
+    /// A persisted cache that can't be read is logged and started empty.
     pub fn new(config: TokenCacheConfig) -> Self {
+        let store = config.persist_path.clone().and_then(|path| {
+            CacheStore::open(path, config.use_keyring)
+                .map_err(|e| warn!("Token cache will not be persisted: {}", e))
+                .ok()
+                .map(Arc::new)
+        });
         let cache = Self {
-            cache: DashMap::with_capacity(config.max_size),
+            cache: Arc::new(DashMap::with_capacity(config.max_size)),
+            tokens: Arc::new(DashMap::new()),
             config,
+            store,
         };
+        cache.restore();
 
         // Start cleanup task
         cache.start_cleanup_task();
@@ -88,9 +357,44 @@ impl TokenCache {
         cache
     }
 
+    /// Load unexpired entries from the persisted cache
+    fn restore(&self) {
+        let Some(store) = &self.store else { return };
+        let persisted = match store.read() {
+            Ok(Some(persisted)) => persisted,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Starting with an empty token cache, {} is unreadable: {}", store.path.display(), e);
+                return;
+            }
+        };
+
+        let now = Utc::now();
+        for entry in persisted.sessions {
+            let Ok(ttl) = (entry.expires_at - now).to_std() else { continue };
+            let mut cached = CachedSession::new(entry.session, ttl);
+            cached.access_count = entry.access_count;
+            self.cache.insert(entry.key, Arc::new(RwLock::new(cached)));
+        }
+        for (key, tokens) in persisted.tokens {
+            self.tokens.insert(key, tokens);
+        }
+        let loaded = self.cache.len() + self.tokens.len();
+        store.loaded.store(loaded, Ordering::Relaxed);
+        info!("Restored {} token cache entries from {}", loaded, store.path.display());
+    }
+
+    fn mark_dirty(&self) {
+        if let Some(store) = &self.store {
+            store.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
     /// Start background cleanup task
     fn start_cleanup_task(&self) {
         let cache = self.cache.clone();
+        let tokens = self.tokens.clone();
+        let store = self.store.clone();
         let interval = self.config.cleanup_interval;
 
         tokio::spawn(async move {
@@ -118,10 +422,24 @@ impl TokenCache {
                         after_count
                     );
                 }
+
+                if let Some(store) = store.as_ref().filter(|s| s.dirty.load(Ordering::Relaxed)) {
+                    if let Err(e) = save_to(store, &cache, &tokens).await {
+                        warn!("Failed to save token cache: {}", e);
+                    }
+                }
             }
         });
     }
 
+    /// Save the cache now, e.g. before shutting down
+    pub async fn save(&self) -> McpResult<()> {
+        match &self.store {
+            Some(store) => save_to(store, &self.cache, &self.tokens).await,
+            None => Ok(()),
+        }
+    }
+
     /// Get a cached session if it exists and is not expired
     pub async fn get(&self, token: &str) -> Option<Session> {
         // Use a simple hash of the token as the key
@@ -158,6 +476,7 @@ impl TokenCache {
 
         let cached = CachedSession::new(session, self.config.default_ttl);
         self.cache.insert(key, Arc::new(RwLock::new(cached)));
+        self.mark_dirty();
         
         debug!("Cached session for token key: {}", &self.hash_token(token)[..8]);
     }
@@ -166,6 +485,7 @@ impl TokenCache {
     pub fn invalidate(&self, token: &str) {
         let key = self.hash_token(token);
         self.cache.remove(&key);
+        self.mark_dirty();
         debug!("Invalidated token cache for key: {}", &key[..8]);
     }
 
@@ -182,6 +502,7 @@ impl TokenCache {
         let after_count = self.cache.len();
         
         if before_count != after_count {
+            self.mark_dirty();
             info!(
                 "Invalidated {} cached sessions for user: {}",
                 before_count - after_count,
@@ -190,12 +511,43 @@ impl TokenCache {
         }
     }
 
-    /// Clear all cached sessions
+    /// Clear all cached sessions and OAuth tokens
     pub fn clear(&self) {
         self.cache.clear();
+        self.tokens.clear();
+        self.mark_dirty();
         info!("Cleared all token cache entries");
     }
 
+    /// Keep OAuth tokens, e.g. after a refresh, under a key of the caller's
+    /// choosing such as the provider and user
+    pub fn put_tokens(&self, key: &str, tokens: Tokens) {
+        self.tokens.insert(
+            key.to_string(),
+            StoredTokens {
+                tokens,
+                saved_at: Utc::now(),
+            },
+        );
+        self.mark_dirty();
+    }
+
+    /// OAuth tokens kept under `key`, with `expires_in` counted down to now
+    pub fn get_tokens(&self, key: &str) -> Option<Tokens> {
+        let stored = self.tokens.get(key)?;
+        let elapsed = (Utc::now() - stored.saved_at).num_seconds();
+        let mut tokens = stored.tokens.clone();
+        tokens.expires_in = tokens.expires_in.map(|secs| secs - elapsed);
+        Some(tokens)
+    }
+
+    /// Forget OAuth tokens kept under `key`
+    pub fn remove_tokens(&self, key: &str) {
+        if self.tokens.remove(key).is_some() {
+            self.mark_dirty();
+        }
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> TokenCacheStats {
         let total_entries = self.cache.len();
@@ -209,21 +561,30 @@ impl TokenCache {
             }
         }
 
+        let store = self.store.as_deref();
         TokenCacheStats {
             total_entries,
             expired_entries,
             active_entries: total_entries - expired_entries,
+            token_entries: self.tokens.len(),
+            persistent: store.is_some(),
+            key_source: store.map(|s| s.key_source),
+            loaded_entries: store.map_or(0, |s| s.loaded.load(Ordering::Relaxed)),
+            saves: store.map_or(0, |s| s.saves.load(Ordering::Relaxed)),
+            save_failures: store.map_or(0, |s| s.save_failures.load(Ordering::Relaxed)),
         }
     }
 
     /// Hash a token to create a cache key
+    ///
+    /// SHA-256, so keys stay the same across restarts and builds and are
+    /// safe to persist.
     fn hash_token(&self, token: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        token.hash(&mut hasher);
-        format!("{:016x}", hasher.finish())
+        ring::digest::digest(&ring::digest::SHA256, token.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
     }
 }
 
@@ -233,12 +594,88 @@ impl Default for TokenCache {
     }
 }
 
+/// Provider answering from a [`TokenCache`] before asking the one it wraps
+///
+/// Validated sessions are cached until the cache TTL or the session's own
+/// expiry, whichever is first. Tokens from a refresh are kept under the
+/// refresh token that obtained them, so repeating a refresh, e.g. after a
+/// restart, gets them back while they last rather than spending a refresh
+/// token the provider may already have rotated.
+pub struct CachedAuth {
+    inner: Arc<dyn AuthProvider>,
+    cache: Arc<TokenCache>,
+}
+
+impl CachedAuth {
+    pub fn new(inner: Arc<dyn AuthProvider>, cache: Arc<TokenCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    /// The cache in front of the provider
+    pub fn cache(&self) -> &Arc<TokenCache> {
+        &self.cache
+    }
+
+    fn refresh_key(refresh_token: &str) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, refresh_token.as_bytes());
+        format!("refresh:{}", STANDARD.encode(digest.as_ref()))
+    }
+}
+
+#[async_trait]
+impl AuthProvider for CachedAuth {
+    async fn validate_token(&self, token: &str) -> McpResult<Session> {
+        if let Some(session) = self.cache.get(token).await {
+            if session.expires_at.is_none_or(|expires_at| expires_at > Utc::now()) {
+                return Ok(session);
+            }
+            self.cache.invalidate(token);
+        }
+
+        let session = self.inner.validate_token(token).await?;
+        self.cache.put(token, session.clone()).await;
+        Ok(session)
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> McpResult<Tokens> {
+        let key = Self::refresh_key(refresh_token);
+        if let Some(tokens) = self.cache.get_tokens(&key) {
+            if tokens.expires_in.is_none_or(|secs| secs > 0) {
+                return Ok(tokens);
+            }
+            self.cache.remove_tokens(&key);
+        }
+
+        let tokens = self.inner.refresh_token(refresh_token).await?;
+        self.cache.put_tokens(&key, tokens.clone());
+        Ok(tokens)
+    }
+
+    async fn generate_token(&self, user_id: &str, scopes: Vec<String>) -> McpResult<Tokens> {
+        self.inner.generate_token(user_id, scopes).await
+    }
+
+    fn is_configured(&self) -> bool {
+        self.inner.is_configured()
+    }
+}
+
 /// Token cache statistics
 #[derive(Debug, Clone)]
 pub struct TokenCacheStats {
     pub total_entries: usize,
     pub expired_entries: usize,
     pub active_entries: usize,
+    /// OAuth token sets kept
+    pub token_entries: usize,
+    /// Whether the cache is saved to disk
+    pub persistent: bool,
+    /// Where the file's key is kept: "keyring" or "file"
+    pub key_source: Option<&'static str>,
+    /// Sessions and token sets restored at startup
+    pub loaded_entries: usize,
+    pub saves: u64,
+    pub save_failures: u64,
 }
 
 #[cfg(test)]
@@ -251,6 +688,8 @@ mod tests {
             token: "test-token".to_string(),
             scopes: vec!["read".to_string()],
             expires_at: None,
+            method: crate::auth::AuthMethod::Jwt,
+            token_label: None,
         }
     }
 
@@ -324,5 +763,126 @@ mod tests {
 
         let stats = cache.stats();
         assert_eq!(stats.total_entries, 2);
+        assert!(!stats.persistent);
+    }
+
+    #[tokio::test]
+    async fn test_cache_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TokenCacheConfig {
+            persist_path: Some(dir.path().join("token-cache.bin")),
+            use_keyring: false,
+            ..Default::default()
+        };
+
+        let cache = TokenCache::new(config.clone());
+        cache.put("token123", create_test_session()).await;
+        cache.put_tokens(
+            "oauth:user123",
+            Tokens {
+                access_token: "access".to_string(),
+                refresh_token: Some("refresh".to_string()),
+                expires_in: Some(3600),
+            },
+        );
+        cache.save().await.unwrap();
+        assert_eq!(cache.stats().saves, 1);
+
+        let file = std::fs::read(dir.path().join("token-cache.bin")).unwrap();
+        assert!(!String::from_utf8_lossy(&file).contains("refresh"));
+
+        let restored = TokenCache::new(config.clone());
+        assert_eq!(restored.get("token123").await.unwrap().user_id, "user123");
+        let tokens = restored.get_tokens("oauth:user123").unwrap();
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        let stats = restored.stats();
+        assert_eq!((stats.loaded_entries, stats.key_source), (2, Some("file")));
+
+        // A file sealed with another key is ignored
+        std::fs::write(dir.path().join("token-cache.key"), STANDARD.encode([7u8; 32])).unwrap();
+        assert!(TokenCache::new(config).get("token123").await.is_none());
+    }
+
+    /// Provider counting the calls that reach it
+    #[derive(Default)]
+    struct Counting {
+        validations: AtomicUsize,
+        refreshes: AtomicUsize,
+        expires_at: Option<DateTime<Utc>>,
+    }
+
+    #[async_trait]
+    impl AuthProvider for Counting {
+        async fn validate_token(&self, token: &str) -> McpResult<Session> {
+            self.validations.fetch_add(1, Ordering::SeqCst);
+            Ok(Session {
+                expires_at: self.expires_at,
+                token: token.to_string(),
+                ..create_test_session()
+            })
+        }
+
+        async fn refresh_token(&self, _refresh_token: &str) -> McpResult<Tokens> {
+            let n = self.refreshes.fetch_add(1, Ordering::SeqCst);
+            Ok(Tokens {
+                access_token: format!("access-{}", n),
+                refresh_token: Some(format!("refresh-{}", n)),
+                expires_in: Some(3600),
+            })
+        }
+
+        async fn generate_token(&self, _user_id: &str, _scopes: Vec<String>) -> McpResult<Tokens> {
+            Err(McpError::AuthError("unsupported".to_string()))
+        }
+
+        fn is_configured(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_auth() {
+        let inner = Arc::new(Counting::default());
+        let auth = CachedAuth::new(inner.clone(), Arc::new(TokenCache::default()));
+
+        auth.validate_token("token").await.unwrap();
+        auth.validate_token("token").await.unwrap();
+        auth.validate_token("other").await.unwrap();
+        assert_eq!(inner.validations.load(Ordering::SeqCst), 2);
+
+        // Sessions past their own expiry are validated again
+        let inner = Arc::new(Counting {
+            expires_at: Some(Utc::now() - chrono::Duration::seconds(1)),
+            ..Default::default()
+        });
+        let auth = CachedAuth::new(inner.clone(), Arc::new(TokenCache::default()));
+        auth.validate_token("token").await.unwrap();
+        auth.validate_token("token").await.unwrap();
+        assert_eq!(inner.validations.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_auth_refresh_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TokenCacheConfig::from_config(&AuthCacheConfig {
+            enabled: true,
+            persist: true,
+            persist_path: Some(dir.path().join("cache.bin").display().to_string()),
+            use_keyring: false,
+            ..Default::default()
+        });
+
+        let inner = Arc::new(Counting::default());
+        let auth = CachedAuth::new(inner.clone(), Arc::new(TokenCache::new(config.clone())));
+        let tokens = auth.refresh_token("refresh").await.unwrap();
+        assert_eq!(auth.refresh_token("refresh").await.unwrap().access_token, tokens.access_token);
+        assert_eq!(inner.refreshes.load(Ordering::SeqCst), 1);
+        auth.cache().save().await.unwrap();
+
+        let inner = Arc::new(Counting::default());
+        let auth = CachedAuth::new(inner.clone(), Arc::new(TokenCache::new(config)));
+        assert_eq!(auth.refresh_token("refresh").await.unwrap().access_token, tokens.access_token);
+        assert_eq!(inner.refreshes.load(Ordering::SeqCst), 0);
+        assert_eq!(auth.cache().stats().loaded_entries, 1);
     }
 }
@@ -0,0 +1,255 @@
+//! Short-lived tokens for spawned servers
+//!
+//! Stdio servers started by the proxy get a JWT in their environment so
+//! they can call back into the proxy's admin and MCP APIs as
+//! `server:<name>`, holding only the scopes configured for them. A token
+//! is minted whenever a process is spawned, supervisor restarts included.
+//! Long-running processes read `<env_var>_FILE` instead, which is
+//! rewritten with a fresh token at half the lifetime.
+
+use crate::auth::cache::write_private;
+use crate::auth::provider::AuthProvider;
+use crate::auth::JwtAuth;
+use crate::config::{Config, DelegationConfig, McpServerConfig};
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Environment variable holding the proxy's base URL
+pub const URL_ENV: &str = "SUPERMCP_URL";
+
+/// Mints and rotates the tokens of spawned servers
+pub struct Delegator {
+    auth: JwtAuth,
+    config: DelegationConfig,
+    url: String,
+    token_dir: PathBuf,
+    /// Servers holding a token, with its scopes
+    servers: DashMap<String, Vec<String>>,
+}
+
+impl Delegator {
+    /// Delegator for `[auth.delegation]`, `None` when it is disabled
+    pub fn from_config(config: &Config) -> McpResult<Option<Self>> {
+        let delegation = &config.auth.delegation;
+        if !delegation.enabled {
+            return Ok(None);
+        }
+        let auth = crate::http_server::server::build_jwt_auth(&config.auth)
+            .map_err(|e| McpError::ConfigError(format!("auth.delegation: {}", e)))?
+            .with_expiry_seconds(delegation.ttl_seconds as i64);
+        if !auth.is_configured() {
+            return Err(McpError::ConfigError(
+                "auth.delegation needs an active JWT signing key".to_string(),
+            ));
+        }
+
+        let url = delegation.url.clone().unwrap_or_else(|| listener_url(config));
+        let token_dir = match &delegation.token_dir {
+            Some(dir) => PathBuf::from(shellexpand::tilde(dir).as_ref()),
+            None => dirs::data_local_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("supermcp/tokens"),
+        };
+
+        Ok(Some(Self {
+            auth,
+            config: delegation.clone(),
+            url,
+            token_dir,
+            servers: DashMap::new(),
+        }))
+    }
+
+    /// Environment for a server about to be spawned: a fresh token, the
+    /// file it is rotated in, and the proxy URL
+    pub async fn issue(&self, server: &McpServerConfig) -> McpResult<HashMap<String, String>> {
+        let scopes = server
+            .delegated_scopes
+            .clone()
+            .unwrap_or_else(|| self.config.scopes.clone());
+        let token = self.mint(&server.name, scopes.clone()).await?;
+        let path = self.token_path(&server.name);
+        write_token(&path, &token)?;
+        self.servers.insert(server.name.clone(), scopes);
+
+        Ok(HashMap::from([
+            (self.config.env_var.clone(), token),
+            (
+                format!("{}_FILE", self.config.env_var),
+                path.to_string_lossy().into_owned(),
+            ),
+            (URL_ENV.to_string(), self.url.clone()),
+        ]))
+    }
+
+    /// Stop rotating a removed server's token and delete its file
+    ///
+    /// Tokens already handed out stay valid until they expire.
+    pub fn revoke(&self, server: &str) {
+        if self.servers.remove(server).is_some() {
+            let _ = std::fs::remove_file(self.token_path(server));
+        }
+    }
+
+    async fn mint(&self, server: &str, scopes: Vec<String>) -> McpResult<String> {
+        let tokens = self
+            .auth
+            .generate_token(&format!("server:{}", server), scopes)
+            .await?;
+        Ok(tokens.access_token)
+    }
+
+    fn token_path(&self, server: &str) -> PathBuf {
+        let file: String = server
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "-_.@".contains(c) { c } else { '_' })
+            .collect();
+        self.token_dir.join(format!("{}.token", file))
+    }
+
+    /// Rewrite every token file with a fresh token
+    async fn rotate(&self) {
+        let servers: Vec<_> = self
+            .servers
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        for (server, scopes) in servers {
+            let result = match self.mint(&server, scopes).await {
+                Ok(token) => write_token(&self.token_path(&server), &token),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to rotate delegated token of {}: {}", server, e);
+            }
+        }
+    }
+
+    fn spawn_rotation(self: Arc<Self>) {
+        let period = Duration::from_secs((self.config.ttl_seconds / 2).max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                self.rotate().await;
+            }
+        });
+    }
+}
+
+/// URL of the `[server]` listener as seen from the same host
+fn listener_url(config: &Config) -> String {
+    let server = &config.server;
+    let scheme = if server.cert_path.is_some() || server.acme.enabled || server.spiffe.enabled {
+        "https"
+    } else {
+        "http"
+    };
+    let host = match server.host.as_str() {
+        "0.0.0.0" | "" => "127.0.0.1".to_string(),
+        "::" => "[::1]".to_string(),
+        host if host.contains(':') => format!("[{}]", host),
+        host => host.to_string(),
+    };
+    format!("{}://{}:{}", scheme, host, server.port)
+}
+
+static DELEGATOR: OnceCell<Arc<Delegator>> = OnceCell::new();
+
+/// Install the delegator and start rotating tokens; false if one was
+/// already installed
+pub fn install(delegator: Arc<Delegator>) -> bool {
+    if DELEGATOR.set(delegator.clone()).is_err() {
+        return false;
+    }
+    delegator.spawn_rotation();
+    true
+}
+
+/// Environment of a stdio server: its configured `env` on top of a
+/// delegated token, when delegation is installed
+pub async fn server_env(server: &McpServerConfig) -> McpResult<HashMap<String, String>> {
+    let mut env = match DELEGATOR.get() {
+        Some(delegator) => delegator.issue(server).await?,
+        None => HashMap::new(),
+    };
+    env.extend(server.env.clone());
+    Ok(env)
+}
+
+/// Forget a removed server's token, if delegation is installed
+pub fn revoke(server: &str) {
+    if let Some(delegator) = DELEGATOR.get() {
+        delegator.revoke(server);
+    }
+}
+
+/// Replace a token file at once, so a server reading it while it rotates
+/// never sees it empty or half written
+fn write_token(path: &Path, token: &str) -> McpResult<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4()));
+    write_private(&tmp, token.as_bytes())?;
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        e.into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthType, JwtKeyConfig};
+
+    #[tokio::test]
+    async fn test_issue_and_revoke() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.auth.auth_type = AuthType::Jwt;
+        config.auth.issuer = Some("super-mcp".to_string());
+        config.auth.jwt_keys = vec![JwtKeyConfig {
+            kid: "k1".to_string(),
+            secret: Some("secret".to_string()),
+            ..Default::default()
+        }];
+        config.auth.delegation = DelegationConfig {
+            enabled: true,
+            scopes: vec!["read".to_string()],
+            token_dir: Some(dir.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let delegator = Delegator::from_config(&config).unwrap().unwrap();
+
+        let server = McpServerConfig {
+            name: "files".to_string(),
+            delegated_scopes: Some(vec!["admin".to_string()]),
+            ..Default::default()
+        };
+        let env = delegator.issue(&server).await.unwrap();
+        assert_eq!(env[URL_ENV], "http://127.0.0.1:3000");
+
+        let session = delegator.auth.validate_token(&env["SUPERMCP_TOKEN"]).await.unwrap();
+        assert_eq!(session.user_id, "server:files");
+        assert_eq!(session.scopes, vec!["admin".to_string()]);
+        let expires_in = session.expires_at.unwrap() - chrono::Utc::now();
+        assert!(expires_in <= chrono::Duration::seconds(900));
+
+        let path = PathBuf::from(&env["SUPERMCP_TOKEN_FILE"]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), env["SUPERMCP_TOKEN"]);
+        delegator.rotate().await;
+        let token = std::fs::read_to_string(&path).unwrap();
+        delegator.auth.validate_token(&token).await.unwrap();
+        // Rotation leaves no temporary files behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        delegator.revoke("files");
+        assert!(!path.exists());
+    }
+}
@@ -1,10 +1,23 @@
 //! JWT authentication provider
-use crate::auth::provider::{AuthProvider, Session, Tokens};
+//!
+//! Tokens are signed with the first active key and carry its `kid`.
+//! Retired keys still verify the tokens they signed, so keys can be
+//! rotated without logging everyone out. Public halves of asymmetric keys
+//! can be published as a JWKS for downstream validators.
+use crate::auth::provider::{AuthMethod, AuthProvider, Session, Tokens};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, KeyPair};
+use rustls::pki_types::PrivateKeyDer;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,17 +29,167 @@ struct Claims {
     jti: String,                    // JWT ID
 }
 
+/// A key for signing and verifying tokens
+pub struct JwtKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Public JWK, for asymmetric keys only
+    jwk: Option<Jwk>,
+    retired: bool,
+}
+
+impl JwtKey {
+    /// Shared-secret key (HS256/HS384/HS512)
+    pub fn hmac(kid: Option<String>, algorithm: Algorithm, secret: &str) -> McpResult<Self> {
+        if !matches!(algorithm, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+            return Err(McpError::ConfigError(format!(
+                "{:?} keys need a private key, not a secret",
+                algorithm
+            )));
+        }
+        Ok(Self {
+            kid,
+            algorithm,
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            jwk: None,
+            retired: false,
+        })
+    }
+
+    /// Asymmetric key from a PEM private key
+    ///
+    /// RSA keys may be PKCS#1 or PKCS#8; EC and Ed25519 keys must be PKCS#8.
+    pub fn from_pem(kid: impl Into<String>, algorithm: Algorithm, pem: &[u8]) -> McpResult<Self> {
+        let kid = kid.into();
+        let invalid = |e: &dyn std::fmt::Display| {
+            McpError::ConfigError(format!("Invalid private key for JWT key '{}': {}", kid, e))
+        };
+        let der = rustls_pemfile::private_key(&mut &pem[..])
+            .map_err(|e| invalid(&e))?
+            .ok_or_else(|| invalid(&"no private key found"))?;
+
+        let (params, encoding) = match algorithm {
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => {
+                let key_pair = match &der {
+                    PrivateKeyDer::Pkcs1(key) => signature::RsaKeyPair::from_der(key.secret_pkcs1_der()),
+                    PrivateKeyDer::Pkcs8(key) => signature::RsaKeyPair::from_pkcs8(key.secret_pkcs8_der()),
+                    _ => return Err(invalid(&"expected an RSA key")),
+                }
+                .map_err(|e| invalid(&e))?;
+                let public =
+                    signature::RsaPublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+                (
+                    json!({
+                        "kty": "RSA",
+                        "n": URL_SAFE_NO_PAD.encode(&public.n),
+                        "e": URL_SAFE_NO_PAD.encode(&public.e),
+                    }),
+                    EncodingKey::from_rsa_pem(pem).map_err(|e| invalid(&e))?,
+                )
+            }
+            Algorithm::ES256 | Algorithm::ES384 => {
+                let PrivateKeyDer::Pkcs8(key) = &der else {
+                    return Err(invalid(&"expected a PKCS#8 EC key"));
+                };
+                let (signing, crv, len) = if algorithm == Algorithm::ES256 {
+                    (&signature::ECDSA_P256_SHA256_FIXED_SIGNING, "P-256", 32)
+                } else {
+                    (&signature::ECDSA_P384_SHA384_FIXED_SIGNING, "P-384", 48)
+                };
+                let key_pair = EcdsaKeyPair::from_pkcs8(
+                    signing,
+                    key.secret_pkcs8_der(),
+                    &ring::rand::SystemRandom::new(),
+                )
+                .map_err(|e| invalid(&e))?;
+                // Uncompressed point: 0x04 || x || y
+                let point = key_pair.public_key().as_ref();
+                (
+                    json!({
+                        "kty": "EC",
+                        "crv": crv,
+                        "x": URL_SAFE_NO_PAD.encode(&point[1..1 + len]),
+                        "y": URL_SAFE_NO_PAD.encode(&point[1 + len..]),
+                    }),
+                    EncodingKey::from_ec_pem(pem).map_err(|e| invalid(&e))?,
+                )
+            }
+            Algorithm::EdDSA => {
+                let PrivateKeyDer::Pkcs8(key) = &der else {
+                    return Err(invalid(&"expected a PKCS#8 Ed25519 key"));
+                };
+                let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(key.secret_pkcs8_der())
+                    .map_err(|e| invalid(&e))?;
+                (
+                    json!({
+                        "kty": "OKP",
+                        "crv": "Ed25519",
+                        "x": URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+                    }),
+                    EncodingKey::from_ed_pem(pem).map_err(|e| invalid(&e))?,
+                )
+            }
+            _ => return Err(invalid(&format!("{:?} needs a secret, not a private key", algorithm))),
+        };
+
+        let mut jwk = params;
+        jwk["kid"] = json!(kid);
+        jwk["alg"] = json!(format!("{:?}", algorithm));
+        jwk["use"] = json!("sig");
+        let jwk: Jwk = serde_json::from_value(jwk).map_err(|e| invalid(&e))?;
+        let decoding = DecodingKey::from_jwk(&jwk).map_err(|e| invalid(&e))?;
+
+        Ok(Self {
+            kid: Some(kid),
+            algorithm,
+            encoding,
+            decoding,
+            jwk: Some(jwk),
+            retired: false,
+        })
+    }
+
+    /// Only verify existing tokens; never sign new ones
+    pub fn retired(mut self) -> Self {
+        self.retired = true;
+        self
+    }
+
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+}
+
 /// JWT authentication provider
 pub struct JwtAuth {
-    secret: String,
+    keys: Vec<JwtKey>,
     issuer: String,
     default_expiry: Duration,
 }
 
 impl JwtAuth {
     pub fn new(secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+        let keys = if secret.is_empty() {
+            Vec::new()
+        } else {
+            JwtKey::hmac(None, Algorithm::HS256, &secret).into_iter().collect()
+        };
+        Self::with_keys(keys)
+    }
+
+    /// Provider with a key set; the first active key signs new tokens
+    pub fn with_keys(keys: Vec<JwtKey>) -> Self {
         Self {
-            secret: secret.into(),
+            keys,
             issuer: "super-mcp".to_string(),
             default_expiry: Duration::hours(24),
         }
@@ -41,6 +204,26 @@ impl JwtAuth {
         self.default_expiry = Duration::hours(hours);
         self
     }
+
+    /// Lifetime of generated tokens, for short-lived ones
+    pub fn with_expiry_seconds(mut self, seconds: i64) -> Self {
+        self.default_expiry = Duration::seconds(seconds);
+        self
+    }
+
+    fn signing_key(&self) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| !key.retired)
+    }
+
+    /// Public keys for `/.well-known/jwks.json`
+    ///
+    /// Retired keys stay listed so validators accept the tokens they
+    /// signed. Shared secrets are never published.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().filter_map(|key| key.jwk.clone()).collect(),
+        }
+    }
 }
 
 #[async_trait]
@@ -49,25 +232,45 @@ impl AuthProvider for JwtAuth {
         &self,
         token: &str,
     ) -> McpResult<Session> {
-        let mut validation = Validation::default();
-        validation.set_issuer(std::slice::from_ref(&self.issuer));
-
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &validation,
-        )
-        .map_err(|e| McpError::AuthError(format!("Invalid token: {}", e)))?;
-
-        let claims = token_data.claims;
-        let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0);
-
-        Ok(Session {
-            user_id: claims.sub,
-            token: token.to_string(),
-            scopes: claims.scopes,
-            expires_at,
-        })
+        let header = decode_header(token)
+            .map_err(|e| McpError::AuthError(format!("Invalid token: {}", e)))?;
+
+        // Tokens without a kid predate rotation: try every key. Only keys of
+        // the header's algorithm are used, so an HMAC token can never be
+        // checked against a published public key.
+        let candidates = self.keys.iter().filter(|key| {
+            key.algorithm == header.alg
+                && (header.kid.is_none() || key.kid == header.kid)
+        });
+
+        let mut last_error = "no matching key".to_string();
+        for key in candidates {
+            let mut validation = Validation::new(key.algorithm);
+            validation.set_issuer(std::slice::from_ref(&self.issuer));
+
+            let token_data = match decode::<Claims>(token, &key.decoding, &validation) {
+                Ok(data) => data,
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => {
+                    last_error = e.to_string();
+                    continue;
+                }
+                Err(e) => return Err(McpError::AuthError(format!("Invalid token: {}", e))),
+            };
+
+            let claims = token_data.claims;
+            let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0);
+
+            return Ok(Session {
+                user_id: claims.sub,
+                token: token.to_string(),
+                scopes: claims.scopes,
+                expires_at,
+                method: AuthMethod::Jwt,
+                token_label: None,
+            });
+        }
+
+        Err(McpError::AuthError(format!("Invalid token: {}", last_error)))
     }
 
     async fn refresh_token(
@@ -85,6 +288,10 @@ impl AuthProvider for JwtAuth {
         user_id: &str,
         scopes: Vec<String>,
     ) -> McpResult<Tokens> {
+        let key = self
+            .signing_key()
+            .ok_or_else(|| McpError::AuthError("No active JWT signing key".to_string()))?;
+
         let now = Utc::now();
         let expires_at = now + self.default_expiry;
 
@@ -96,12 +303,11 @@ impl AuthProvider for JwtAuth {
             jti: Uuid::new_v4().to_string(),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| McpError::AuthError(format!("Token generation failed: {}", e)))?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = key.kid.clone();
+
+        let token = encode(&header, &claims, &key.encoding)
+            .map_err(|e| McpError::AuthError(format!("Token generation failed: {}", e)))?;
 
         Ok(Tokens {
             access_token: token,
@@ -111,6 +317,58 @@ impl AuthProvider for JwtAuth {
     }
 
     fn is_configured(&self) -> bool {
-        !self.secret.is_empty()
+        self.signing_key().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hmac(kid: &str, secret: &str) -> JwtKey {
+        JwtKey::hmac(Some(kid.to_string()), Algorithm::HS256, secret).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_old_tokens_valid() {
+        let old = JwtAuth::with_keys(vec![hmac("2024", "old-secret")]);
+        let old_token = old.generate_token("alice", vec![]).await.unwrap().access_token;
+
+        let rotated = JwtAuth::with_keys(vec![hmac("2025", "new-secret"), hmac("2024", "old-secret").retired()]);
+        let new_token = rotated.generate_token("bob", vec![]).await.unwrap().access_token;
+        assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some("2025"));
+
+        assert_eq!(rotated.validate_token(&old_token).await.unwrap().user_id, "alice");
+        assert_eq!(rotated.validate_token(&new_token).await.unwrap().user_id, "bob");
+        assert!(old.validate_token(&new_token).await.is_err());
+
+        // Tokens from before kids were used
+        let legacy = JwtAuth::new("old-secret").generate_token("carol", vec![]).await.unwrap();
+        assert!(rotated.validate_token(&legacy.access_token).await.is_ok());
+
+        let retired_only = JwtAuth::with_keys(vec![hmac("2024", "old-secret").retired()]);
+        assert!(!retired_only.is_configured());
+        assert!(retired_only.validate_token(&old_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_jwks_publishes_only_public_keys() {
+        let pem = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519)
+            .unwrap()
+            .serialize_pem();
+        let auth = JwtAuth::with_keys(vec![
+            JwtKey::from_pem("ed-1", Algorithm::EdDSA, pem.as_bytes()).unwrap(),
+            hmac("legacy", "secret").retired(),
+        ]);
+
+        let jwks = auth.jwks();
+        assert_eq!(jwks.keys.len(), 1);
+        assert_eq!(jwks.keys[0].common.key_id.as_deref(), Some("ed-1"));
+
+        let token = auth.generate_token("alice", vec![]).await.unwrap().access_token;
+        let jwk = jwks.find("ed-1").unwrap();
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.set_issuer(&["super-mcp"]);
+        assert!(decode::<Claims>(&token, &DecodingKey::from_jwk(jwk).unwrap(), &validation).is_ok());
     }
 }
@@ -0,0 +1,127 @@
+//! Secrets in the OS keyring
+//!
+//! Uses the platform's command-line client: `security` for the macOS
+//! Keychain and `secret-tool` for the Secret Service (GNOME Keyring,
+//! KWallet) on Linux. Other platforms have no keyring support.
+
+use crate::utils::errors::{McpError, McpResult};
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+
+/// Service name secrets are stored under
+const SERVICE: &str = "supermcp";
+
+fn unavailable(detail: impl std::fmt::Display) -> McpError {
+    McpError::InternalError(format!("OS keyring unavailable: {}", detail))
+}
+
+/// Read a secret, `None` if the keyring has none for `account`
+#[cfg(target_os = "macos")]
+pub fn get(account: &str) -> McpResult<Option<String>> {
+    let output = Command::new("security")
+        .args(["find-generic-password", "-s", SERVICE, "-a", account, "-w"])
+        .output()
+        .map_err(unavailable)?;
+    // 44 is errSecItemNotFound
+    match output.status.code() {
+        Some(0) => Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string())),
+        Some(44) => Ok(None),
+        _ => Err(unavailable(String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+/// Store a secret, replacing any existing one
+#[cfg(target_os = "macos")]
+pub fn set(account: &str, secret: &str) -> McpResult<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    // `security -i` reads the command from stdin, which keeps the secret
+    // out of the process list the way `-w <secret>` on argv would not
+    let mut child = Command::new("security")
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(unavailable)?;
+    let line = format!(
+        "add-generic-password -U -s {} -a {} -w {}\n",
+        quote(SERVICE),
+        quote(account),
+        quote(secret)
+    );
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| unavailable("no stdin"))?
+        .write_all(line.as_bytes())
+        .map_err(unavailable)?;
+    let output = child.wait_with_output().map_err(unavailable)?;
+    // Interactive mode can exit 0 after a failed command, so stderr counts too
+    if !output.status.success() || !output.stderr.is_empty() {
+        return Err(unavailable(String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Double-quote an argument for the `security -i` command line
+#[cfg(target_os = "macos")]
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Read a secret, `None` if the keyring has none for `account`
+#[cfg(target_os = "linux")]
+pub fn get(account: &str) -> McpResult<Option<String>> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", SERVICE, "account", account])
+        .output()
+        .map_err(unavailable)?;
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match (output.status.success(), secret.is_empty(), output.stderr.is_empty()) {
+        (true, false, _) => Ok(Some(secret)),
+        // secret-tool exits 1 with no output when nothing matches
+        (_, true, true) => Ok(None),
+        _ => Err(unavailable(String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+/// Store a secret, replacing any existing one
+#[cfg(target_os = "linux")]
+pub fn set(account: &str, secret: &str) -> McpResult<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    // The secret goes over stdin so it never shows up in the process list
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label", "super-mcp", "service", SERVICE, "account", account])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(unavailable)?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| unavailable("no stdin"))?
+        .write_all(secret.as_bytes())
+        .map_err(unavailable)?;
+    let output = child.wait_with_output().map_err(unavailable)?;
+    if !output.status.success() {
+        return Err(unavailable(String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Read a secret, `None` if the keyring has none for `account`
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn get(_account: &str) -> McpResult<Option<String>> {
+    Err(unavailable("not supported on this platform"))
+}
+
+/// Store a secret, replacing any existing one
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn set(_account: &str, _secret: &str) -> McpResult<()> {
+    Err(unavailable("not supported on this platform"))
+}
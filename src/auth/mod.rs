@@ -1,13 +1,18 @@
 //! Authentication module
 
+pub mod anonymous;
 pub mod cache;
+pub mod delegation;
 pub mod jwt;
+pub mod keyring;
 pub mod oauth;
 pub mod provider;
+pub mod rbac;
 pub mod static_token;
 
-pub use cache::{TokenCache, TokenCacheConfig, CachedSession, TokenCacheStats};
-pub use jwt::JwtAuth;
+pub use anonymous::AnonymousReadonlyAuth;
+pub use cache::{CachedAuth, TokenCache, TokenCacheConfig, CachedSession, TokenCacheStats};
+pub use jwt::{JwtAuth, JwtKey};
 pub use oauth::OAuthAuth;
-pub use provider::{AuthProvider, Session, Tokens};
+pub use provider::{AuthMethod, AuthProvider, Session, Tokens};
 pub use static_token::StaticTokenAuth;
@@ -1,12 +1,14 @@
 //! OAuth 2.1 authentication provider
-use crate::auth::provider::{AuthProvider, Session, Tokens};
+use crate::auth::provider::{AuthMethod, AuthProvider, Session, Tokens};
+use crate::transport::http_client::{apply_proxy, global_proxy};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use chrono::Utc;
 use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
 use oauth2::{
     basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId,
-    ClientSecret, CsrfToken, RedirectUrl, RefreshToken, Scope, TokenResponse, TokenUrl,
+    ClientSecret, CsrfToken, PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken,
+    Scope, TokenResponse, TokenUrl,
 };
 use serde::Deserialize;
 use std::sync::Arc;
@@ -14,6 +16,14 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
+/// Client for discovery, introspection, userinfo and JWKS requests,
+/// through the global `[proxy]`
+fn http_client() -> McpResult<reqwest::Client> {
+    apply_proxy(reqwest::Client::builder(), &global_proxy())?
+        .build()
+        .map_err(|e| McpError::TransportError(e.to_string()))
+}
+
 /// OAuth 2.1 authentication provider
 pub struct OAuthAuth {
     client: Arc<BasicClient>,
@@ -103,7 +113,7 @@ impl OAuthAuth {
         
         debug!("Fetching OAuth discovery from: {}", discovery_url);
         
-        let client = reqwest::Client::new();
+        let client = http_client()?;
         let response = client
             .get(&discovery_url)
             .send()
@@ -234,13 +244,53 @@ impl OAuthAuth {
         (url.to_string(), csrf)
     }
 
+    /// Generate an authorization URL with a PKCE challenge
+    ///
+    /// Returns the verifier to present when exchanging the code.
+    pub fn get_authorization_url_with_pkce(
+        &self,
+        scopes: Vec<String>,
+    ) -> (String, CsrfToken, PkceCodeVerifier) {
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        let mut request = self
+            .client
+            .authorize_url(CsrfToken::new_random)
+            .set_pkce_challenge(challenge);
+
+        for scope in scopes {
+            request = request.add_scope(Scope::new(scope));
+        }
+
+        let (url, csrf) = request.url();
+        (url.to_string(), csrf, verifier)
+    }
+
     /// Exchange authorization code for tokens
     pub async fn exchange_code(&self, code: &str) -> McpResult<Tokens> {
+        self.exchange_code_inner(code, None).await
+    }
+
+    /// Exchange an authorization code obtained with a PKCE challenge
+    pub async fn exchange_code_with_pkce(
+        &self,
+        code: &str,
+        verifier: PkceCodeVerifier,
+    ) -> McpResult<Tokens> {
+        self.exchange_code_inner(code, Some(verifier)).await
+    }
+
+    async fn exchange_code_inner(
+        &self,
+        code: &str,
+        verifier: Option<PkceCodeVerifier>,
+    ) -> McpResult<Tokens> {
         let code = AuthorizationCode::new(code.to_string());
 
-        let token_response = self
-            .client
-            .exchange_code(code)
+        let mut request = self.client.exchange_code(code);
+        if let Some(verifier) = verifier {
+            request = request.set_pkce_verifier(verifier);
+        }
+        let token_response = request
             .request_async(async_http_client)
             .await
             .map_err(|e| McpError::AuthError(format!("Token exchange failed: {}", e)))?;
@@ -259,7 +309,7 @@ impl OAuthAuth {
             .as_ref()
             .ok_or_else(|| McpError::AuthError("Token introspection not configured".to_string()))?;
 
-        let client = reqwest::Client::new();
+        let client = http_client()?;
         let params = [
             ("token", token),
             ("token_type_hint", "access_token"),
@@ -289,12 +339,18 @@ impl OAuthAuth {
 
     /// Get user information from userinfo endpoint
     async fn get_userinfo(&self, token: &str) -> McpResult<UserInfoResponse> {
+        serde_json::from_value(self.userinfo_claims(token).await?)
+            .map_err(|e| McpError::InternalError(format!("Failed to parse userinfo response: {}", e)))
+    }
+
+    /// All claims the userinfo endpoint returns for an access token
+    pub async fn userinfo_claims(&self, token: &str) -> McpResult<serde_json::Value> {
         let url = self
             .userinfo_url
             .as_ref()
             .ok_or_else(|| McpError::AuthError("Userinfo endpoint not configured".to_string()))?;
 
-        let client = reqwest::Client::new();
+        let client = http_client()?;
         let response = client
             .get(url)
             .bearer_auth(token)
@@ -309,12 +365,10 @@ impl OAuthAuth {
             )));
         }
 
-        let userinfo: UserInfoResponse = response
+        response
             .json()
             .await
-            .map_err(|e| McpError::InternalError(format!("Failed to parse introspection response: {}", e)))?;
-
-        Ok(userinfo)
+            .map_err(|e| McpError::InternalError(format!("Failed to parse userinfo response: {}", e)))
     }
 
     async fn fetch_jwks(&self) -> McpResult<Arc<JwkSet>> {
@@ -323,7 +377,7 @@ impl OAuthAuth {
             .as_ref()
             .ok_or_else(|| McpError::AuthError("JWKS URL not configured".to_string()))?;
 
-        let client = reqwest::Client::new();
+        let client = http_client()?;
         let response = client
             .get(url)
             .send()
@@ -436,6 +490,8 @@ impl OAuthAuth {
             token: token.to_string(),
             scopes,
             expires_at,
+            method: AuthMethod::OAuth,
+            token_label: None,
         })
     }
 
@@ -549,6 +605,8 @@ impl AuthProvider for OAuthAuth {
                 token: token.to_string(),
                 scopes,
                 expires_at,
+                method: AuthMethod::OAuth,
+                token_label: None,
             });
         }
 
@@ -567,6 +625,8 @@ impl AuthProvider for OAuthAuth {
                 token: token.to_string(),
                 scopes: Vec::new(),
                 expires_at: None,
+                method: AuthMethod::OAuth,
+                token_label: None,
             });
         }
 
@@ -588,6 +648,8 @@ impl AuthProvider for OAuthAuth {
                 token: token.to_string(),
                 scopes,
                 expires_at,
+                method: AuthMethod::OAuth,
+                token_label: None,
             });
         }
 
@@ -10,10 +10,30 @@ pub struct Session {
     pub token: String,
     pub scopes: Vec<String>,
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// How the session was authenticated
+    #[serde(default)]
+    pub method: AuthMethod,
+    /// Label of the static token the session was opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_label: Option<String>,
+}
+
+/// How a [`Session`] was authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    StaticToken,
+    Jwt,
+    OAuth,
+    /// Browser login to the admin API
+    AdminOidc,
+    /// Sessions persisted before the method was recorded
+    #[default]
+    Unknown,
 }
 
 /// Token pair for authentication
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tokens {
     pub access_token: String,
     pub refresh_token: Option<String>,
@@ -0,0 +1,207 @@
+//! Role-based access control
+//!
+//! Roles are resolved from a session: scopes naming a role (OIDC logins
+//! carry the roles mapped from their groups as scopes), the roles of its
+//! static token label, and the default role when none of those match.
+//! Roles grant permissions on the admin API and limit which presets a
+//! session may select.
+
+use crate::auth::provider::{AuthMethod, Session};
+use crate::config::{Permission, RbacConfig, RoleConfig};
+use std::collections::{BTreeSet, HashMap};
+
+/// Roles and how sessions map to them
+pub struct Rbac {
+    roles: HashMap<String, RoleConfig>,
+    token_roles: HashMap<String, Vec<String>>,
+    default_role: Option<String>,
+}
+
+/// Built-in roles, before overrides from config
+fn builtin_roles() -> HashMap<String, RoleConfig> {
+    let role = |permissions: &[Permission]| RoleConfig {
+        permissions: permissions.to_vec(),
+        presets: Vec::new(),
+    };
+    HashMap::from([
+        (
+            "admin".to_string(),
+            role(&[
+                Permission::AdminRead,
+                Permission::AdminWrite,
+                Permission::ServersManage,
+                Permission::SandboxElevate,
+            ]),
+        ),
+        (
+            "operator".to_string(),
+            role(&[Permission::AdminRead, Permission::ServersManage]),
+        ),
+        ("user".to_string(), role(&[])),
+    ])
+}
+
+impl Rbac {
+    pub fn from_config(config: &RbacConfig) -> Self {
+        let mut roles = builtin_roles();
+        roles.extend(config.roles.clone());
+        Self {
+            roles,
+            token_roles: config.token_roles.clone(),
+            default_role: config.default_role.clone(),
+        }
+    }
+
+    /// Names of the roles a session holds
+    pub fn roles_of(&self, session: &Session) -> BTreeSet<&str> {
+        let mut held: BTreeSet<&str> = session
+            .scopes
+            .iter()
+            .filter_map(|scope| match scope.as_str() {
+                "*" => Some("admin"),
+                scope => self.roles.get_key_value(scope).map(|(name, _)| name.as_str()),
+            })
+            .collect();
+        // Only static token sessions carry a label; other identities that
+        // happen to equal one get nothing from it
+        let label = session
+            .token_label
+            .as_ref()
+            .filter(|_| session.method == AuthMethod::StaticToken);
+        held.extend(
+            label
+                .and_then(|label| self.token_roles.get(label))
+                .into_iter()
+                .flatten()
+                .filter_map(|role| self.roles.get_key_value(role).map(|(name, _)| name.as_str())),
+        );
+        if held.is_empty() {
+            if let Some(role) = &self.default_role {
+                held.extend(self.roles.get_key_value(role).map(|(name, _)| name.as_str()));
+            }
+        }
+        held
+    }
+
+    /// Whether any of the session's roles grants `permission`
+    pub fn allows(&self, session: &Session, permission: Permission) -> bool {
+        self.roles_of(session)
+            .into_iter()
+            .any(|role| self.roles[role].permissions.contains(&permission))
+    }
+
+    /// Whether the session may select `preset`, or no preset at all
+    ///
+    /// A role without a preset list allows everything; otherwise the
+    /// session is limited to the presets its roles list together.
+    pub fn allows_preset(&self, session: &Session, preset: Option<&str>) -> bool {
+        match self.allowed_presets(session) {
+            None => true,
+            Some(allowed) => preset.is_some_and(|preset| allowed.contains(preset)),
+        }
+    }
+
+    /// Presets the session's roles allow, or `None` when they allow any
+    pub fn allowed_presets(&self, session: &Session) -> Option<BTreeSet<&str>> {
+        let roles = self.roles_of(session);
+        if roles.iter().any(|role| self.roles[*role].presets.is_empty()) {
+            return None;
+        }
+        Some(
+            roles
+                .iter()
+                .flat_map(|role| self.roles[*role].presets.iter().map(String::as_str))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(user_id: &str, scopes: &[&str]) -> Session {
+        Session {
+            user_id: user_id.to_string(),
+            token: String::new(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: None,
+            method: AuthMethod::Jwt,
+            token_label: None,
+        }
+    }
+
+    fn static_token(label: &str) -> Session {
+        Session {
+            method: AuthMethod::StaticToken,
+            token_label: Some(label.to_string()),
+            ..session(label, &[])
+        }
+    }
+
+    #[test]
+    fn test_roles_and_permissions() {
+        let rbac = Rbac::from_config(&RbacConfig {
+            enabled: true,
+            roles: HashMap::from([(
+                "analyst".to_string(),
+                RoleConfig {
+                    permissions: vec![Permission::AdminRead],
+                    presets: vec!["research".to_string()],
+                },
+            )]),
+            token_roles: HashMap::from([("ci".to_string(), vec!["operator".to_string()])]),
+            ..Default::default()
+        });
+
+        let admin = session("admin", &["*"]);
+        assert!(rbac.allows(&admin, Permission::AdminWrite));
+
+        let ci = static_token("ci");
+        assert_eq!(rbac.roles_of(&ci).into_iter().collect::<Vec<_>>(), ["operator"]);
+        assert!(rbac.allows(&ci, Permission::ServersManage));
+        assert!(!rbac.allows(&ci, Permission::AdminWrite));
+        assert!(!rbac.allows(&ci, Permission::SandboxElevate));
+        assert!(rbac.allows(&admin, Permission::SandboxElevate));
+
+        // Unknown scopes fall back to the default role
+        let user = session("alice", &["mcp:access"]);
+        assert_eq!(rbac.roles_of(&user).into_iter().collect::<Vec<_>>(), ["user"]);
+        assert!(!rbac.allows(&user, Permission::AdminRead));
+        assert!(rbac.allows_preset(&user, None));
+
+        let analyst = session("bob", &["analyst"]);
+        assert!(rbac.allows(&analyst, Permission::AdminRead));
+        assert!(rbac.allows_preset(&analyst, Some("research")));
+        assert!(!rbac.allows_preset(&analyst, Some("ops")));
+        assert!(!rbac.allows_preset(&analyst, None));
+    }
+
+    #[test]
+    fn test_token_roles_only_for_static_tokens() {
+        let rbac = Rbac::from_config(&RbacConfig {
+            enabled: true,
+            token_roles: HashMap::from([("ci".to_string(), vec!["admin".to_string()])]),
+            ..Default::default()
+        });
+        assert!(rbac.allows(&static_token("ci"), Permission::AdminWrite));
+
+        // A JWT subject or OIDC user named like the label is just a user
+        let jwt = session("ci", &[]);
+        let oidc = Session {
+            method: AuthMethod::AdminOidc,
+            ..session("ci", &[])
+        };
+        for session in [jwt, oidc] {
+            assert_eq!(rbac.roles_of(&session).into_iter().collect::<Vec<_>>(), ["user"]);
+            assert!(!rbac.allows(&session, Permission::AdminRead));
+        }
+
+        // Even with a label copied onto it
+        let forged = Session {
+            token_label: Some("ci".to_string()),
+            ..session("ci", &[])
+        };
+        assert!(!rbac.allows(&forged, Permission::AdminRead));
+    }
+}
@@ -1,13 +1,16 @@
 //! Static token authentication provider
-use crate::auth::provider::{AuthProvider, Session, Tokens};
+use crate::auth::provider::{AuthMethod, AuthProvider, Session, Tokens};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 /// Static token authentication (for development/simple deployments)
 pub struct StaticTokenAuth {
     token: String,
     user_id: String,
     scopes: Vec<String>,
+    /// Labelled tokens: token to label and scopes
+    labeled: HashMap<String, (String, Vec<String>)>,
 }
 
 impl StaticTokenAuth {
@@ -16,9 +19,21 @@ impl StaticTokenAuth {
             token: token.into(),
             user_id: "admin".to_string(),
             scopes: vec!["*".to_string()], // Full access
+            labeled: HashMap::new(),
         }
     }
 
+    /// Accept another token, whose sessions carry `label` as user ID
+    pub fn with_labeled_token(
+        mut self,
+        label: impl Into<String>,
+        token: impl Into<String>,
+        scopes: Vec<String>,
+    ) -> Self {
+        self.labeled.insert(token.into(), (label.into(), scopes));
+        self
+    }
+
     pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
         self.user_id = user_id.into();
         self
@@ -36,16 +51,21 @@ impl AuthProvider for StaticTokenAuth {
         &self,
         token: &str,
     ) -> McpResult<Session> {
-        if token == self.token {
-            Ok(Session {
-                user_id: self.user_id.clone(),
-                token: token.to_string(),
-                scopes: self.scopes.clone(),
-                expires_at: None, // Static tokens don't expire
-            })
+        let (user_id, scopes, token_label) = if !self.token.is_empty() && token == self.token {
+            (&self.user_id, &self.scopes, None)
+        } else if let Some((label, scopes)) = self.labeled.get(token) {
+            (label, scopes, Some(label.clone()))
         } else {
-            Err(McpError::AuthError("Invalid token".to_string()))
-        }
+            return Err(McpError::AuthError("Invalid token".to_string()));
+        };
+        Ok(Session {
+            user_id: user_id.clone(),
+            token: token.to_string(),
+            scopes: scopes.clone(),
+            expires_at: None, // Static tokens don't expire
+            method: AuthMethod::StaticToken,
+            token_label,
+        })
     }
 
     async fn refresh_token(
@@ -65,6 +85,6 @@ impl AuthProvider for StaticTokenAuth {
     }
 
     fn is_configured(&self) -> bool {
-        !self.token.is_empty()
+        !self.token.is_empty() || !self.labeled.is_empty()
     }
 }
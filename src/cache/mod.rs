@@ -1,3 +1,4 @@
 //! Caching utilities for Super MCP
 
+pub mod replication;
 pub mod schema_cache;
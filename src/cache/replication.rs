@@ -0,0 +1,258 @@
+//! Sharing fetched schemas between nodes
+//!
+//! Schemas the lazy loader fetches, tool lists included, are also written
+//! to the state backend under `schemas/` with their expiry. Other nodes
+//! read them on a cache miss before asking the upstream server, and a
+//! starting node loads every live entry, so joining a cluster does not
+//! mean re-fetching schemas from every server.
+//!
+//! [`SharedLists`] does the same for whole results of MCP list requests,
+//! which read replicas answer without any upstream server.
+
+use crate::cache::schema_cache::{SchemaCache, SchemaType};
+use crate::cloud::StateBackend;
+use crate::utils::errors::McpResult;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// State backend key prefix for schemas
+const KEY_PREFIX: &str = "schemas/";
+
+/// A schema as stored in the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplicatedSchema {
+    server: String,
+    name: String,
+    schema_type: SchemaType,
+    schema: Value,
+    expires_at: DateTime<Utc>,
+}
+
+impl ReplicatedSchema {
+    /// Time left before the entry expires, `None` once it has
+    fn ttl_remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        (self.expires_at - now).to_std().ok().filter(|ttl| !ttl.is_zero())
+    }
+}
+
+fn server_prefix(server: &str) -> String {
+    format!("{}{}/", KEY_PREFIX, URL_SAFE_NO_PAD.encode(server))
+}
+
+fn key(server: &str, name: &str, schema_type: SchemaType) -> String {
+    let schema_type = match schema_type {
+        SchemaType::Tool => "tool",
+        SchemaType::Resource => "resource",
+        SchemaType::Prompt => "prompt",
+    };
+    format!("{}{}/{}", server_prefix(server), schema_type, URL_SAFE_NO_PAD.encode(name))
+}
+
+/// Schema cache entries shared through the state backend
+pub struct SchemaReplicator {
+    backend: Arc<dyn StateBackend>,
+}
+
+impl SchemaReplicator {
+    pub fn new(backend: Arc<dyn StateBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Share a schema that was just fetched
+    pub async fn publish(
+        &self,
+        server: &str,
+        name: &str,
+        schema_type: SchemaType,
+        schema: &Value,
+        ttl: Duration,
+    ) -> McpResult<()> {
+        let entry = ReplicatedSchema {
+            server: server.to_string(),
+            name: name.to_string(),
+            schema_type,
+            schema: schema.clone(),
+            expires_at: Utc::now() + chrono::Duration::from_std(ttl).unwrap_or_default(),
+        };
+        self.backend
+            .set(&key(server, name, schema_type), serde_json::to_vec(&entry)?)
+            .await
+    }
+
+    /// Copy a schema another node fetched into `cache`, if it is still live
+    pub async fn load(
+        &self,
+        cache: &SchemaCache,
+        server: &str,
+        name: &str,
+        schema_type: SchemaType,
+    ) -> McpResult<bool> {
+        let Some(data) = self.backend.get(&key(server, name, schema_type)).await? else {
+            return Ok(false);
+        };
+        let entry: ReplicatedSchema = serde_json::from_slice(&data)?;
+        Ok(Self::insert(cache, entry, Utc::now()))
+    }
+
+    /// Load every live schema into `cache`, returning how many were loaded
+    pub async fn warm(&self, cache: &SchemaCache) -> McpResult<usize> {
+        let now = Utc::now();
+        let mut loaded = 0;
+        for key in self.backend.list(KEY_PREFIX).await? {
+            let Some(data) = self.backend.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<ReplicatedSchema>(&data) {
+                Ok(entry) => {
+                    if Self::insert(cache, entry, now) {
+                        loaded += 1;
+                    }
+                }
+                Err(e) => warn!("Ignoring unreadable schema {}: {}", key, e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn insert(cache: &SchemaCache, entry: ReplicatedSchema, now: DateTime<Utc>) -> bool {
+        let Some(ttl) = entry.ttl_remaining(now) else {
+            return false;
+        };
+        cache.insert_with_ttl(entry.server, entry.name, entry.schema, entry.schema_type, ttl);
+        true
+    }
+
+    /// Forget the shared schemas of one server, or of all servers
+    pub async fn clear(&self, server: Option<&str>) -> McpResult<()> {
+        let prefix = server.map(server_prefix).unwrap_or_else(|| KEY_PREFIX.to_string());
+        for key in self.backend.list(&prefix).await? {
+            self.backend.delete(&key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// State backend key prefix for list results
+const LIST_PREFIX: &str = "list-results/";
+
+/// MCP methods whose results are shared with read replicas
+pub const SHARED_LIST_METHODS: &[&str] = &[
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "prompts/list",
+];
+
+/// A list result as stored in the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SharedList {
+    result: Value,
+    expires_at: DateTime<Utc>,
+}
+
+fn list_key(preset: Option<&str>, method: &str) -> String {
+    format!(
+        "{}{}/{}",
+        LIST_PREFIX,
+        URL_SAFE_NO_PAD.encode(preset.unwrap_or_default()),
+        method
+    )
+}
+
+/// Results of MCP list requests, per preset, shared through the state
+/// backend
+pub struct SharedLists {
+    backend: Arc<dyn StateBackend>,
+    ttl: Duration,
+}
+
+impl SharedLists {
+    pub fn new(backend: Arc<dyn StateBackend>, ttl: Duration) -> Self {
+        Self { backend, ttl }
+    }
+
+    /// Share the result a node returned for a list request
+    pub async fn publish(&self, preset: Option<&str>, method: &str, result: &Value) -> McpResult<()> {
+        let entry = SharedList {
+            result: result.clone(),
+            expires_at: Utc::now() + chrono::Duration::from_std(self.ttl).unwrap_or_default(),
+        };
+        self.backend
+            .set(&list_key(preset, method), serde_json::to_vec(&entry)?)
+            .await
+    }
+
+    /// A shared result for a list request, if one is still live
+    pub async fn load(&self, preset: Option<&str>, method: &str) -> McpResult<Option<Value>> {
+        let Some(data) = self.backend.get(&list_key(preset, method)).await? else {
+            return Ok(None);
+        };
+        let entry: SharedList = serde_json::from_slice(&data)?;
+        Ok((entry.expires_at > Utc::now()).then_some(entry.result))
+    }
+
+    /// Forget every shared result
+    pub async fn clear(&self) -> McpResult<()> {
+        for key in self.backend.list(LIST_PREFIX).await? {
+            self.backend.delete(&key).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::InMemoryBackend;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_schemas_shared_between_caches() {
+        let replicator = SchemaReplicator::new(Arc::new(InMemoryBackend::new()));
+        let tools = json!({ "tools": [{ "name": "read_file" }] });
+        replicator
+            .publish("files", "list", SchemaType::Tool, &tools, Duration::from_secs(60))
+            .await
+            .unwrap();
+        replicator
+            .publish("old", "list", SchemaType::Tool, &tools, Duration::ZERO)
+            .await
+            .unwrap();
+
+        // A node joining later starts with the live entries only
+        let cache = SchemaCache::new(Duration::from_secs(60));
+        assert_eq!(replicator.warm(&cache).await.unwrap(), 1);
+        assert_eq!(cache.get("files", "list", SchemaType::Tool).unwrap().schema, tools);
+
+        let other = SchemaCache::new(Duration::from_secs(60));
+        assert!(replicator.load(&other, "files", "list", SchemaType::Tool).await.unwrap());
+        assert!(!replicator.load(&other, "files", "x", SchemaType::Tool).await.unwrap());
+
+        replicator.clear(Some("files")).await.unwrap();
+        let fresh = SchemaCache::new(Duration::from_secs(60));
+        assert_eq!(replicator.warm(&fresh).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_results_shared_per_preset() {
+        let backend: Arc<dyn StateBackend> = Arc::new(InMemoryBackend::new());
+        let lists = SharedLists::new(backend.clone(), Duration::from_secs(60));
+        let tools = json!({ "tools": [{ "name": "search" }] });
+        lists.publish(Some("research"), "tools/list", &tools).await.unwrap();
+
+        assert_eq!(lists.load(Some("research"), "tools/list").await.unwrap(), Some(tools));
+        assert_eq!(lists.load(None, "tools/list").await.unwrap(), None);
+
+        let expired = SharedLists::new(backend, Duration::ZERO);
+        expired.publish(None, "prompts/list", &json!({ "prompts": [] })).await.unwrap();
+        assert_eq!(expired.load(None, "prompts/list").await.unwrap(), None);
+
+        lists.clear().await.unwrap();
+        assert_eq!(lists.load(Some("research"), "tools/list").await.unwrap(), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! TTL-based cache for MCP tool/resource/prompt schemas with coalescing support
 
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -35,7 +36,8 @@ impl CachedSchema {
 }
 
 /// Type of schema being cached
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SchemaType {
     Tool,
     Resource,
@@ -160,18 +162,22 @@ impl SchemaCache {
         let key = Self::cache_key(server_name, name);
         let cache_map = self.get_cache_map(schema_type);
 
-        if let Some(entry) = cache_map.get(&key) {
-            if entry.is_expired() {
-                // Remove expired entry
+        let cached = cache_map.get(&key).map(|entry| entry.value().clone());
+        match cached {
+            Some(entry) if entry.is_expired() => {
+                // Remove expired entry, once the read guard above is gone
                 cache_map.remove(&key);
                 self.metrics.record_eviction();
-                return None;
+                None
+            }
+            Some(entry) => {
+                self.metrics.record_hit();
+                Some(entry)
+            }
+            None => {
+                self.metrics.record_miss();
+                None
             }
-            self.metrics.record_hit();
-            Some(entry.value().clone())
-        } else {
-            self.metrics.record_miss();
-            None
         }
     }
 
@@ -3,9 +3,21 @@
 //! This module contains all CLI argument types used by both the binary
 //! and integration tests.
 
+use crate::cli::output::OutputFormat;
 use crate::config::types::LazyLoadingMode;
 use clap::{Parser, Subcommand};
 
+/// Exit codes of the commands CI gates on; see `cli::exit`
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  ok         everything checked out
+  1  error      unexpected failure
+  2  usage      bad arguments
+  3  config     config file missing, unreadable or not parseable
+  4  invalid    validation failed
+  5  not-found  the named preset does not exist
+  6  empty      nothing to check, match or import";
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum LazyLoadingModeCli {
     /// Return meta-tools instead of actual tools
@@ -33,6 +45,8 @@ impl From<LazyLoadingModeCli> for LazyLoadingMode {
 pub enum Cli {
     /// Start the Super MCP server
     Serve(ServeArgs),
+    /// Create a config file interactively
+    Init(InitArgs),
     /// Manage MCP servers
     Mcp(McpArgs),
     /// Manage presets
@@ -41,22 +55,46 @@ pub enum Cli {
     Registry(RegistryArgs),
     /// Install/uninstall startup manager
     Install(InstallArgs),
+    /// Show or restart the installed startup service
+    Service(ServiceArgs),
     /// Validate configuration file
     Validate(ValidateArgs),
     /// Migrate from 1MCP configuration
     Migrate(MigrateArgs),
+    /// Upgrade a Super MCP config file to a newer schema version
+    MigrateConfig(MigrateConfigArgs),
     /// Show migration guide and feature comparison
     Guide,
     /// Manage runtimes
     Runtime(RuntimeArgs),
+    /// Install and manage skills
+    Skill(SkillArgs),
     /// Call an MCP tool directly (lightweight client)
     Call(CallArgs),
+    /// Call MCP tools interactively, with completion and history
+    Repl(ReplArgs),
+    /// Run a pipeline of tool calls from a YAML file
+    Run(RunArgs),
     /// List tools from an MCP server or skill
     Tools(ToolsArgs),
     /// List all available providers (MCPs and skills)
     Providers(ProvidersArgs),
     /// Import MCP servers from AI editors (cursor, claude, vscode, etc.)
     Import(ImportArgs),
+    /// Inspect and terminate sessions on a running server
+    Sessions(SessionsArgs),
+    /// Diff a config file against a running server and apply it
+    Config(ConfigArgs),
+    /// Package supermcp for machines without internet access
+    Bundle(BundleArgs),
+    /// Generate container files for the configured fleet
+    Generate(GenerateArgs),
+    /// Export, verify and compare signed snapshots of server capabilities
+    Capabilities(CapabilitiesArgs),
+    /// Read audit logs sealed with per-tenant keys
+    Audit(AuditArgs),
+    /// Work out sandbox profiles and grant temporary elevation on a running server
+    Sandbox(SandboxArgs),
 }
 
 #[derive(Parser)]
@@ -76,6 +114,9 @@ pub struct ServeArgs {
     /// Enable lazy loading mode (metatool, hybrid, full)
     #[arg(long, value_enum)]
     pub lazy: Option<LazyLoadingModeCli>,
+    /// Run under the Windows Service Control Manager (set by `install`)
+    #[arg(long, hide = true)]
+    pub windows_service: bool,
 }
 
 #[derive(Parser)]
@@ -107,7 +148,11 @@ pub enum McpCommand {
         description: Option<String>,
     },
     /// List configured MCP servers
-    List,
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
     /// Remove an MCP server
     Remove { name: String },
     /// Show MCP server status
@@ -131,6 +176,51 @@ pub enum McpCommand {
         #[arg(short, long)]
         description: Option<String>,
     },
+    /// Add a server from a template, prompting for missing parameters
+    Instantiate {
+        template: String,
+        /// Parameter value (KEY=value), repeatable
+        #[arg(short, long = "param")]
+        params: Vec<String>,
+        /// Name for the new server (default: template name plus a hash of the values)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Enable servers whose name or a tag matches a glob, e.g. "github*"
+    Enable { pattern: String },
+    /// Disable servers whose name or a tag matches a glob; `serve` skips them
+    Disable { pattern: String },
+    /// Restart running servers whose name or a tag matches a glob
+    Restart {
+        pattern: String,
+        /// Base URL of the running server (defaults to the configured host and port)
+        #[arg(long)]
+        url: Option<String>,
+        /// Bearer token with the admin scope
+        #[arg(long, env = "SUPERMCP_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
+    /// Add or remove tags on servers whose name or a tag matches a glob
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// Add tags to the matched servers
+    Add {
+        pattern: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from the matched servers
+    Remove {
+        pattern: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
 }
 
 #[derive(Parser)]
@@ -155,7 +245,11 @@ pub enum PresetCommand {
         description: Option<String>,
     },
     /// List available presets
-    List,
+    List {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
     /// Edit a preset
     Edit {
         name: String,
@@ -167,7 +261,13 @@ pub enum PresetCommand {
         description: Option<String>,
     },
     /// Test a preset (shows matching servers)
-    Test { name: String },
+    #[command(after_help = EXIT_CODES)]
+    Test {
+        name: String,
+        /// Print only a one-line JSON result
+        #[arg(short, long)]
+        quiet: bool,
+    },
     /// Remove a preset
     Remove { name: String },
 }
@@ -184,7 +284,12 @@ pub struct RegistryArgs {
 #[derive(Subcommand, Debug)]
 pub enum RegistryCommand {
     /// Search for MCP servers in the registry
-    Search { query: String },
+    Search {
+        query: String,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
     /// Install an MCP server from the registry
     Install { name: String },
     /// Show registry information
@@ -195,9 +300,12 @@ pub enum RegistryCommand {
 
 #[derive(Parser)]
 pub struct InstallArgs {
-    /// Startup manager to use (launchd, systemd, openrc, runit, nssm, schtasks)
+    /// Startup manager to use (launchd, homebrew, systemd, openrc, runit, nssm, schtasks)
     #[arg(short, long)]
     pub manager: Option<String>,
+    /// Install for the current user instead of system-wide (systemd user units)
+    #[arg(long)]
+    pub user: bool,
     /// Path to the supermcp binary
     #[arg(short, long)]
     pub binary: Option<String>,
@@ -210,6 +318,40 @@ pub struct InstallArgs {
 }
 
 #[derive(Parser)]
+pub struct ServiceArgs {
+    #[command(subcommand)]
+    pub command: ServiceCommand,
+    /// Startup manager the service was installed with (detected if omitted)
+    #[arg(short, long, global = true)]
+    pub manager: Option<String>,
+    /// Use the current user's service (systemd user units)
+    #[arg(long, global = true)]
+    pub user: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum ServiceCommand {
+    /// Show whether the service is running
+    Status,
+    /// Restart the service
+    Restart,
+}
+
+#[derive(Parser)]
+pub struct InitArgs {
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/supermcp/config.toml")]
+    pub config: String,
+    /// Replace an existing config (a .bak copy is kept)
+    #[arg(long)]
+    pub force: bool,
+    /// Accept the defaults instead of asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Parser)]
+#[command(after_help = EXIT_CODES)]
 pub struct ValidateArgs {
     /// Configuration file path
     #[arg(short, long, default_value = "~/.config/supermcp/config.toml")]
@@ -217,6 +359,9 @@ pub struct ValidateArgs {
     /// Output format
     #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
     pub format: String,
+    /// Print only a one-line JSON result
+    #[arg(short, long)]
+    pub quiet: bool,
 }
 
 #[derive(Parser)]
@@ -235,6 +380,22 @@ pub struct MigrateArgs {
     pub dry_run: bool,
 }
 
+#[derive(Parser)]
+pub struct MigrateConfigArgs {
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/supermcp/config.toml")]
+    pub config: String,
+    /// Upgrade to the latest schema version
+    #[arg(long, required_unless_present = "to")]
+    pub to_latest: bool,
+    /// Upgrade to this schema version
+    #[arg(long, conflicts_with = "to_latest")]
+    pub to: Option<u32>,
+    /// Dry run - print the upgraded config without writing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser)]
 pub struct RuntimeArgs {
     #[command(subcommand)]
@@ -249,7 +410,7 @@ pub enum RuntimeCommand {
     /// Add a new runtime
     Add {
         name: String,
-        /// Runtime type (python_wasm, node_pnpm, node_npm, node_bun)
+        /// Runtime type (python_wasm, python_uv, node_pnpm, node_npm, node_bun, node_deno)
         #[arg(short, long)]
         type_: String,
         /// Packages to install (comma-separated for Node.js)
@@ -276,8 +437,11 @@ pub enum RuntimeCommand {
     },
     /// List all configured runtimes
     List {
-        /// Output as JSON
-        #[arg(short, long)]
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+        /// Same as --output json
+        #[arg(short, long, hide = true, conflicts_with = "output")]
         json: bool,
     },
     /// Remove a runtime
@@ -285,7 +449,12 @@ pub enum RuntimeCommand {
     /// Show runtime information
     Info { name: String },
     /// Validate all runtimes
-    Validate,
+    #[command(after_help = EXIT_CODES)]
+    Validate {
+        /// Print only a one-line JSON result
+        #[arg(short, long)]
+        quiet: bool,
+    },
     /// Execute a script using a runtime
     Exec {
         /// Runtime name to use
@@ -297,6 +466,334 @@ pub enum RuntimeCommand {
         #[arg(short, long)]
         file: Option<String>,
     },
+    /// Resolve a runtime's packages and save its lockfile
+    Freeze {
+        name: String,
+        /// Directory for the manifest and lockfile (default: runtimes/<name> beside the config)
+        #[arg(long)]
+        lock_dir: Option<String>,
+    },
+    /// Check that a runtime's lockfile matches its packages and installs cleanly
+    Verify { name: String },
+}
+
+#[derive(Parser)]
+pub struct SkillArgs {
+    #[command(subcommand)]
+    pub command: SkillCommand,
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/super-mcp/config.toml", global = true)]
+    pub config: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SkillCommand {
+    /// Install a skill from a git URL, local path or registry name
+    Install {
+        source: String,
+        /// Branch or tag to check out for git sources
+        #[arg(long = "ref")]
+        reference: Option<String>,
+        /// Reinstall even if the same version is already installed
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// List installed skills
+    List {
+        /// Output as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Remove an installed skill
+    Remove { name: String },
+    /// Show skill information
+    Info { name: String },
+    /// Validate a skill directory's SKILL.md
+    Validate { path: String },
+}
+
+#[derive(Parser)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Package the binary, config, presets and registry cache into one file
+    Create {
+        /// Configuration file path
+        #[arg(short, long, default_value = "~/.config/supermcp/config.toml")]
+        config: String,
+        /// Bundle file to write
+        #[arg(short, long, default_value = "supermcp-bundle.tar.gz")]
+        output: String,
+        /// Binary to bundle (defaults to the running one)
+        #[arg(long)]
+        binary: Option<String>,
+        /// Config overrides for an environment, applied at unpack time (NAME=FILE)
+        #[arg(long = "env-override", value_name = "NAME=FILE")]
+        env_overrides: Vec<String>,
+        /// Write a self-extracting shell script instead of a tarball
+        #[arg(long)]
+        self_extracting: bool,
+    },
+    /// Unpack a bundle
+    Unpack {
+        /// Bundle file
+        archive: String,
+        /// Directory to unpack into
+        #[arg(short, long, default_value = "supermcp")]
+        dest: String,
+        /// Environment whose overrides to apply
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+    /// Prepare the config of an unpacked bundle
+    Apply {
+        /// Directory the bundle was unpacked into
+        dir: String,
+        /// Environment whose overrides to apply
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+}
+
+#[derive(Parser)]
+pub struct CapabilitiesArgs {
+    #[command(subcommand)]
+    pub command: CapabilitiesCommand,
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/supermcp/config.toml", global = true)]
+    pub config: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CapabilitiesCommand {
+    /// Write a signed snapshot of every server's tools, resources and prompts
+    Export {
+        /// File to write (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Signing key from `capabilities keygen` (a one-off key if omitted)
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+    /// Verify a snapshot and list what differs in another one, or in the live servers
+    #[command(alias = "import")]
+    Compare {
+        /// Reviewed snapshot
+        baseline: String,
+        /// Snapshot to compare with (defaults to the configured servers)
+        other: Option<String>,
+        /// Public key the snapshots must be signed with
+        #[arg(long)]
+        trusted_key: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Fail if anything differs
+        #[arg(long)]
+        check: bool,
+    },
+    /// Pin the live tool schemas in the lockfile for `pinned_schemas` servers
+    Lock {
+        /// Lockfile to write (defaults to `schema_lock.path`)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Ed25519 key for signing snapshots; prints its public key
+    Keygen {
+        /// File to write the private key to
+        #[arg(short, long, default_value = "supermcp-snapshot.key")]
+        output: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct AuditArgs {
+    #[command(subcommand)]
+    pub command: AuditCommand,
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/supermcp/config.toml", global = true)]
+    pub config: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Print an audit log with the records sealed by `[tenant_keys]` opened
+    Decrypt {
+        /// Audit log to read (defaults to `audit.path`)
+        path: Option<String>,
+        /// Only print records of this tenant
+        #[arg(short, long)]
+        tenant: Option<String>,
+    },
+}
+
+#[derive(Parser)]
+pub struct GenerateArgs {
+    #[command(subcommand)]
+    pub command: GenerateCommand,
+    /// Configuration file path
+    #[arg(short, long, default_value = "~/.config/supermcp/config.toml", global = true)]
+    pub config: String,
+    /// File to write (defaults to stdout)
+    #[arg(short, long, global = true)]
+    pub output: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GenerateCommand {
+    /// Dockerfile with the config and the runtimes its servers need
+    Dockerfile,
+    /// compose.yaml building that Dockerfile
+    Compose,
+    /// Kubernetes manifests (or Helm values) running that image
+    K8s {
+        /// Namespace to deploy into
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+        /// Image built from the generated Dockerfile
+        #[arg(long, default_value = "supermcp:latest")]
+        image: String,
+        /// Pod replicas, and the autoscaler's minimum
+        #[arg(long, default_value_t = 1)]
+        replicas: u32,
+        /// Add a HorizontalPodAutoscaler scaling up to this many replicas
+        #[arg(long)]
+        hpa_max_replicas: Option<u32>,
+        /// CPU utilization the autoscaler targets
+        #[arg(long, default_value_t = 80)]
+        hpa_cpu_percent: u32,
+        /// Emit Helm values instead of manifests
+        #[arg(long)]
+        helm: bool,
+    },
+    /// X25519 key pair for encrypted federation
+    FederationKey,
+}
+
+#[derive(Parser)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub command: SessionsCommand,
+    /// Configuration file path, used to find the server and its token
+    #[arg(short, long, default_value = "~/.config/super-mcp/config.toml", global = true)]
+    pub config: String,
+    /// Base URL of the running server (defaults to the configured host and port)
+    #[arg(long, global = true)]
+    pub url: Option<String>,
+    /// Bearer token with the admin scope
+    #[arg(long, env = "SUPERMCP_TOKEN", global = true, hide_env_values = true)]
+    pub token: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionsCommand {
+    /// List active sessions
+    List {
+        /// Output as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Terminate a session and its dedicated upstream processes
+    Kill { id: String },
+}
+
+#[derive(Parser)]
+pub struct SandboxArgs {
+    #[command(subcommand)]
+    pub command: SandboxCommand,
+    /// Configuration file path, used to find the server and its token
+    #[arg(short, long, default_value = "~/.config/super-mcp/config.toml", global = true)]
+    pub config: String,
+    /// Base URL of the running server (defaults to the configured host and port)
+    #[arg(long, global = true)]
+    pub url: Option<String>,
+    /// Bearer token with the admin scope
+    #[arg(long, env = "SUPERMCP_TOKEN", global = true, hide_env_values = true)]
+    pub token: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SandboxCommand {
+    /// Run a server permissively while it is used, then print the minimal
+    /// sandbox that covers what it did
+    Learn {
+        /// Server to learn
+        server: String,
+        /// How long to watch it, e.g. `90s`, `10m` or `1h`
+        #[arg(short, long, default_value = "10m")]
+        duration: String,
+        /// Output the full report as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Temporarily give a server more than its sandbox allows; it is
+    /// restarted under its own sandbox when the grant expires
+    Elevate {
+        /// Server to elevate
+        server: String,
+        /// Allow network access
+        #[arg(long)]
+        network: bool,
+        /// Allow writing under this path (repeatable)
+        #[arg(long = "filesystem", value_name = "PATH")]
+        filesystem: Vec<String>,
+        /// Allow GPU devices
+        #[arg(long)]
+        gpu: bool,
+        /// How long the grant lasts, e.g. `90s`, `15m` or `1h`
+        #[arg(short, long, default_value = "15m")]
+        duration: String,
+        /// Why it is needed, for the audit log
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+    /// End a server's elevation early
+    Revoke {
+        /// Elevated server
+        server: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+    /// Configuration file path, used to find the server and its token
+    #[arg(short, long, default_value = "~/.config/super-mcp/config.toml", global = true)]
+    pub config: String,
+    /// Base URL of the running server (defaults to the configured host and port)
+    #[arg(long, global = true)]
+    pub url: Option<String>,
+    /// Bearer token with the admin scope
+    #[arg(long, env = "SUPERMCP_TOKEN", global = true, hide_env_values = true)]
+    pub token: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Show how a config file differs from the one the server is running
+    Diff {
+        /// Candidate config file
+        file: String,
+        /// Output as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Replace the running server's config, rolling back if a server fails to start
+    Apply {
+        /// Candidate config file
+        file: String,
+        /// Validate and diff on the server without applying
+        #[arg(long)]
+        dry_run: bool,
+        /// Output as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
 }
 
 #[derive(Parser)]
@@ -324,6 +821,43 @@ pub struct CallArgs {
     /// Output as JSON
     #[arg(short, long)]
     pub json: bool,
+    /// Validate the arguments and show what would be called, without calling it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser)]
+pub struct ReplArgs {
+    /// Configuration file path
+    #[arg(short, long)]
+    pub config: Option<String>,
+    /// Ad-hoc stdio command
+    #[arg(long, conflicts_with_all = ["http_url", "skill"])]
+    pub stdio: Option<String>,
+    /// Ad-hoc HTTP/SSE URL
+    #[arg(long, conflicts_with_all = ["stdio", "skill"])]
+    pub http_url: Option<String>,
+    /// Use a skill provider
+    #[arg(long, conflicts_with_all = ["stdio", "http_url"])]
+    pub skill: Option<String>,
+    /// Environment variables (KEY=value format)
+    #[arg(short, long, value_delimiter = ',')]
+    pub env: Vec<String>,
+    /// Output results as JSON
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[derive(Parser)]
+pub struct RunArgs {
+    /// Pipeline file (YAML)
+    pub pipeline: String,
+    /// Configuration file path
+    #[arg(short, long)]
+    pub config: Option<String>,
+    /// Write the JSON report to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<String>,
 }
 
 #[derive(Parser)]
@@ -351,6 +885,12 @@ pub struct ToolsArgs {
     /// Output as JSON
     #[arg(short, long)]
     pub json: bool,
+    /// Keep refreshing the table, highlighting tools added, removed or changed
+    #[arg(short, long, conflicts_with = "json")]
+    pub watch: bool,
+    /// Seconds between refreshes with --watch
+    #[arg(long, default_value_t = 2, requires = "watch")]
+    pub interval: u64,
 }
 
 #[derive(Parser)]
@@ -358,8 +898,11 @@ pub struct ProvidersArgs {
     /// Configuration file path
     #[arg(short, long)]
     pub config: Option<String>,
-    /// Output as JSON
-    #[arg(short, long)]
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+    /// Same as --output json
+    #[arg(short, long, hide = true, conflicts_with = "output")]
     pub json: bool,
 }
 
@@ -377,11 +920,14 @@ pub enum ImportSource {
     Qwen,
     #[value(name = "github-copilot")]
     GithubCopilot,
+    Jetbrains,
+    Zed,
 }
 
 #[derive(Parser)]
+#[command(after_help = EXIT_CODES)]
 pub struct ImportArgs {
-    /// Specific source to import from (cursor, claude, vscode, codex, kimi-cli, windsurf, opencode, all)
+    /// Specific source to import from (cursor, claude, vscode, codex, kimi-cli, windsurf, opencode, jetbrains, zed, all)
     #[arg(value_enum)]
     pub source: ImportSource,
     /// Configuration file path
@@ -393,4 +939,7 @@ pub struct ImportArgs {
     /// Output as JSON
     #[arg(short, long)]
     pub json: bool,
+    /// Print only a one-line JSON result
+    #[arg(short, long, conflicts_with = "json")]
+    pub quiet: bool,
 }
@@ -0,0 +1,39 @@
+//! Reading sealed audit logs
+//!
+//! With `[tenant_keys]` enabled, audit records of authenticated users are
+//! written sealed with their tenant's key. `supermcp audit decrypt` opens
+//! them with the same keys the server uses and prints the log as it would
+//! have been written in the clear.
+
+use crate::audit::logger::{open_line, sealed_tenant};
+use crate::cli::expand_path;
+use crate::cli::skill::load_config;
+use crate::cloud::tenant_keys::TenantKeys;
+use crate::utils::errors::{McpError, McpResult};
+
+/// `supermcp audit decrypt`
+pub async fn decrypt(config_path: &str, path: Option<&str>, tenant: Option<&str>) -> McpResult<()> {
+    let config = load_config(config_path).await?;
+    let keys = TenantKeys::from_config(&config)?
+        .ok_or_else(|| McpError::ConfigError("tenant_keys is not enabled".to_string()))?;
+    let path = expand_path(path.unwrap_or(&config.audit.path));
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read {}: {}", path, e)))?;
+
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(tenant) = tenant {
+            if sealed_tenant(line).as_deref() != Some(tenant) {
+                continue;
+            }
+        }
+        match open_line(&keys, line).await {
+            Ok(opened) => println!("{}", opened),
+            Err(e) => eprintln!("Line {}: {}", number + 1, e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,343 @@
+//! Portable bundles for offline deployment
+//!
+//! `supermcp bundle create` packs the binary, the config (with its presets)
+//! and the cached registry into one `.tar.gz`, or a self-extracting shell
+//! script. Environment overrides are packed alongside and merged into the
+//! config when the bundle is unpacked with `--env`.
+
+use crate::cloud::Location;
+use crate::config::migration::{parse, serialize};
+use crate::config::{ConfigFormat, RegistryConfig};
+use crate::registry::cache::CACHE_FILE;
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Manifest describing a bundle's contents
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Line separating a self-extracting script from its archive
+const ARCHIVE_MARKER: &str = "__SUPERMCP_ARCHIVE__";
+
+const SELF_EXTRACT_HEADER: &str = r#"#!/bin/sh
+# Self-extracting supermcp bundle
+# Usage: sh <this file> [DEST] [ENV]
+set -e
+DEST="${1:-supermcp}"
+mkdir -p "$DEST"
+LINE=$(awk '/^__SUPERMCP_ARCHIVE__$/ { print NR + 1; exit }' "$0")
+tail -n +"$LINE" "$0" | tar xzf - -C "$DEST"
+if [ -n "$2" ]; then set -- --env "$2"; else set --; fi
+"$DEST/bin/supermcp" bundle apply "$DEST" "$@"
+exit 0
+"#;
+
+/// What a bundle contains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// supermcp version that created the bundle
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    /// `<os>-<arch>` the binary was built for
+    pub platform: String,
+    /// Config file name in the bundle
+    pub config: String,
+    /// Whether `registry/` holds a registry cache
+    pub registry_cache: bool,
+    /// Environments with overrides under `environments/`
+    pub environments: Vec<String>,
+}
+
+fn bundle_error(message: impl std::fmt::Display) -> McpError {
+    McpError::InternalError(format!("Bundle: {}", message))
+}
+
+/// Extension of bundled config files, which keep the source's format
+fn extension(format: ConfigFormat) -> &'static str {
+    match format {
+        ConfigFormat::Toml => "toml",
+        ConfigFormat::Json => "json",
+        ConfigFormat::Yaml => "yaml",
+    }
+}
+
+fn binary_name() -> &'static str {
+    if cfg!(windows) {
+        "supermcp.exe"
+    } else {
+        "supermcp"
+    }
+}
+
+fn append_file<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> McpResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp() as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+/// Package a config and everything it needs into `output`
+///
+/// `env_overrides` are `NAME=FILE` pairs; each file holds config values
+/// merged over the bundled config when unpacked for that environment.
+pub async fn create(
+    config_path: &str,
+    output: &str,
+    binary: Option<&str>,
+    env_overrides: &[String],
+    self_extracting: bool,
+) -> McpResult<()> {
+    let config_path = PathBuf::from(shellexpand::tilde(config_path).to_string());
+    let content = tokio::fs::read_to_string(&config_path).await.map_err(|e| {
+        bundle_error(format!("failed to read {}: {}", config_path.display(), e))
+    })?;
+    let format = ConfigFormat::detect(&config_path, &content);
+    let config = parse(&content, format)?;
+
+    let binary = match binary {
+        Some(path) => PathBuf::from(shellexpand::tilde(path).to_string()),
+        None => std::env::current_exe()?,
+    };
+
+    let mut environments = Vec::new();
+    let mut override_files = Vec::new();
+    for pair in env_overrides {
+        let (name, path) = pair
+            .split_once('=')
+            .filter(|(name, path)| !name.is_empty() && !path.is_empty())
+            .ok_or_else(|| bundle_error(format!("override {} is not NAME=FILE", pair)))?;
+        let path = PathBuf::from(shellexpand::tilde(path).to_string());
+        let overrides = std::fs::read_to_string(&path)
+            .map_err(|e| bundle_error(format!("failed to read {}: {}", path.display(), e)))?;
+        // Stored in the config's format so unpacking needs one parser
+        let overrides = serialize(&parse(&overrides, ConfigFormat::detect(&path, &overrides))?, format)?;
+        environments.push(name.to_string());
+        override_files.push((name.to_string(), overrides));
+    }
+
+    let cache_dir = config
+        .pointer("/registry/cache_dir")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| RegistryConfig::default().cache_dir);
+    let registry = Location::parse(&cache_dir)?
+        .read(CACHE_FILE)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Registry cache not bundled: {}", e);
+            None
+        });
+
+    let manifest = BundleManifest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        config: format!("config.{}", extension(format)),
+        registry_cache: registry.is_some(),
+        environments,
+    };
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    append_file(&mut builder, MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?)?;
+    builder
+        .append_path_with_name(&binary, format!("bin/{}", binary_name()))
+        .map_err(|e| bundle_error(format!("failed to add binary {}: {}", binary.display(), e)))?;
+    append_file(&mut builder, &manifest.config, content.as_bytes())?;
+    if let Some(registry) = &registry {
+        append_file(&mut builder, &format!("registry/{}", CACHE_FILE), registry)?;
+    }
+    for (name, overrides) in &override_files {
+        let file = format!("environments/{}.{}", name, extension(format));
+        append_file(&mut builder, &file, overrides.as_bytes())?;
+    }
+    let archive = builder.into_inner()?.finish()?;
+
+    let output = PathBuf::from(shellexpand::tilde(output).to_string());
+    if self_extracting {
+        let mut script = format!("{}{}\n", SELF_EXTRACT_HEADER, ARCHIVE_MARKER).into_bytes();
+        script.extend_from_slice(&archive);
+        tokio::fs::write(&output, script).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&output, std::fs::Permissions::from_mode(0o755))?;
+        }
+    } else {
+        tokio::fs::write(&output, archive).await?;
+    }
+
+    println!("✓ Created bundle {}", output.display());
+    println!("  Platform: {}", manifest.platform);
+    let presets = config.get("presets").and_then(Value::as_array).map_or(0, Vec::len);
+    println!("  Config: {} ({} presets)", manifest.config, presets);
+    println!(
+        "  Registry cache: {}",
+        if manifest.registry_cache { "included" } else { "not found" }
+    );
+    if !manifest.environments.is_empty() {
+        println!("  Environments: {}", manifest.environments.join(", "));
+    }
+    Ok(())
+}
+
+/// Unpack a bundle or self-extracting script into `dest`
+pub async fn unpack(archive: &str, dest: &str, env: Option<&str>) -> McpResult<()> {
+    let archive = PathBuf::from(shellexpand::tilde(archive).to_string());
+    let data = tokio::fs::read(&archive).await?;
+
+    // Skip the script header of a self-extracting bundle
+    let marker = format!("\n{}\n", ARCHIVE_MARKER);
+    let payload = match data.windows(marker.len()).position(|w| w == marker.as_bytes()) {
+        Some(pos) if data.starts_with(b"#!") => &data[pos + marker.len()..],
+        _ => &data[..],
+    };
+
+    let dest = PathBuf::from(shellexpand::tilde(dest).to_string());
+    std::fs::create_dir_all(&dest)?;
+    tar::Archive::new(GzDecoder::new(payload))
+        .unpack(&dest)
+        .map_err(|e| bundle_error(format!("failed to unpack {}: {}", archive.display(), e)))?;
+
+    apply_overrides(&dest, env)?;
+    println!("✓ Unpacked bundle to {}", dest.display());
+    Ok(())
+}
+
+/// Point an unpacked bundle's config at its own registry cache and merge
+/// the overrides of `env`
+pub fn apply(dir: &str, env: Option<&str>) -> McpResult<()> {
+    let dir = PathBuf::from(shellexpand::tilde(dir).to_string());
+    let config = apply_overrides(&dir, env)?;
+    println!("✓ Config ready at {}", config.display());
+    Ok(())
+}
+
+fn apply_overrides(dir: &Path, env: Option<&str>) -> McpResult<PathBuf> {
+    let manifest: BundleManifest = serde_json::from_slice(&std::fs::read(dir.join(MANIFEST_FILE))?)
+        .map_err(|e| bundle_error(format!("invalid manifest: {}", e)))?;
+    let config_path = dir.join(&manifest.config);
+    let format = ConfigFormat::from_path(&config_path);
+    let mut config = parse(&std::fs::read_to_string(&config_path)?, format)?;
+
+    if manifest.registry_cache {
+        let registry_dir = std::fs::canonicalize(dir)?.join("registry");
+        set_path(
+            &mut config,
+            &["registry", "cache_dir"],
+            Value::String(registry_dir.display().to_string()),
+        );
+    }
+
+    if let Some(env) = env {
+        if !manifest.environments.iter().any(|e| e == env) {
+            return Err(bundle_error(format!(
+                "no overrides for environment {} (available: {})",
+                env,
+                manifest.environments.join(", ")
+            )));
+        }
+        let overrides = std::fs::read_to_string(
+            dir.join("environments").join(format!("{}.{}", env, extension(format))),
+        )?;
+        merge(&mut config, parse(&overrides, format)?);
+    }
+
+    std::fs::write(&config_path, serialize(&config, format)?)?;
+    Ok(config_path)
+}
+
+/// Merge `overlay` into `base`; tables merge key by key, anything else is
+/// replaced
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn set_path(value: &mut Value, path: &[&str], new: Value) {
+    let mut overlay = new;
+    for key in path.iter().rev() {
+        let mut table = Map::new();
+        table.insert(key.to_string(), overlay);
+        overlay = Value::Object(table);
+    }
+    merge(value, overlay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge() {
+        let mut base = json!({ "server": { "host": "127.0.0.1", "port": 3000 }, "servers": [1, 2] });
+        merge(&mut base, json!({ "server": { "port": 8080 }, "servers": [3] }));
+        assert_eq!(base, json!({ "server": { "host": "127.0.0.1", "port": 8080 }, "servers": [3] }));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_unpack() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = dir.path().join("cache");
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(cache.join(CACHE_FILE), "{}").unwrap();
+
+        let config = dir.path().join("config.toml");
+        std::fs::write(
+            &config,
+            format!("[server]\nport = 3000\n\n[registry]\ncache_dir = \"{}\"\n", cache.display()),
+        )
+        .unwrap();
+        let prod = dir.path().join("prod.yaml");
+        std::fs::write(&prod, "server:\n  port: 8080\n").unwrap();
+        let binary = dir.path().join("supermcp");
+        std::fs::write(&binary, "binary").unwrap();
+
+        let output = dir.path().join("bundle.sh");
+        create(
+            &config.display().to_string(),
+            &output.display().to_string(),
+            Some(&binary.display().to_string()),
+            &[format!("prod={}", prod.display())],
+            true,
+        )
+        .await
+        .unwrap();
+
+        let dest = dir.path().join("out");
+        unpack(&output.display().to_string(), &dest.display().to_string(), Some("prod"))
+            .await
+            .unwrap();
+
+        let unpacked = std::fs::read_to_string(dest.join("config.toml")).unwrap();
+        assert!(unpacked.contains("port = 8080"));
+        let registry = std::fs::canonicalize(&dest).unwrap().join("registry");
+        assert!(unpacked.contains(&registry.display().to_string()));
+        assert!(dest.join("registry").join(CACHE_FILE).exists());
+        assert_eq!(std::fs::read_to_string(dest.join("bin").join(binary_name())).unwrap(), "binary");
+        assert!(apply(&dest.display().to_string(), Some("staging")).is_err());
+    }
+}
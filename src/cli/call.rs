@@ -4,14 +4,18 @@
 //! and skills directly without running a proxy server.
 
 use crate::cli::expand_path;
+use crate::cli::output::{print_list, OutputFormat, Table};
 use crate::cli::skill_provider::SkillProvider;
 use crate::config::{Config, McpServerConfig, SandboxConfig};
 // Note: JsonRpcRequest is used internally by McpProvider
 use crate::core::provider::{McpProvider, Provider, ProviderRegistry, ProviderType, Tool, ToolResult};
 use crate::core::server::{ManagedServer, TransportType};
+use crate::skills::SkillManager;
 use crate::utils::errors::{McpError, McpResult};
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use dialoguer::console::{style, Term};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use tracing::{debug, info};
 
@@ -50,6 +54,25 @@ fn split_args_respecting_nesting(input: &str) -> Vec<&str> {
     result
 }
 
+/// Strip the surrounding quotes from a string literal and resolve backslash escapes
+fn unquote(value: &str) -> String {
+    let quote = value.chars().next().unwrap_or('"');
+    let inner = value[quote.len_utf8()..].strip_suffix(quote).unwrap_or(&value[quote.len_utf8()..]);
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// Parse arguments in shell-friendly format: key:value or key=value
 pub fn parse_call_args(args: &[String]) -> McpResult<Value> {
     let mut map = serde_json::Map::new();
@@ -130,7 +153,7 @@ pub fn parse_function_style(input: &str) -> McpResult<(String, Value)> {
 
                 let parsed_value = if value.starts_with('"') || value.starts_with('\'') {
                     // String literal
-                    Value::String(value.trim_matches('"').trim_matches('\'').to_string())
+                    Value::String(unquote(value))
                 } else if let Ok(n) = value.parse::<i64>() {
                     Value::Number(n.into())
                 } else if let Ok(b) = value.parse::<bool>() {
@@ -161,6 +184,7 @@ pub async fn execute(
     skill_name: Option<&str>,
     env_vars: Vec<String>,
     json_output: bool,
+    dry_run: bool,
 ) -> McpResult<()> {
     // Parse environment variables
     let env_vars_map = parse_env_vars(&env_vars)?;
@@ -168,6 +192,17 @@ pub async fn execute(
     // Build provider registry with env vars for adhoc servers
     let registry = build_registry(config_path, stdio_cmd, http_url, skill_name, Some(env_vars_map)).await?;
 
+    call_tool(&registry, target, args, json_output, dry_run).await
+}
+
+/// Call a tool from a registry and print its result
+pub async fn call_tool(
+    registry: &ProviderRegistry,
+    target: &str,
+    args: Vec<String>,
+    json_output: bool,
+    dry_run: bool,
+) -> McpResult<()> {
     // Parse the tool name and arguments
     let (tool_name, params) = if target.contains('(') {
         // Function-style: toolName(args...)
@@ -186,36 +221,7 @@ pub async fn execute(
     };
 
     // Find the provider and tool
-    let (provider_name, tool_name) = if tool_name.contains('.') {
-        let parts: Vec<&str> = tool_name.splitn(2, '.').collect();
-        (parts[0].to_string(), parts[1].to_string())
-    } else {
-        // Try to find the tool in any provider
-        let all_tools = registry.list_all_tools().await?;
-        let matching = all_tools
-            .into_iter()
-            .filter(|t| t.display_name() == tool_name || t.snake_name() == tool_name)
-            .collect::<Vec<_>>();
-
-        if matching.is_empty() {
-            return Err(McpError::ToolExecutionError(format!(
-                "Tool '{}' not found in any provider",
-                tool_name
-            )));
-        }
-
-        if matching.len() > 1 {
-            let providers: Vec<_> = matching.iter().map(|t| t.provider.clone()).collect();
-            return Err(McpError::InvalidRequest(format!(
-                "Ambiguous tool name '{}'. Found in providers: {}. Use provider.tool_name format.",
-                tool_name,
-                providers.join(", ")
-            )));
-        }
-
-        let tool = &matching[0];
-        (tool.provider.clone(), tool.display_name().to_string())
-    };
+    let (provider_name, tool_name) = resolve_tool(registry, &tool_name).await?;
 
     // Convert snake_case to kebab-case for MCP compatibility
     let tool_name_kebab = tool_name.replace('_', "-");
@@ -228,6 +234,20 @@ pub async fn execute(
         .get(&provider_name)
         .ok_or_else(|| McpError::ServerNotFound(provider_name.clone()))?;
 
+    if dry_run {
+        let tools = provider.list_tools().await?;
+        let tool = tools.iter().find(|t| {
+            t.name == full_tool_name || t.display_name() == tool_name || t.snake_name() == tool_name
+        });
+        let report = crate::core::dry_run::report(
+            &provider_name,
+            tool.map(|t| t.display_name()).unwrap_or(tool_name.as_str()),
+            &params,
+            tool.map(parameters_schema).as_ref(),
+        );
+        return print_dry_run(&report, json_output);
+    }
+
     let result = provider.call_tool(&full_tool_name, params).await?;
 
     // Handle the result
@@ -264,6 +284,40 @@ pub async fn execute(
     Ok(())
 }
 
+/// Provider and tool name for `provider.tool`, or for a bare tool name
+/// offered by exactly one provider
+pub async fn resolve_tool(registry: &ProviderRegistry, tool_name: &str) -> McpResult<(String, String)> {
+    if let Some((provider, tool)) = tool_name.split_once('.') {
+        return Ok((provider.to_string(), tool.to_string()));
+    }
+
+    // Try to find the tool in any provider
+    let all_tools = registry.list_all_tools().await?;
+    let matching = all_tools
+        .into_iter()
+        .filter(|t| t.display_name() == tool_name || t.snake_name() == tool_name)
+        .collect::<Vec<_>>();
+
+    if matching.is_empty() {
+        return Err(McpError::ToolExecutionError(format!(
+            "Tool '{}' not found in any provider",
+            tool_name
+        )));
+    }
+
+    if matching.len() > 1 {
+        let providers: Vec<_> = matching.iter().map(|t| t.provider.clone()).collect();
+        return Err(McpError::InvalidRequest(format!(
+            "Ambiguous tool name '{}'. Found in providers: {}. Use provider.tool_name format.",
+            tool_name,
+            providers.join(", ")
+        )));
+    }
+
+    let tool = &matching[0];
+    Ok((tool.provider.clone(), tool.display_name().to_string()))
+}
+
 /// Build the provider registry from all sources
 pub async fn build_registry(
     config_path: Option<&str>,
@@ -274,9 +328,11 @@ pub async fn build_registry(
 ) -> McpResult<ProviderRegistry> {
     let registry = ProviderRegistry::new();
 
+    let config = load_config(config_path).await.ok();
+
     // Add MCP servers from config
-    if let Ok(config) = load_config(config_path).await {
-        for server_config in config.servers {
+    if let Some(config) = &config {
+        for server_config in config.servers.iter().cloned() {
             let name = server_config.name.clone();
             match ManagedServer::new(server_config).await {
                 Ok(server) => {
@@ -291,6 +347,36 @@ pub async fn build_registry(
         }
     }
 
+    // Add WASM provider plugins from config
+    if let Some(config) = &config {
+        for plugin_config in config.plugins.iter().filter(|p| p.enabled) {
+            match crate::plugins::load(plugin_config).await {
+                Ok(provider) => {
+                    registry.register(provider);
+                    debug!("Registered plugin provider: {}", plugin_config.name);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to load plugin {}: {}", plugin_config.name, e);
+                }
+            }
+        }
+    }
+
+    // Add built-in providers from config
+    if let Some(config) = &config {
+        for provider_config in &config.providers {
+            match crate::providers::load(provider_config) {
+                Ok(provider) => {
+                    registry.register(provider);
+                    debug!("Registered provider: {}", provider_config.name());
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to load provider {}: {}", provider_config.name(), e);
+                }
+            }
+        }
+    }
+
     // Add ad-hoc stdio server if specified
     if let Some(cmd) = stdio_cmd {
         let env_vars = stdio_env_vars.unwrap_or_default();
@@ -318,13 +404,13 @@ pub async fn build_registry(
 
     // Add skill if specified
     if let Some(name) = skill_name {
-        if let Some(provider) = load_skill_provider(name).await? {
+        if let Some(provider) = load_skill_provider(name, config.as_ref()).await? {
             registry.register(provider);
         }
     }
 
     // Auto-discover and load skills
-    if let Ok(skills) = discover_skills().await {
+    if let Ok(skills) = discover_skills(config.as_ref()).await {
         for skill in skills {
             registry.register(skill);
         }
@@ -354,6 +440,7 @@ async fn create_adhoc_stdio_server(cmd: &str, env_vars: HashMap<String, String>)
         tags: vec!["adhoc".to_string()],
         description: Some("Ad-hoc stdio connection".to_string()),
         sandbox: SandboxConfig::default(),
+        ..Default::default()
     };
 
     ManagedServer::new(config).await
@@ -362,7 +449,11 @@ async fn create_adhoc_stdio_server(cmd: &str, env_vars: HashMap<String, String>)
 /// Create an ad-hoc HTTP/SSE server
 async fn create_adhoc_http_server(url: &str) -> McpResult<ManagedServer> {
     // Determine transport type from URL
-    let transport_type = if url.starts_with("http://") || url.starts_with("https://") {
+    let transport_type = if url.starts_with(r"\\") || url.starts_with("pipe:") {
+        TransportType::NamedPipe
+    } else if url.starts_with("nats://") {
+        TransportType::Nats
+    } else if url.starts_with("http://") || url.starts_with("https://") {
         // Try to detect if it's SSE or regular HTTP
         if url.contains("/sse") || url.contains("stream") {
             TransportType::Sse
@@ -371,7 +462,7 @@ async fn create_adhoc_http_server(url: &str) -> McpResult<ManagedServer> {
         }
     } else {
         return Err(McpError::ConfigError(format!(
-            "Invalid URL scheme: {}. Use http:// or https://",
+            "Invalid URL scheme: {}. Use http://, https://, nats:// or a named pipe path",
             url
         )));
     };
@@ -384,19 +475,25 @@ async fn create_adhoc_http_server(url: &str) -> McpResult<ManagedServer> {
         tags: vec!["adhoc".to_string()],
         description: Some(format!("Ad-hoc HTTP connection: {}", url)),
         sandbox: SandboxConfig::default(),
+        ..Default::default()
     };
 
-    ManagedServer::with_transport(config, transport_type, Some(url.to_string())).await
+    let endpoint = url.strip_prefix("pipe:").unwrap_or(url).to_string();
+    ManagedServer::with_transport(config, transport_type, Some(endpoint)).await
 }
 
 /// Load a skill provider by name
-async fn load_skill_provider(name: &str) -> McpResult<Option<Box<dyn crate::core::provider::Provider>>> {
+async fn load_skill_provider(
+    name: &str,
+    config: Option<&Config>,
+) -> McpResult<Option<Box<dyn crate::core::provider::Provider>>> {
     let skill_paths = vec![
         dirs::config_dir()
             .map(|d| d.join(format!("agents/skills/{}", name))),
         dirs::home_dir()
             .map(|d| d.join(format!(".config/agents/skills/{}", name))),
         Some(PathBuf::from(format!("./skills/{}", name))),
+        Some(managed_skills_dir(config).join(name)),
     ];
 
     for path in skill_paths.into_iter().flatten() {
@@ -416,14 +513,24 @@ async fn load_skill_provider(name: &str) -> McpResult<Option<Box<dyn crate::core
     Ok(None)
 }
 
+/// Directory `supermcp skill install` manages, `[skills] directory`
+fn managed_skills_dir(config: Option<&Config>) -> PathBuf {
+    let default = crate::config::SkillsConfig::default();
+    let skills = config.map_or(&default, |config| &config.skills);
+    SkillManager::from_config(skills).directory().to_path_buf()
+}
+
 /// Discover all available skills
-async fn discover_skills() -> McpResult<Vec<Box<dyn crate::core::provider::Provider>>> {
+async fn discover_skills(
+    config: Option<&Config>,
+) -> McpResult<Vec<Box<dyn crate::core::provider::Provider>>> {
     let mut providers = Vec::new();
 
     let skill_dirs = vec![
         dirs::config_dir().map(|d| d.join("agents/skills")),
         dirs::home_dir().map(|d| d.join(".config/agents/skills")),
         Some(PathBuf::from("./skills")),
+        Some(managed_skills_dir(config)),
     ];
 
     for dir in skill_dirs.into_iter().flatten() {
@@ -466,9 +573,14 @@ pub async fn list_tools(
     show_schema: bool,
     json_output: bool,
     all: bool,
+    watch: Option<u64>,
 ) -> McpResult<()> {
     let registry = build_registry(config_path, stdio_cmd, http_url, skill_name, None).await?;
 
+    if let Some(interval) = watch {
+        return watch_tools(&registry, provider_filter, show_schema, interval).await;
+    }
+
     let all_tools;
 
     if let Some(filter) = provider_filter {
@@ -523,8 +635,96 @@ pub async fn list_tools(
     Ok(())
 }
 
+/// How a tool differs from the previous refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Tools added, removed or changed between two listings keyed by name
+fn tool_changes(before: &BTreeMap<String, Tool>, after: &BTreeMap<String, Tool>) -> BTreeMap<String, ToolChange> {
+    let signature = |tool: &Tool| serde_json::to_value((&tool.description, &tool.parameters)).ok();
+    let mut changes = BTreeMap::new();
+    for (name, tool) in after {
+        match before.get(name) {
+            None => {
+                changes.insert(name.clone(), ToolChange::Added);
+            }
+            Some(old) if signature(old) != signature(tool) => {
+                changes.insert(name.clone(), ToolChange::Changed);
+            }
+            Some(_) => {}
+        }
+    }
+    for name in before.keys().filter(|name| !after.contains_key(*name)) {
+        changes.insert(name.clone(), ToolChange::Removed);
+    }
+    changes
+}
+
+/// Redraw the tool table every `interval_secs`, marking tools added (+),
+/// removed (-) or changed (~) since the previous refresh
+async fn watch_tools(
+    registry: &ProviderRegistry,
+    provider_filter: Option<&str>,
+    show_schema: bool,
+    interval_secs: u64,
+) -> McpResult<()> {
+    let term = Term::stdout();
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs.max(1)));
+    let mut previous: Option<BTreeMap<String, Tool>> = None;
+
+    loop {
+        ticker.tick().await;
+        let tools = match provider_filter {
+            Some(filter) => {
+                let provider = registry
+                    .get(filter)
+                    .ok_or_else(|| McpError::ServerNotFound(filter.to_string()))?;
+                provider.list_tools().await.unwrap_or_else(|e| {
+                    debug!("Failed to list tools from {}: {}", filter, e);
+                    Vec::new()
+                })
+            }
+            None => registry.list_all_tools().await?,
+        };
+        let current: BTreeMap<String, Tool> = tools.into_iter().map(|t| (t.name.clone(), t)).collect();
+        let changes = previous
+            .as_ref()
+            .map(|before| tool_changes(before, &current))
+            .unwrap_or_default();
+
+        term.clear_screen()?;
+        println!(
+            "Watching {} tools every {}s (Ctrl-C to stop), updated {}",
+            current.len(),
+            interval_secs.max(1),
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        let mut provider = None;
+        for tool in current.values() {
+            if provider != Some(&tool.provider) {
+                provider = Some(&tool.provider);
+                println!("\n📦 {}", tool.provider);
+            }
+            match changes.get(&tool.name) {
+                Some(ToolChange::Added) => print!("{}", style("+").green().bold()),
+                Some(ToolChange::Changed) => print!("{}", style("~").yellow().bold()),
+                _ => print!(" "),
+            }
+            print_tool(tool, show_schema);
+        }
+        for (name, _) in changes.iter().filter(|(_, change)| **change == ToolChange::Removed) {
+            println!("{}", style(format!("-  {} (removed)", name)).red());
+        }
+        previous = Some(current);
+    }
+}
+
 /// Print a tool in a readable format
-fn print_tool(tool: &Tool, show_schema: bool) {
+pub(crate) fn print_tool(tool: &Tool, show_schema: bool) {
     let display_name = tool.snake_name();
     let provider_type = format!("[{}]", tool.provider_type);
 
@@ -548,6 +748,46 @@ fn print_tool(tool: &Tool, show_schema: bool) {
     }
 }
 
+/// JSON schema equivalent to a tool's parameters
+fn parameters_schema(tool: &Tool) -> Value {
+    let properties: serde_json::Map<String, Value> = tool
+        .parameters
+        .iter()
+        .map(|param| (param.name.clone(), serde_json::json!({ "type": param.param_type })))
+        .collect();
+    let required: Vec<&str> = tool
+        .parameters
+        .iter()
+        .filter(|param| param.required)
+        .map(|param| param.name.as_str())
+        .collect();
+    serde_json::json!({ "type": "object", "properties": properties, "required": required })
+}
+
+/// Print a dry-run report, failing when the arguments are invalid
+fn print_dry_run(report: &Value, json_output: bool) -> McpResult<()> {
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(report).unwrap_or_default());
+    } else {
+        println!(
+            "Dry run: would call {} on {}",
+            report["tool"].as_str().unwrap_or_default(),
+            report["server"].as_str().unwrap_or_default()
+        );
+        for problem in report["problems"].as_array().into_iter().flatten() {
+            println!("  ✗ {}", problem.as_str().unwrap_or_default());
+        }
+        println!(
+            "Audit entry:\n{}",
+            serde_json::to_string_pretty(&report["audit"]).unwrap_or_default()
+        );
+    }
+    if report["valid"].as_bool() != Some(true) {
+        return Err(McpError::InvalidRequest("Arguments do not match the tool's schema".to_string()));
+    }
+    Ok(())
+}
+
 /// Print tool result in a readable format
 fn print_tool_result(result: &ToolResult) {
     // Handle content array format
@@ -570,40 +810,50 @@ fn print_tool_result(result: &ToolResult) {
 /// List all available providers (MCPs and skills)
 pub async fn list_providers(
     config_path: Option<&str>,
-    json_output: bool,
+    output: OutputFormat,
 ) -> McpResult<()> {
     let registry = build_registry(config_path, None, None, None, None).await?;
-    
-    let providers: Vec<_> = registry
-        .list()
-        .into_iter()
-        .map(|name| {
-            let provider = registry.get(&name).unwrap();
-            serde_json::json!({
-                "name": name,
-                "type": provider.provider_type().to_string(),
-                "available": "unknown", // Would need async call here
-            })
-        })
-        .collect();
 
-    if json_output {
-        println!("{}", serde_json::to_string_pretty(&providers).unwrap());
-    } else {
-        println!("\n📦 Available Providers:\n");
-        for p in &providers {
-            println!(
-                "  {} [{}]",
-                p["name"].as_str().unwrap_or("unknown"),
-                p["type"].as_str().unwrap_or("unknown")
-            );
+    let mut providers = Vec::new();
+    for name in registry.list() {
+        let Some(provider) = registry.get(&name) else {
+            continue;
+        };
+        providers.push(ProviderSummary {
+            provider_type: provider.provider_type().to_string(),
+            available: provider.is_available().await,
+            name,
+        });
+    }
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+
+    print_list(output, "ProviderList", &providers, || {
+        let mut table = Table::new().column("NAME").column("TYPE").wide_column("AVAILABLE");
+        for provider in &providers {
+            table.row(vec![
+                provider.name.clone(),
+                provider.provider_type.clone(),
+                provider.available.to_string(),
+            ]);
         }
+        table
+    })?;
+
+    if output.is_human() {
         println!("\nTotal: {} providers", providers.len());
     }
-
     Ok(())
 }
 
+/// A provider, as listed by `providers --output json|yaml`
+#[derive(Debug, Serialize)]
+pub struct ProviderSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub provider_type: String,
+    pub available: bool,
+}
+
 /// Load configuration from file
 async fn load_config(config_path: Option<&str>) -> McpResult<Config> {
     let path = config_path
@@ -619,12 +869,14 @@ async fn load_config(config_path: Option<&str>) -> McpResult<Config> {
         .await
         .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
 
-    toml::from_str(&content)
-        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))
+    let config: Config = toml::from_str(&content)
+        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?;
+    crate::transport::http_client::install_proxy(config.proxy.clone());
+    Ok(config)
 }
 
 /// Parse environment variables in KEY=value format
-fn parse_env_vars(env_vars: &[String]) -> McpResult<HashMap<String, String>> {
+pub(crate) fn parse_env_vars(env_vars: &[String]) -> McpResult<HashMap<String, String>> {
     let mut map = HashMap::new();
     for var in env_vars {
         let parts: Vec<&str> = var.splitn(2, '=').collect();
@@ -659,6 +911,17 @@ mod tests {
         assert_eq!(result["json"]["nested"], "value");
     }
 
+    #[test]
+    fn test_managed_skills_dir_follows_config() {
+        let mut config = Config::default();
+        config.skills.directory = "/srv/skills".to_string();
+        assert_eq!(managed_skills_dir(Some(&config)), PathBuf::from("/srv/skills"));
+        assert_eq!(
+            managed_skills_dir(None),
+            PathBuf::from(expand_path(&crate::config::SkillsConfig::default().directory))
+        );
+    }
+
     #[test]
     fn test_parse_function_style() {
         let input = "server.tool_name(key1: value1, key2: 42)";
@@ -668,6 +931,29 @@ mod tests {
         assert_eq!(params["key2"], 42);
     }
 
+    #[test]
+    fn test_tool_changes() {
+        let tool = |name: &str, description: &str| Tool {
+            name: format!("fs.{}", name),
+            description: Some(description.to_string()),
+            provider: "fs".to_string(),
+            provider_type: ProviderType::McpStdio,
+            parameters: Vec::new(),
+            metadata: HashMap::new(),
+        };
+        let listing = |tools: Vec<Tool>| -> BTreeMap<String, Tool> {
+            tools.into_iter().map(|t| (t.name.clone(), t)).collect()
+        };
+        let before = listing(vec![tool("read", "Read"), tool("write", "Write")]);
+        let after = listing(vec![tool("read", "Read a file"), tool("list", "List")]);
+
+        let changes = tool_changes(&before, &after);
+        assert_eq!(changes["fs.read"], ToolChange::Changed);
+        assert_eq!(changes["fs.list"], ToolChange::Added);
+        assert_eq!(changes["fs.write"], ToolChange::Removed);
+        assert!(tool_changes(&after, &after).is_empty());
+    }
+
     #[test]
     fn test_parse_function_style_quotes() {
         let input = r#"search(query: "hello world", limit: 10)"#;
@@ -0,0 +1,565 @@
+//! Capability snapshots for offline review
+//!
+//! `supermcp capabilities export` connects to every configured server and
+//! writes its tools, resources and prompts, as the server describes them,
+//! to one JSON file signed with Ed25519. `supermcp capabilities compare`
+//! verifies a snapshot and lists what differs from another snapshot, or
+//! from the live servers, so a reviewer on an air-gapped machine can sign
+//! off on a server before it is enabled. `supermcp capabilities lock`
+//! pins the live tool schemas (see [`crate::core::schema_lock`]).
+
+use crate::cli::expand_path;
+use crate::cli::skill::load_config;
+use crate::config::McpServerConfig;
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, ProtocolVersion};
+use crate::core::schema_lock::SchemaLock;
+use crate::core::server::ManagedServer;
+use crate::utils::errors::{McpError, McpResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// What every configured server offered at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilitySnapshot {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// supermcp version that took the snapshot
+    pub generator: String,
+    pub servers: BTreeMap<String, ServerSnapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SnapshotSignature>,
+}
+
+/// Tools by name, resources by URI and prompts by name, as listed by a server
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerSnapshot {
+    /// Why the server couldn't be listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub tools: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub resources: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub prompts: BTreeMap<String, Value>,
+}
+
+/// Ed25519 signature over the snapshot without this field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSignature {
+    pub algorithm: String,
+    /// Base64 public key
+    pub public_key: String,
+    /// Base64 signature
+    pub value: String,
+}
+
+/// JSON with object keys sorted, so signed or hashed bytes don't depend
+/// on field order
+pub fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            Value::Object(
+                keys.into_iter()
+                    .map(|key| (key.clone(), canonical(&object[key])))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+impl CapabilitySnapshot {
+    /// Bytes covered by the signature
+    fn signed_bytes(&self) -> McpResult<Vec<u8>> {
+        let mut unsigned = serde_json::to_value(self)?;
+        if let Some(object) = unsigned.as_object_mut() {
+            object.remove("signature");
+        }
+        Ok(serde_json::to_vec(&canonical(&unsigned))?)
+    }
+
+    /// Sign with a PKCS#8 Ed25519 key
+    pub fn sign(&mut self, pkcs8: &[u8]) -> McpResult<()> {
+        let key = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map_err(|e| McpError::ConfigError(format!("Invalid Ed25519 signing key: {}", e)))?;
+        let signature = key.sign(&self.signed_bytes()?);
+        self.signature = Some(SnapshotSignature {
+            algorithm: "ed25519".to_string(),
+            public_key: STANDARD.encode(key.public_key().as_ref()),
+            value: STANDARD.encode(signature.as_ref()),
+        });
+        Ok(())
+    }
+
+    /// Check the signature, and that it was made by `trusted_key` if given;
+    /// returns the signer's public key
+    pub fn verify(&self, trusted_key: Option<&str>) -> McpResult<String> {
+        let invalid = |message: &str| McpError::InvalidRequest(format!("Snapshot {}", message));
+        let signature = self.signature.as_ref().ok_or_else(|| invalid("is not signed"))?;
+        if signature.algorithm != "ed25519" {
+            return Err(invalid(&format!("uses unsupported algorithm {}", signature.algorithm)));
+        }
+        if trusted_key.is_some_and(|key| key.trim() != signature.public_key) {
+            return Err(invalid("was not signed by the trusted key"));
+        }
+        let public_key = STANDARD
+            .decode(&signature.public_key)
+            .map_err(|_| invalid("has a malformed public key"))?;
+        let value = STANDARD
+            .decode(&signature.value)
+            .map_err(|_| invalid("has a malformed signature"))?;
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&self.signed_bytes()?, &value)
+            .map_err(|_| invalid("signature does not match its contents"))?;
+        Ok(signature.public_key.clone())
+    }
+}
+
+/// Result of a list request, or its JSON-RPC error
+fn rpc_result(method: &str, response: JsonRpcResponse) -> McpResult<Value> {
+    match response.error {
+        Some(error) => Err(McpError::TransportError(format!("{} failed: {}", method, error.message))),
+        None => Ok(response.result.unwrap_or(Value::Null)),
+    }
+}
+
+/// Every item of a paginated list, keyed by its `key` field
+pub async fn list_all(
+    server: &ManagedServer,
+    method: &str,
+    field: &str,
+    key: &str,
+) -> McpResult<BTreeMap<String, Value>> {
+    let mut items = BTreeMap::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let params = cursor.as_ref().map(|cursor| json!({ "cursor": cursor }));
+        let result = rpc_result(method, server.send_request(JsonRpcRequest::new(method, params)).await?)?;
+        for item in result.get(field).and_then(Value::as_array).into_iter().flatten() {
+            if let Some(name) = item.get(key).and_then(Value::as_str) {
+                items.insert(name.to_string(), item.clone());
+            }
+        }
+        match result.get("nextCursor").and_then(Value::as_str) {
+            Some(next) if cursor.as_deref() != Some(next) => cursor = Some(next.to_string()),
+            _ => return Ok(items),
+        }
+    }
+}
+
+async fn list_server(server: &ManagedServer) -> McpResult<ServerSnapshot> {
+    let mut params = json!({
+        "protocolVersion": ProtocolVersion::LATEST.as_str(),
+        "capabilities": {},
+        "clientInfo": {
+            "name": "super-mcp",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    });
+    server.config.initialize.apply(&mut params);
+    let initialize = JsonRpcRequest::new("initialize", Some(params));
+    let result = rpc_result("initialize", server.send_request(initialize).await?)?;
+    server
+        .send_notification(JsonRpcRequest::new("notifications/initialized", None))
+        .await?;
+
+    // Only ask for what the server says it offers
+    let offers = |capability: &str| result.get("capabilities").and_then(|c| c.get(capability)).is_some();
+    let mut snapshot = ServerSnapshot::default();
+    if offers("tools") {
+        snapshot.tools = list_all(server, "tools/list", "tools", "name").await?;
+    }
+    if offers("resources") {
+        snapshot.resources = list_all(server, "resources/list", "resources", "uri").await?;
+    }
+    if offers("prompts") {
+        snapshot.prompts = list_all(server, "prompts/list", "prompts", "name").await?;
+    }
+    Ok(snapshot)
+}
+
+/// Snapshot of one server; failures are recorded rather than fatal
+async fn snapshot_server(config: McpServerConfig) -> ServerSnapshot {
+    let name = config.name.clone();
+    let result = match ManagedServer::new(config).await {
+        Ok(server) => {
+            let result = list_server(&server).await;
+            let _ = server.stop().await;
+            result
+        }
+        Err(e) => Err(e),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("⚠ {}: {}", name, e);
+        ServerSnapshot {
+            error: Some(e.to_string()),
+            ..Default::default()
+        }
+    })
+}
+
+/// Unsigned snapshot of every server in the config
+pub async fn take_snapshot(config_path: &str) -> McpResult<CapabilitySnapshot> {
+    let config = load_config(config_path).await?;
+    let mut servers = BTreeMap::new();
+    for server in config.servers {
+        let name = server.name.clone();
+        servers.insert(name, snapshot_server(server).await);
+    }
+    Ok(CapabilitySnapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: Utc::now(),
+        generator: format!("supermcp {}", env!("CARGO_PKG_VERSION")),
+        servers,
+        signature: None,
+    })
+}
+
+/// How an item differs between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Top-level fields whose values differ, e.g. `description`, `inputSchema`
+    Modified(Vec<String>),
+}
+
+/// One tool, resource or prompt that differs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub server: String,
+    /// `tool`, `resource` or `prompt`
+    pub kind: &'static str,
+    pub name: String,
+    pub change: ChangeKind,
+}
+
+/// Differences between a baseline snapshot and another
+#[derive(Debug, Default, Serialize)]
+pub struct SnapshotDiff {
+    pub servers_added: Vec<String>,
+    pub servers_removed: Vec<String>,
+    /// Servers that couldn't be listed in either snapshot, so weren't compared
+    pub servers_unavailable: Vec<String>,
+    pub changes: Vec<Change>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.servers_added.is_empty()
+            && self.servers_removed.is_empty()
+            && self.servers_unavailable.is_empty()
+            && self.changes.is_empty()
+    }
+}
+
+/// Changes from the `old` to the `new` items of one kind on a server
+pub fn diff_items(
+    server: &str,
+    kind: &'static str,
+    old: &BTreeMap<String, Value>,
+    new: &BTreeMap<String, Value>,
+    changes: &mut Vec<Change>,
+) {
+    let change = |name: &str, change| Change {
+        server: server.to_string(),
+        kind,
+        name: name.to_string(),
+        change,
+    };
+    for (name, old_item) in old {
+        match new.get(name) {
+            None => changes.push(change(name, ChangeKind::Removed)),
+            Some(new_item) if new_item != old_item => {
+                let empty = Map::new();
+                let old_fields = old_item.as_object().unwrap_or(&empty);
+                let new_fields = new_item.as_object().unwrap_or(&empty);
+                let mut fields: Vec<String> = old_fields
+                    .keys()
+                    .chain(new_fields.keys())
+                    .filter(|field| old_fields.get(*field) != new_fields.get(*field))
+                    .cloned()
+                    .collect();
+                fields.sort();
+                fields.dedup();
+                changes.push(change(name, ChangeKind::Modified(fields)));
+            }
+            Some(_) => {}
+        }
+    }
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        changes.push(change(name, ChangeKind::Added));
+    }
+}
+
+/// What changed from `baseline` to `other`
+pub fn diff(baseline: &CapabilitySnapshot, other: &CapabilitySnapshot) -> SnapshotDiff {
+    let mut result = SnapshotDiff::default();
+    for (name, old) in &baseline.servers {
+        let Some(new) = other.servers.get(name) else {
+            result.servers_removed.push(name.clone());
+            continue;
+        };
+        if old.error.is_some() || new.error.is_some() {
+            result.servers_unavailable.push(name.clone());
+            continue;
+        }
+        diff_items(name, "tool", &old.tools, &new.tools, &mut result.changes);
+        diff_items(name, "resource", &old.resources, &new.resources, &mut result.changes);
+        diff_items(name, "prompt", &old.prompts, &new.prompts, &mut result.changes);
+    }
+    result.servers_added = other
+        .servers
+        .keys()
+        .filter(|name| !baseline.servers.contains_key(*name))
+        .cloned()
+        .collect();
+    result
+}
+
+/// Read a snapshot file, checking its format version
+pub async fn read_snapshot(path: &str) -> McpResult<CapabilitySnapshot> {
+    let path = expand_path(path);
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read snapshot {}: {}", path, e)))?;
+    let snapshot: CapabilitySnapshot = serde_json::from_str(&content)?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(McpError::InvalidRequest(format!(
+            "Unsupported snapshot version {} in {}",
+            snapshot.version, path
+        )));
+    }
+    Ok(snapshot)
+}
+
+async fn read_key(path: &str) -> McpResult<Vec<u8>> {
+    let path = expand_path(path);
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read key {}: {}", path, e)))?;
+    STANDARD
+        .decode(content.trim())
+        .map_err(|_| McpError::ConfigError(format!("Key {} is not base64", path)))
+}
+
+fn generate_key() -> McpResult<Vec<u8>> {
+    Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map(|pkcs8| pkcs8.as_ref().to_vec())
+        .map_err(|_| McpError::InternalError("Failed to generate key".to_string()))
+}
+
+/// `supermcp capabilities export`
+pub async fn export(config_path: &str, output: Option<&str>, key_path: Option<&str>) -> McpResult<()> {
+    let key = match key_path {
+        Some(path) => read_key(path).await?,
+        None => {
+            eprintln!("⚠ No --key given; signing with a one-off key, which only proves integrity");
+            generate_key()?
+        }
+    };
+    let mut snapshot = take_snapshot(config_path).await?;
+    snapshot.sign(&key)?;
+
+    let text = serde_json::to_string_pretty(&snapshot)?;
+    match output {
+        Some(path) => {
+            let path = expand_path(path);
+            tokio::fs::write(&path, text).await?;
+            eprintln!("✓ Wrote snapshot of {} servers to {}", snapshot.servers.len(), path);
+        }
+        None => println!("{}", text),
+    }
+    if let Some(signature) = &snapshot.signature {
+        eprintln!("  Signed by {}", signature.public_key);
+    }
+    Ok(())
+}
+
+/// `supermcp capabilities lock`
+pub async fn lock(config_path: &str, output: Option<&str>) -> McpResult<()> {
+    let path = match output {
+        Some(path) => path.to_string(),
+        None => load_config(config_path).await?.schema_lock.path,
+    };
+    let snapshot = take_snapshot(config_path).await?;
+    let lock = SchemaLock::from_snapshot(&snapshot);
+    let path = expand_path(&path);
+    tokio::fs::write(&path, serde_json::to_string_pretty(&lock)?).await?;
+    let tools: usize = lock.servers.values().map(|tools| tools.len()).sum();
+    eprintln!("✓ Pinned {} tools of {} servers in {}", tools, lock.servers.len(), path);
+    Ok(())
+}
+
+/// `supermcp capabilities keygen`
+pub async fn keygen(output: &str) -> McpResult<()> {
+    let pkcs8 = generate_key()?;
+    let key = Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|e| McpError::InternalError(format!("Failed to generate key: {}", e)))?;
+    let path = expand_path(output);
+    tokio::fs::write(&path, format!("{}\n", STANDARD.encode(&pkcs8))).await?;
+    eprintln!("✓ Wrote signing key to {}; keep it secret", path);
+    println!("{}", STANDARD.encode(key.public_key().as_ref()));
+    Ok(())
+}
+
+fn print_diff(diff: &SnapshotDiff, baseline: &CapabilitySnapshot, other: &CapabilitySnapshot) {
+    for name in &diff.servers_added {
+        let server = &other.servers[name];
+        println!(
+            "+ server {} ({} tools, {} resources, {} prompts)",
+            name,
+            server.tools.len(),
+            server.resources.len(),
+            server.prompts.len()
+        );
+    }
+    for name in &diff.servers_removed {
+        println!("- server {}", name);
+    }
+    for name in &diff.servers_unavailable {
+        let error = baseline.servers[name]
+            .error
+            .as_deref()
+            .or(other.servers[name].error.as_deref())
+            .unwrap_or_default();
+        println!("! server {} not compared: {}", name, error);
+    }
+    for change in &diff.changes {
+        match &change.change {
+            ChangeKind::Added => println!("+ {}: {} {}", change.server, change.kind, change.name),
+            ChangeKind::Removed => println!("- {}: {} {}", change.server, change.kind, change.name),
+            ChangeKind::Modified(fields) => println!(
+                "~ {}: {} {} ({})",
+                change.server,
+                change.kind,
+                change.name,
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+/// `supermcp capabilities compare`
+pub async fn compare(
+    config_path: &str,
+    baseline_path: &str,
+    other_path: Option<&str>,
+    trusted_key: Option<&str>,
+    json_output: bool,
+    check: bool,
+) -> McpResult<()> {
+    let baseline = read_snapshot(baseline_path).await?;
+    let signer = baseline.verify(trusted_key)?;
+    eprintln!("✓ {} is signed by {}", baseline_path, signer);
+
+    let other = match other_path {
+        Some(path) => {
+            let other = read_snapshot(path).await?;
+            let signer = other.verify(trusted_key)?;
+            eprintln!("✓ {} is signed by {}", path, signer);
+            other
+        }
+        None => take_snapshot(config_path).await?,
+    };
+
+    let diff = diff(&baseline, &other);
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else if diff.is_empty() {
+        println!("No differences");
+    } else {
+        print_diff(&diff, &baseline, &other);
+    }
+
+    if check && !diff.is_empty() {
+        return Err(McpError::InvalidRequest("Capabilities differ from the baseline".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tools: &[(&str, Value)]) -> CapabilitySnapshot {
+        let server = ServerSnapshot {
+            tools: tools.iter().map(|(name, tool)| (name.to_string(), tool.clone())).collect(),
+            ..Default::default()
+        };
+        CapabilitySnapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            generator: "test".to_string(),
+            servers: BTreeMap::from([("fs".to_string(), server)]),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut snapshot = snapshot(&[("read", json!({ "name": "read", "description": "Read a file" }))]);
+        assert!(snapshot.verify(None).is_err());
+
+        snapshot.sign(&generate_key().unwrap()).unwrap();
+        let signer = snapshot.verify(None).unwrap();
+        assert!(snapshot.verify(Some(&signer)).is_ok());
+        assert!(snapshot.verify(Some("c29tZW9uZSBlbHNl")).is_err());
+
+        // Round-trips through JSON with its signature intact
+        let reparsed: CapabilitySnapshot =
+            serde_json::from_str(&serde_json::to_string_pretty(&snapshot).unwrap()).unwrap();
+        assert!(reparsed.verify(Some(&signer)).is_ok());
+
+        snapshot.servers.get_mut("fs").unwrap().tools.get_mut("read").unwrap()["description"] =
+            json!("Read any file");
+        assert!(snapshot.verify(None).is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let baseline = snapshot(&[
+            ("read", json!({ "name": "read", "description": "Read a file" })),
+            ("list", json!({ "name": "list", "inputSchema": { "type": "object" } })),
+        ]);
+        let mut other = snapshot(&[
+            ("read", json!({ "name": "read", "description": "Read any file" })),
+            ("delete", json!({ "name": "delete" })),
+        ]);
+        other.servers.insert(
+            "web".to_string(),
+            ServerSnapshot {
+                error: Some("connection refused".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let diff = diff(&baseline, &other);
+        assert_eq!(diff.servers_added, vec!["web"]);
+        assert!(diff.servers_removed.is_empty());
+        let summary: Vec<(&str, &ChangeKind)> =
+            diff.changes.iter().map(|c| (c.name.as_str(), &c.change)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("list", &ChangeKind::Removed),
+                ("read", &ChangeKind::Modified(vec!["description".to_string()])),
+                ("delete", &ChangeKind::Added),
+            ]
+        );
+        assert!(super::diff(&baseline, &baseline).is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! GitOps-style config management against a running server
+//!
+//! `config diff` compares a candidate config file with the config the
+//! server is running, fetched from the admin API. The server redacts its
+//! secrets, so the candidate's are redacted too and never printed. `config apply` sends the
+//! candidate to the server, which validates it, writes it over its config
+//! file and restarts changed servers, putting everything back if one fails
+//! to start.
+
+use crate::cli::expand_path;
+use crate::cli::sessions::admin_request;
+use crate::config::diff::{self, ChangeKind, ConfigChange};
+use crate::config::redact;
+use crate::config::{Config, ConfigManager, ConfigValidator};
+use crate::http_server::admin::{ConfigApplyResult, ConfigCandidate};
+use crate::utils::errors::{McpError, McpResult};
+use dialoguer::console::style;
+use serde_json::Value;
+
+fn admin_disabled() -> McpError {
+    McpError::ConfigError("The server does not expose the admin API; set [admin] enabled = true".to_string())
+}
+
+/// Config the server is running, with its secrets redacted
+async fn running_config(config_path: &str, url: Option<&str>, token: Option<&str>) -> McpResult<Value> {
+    let response = admin_request(config_path, url, token, reqwest::Method::GET, "config", None).await?;
+    if !response.status().is_success() {
+        return Err(admin_disabled());
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))
+}
+
+/// Load a candidate config file, failing if it does not validate
+async fn load_candidate(file: &str) -> McpResult<Config> {
+    let config = ConfigManager::new(expand_path(file)).await?.get_config();
+    ConfigValidator::new().validate_config(&config).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        McpError::InvalidRequest(format!("Invalid config: {}", errors.join("; ")))
+    })?;
+    Ok(config)
+}
+
+fn show(value: &Option<Value>) -> String {
+    value.as_ref().map_or_else(|| "-".to_string(), Value::to_string)
+}
+
+/// Print changes one per line, `+` added, `-` removed and `~` changed
+fn print_changes(changes: &[ConfigChange]) {
+    if changes.is_empty() {
+        println!("No changes");
+        return;
+    }
+    for change in changes {
+        match change.kind {
+            ChangeKind::Added => {
+                println!("{}", style(format!("+ {} = {}", change.path, show(&change.after))).green())
+            }
+            ChangeKind::Removed => println!("{}", style(format!("- {}", change.path)).red()),
+            ChangeKind::Changed => println!(
+                "{}",
+                style(format!(
+                    "~ {}: {} -> {}",
+                    change.path,
+                    show(&change.before),
+                    show(&change.after)
+                ))
+                .yellow()
+            ),
+        }
+    }
+}
+
+/// Show how a candidate config differs from the running one
+pub async fn diff(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    file: &str,
+    json_output: bool,
+) -> McpResult<()> {
+    let candidate = load_candidate(file).await?;
+    let running = running_config(config_path, url, token).await?;
+    let changes = diff::diff(&running, &redact::redact(&candidate)?);
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+    } else {
+        print_changes(&changes);
+    }
+    Ok(())
+}
+
+/// Replace the running server's config with a candidate file
+pub async fn apply(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    file: &str,
+    dry_run: bool,
+    json_output: bool,
+) -> McpResult<()> {
+    let content = tokio::fs::read_to_string(expand_path(file))
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read {}: {}", file, e)))?;
+    load_candidate(file).await?;
+
+    let body = serde_json::to_value(ConfigCandidate { content, dry_run })?;
+    let response = admin_request(config_path, url, token, reqwest::Method::PUT, "config", Some(&body)).await?;
+    if !response.status().is_success() {
+        return Err(admin_disabled());
+    }
+    let result: ConfigApplyResult = response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))?;
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    print_changes(&result.changes);
+    if result.applied {
+        println!("\n✓ Applied {} change(s)", result.changes.len());
+    } else if !result.changes.is_empty() {
+        println!("\nDry run - nothing was applied");
+    }
+    if !result.restart_required.is_empty() {
+        println!(
+            "Changes to {} take effect once the server restarts",
+            result.restart_required.join(", ")
+        );
+    }
+    Ok(())
+}
@@ -14,10 +14,12 @@
 //! - Windsurf (codeium.com/windsurf)
 //! - OpenCode
 //! - Continue.dev
+//! - JetBrains AI Assistant (IntelliJ IDEA, PyCharm, ...)
+//! - Zed (zed.dev)
 //!
 
 use crate::cli::{ensure_config_dir, expand_path};
-use crate::config::{Config, McpServerConfig, SandboxConfig};
+use crate::config::{Config, McpServerConfig, RemoteTransport, SandboxConfig};
 use crate::utils::errors::{McpError, McpResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -42,12 +44,67 @@ pub struct DiscoveredMcp {
     pub source_path: PathBuf,
     /// Whether auto-approve is enabled (if available)
     pub auto_approve: Option<bool>,
+    /// Remote endpoint, for servers reached over HTTP instead of a command
+    pub remote: Option<RemoteEndpoint>,
+}
+
+/// HTTP/SSE endpoint of a discovered remote server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RemoteEndpoint {
+    /// Endpoint URL
+    pub url: String,
+    /// Transport declared by the source config
+    pub transport: RemoteTransport,
+    /// HTTP headers, e.g. `Authorization`
+    pub headers: HashMap<String, String>,
+}
+
+impl RemoteEndpoint {
+    /// Build from an entry's `url`, `type`/`transport` and `headers` fields
+    fn from_entry(server: &serde_json::Map<String, serde_json::Value>) -> Option<Self> {
+        let url = server
+            .get("url")
+            .or_else(|| server.get("serverUrl"))
+            .and_then(|u| u.as_str())?;
+        let kind = server
+            .get("type")
+            .or_else(|| server.get("transport"))
+            .and_then(|t| t.as_str());
+        Some(Self {
+            url: url.to_string(),
+            transport: remote_transport(kind),
+            headers: string_map(server.get("headers")),
+        })
+    }
+}
+
+/// Map an editor's transport name onto ours
+fn remote_transport(kind: Option<&str>) -> RemoteTransport {
+    match kind.map(|k| k.to_ascii_lowercase()) {
+        Some(k) if k == "sse" => RemoteTransport::Sse,
+        Some(k) if k == "http" || k == "streamable-http" || k == "streamablehttp" || k == "streamable_http" => {
+            RemoteTransport::StreamableHttp
+        }
+        _ => RemoteTransport::Auto,
+    }
+}
+
+/// String entries of a JSON object, dropping non-string values
+fn string_map(value: Option<&serde_json::Value>) -> HashMap<String, String> {
+    value
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl DiscoveredMcp {
     /// Convert to McpServerConfig
     pub fn to_config(&self) -> McpServerConfig {
-        McpServerConfig {
+        let mut config = McpServerConfig {
             name: self.name.clone(),
             command: self.command.clone(),
             args: self.args.clone(),
@@ -57,7 +114,14 @@ impl DiscoveredMcp {
                 Some(format!("Imported from {}", self.source))
             }),
             sandbox: SandboxConfig::default(),
+            ..Default::default()
+        };
+        if let Some(remote) = &self.remote {
+            config.url = Some(remote.url.clone());
+            config.transport = remote.transport;
+            config.headers = remote.headers.clone();
         }
+        config
     }
 }
 
@@ -70,6 +134,7 @@ struct CursorMcpConfig {
 
 #[derive(Debug, Deserialize)]
 struct CursorMcpServer {
+    #[serde(default)]
     command: String,
     #[serde(default)]
     args: Vec<String>,
@@ -78,6 +143,9 @@ struct CursorMcpServer {
     #[serde(default)]
     #[serde(rename = "autoApprove")]
     auto_approve: Option<bool>,
+    /// Everything else, for `url`/`type`/`headers` of remote entries
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Claude Desktop MCP configuration
@@ -89,11 +157,15 @@ struct ClaudeDesktopConfig {
 
 #[derive(Debug, Deserialize)]
 struct ClaudeMcpServer {
+    #[serde(default)]
     command: String,
     #[serde(default)]
     args: Vec<String>,
     #[serde(default)]
     env: HashMap<String, serde_json::Value>,
+    /// Everything else, for `url`/`type`/`headers` of remote entries
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// VS Code/Continue/Cline/Kilo/Roo format
@@ -137,6 +209,10 @@ pub async fn discover_cursor() -> McpResult<Vec<DiscoveredMcp>> {
             .map_err(|e| McpError::ConfigError(format!("Failed to parse Cursor config: {}", e)))?;
 
         for (name, server) in config.mcp_servers {
+            let remote = RemoteEndpoint::from_entry(&server.extra);
+            if server.command.is_empty() && remote.is_none() {
+                continue;
+            }
             results.push(DiscoveredMcp {
                 source: "cursor".to_string(),
                 name: sanitize_name(&name),
@@ -146,6 +222,7 @@ pub async fn discover_cursor() -> McpResult<Vec<DiscoveredMcp>> {
                 description: None,
                 source_path: path.clone(),
                 auto_approve: server.auto_approve,
+                remote,
             });
         }
     }
@@ -176,6 +253,10 @@ pub async fn discover_claude() -> McpResult<Vec<DiscoveredMcp>> {
             .map_err(|e| McpError::ConfigError(format!("Failed to parse Claude config: {}", e)))?;
 
         for (name, server) in config.mcp_servers {
+            let remote = RemoteEndpoint::from_entry(&server.extra);
+            if server.command.is_empty() && remote.is_none() {
+                continue;
+            }
             // Claude uses a different env format - convert it
             let env = server
                 .env
@@ -198,6 +279,7 @@ pub async fn discover_claude() -> McpResult<Vec<DiscoveredMcp>> {
                 description: None,
                 source_path: path.clone(),
                 auto_approve: None,
+                remote,
             });
         }
     }
@@ -241,45 +323,38 @@ pub async fn discover_vscode_extensions() -> McpResult<Vec<DiscoveredMcp>> {
         for (ext_name, key) in extensions {
             if let Some(servers) = settings.get(key).and_then(|v| v.as_array()) {
                 for server in servers {
-                    if let (Some(name), Some(command)) = (
-                        server.get("name").and_then(|n| n.as_str()),
-                        server.get("command").and_then(|c| c.as_str()),
-                    ) {
-                        let args: Vec<String> = server
-                            .get("args")
-                            .and_then(|a| a.as_array())
-                            .map(|arr| {
-                                arr.iter()
-                                    .filter_map(|v| v.as_str().map(String::from))
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-
-                        let env: HashMap<String, String> = server
-                            .get("env")
-                            .and_then(|e| e.as_object())
-                            .map(|obj| {
-                                obj.iter()
-                                    .filter_map(|(k, v)| {
-                                        v.as_str().map(|s| (k.clone(), s.to_string()))
-                                    })
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-
-                        results.push(DiscoveredMcp {
-                            source: format!("{}-{}", editor, ext_name),
-                            name: sanitize_name(name),
-                            command: command.to_string(),
-                            args,
-                            env,
-                            description: None,
-                            source_path: path.clone(),
-                            auto_approve: server
-                                .get("autoApprove")
-                                .and_then(|a| a.as_bool()),
-                        });
+                    let Some(name) = server.get("name").and_then(|n| n.as_str()) else {
+                        continue;
+                    };
+                    let remote = server.as_object().and_then(RemoteEndpoint::from_entry);
+                    let command = server.get("command").and_then(|c| c.as_str());
+                    if command.is_none() && remote.is_none() {
+                        continue;
                     }
+
+                    let args: Vec<String> = server
+                        .get("args")
+                        .and_then(|a| a.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(String::from))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    results.push(DiscoveredMcp {
+                        source: format!("{}-{}", editor, ext_name),
+                        name: sanitize_name(name),
+                        command: command.unwrap_or_default().to_string(),
+                        args,
+                        env: string_map(server.get("env")),
+                        description: None,
+                        source_path: path.clone(),
+                        auto_approve: server
+                            .get("autoApprove")
+                            .and_then(|a| a.as_bool()),
+                        remote,
+                    });
                 }
             }
         }
@@ -306,6 +381,10 @@ pub async fn discover_codex() -> McpResult<Vec<DiscoveredMcp>> {
         // Codex uses a similar format to Claude
         if let Ok(config) = serde_json::from_str::<ClaudeDesktopConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 let env = server
                     .env
                     .into_iter()
@@ -324,6 +403,7 @@ pub async fn discover_codex() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: None,
+                    remote,
                 });
             }
         }
@@ -353,6 +433,10 @@ pub async fn discover_kimi_cli() -> McpResult<Vec<DiscoveredMcp>> {
         // Format 1: Similar to Claude
         if let Ok(config) = serde_json::from_str::<ClaudeDesktopConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 let env = server
                     .env
                     .into_iter()
@@ -371,6 +455,7 @@ pub async fn discover_kimi_cli() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: None,
+                    remote,
                 });
             }
         }
@@ -399,6 +484,10 @@ pub async fn discover_windsurf() -> McpResult<Vec<DiscoveredMcp>> {
         // Try Cursor format first
         if let Ok(config) = serde_json::from_str::<CursorMcpConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 results.push(DiscoveredMcp {
                     source: "windsurf".to_string(),
                     name: sanitize_name(&name),
@@ -408,6 +497,7 @@ pub async fn discover_windsurf() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: server.auto_approve,
+                    remote,
                 });
             }
         }
@@ -435,6 +525,10 @@ pub async fn discover_gemini() -> McpResult<Vec<DiscoveredMcp>> {
         
         if let Ok(config) = serde_json::from_str::<ClaudeDesktopConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 let env = server
                     .env
                     .into_iter()
@@ -453,6 +547,7 @@ pub async fn discover_gemini() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: None,
+                    remote,
                 });
             }
         }
@@ -480,6 +575,10 @@ pub async fn discover_qwen() -> McpResult<Vec<DiscoveredMcp>> {
         
         if let Ok(config) = serde_json::from_str::<ClaudeDesktopConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 let env = server
                     .env
                     .into_iter()
@@ -498,6 +597,7 @@ pub async fn discover_qwen() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: None,
+                    remote,
                 });
             }
         }
@@ -525,6 +625,10 @@ pub async fn discover_github_copilot() -> McpResult<Vec<DiscoveredMcp>> {
         
         if let Ok(config) = serde_json::from_str::<CursorMcpConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 results.push(DiscoveredMcp {
                     source: "github-copilot".to_string(),
                     name: sanitize_name(&name),
@@ -534,6 +638,7 @@ pub async fn discover_github_copilot() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: server.auto_approve,
+                    remote,
                 });
             }
         }
@@ -560,6 +665,10 @@ pub async fn discover_opencode() -> McpResult<Vec<DiscoveredMcp>> {
         
         if let Ok(config) = serde_json::from_str::<ClaudeDesktopConfig>(&content) {
             for (name, server) in config.mcp_servers {
+                let remote = RemoteEndpoint::from_entry(&server.extra);
+                if server.command.is_empty() && remote.is_none() {
+                    continue;
+                }
                 let env = server
                     .env
                     .into_iter()
@@ -578,6 +687,7 @@ pub async fn discover_opencode() -> McpResult<Vec<DiscoveredMcp>> {
                     description: None,
                     source_path: path.clone(),
                     auto_approve: None,
+                    remote,
                 });
             }
         }
@@ -586,6 +696,349 @@ pub async fn discover_opencode() -> McpResult<Vec<DiscoveredMcp>> {
     Ok(results)
 }
 
+/// Discover MCP servers from JetBrains AI Assistant
+///
+/// Every IDE keeps its own `options/llm.mcpServers.xml` under the
+/// JetBrains config dir, so all products and versions are scanned.
+pub async fn discover_jetbrains() -> McpResult<Vec<DiscoveredMcp>> {
+    let Some(root) = dirs::config_dir().map(|d| d.join("JetBrains")) else {
+        return Ok(Vec::new());
+    };
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut product_dirs = Vec::new();
+    let mut entries = tokio::fs::read_dir(&root).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        product_dirs.push(entry.path());
+    }
+    product_dirs.sort();
+
+    let mut results = Vec::new();
+
+    for dir in product_dirs {
+        let path = dir.join("options/llm.mcpServers.xml");
+        if !path.exists() {
+            continue;
+        }
+
+        let content = tokio::fs::read_to_string(&path).await?;
+        for server in parse_jetbrains_mcp_servers(&content) {
+            if !server.enabled {
+                continue;
+            }
+            results.push(DiscoveredMcp {
+                source: "jetbrains".to_string(),
+                name: sanitize_name(&server.name),
+                command: server.command,
+                args: server.args,
+                env: server.env,
+                description: None,
+                source_path: path.clone(),
+                auto_approve: None,
+                remote: None,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Discover MCP servers from Zed's `context_servers` setting
+pub async fn discover_zed() -> McpResult<Vec<DiscoveredMcp>> {
+    let mut paths = vec![
+        dirs::home_dir().map(|d| d.join(".config/zed/settings.json")),
+        dirs::config_dir().map(|d| d.join("zed/settings.json")),
+    ];
+    paths.dedup();
+
+    let mut results = Vec::new();
+
+    for path in paths.into_iter().flatten() {
+        if !path.exists() {
+            continue;
+        }
+
+        let content = tokio::fs::read_to_string(&path).await?;
+        let settings: serde_json::Value = serde_json::from_str(&strip_jsonc(&content))
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse Zed settings: {}", e)))?;
+
+        results.extend(parse_zed_context_servers(&settings, &path));
+    }
+
+    Ok(results)
+}
+
+/// Server entry from JetBrains' `McpApplicationServerCommands` component
+#[derive(Debug, Default, PartialEq)]
+struct JetbrainsMcpServer {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    enabled: bool,
+}
+
+/// Parse JetBrains' IDE settings XML for MCP servers
+///
+/// The file is a flat list of `<McpServerCommand>` elements made of
+/// `<option name=".." value=".."/>` fields; `args` is a `<list>` of bare
+/// options and `envs` a `<map>` of `<entry key=".." value=".."/>`.
+fn parse_jetbrains_mcp_servers(xml: &str) -> Vec<JetbrainsMcpServer> {
+    let mut servers = Vec::new();
+    let mut current: Option<JetbrainsMcpServer> = None;
+    let mut section: Option<String> = None;
+
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let tag = tag.trim_end_matches('/').trim();
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+
+        match tag_name {
+            "McpServerCommand" => {
+                current = Some(JetbrainsMcpServer {
+                    enabled: true,
+                    ..Default::default()
+                });
+            }
+            "/McpServerCommand" => {
+                if let Some(server) = current.take() {
+                    if !server.command.is_empty() {
+                        servers.push(server);
+                    }
+                }
+                section = None;
+            }
+            "/option" => section = None,
+            "option" => {
+                let Some(server) = current.as_mut() else {
+                    continue;
+                };
+                let name = xml_attr(tag, "name");
+                let value = xml_attr(tag, "value");
+                match (name.as_deref(), value) {
+                    (Some("name"), Some(value)) => server.name = value,
+                    (Some("command"), Some(value)) => server.command = value,
+                    (Some("enabled"), Some(value)) => server.enabled = value != "false",
+                    (Some(name), None) => section = Some(name.to_string()),
+                    (None, Some(value)) if section.as_deref() == Some("args") => {
+                        server.args.push(value)
+                    }
+                    _ => {}
+                }
+            }
+            "entry" if section.as_deref() == Some("envs") => {
+                if let (Some(server), Some(key)) = (current.as_mut(), xml_attr(tag, "key")) {
+                    server.env.insert(key, xml_attr(tag, "value").unwrap_or_default());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    servers
+}
+
+/// Value of a double-quoted attribute, with XML entities decoded
+fn xml_attr(tag: &str, attr: &str) -> Option<String> {
+    let needle = format!("{}=\"", attr);
+    let mut search = tag;
+    loop {
+        let pos = search.find(&needle)?;
+        // Don't match `key=` inside `somekey=`
+        let boundary = pos == 0 || search.as_bytes()[pos - 1].is_ascii_whitespace();
+        let after = &search[pos + needle.len()..];
+        if boundary {
+            let end = after.find('"')?;
+            return Some(
+                after[..end]
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&amp;", "&"),
+            );
+        }
+        search = after;
+    }
+}
+
+/// Servers from Zed settings
+///
+/// Zed has used both `{"command": "..", "args": [..], "env": {..}}` and the
+/// older `{"command": {"path": "..", "args": [..], "env": {..}}}` for local
+/// servers, and `{"url": "..", "headers": {..}}` for remote ones. Servers
+/// provided by Zed extensions carry neither and are skipped.
+fn parse_zed_context_servers(settings: &serde_json::Value, path: &std::path::Path) -> Vec<DiscoveredMcp> {
+    let Some(servers) = settings.get("context_servers").and_then(|v| v.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut results = Vec::new();
+    for (name, server) in servers {
+        let remote = server.as_object().and_then(RemoteEndpoint::from_entry);
+        let (command, spec) = match server.get("command") {
+            Some(serde_json::Value::String(command)) => (command.clone(), server),
+            Some(spec @ serde_json::Value::Object(_)) => {
+                match spec.get("path").and_then(|p| p.as_str()) {
+                    Some(path) => (path.to_string(), spec),
+                    None => continue,
+                }
+            }
+            _ if remote.is_some() => (String::new(), server),
+            _ => continue,
+        };
+
+        let args = spec
+            .get("args")
+            .and_then(|a| a.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+
+        results.push(DiscoveredMcp {
+            source: "zed".to_string(),
+            name: sanitize_name(name),
+            command,
+            args,
+            env: string_map(spec.get("env")),
+            description: None,
+            source_path: path.to_path_buf(),
+            auto_approve: None,
+            remote,
+        });
+    }
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    results
+}
+
+/// Drop `//` and `/* */` comments and trailing commas from JSONC
+fn strip_jsonc(content: &str) -> String {
+    let mut without_comments = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            without_comments.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        without_comments.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        without_comments.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => {
+                in_string = c == '"';
+                without_comments.push(c);
+            }
+        }
+    }
+
+    // Second pass: a comma is trailing if the next token closes a container
+    let mut out = String::with_capacity(without_comments.len());
+    let mut pending_comma = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in without_comments.chars() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        if c == ',' {
+            pending_comma = Some(out.len());
+            out.push(c);
+        } else if c.is_whitespace() {
+            out.push(c);
+        } else {
+            if let Some(pos) = pending_comma.take() {
+                if c == '}' || c == ']' {
+                    out.remove(pos);
+                }
+            }
+            in_string = c == '"';
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Source names accepted by `discover_source`, as on the `import` command line
+pub const IMPORT_SOURCES: &[&str] = &[
+    "all",
+    "cursor",
+    "claude",
+    "vscode",
+    "codex",
+    "kimi-cli",
+    "windsurf",
+    "opencode",
+    "gemini",
+    "qwen",
+    "github-copilot",
+    "jetbrains",
+    "zed",
+];
+
+/// Discover MCP servers from one source by name
+pub async fn discover_source(source: &str) -> McpResult<Vec<DiscoveredMcp>> {
+    match source {
+        "all" => discover_all().await,
+        "cursor" => discover_cursor().await,
+        "claude" => discover_claude().await,
+        "vscode" => discover_vscode_extensions().await,
+        "codex" => discover_codex().await,
+        "kimi-cli" => discover_kimi_cli().await,
+        "windsurf" => discover_windsurf().await,
+        "opencode" => discover_opencode().await,
+        "gemini" => discover_gemini().await,
+        "qwen" => discover_qwen().await,
+        "github-copilot" => discover_github_copilot().await,
+        "jetbrains" => discover_jetbrains().await,
+        "zed" => discover_zed().await,
+        other => Err(McpError::ConfigError(format!("Unknown import source: {}", other))),
+    }
+}
+
 /// Discover all MCP servers from all sources
 pub async fn discover_all() -> McpResult<Vec<DiscoveredMcp>> {
     let mut all = Vec::new();
@@ -602,6 +1055,8 @@ pub async fn discover_all() -> McpResult<Vec<DiscoveredMcp>> {
         discover_gemini(),
         discover_qwen(),
         discover_github_copilot(),
+        discover_jetbrains(),
+        discover_zed(),
     );
 
     if let Ok(servers) = discoveries.0 {
@@ -634,6 +1089,12 @@ pub async fn discover_all() -> McpResult<Vec<DiscoveredMcp>> {
     if let Ok(servers) = discoveries.9 {
         all.extend(servers);
     }
+    if let Ok(servers) = discoveries.10 {
+        all.extend(servers);
+    }
+    if let Ok(servers) = discoveries.11 {
+        all.extend(servers);
+    }
 
     // Deduplicate by name (prefer first found)
     let mut seen = std::collections::HashSet::new();
@@ -724,4 +1185,119 @@ mod tests {
         assert_eq!(sanitize_name("server@123"), "server_123");
         assert_eq!(sanitize_name("Valid-Name_123"), "valid-name_123");
     }
+
+    #[test]
+    fn test_parse_jetbrains_mcp_servers() {
+        let xml = r#"<application>
+  <component name="McpApplicationServerCommands">
+    <commands>
+      <McpServerCommand>
+        <option name="args">
+          <list>
+            <option value="-y" />
+            <option value="@modelcontextprotocol/server-filesystem" />
+          </list>
+        </option>
+        <option name="command" value="npx" />
+        <option name="envs">
+          <map>
+            <entry key="TOKEN" value="a&amp;b" />
+          </map>
+        </option>
+        <option name="name" value="filesystem" />
+      </McpServerCommand>
+      <McpServerCommand>
+        <option name="command" value="uvx" />
+        <option name="enabled" value="false" />
+        <option name="name" value="off" />
+      </McpServerCommand>
+    </commands>
+  </component>
+</application>"#;
+
+        let servers = parse_jetbrains_mcp_servers(xml);
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "filesystem");
+        assert_eq!(servers[0].command, "npx");
+        assert_eq!(servers[0].args, vec!["-y", "@modelcontextprotocol/server-filesystem"]);
+        assert_eq!(servers[0].env.get("TOKEN").map(String::as_str), Some("a&b"));
+        assert!(servers[0].enabled);
+        assert!(!servers[1].enabled);
+    }
+
+    #[test]
+    fn test_parse_zed_context_servers() {
+        let content = r#"{
+  // Zed settings
+  "theme": "One Dark",
+  "context_servers": {
+    "github": {
+      "source": "custom",
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "env": { "GITHUB_TOKEN": "x" },
+    },
+    "legacy": {
+      "command": { "path": "/usr/bin/mcp", "args": ["--stdio"], "env": null }
+    },
+    /* provided by an extension */
+    "postgres": { "source": "extension", "settings": {} },
+    "remote": { "url": "https://mcp.example.com/mcp", "headers": { "Authorization": "Bearer t" } },
+  },
+}"#;
+        let settings: serde_json::Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        let servers = parse_zed_context_servers(&settings, std::path::Path::new("settings.json"));
+
+        assert_eq!(servers.len(), 3);
+        assert_eq!(servers[0].name, "github");
+        assert_eq!(servers[0].command, "npx");
+        assert_eq!(servers[0].env.get("GITHUB_TOKEN").map(String::as_str), Some("x"));
+        assert_eq!(servers[1].name, "legacy");
+        assert_eq!(servers[1].command, "/usr/bin/mcp");
+        assert_eq!(servers[1].args, vec!["--stdio"]);
+        let remote = servers[2].remote.as_ref().unwrap();
+        assert_eq!(remote.url, "https://mcp.example.com/mcp");
+        assert_eq!(remote.headers.get("Authorization").map(String::as_str), Some("Bearer t"));
+    }
+
+    #[test]
+    fn test_remote_entries_survive_import() {
+        let content = r#"{"mcpServers": {
+            "local": {"command": "uvx", "args": ["mcp-server-time"]},
+            "linear": {"type": "sse", "url": "https://mcp.linear.app/sse", "headers": {"X-Key": "k"}},
+            "broken": {"args": []}
+        }}"#;
+        let config: ClaudeDesktopConfig = serde_json::from_str(content).unwrap();
+        let linear = &config.mcp_servers["linear"];
+        assert!(linear.command.is_empty());
+
+        let remote = RemoteEndpoint::from_entry(&linear.extra).unwrap();
+        assert_eq!(remote.transport, RemoteTransport::Sse);
+        assert!(RemoteEndpoint::from_entry(&config.mcp_servers["broken"].extra).is_none());
+        assert!(RemoteEndpoint::from_entry(&config.mcp_servers["local"].extra).is_none());
+
+        let discovered = DiscoveredMcp {
+            source: "claude".to_string(),
+            name: "linear".to_string(),
+            command: String::new(),
+            args: vec![],
+            env: HashMap::new(),
+            description: None,
+            source_path: PathBuf::from("claude.json"),
+            auto_approve: None,
+            remote: Some(remote),
+        };
+        let server = discovered.to_config();
+        assert_eq!(server.url.as_deref(), Some("https://mcp.linear.app/sse"));
+        assert_eq!(server.transport, RemoteTransport::Sse);
+        assert_eq!(server.headers.get("X-Key").map(String::as_str), Some("k"));
+    }
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings() {
+        let stripped = strip_jsonc(r#"{"url": "http://x//y", "a": [1, 2,], /* c */ "b": "/*,}"}"#);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["url"], "http://x//y");
+        assert_eq!(value["b"], "/*,}");
+    }
 }
@@ -0,0 +1,122 @@
+//! Exit codes for CI
+//!
+//! `validate`, `preset test`, `runtime validate` and `import` (including
+//! `--dry-run`) exit with a code per failure class, so a CI job can gate on
+//! them without parsing output:
+//!
+//! | Code | Status      | Meaning                                          |
+//! |------|-------------|--------------------------------------------------|
+//! | 0    | `ok`        | Everything checked out                           |
+//! | 1    | `error`     | Unexpected failure, such as an I/O error         |
+//! | 2    | `usage`     | Bad command-line arguments                       |
+//! | 3    | `config`    | Config file missing, unreadable or not parseable |
+//! | 4    | `invalid`   | Something failed validation                      |
+//! | 5    | `not-found` | The named preset does not exist                  |
+//! | 6    | `empty`     | Nothing to check, match or import                |
+//!
+//! With `--quiet` they print one JSON line, `{"status", "exitCode", ...}`,
+//! and nothing else.
+
+use crate::utils::errors::McpError;
+use serde_json::{json, Value};
+
+/// Outcome of a command, by failure class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Ok,
+    Error,
+    Usage,
+    Config,
+    Invalid,
+    NotFound,
+    Empty,
+}
+
+impl ExitStatus {
+    /// Process exit code
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Ok => 0,
+            ExitStatus::Error => 1,
+            ExitStatus::Usage => 2,
+            ExitStatus::Config => 3,
+            ExitStatus::Invalid => 4,
+            ExitStatus::NotFound => 5,
+            ExitStatus::Empty => 6,
+        }
+    }
+
+    /// Name used in `--quiet` output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExitStatus::Ok => "ok",
+            ExitStatus::Error => "error",
+            ExitStatus::Usage => "usage",
+            ExitStatus::Config => "config",
+            ExitStatus::Invalid => "invalid",
+            ExitStatus::NotFound => "not-found",
+            ExitStatus::Empty => "empty",
+        }
+    }
+
+    /// Failure class of an error a command returned
+    pub fn from_error(error: &McpError) -> Self {
+        match error {
+            McpError::ConfigError(_) => ExitStatus::Config,
+            McpError::InvalidRequest(_) => ExitStatus::Usage,
+            _ => ExitStatus::Error,
+        }
+    }
+}
+
+/// The `--quiet` result line: `fields` plus `status` and `exitCode`
+pub fn result_line(status: ExitStatus, fields: Value) -> String {
+    let mut line = json!({ "status": status.as_str(), "exitCode": status.code() });
+    if let (Some(line), Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
+    }
+    line.to_string()
+}
+
+/// Print the `--quiet` result line
+pub fn print_result(status: ExitStatus, fields: Value) {
+    println!("{}", result_line(status, fields));
+}
+
+/// Exit with the code of a command's outcome, reporting any error
+///
+/// Returns only when the command succeeded.
+pub fn finish(result: Result<ExitStatus, McpError>, quiet: bool) {
+    let status = match result {
+        Ok(status) => status,
+        Err(e) => {
+            let status = ExitStatus::from_error(&e);
+            if quiet {
+                print_result(status, json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("Error: {}", e);
+            }
+            status
+        }
+    };
+    if status != ExitStatus::Ok {
+        std::process::exit(status.code());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_line() {
+        let line = result_line(ExitStatus::NotFound, json!({ "preset": "dev" }));
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value, json!({ "status": "not-found", "exitCode": 5, "preset": "dev" }));
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            ExitStatus::from_error(&McpError::ConfigError("bad".to_string())),
+            ExitStatus::Config
+        );
+    }
+}
@@ -0,0 +1,561 @@
+//! Container files for a configured fleet
+//!
+//! `supermcp generate dockerfile` emits a Dockerfile with the config
+//! embedded and the runtimes its servers launch with (node, uv, ...)
+//! installed; `supermcp generate compose` emits a matching compose.yaml.
+//! `supermcp generate k8s` emits Kubernetes manifests, or Helm values, for
+//! running that image. `supermcp generate federation-key` emits a key
+//! pair for encrypted federation.
+
+use crate::cli::expand_path;
+use crate::cli::skill::load_config;
+use crate::config::{Config, DetectedRunner, ServerType};
+use crate::transport::envelope::FederationKey;
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Where the config lives in the image
+const CONFIG_DIR: &str = "/etc/supermcp";
+
+/// Heredoc delimiter of the embedded config
+const CONFIG_DELIMITER: &str = "SUPERMCP_CONFIG";
+
+/// Runtime an upstream server needs in the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Runtime {
+    Node,
+    Pnpm,
+    Bun,
+    Deno,
+    Uv,
+    Python,
+    Pipx,
+    Go,
+    Cargo,
+    Ruby,
+    Php,
+}
+
+impl Runtime {
+    /// Dockerfile lines installing the runtime
+    fn install(&self) -> &'static str {
+        match self {
+            Runtime::Node => "COPY --from=node:22-bookworm-slim /usr/local/ /usr/local/",
+            Runtime::Pnpm => "RUN corepack enable pnpm",
+            Runtime::Bun => {
+                "COPY --from=oven/bun:1 /usr/local/bin/bun /usr/local/bin/bun\n\
+                 RUN ln -s /usr/local/bin/bun /usr/local/bin/bunx"
+            }
+            Runtime::Deno => "COPY --from=denoland/deno:bin /deno /usr/local/bin/deno",
+            Runtime::Uv => "COPY --from=ghcr.io/astral-sh/uv:latest /uv /uvx /usr/local/bin/",
+            Runtime::Python => "RUN apt-get update && apt-get install -y --no-install-recommends python3 python3-pip && rm -rf /var/lib/apt/lists/*",
+            Runtime::Pipx => "RUN apt-get update && apt-get install -y --no-install-recommends pipx && rm -rf /var/lib/apt/lists/*",
+            Runtime::Go => {
+                "COPY --from=golang:1-bookworm /usr/local/go /usr/local/go\n\
+                 ENV PATH=\"/usr/local/go/bin:${PATH}\""
+            }
+            Runtime::Cargo => "RUN apt-get update && apt-get install -y --no-install-recommends cargo && rm -rf /var/lib/apt/lists/*",
+            Runtime::Ruby => "RUN apt-get update && apt-get install -y --no-install-recommends ruby-bundler && rm -rf /var/lib/apt/lists/*",
+            Runtime::Php => "RUN apt-get update && apt-get install -y --no-install-recommends php-cli composer && rm -rf /var/lib/apt/lists/*",
+        }
+    }
+}
+
+/// Runtimes the configured stdio servers need, and the servers whose
+/// commands must already be in the image
+pub fn required_runtimes(config: &Config) -> (BTreeSet<Runtime>, Vec<String>) {
+    let mut runtimes = BTreeSet::new();
+    let mut unknown = Vec::new();
+
+    for server in &config.servers {
+        if server.server_type != ServerType::Local
+            || server.url.is_some()
+            || server.named_pipe.is_some()
+        {
+            continue;
+        }
+        let needed: &[Runtime] = match server.detected_runner() {
+            DetectedRunner::Npx | DetectedRunner::Npm => &[Runtime::Node],
+            DetectedRunner::Pnpm | DetectedRunner::Pnpx => &[Runtime::Node, Runtime::Pnpm],
+            DetectedRunner::Bunx => &[Runtime::Bun],
+            DetectedRunner::DenoRun => &[Runtime::Deno],
+            DetectedRunner::Uvx => &[Runtime::Uv],
+            DetectedRunner::Pipx => &[Runtime::Pipx],
+            DetectedRunner::GoRun => &[Runtime::Go],
+            DetectedRunner::CargoRun => &[Runtime::Cargo],
+            DetectedRunner::BundleExec => &[Runtime::Ruby],
+            DetectedRunner::ComposerExec => &[Runtime::Php],
+            DetectedRunner::Local => {
+                let exe = Path::new(&server.command)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default()
+                    .to_lowercase();
+                match exe.as_str() {
+                    "node" => &[Runtime::Node],
+                    "uv" => &[Runtime::Uv],
+                    "python" | "python3" => &[Runtime::Python],
+                    _ => {
+                        unknown.push(server.name.clone());
+                        &[]
+                    }
+                }
+            }
+        };
+        runtimes.extend(needed.iter().copied());
+    }
+
+    (runtimes, unknown)
+}
+
+/// `${VAR}` references in the config, passed through from the host
+fn referenced_env_vars(content: &str) -> BTreeSet<String> {
+    content
+        .split("${")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name.split(":-").next().unwrap_or(name))
+        .filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Health check URL from inside the container
+fn health_url(config: &Config) -> String {
+    let server = &config.server;
+    let scheme = if server.cert_path.is_some() || server.acme.enabled || server.spiffe.enabled {
+        "https"
+    } else {
+        "http"
+    };
+    format!("{}://localhost:{}/health", scheme, server.port)
+}
+
+/// Dockerfile for `config`, whose file is named `config_name` and holds
+/// `content`
+pub fn dockerfile(config: &Config, content: &str, config_name: &str) -> McpResult<String> {
+    if content.lines().any(|line| line.trim() == CONFIG_DELIMITER) {
+        return Err(McpError::ConfigError(format!(
+            "Config contains a line reading {}, which ends the embedded copy",
+            CONFIG_DELIMITER
+        )));
+    }
+
+    let (runtimes, unknown) = required_runtimes(config);
+    let port = config.server.port;
+    let config_path = format!("{}/{}", CONFIG_DIR, config_name);
+
+    let mut out = String::new();
+    out.push_str("# syntax=docker/dockerfile:1\n");
+    out.push_str("# Generated by `supermcp generate dockerfile`\n\n");
+    out.push_str("FROM rust:1-slim-bookworm AS builder\n");
+    out.push_str("RUN apt-get update && apt-get install -y --no-install-recommends pkg-config libssl-dev git && rm -rf /var/lib/apt/lists/*\n");
+    out.push_str(&format!(
+        "RUN cargo install --locked --git {} --tag v{} supermcp\n\n",
+        env!("CARGO_PKG_REPOSITORY"),
+        env!("CARGO_PKG_VERSION")
+    ));
+    out.push_str("FROM debian:bookworm-slim\n");
+    out.push_str("RUN apt-get update && apt-get install -y --no-install-recommends ca-certificates curl libssl3 && rm -rf /var/lib/apt/lists/*\n");
+
+    if !runtimes.is_empty() {
+        out.push_str("\n# Runtimes the configured servers are launched with\n");
+        for runtime in &runtimes {
+            out.push_str(runtime.install());
+            out.push('\n');
+        }
+    }
+    if !unknown.is_empty() {
+        out.push_str(&format!(
+            "\n# Add the commands of these servers to the image: {}\n",
+            unknown.join(", ")
+        ));
+    }
+
+    out.push_str("\nCOPY --from=builder /usr/local/cargo/bin/supermcp /usr/local/bin/supermcp\n");
+    out.push_str("RUN useradd -r -m -d /home/supermcp supermcp\n\n");
+    out.push_str("# Config embedded at generation time; ${VAR} references resolve at runtime\n");
+    out.push_str(&format!("COPY --chown=supermcp <<'{}' {}\n", CONFIG_DELIMITER, config_path));
+    out.push_str(content);
+    if !content.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(CONFIG_DELIMITER);
+    out.push_str("\n\n");
+
+    out.push_str("USER supermcp\n");
+    out.push_str("ENV RUST_LOG=info\n");
+    out.push_str(&format!("EXPOSE {}\n", port));
+    out.push_str(&format!(
+        "HEALTHCHECK --interval=30s --timeout=5s --start-period=20s --retries=3 \\\n    CMD curl -fsSk {} || exit 1\n",
+        health_url(config)
+    ));
+    out.push_str("ENTRYPOINT [\"supermcp\"]\n");
+    out.push_str(&format!(
+        "CMD [\"serve\", \"--config\", \"{}\", \"--host\", \"0.0.0.0\", \"--port\", \"{}\"]\n",
+        config_path, port
+    ));
+    Ok(out)
+}
+
+/// compose.yaml building the generated Dockerfile
+pub fn compose(config: &Config, content: &str) -> String {
+    let port = config.server.port;
+
+    let mut out = String::new();
+    out.push_str("# Generated by `supermcp generate compose`; build with the Dockerfile from\n");
+    out.push_str("# `supermcp generate dockerfile`\n");
+    out.push_str("services:\n");
+    out.push_str("  supermcp:\n");
+    out.push_str("    build:\n");
+    out.push_str("      context: .\n");
+    out.push_str("      dockerfile: Dockerfile\n");
+    out.push_str("    ports:\n");
+    out.push_str(&format!("      - \"{}:{}\"\n", port, port));
+    out.push_str("    environment:\n");
+    out.push_str("      - RUST_LOG=info\n");
+    for name in referenced_env_vars(content) {
+        out.push_str(&format!("      - {}\n", name));
+    }
+    out.push_str("    restart: unless-stopped\n");
+    out.push_str("    healthcheck:\n");
+    out.push_str(&format!(
+        "      test: [\"CMD\", \"curl\", \"-fsSk\", \"{}\"]\n",
+        health_url(config)
+    ));
+    out.push_str("      interval: 30s\n");
+    out.push_str("      timeout: 5s\n");
+    out.push_str("      retries: 3\n");
+    out.push_str("      start_period: 20s\n");
+    out
+}
+
+/// How `supermcp generate k8s` deploys the fleet
+#[derive(Debug, Clone)]
+pub struct K8sOptions {
+    pub namespace: String,
+    /// Image built from the generated Dockerfile
+    pub image: String,
+    pub replicas: u32,
+    /// Scale between `replicas` and this many pods on CPU use
+    pub hpa_max_replicas: Option<u32>,
+    pub hpa_cpu_percent: u32,
+}
+
+const K8S_NAME: &str = "supermcp";
+
+fn resources() -> Value {
+    json!({
+        "requests": { "cpu": "100m", "memory": "128Mi" },
+        "limits": { "cpu": "1", "memory": "512Mi" },
+    })
+}
+
+fn probe(config: &Config, initial_delay: u32) -> Value {
+    let scheme = if health_url(config).starts_with("https") { "HTTPS" } else { "HTTP" };
+    json!({
+        "httpGet": { "path": "/health", "port": "http", "scheme": scheme },
+        "initialDelaySeconds": initial_delay,
+        "periodSeconds": 10,
+    })
+}
+
+/// Empty Secret entries for the `${VAR}`s the config references
+fn secret_env(content: &str) -> Map<String, Value> {
+    referenced_env_vars(content)
+        .into_iter()
+        .map(|name| (name, Value::String(String::new())))
+        .collect()
+}
+
+fn to_yaml(value: &Value) -> McpResult<String> {
+    serde_yaml::to_string(value)
+        .map_err(|e| McpError::InternalError(format!("Failed to serialize YAML: {}", e)))
+}
+
+/// Deployment, Service, ConfigMap and Secret manifests for `config`, plus
+/// a HorizontalPodAutoscaler when `hpa_max_replicas` is set
+pub fn k8s_manifests(
+    config: &Config,
+    content: &str,
+    config_name: &str,
+    options: &K8sOptions,
+) -> McpResult<String> {
+    let port = config.server.port;
+    let labels = json!({ "app.kubernetes.io/name": K8S_NAME });
+    let metadata = json!({ "name": K8S_NAME, "namespace": options.namespace, "labels": labels });
+    let secret_env = secret_env(content);
+
+    let mut manifests = vec![
+        json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": metadata,
+            "data": { config_name: content },
+        }),
+        // Values of the ${VAR}s the config references; fill in before applying
+        json!({
+            "apiVersion": "v1",
+            "kind": "Secret",
+            "metadata": metadata,
+            "type": "Opaque",
+            "stringData": secret_env,
+        }),
+        json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": metadata,
+            "spec": {
+                "replicas": options.replicas,
+                "selector": { "matchLabels": labels },
+                "template": {
+                    "metadata": { "labels": labels },
+                    "spec": {
+                        "containers": [{
+                            "name": K8S_NAME,
+                            "image": options.image,
+                            "args": [
+                                "serve", "--config", format!("{}/{}", CONFIG_DIR, config_name),
+                                "--host", "0.0.0.0", "--port", port.to_string(),
+                            ],
+                            "ports": [{ "name": "http", "containerPort": port }],
+                            "envFrom": [{ "secretRef": { "name": K8S_NAME } }],
+                            "livenessProbe": probe(config, 20),
+                            "readinessProbe": probe(config, 5),
+                            "resources": resources(),
+                            "volumeMounts": [{ "name": "config", "mountPath": CONFIG_DIR, "readOnly": true }],
+                        }],
+                        "volumes": [{ "name": "config", "configMap": { "name": K8S_NAME } }],
+                    },
+                },
+            },
+        }),
+        json!({
+            "apiVersion": "v1",
+            "kind": "Service",
+            "metadata": metadata,
+            "spec": {
+                "selector": labels,
+                "ports": [{ "name": "http", "port": port, "targetPort": "http" }],
+            },
+        }),
+    ];
+
+    if let Some(max) = options.hpa_max_replicas {
+        manifests.push(json!({
+            "apiVersion": "autoscaling/v2",
+            "kind": "HorizontalPodAutoscaler",
+            "metadata": metadata,
+            "spec": {
+                "scaleTargetRef": { "apiVersion": "apps/v1", "kind": "Deployment", "name": K8S_NAME },
+                "minReplicas": options.replicas,
+                "maxReplicas": max.max(options.replicas),
+                "metrics": [{
+                    "type": "Resource",
+                    "resource": {
+                        "name": "cpu",
+                        "target": { "type": "Utilization", "averageUtilization": options.hpa_cpu_percent },
+                    },
+                }],
+            },
+        }));
+    }
+
+    let mut out = String::from("# Generated by `supermcp generate k8s`\n");
+    for (i, manifest) in manifests.iter().enumerate() {
+        if i > 0 {
+            out.push_str("---\n");
+        }
+        out.push_str(&to_yaml(manifest)?);
+    }
+    Ok(out)
+}
+
+/// Helm values with the same settings as [`k8s_manifests`]
+pub fn helm_values(
+    config: &Config,
+    content: &str,
+    config_name: &str,
+    options: &K8sOptions,
+) -> McpResult<String> {
+    let (repository, tag) = match options.image.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+        _ => (options.image.as_str(), "latest"),
+    };
+    let values = json!({
+        "namespace": options.namespace,
+        "replicaCount": options.replicas,
+        "image": { "repository": repository, "tag": tag, "pullPolicy": "IfNotPresent" },
+        "service": { "type": "ClusterIP", "port": config.server.port },
+        "resources": resources(),
+        "livenessProbe": probe(config, 20),
+        "readinessProbe": probe(config, 5),
+        "autoscaling": {
+            "enabled": options.hpa_max_replicas.is_some(),
+            "minReplicas": options.replicas,
+            "maxReplicas": options.hpa_max_replicas.unwrap_or(options.replicas).max(options.replicas),
+            "targetCPUUtilizationPercentage": options.hpa_cpu_percent,
+        },
+        "config": { "fileName": config_name, "content": content },
+        "secretEnv": secret_env(content),
+    });
+    Ok(format!("# Generated by `supermcp generate k8s --helm`\n{}", to_yaml(&values)?))
+}
+
+/// Write generated text to `output`, or stdout
+async fn emit(text: &str, output: Option<&str>) -> McpResult<()> {
+    match output {
+        Some(path) => {
+            let path = expand_path(path);
+            tokio::fs::write(&path, text).await?;
+            eprintln!("✓ Wrote {}", path);
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+async fn read_config(config_path: &str) -> McpResult<(Config, String, String)> {
+    let path = expand_path(config_path);
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read config {}: {}", path, e)))?;
+    let config = load_config(config_path).await?;
+    let name = Path::new(&path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("config.toml")
+        .to_string();
+    Ok((config, content, name))
+}
+
+/// `supermcp generate dockerfile`
+pub async fn generate_dockerfile(config_path: &str, output: Option<&str>) -> McpResult<()> {
+    let (config, content, name) = read_config(config_path).await?;
+    emit(&dockerfile(&config, &content, &name)?, output).await
+}
+
+/// `supermcp generate compose`
+pub async fn generate_compose(config_path: &str, output: Option<&str>) -> McpResult<()> {
+    let (config, content, _) = read_config(config_path).await?;
+    emit(&compose(&config, &content), output).await
+}
+
+/// `supermcp generate k8s`
+pub async fn generate_k8s(
+    config_path: &str,
+    output: Option<&str>,
+    options: &K8sOptions,
+    helm: bool,
+) -> McpResult<()> {
+    let (config, content, name) = read_config(config_path).await?;
+    let text = if helm {
+        helm_values(&config, &content, &name, options)?
+    } else {
+        k8s_manifests(&config, &content, &name, options)?
+    };
+    emit(&text, output).await
+}
+
+/// `supermcp generate federation-key`
+pub async fn generate_federation_key(output: Option<&str>) -> McpResult<()> {
+    let key = FederationKey::generate()?;
+    let text = format!(
+        "# [federation] private_key of this instance; keep it secret\n\
+         private_key = \"{}\"\n\
+         # peer_public_key of servers federating to it, or public_key in its peers\n\
+         public_key = \"{}\"\n",
+        key.private_base64(),
+        key.public_base64()
+    );
+    emit(&text, output).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[server]
+port = 8080
+
+[[servers]]
+name = "github"
+command = "npx"
+args = ["-y", "@modelcontextprotocol/server-github"]
+env = { GITHUB_TOKEN = "${GITHUB_TOKEN}" }
+
+[[servers]]
+name = "fetch"
+command = "uvx"
+args = ["mcp-server-fetch"]
+
+[[servers]]
+name = "custom"
+command = "/opt/tools/custom-mcp"
+"#;
+
+    #[test]
+    fn test_dockerfile_installs_runtimes() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let out = dockerfile(&config, CONFIG, "config.toml").unwrap();
+
+        assert!(out.contains("COPY --from=node:22-bookworm-slim"));
+        assert!(out.contains("/uv /uvx /usr/local/bin/"));
+        assert!(!out.contains("oven/bun"));
+        assert!(out.contains("these servers to the image: custom"));
+        assert!(out.contains("name = \"fetch\""));
+        assert!(out.contains("http://localhost:8080/health"));
+        assert!(out.contains("\"--port\", \"8080\""));
+    }
+
+    #[test]
+    fn test_compose_passes_env_vars() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let out = compose(&config, CONFIG);
+
+        assert!(out.contains("\"8080:8080\""));
+        assert!(out.contains("      - GITHUB_TOKEN\n"));
+        assert_eq!(
+            referenced_env_vars("${A} ${B:-x} ${not valid} $C"),
+            BTreeSet::from(["A".to_string(), "B".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_k8s_manifests() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let options = K8sOptions {
+            namespace: "mcp".to_string(),
+            image: "registry.example.com/supermcp:1.2".to_string(),
+            replicas: 2,
+            hpa_max_replicas: Some(5),
+            hpa_cpu_percent: 70,
+        };
+        let out = k8s_manifests(&config, CONFIG, "config.toml", &options).unwrap();
+        let docs: Vec<Value> = out
+            .split("---\n")
+            .map(|doc| serde_yaml::from_str(doc).unwrap())
+            .collect();
+
+        let kinds: Vec<_> = docs.iter().map(|d| d["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["ConfigMap", "Secret", "Deployment", "Service", "HorizontalPodAutoscaler"]);
+        assert!(docs.iter().all(|d| d["metadata"]["namespace"] == "mcp"));
+        assert_eq!(docs[0]["data"]["config.toml"], CONFIG);
+        assert_eq!(docs[1]["stringData"]["GITHUB_TOKEN"], "");
+        let container = &docs[2]["spec"]["template"]["spec"]["containers"][0];
+        assert_eq!(container["readinessProbe"]["httpGet"]["path"], "/health");
+        assert_eq!(container["ports"][0]["containerPort"], 8080);
+        assert_eq!(docs[4]["spec"]["maxReplicas"], 5);
+
+        let values = helm_values(&config, CONFIG, "config.toml", &options).unwrap();
+        let values: Value = serde_yaml::from_str(&values).unwrap();
+        assert_eq!(values["image"]["repository"], "registry.example.com/supermcp");
+        assert_eq!(values["image"]["tag"], "1.2");
+        assert_eq!(values["autoscaling"]["enabled"], true);
+    }
+}
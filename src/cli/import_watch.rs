@@ -0,0 +1,197 @@
+//! Continuous import from editor configs
+//!
+//! With `[import.watch]` enabled, `serve` rescans the chosen sources every
+//! `interval_secs` and registers servers it has not seen before, under the
+//! configured sandbox rather than whatever the editor granted them. A name
+//! that is already registered is left alone, so configured servers always
+//! win over imported ones.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::cli::discover::{discover_source, DiscoveredMcp};
+use crate::config::{ImportWatchConfig, McpServerConfig};
+use crate::core::ServerManager;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Polls editor configs and registers new servers
+pub struct ImportWatcher {
+    config: ImportWatchConfig,
+    server_manager: Arc<ServerManager>,
+    /// Entry fingerprint per `source:name`; a failed import is retried only
+    /// once its entry changes
+    seen: HashMap<String, String>,
+}
+
+impl ImportWatcher {
+    pub fn new(config: ImportWatchConfig, server_manager: Arc<ServerManager>) -> Self {
+        Self {
+            config,
+            server_manager,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Start watching in the background, if enabled
+    pub fn spawn(config: ImportWatchConfig, server_manager: Arc<ServerManager>) -> Option<JoinHandle<()>> {
+        if !config.enabled {
+            return None;
+        }
+        info!(
+            "Watching {} for new MCP servers every {}s",
+            config.sources.join(", "),
+            config.interval_secs
+        );
+
+        let mut watcher = Self::new(config, server_manager);
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(watcher.config.interval_secs.max(1)));
+            loop {
+                ticker.tick().await;
+                watcher.scan().await;
+            }
+        }))
+    }
+
+    /// Run one scan, returning the names of servers registered
+    pub async fn scan(&mut self) -> Vec<String> {
+        let mut found = Vec::new();
+        for source in &self.config.sources {
+            match discover_source(source).await {
+                Ok(servers) => found.extend(servers),
+                Err(e) => debug!("Import source {} unavailable: {}", source, e),
+            }
+        }
+
+        let manager = self.server_manager.clone();
+        let planned = self.plan(found, |name| manager.get_server(name).is_some());
+
+        let mut imported = Vec::new();
+        for (mcp, server) in planned {
+            let name = server.name.clone();
+            let details = serde_json::json!({
+                "source": mcp.source,
+                "source_path": mcp.source_path,
+                "command": mcp.command,
+                "url": mcp.remote.as_ref().map(|r| &r.url),
+            });
+
+            match self.server_manager.add_server(server).await {
+                Ok(()) => {
+                    info!("Imported server {} from {}", name, mcp.source);
+                    audit::record(
+                        AuditEvent::new(AuditEventType::ServerImported)
+                            .with_server_name(&name)
+                            .with_details(details),
+                    );
+                    imported.push(name);
+                }
+                Err(e) => {
+                    warn!("Failed to import server {} from {}: {}", name, mcp.source, e);
+                    audit::record(
+                        AuditEvent::new(AuditEventType::ServerImported)
+                            .with_server_name(&name)
+                            .with_details(details)
+                            .with_error(e.to_string()),
+                    );
+                }
+            }
+        }
+        imported
+    }
+
+    /// Pick the discovered servers to register and build their configs
+    fn plan(
+        &mut self,
+        found: Vec<DiscoveredMcp>,
+        exists: impl Fn(&str) -> bool,
+    ) -> Vec<(DiscoveredMcp, McpServerConfig)> {
+        let mut planned = Vec::new();
+        for mcp in found {
+            if exists(&mcp.name) || planned.iter().any(|(_, s): &(_, McpServerConfig)| s.name == mcp.name) {
+                continue;
+            }
+
+            let mut server = mcp.to_config();
+            let fingerprint = serde_json::to_string(&(&server.command, &server.args, &server.env, &server.url))
+                .unwrap_or_default();
+            let key = format!("{}:{}", mcp.source, mcp.name);
+            if self.seen.get(&key) == Some(&fingerprint) {
+                continue;
+            }
+            self.seen.insert(key, fingerprint);
+
+            server.sandbox = self.config.sandbox.clone();
+            for tag in &self.config.tags {
+                if !server.tags.contains(tag) {
+                    server.tags.push(tag.clone());
+                }
+            }
+            planned.push((mcp, server));
+        }
+        planned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilesystemAccess;
+    use std::path::PathBuf;
+
+    fn discovered(source: &str, name: &str, command: &str) -> DiscoveredMcp {
+        DiscoveredMcp {
+            source: source.to_string(),
+            name: name.to_string(),
+            command: command.to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            description: None,
+            source_path: PathBuf::from("mcp.json"),
+            auto_approve: Some(true),
+            remote: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_applies_sandbox_and_tags() {
+        let mut config = ImportWatchConfig {
+            enabled: true,
+            sources: vec!["cursor".to_string()],
+            ..Default::default()
+        };
+        config.sandbox.network = false;
+        let mut watcher = ImportWatcher::new(config, Arc::new(ServerManager::new()));
+
+        let planned = watcher.plan(vec![discovered("cursor", "fs", "npx")], |_| false);
+        assert_eq!(planned.len(), 1);
+        let server = &planned[0].1;
+        assert!(server.sandbox.enabled);
+        assert!(!server.sandbox.network);
+        assert!(matches!(&server.sandbox.filesystem, FilesystemAccess::Simple(s) if s == "readonly"));
+        assert_eq!(server.tags, vec!["cursor", "imported", "auto-imported"]);
+    }
+
+    #[test]
+    fn test_plan_skips_known_and_existing() {
+        let mut watcher = ImportWatcher::new(ImportWatchConfig::default(), Arc::new(ServerManager::new()));
+
+        // Configured servers keep their name
+        assert!(watcher.plan(vec![discovered("cursor", "fs", "npx")], |name| name == "fs").is_empty());
+
+        // Same name from two sources in one scan: first wins
+        let planned = watcher.plan(
+            vec![discovered("cursor", "git", "uvx"), discovered("zed", "git", "uvx")],
+            |_| false,
+        );
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].0.source, "cursor");
+
+        // An unchanged entry that failed to start is not retried...
+        assert!(watcher.plan(vec![discovered("cursor", "git", "uvx")], |_| false).is_empty());
+        // ...until it is edited
+        assert_eq!(watcher.plan(vec![discovered("cursor", "git", "uv")], |_| false).len(), 1);
+    }
+}
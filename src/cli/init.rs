@@ -0,0 +1,439 @@
+//! First-run setup (`supermcp init`)
+//!
+//! Detects editors with MCP servers and installed runtimes, asks which
+//! servers to import, how clients authenticate and how tools are loaded,
+//! then writes a commented config.toml. With `--yes`, or without a
+//! terminal, every question takes its default.
+
+use crate::cli::discover::{discover_all, DiscoveredMcp};
+use crate::cli::{ensure_config_dir, expand_path};
+use crate::config::{LazyLoadingMode, McpServerConfig, RemoteTransport, CURRENT_CONFIG_VERSION};
+use crate::utils::errors::{McpError, McpResult};
+use dialoguer::{Confirm, MultiSelect, Select};
+use rand::Rng;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Commands MCP servers are commonly launched with
+const RUNTIME_COMMANDS: &[(&str, &str)] = &[
+    ("node", "Node.js"),
+    ("npx", "npx"),
+    ("uvx", "uv"),
+    ("python3", "Python"),
+    ("deno", "Deno"),
+    ("docker", "Docker"),
+];
+
+/// Servers above which the meta-tools are suggested over listing every tool
+const METATOOL_THRESHOLD: usize = 5;
+
+/// How clients authenticate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitAuth {
+    /// No authentication; the server stays on loopback
+    None,
+    /// Every client needs the token
+    StaticToken(String),
+    /// Anyone may list, the token holder may call tools
+    AnonymousReadonly(String),
+}
+
+/// Everything the wizard asked
+#[derive(Debug, Clone)]
+pub struct InitAnswers {
+    pub servers: Vec<(DiscoveredMcp, McpServerConfig)>,
+    pub auth: InitAuth,
+    pub lazy_loading: LazyLoadingMode,
+}
+
+/// Run the wizard and write the config to `config_path`
+pub async fn run(config_path: &str, force: bool, yes: bool) -> McpResult<()> {
+    let path = PathBuf::from(expand_path(config_path));
+    let interactive = !yes && atty::is(atty::Stream::Stdin);
+
+    if path.exists() && !force {
+        let overwrite = interactive
+            && Confirm::new()
+                .with_prompt(format!("{} already exists. Replace it?", path.display()))
+                .default(false)
+                .interact()?;
+        if !overwrite {
+            return Err(McpError::ConfigError(format!(
+                "{} already exists; pass --force to replace it",
+                path.display()
+            )));
+        }
+    }
+
+    let runtimes = detect_runtimes();
+    if runtimes.is_empty() {
+        println!("No runtimes found on PATH (looked for {})", runtime_names(RUNTIME_COMMANDS));
+    } else {
+        println!("Runtimes: {}", runtime_names(&runtimes));
+    }
+
+    let discovered = unique_by_name(discover_all().await.unwrap_or_default());
+    let mut by_source: BTreeMap<&str, usize> = BTreeMap::new();
+    for mcp in &discovered {
+        *by_source.entry(mcp.source.as_str()).or_default() += 1;
+    }
+    if by_source.is_empty() {
+        println!("No MCP servers found in editor configs");
+    } else {
+        let summary: Vec<_> = by_source.iter().map(|(source, n)| format!("{} ({})", source, n)).collect();
+        println!("MCP servers found in: {}", summary.join(", "));
+    }
+
+    let servers = choose_servers(discovered, interactive)?;
+    let auth = choose_auth(interactive)?;
+    let lazy_loading = choose_lazy_loading(servers.len(), interactive)?;
+    let answers = InitAnswers {
+        servers,
+        auth,
+        lazy_loading,
+    };
+
+    ensure_config_dir(&path).await?;
+    if path.exists() {
+        let backup = path.with_file_name(format!(
+            "{}.bak",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        tokio::fs::copy(&path, &backup).await?;
+        println!("Previous config saved to {}", backup.display());
+    }
+    tokio::fs::write(&path, render_config(&answers)).await?;
+
+    println!("\nWrote {}", path.display());
+    match &answers.auth {
+        InitAuth::None => {}
+        InitAuth::StaticToken(token) | InitAuth::AnonymousReadonly(token) => {
+            println!("Client token (also in the config): {}", token);
+        }
+    }
+    println!("Start the server with: supermcp serve --config {}", path.display());
+    Ok(())
+}
+
+/// Runtimes from [`RUNTIME_COMMANDS`] found on PATH
+pub fn detect_runtimes() -> Vec<(&'static str, &'static str)> {
+    RUNTIME_COMMANDS
+        .iter()
+        .copied()
+        .filter(|(command, _)| which::which(command).is_ok())
+        .collect()
+}
+
+fn runtime_names(runtimes: &[(&str, &str)]) -> String {
+    runtimes.iter().map(|(_, name)| *name).collect::<Vec<_>>().join(", ")
+}
+
+/// Keep the first server of each name; editors often share servers
+fn unique_by_name(discovered: Vec<DiscoveredMcp>) -> Vec<DiscoveredMcp> {
+    let mut unique: Vec<DiscoveredMcp> = Vec::new();
+    for mcp in discovered {
+        if !unique.iter().any(|seen| seen.name == mcp.name) {
+            unique.push(mcp);
+        }
+    }
+    unique
+}
+
+/// Whether the server's command is installed (remote servers always are)
+fn is_runnable(mcp: &DiscoveredMcp) -> bool {
+    mcp.remote.is_some() || which::which(&mcp.command).is_ok()
+}
+
+fn choose_servers(
+    discovered: Vec<DiscoveredMcp>,
+    interactive: bool,
+) -> McpResult<Vec<(DiscoveredMcp, McpServerConfig)>> {
+    if discovered.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Servers whose command is missing would only fail at startup
+    let defaults: Vec<bool> = discovered.iter().map(is_runnable).collect();
+    let selected: Vec<usize> = if interactive {
+        let items: Vec<String> = discovered
+            .iter()
+            .zip(&defaults)
+            .map(|(mcp, runnable)| {
+                let target = match &mcp.remote {
+                    Some(remote) => remote.url.clone(),
+                    None => mcp.command.clone(),
+                };
+                let missing = if *runnable { "" } else { " - not installed" };
+                format!("{} [{}] {}{}", mcp.name, mcp.source, target, missing)
+            })
+            .collect();
+        MultiSelect::new()
+            .with_prompt("Import servers (space to toggle, enter to confirm)")
+            .items(&items)
+            .defaults(&defaults)
+            .interact()?
+    } else {
+        (0..discovered.len()).filter(|i| defaults[*i]).collect()
+    };
+
+    Ok(discovered
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .map(|(_, mcp)| {
+            let config = mcp.to_config();
+            (mcp, config)
+        })
+        .collect())
+}
+
+fn choose_auth(interactive: bool) -> McpResult<InitAuth> {
+    if !interactive {
+        return Ok(InitAuth::None);
+    }
+    let choice = Select::new()
+        .with_prompt("How should clients authenticate?")
+        .items(&[
+            "No authentication (local clients only)",
+            "Static bearer token (generated)",
+            "Anonymous read-only, full access with a generated token",
+        ])
+        .default(0)
+        .interact()?;
+    Ok(match choice {
+        1 => InitAuth::StaticToken(generate_token()),
+        2 => InitAuth::AnonymousReadonly(generate_token()),
+        _ => InitAuth::None,
+    })
+}
+
+fn choose_lazy_loading(server_count: usize, interactive: bool) -> McpResult<LazyLoadingMode> {
+    let modes = [
+        (LazyLoadingMode::Disabled, "Disabled - list every tool up front"),
+        (LazyLoadingMode::Metatool, "Metatool - expose tool_list/tool_schema/tool_invoke instead"),
+        (LazyLoadingMode::Hybrid, "Hybrid - preload chosen servers, load the rest on demand"),
+        (LazyLoadingMode::Full, "Full - fetch every schema on demand"),
+    ];
+    let default = usize::from(server_count > METATOOL_THRESHOLD);
+    if !interactive {
+        return Ok(modes[default].0);
+    }
+    let items: Vec<_> = modes.iter().map(|(_, label)| *label).collect();
+    let choice = Select::new()
+        .with_prompt("How should tools be loaded?")
+        .items(&items)
+        .default(default)
+        .interact()?;
+    Ok(modes[choice].0)
+}
+
+fn generate_token() -> String {
+    rand::rng()
+        .sample_iter(rand::distr::Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
+
+/// TOML for a string, array or other value
+fn toml_value(value: impl Into<toml::Value>) -> String {
+    value.into().to_string()
+}
+
+/// Bare key when TOML allows it, quoted otherwise
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml_value(key)
+    }
+}
+
+fn inline_table(map: &HashMap<String, String>) -> String {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort();
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(key, value)| format!("{} = {}", toml_key(key), toml_value(value.as_str())))
+        .collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// The commented config.toml for the answers
+pub fn render_config(answers: &InitAnswers) -> String {
+    let mut out = String::new();
+    out.push_str("# Super MCP configuration, written by `supermcp init`\n");
+    out.push_str("# Every option is documented in config.example.toml.\n\n");
+    out.push_str(&format!("version = {}\n\n", CURRENT_CONFIG_VERSION));
+
+    out.push_str("[server]\n");
+    out.push_str("# Loopback only; bind 0.0.0.0 to serve other machines (set up auth first)\n");
+    out.push_str("host = \"127.0.0.1\"\nport = 3000\n\n");
+
+    out.push_str("[auth]\n");
+    let auth_enabled = match &answers.auth {
+        InitAuth::None => {
+            out.push_str("# Options: none, static, jwt, oauth, anonymous_readonly\n");
+            out.push_str("type = \"none\"\n\n");
+            false
+        }
+        InitAuth::StaticToken(token) => {
+            out.push_str("# Clients send `Authorization: Bearer <token>`\n");
+            out.push_str("type = \"static\"\n");
+            out.push_str(&format!("token = {}\n\n", toml_value(token.as_str())));
+            true
+        }
+        InitAuth::AnonymousReadonly(token) => {
+            out.push_str("# Anyone may list tools; calling them needs the token\n");
+            out.push_str("type = \"anonymous_readonly\"\n");
+            out.push_str(&format!("token = {}\n\n", toml_value(token.as_str())));
+            true
+        }
+    };
+
+    out.push_str("[features]\n");
+    out.push_str(&format!("auth = {}\n", auth_enabled));
+    out.push_str("sandbox = true\nhot_reload = true\naudit_logging = true\n\n");
+
+    let mode = match answers.lazy_loading {
+        LazyLoadingMode::Disabled => "disabled",
+        LazyLoadingMode::Metatool => "metatool",
+        LazyLoadingMode::Hybrid => "hybrid",
+        LazyLoadingMode::Full => "full",
+    };
+    out.push_str("[lazy_loading]\n");
+    out.push_str("# disabled: list every tool; metatool: tool_list/tool_schema/tool_invoke;\n");
+    out.push_str("# hybrid: preload preload_servers, fetch the rest on demand; full: all on demand\n");
+    out.push_str(&format!("mode = {}\n", toml_value(mode)));
+    if answers.lazy_loading == LazyLoadingMode::Hybrid {
+        out.push_str("# preload_servers = [\"filesystem\"]\n");
+    }
+
+    if answers.servers.is_empty() {
+        out.push_str("\n# Add servers with `supermcp mcp add` or `supermcp import`, e.g.\n");
+        out.push_str("# [[servers]]\n# name = \"filesystem\"\n# command = \"npx\"\n");
+        out.push_str("# args = [\"-y\", \"@modelcontextprotocol/server-filesystem\", \"/tmp\"]\n");
+        return out;
+    }
+
+    out.push_str("\n# Servers run sandboxed (read-only filesystem, no network) unless a\n");
+    out.push_str("# [servers.sandbox] table says otherwise\n");
+    for (mcp, server) in &answers.servers {
+        out.push_str(&format!(
+            "\n# Imported from {} ({})\n[[servers]]\n",
+            mcp.source,
+            mcp.source_path.display()
+        ));
+        out.push_str(&format!("name = {}\n", toml_value(server.name.as_str())));
+        match &server.url {
+            Some(url) => {
+                out.push_str(&format!("url = {}\n", toml_value(url.as_str())));
+                match server.transport {
+                    RemoteTransport::Auto => {}
+                    RemoteTransport::StreamableHttp => out.push_str("transport = \"streamable_http\"\n"),
+                    RemoteTransport::Sse => out.push_str("transport = \"sse\"\n"),
+                    RemoteTransport::Nats => out.push_str("transport = \"nats\"\n"),
+                }
+                if !server.headers.is_empty() {
+                    out.push_str(&format!("headers = {}\n", inline_table(&server.headers)));
+                }
+            }
+            None => {
+                out.push_str(&format!("command = {}\n", toml_value(server.command.as_str())));
+                if !server.args.is_empty() {
+                    out.push_str(&format!("args = {}\n", toml_value(server.args.clone())));
+                }
+            }
+        }
+        if !server.env.is_empty() {
+            out.push_str(&format!("env = {}\n", inline_table(&server.env)));
+        }
+        out.push_str(&format!("tags = {}\n", toml_value(server.tags.clone())));
+        if let Some(description) = &server.description {
+            out.push_str(&format!("description = {}\n", toml_value(description.as_str())));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::discover::RemoteEndpoint;
+    use crate::config::{AuthType, Config};
+
+    fn discovered(name: &str) -> DiscoveredMcp {
+        DiscoveredMcp {
+            source: "cursor".to_string(),
+            name: name.to_string(),
+            command: "npx".to_string(),
+            args: vec!["-y".to_string(), "@modelcontextprotocol/server-filesystem".to_string()],
+            env: HashMap::from([("API KEY".to_string(), "a\"b".to_string())]),
+            description: None,
+            source_path: PathBuf::from("/home/me/.cursor/mcp.json"),
+            auto_approve: None,
+            remote: None,
+        }
+    }
+
+    #[test]
+    fn test_render_config_round_trips() {
+        let local = discovered("fs");
+        let mut remote = discovered("linear");
+        remote.remote = Some(RemoteEndpoint {
+            url: "https://mcp.linear.app/sse".to_string(),
+            transport: RemoteTransport::Sse,
+            headers: HashMap::from([("Authorization".to_string(), "Bearer x".to_string())]),
+        });
+        let answers = InitAnswers {
+            servers: vec![local, remote]
+                .into_iter()
+                .map(|mcp| {
+                    let config = mcp.to_config();
+                    (mcp, config)
+                })
+                .collect(),
+            auth: InitAuth::StaticToken("secret".to_string()),
+            lazy_loading: LazyLoadingMode::Metatool,
+        };
+
+        let rendered = render_config(&answers);
+        let config: Config = toml::from_str(&rendered).unwrap();
+        assert_eq!(config.version.0, CURRENT_CONFIG_VERSION);
+        assert!(matches!(config.auth.auth_type, AuthType::Static));
+        assert_eq!(config.auth.token.as_deref(), Some("secret"));
+        assert!(config.features.auth);
+        assert_eq!(config.lazy_loading.mode, LazyLoadingMode::Metatool);
+
+        assert_eq!(config.servers.len(), 2);
+        assert_eq!(config.servers[0].args, answers.servers[0].1.args);
+        assert_eq!(config.servers[0].env["API KEY"], "a\"b");
+        assert_eq!(config.servers[1].url.as_deref(), Some("https://mcp.linear.app/sse"));
+        assert_eq!(config.servers[1].transport, RemoteTransport::Sse);
+        assert_eq!(config.servers[1].headers["Authorization"], "Bearer x");
+    }
+
+    #[test]
+    fn test_render_config_without_servers() {
+        let answers = InitAnswers {
+            servers: Vec::new(),
+            auth: InitAuth::None,
+            lazy_loading: LazyLoadingMode::Disabled,
+        };
+        let config: Config = toml::from_str(&render_config(&answers)).unwrap();
+        assert!(config.servers.is_empty());
+        assert!(!config.features.auth);
+        assert_eq!(config.server.host, "127.0.0.1");
+    }
+
+    #[test]
+    fn test_unique_by_name() {
+        let mut zed = discovered("fs");
+        zed.source = "zed".to_string();
+        let unique = unique_by_name(vec![discovered("fs"), zed, discovered("git")]);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].source, "cursor");
+    }
+}
@@ -1,5 +1,6 @@
 //! Startup manager installation commands
 
+use crate::cli::args::ServiceCommand;
 use anyhow::{anyhow, Result as AnyhowResult};
 use dialoguer::{Confirm, MultiSelect};
 use std::fs;
@@ -12,12 +13,16 @@ use tokio::fs as async_fs;
 pub enum StartupManager {
     /// macOS launchd
     Launchd,
+    /// macOS launchd agent managed with `brew services`
+    Homebrew,
     /// Linux systemd
     Systemd,
     /// Linux OpenRC
     Openrc,
     /// Linux runit
     Runit,
+    /// Windows Service Control Manager
+    WindowsService,
     /// Windows NSSM
     Nssm,
     /// Windows Task Scheduler
@@ -29,9 +34,11 @@ impl StartupManager {
     pub fn display_name(&self) -> &'static str {
         match self {
             StartupManager::Launchd => "macOS launchd (launchctl)",
+            StartupManager::Homebrew => "Homebrew services (brew services)",
             StartupManager::Systemd => "Linux systemd",
             StartupManager::Openrc => "Linux OpenRC",
             StartupManager::Runit => "Linux runit",
+            StartupManager::WindowsService => "Windows Service (SCM)",
             StartupManager::Nssm => "Windows NSSM",
             StartupManager::Schtasks => "Windows Task Scheduler (schtasks)",
         }
@@ -41,9 +48,11 @@ impl StartupManager {
     pub fn platform(&self) -> &'static str {
         match self {
             StartupManager::Launchd => "macOS",
+            StartupManager::Homebrew => "macOS",
             StartupManager::Systemd => "Linux",
             StartupManager::Openrc => "Linux",
             StartupManager::Runit => "Linux",
+            StartupManager::WindowsService => "Windows",
             StartupManager::Nssm => "Windows",
             StartupManager::Schtasks => "Windows",
         }
@@ -55,6 +64,7 @@ impl StartupManager {
             StartupManager::Launchd => {
                 Command::new("launchctl").arg("--version").output().is_ok()
             }
+            StartupManager::Homebrew => which::which("brew").is_ok(),
             StartupManager::Systemd => {
                 Path::new("/etc/systemd/system").exists() && which::which("systemctl").is_ok()
             }
@@ -62,19 +72,151 @@ impl StartupManager {
                 which::which("rc-service").is_ok() || which::which("openrc").is_ok()
             }
             StartupManager::Runit => Path::new("/etc/service").exists(),
+            StartupManager::WindowsService => cfg!(windows),
             StartupManager::Nssm => which::which("nssm").is_ok(),
             StartupManager::Schtasks => which::which("schtasks").is_ok(),
         }
     }
+
+    /// Parse a manager name as given to `--manager`
+    pub fn parse(name: &str) -> AnyhowResult<Self> {
+        match name.to_lowercase().as_str() {
+            "launchd" | "macos" | "darwin" => Ok(StartupManager::Launchd),
+            "homebrew" | "brew" => Ok(StartupManager::Homebrew),
+            "systemd" => Ok(StartupManager::Systemd),
+            "openrc" | "open-rc" => Ok(StartupManager::Openrc),
+            "runit" => Ok(StartupManager::Runit),
+            "windows-service" | "windows" | "scm" => Ok(StartupManager::WindowsService),
+            "nssm" => Ok(StartupManager::Nssm),
+            "schtasks" | "taskscheduler" | "task-scheduler" => Ok(StartupManager::Schtasks),
+            _ => Err(anyhow!(
+                "Unknown startup manager: {}. Valid options: launchd, homebrew, systemd, openrc, runit, windows-service, nssm, schtasks",
+                name
+            )),
+        }
+    }
+
+    /// Whether the manager can run a service for the current user only
+    pub fn supports_user(&self) -> bool {
+        matches!(
+            self,
+            StartupManager::Launchd | StartupManager::Homebrew | StartupManager::Systemd | StartupManager::Schtasks
+        )
+    }
+
+    /// Whether super-mcp is installed with this manager
+    pub fn is_installed(&self, user: bool) -> bool {
+        match self {
+            StartupManager::Launchd => launchd_plist_path(LAUNCHD_LABEL).is_ok_and(|p| p.exists()),
+            StartupManager::Homebrew => launchd_plist_path(HOMEBREW_LABEL).is_ok_and(|p| p.exists()),
+            StartupManager::Systemd => systemd_unit_path(user).is_ok_and(|p| p.exists()),
+            StartupManager::Openrc => Path::new("/etc/init.d/super-mcp").exists(),
+            StartupManager::Runit => Path::new("/etc/service/super-mcp").exists(),
+            StartupManager::WindowsService => succeeds("sc", &["query", "super-mcp"]),
+            StartupManager::Nssm => succeeds("nssm", &["status", "super-mcp"]),
+            StartupManager::Schtasks => succeeds("schtasks", &["/Query", "/TN", "super-mcp"]),
+        }
+    }
+
+    /// Command reporting the service's state, or restarting it
+    fn service_commands(&self, user: bool, command: ServiceCommand) -> Vec<Vec<String>> {
+        let label = match self {
+            StartupManager::Homebrew => HOMEBREW_LABEL,
+            _ => LAUNCHD_LABEL,
+        };
+        let commands: Vec<Vec<&str>> = match (self, command) {
+            (StartupManager::Launchd | StartupManager::Homebrew, ServiceCommand::Status) => {
+                vec![vec!["launchctl", "list", label]]
+            }
+            (StartupManager::Launchd | StartupManager::Homebrew, ServiceCommand::Restart) => {
+                vec![vec!["launchctl", "stop", label], vec!["launchctl", "start", label]]
+            }
+            (StartupManager::Systemd, ServiceCommand::Status) => {
+                vec![systemctl(user, &["status", "super-mcp", "--no-pager"])]
+            }
+            (StartupManager::Systemd, ServiceCommand::Restart) => {
+                vec![systemctl(user, &["restart", "super-mcp"])]
+            }
+            (StartupManager::Openrc, ServiceCommand::Status) => vec![vec!["rc-service", "super-mcp", "status"]],
+            (StartupManager::Openrc, ServiceCommand::Restart) => vec![vec!["rc-service", "super-mcp", "restart"]],
+            (StartupManager::Runit, ServiceCommand::Status) => vec![vec!["sv", "status", "super-mcp"]],
+            (StartupManager::Runit, ServiceCommand::Restart) => vec![vec!["sv", "restart", "super-mcp"]],
+            (StartupManager::WindowsService, ServiceCommand::Status) => vec![vec!["sc", "query", "super-mcp"]],
+            (StartupManager::WindowsService, ServiceCommand::Restart) => {
+                vec![vec!["powershell", "-NoProfile", "-Command", "Restart-Service super-mcp"]]
+            }
+            (StartupManager::Nssm, ServiceCommand::Status) => vec![vec!["nssm", "status", "super-mcp"]],
+            (StartupManager::Nssm, ServiceCommand::Restart) => vec![vec!["nssm", "restart", "super-mcp"]],
+            (StartupManager::Schtasks, ServiceCommand::Status) => {
+                vec![vec!["schtasks", "/Query", "/TN", "super-mcp", "/V", "/FO", "LIST"]]
+            }
+            (StartupManager::Schtasks, ServiceCommand::Restart) => vec![
+                vec!["schtasks", "/End", "/TN", "super-mcp"],
+                vec!["schtasks", "/Run", "/TN", "super-mcp"],
+            ],
+        };
+        commands
+            .into_iter()
+            .map(|c| c.into_iter().map(String::from).collect())
+            .collect()
+    }
+}
+
+/// launchd label of the agent installed with `--manager launchd`
+const LAUNCHD_LABEL: &str = "com.super-mcp.agent";
+
+/// launchd label `brew services` uses for the supermcp formula
+const HOMEBREW_LABEL: &str = "homebrew.mxcl.supermcp";
+
+fn launchd_plist_path(label: &str) -> AnyhowResult<PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow!("Could not determine home directory"))?
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", label)))
+}
+
+fn systemd_unit_path(user: bool) -> AnyhowResult<PathBuf> {
+    if !user {
+        return Ok(PathBuf::from("/etc/systemd/system/super-mcp.service"));
+    }
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow!("Could not determine home directory"))?
+        .join(".config/systemd/user/super-mcp.service"))
+}
+
+/// Command line of a `systemctl` call, against the user manager when `user` is set
+fn systemctl<'a>(user: bool, args: &[&'a str]) -> Vec<&'a str> {
+    let mut command = vec!["systemctl"];
+    if user {
+        command.push("--user");
+    }
+    command.extend_from_slice(args);
+    command
+}
+
+/// `systemctl`, against the user manager when `user` is set
+fn systemctl_command(user: bool) -> Command {
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    command
+}
+
+fn succeeds(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .is_ok_and(|output| output.status.success())
 }
 
 /// Detect available startup managers for the current OS
 pub fn detect_available_managers() -> Vec<StartupManager> {
     let managers: Vec<StartupManager> = match std::env::consts::OS {
-        "macos" => vec![StartupManager::Launchd],
+        "macos" => vec![StartupManager::Launchd, StartupManager::Homebrew],
         "linux" => vec![StartupManager::Systemd, StartupManager::Openrc, StartupManager::Runit],
         "windows" => {
-            let mut managers = vec![StartupManager::Schtasks];
+            let mut managers = vec![StartupManager::WindowsService, StartupManager::Schtasks];
             if StartupManager::Nssm.is_available() {
                 managers.push(StartupManager::Nssm);
             }
@@ -152,6 +294,7 @@ pub async fn install(
     binary_path: Option<&str>,
     config_path: Option<&str>,
     manager: Option<&str>,
+    user: bool,
     uninstall: bool,
 ) -> AnyhowResult<()> {
     let binary_path = binary_path.map(|s| s.to_string()).unwrap_or_else(|| {
@@ -186,24 +329,22 @@ pub async fn install(
         ));
     }
 
+    // Only managers that can run without root are offered for --user
+    let available_managers: Vec<_> = available_managers
+        .into_iter()
+        .filter(|m| !user || m.supports_user())
+        .collect();
+
     let selected_managers = if let Some(mgr) = manager {
         // Parse single manager from argument
-        let mgr = match mgr.to_lowercase().as_str() {
-            "launchd" | "macos" | "darwin" => vec![StartupManager::Launchd],
-            "systemd" => vec![StartupManager::Systemd],
-            "openrc" | "open-rc" => vec![StartupManager::Openrc],
-            "runit" => vec![StartupManager::Runit],
-            "nssm" => vec![StartupManager::Nssm],
-            "schtasks" | "taskscheduler" | "task-scheduler" => {
-                vec![StartupManager::Schtasks]
-            }
-            _ => {
-                return Err(anyhow!(
-                    "Unknown startup manager: {}. Valid options: launchd, systemd, openrc, runit, nssm, schtasks",
-                    mgr
-                ));
-            }
-        };
+        let mgr = vec![StartupManager::parse(mgr)?];
+
+        if user && !mgr[0].supports_user() {
+            return Err(anyhow!(
+                "Manager '{}' does not support --user installs.",
+                mgr[0].display_name()
+            ));
+        }
 
         // Verify the manager is available
         for m in &mgr {
@@ -216,6 +357,10 @@ pub async fn install(
         }
 
         mgr
+    } else if available_managers.is_empty() {
+        return Err(anyhow!(
+            "No startup managers on this platform support --user installs."
+        ));
     } else if uninstall {
         // For uninstall, try all available managers
         available_managers.clone()
@@ -249,10 +394,10 @@ pub async fn install(
     for manager in &selected_managers {
         if uninstall {
             println!("Uninstalling from {}...", manager.display_name());
-            uninstall_from_manager(manager, &binary_path_expanded, &config_path_expanded).await?;
+            uninstall_from_manager(manager, user).await?;
         } else {
             println!("Installing to {}...", manager.display_name());
-            install_to_manager(manager, &binary_path_expanded, &config_path_expanded).await?;
+            install_to_manager(manager, &binary_path_expanded, &config_path_expanded, user).await?;
         }
     }
 
@@ -282,42 +427,42 @@ async fn install_to_manager(
     manager: &StartupManager,
     binary_path: &str,
     config_path: &str,
+    user: bool,
 ) -> AnyhowResult<()> {
     match manager {
         StartupManager::Launchd => install_launchd(binary_path, config_path).await,
-        StartupManager::Systemd => install_systemd(binary_path, config_path).await,
+        StartupManager::Homebrew => install_homebrew(binary_path, config_path).await,
+        StartupManager::Systemd => install_systemd(binary_path, config_path, user).await,
         StartupManager::Openrc => install_openrc(binary_path, config_path).await,
         StartupManager::Runit => install_runit(binary_path, config_path).await,
+        StartupManager::WindowsService => install_windows_service(binary_path, config_path),
         StartupManager::Nssm => install_nssm(binary_path, config_path).await,
         StartupManager::Schtasks => install_schtasks(binary_path, config_path).await,
     }
 }
 
 /// Uninstall from a specific manager
-#[allow(dead_code)]
-async fn uninstall_from_manager(
-    manager: &StartupManager,
-    _binary_path: &str,
-    _config_path: &str,
-) -> AnyhowResult<()> {
+async fn uninstall_from_manager(manager: &StartupManager, user: bool) -> AnyhowResult<()> {
     match manager {
         StartupManager::Launchd => uninstall_launchd().await,
-        StartupManager::Systemd => uninstall_systemd().await,
+        StartupManager::Homebrew => uninstall_homebrew().await,
+        StartupManager::Systemd => uninstall_systemd(user).await,
         StartupManager::Openrc => uninstall_openrc().await,
         StartupManager::Runit => uninstall_runit().await,
+        StartupManager::WindowsService => uninstall_windows_service(),
         StartupManager::Nssm => uninstall_nssm().await,
         StartupManager::Schtasks => uninstall_schtasks().await,
     }
 }
 
-/// Install using macOS launchd
-async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
-    let plist_content = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+/// launchd agent running `serve`, logging to the given files
+fn launchd_plist(label: &str, binary_path: &str, config_path: &str, out_log: &str, err_log: &str) -> String {
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
 <plist version="1.0">
 <dict>
     <key>Label</key>
-    <string>com.super-mcp.agent</string>
+    <string>{}</string>
     <key>ProgramArguments</key>
     <array>
         <string>{}</string>
@@ -330,9 +475,9 @@ async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<(
     <key>KeepAlive</key>
     <true/>
     <key>StandardOutPath</key>
-    <string>/tmp/super-mcp.out.log</string>
+    <string>{}</string>
     <key>StandardErrorPath</key>
-    <string>/tmp/super-mcp.err.log</string>
+    <string>{}</string>
     <key>EnvironmentVariables</key>
     <dict>
         <key>RUST_LOG</key>
@@ -341,13 +486,25 @@ async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<(
 </dict>
 </plist>
 "#,
+        label,
         binary_path,
-        config_path
+        config_path,
+        out_log,
+        err_log
+    )
+}
+
+/// Install using macOS launchd
+async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
+    let plist_content = launchd_plist(
+        LAUNCHD_LABEL,
+        binary_path,
+        config_path,
+        "/tmp/super-mcp.out.log",
+        "/tmp/super-mcp.err.log",
     );
 
-    let plist_path = dirs::home_dir()
-        .ok_or_else(|| anyhow!("Could not determine home directory"))?
-        .join("Library/LaunchAgents/com.super-mcp.agent.plist");
+    let plist_path = launchd_plist_path(LAUNCHD_LABEL)?;
 
     // Ensure directory exists
     if let Some(parent) = plist_path.parent() {
@@ -368,7 +525,7 @@ async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<(
 
     // Start the daemon
     let output = Command::new("launchctl")
-        .args(["start", "com.super-mcp.agent"])
+        .args(["start", LAUNCHD_LABEL])
         .output()?;
 
     if !output.status.success() {
@@ -378,7 +535,7 @@ async fn install_launchd(binary_path: &str, config_path: &str) -> AnyhowResult<(
 
     println!("✓ Installed super-mcp as launchd daemon");
     println!("  Plist: {}", plist_path.display());
-    println!("  Use 'launchctl list | grep super-mcp' to check status");
+    println!("  Use 'supermcp service status' to check status");
 
     Ok(())
 }
@@ -408,9 +565,97 @@ async fn uninstall_launchd() -> AnyhowResult<()> {
     Ok(())
 }
 
-/// Install using Linux systemd
-async fn install_systemd(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
-    let service_content = format!(r#"[Unit]
+/// Whether supermcp itself was installed as a Homebrew formula
+fn is_homebrew_formula() -> bool {
+    succeeds("brew", &["list", "--formula", "supermcp"])
+}
+
+/// Install as a `brew services` agent
+///
+/// A Homebrew-installed supermcp is started with `brew services`; any other
+/// binary gets an agent under the label `brew services` uses, so it can be
+/// listed and stopped the same way.
+async fn install_homebrew(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
+    if is_homebrew_formula() {
+        let output = Command::new("brew")
+            .args(["services", "start", "supermcp"])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to start brew service: {}", stderr));
+        }
+
+        println!("✓ Started super-mcp with brew services");
+        println!("  Use 'brew services info supermcp' to check status");
+        return Ok(());
+    }
+
+    let prefix = Command::new("brew")
+        .arg("--prefix")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "/opt/homebrew".to_string());
+    let log_dir = PathBuf::from(&prefix).join("var/log");
+    async_fs::create_dir_all(&log_dir).await?;
+    let log_path = log_dir.join("supermcp.log");
+    let log_path = log_path.to_string_lossy();
+
+    let plist_path = launchd_plist_path(HOMEBREW_LABEL)?;
+    if let Some(parent) = plist_path.parent() {
+        async_fs::create_dir_all(parent).await?;
+    }
+    async_fs::write(
+        &plist_path,
+        launchd_plist(HOMEBREW_LABEL, binary_path, config_path, &log_path, &log_path),
+    )
+    .await?;
+
+    let output = Command::new("launchctl")
+        .args(["load", "-w", plist_path.to_str().unwrap()])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Failed to load launchd agent: {}", stderr));
+    }
+
+    println!("✓ Installed super-mcp as a brew services agent");
+    println!("  Plist: {}", plist_path.display());
+    println!("  Log: {}", log_path);
+    println!("  Use 'supermcp service status' to check status");
+
+    Ok(())
+}
+
+/// Uninstall from Homebrew services
+async fn uninstall_homebrew() -> AnyhowResult<()> {
+    if is_homebrew_formula() {
+        let _ = Command::new("brew")
+            .args(["services", "stop", "supermcp"])
+            .output();
+        println!("✓ Stopped super-mcp with brew services");
+        return Ok(());
+    }
+
+    let plist_path = launchd_plist_path(HOMEBREW_LABEL)?;
+    if plist_path.exists() {
+        let _ = Command::new("launchctl")
+            .args(["unload", "-w", plist_path.to_str().unwrap()])
+            .output();
+        async_fs::remove_file(&plist_path).await?;
+        println!("✓ Removed brew services plist");
+    }
+
+    println!("✓ Uninstalled super-mcp from Homebrew services");
+    Ok(())
+}
+
+/// systemd unit running `serve`; user units start with the user's session
+fn systemd_unit(binary_path: &str, config_path: &str, user: bool) -> String {
+    format!(r#"[Unit]
 Description=Super MCP Server
 After=network.target
 
@@ -424,18 +669,25 @@ StandardOutput=journal
 StandardError=journal
 
 [Install]
-WantedBy=multi-user.target
+WantedBy={}
 "#,
         binary_path,
-        config_path
-    );
+        config_path,
+        if user { "default.target" } else { "multi-user.target" }
+    )
+}
 
-    let service_path = PathBuf::from("/etc/systemd/system/super-mcp.service");
+/// Install using Linux systemd, as a user unit when `user` is set
+async fn install_systemd(binary_path: &str, config_path: &str, user: bool) -> AnyhowResult<()> {
+    let service_path = systemd_unit_path(user)?;
 
-    async_fs::write(&service_path, service_content).await?;
+    if let Some(parent) = service_path.parent() {
+        async_fs::create_dir_all(parent).await?;
+    }
+    async_fs::write(&service_path, systemd_unit(binary_path, config_path, user)).await?;
 
     // Reload systemd daemon
-    let output = Command::new("systemctl")
+    let output = systemctl_command(user)
         .args(["daemon-reload"])
         .output()?;
 
@@ -445,7 +697,7 @@ WantedBy=multi-user.target
     }
 
     // Enable and start the service
-    let output = Command::new("systemctl")
+    let output = systemctl_command(user)
         .args(["enable", "--now", "super-mcp"])
         .output()?;
 
@@ -454,30 +706,35 @@ WantedBy=multi-user.target
         return Err(anyhow!("Failed to enable/start service: {}", stderr));
     }
 
-    println!("✓ Installed super-mcp as systemd service");
-    println!("  Service: /etc/systemd/system/super-mcp.service");
-    println!("  Use 'systemctl status super-mcp' to check status");
+    println!("✓ Installed super-mcp as systemd {}service", if user { "user " } else { "" });
+    println!("  Service: {}", service_path.display());
+    if user {
+        println!("  Run 'loginctl enable-linger' to keep it running after logout");
+    }
+    println!("  Use 'supermcp service status{}' to check status", if user { " --user" } else { "" });
 
     Ok(())
 }
 
 /// Uninstall from Linux systemd
-async fn uninstall_systemd() -> AnyhowResult<()> {
+async fn uninstall_systemd(user: bool) -> AnyhowResult<()> {
     // Stop and disable the service
-    let _ = Command::new("systemctl")
+    let _ = systemctl_command(user)
         .args(["stop", "super-mcp"])
         .output();
 
-    let _output = Command::new("systemctl")
+    let _output = systemctl_command(user)
         .args(["disable", "super-mcp"])
         .output();
 
-    let service_path = PathBuf::from("/etc/systemd/system/super-mcp.service");
+    let service_path = systemd_unit_path(user)?;
 
     if service_path.exists() {
         async_fs::remove_file(&service_path).await?;
         // Reload daemon
-        let _ = Command::new("systemctl").args(["daemon-reload"]).output();
+        let _ = systemctl_command(user)
+            .args(["daemon-reload"])
+            .output();
         println!("✓ Removed systemd service file");
     }
 
@@ -606,6 +863,28 @@ async fn uninstall_runit() -> AnyhowResult<()> {
     Ok(())
 }
 
+/// Install as a native Windows service
+#[cfg(windows)]
+fn install_windows_service(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
+    crate::cli::win_service::install(&binary_path.replace('/', "\\"), &config_path.replace('/', "\\"))
+}
+
+#[cfg(not(windows))]
+fn install_windows_service(_binary_path: &str, _config_path: &str) -> AnyhowResult<()> {
+    Err(anyhow!("Windows services can only be installed on Windows"))
+}
+
+/// Uninstall the native Windows service
+#[cfg(windows)]
+fn uninstall_windows_service() -> AnyhowResult<()> {
+    crate::cli::win_service::uninstall()
+}
+
+#[cfg(not(windows))]
+fn uninstall_windows_service() -> AnyhowResult<()> {
+    Err(anyhow!("Windows services can only be removed on Windows"))
+}
+
 /// Install using Windows NSSM
 async fn install_nssm(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
     let binary_path = binary_path.replace('/', "\\");
@@ -778,3 +1057,71 @@ async fn uninstall_schtasks() -> AnyhowResult<()> {
     println!("✓ Uninstalled super-mcp from Task Scheduler");
     Ok(())
 }
+
+/// Show the status of, or restart, the installed service
+///
+/// Without `manager`, every available manager super-mcp is installed with
+/// is used.
+pub fn service(command: ServiceCommand, manager: Option<&str>, user: bool) -> AnyhowResult<()> {
+    let managers = match manager {
+        Some(name) => vec![StartupManager::parse(name)?],
+        None => detect_available_managers()
+            .into_iter()
+            .filter(|m| m.is_installed(user))
+            .collect(),
+    };
+
+    if managers.is_empty() {
+        return Err(anyhow!(
+            "super-mcp is not installed as a {}service. Run 'supermcp install{}' first.",
+            if user { "user " } else { "" },
+            if user { " --user" } else { "" }
+        ));
+    }
+
+    for manager in &managers {
+        println!("{}:", manager.display_name());
+        for args in manager.service_commands(user, command) {
+            let output = Command::new(&args[0]).args(&args[1..]).output()?;
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+
+            // A stopped service reports a non-zero status, which is still a status
+            if !output.status.success() && matches!(command, ServiceCommand::Restart) {
+                return Err(anyhow!("'{}' failed", args.join(" ")));
+            }
+        }
+        if matches!(command, ServiceCommand::Restart) {
+            println!("✓ Restarted super-mcp");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_user_unit() {
+        let unit = systemd_unit("/usr/local/bin/supermcp", "/home/me/config.toml", true);
+        assert!(unit.contains("ExecStart=/usr/local/bin/supermcp serve --config /home/me/config.toml"));
+        assert!(unit.contains("WantedBy=default.target"));
+        assert!(systemd_unit("supermcp", "config.toml", false).contains("WantedBy=multi-user.target"));
+    }
+
+    #[test]
+    fn test_service_commands() {
+        assert_eq!(
+            StartupManager::Systemd.service_commands(true, ServiceCommand::Restart),
+            vec![vec!["systemctl", "--user", "restart", "super-mcp"]]
+        );
+        assert_eq!(
+            StartupManager::Homebrew.service_commands(false, ServiceCommand::Status),
+            vec![vec!["launchctl", "list", HOMEBREW_LABEL]]
+        );
+        assert!(StartupManager::parse("brew").is_ok_and(|m| m == StartupManager::Homebrew));
+        assert!(!StartupManager::Openrc.supports_user());
+    }
+}
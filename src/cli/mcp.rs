@@ -1,10 +1,14 @@
 //! MCP server management commands
 
+use crate::cli::output::{print_list, OutputFormat, Table};
+use crate::cli::sessions::admin_request;
 use crate::cli::{ensure_config_dir, expand_path};
 use crate::config::{Config, McpServerConfig, SandboxConfig};
 use crate::utils::errors::{McpError, McpResult};
+use dialoguer::{Input, Password};
+use serde::Serialize;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Add a new MCP server
 pub async fn add(
@@ -50,6 +54,7 @@ pub async fn add(
         tags: tags.unwrap_or_default(),
         description,
         sandbox: SandboxConfig::default(),
+        ..Default::default()
     };
 
     config.servers.push(server_config);
@@ -62,52 +67,88 @@ pub async fn add(
 }
 
 /// List all MCP servers
-pub async fn list(config_path: &str) -> McpResult<()> {
+pub async fn list(config_path: &str, output: OutputFormat) -> McpResult<()> {
     let path = PathBuf::from(expand_path(config_path));
 
-    if !path.exists() {
+    let config: Config = if path.exists() {
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+        toml::from_str(&content)
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?
+    } else if output.is_human() {
         println!("No configuration file found at {}", path.display());
         return Ok(());
-    }
-
-    let content = tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
-    let config: Config = toml::from_str(&content)
-        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?;
+    } else {
+        Config::default()
+    };
 
-    if config.servers.is_empty() {
+    if config.servers.is_empty() && output.is_human() {
         println!("No MCP servers configured.");
         println!("Use 'mcpo mcp add <name> <command>' to add a server.");
         return Ok(());
     }
 
-    println!("\n{:<20} {:<30} {:<15}", "NAME", "COMMAND", "TAGS");
-    println!("{}", "-".repeat(70));
+    let servers: Vec<ServerSummary> = config
+        .servers
+        .iter()
+        .map(|server| ServerSummary {
+            name: server.name.clone(),
+            command: server.command.clone(),
+            args: server.args.clone(),
+            tags: server.tags.clone(),
+            description: server.description.clone(),
+            url: server.url.clone(),
+            enabled: server.enabled,
+        })
+        .collect();
+
+    print_list(output, "ServerList", &servers, || {
+        let mut table = Table::new()
+            .column("NAME")
+            .column("COMMAND")
+            .column("TAGS")
+            .wide_column("ENABLED")
+            .wide_column("DESCRIPTION");
+        for server in &servers {
+            let command = match &server.url {
+                Some(url) if server.command.is_empty() => url.clone(),
+                _ => std::iter::once(server.command.as_str())
+                    .chain(server.args.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            };
+            table.row(vec![
+                server.name.clone(),
+                command,
+                server.tags.join(", "),
+                server.enabled.to_string(),
+                server.description.clone().unwrap_or_default(),
+            ]);
+        }
+        table
+    })?;
 
-    for server in &config.servers {
-        let cmd_display = if server.args.is_empty() {
-            server.command.clone()
-        } else {
-            format!("{} {}", server.command, server.args.join(" "))
-        };
-        let cmd_display = if cmd_display.len() > 28 {
-            format!("{}...", &cmd_display[..25])
-        } else {
-            cmd_display
-        };
-        let tags = if server.tags.is_empty() {
-            "-".to_string()
-        } else {
-            server.tags.join(", ")
-        };
-        println!("{:<20} {:<30} {:<15}", server.name, cmd_display, tags);
+    if output.is_human() {
+        println!("\nTotal: {} server(s)", servers.len());
     }
-
-    println!("\nTotal: {} server(s)", config.servers.len());
     Ok(())
 }
 
+/// A configured server, as listed by `mcp list --output json|yaml`
+#[derive(Debug, Serialize)]
+pub struct ServerSummary {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    /// Endpoint of remote servers
+    pub url: Option<String>,
+    /// Whether `serve` starts it
+    pub enabled: bool,
+}
+
 /// Remove an MCP server
 pub async fn remove(config_path: &str, name: &str) -> McpResult<()> {
     let path = PathBuf::from(expand_path(config_path));
@@ -182,6 +223,150 @@ pub async fn status(config_path: &str, name: Option<&str>) -> McpResult<()> {
     Ok(())
 }
 
+/// Whether `text` matches a glob where `*` is any run of characters and
+/// `?` any one character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Last `*` seen, and where in `text` it started matching
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Servers whose name or one of whose tags matches `pattern`
+fn matching_servers<'a>(
+    config: &'a mut Config,
+    pattern: &str,
+) -> McpResult<Vec<&'a mut McpServerConfig>> {
+    let servers: Vec<_> = config
+        .servers
+        .iter_mut()
+        .filter(|s| glob_match(pattern, &s.name) || s.tags.iter().any(|tag| glob_match(pattern, tag)))
+        .collect();
+    if servers.is_empty() {
+        return Err(McpError::ServerNotFound(format!("No servers match '{}'", pattern)));
+    }
+    Ok(servers)
+}
+
+async fn load_existing(path: &Path) -> McpResult<Config> {
+    if !path.exists() {
+        return Err(McpError::ConfigError(format!(
+            "Configuration file not found: {}",
+            path.display()
+        )));
+    }
+
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+    toml::from_str(&content)
+        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))
+}
+
+/// Enable or disable every server matched by name or tag
+pub async fn set_enabled(config_path: &str, pattern: &str, enabled: bool) -> McpResult<()> {
+    let path = PathBuf::from(expand_path(config_path));
+    let mut config = load_existing(&path).await?;
+
+    let verb = if enabled { "Enabled" } else { "Disabled" };
+    for server in matching_servers(&mut config, pattern)? {
+        if server.enabled == enabled {
+            println!("  {} is already {}", server.name, verb.to_lowercase());
+        } else {
+            server.enabled = enabled;
+            println!("✓ {} MCP server '{}'", verb, server.name);
+        }
+    }
+
+    save_config(&path, &config).await
+}
+
+/// Add or remove tags on every server matched by name or tag
+pub async fn tag(config_path: &str, pattern: &str, tags: &[String], add: bool) -> McpResult<()> {
+    let path = PathBuf::from(expand_path(config_path));
+    let mut config = load_existing(&path).await?;
+
+    for server in matching_servers(&mut config, pattern)? {
+        if add {
+            for tag in tags {
+                if !server.tags.contains(tag) {
+                    server.tags.push(tag.clone());
+                }
+            }
+        } else {
+            server.tags.retain(|tag| !tags.contains(tag));
+        }
+        println!("✓ {}: tags {}", server.name, server.tags.join(", "));
+    }
+
+    save_config(&path, &config).await
+}
+
+/// Restart every enabled server matched by name or tag on the running
+/// proxy, through the admin API
+pub async fn restart(
+    config_path: &str,
+    pattern: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+) -> McpResult<()> {
+    let path = PathBuf::from(expand_path(config_path));
+    let mut config = load_existing(&path).await?;
+    let names: Vec<String> = matching_servers(&mut config, pattern)?
+        .into_iter()
+        .filter(|s| s.enabled)
+        .map(|s| s.name.clone())
+        .collect();
+
+    let mut failed = 0;
+    for name in &names {
+        let request_path = format!("servers/{}/restart", name);
+        let response = admin_request(config_path, url, token, reqwest::Method::POST, &request_path, None).await;
+        match response {
+            Ok(response) if response.status().is_success() => println!("✓ Restarted MCP server '{}'", name),
+            Ok(_) => {
+                eprintln!("✗ {}: not running, or the admin API is disabled", name);
+                failed += 1;
+            }
+            Err(e) => {
+                eprintln!("✗ {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(McpError::InternalError(format!(
+            "{} of {} server(s) failed to restart",
+            failed,
+            names.len()
+        )));
+    }
+    Ok(())
+}
+
 /// Edit an MCP server (update command, args, env, etc.)
 pub async fn edit(
     config_path: &str,
@@ -236,7 +421,7 @@ pub async fn edit(
 }
 
 fn print_server_details(server: &McpServerConfig) {
-    println!("Server: {}", server.name);
+    println!("Server: {}{}", server.name, if server.enabled { "" } else { " (disabled)" });
     println!("  Command: {} {}", server.command, server.args.join(" "));
     if let Some(desc) = &server.description {
         println!("  Description: {}", desc);
@@ -253,6 +438,74 @@ fn print_server_details(server: &McpServerConfig) {
     println!("  Sandbox: enabled={}, network={}", server.sandbox.enabled, server.sandbox.network);
 }
 
+/// Add a server instantiated from a template
+pub async fn instantiate(
+    config_path: &str,
+    template_name: &str,
+    params: Vec<String>,
+    name: Option<&str>,
+) -> McpResult<()> {
+    let path = PathBuf::from(expand_path(config_path));
+
+    if !path.exists() {
+        return Err(McpError::ConfigError(format!(
+            "Configuration file not found: {}",
+            path.display()
+        )));
+    }
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+    let mut config: Config = toml::from_str(&content)
+        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?;
+
+    let template = config
+        .templates
+        .iter()
+        .find(|t| t.server.name == template_name)
+        .ok_or_else(|| McpError::ConfigError(format!("Template '{}' not found", template_name)))?;
+
+    let mut values = parse_env_vars(params)?;
+    let interactive = atty::is(atty::Stream::Stdin);
+    for parameter in &template.parameters {
+        if values.contains_key(&parameter.name) || parameter.default.is_some() {
+            continue;
+        }
+        if !interactive {
+            return Err(McpError::ConfigError(format!(
+                "Missing parameter '{}'; pass --param {}=<value>",
+                parameter.name, parameter.name
+            )));
+        }
+        let prompt = match &parameter.description {
+            Some(description) => format!("{} ({})", parameter.name, description),
+            None => parameter.name.clone(),
+        };
+        let value = if parameter.secret {
+            Password::new().with_prompt(prompt).interact()?
+        } else {
+            Input::<String>::new().with_prompt(prompt).interact_text()?
+        };
+        values.insert(parameter.name.clone(), value);
+    }
+
+    let server = crate::core::template::instantiate(template, name, &values)?;
+    if config.servers.iter().any(|s| s.name == server.name) {
+        return Err(McpError::ConfigError(format!(
+            "Server '{}' already exists. Use 'mcpo mcp remove {}' first if you want to replace it.",
+            server.name, server.name
+        )));
+    }
+
+    let server_name = server.name.clone();
+    config.servers.push(server);
+    save_config(&path, &config).await?;
+
+    println!("✓ Added MCP server '{}' from template '{}'", server_name, template_name);
+    Ok(())
+}
+
 fn parse_env_vars(env_vars: Vec<String>) -> McpResult<HashMap<String, String>> {
     let mut map = HashMap::new();
     for var in env_vars {
@@ -276,3 +529,19 @@ async fn save_config(path: &PathBuf, config: &Config) -> McpResult<()> {
         .map_err(|e| McpError::ConfigError(format!("Failed to write config: {}", e)))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("github*", "github-enterprise"));
+        assert!(glob_match("*fs*", "local-fs-readonly"));
+        assert!(glob_match("db-?", "db-1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("db-?", "db-10"));
+        assert!(!glob_match("git*hub", "gitlab"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+    }
+}
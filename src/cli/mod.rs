@@ -1,15 +1,31 @@
 //! CLI command implementations
 
 pub mod args;
+pub mod audit;
+pub mod bundle;
 pub mod call;
 pub use call::build_registry;
+pub mod capabilities;
+pub mod config;
 pub mod discover;
+pub mod exit;
+pub mod generate;
+pub mod import_watch;
+pub mod init;
 pub mod install;
 pub mod mcp;
+pub mod output;
+pub mod pipeline;
 pub mod preset;
 pub mod registry;
+pub mod repl;
 pub mod runtime;
+pub mod sandbox;
+pub mod sessions;
+pub mod skill;
 pub mod skill_provider;
+#[cfg(windows)]
+pub mod win_service;
 pub use skill_provider::SkillProvider;
 
 use crate::utils::errors::McpResult;
@@ -0,0 +1,209 @@
+//! Output formats for listing commands
+//!
+//! Listings take `--output table|json|yaml|wide`. JSON and YAML wrap the
+//! items in a versioned envelope, `{ apiVersion, kind, count, items }`,
+//! whose item fields only change with a new `apiVersion`, so scripts can
+//! rely on them. `table` fits columns to the terminal by truncating long
+//! cells; `wide` adds the extra columns and never truncates.
+
+use crate::utils::errors::{McpError, McpResult};
+use serde::Serialize;
+
+/// Version of the JSON and YAML listing envelopes
+pub const API_VERSION: &str = "supermcp/v1";
+
+/// Longest cell shown by `table` before it is cut short
+const MAX_CELL: usize = 40;
+
+/// How a listing is printed
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Aligned columns for reading
+    #[default]
+    Table,
+    /// Versioned JSON envelope for scripts
+    Json,
+    /// Versioned YAML envelope for scripts
+    Yaml,
+    /// Table with every column, untruncated
+    Wide,
+}
+
+impl OutputFormat {
+    /// Whether the output is meant for people rather than scripts
+    pub fn is_human(self) -> bool {
+        matches!(self, OutputFormat::Table | OutputFormat::Wide)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<'a, T> {
+    api_version: &'static str,
+    kind: &'a str,
+    count: usize,
+    items: &'a [T],
+}
+
+/// Columns of a listing; some only appear in `wide`
+pub struct Table {
+    columns: Vec<(&'static str, bool)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a column shown in every table
+    pub fn column(mut self, header: &'static str) -> Self {
+        self.columns.push((header, false));
+        self
+    }
+
+    /// Add a column shown only by `wide`
+    pub fn wide_column(mut self, header: &'static str) -> Self {
+        self.columns.push((header, true));
+        self
+    }
+
+    /// Add a row with a cell for every column, wide ones included
+    pub fn row(&mut self, cells: Vec<String>) {
+        debug_assert_eq!(cells.len(), self.columns.len());
+        self.rows.push(cells);
+    }
+
+    /// Lay the table out for `table` or `wide`
+    pub fn render(&self, wide: bool) -> String {
+        let shown: Vec<usize> = (0..self.columns.len())
+            .filter(|&i| wide || !self.columns[i].1)
+            .collect();
+        let cell = |text: &str| -> String {
+            let text = if text.is_empty() { "-" } else { text };
+            if wide || text.chars().count() <= MAX_CELL {
+                text.to_string()
+            } else {
+                let cut: String = text.chars().take(MAX_CELL - 3).collect();
+                format!("{}...", cut)
+            }
+        };
+        let header: Vec<String> = shown.iter().map(|&i| self.columns[i].0.to_string()).collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| shown.iter().map(|&i| cell(&row[i])).collect())
+            .collect();
+        let widths: Vec<usize> = (0..shown.len())
+            .map(|c| {
+                std::iter::once(&header)
+                    .chain(&rows)
+                    .map(|row| row[c].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&rows) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(text, width)| format!("{:<width$}", text, width = width))
+                .collect();
+            out.push_str(line.join("  ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render a listing; `table` builds the human-readable layout
+pub fn render_list<T: Serialize>(
+    format: OutputFormat,
+    kind: &str,
+    items: &[T],
+    table: impl FnOnce() -> Table,
+) -> McpResult<String> {
+    let envelope = Envelope {
+        api_version: API_VERSION,
+        kind,
+        count: items.len(),
+        items,
+    };
+    match format {
+        OutputFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&envelope)?)),
+        OutputFormat::Yaml => serde_yaml::to_string(&envelope)
+            .map_err(|e| McpError::InternalError(format!("Failed to render YAML: {}", e))),
+        OutputFormat::Table => Ok(table().render(false)),
+        OutputFormat::Wide => Ok(table().render(true)),
+    }
+}
+
+/// Print a listing; `table` builds the human-readable layout
+pub fn print_list<T: Serialize>(
+    format: OutputFormat,
+    kind: &str,
+    items: &[T],
+    table: impl FnOnce() -> Table,
+) -> McpResult<()> {
+    print!("{}", render_list(format, kind, items, table)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new().column("NAME").column("COMMAND").wide_column("TAGS");
+        table.row(vec!["fs".to_string(), "x".repeat(50), "files, local".to_string()]);
+        table.row(vec!["web".to_string(), "fetch".to_string(), String::new()]);
+        table
+    }
+
+    #[test]
+    fn test_table_and_wide() {
+        let narrow = table().render(false);
+        let lines: Vec<&str> = narrow.lines().collect();
+        assert!(lines[0].starts_with("NAME") && !lines[0].contains("TAGS"));
+        assert!(lines[1].ends_with("..."));
+        assert_eq!(lines[1].len(), "NAME  ".len() + MAX_CELL);
+
+        let wide = table().render(true);
+        assert!(wide.lines().next().unwrap().ends_with("TAGS"));
+        assert!(wide.contains(&"x".repeat(50)));
+        assert!(wide.lines().nth(2).unwrap().ends_with('-'));
+    }
+
+    #[test]
+    fn test_envelope() {
+        #[derive(Serialize)]
+        struct Item {
+            name: &'static str,
+        }
+        let items = [Item { name: "fs" }];
+        let json = render_list(OutputFormat::Json, "ServerList", &items, Table::new).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "apiVersion": API_VERSION,
+                "kind": "ServerList",
+                "count": 1,
+                "items": [{ "name": "fs" }],
+            })
+        );
+        let yaml = render_list(OutputFormat::Yaml, "ServerList", &items, Table::new).unwrap();
+        assert!(yaml.contains("apiVersion: supermcp/v1") && yaml.contains("- name: fs"));
+    }
+}
@@ -0,0 +1,485 @@
+//! Tool call pipelines
+//!
+//! `supermcp run pipeline.yaml` calls tools in stages, without an agent:
+//!
+//! ```yaml
+//! name: triage
+//! steps:
+//!   - id: issues
+//!     tool: github.list_issues
+//!     args: { repo: acme/api, state: open }
+//!     retries: 2
+//!   - parallel:
+//!       - id: first
+//!         tool: github.get_issue
+//!         args: { number: "${{ steps.issues.json[0].number }}" }
+//!       - id: titles
+//!         tool: notes.append
+//!         args: { lines: "${{ steps.issues.json[].title }}" }
+//! ```
+//!
+//! A stage is a step or a `parallel` group. Arguments may reference outputs
+//! of earlier stages with `${{ path }}`, where `path` is a jq-style path
+//! into `steps.<id>`: `.field`, `[n]`, and `[]` to map over an array. A
+//! string that is just one reference takes the referenced JSON value;
+//! otherwise references are interpolated as text. Each step's output has
+//! `success`, `data` (the raw tool result), `content`, `text` (its first
+//! text content) and `json` (that text parsed as JSON, when it parses).
+
+use crate::cli::call::{build_registry, resolve_tool};
+use crate::core::provider::{ProviderRegistry, ToolResult};
+use crate::utils::errors::{McpError, McpResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// A pipeline file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pipeline {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub steps: Vec<Stage>,
+}
+
+/// One step, or steps run concurrently
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Stage {
+    Parallel { parallel: Vec<Step> },
+    Step(Step),
+}
+
+impl Stage {
+    fn steps(&self) -> &[Step] {
+        match self {
+            Stage::Parallel { parallel } => parallel,
+            Stage::Step(step) => std::slice::from_ref(step),
+        }
+    }
+}
+
+/// A tool call
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub id: String,
+    /// `provider.tool`, or a tool name offered by one provider
+    pub tool: String,
+    #[serde(default)]
+    pub args: Value,
+    /// Extra attempts after a failure
+    #[serde(default)]
+    pub retries: u32,
+    /// Wait before each retry
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// Carry on with later stages if this step fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+/// Outcome of one step
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub id: String,
+    pub tool: String,
+    pub status: StepStatus,
+    pub attempts: u32,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Outcome of a pipeline run
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub steps: Vec<StepReport>,
+}
+
+/// One segment of a reference path
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    Each,
+}
+
+fn parse_path(path: &str) -> McpResult<Vec<Segment>> {
+    let invalid = || McpError::InvalidRequest(format!("Invalid reference path: {}", path));
+    let mut segments = Vec::new();
+    let mut rest = path.trim().trim_start_matches('.');
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let end = inner.find(']').ok_or_else(invalid)?;
+            segments.push(match inner[..end].trim() {
+                "" => Segment::Each,
+                index => Segment::Index(index.parse().map_err(|_| invalid())?),
+            });
+            rest = inner[end + 1..].trim_start_matches('.');
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(Segment::Field(rest[..end].to_string()));
+            rest = rest[end..].strip_prefix('.').unwrap_or(&rest[end..]);
+        }
+    }
+    Ok(segments)
+}
+
+/// Value at `segments` under `value`, `null` where it is missing
+fn select(value: &Value, segments: &[Segment]) -> Value {
+    let Some((segment, rest)) = segments.split_first() else {
+        return value.clone();
+    };
+    match (segment, value) {
+        (Segment::Field(name), Value::Object(map)) => map.get(name).map_or(Value::Null, |v| select(v, rest)),
+        (Segment::Index(i), Value::Array(items)) => items.get(*i).map_or(Value::Null, |v| select(v, rest)),
+        (Segment::Each, Value::Array(items)) => Value::Array(items.iter().map(|v| select(v, rest)).collect()),
+        _ => Value::Null,
+    }
+}
+
+/// Paths of the `${{ ... }}` references in a string
+fn references(text: &str) -> McpResult<Vec<(std::ops::Range<usize>, &str)>> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("${{") {
+        let start = offset + start;
+        let end = text[start..]
+            .find("}}")
+            .map(|end| start + end + 2)
+            .ok_or_else(|| McpError::InvalidRequest(format!("Unclosed reference in {:?}", text)))?;
+        found.push((start..end, text[start + 3..end - 2].trim()));
+        offset = end;
+    }
+    Ok(found)
+}
+
+/// Replace references in every string of `args` with values from `context`
+fn resolve(args: &Value, context: &Value) -> McpResult<Value> {
+    Ok(match args {
+        Value::String(text) => {
+            let refs = references(text)?;
+            match refs.as_slice() {
+                [] => args.clone(),
+                [(range, path)] if range.len() == text.len() => select(context, &parse_path(path)?),
+                _ => {
+                    let mut resolved = String::new();
+                    let mut last = 0;
+                    for (range, path) in refs {
+                        resolved.push_str(&text[last..range.start]);
+                        match select(context, &parse_path(path)?) {
+                            Value::String(s) => resolved.push_str(&s),
+                            other => resolved.push_str(&other.to_string()),
+                        }
+                        last = range.end;
+                    }
+                    resolved.push_str(&text[last..]);
+                    Value::String(resolved)
+                }
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| resolve(v, context)).collect::<McpResult<_>>()?),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), resolve(v, context)?)))
+                .collect::<McpResult<Map<_, _>>>()?,
+        ),
+        _ => args.clone(),
+    })
+}
+
+fn collect_step_refs<'a>(args: &'a Value, out: &mut Vec<&'a str>) -> McpResult<()> {
+    match args {
+        Value::String(text) => {
+            for (_, path) in references(text)? {
+                out.push(path);
+            }
+        }
+        Value::Array(items) => items.iter().try_for_each(|v| collect_step_refs(v, out))?,
+        Value::Object(map) => map.values().try_for_each(|v| collect_step_refs(v, out))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+impl Pipeline {
+    pub fn parse(content: &str) -> McpResult<Self> {
+        let pipeline: Self = serde_yaml::from_str(content)
+            .map_err(|e| McpError::ConfigError(format!("Invalid pipeline: {}", e)))?;
+        pipeline.validate()?;
+        Ok(pipeline)
+    }
+
+    /// Check ids are unique and references only reach earlier stages
+    fn validate(&self) -> McpResult<()> {
+        let mut earlier: HashSet<&str> = HashSet::new();
+        for stage in &self.steps {
+            for step in stage.steps() {
+                let mut refs = Vec::new();
+                collect_step_refs(&step.args, &mut refs)?;
+                for path in refs {
+                    let segments = parse_path(path)?;
+                    let target = match segments.as_slice() {
+                        [Segment::Field(root), Segment::Field(id), ..] if root == "steps" => id,
+                        _ => {
+                            return Err(McpError::ConfigError(format!(
+                                "Step {} references {}; references start with steps.<id>",
+                                step.id, path
+                            )))
+                        }
+                    };
+                    if !earlier.contains(target.as_str()) {
+                        return Err(McpError::ConfigError(format!(
+                            "Step {} references step {}, which doesn't run before it",
+                            step.id, target
+                        )));
+                    }
+                }
+            }
+            for step in stage.steps() {
+                if !earlier.insert(step.id.as_str()) {
+                    return Err(McpError::ConfigError(format!("Duplicate step id {}", step.id)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A step's output as seen by later references
+fn step_output(result: &ToolResult) -> Value {
+    let text = result.text();
+    let parsed = text.as_deref().and_then(|t| serde_json::from_str::<Value>(t).ok());
+    json!({
+        "success": result.success,
+        "data": result.data,
+        "content": result.content,
+        "text": text,
+        "json": parsed,
+    })
+}
+
+async fn call_step(registry: &ProviderRegistry, step: &Step, args: Value) -> McpResult<ToolResult> {
+    let (provider_name, tool_name) = resolve_tool(registry, &step.tool).await?;
+    let full_tool_name = format!("{}.{}", provider_name, tool_name.replace('_', "-"));
+    let provider = registry
+        .get(&provider_name)
+        .ok_or_else(|| McpError::ServerNotFound(provider_name.clone()))?;
+    let result = provider.call_tool(&full_tool_name, args).await?;
+    let is_error = result.data.as_ref().and_then(|d| d.get("isError")).and_then(Value::as_bool);
+    if !result.success || is_error == Some(true) {
+        let message = result.error.clone().or_else(|| result.text()).unwrap_or_default();
+        return Err(McpError::ToolExecutionError(message));
+    }
+    Ok(result)
+}
+
+async fn run_step(registry: &ProviderRegistry, step: &Step, context: &Value) -> (StepReport, Option<Value>) {
+    let started = Instant::now();
+    let mut report = StepReport {
+        id: step.id.clone(),
+        tool: step.tool.clone(),
+        status: StepStatus::Failed,
+        attempts: 0,
+        duration_ms: 0,
+        arguments: None,
+        output: None,
+        error: None,
+    };
+    let args = match resolve(&step.args, context) {
+        Ok(args) => args,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return (report, None);
+        }
+    };
+    report.arguments = Some(args.clone());
+
+    let mut output = None;
+    while report.attempts <= step.retries {
+        if report.attempts > 0 {
+            tokio::time::sleep(Duration::from_millis(step.retry_delay_ms)).await;
+        }
+        report.attempts += 1;
+        match call_step(registry, step, args.clone()).await {
+            Ok(result) => {
+                let value = step_output(&result);
+                report.status = StepStatus::Succeeded;
+                report.output = Some(value.clone());
+                report.error = None;
+                output = Some(value);
+                break;
+            }
+            Err(e) => {
+                warn!("Step {} failed (attempt {}): {}", step.id, report.attempts, e);
+                report.error = Some(e.to_string());
+            }
+        }
+    }
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    (report, output)
+}
+
+fn skipped(step: &Step) -> StepReport {
+    StepReport {
+        id: step.id.clone(),
+        tool: step.tool.clone(),
+        status: StepStatus::Skipped,
+        attempts: 0,
+        duration_ms: 0,
+        arguments: None,
+        output: None,
+        error: None,
+    }
+}
+
+/// Run every stage in order, stopping after a stage with a failed step
+/// unless that step may continue on error
+pub async fn run(registry: &ProviderRegistry, pipeline: &Pipeline) -> PipelineReport {
+    let started = Instant::now();
+    let mut outputs = Map::new();
+    let mut reports = Vec::new();
+    let mut halted = false;
+
+    for stage in &pipeline.steps {
+        if halted {
+            reports.extend(stage.steps().iter().map(skipped));
+            continue;
+        }
+        let context = json!({ "steps": outputs });
+        let results =
+            futures::future::join_all(stage.steps().iter().map(|step| run_step(registry, step, &context))).await;
+        for (step, (report, output)) in stage.steps().iter().zip(results) {
+            info!("Step {}: {:?}", step.id, report.status);
+            if report.status == StepStatus::Failed && !step.continue_on_error {
+                halted = true;
+            }
+            outputs.insert(step.id.clone(), output.unwrap_or(Value::Null));
+            reports.push(report);
+        }
+    }
+
+    PipelineReport {
+        name: pipeline.name.clone(),
+        success: reports.iter().all(|r| r.status == StepStatus::Succeeded),
+        duration_ms: started.elapsed().as_millis() as u64,
+        steps: reports,
+    }
+}
+
+/// Run a pipeline file and print its JSON report
+pub async fn execute(config_path: Option<&str>, path: &str, output: Option<&str>) -> McpResult<()> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read pipeline {}: {}", path, e)))?;
+    let pipeline = Pipeline::parse(&content)?;
+    let registry = build_registry(config_path, None, None, None, None).await?;
+
+    let report = run(&registry, &pipeline).await;
+    let json = serde_json::to_string_pretty(&report)?;
+    match output {
+        Some(output) => tokio::fs::write(output, &json).await?,
+        None => println!("{}", json),
+    }
+    if !report.success {
+        let failed: Vec<_> = report
+            .steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Failed)
+            .map(|s| s.id.as_str())
+            .collect();
+        return Err(McpError::ToolExecutionError(format!(
+            "Pipeline failed at step {}",
+            failed.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_references() {
+        let context = json!({ "steps": { "list": { "json": { "items": [
+            { "name": "a", "size": 1 },
+            { "name": "b", "size": 2 },
+        ] } } } });
+        let args = json!({
+            "first": "${{ steps.list.json.items[0].name }}",
+            "names": "${{ .steps.list.json.items[].name }}",
+            "size": "${{ steps.list.json.items[1].size }}",
+            "label": "${{ steps.list.json.items[0].name }}-${{ steps.list.json.items[1].size }}",
+            "missing": "${{ steps.list.json.nope }}",
+        });
+        assert_eq!(
+            resolve(&args, &context).unwrap(),
+            json!({
+                "first": "a",
+                "names": ["a", "b"],
+                "size": 2,
+                "label": "a-2",
+                "missing": null,
+            })
+        );
+        assert!(resolve(&json!("${{ steps.x"), &context).is_err());
+    }
+
+    #[test]
+    fn test_parse_pipeline() {
+        let pipeline = Pipeline::parse(
+            r#"
+name: demo
+steps:
+  - id: list
+    tool: fs.list
+    retries: 2
+  - parallel:
+      - id: a
+        tool: fs.read
+        args: { path: "${{ steps.list.json[0] }}" }
+      - id: b
+        tool: fs.stat
+"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.steps.len(), 2);
+        assert!(matches!(&pipeline.steps[1], Stage::Parallel { parallel } if parallel.len() == 2));
+        assert_eq!(pipeline.steps[0].steps()[0].retries, 2);
+
+        // References must reach an earlier stage
+        let same_stage = r#"
+steps:
+  - parallel:
+      - { id: a, tool: fs.list }
+      - { id: b, tool: fs.read, args: { path: "${{ steps.a.text }}" } }
+"#;
+        assert!(Pipeline::parse(same_stage).is_err());
+        let duplicate = "steps:\n  - { id: a, tool: x }\n  - { id: a, tool: y }\n";
+        assert!(Pipeline::parse(duplicate).is_err());
+    }
+}
@@ -1,8 +1,12 @@
 //! Preset management commands
 
+use crate::cli::exit::{print_result, ExitStatus};
+use crate::cli::output::{print_list, OutputFormat, Table};
 use crate::cli::{ensure_config_dir, expand_path};
 use crate::config::{Config, PresetConfig};
 use crate::utils::errors::{McpError, McpResult};
+use serde::Serialize;
+use serde_json::json;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
@@ -84,52 +88,77 @@ pub async fn create(
 }
 
 /// List all presets
-pub async fn list(config_path: &str) -> McpResult<()> {
+pub async fn list(config_path: &str, output: OutputFormat) -> McpResult<()> {
     let path = PathBuf::from(expand_path(config_path));
 
-    if !path.exists() {
+    let config: Config = if path.exists() {
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+        toml::from_str(&content)
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?
+    } else if output.is_human() {
         println!("No configuration file found at {}", path.display());
         return Ok(());
-    }
-
-    let content = tokio::fs::read_to_string(&path)
-        .await
-        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
-    let config: Config = toml::from_str(&content)
-        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?;
+    } else {
+        Config::default()
+    };
 
-    if config.presets.is_empty() {
+    if config.presets.is_empty() && output.is_human() {
         println!("No presets configured.");
         println!("Use 'mcpo preset create <name>' to create a preset.");
         return Ok(());
     }
 
-    println!("\n{:<20} {:<30} {:<30}", "NAME", "TAGS", "DESCRIPTION");
-    println!("{}", "-".repeat(85));
+    let presets: Vec<PresetSummary> = config
+        .presets
+        .iter()
+        .map(|preset| PresetSummary {
+            name: preset.name.clone(),
+            tags: preset.tags.clone(),
+            description: preset.description.clone(),
+            servers: config
+                .servers
+                .iter()
+                .filter(|s| s.tags.iter().any(|tag| preset.tags.contains(tag)))
+                .map(|s| s.name.clone())
+                .collect(),
+        })
+        .collect();
 
-    for preset in &config.presets {
-        let tags = preset.tags.join(", ");
-        let tags_display = if tags.len() > 28 {
-            format!("{}...", &tags[..25])
-        } else {
-            tags
-        };
-        let desc = preset.description.as_deref().unwrap_or("-");
-        let desc_display = if desc.len() > 28 {
-            format!("{}...", &desc[..25])
-        } else {
-            desc.to_string()
-        };
-        println!(
-            "{:<20} {:<30} {:<30}",
-            preset.name, tags_display, desc_display
-        );
-    }
+    print_list(output, "PresetList", &presets, || {
+        let mut table = Table::new()
+            .column("NAME")
+            .column("TAGS")
+            .column("DESCRIPTION")
+            .wide_column("SERVERS");
+        for preset in &presets {
+            table.row(vec![
+                preset.name.clone(),
+                preset.tags.join(", "),
+                preset.description.clone().unwrap_or_default(),
+                preset.servers.join(", "),
+            ]);
+        }
+        table
+    })?;
 
-    println!("\nTotal: {} preset(s)", config.presets.len());
+    if output.is_human() {
+        println!("\nTotal: {} preset(s)", presets.len());
+    }
     Ok(())
 }
 
+/// A preset, as listed by `preset list --output json|yaml`
+#[derive(Debug, Serialize)]
+pub struct PresetSummary {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    /// Servers carrying one of the preset's tags
+    pub servers: Vec<String>,
+}
+
 /// Edit a preset
 pub async fn edit(
     config_path: &str,
@@ -172,7 +201,9 @@ pub async fn edit(
 }
 
 /// Test a preset (shows which servers would be included)
-pub async fn test(config_path: &str, name: &str) -> McpResult<()> {
+///
+/// `NotFound` when there is no such preset, `Empty` when no server matches.
+pub async fn test(config_path: &str, name: &str, quiet: bool) -> McpResult<ExitStatus> {
     let path = PathBuf::from(expand_path(config_path));
 
     if !path.exists() {
@@ -188,17 +219,14 @@ pub async fn test(config_path: &str, name: &str) -> McpResult<()> {
     let config: Config = toml::from_str(&content)
         .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))?;
 
-    let preset = config
-        .presets
-        .iter()
-        .find(|p| p.name == name)
-        .ok_or_else(|| McpError::ConfigError(format!("Preset '{}' not found", name)))?;
-
-    println!("\nPreset: {}", preset.name);
-    if let Some(desc) = &preset.description {
-        println!("Description: {}", desc);
-    }
-    println!("Tags: {}", preset.tags.join(", "));
+    let Some(preset) = config.presets.iter().find(|p| p.name == name) else {
+        if quiet {
+            print_result(ExitStatus::NotFound, json!({ "preset": name }));
+        } else {
+            eprintln!("Error: Preset '{}' not found", name);
+        }
+        return Ok(ExitStatus::NotFound);
+    };
 
     // Find matching servers
     let preset_tags: std::collections::HashSet<_> = preset.tags.iter().cloned().collect();
@@ -207,6 +235,23 @@ pub async fn test(config_path: &str, name: &str) -> McpResult<()> {
         .iter()
         .filter(|s| s.tags.iter().any(|tag| preset_tags.contains(tag)))
         .collect();
+    let status = if matching_servers.is_empty() {
+        ExitStatus::Empty
+    } else {
+        ExitStatus::Ok
+    };
+
+    if quiet {
+        let servers: Vec<&str> = matching_servers.iter().map(|s| s.name.as_str()).collect();
+        print_result(status, json!({ "preset": name, "servers": servers }));
+        return Ok(status);
+    }
+
+    println!("\nPreset: {}", preset.name);
+    if let Some(desc) = &preset.description {
+        println!("Description: {}", desc);
+    }
+    println!("Tags: {}", preset.tags.join(", "));
 
     if matching_servers.is_empty() {
         println!("\n⚠ No servers match this preset's tags.");
@@ -228,7 +273,7 @@ pub async fn test(config_path: &str, name: &str) -> McpResult<()> {
         }
     }
 
-    Ok(())
+    Ok(status)
 }
 
 /// Remove a preset
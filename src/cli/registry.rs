@@ -1,25 +1,28 @@
 //! Registry commands for searching and installing MCP servers
 
+use crate::cli::output::{print_list, OutputFormat, Table};
 use crate::cli::{ensure_config_dir, expand_path};
 use crate::config::{Config, McpServerConfig, SandboxConfig};
 use crate::registry::{RegistryClient, RegistryEntry};
 use crate::registry::types::RegistryConfig;
 use crate::utils::errors::{McpError, McpResult};
+use serde::Serialize;
 use shellexpand::tilde;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
-fn create_registry_config(config: &Config) -> RegistryConfig {
+pub(crate) fn create_registry_config(config: &Config) -> RegistryConfig {
     let cache_dir = tilde(&config.registry.cache_dir).to_string();
     RegistryConfig {
         url: config.registry.url.clone(),
         cache_dir: PathBuf::from(cache_dir),
         cache_ttl_hours: config.registry.cache_ttl_hours,
+        proxy: config.proxy.clone(),
     }
 }
 
 /// Search for MCP servers in the registry
-pub async fn search(config_path: &str, query: &str) -> McpResult<()> {
+pub async fn search(config_path: &str, query: &str, output: OutputFormat) -> McpResult<()> {
     let path = PathBuf::from(expand_path(config_path));
 
     // Load config to get registry settings
@@ -36,33 +39,80 @@ pub async fn search(config_path: &str, query: &str) -> McpResult<()> {
 
     let client = RegistryClient::new(registry_config)?;
 
-    println!("Searching registry for: '{}'...\n", query);
+    if output.is_human() {
+        println!("Searching registry for: '{}'...\n", query);
+    }
 
     match client.search(query).await {
         Ok(results) => {
-            if results.entries.is_empty() {
+            if results.entries.is_empty() && output.is_human() {
                 println!("No servers found matching '{}'.", query);
                 return Ok(());
             }
 
-            println!("Found {} result(s):\n", results.total);
+            let entries: Vec<RegistrySearchResult> = results
+                .entries
+                .iter()
+                .map(|entry| RegistrySearchResult {
+                    name: entry.name.clone(),
+                    version: entry.version.clone(),
+                    description: entry.description.clone(),
+                    author: entry.author.clone(),
+                    license: entry.license.clone(),
+                    tags: entry.tags.clone(),
+                    repository: entry.repository.clone(),
+                })
+                .collect();
+
+            print_list(output, "RegistrySearchResultList", &entries, || {
+                let mut table = Table::new()
+                    .column("NAME")
+                    .column("VERSION")
+                    .column("DESCRIPTION")
+                    .wide_column("TAGS")
+                    .wide_column("AUTHOR")
+                    .wide_column("LICENSE");
+                for entry in &entries {
+                    table.row(vec![
+                        entry.name.clone(),
+                        entry.version.clone(),
+                        entry.description.clone(),
+                        entry.tags.join(", "),
+                        entry.author.clone(),
+                        entry.license.clone(),
+                    ]);
+                }
+                table
+            })?;
 
-            for entry in &results.entries {
-                print_entry_summary(entry);
-                println!();
+            if output.is_human() {
+                println!("\nFound {} result(s)", results.total);
             }
-
             Ok(())
         }
         Err(e) => {
-            println!("Search failed: {}", e);
-            println!("\nNote: The registry service may not be available.");
-            println!("You can still add servers manually using 'mcpo mcp add <name> <command>'");
+            if output.is_human() {
+                println!("Search failed: {}", e);
+                println!("\nNote: The registry service may not be available.");
+                println!("You can still add servers manually using 'mcpo mcp add <name> <command>'");
+            }
             Err(e)
         }
     }
 }
 
+/// A registry entry, as listed by `registry search --output json|yaml`
+#[derive(Debug, Serialize)]
+pub struct RegistrySearchResult {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub author: String,
+    pub license: String,
+    pub tags: Vec<String>,
+    pub repository: Option<String>,
+}
+
 /// Install an MCP server from the registry
 pub async fn install(config_path: &str, name: &str) -> McpResult<()> {
     let path = PathBuf::from(expand_path(config_path));
@@ -125,6 +175,7 @@ pub async fn install(config_path: &str, name: &str) -> McpResult<()> {
                 tags: entry.tags,
                 description: Some(entry.description),
                 sandbox: SandboxConfig::default(),
+                ..Default::default()
             };
 
             config.servers.push(server_config);
@@ -216,13 +267,6 @@ pub async fn refresh(config_path: &str) -> McpResult<()> {
     }
 }
 
-fn print_entry_summary(entry: &RegistryEntry) {
-    println!("  {} v{}", entry.name, entry.version);
-    println!("    {}", entry.description);
-    println!("    Tags: {}", entry.tags.join(", "));
-    println!("    Author: {} | License: {}", entry.author, entry.license);
-}
-
 fn confirm_install() -> McpResult<bool> {
     print!("Run install command now? [y/N]: ");
     io::stdout().flush().map_err(McpError::Io)?;
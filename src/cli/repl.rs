@@ -0,0 +1,268 @@
+//! Interactive tool-calling shell
+//!
+//! `supermcp repl` connects to the same providers as `supermcp call` once,
+//! then reads calls line by line in `call`'s syntax. Tab completes provider
+//! and tool names, then argument names from the tool's input schema; the
+//! arrow keys walk a history kept across sessions.
+
+use crate::cli::call::{build_registry, call_tool, parse_env_vars, print_tool};
+use crate::core::provider::{ProviderRegistry, Tool};
+use crate::utils::errors::McpResult;
+use dialoguer::{Completion, History, Input};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Commands besides tool calls
+const COMMANDS: &[&str] = &["help", "tools", "providers", "json", "dry-run", "exit"];
+
+/// Most history entries kept
+const MAX_HISTORY: usize = 1000;
+
+/// Completes the word under the cursor from the known tools
+struct ToolCompleter {
+    /// `provider.tool` names and the argument names of each
+    tools: Vec<(String, Vec<String>)>,
+}
+
+impl ToolCompleter {
+    fn new(tools: &[Tool]) -> Self {
+        let tools = tools
+            .iter()
+            .map(|tool| {
+                let name = format!("{}.{}", tool.provider, tool.snake_name());
+                let params = tool.parameters.iter().map(|p| p.name.clone()).collect();
+                (name, params)
+            })
+            .collect();
+        Self { tools }
+    }
+}
+
+/// Longest prefix shared by every candidate
+fn common_prefix<'a>(mut candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let first = candidates.next()?;
+    let mut len = first.len();
+    for candidate in candidates {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(len);
+    }
+    Some(first[..len].to_string())
+}
+
+impl Completion for ToolCompleter {
+    fn get(&self, input: &str) -> Option<String> {
+        let (head, word) = match input.rfind(char::is_whitespace) {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input),
+        };
+        if word.contains([':', '=']) {
+            return None;
+        }
+
+        let completed = if head.trim().is_empty() {
+            let names = COMMANDS.iter().copied().chain(self.tools.iter().map(|(name, _)| name.as_str()));
+            common_prefix(names.filter(|name| name.starts_with(word)))?
+        } else {
+            let tool = head.split_whitespace().next()?;
+            let (_, params) = self.tools.iter().find(|(name, _)| name == tool)?;
+            let given: Vec<&str> = head
+                .split_whitespace()
+                .skip(1)
+                .filter_map(|arg| arg.split([':', '=']).next())
+                .collect();
+            let mut matches = params
+                .iter()
+                .filter(|p| p.starts_with(word) && !given.contains(&p.as_str()));
+            match (matches.next(), matches.clone().next()) {
+                (Some(only), None) => format!("{}:", only),
+                (Some(first), Some(_)) => {
+                    common_prefix(std::iter::once(first.as_str()).chain(matches.map(String::as_str)))?
+                }
+                (None, _) => return None,
+            }
+        };
+        (completed.len() > word.len()).then(|| format!("{}{}", head, completed))
+    }
+}
+
+/// History that is also appended to a file
+struct ReplHistory {
+    entries: VecDeque<String>,
+    path: Option<PathBuf>,
+}
+
+impl ReplHistory {
+    fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|content| content.lines().rev().take(MAX_HISTORY).map(String::from).collect())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+}
+
+impl History<String> for ReplHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, line: &String) {
+        if line.trim().is_empty() || self.entries.front() == Some(line) {
+            return;
+        }
+        self.entries.push_front(line.clone());
+        self.entries.truncate(MAX_HISTORY);
+        if let Some(path) = &self.path {
+            let appended = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", line));
+            if let Err(e) = appended {
+                tracing::debug!("Could not save REPL history to {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join("supermcp");
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("repl_history"))
+}
+
+fn print_help() {
+    println!("Call a tool:        provider.tool key:value key2=value2");
+    println!("                    \"provider.tool(key: value)\"");
+    println!("  tools [provider]  List tools, with their parameters");
+    println!("  providers         List providers");
+    println!("  json              Toggle JSON output");
+    println!("  dry-run           Toggle dry runs (validate without calling)");
+    println!("  exit              Leave the REPL (or Ctrl-D)");
+    println!("Tab completes tool and argument names; Up/Down walk the history.");
+}
+
+async fn print_tools(registry: &ProviderRegistry, provider: Option<&str>) -> McpResult<()> {
+    let tools = registry.list_all_tools().await?;
+    for tool in tools.iter().filter(|t| provider.is_none_or(|p| t.provider == p)) {
+        print_tool(tool, true);
+    }
+    Ok(())
+}
+
+/// Run the REPL until `exit` or end of input
+pub async fn execute(
+    config_path: Option<&str>,
+    stdio_cmd: Option<&str>,
+    http_url: Option<&str>,
+    skill_name: Option<&str>,
+    env_vars: Vec<String>,
+    json_output: bool,
+) -> McpResult<()> {
+    let env_vars_map = parse_env_vars(&env_vars)?;
+    let registry = build_registry(config_path, stdio_cmd, http_url, skill_name, Some(env_vars_map)).await?;
+    let tools = registry.list_all_tools().await?;
+    let completer = ToolCompleter::new(&tools);
+    let mut history = ReplHistory::load(history_path());
+    let mut json_output = json_output;
+    let mut dry_run = false;
+
+    println!(
+        "supermcp repl: {} tools from {} providers. Type 'help' for commands.",
+        tools.len(),
+        registry.list().len()
+    );
+    // Ends at the end of input, or without a terminal
+    while let Ok(line) = Input::<String>::new()
+        .with_prompt(if dry_run { "supermcp (dry-run)" } else { "supermcp" })
+        .allow_empty(true)
+        .history_with(&mut history)
+        .completion_with(&completer)
+        .interact_text()
+    {
+        let words = match shell_words::split(&line) {
+            Ok(words) => words,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                continue;
+            }
+        };
+        let Some((command, args)) = words.split_first() else {
+            continue;
+        };
+
+        let result = match command.as_str() {
+            "exit" | "quit" => break,
+            "help" => {
+                print_help();
+                Ok(())
+            }
+            "tools" => print_tools(&registry, args.first().map(String::as_str)).await,
+            "providers" => {
+                for name in registry.list() {
+                    println!("  {}", name);
+                }
+                Ok(())
+            }
+            "json" => {
+                json_output = !json_output;
+                println!("JSON output {}", if json_output { "on" } else { "off" });
+                Ok(())
+            }
+            "dry-run" => {
+                dry_run = !dry_run;
+                println!("Dry runs {}", if dry_run { "on" } else { "off" });
+                Ok(())
+            }
+            target => call_tool(&registry, target, args.to_vec(), json_output, dry_run).await,
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completer() -> ToolCompleter {
+        ToolCompleter {
+            tools: vec![
+                ("fs.read_file".to_string(), vec!["path".to_string(), "encoding".to_string()]),
+                ("fs.read_dir".to_string(), vec!["path".to_string(), "pattern".to_string()]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_completes_tool_names() {
+        let completer = completer();
+        assert_eq!(completer.get("f").as_deref(), Some("fs.read_"));
+        assert_eq!(completer.get("fs.read_f").as_deref(), Some("fs.read_file"));
+        assert_eq!(completer.get("he").as_deref(), Some("help"));
+        assert_eq!(completer.get("fs.read_").as_deref(), None);
+        assert_eq!(completer.get("zz"), None);
+    }
+
+    #[test]
+    fn test_completes_argument_names() {
+        let completer = completer();
+        assert_eq!(completer.get("fs.read_dir pa").as_deref(), Some("fs.read_dir pat"));
+        assert_eq!(
+            completer.get("fs.read_dir path:/tmp pa").as_deref(),
+            Some("fs.read_dir path:/tmp pattern:")
+        );
+        assert_eq!(completer.get("fs.read_file e").as_deref(), Some("fs.read_file encoding:"));
+        assert_eq!(completer.get("fs.read_file path:/t"), None);
+        assert_eq!(completer.get("fs.unknown p"), None);
+    }
+}
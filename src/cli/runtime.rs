@@ -1,7 +1,10 @@
 //! CLI commands for runtime management
 
+use crate::cli::exit::{print_result, ExitStatus};
 use crate::cli::expand_path;
+use crate::cli::output::{print_list, OutputFormat, Table};
 use crate::utils::errors::McpResult;
+use serde::Serialize;
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -9,9 +12,11 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, PartialEq)]
 pub enum RuntimeTypeCli {
     PythonWasm,
+    PythonUv,
     NodePnpm,
     NodeNpm,
     NodeBun,
+    NodeDeno,
 }
 
 impl std::str::FromStr for RuntimeTypeCli {
@@ -20,10 +25,12 @@ impl std::str::FromStr for RuntimeTypeCli {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "python_wasm" | "python-wasm" | "pythonwasm" | "python" => Ok(RuntimeTypeCli::PythonWasm),
+            "python_uv" | "python-uv" | "pythonuv" | "uv" => Ok(RuntimeTypeCli::PythonUv),
             "node_pnpm" | "node-pnpm" | "nodepnpm" | "pnpm" => Ok(RuntimeTypeCli::NodePnpm),
             "node_npm" | "node-npm" | "nodenpm" | "npm" => Ok(RuntimeTypeCli::NodeNpm),
             "node_bun" | "node-bun" | "nodebun" | "bun" => Ok(RuntimeTypeCli::NodeBun),
-            _ => Err(format!("Unknown runtime type: {}. Valid types are: python_wasm, node_pnpm, node_npm, node_bun", s)),
+            "node_deno" | "node-deno" | "nodedeno" | "deno" => Ok(RuntimeTypeCli::NodeDeno),
+            _ => Err(format!("Unknown runtime type: {}. Valid types are: python_wasm, python_uv, node_pnpm, node_npm, node_bun, node_deno", s)),
         }
     }
 }
@@ -33,9 +40,11 @@ impl RuntimeTypeCli {
     pub fn to_config_type(&self) -> crate::runtime::types::RuntimeType {
         match self {
             RuntimeTypeCli::PythonWasm => crate::runtime::types::RuntimeType::PythonWasm,
+            RuntimeTypeCli::PythonUv => crate::runtime::types::RuntimeType::PythonUv,
             RuntimeTypeCli::NodePnpm => crate::runtime::types::RuntimeType::NodePnpm,
             RuntimeTypeCli::NodeNpm => crate::runtime::types::RuntimeType::NodeNpm,
             RuntimeTypeCli::NodeBun => crate::runtime::types::RuntimeType::NodeBun,
+            RuntimeTypeCli::NodeDeno => crate::runtime::types::RuntimeType::NodeDeno,
         }
     }
 
@@ -43,9 +52,11 @@ impl RuntimeTypeCli {
     pub fn name(&self) -> &'static str {
         match self {
             RuntimeTypeCli::PythonWasm => "Python (WASM)",
+            RuntimeTypeCli::PythonUv => "Python (uv)",
             RuntimeTypeCli::NodePnpm => "Node.js (pnpm)",
             RuntimeTypeCli::NodeNpm => "Node.js (npm)",
             RuntimeTypeCli::NodeBun => "Node.js (bun)",
+            RuntimeTypeCli::NodeDeno => "Deno",
         }
     }
 }
@@ -104,6 +115,7 @@ pub async fn add(
             filesystem: crate::runtime::types::RuntimeFilesystemAccess::ReadOnly,
         },
         enabled: true,
+        lock_dir: None,
     };
 
     config.runtimes.push(runtime_config);
@@ -116,70 +128,81 @@ pub async fn add(
 }
 
 /// List all runtimes
-pub async fn list(config_path: &str, json_output: bool) -> McpResult<()> {
+pub async fn list(config_path: &str, output: OutputFormat) -> McpResult<()> {
     use crate::config::ConfigManager;
 
     let expanded_path = expand_path(config_path);
     let config_manager = ConfigManager::new(&expanded_path).await?;
     let config = config_manager.get_config();
 
-    if config.runtimes.is_empty() {
+    if config.runtimes.is_empty() && output.is_human() {
         println!("No runtimes configured.");
         println!("\nAdd a runtime with:");
         println!("  supermcp runtime add <name> <type>");
         return Ok(());
     }
 
-    if json_output {
-        let json_runtimes: Vec<_> = config.runtimes.iter().map(|r| {
-            json!({
-                "name": r.name,
-                "type": format!("{:?}", r.type_),
-                "enabled": r.enabled,
-                "packages": r.packages,
-                "resource_limits": {
-                    "max_memory_mb": r.resource_limits.max_memory_mb,
-                    "max_cpu_percent": r.resource_limits.max_cpu_percent,
-                    "timeout_seconds": r.resource_limits.timeout_seconds,
-                    "network_access": r.resource_limits.network_access,
-                }
-            })
-        }).collect();
-
-        let output = json!({
-            "runtimes": json_runtimes,
-            "count": config.runtimes.len()
-        });
-
-        println!("{}", serde_json::to_string_pretty(&output)?);
-    } else {
-        println!("Configured Runtimes:");
-        println!("--------------------");
-
-        for (i, runtime) in config.runtimes.iter().enumerate() {
-            let status = if runtime.enabled { "enabled" } else { "disabled" };
+    let runtimes: Vec<RuntimeSummary> = config
+        .runtimes
+        .iter()
+        .map(|r| RuntimeSummary {
+            name: r.name.clone(),
+            type_: r.type_.clone(),
+            enabled: r.enabled,
+            packages: r.packages.clone(),
+            max_memory_mb: r.resource_limits.max_memory_mb,
+            max_cpu_percent: r.resource_limits.max_cpu_percent,
+            timeout_seconds: r.resource_limits.timeout_seconds,
+            network_access: r.resource_limits.network_access,
+        })
+        .collect();
+
+    print_list(output, "RuntimeList", &runtimes, || {
+        let mut table = Table::new()
+            .column("NAME")
+            .column("TYPE")
+            .column("STATUS")
+            .column("PACKAGES")
+            .wide_column("MEMORY")
+            .wide_column("CPU")
+            .wide_column("TIMEOUT")
+            .wide_column("NETWORK");
+        for runtime in &runtimes {
             let type_name = match runtime.type_ {
                 crate::runtime::types::RuntimeType::PythonWasm => "Python (WASM)",
+                crate::runtime::types::RuntimeType::PythonUv => "Python (uv)",
                 crate::runtime::types::RuntimeType::NodePnpm => "Node.js (pnpm)",
                 crate::runtime::types::RuntimeType::NodeNpm => "Node.js (npm)",
                 crate::runtime::types::RuntimeType::NodeBun => "Node.js (bun)",
+                crate::runtime::types::RuntimeType::NodeDeno => "Deno",
             };
-
-            println!("{}. {} ({}) - {}", i + 1, runtime.name, type_name, status);
-
-            if !runtime.packages.is_empty() {
-                println!("   Packages: {}", runtime.packages.join(", "));
-            }
-
-            println!("   Memory limit: {} MB", runtime.resource_limits.max_memory_mb);
-            println!("   CPU limit: {}%", runtime.resource_limits.max_cpu_percent);
-            println!("   Timeout: {}s", runtime.resource_limits.timeout_seconds);
-            println!("   Network access: {}", if runtime.resource_limits.network_access { "allowed" } else { "blocked" });
-            println!();
+            table.row(vec![
+                runtime.name.clone(),
+                type_name.to_string(),
+                if runtime.enabled { "enabled" } else { "disabled" }.to_string(),
+                runtime.packages.join(", "),
+                format!("{} MB", runtime.max_memory_mb),
+                format!("{}%", runtime.max_cpu_percent),
+                format!("{}s", runtime.timeout_seconds),
+                if runtime.network_access { "allowed" } else { "blocked" }.to_string(),
+            ]);
         }
-    }
+        table
+    })
+}
 
-    Ok(())
+/// A runtime, as listed by `runtime list --output json|yaml`
+#[derive(Debug, Serialize)]
+pub struct RuntimeSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: crate::runtime::types::RuntimeType,
+    pub enabled: bool,
+    pub packages: Vec<String>,
+    pub max_memory_mb: u64,
+    pub max_cpu_percent: u32,
+    pub timeout_seconds: u64,
+    pub network_access: bool,
 }
 
 /// Remove a runtime
@@ -219,9 +242,11 @@ pub async fn info(config_path: &str, name: &str) -> McpResult<()> {
 
     let type_name = match runtime.type_ {
         crate::runtime::types::RuntimeType::PythonWasm => "Python (WASM)",
+        crate::runtime::types::RuntimeType::PythonUv => "Python (uv)",
         crate::runtime::types::RuntimeType::NodePnpm => "Node.js (pnpm)",
         crate::runtime::types::RuntimeType::NodeNpm => "Node.js (npm)",
         crate::runtime::types::RuntimeType::NodeBun => "Node.js (bun)",
+        crate::runtime::types::RuntimeType::NodeDeno => "Deno",
     };
 
     println!("Runtime: {}", runtime.name);
@@ -239,6 +264,10 @@ pub async fn info(config_path: &str, name: &str) -> McpResult<()> {
         println!("\nWorking directory: {}", wd);
     }
 
+    if let Some(ref lock_dir) = runtime.lock_dir {
+        println!("Lock directory: {}", lock_dir);
+    }
+
     println!("\nResource Limits:");
     println!("  Memory: {} MB", runtime.resource_limits.max_memory_mb);
     println!("  CPU: {}%", runtime.resource_limits.max_cpu_percent);
@@ -256,49 +285,74 @@ pub async fn info(config_path: &str, name: &str) -> McpResult<()> {
 }
 
 /// Validate all runtimes
-pub async fn validate(config_path: &str) -> McpResult<()> {
+///
+/// `Invalid` when any runtime fails, `Empty` when none are configured.
+pub async fn validate(config_path: &str, quiet: bool) -> McpResult<ExitStatus> {
     use crate::config::ConfigManager;
     use crate::runtime::RuntimeManager;
-    use crate::utils::errors::McpError;
 
     let expanded_path = expand_path(config_path);
     let config_manager = ConfigManager::new(&expanded_path).await?;
     let config = config_manager.get_config();
 
     if config.runtimes.is_empty() {
-        println!("No runtimes configured to validate.");
-        return Ok(());
+        if quiet {
+            print_result(ExitStatus::Empty, json!({ "runtimes": [] }));
+        } else {
+            println!("No runtimes configured to validate.");
+        }
+        return Ok(ExitStatus::Empty);
     }
 
-    println!("Validating runtimes...\n");
+    if !quiet {
+        println!("Validating runtimes...\n");
+    }
 
     let manager = RuntimeManager::new();
 
-    // Register all runtimes
+    // Register all runtimes; one that cannot be registered has failed too
+    let mut results = Vec::new();
     for runtime_config in &config.runtimes {
-        let _ = manager.register_auto(runtime_config.clone());
+        if let Err(e) = manager.register_auto(runtime_config.clone()) {
+            results.push((runtime_config.name.clone(), Err(e.to_string())));
+        }
+    }
+    results.extend(
+        manager
+            .validate_all()
+            .await
+            .into_iter()
+            .map(|(name, result)| (name, result.map_err(|e| e.to_string()))),
+    );
+
+    let all_valid = results.iter().all(|(_, result)| result.is_ok());
+    let status = if all_valid { ExitStatus::Ok } else { ExitStatus::Invalid };
+
+    if quiet {
+        let runtimes: Vec<_> = results
+            .iter()
+            .map(|(name, result)| {
+                json!({ "name": name, "valid": result.is_ok(), "error": result.as_ref().err() })
+            })
+            .collect();
+        print_result(status, json!({ "runtimes": runtimes }));
+        return Ok(status);
     }
 
-    let results = manager.validate_all().await;
-
-    let mut all_valid = true;
-    for (name, result) in results {
+    for (name, result) in &results {
         match result {
             Ok(()) => println!("[OK] {} - validated successfully", name),
-            Err(e) => {
-                println!("[FAIL] {} - {}", name, e);
-                all_valid = false;
-            }
+            Err(e) => println!("[FAIL] {} - {}", name, e),
         }
     }
 
     if all_valid {
         println!("\nAll runtimes are valid.");
     } else {
-        return Err(McpError::ConfigError("Some runtimes failed validation".to_string()));
+        println!("\nSome runtimes failed validation.");
     }
 
-    Ok(())
+    Ok(status)
 }
 
 /// Execute a script using a runtime
@@ -363,3 +417,84 @@ pub async fn execute(
 
     Ok(())
 }
+
+/// Resolve a runtime's packages and save the manifest and lockfile
+pub async fn freeze(config_path: &str, name: &str, lock_dir: Option<String>) -> McpResult<()> {
+    use crate::config::ConfigManager;
+    use crate::runtime::lockfile::default_lock_dir;
+    use crate::runtime::RuntimeManager;
+    use crate::utils::errors::McpError;
+
+    let expanded_path = expand_path(config_path);
+    let config_manager = ConfigManager::new(&expanded_path).await?;
+    let mut config = config_manager.get_config();
+
+    let runtime_config = config
+        .runtimes
+        .iter_mut()
+        .find(|r| r.name == name)
+        .ok_or_else(|| McpError::ConfigError(format!("Runtime '{}' not found", name)))?;
+
+    let lock_dir = lock_dir
+        .or_else(|| runtime_config.lock_dir.clone())
+        .map(|dir| std::path::PathBuf::from(expand_path(&dir)))
+        .unwrap_or_else(|| default_lock_dir(std::path::Path::new(&expanded_path), name));
+
+    let manager = RuntimeManager::new();
+    let _ = manager.register_auto(runtime_config.clone());
+    let runtime = manager
+        .get(name)
+        .ok_or_else(|| McpError::ConfigError(format!("Runtime '{}' not found", name)))?;
+
+    println!("Resolving packages for runtime '{}'...", name);
+    runtime
+        .runtime()
+        .freeze(&lock_dir)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Freeze failed: {}", e)))?;
+
+    runtime_config.lock_dir = Some(lock_dir.to_string_lossy().to_string());
+    config_manager.save(&config).await?;
+
+    println!("Saved lockfile for runtime '{}' to {}", name, lock_dir.display());
+    Ok(())
+}
+
+/// Check that a runtime's lockfile matches its packages and installs cleanly
+pub async fn verify(config_path: &str, name: &str) -> McpResult<()> {
+    use crate::config::ConfigManager;
+    use crate::runtime::RuntimeManager;
+    use crate::utils::errors::McpError;
+
+    let expanded_path = expand_path(config_path);
+    let config_manager = ConfigManager::new(&expanded_path).await?;
+    let config = config_manager.get_config();
+
+    let runtime_config = config
+        .runtimes
+        .iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| McpError::ConfigError(format!("Runtime '{}' not found", name)))?;
+
+    let lock_dir = runtime_config.lock_dir.as_deref().ok_or_else(|| {
+        McpError::ConfigError(format!(
+            "Runtime '{}' has no lockfile; run `supermcp runtime freeze {}` first",
+            name, name
+        ))
+    })?;
+
+    let manager = RuntimeManager::new();
+    let _ = manager.register_auto(runtime_config.clone());
+    let runtime = manager
+        .get(name)
+        .ok_or_else(|| McpError::ConfigError(format!("Runtime '{}' not found", name)))?;
+
+    runtime
+        .runtime()
+        .verify(std::path::Path::new(&expand_path(lock_dir)))
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Verification failed: {}", e)))?;
+
+    println!("[OK] {} - lockfile matches and installs cleanly", name);
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! CLI wrappers around sandbox learning and elevation on a running server
+
+use crate::cli::sessions::admin_request;
+use crate::sandbox::elevation::ElevationRequest;
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// How often a running learning session is checked on
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Parse a duration such as `90s`, `10m`, `1h` or plain seconds
+pub fn parse_duration(spec: &str) -> McpResult<Duration> {
+    let spec = spec.trim();
+    let (number, unit) = match spec.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => spec.split_at(split),
+        None => (spec, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| McpError::InvalidRequest(format!("Invalid duration '{}'", spec)))?;
+    let seconds = match unit {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => {
+            return Err(McpError::InvalidRequest(format!(
+                "Invalid duration '{}'; use s, m or h",
+                spec
+            )))
+        }
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Learn a server's sandbox profile and print the suggested config
+pub async fn learn(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    server: &str,
+    duration: &str,
+    json_output: bool,
+) -> McpResult<()> {
+    let duration = parse_duration(duration)?;
+    let path = format!("servers/{}/learn", server);
+    let body = json!({ "duration_secs": duration.as_secs() });
+    let response =
+        admin_request(config_path, url, token, reqwest::Method::POST, &path, Some(&body)).await?;
+    if !response.status().is_success() {
+        return Err(McpError::ServerNotFound(server.to_string()));
+    }
+    let report: Value = response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))?;
+    eprintln!(
+        "Learning {} until {}; use it as usual meanwhile (Ctrl-C to stop early)",
+        server,
+        report["ends_at"].as_str().unwrap_or_default()
+    );
+
+    let report = loop {
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = tokio::signal::ctrl_c() => {
+                break fetch(config_path, url, token, reqwest::Method::DELETE, &path).await?;
+            }
+        }
+        let report = fetch(config_path, url, token, reqwest::Method::GET, &path).await?;
+        if report["finished"].as_bool().unwrap_or(true) {
+            break report;
+        }
+    };
+
+    if !report["processes_seen"].as_bool().unwrap_or(false) {
+        return Err(McpError::SandboxError(format!(
+            "No process of {} was observed, so there is nothing to suggest a sandbox from; \
+             learning needs a Linux host and the server running (use it during the session)",
+            server
+        )));
+    }
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    let count = |key: &str| report[key].as_array().map_or(0, Vec::len);
+    eprintln!(
+        "Seen {} paths ({} written), {} remote endpoints and {} syscalls outside the allow-list",
+        count("paths"),
+        count("written"),
+        count("hosts"),
+        report["syscalls"].as_object().map_or(0, |syscalls| syscalls.len())
+    );
+    for host in report["hosts"].as_array().into_iter().flatten() {
+        eprintln!("  connected to {}", host.as_str().unwrap_or_default());
+    }
+    eprintln!("\nSuggested sandbox, to replace the server's [servers.sandbox]:\n");
+    println!("{}", report["suggested_toml"].as_str().unwrap_or_default());
+    Ok(())
+}
+
+/// Elevate a server's sandbox for a while
+pub async fn elevate(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    server: &str,
+    duration: &str,
+    mut request: ElevationRequest,
+) -> McpResult<()> {
+    request.duration_secs = parse_duration(duration)?.as_secs();
+    let path = format!("servers/{}/elevation", server);
+    let body = serde_json::to_value(&request)?;
+    let response =
+        admin_request(config_path, url, token, reqwest::Method::POST, &path, Some(&body)).await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(McpError::InvalidRequest(format!("Elevation refused ({}): {}", status, text)));
+    }
+    let grant: Value = response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))?;
+    println!(
+        "Elevated {} until {}",
+        server,
+        grant["expires_at"].as_str().unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// End a server's elevation early
+pub async fn revoke(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    server: &str,
+) -> McpResult<()> {
+    let path = format!("servers/{}/elevation", server);
+    let response =
+        admin_request(config_path, url, token, reqwest::Method::DELETE, &path, None).await?;
+    if !response.status().is_success() {
+        return Err(McpError::InvalidRequest(format!("{} is not elevated", server)));
+    }
+    println!("Revoked the elevation of {}; it restarts under its own sandbox", server);
+    Ok(())
+}
+
+async fn fetch(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    method: reqwest::Method,
+    path: &str,
+) -> McpResult<Value> {
+    let response = admin_request(config_path, url, token, method, path, None).await?;
+    if !response.status().is_success() {
+        return Err(McpError::InvalidRequest(
+            "The learning session is gone; was the server restarted?".to_string(),
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+}
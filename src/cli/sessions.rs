@@ -0,0 +1,150 @@
+//! CLI wrapper around the session admin API
+
+use crate::cli::skill::load_config;
+use crate::config::Config;
+use crate::http_server::admin::SessionList;
+use crate::utils::errors::{McpError, McpResult};
+
+/// Base URL of the running server from its config
+pub(crate) fn server_url(config: &Config) -> String {
+    let server = &config.server;
+    let scheme = if server.cert_path.is_some() || server.acme.enabled || server.spiffe.enabled {
+        "https"
+    } else {
+        "http"
+    };
+    let host = match server.host.as_str() {
+        "0.0.0.0" => "127.0.0.1",
+        "::" => "[::1]",
+        host => host,
+    };
+    format!("{}://{}:{}", scheme, host, server.port)
+}
+
+/// Send a request to the admin API, passing 404s through to the caller
+pub(crate) async fn admin_request(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    method: reqwest::Method,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> McpResult<reqwest::Response> {
+    let config = load_config(config_path).await?;
+    let base = url.map(|u| u.to_string()).unwrap_or_else(|| server_url(&config));
+    let token = token.map(|t| t.to_string()).or(config.auth.token);
+
+    let mut request = reqwest::Client::new().request(
+        method,
+        format!("{}/admin/v1/{}", base.trim_end_matches('/'), path),
+    );
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| McpError::TransportError(format!("Failed to reach {}: {}", base, e)))?;
+    if response.status().is_success() || response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(match status {
+        reqwest::StatusCode::BAD_REQUEST => McpError::InvalidRequest(
+            serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|error| {
+                    let message = error["message"].as_str()?;
+                    Some(message.trim_start_matches("invalid request: ").to_string())
+                })
+                .unwrap_or(body),
+        ),
+        reqwest::StatusCode::UNAUTHORIZED => McpError::AuthError(body),
+        reqwest::StatusCode::FORBIDDEN => McpError::AuthorizationError(body),
+        _ => McpError::TransportError(format!("Admin API returned {}: {}", status, body)),
+    })
+}
+
+/// List active sessions
+pub async fn list(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    json_output: bool,
+) -> McpResult<()> {
+    let response = admin_request(config_path, url, token, reqwest::Method::GET, "sessions", None).await?;
+    if !response.status().is_success() {
+        return Err(McpError::ConfigError(
+            "The server does not expose the admin API; set [admin] enabled = true".to_string(),
+        ));
+    }
+    let list: SessionList = response
+        .json()
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))?;
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&list)?);
+        return Ok(());
+    }
+
+    if list.sessions.is_empty() {
+        println!("No active sessions");
+        return Ok(());
+    }
+
+    println!("Active sessions ({}):\n", list.total);
+    for session in &list.sessions {
+        println!("  {}", session.id);
+        println!(
+            "    Identity: {}  Transport: {}  Preset: {}",
+            session.identity.as_deref().unwrap_or("anonymous"),
+            session.transport,
+            session.preset.as_deref().unwrap_or("-")
+        );
+        println!(
+            "    Requests: {}  Age: {}s  Last seen: {}",
+            session.request_count,
+            session.age_seconds,
+            session.last_seen.to_rfc3339()
+        );
+    }
+    Ok(())
+}
+
+/// Terminate a session
+pub async fn kill(
+    config_path: &str,
+    url: Option<&str>,
+    token: Option<&str>,
+    id: &str,
+) -> McpResult<()> {
+    let path = format!("sessions/{}", id);
+    let response = admin_request(config_path, url, token, reqwest::Method::DELETE, &path, None).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(McpError::InvalidRequest(format!("No active session {}", id)));
+    }
+    println!("✓ Terminated session {}", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_url() {
+        let mut config = Config::default();
+        assert_eq!(server_url(&config), "http://127.0.0.1:3000");
+
+        config.server.host = "0.0.0.0".to_string();
+        config.server.cert_path = Some("cert.pem".to_string());
+        assert_eq!(server_url(&config), "https://127.0.0.1:3000");
+    }
+}
@@ -0,0 +1,154 @@
+//! CLI commands for installing and managing skills
+
+use crate::cli::expand_path;
+use crate::cli::registry::create_registry_config;
+use crate::config::Config;
+use crate::registry::RegistryClient;
+use crate::skills::{InstalledSkill, SkillManager, SkillManifest, SkillSource};
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// Load the config file, falling back to defaults when it doesn't exist
+pub(crate) async fn load_config(config_path: &str) -> McpResult<Config> {
+    let path = PathBuf::from(expand_path(config_path));
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+    toml::from_str(&content)
+        .map_err(|e| McpError::ConfigError(format!("Failed to parse config: {}", e)))
+}
+
+/// Install a skill from a git URL, local path or registry name
+pub async fn install(
+    config_path: &str,
+    source: &str,
+    reference: Option<String>,
+    force: bool,
+) -> McpResult<()> {
+    let config = load_config(config_path).await?;
+    let manager = SkillManager::from_config(&config.skills);
+
+    let source = match SkillSource::parse(source, reference.clone()) {
+        SkillSource::Registry(name) => {
+            let client = RegistryClient::new(create_registry_config(&config))?;
+            let entry = client
+                .get_info(&name)
+                .await?
+                .ok_or_else(|| McpError::ConfigError(format!("Skill '{}' not found in registry", name)))?;
+            let url = entry.repository.ok_or_else(|| {
+                McpError::ConfigError(format!("Registry entry '{}' has no repository to install from", name))
+            })?;
+            SkillSource::Git { url, reference }
+        }
+        source => source,
+    };
+
+    println!("Installing skill from {}...", source);
+    let (skill, previous) = manager.install(&source, force).await?;
+
+    let version = skill.manifest.version.as_deref().unwrap_or("unversioned");
+    match previous {
+        Some(previous) => println!(
+            "✓ Updated skill '{}' ({} -> {})",
+            skill.manifest.name,
+            previous.manifest.version.as_deref().unwrap_or("unversioned"),
+            version
+        ),
+        None => println!("✓ Installed skill '{}' ({})", skill.manifest.name, version),
+    }
+    println!("  Location: {}", skill.path.display());
+    Ok(())
+}
+
+/// List installed skills
+pub async fn list(config_path: &str, json_output: bool) -> McpResult<()> {
+    let config = load_config(config_path).await?;
+    let manager = SkillManager::from_config(&config.skills);
+    let skills = manager.list().await?;
+
+    if json_output {
+        let output: Vec<_> = skills.iter().map(skill_json).collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if skills.is_empty() {
+        println!("No skills installed in {}", manager.directory().display());
+        return Ok(());
+    }
+
+    println!("Installed skills ({}):\n", manager.directory().display());
+    for skill in &skills {
+        println!(
+            "  {} ({})",
+            skill.manifest.name,
+            skill.manifest.version.as_deref().unwrap_or("unversioned")
+        );
+        println!("    {}", skill.manifest.description);
+    }
+    Ok(())
+}
+
+/// Remove an installed skill
+pub async fn remove(config_path: &str, name: &str) -> McpResult<()> {
+    let config = load_config(config_path).await?;
+    SkillManager::from_config(&config.skills).remove(name).await?;
+    println!("✓ Removed skill '{}'", name);
+    Ok(())
+}
+
+/// Show details of an installed skill
+pub async fn info(config_path: &str, name: &str) -> McpResult<()> {
+    let config = load_config(config_path).await?;
+    let skill = SkillManager::from_config(&config.skills)
+        .get(name)
+        .await?
+        .ok_or_else(|| McpError::ConfigError(format!("Skill '{}' is not installed", name)))?;
+
+    println!("Skill: {}", skill.manifest.name);
+    println!("  Description: {}", skill.manifest.description);
+    if let Some(version) = &skill.manifest.version {
+        println!("  Version: {}", version);
+    }
+    if let Some(license) = &skill.manifest.license {
+        println!("  License: {}", license);
+    }
+    println!("  Location: {}", skill.path.display());
+    if let Some(record) = &skill.record {
+        println!("  Source: {}", record.source);
+        if let Some(revision) = &record.revision {
+            println!("  Revision: {}", revision);
+        }
+        println!("  Installed: {}", record.installed_at.to_rfc3339());
+    }
+    Ok(())
+}
+
+/// Check a skill directory's SKILL.md without installing it
+pub async fn validate(path: &str) -> McpResult<()> {
+    let dir = PathBuf::from(expand_path(path));
+    let manifest = SkillManifest::load(Path::new(&dir)).await?;
+    println!(
+        "✓ {} ({}) is a valid skill",
+        manifest.name,
+        manifest.version.as_deref().unwrap_or("unversioned")
+    );
+    Ok(())
+}
+
+fn skill_json(skill: &InstalledSkill) -> serde_json::Value {
+    json!({
+        "name": skill.manifest.name,
+        "description": skill.manifest.description,
+        "version": skill.manifest.version,
+        "path": skill.path,
+        "source": skill.record.as_ref().map(|r| r.source.clone()),
+        "revision": skill.record.as_ref().and_then(|r| r.revision.clone()),
+        "installed_at": skill.record.as_ref().map(|r| r.installed_at.to_rfc3339()),
+    })
+}
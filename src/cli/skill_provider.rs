@@ -1,18 +1,31 @@
 //! Skill provider implementation
 //!
 //! Parses SKILL.md files to extract tool definitions and execute skills.
+//!
+//! A tool section may name a script with a `Script: scripts/run.py` line.
+//! Scripts run in the same sandbox as MCP servers, limited by the
+//! `resources` block of the SKILL.md frontmatter, and receive the tool
+//! arguments as JSON on stdin.
 
+use crate::config::McpServerConfig;
 use crate::core::provider::{ParameterSchema, Provider, ProviderType, Tool, ToolResult};
+use crate::runtime::types::ResourceLimits;
+use crate::skills::SkillManifest;
 use crate::utils::errors::{McpError, McpResult};
+use crate::utils::process::output_with_timeout;
 use async_trait::async_trait;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, warn};
 
 /// Provider for SKILL.md-based skills
 pub struct SkillProvider {
     name: String,
     skill_path: PathBuf,
     tools: Vec<Tool>,
+    limits: ResourceLimits,
 }
 
 impl SkillProvider {
@@ -31,14 +44,104 @@ impl SkillProvider {
             .map_err(|e| McpError::ConfigError(format!("Failed to read SKILL.md: {}", e)))?;
 
         let tools = Self::parse_skill_md(name, &content)?;
+        // Invalid frontmatter falls back to the default (restrictive) limits
+        let limits = match SkillManifest::parse_optional(&content) {
+            Ok(manifest) => manifest.map(|m| m.resources).unwrap_or_default(),
+            Err(e) => {
+                warn!("Skill {} uses default sandbox limits: {}", name, e);
+                ResourceLimits::default()
+            }
+        };
 
         Ok(Self {
             name: name.to_string(),
             skill_path: path,
             tools,
+            limits,
         })
     }
 
+    /// Sandbox limits applied to this skill's scripts
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// Resolve a tool's script, refusing paths outside the skill directory
+    fn script_path(&self, script: &str) -> McpResult<PathBuf> {
+        let root = self.skill_path.canonicalize()?;
+        let path = root.join(script).canonicalize().map_err(|e| {
+            McpError::ToolExecutionError(format!("Script '{}' not found: {}", script, e))
+        })?;
+
+        if !path.starts_with(&root) {
+            return Err(McpError::ToolExecutionError(format!(
+                "Script '{}' is outside the skill directory",
+                script
+            )));
+        }
+        Ok(path)
+    }
+
+    /// Server-style config that runs a script under this skill's limits
+    fn script_config(&self, script_path: &Path) -> McpServerConfig {
+        let script = script_path.to_string_lossy().to_string();
+        let (command, args) = match script_path.extension().and_then(|e| e.to_str()) {
+            Some("py") => ("python3".to_string(), vec![script]),
+            Some("js") | Some("mjs") | Some("cjs") => ("node".to_string(), vec![script]),
+            Some("ts") => ("deno".to_string(), vec!["run".to_string(), script]),
+            Some("sh") => ("sh".to_string(), vec![script]),
+            _ => (script, Vec::new()),
+        };
+
+        let mut env = HashMap::new();
+        env.insert(
+            "SKILL_DIR".to_string(),
+            self.skill_path.to_string_lossy().to_string(),
+        );
+
+        let mut config = McpServerConfig {
+            name: format!("skill-{}", self.name),
+            command,
+            args,
+            env,
+            sandbox: self.limits.to_sandbox_config(&self.skill_path),
+            ..Default::default()
+        };
+        crate::runtime::deno::apply_permission_flags(&mut config);
+        config
+    }
+
+    /// Run a script in the sandbox, passing `arguments` on stdin
+    async fn run_script(&self, script_path: &Path, arguments: &Value) -> McpResult<ToolResult> {
+        let config = self.script_config(script_path);
+        let sandbox = crate::sandbox::create_sandbox(&config);
+        debug!("Running skill script: {} {}", config.command, config.args.join(" "));
+
+        let child = sandbox.spawn(&config).await?;
+
+        let timeout = Duration::from_secs(self.limits.timeout_seconds);
+        let input = arguments.to_string();
+        let Some(output) = output_with_timeout(child, Some(input.as_bytes()), timeout).await? else {
+            return Ok(ToolResult::error(format!(
+                "Skill script timed out after {}s",
+                self.limits.timeout_seconds
+            )));
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Ok(ToolResult::error(format!(
+                "Skill script exited with code {}: {}",
+                output.status.code().unwrap_or(-1),
+                stderr.trim()
+            )));
+        }
+
+        let data = serde_json::from_str(&stdout).unwrap_or_else(|_| Value::String(stdout.clone()));
+        Ok(ToolResult::success(data)?
+            .with_content(vec![serde_json::json!({ "type": "text", "text": stdout })]))
+    }
+
     /// Parse SKILL.md content to extract tool definitions
     pub fn parse_skill_md(skill_name: &str, content: &str) -> McpResult<Vec<Tool>> {
         let mut tools = Vec::new();
@@ -74,12 +177,15 @@ impl SkillProvider {
 
                 // Collect description (lines until "Arguments:" or next ###)
                 let mut description = String::new();
+                let mut metadata = std::collections::HashMap::new();
                 let mut end_of_tool = i + 1;
                 while end_of_tool < lines.len() && !lines[end_of_tool].trim().starts_with("### ") {
                     if lines[end_of_tool].trim() == "Arguments:" {
                         break;
                     }
-                    if !lines[end_of_tool].trim().is_empty() {
+                    if let Some(script) = lines[end_of_tool].trim().strip_prefix("Script:") {
+                        metadata.insert("script".to_string(), Value::String(script.trim().to_string()));
+                    } else if !lines[end_of_tool].trim().is_empty() {
                         if !description.is_empty() {
                             description.push(' ');
                         }
@@ -95,19 +201,23 @@ impl SkillProvider {
                     while k < lines.len() && lines[k].trim().starts_with("- ") {
                         let arg_line = lines[k].trim().trim_start_matches("- ");
                         if let Some((arg_def, arg_desc)) = arg_line.split_once(':') {
-                            let parts: Vec<&str> = arg_def.splitn(2, '(').collect();
-                            let arg_name = parts[0].trim().to_string();
-
-                            let required = if parts.len() > 1 {
-                                let type_part = parts[1];
-                                type_part.contains("required")
-                            } else {
-                                false
+                            // Accept both `name (type, required): desc` and
+                            // `name: type (required) - desc`.
+                            let (arg_name, type_part, arg_desc) = match arg_def.split_once('(') {
+                                Some((name, spec)) => (name, Some(spec), arg_desc),
+                                None => match arg_desc.split_once(" - ") {
+                                    Some((spec, desc)) => (arg_def, Some(spec), desc),
+                                    None => (arg_def, None, arg_desc),
+                                },
                             };
+                            let arg_name = arg_name.trim().to_string();
+
+                            let required = type_part
+                                .map(|type_part| type_part.contains("required"))
+                                .unwrap_or(false);
 
                             // Extract type
-                            let arg_type = if parts.len() > 1 {
-                                let type_part = parts[1];
+                            let arg_type = if let Some(type_part) = type_part {
                                 if type_part.contains("string") {
                                     "string".to_string()
                                 } else if type_part.contains("number") {
@@ -144,7 +254,7 @@ impl SkillProvider {
                     provider: skill_name.to_string(),
                     provider_type: ProviderType::Skill,
                     parameters,
-                    metadata: std::collections::HashMap::new(),
+                    metadata,
                 });
 
                 i = end_of_tool;
@@ -182,27 +292,23 @@ impl Provider for SkillProvider {
             .unwrap_or(name);
 
         // Find the tool definition
-        let tool = self.tools.iter().find(|t| {
+        let Some(tool) = self.tools.iter().find(|t| {
             t.name == name || t.name == format!("{}.{}", self.name, tool_name)
-        });
-
-        if tool.is_none() {
+        }) else {
             return Ok(ToolResult::error(format!("Tool '{}' not found in skill", tool_name)));
-        }
+        };
 
-        // For now, return a placeholder result
-        // Full implementation would execute the skill logic
-        Ok(ToolResult {
-            success: true,
-            data: Some(serde_json::json!({
-                "status": "skill_called",
-                "skill": self.name,
-                "tool": tool_name,
-                "arguments": arguments,
-                "note": "Skill execution not yet fully implemented"
-            })),
-            error: None,
-            content: None,
-        })
+        let Some(script) = tool.metadata.get("script").and_then(|s| s.as_str()) else {
+            return Ok(ToolResult::error(format!(
+                "Tool '{}' has no script; add a 'Script:' line to its SKILL.md section",
+                tool_name
+            )));
+        };
+
+        let script_path = match self.script_path(script) {
+            Ok(path) => path,
+            Err(e) => return Ok(ToolResult::error(e.to_string())),
+        };
+        self.run_script(&script_path, &arguments).await
     }
 }
@@ -0,0 +1,334 @@
+//! Native Windows Service support
+//!
+//! `supermcp install --manager windows-service` registers supermcp with the
+//! Service Control Manager, restarting it on failure. The SCM starts
+//! `supermcp serve --windows-service`, which reports its state back and
+//! handles stop, pause and continue requests. Lifecycle events are written
+//! to the Application event log under the `super-mcp` source.
+
+use crate::config::McpServerConfig;
+use crate::core::ServerManager;
+use anyhow::{anyhow, Result as AnyhowResult};
+use std::ffi::{OsStr, OsString};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::{error, info};
+use windows_service::service::{
+    ServiceAccess, ServiceAction, ServiceActionType, ServiceControl, ServiceControlAccept,
+    ServiceErrorControl, ServiceExitCode, ServiceFailureActions, ServiceFailureResetPeriod,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{
+    self, ServiceControlHandlerResult, ServiceStatusHandle,
+};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE,
+};
+
+/// Service and event log source name
+const SERVICE_NAME: &str = "super-mcp";
+
+/// Registry key of the event log source
+const EVENT_SOURCE_KEY: &str =
+    r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\super-mcp";
+
+/// Message file with a pass-through message for event IDs 1-1000
+const EVENT_MESSAGE_FILE: &str = r"%SystemRoot%\System32\EventCreate.exe";
+
+/// Control requests from the SCM, handled by the running server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceControlEvent {
+    Stop,
+    Pause,
+    Continue,
+}
+
+/// Severity of an event log entry
+#[derive(Debug, Clone, Copy)]
+pub enum EventLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+static CONTROL_EVENTS: OnceLock<UnboundedSender<ServiceControlEvent>> = OnceLock::new();
+static STATUS_HANDLE: OnceLock<ServiceStatusHandle> = OnceLock::new();
+
+/// Register supermcp with the Service Control Manager and start it
+pub fn install(binary_path: &str, config_path: &str) -> AnyhowResult<()> {
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )?;
+
+    let info = ServiceInfo {
+        name: OsString::from(SERVICE_NAME),
+        display_name: OsString::from("Super MCP Server"),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: binary_path.into(),
+        launch_arguments: ["serve", "--config", config_path, "--windows-service"]
+            .iter()
+            .map(OsString::from)
+            .collect(),
+        dependencies: vec![],
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager.create_service(
+        &info,
+        ServiceAccess::CHANGE_CONFIG | ServiceAccess::START | ServiceAccess::QUERY_STATUS,
+    )?;
+    service.set_description("Proxies and aggregates MCP servers")?;
+
+    // Restart after 5s, then 30s, then every minute; forget failures after a day
+    service.update_failure_actions(ServiceFailureActions {
+        reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(24 * 60 * 60)),
+        reboot_msg: None,
+        command: None,
+        actions: Some(
+            [5, 30, 60]
+                .into_iter()
+                .map(|secs| ServiceAction {
+                    action_type: ServiceActionType::Restart,
+                    delay: Duration::from_secs(secs),
+                })
+                .collect(),
+        ),
+    })?;
+    service.set_failure_actions_on_non_crash_failures(true)?;
+
+    register_event_source()?;
+    service.start::<&OsStr>(&[])?;
+
+    println!("✓ Installed super-mcp as a Windows service");
+    println!("  Service name: {}", SERVICE_NAME);
+    println!("  Events are logged to the Application event log");
+    println!("  Use 'supermcp service status' to check status");
+
+    Ok(())
+}
+
+/// Stop and remove the service and its event log source
+pub fn uninstall() -> AnyhowResult<()> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    let service = manager.open_service(
+        SERVICE_NAME,
+        ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+    )?;
+
+    if service.query_status()?.current_state != ServiceState::Stopped {
+        service.stop()?;
+    }
+    service.delete()?;
+
+    let _ = Command::new("reg")
+        .args(["delete", EVENT_SOURCE_KEY, "/f"])
+        .output();
+
+    println!("✓ Uninstalled super-mcp Windows service");
+    Ok(())
+}
+
+fn register_event_source() -> AnyhowResult<()> {
+    let output = Command::new("reg")
+        .args([
+            "add",
+            EVENT_SOURCE_KEY,
+            "/v",
+            "EventMessageFile",
+            "/t",
+            "REG_EXPAND_SZ",
+            "/d",
+            EVENT_MESSAGE_FILE,
+            "/f",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Failed to register event log source: {}", stderr));
+    }
+    Ok(())
+}
+
+/// Write an entry to the Application event log
+pub fn event_log(level: EventLevel, message: &str) {
+    let source: Vec<u16> = SERVICE_NAME.encode_utf16().chain(Some(0)).collect();
+    let message: Vec<u16> = message.encode_utf16().chain(Some(0)).collect();
+    let event_type = match level {
+        EventLevel::Info => EVENTLOG_INFORMATION_TYPE,
+        EventLevel::Warning => EVENTLOG_WARNING_TYPE,
+        EventLevel::Error => EVENTLOG_ERROR_TYPE,
+    };
+
+    // SAFETY: both strings are NUL-terminated and outlive the calls
+    unsafe {
+        let handle = RegisterEventSourceW(std::ptr::null(), source.as_ptr());
+        if handle.is_null() {
+            return;
+        }
+        let strings = [message.as_ptr()];
+        ReportEventW(
+            handle,
+            event_type,
+            0,
+            1,
+            std::ptr::null_mut(),
+            1,
+            0,
+            strings.as_ptr(),
+            std::ptr::null(),
+        );
+        DeregisterEventSource(handle);
+    }
+}
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Connect to the SCM on a background thread
+///
+/// Returns the control requests to handle; the SCM expects the process to
+/// call the dispatcher soon after it starts.
+pub fn start_dispatcher() -> UnboundedReceiver<ServiceControlEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = CONTROL_EVENTS.set(tx);
+
+    std::thread::spawn(|| {
+        if let Err(e) = service_dispatcher::start(SERVICE_NAME, ffi_service_main) {
+            error!("Failed to connect to the Service Control Manager: {}", e);
+        }
+    });
+    rx
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    let handler = |control| {
+        let (event, pending) = match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                (ServiceControlEvent::Stop, ServiceState::StopPending)
+            }
+            ServiceControl::Pause => (ServiceControlEvent::Pause, ServiceState::PausePending),
+            ServiceControl::Continue => {
+                (ServiceControlEvent::Continue, ServiceState::ContinuePending)
+            }
+            ServiceControl::Interrogate => return ServiceControlHandlerResult::NoError,
+            _ => return ServiceControlHandlerResult::NotImplemented,
+        };
+        set_state(pending, 0);
+        if let Some(tx) = CONTROL_EVENTS.get() {
+            let _ = tx.send(event);
+        }
+        ServiceControlHandlerResult::NoError
+    };
+
+    match service_control_handler::register(SERVICE_NAME, handler) {
+        Ok(handle) => {
+            let _ = STATUS_HANDLE.set(handle);
+            set_state(ServiceState::Running, 0);
+            event_log(EventLevel::Info, "super-mcp service started");
+        }
+        Err(e) => {
+            event_log(
+                EventLevel::Error,
+                &format!("Failed to register service control handler: {}", e),
+            );
+            return;
+        }
+    }
+
+    // The process exits once the server has stopped; until then this
+    // thread keeps the dispatcher connection open
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Report the service's state to the SCM
+pub fn set_state(state: ServiceState, exit_code: u32) {
+    let Some(handle) = STATUS_HANDLE.get() else {
+        return;
+    };
+    let controls_accepted = match state {
+        ServiceState::Running | ServiceState::Paused => {
+            ServiceControlAccept::STOP
+                | ServiceControlAccept::PAUSE_CONTINUE
+                | ServiceControlAccept::SHUTDOWN
+        }
+        _ => ServiceControlAccept::empty(),
+    };
+    let _ = handle.set_service_status(ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted,
+        exit_code: ServiceExitCode::Win32(exit_code),
+        checkpoint: 0,
+        wait_hint: Duration::from_secs(30),
+        process_id: None,
+    });
+}
+
+/// Handle SCM control requests for the running server
+///
+/// Pausing stops the upstream servers while the HTTP listener stays up;
+/// continuing starts `servers` again.
+pub fn spawn_control_loop(
+    mut events: UnboundedReceiver<ServiceControlEvent>,
+    server_manager: Arc<ServerManager>,
+    servers: Vec<McpServerConfig>,
+) {
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                ServiceControlEvent::Stop => {
+                    info!("Stopping on request of the Service Control Manager");
+                    server_manager.stop_all().await;
+                    event_log(EventLevel::Info, "super-mcp service stopped");
+                    set_state(ServiceState::Stopped, 0);
+                    std::process::exit(0);
+                }
+                ServiceControlEvent::Pause => {
+                    info!("Pausing on request of the Service Control Manager");
+                    server_manager.stop_all().await;
+                    event_log(EventLevel::Info, "super-mcp service paused");
+                    set_state(ServiceState::Paused, 0);
+                }
+                ServiceControlEvent::Continue => {
+                    info!("Continuing on request of the Service Control Manager");
+                    for server_config in servers.clone() {
+                        let name = server_config.name.clone();
+                        if let Err(e) = server_manager.add_server(server_config).await {
+                            event_log(
+                                EventLevel::Warning,
+                                &format!("Failed to restart server {}: {}", name, e),
+                            );
+                        }
+                    }
+                    event_log(EventLevel::Info, "super-mcp service continued");
+                    set_state(ServiceState::Running, 0);
+                }
+            }
+        }
+    });
+}
+
+/// Record how the server exited, so the SCM applies its recovery actions
+pub fn report_exit(result: &anyhow::Result<()>) {
+    match result {
+        Ok(()) => set_state(ServiceState::Stopped, 0),
+        Err(e) => {
+            event_log(
+                EventLevel::Error,
+                &format!("super-mcp service failed: {}", e),
+            );
+            // ERROR_SERVICE_SPECIFIC_ERROR
+            set_state(ServiceState::Stopped, 1066);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Artifact store for large tool results
+//!
+//! Tool results above a size threshold are written to a local directory or
+//! object storage and replaced by a `resource_link` to
+//! `supermcp://artifact/<id>`, which clients read back through the proxy
+//! (or over HTTP at `/artifacts/<id>`). Artifacts expire after a TTL and
+//! are removed by a periodic sweep. Each artifact is two objects: `<id>`
+//! with the data and `<id>.json` with its metadata. With tenant keys, the
+//! data of an artifact produced for a user is sealed with that user's key.
+
+use crate::cloud::location::Location;
+use crate::cloud::tenant_keys::TenantKeys;
+use crate::config::ArtifactsConfig;
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// URI prefix of artifact links
+pub const ARTIFACT_URI_PREFIX: &str = "supermcp://artifact/";
+
+/// Metadata kept next to each artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactMeta {
+    pub id: String,
+    pub mime_type: String,
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Authenticated user the result was produced for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// The data is sealed with the owner's tenant key
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sealed: bool,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ArtifactMeta {
+    pub fn uri(&self) -> String {
+        format!("{}{}", ARTIFACT_URI_PREFIX, self.id)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Artifacts produced for a user are only readable by that user
+    pub fn readable_by(&self, user: Option<&str>) -> bool {
+        self.owner.is_none() || self.owner.as_deref() == user
+    }
+}
+
+/// Artifact IDs are generated hex strings; anything else is refused so an
+/// ID can't name another object
+fn is_valid_id(id: &str) -> bool {
+    id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Large tool results kept outside the conversation
+pub struct ArtifactStore {
+    location: Location,
+    threshold_bytes: usize,
+    ttl: chrono::Duration,
+    gc_interval: Duration,
+    tenant_keys: Option<Arc<TenantKeys>>,
+}
+
+impl ArtifactStore {
+    pub fn open(config: &ArtifactsConfig) -> McpResult<Self> {
+        Ok(Self {
+            location: Location::parse(&config.location)?,
+            threshold_bytes: config.threshold_bytes,
+            ttl: chrono::Duration::seconds(config.ttl_seconds as i64),
+            gc_interval: Duration::from_secs(config.gc_interval_seconds.max(1)),
+            tenant_keys: None,
+        })
+    }
+
+    /// Seal the data of owned artifacts with the owner's tenant key
+    pub fn with_tenant_keys(mut self, keys: Arc<TenantKeys>) -> Self {
+        self.tenant_keys = Some(keys);
+        self
+    }
+
+    /// Store data and return its metadata
+    pub async fn put(
+        &self,
+        data: Vec<u8>,
+        mime_type: &str,
+        tool: Option<&str>,
+        owner: Option<&str>,
+    ) -> McpResult<ArtifactMeta> {
+        let now = Utc::now();
+        let mut meta = ArtifactMeta {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            mime_type: mime_type.to_string(),
+            size: data.len(),
+            tool: tool.map(str::to_string),
+            owner: owner.map(str::to_string),
+            sealed: false,
+            created_at: now,
+            expires_at: now + self.ttl,
+        };
+        let data = match (&self.tenant_keys, owner) {
+            (Some(keys), Some(owner)) => {
+                meta.sealed = true;
+                keys.seal(owner, meta.id.as_bytes(), &data).await?
+            }
+            _ => data,
+        };
+        self.location.write(&meta.id, data).await?;
+        self.location
+            .write(&format!("{}.json", meta.id), serde_json::to_vec(&meta)?)
+            .await?;
+        debug!("Stored artifact {} ({} bytes)", meta.id, meta.size);
+        Ok(meta)
+    }
+
+    async fn meta(&self, id: &str) -> McpResult<Option<ArtifactMeta>> {
+        match self.location.read(&format!("{}.json", id)).await? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// An unexpired artifact and its data
+    pub async fn get(&self, id: &str) -> McpResult<Option<(ArtifactMeta, Vec<u8>)>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+        let Some(meta) = self.meta(id).await? else {
+            return Ok(None);
+        };
+        if meta.is_expired(Utc::now()) {
+            return Ok(None);
+        }
+        let Some(data) = self.location.read(id).await? else {
+            return Ok(None);
+        };
+        if !meta.sealed {
+            return Ok(Some((meta, data)));
+        }
+        let (Some(keys), Some(owner)) = (&self.tenant_keys, &meta.owner) else {
+            return Err(McpError::InternalError(format!(
+                "Artifact {} is sealed but tenant keys are not configured",
+                id
+            )));
+        };
+        let data = keys.open(owner, id.as_bytes(), &data).await?;
+        Ok(Some((meta, data)))
+    }
+
+    /// Every stored artifact, oldest first
+    pub async fn list(&self) -> McpResult<Vec<ArtifactMeta>> {
+        let mut artifacts = Vec::new();
+        for name in self.location.list().await? {
+            let Some(id) = name.strip_suffix(".json") else {
+                continue;
+            };
+            match self.meta(id).await {
+                Ok(Some(meta)) => artifacts.push(meta),
+                Ok(None) => {}
+                Err(e) => warn!("Skipping unreadable artifact {}: {}", id, e),
+            }
+        }
+        artifacts.sort_by_key(|a| a.created_at);
+        Ok(artifacts)
+    }
+
+    /// Delete an artifact; returns whether it existed
+    pub async fn delete(&self, id: &str) -> McpResult<bool> {
+        if !is_valid_id(id) || self.meta(id).await?.is_none() {
+            return Ok(false);
+        }
+        self.location.remove(id).await?;
+        self.location.remove(&format!("{}.json", id)).await?;
+        Ok(true)
+    }
+
+    /// Delete expired artifacts, returning how many were removed
+    pub async fn collect_garbage(&self) -> McpResult<usize> {
+        let now = Utc::now();
+        let mut removed = 0;
+        for meta in self.list().await? {
+            if meta.is_expired(now) && self.delete(&meta.id).await? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Run [`collect_garbage`](Self::collect_garbage) periodically, on
+    /// the cluster leader only
+    pub fn spawn_gc(self: Arc<Self>) {
+        let period = self.gc_interval;
+        crate::cloud::leader::spawn_leader_only("artifact GC", period, move || {
+            let store = self.clone();
+            async move {
+                match store.collect_garbage().await {
+                    Ok(0) => {}
+                    Ok(n) => info!("Deleted {} expired artifact(s)", n),
+                    Err(e) => warn!("Failed to delete expired artifacts: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Replace a large `tools/call` result by a link to a stored copy
+    ///
+    /// Returns whether the result was replaced.
+    pub async fn offload(
+        &self,
+        result: &mut Value,
+        tool: Option<&str>,
+        owner: Option<&str>,
+    ) -> McpResult<bool> {
+        let data = serde_json::to_vec(result)?;
+        if data.len() <= self.threshold_bytes {
+            return Ok(false);
+        }
+
+        let meta = self.put(data, "application/json", tool, owner).await?;
+        let mut replacement = json!({
+            "content": [{
+                "type": "resource_link",
+                "uri": meta.uri(),
+                "name": format!("{}-result.json", tool.unwrap_or("tool")),
+                "mimeType": meta.mime_type,
+                "size": meta.size,
+                "description": format!(
+                    "Tool result of {} bytes, available until {}",
+                    meta.size,
+                    meta.expires_at.to_rfc3339()
+                ),
+            }]
+        });
+        if let Some(is_error) = result.get("isError") {
+            replacement["isError"] = is_error.clone();
+        }
+        *result = replacement;
+        Ok(true)
+    }
+
+    /// `resources/read` result for an artifact link, as read by `user`
+    pub async fn read_resource(&self, uri: &str, user: Option<&str>) -> McpResult<Value> {
+        let id = uri.strip_prefix(ARTIFACT_URI_PREFIX).unwrap_or(uri);
+        let (meta, data) = self
+            .get(id)
+            .await?
+            .filter(|(meta, _)| meta.readable_by(user))
+            .ok_or_else(|| McpError::InvalidRequest(format!("Unknown or expired artifact: {}", uri)))?;
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": meta.mime_type,
+                "text": String::from_utf8_lossy(&data),
+            }]
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &std::path::Path, ttl_seconds: u64) -> ArtifactStore {
+        ArtifactStore::open(&ArtifactsConfig {
+            enabled: true,
+            location: dir.display().to_string(),
+            threshold_bytes: 64,
+            ttl_seconds,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_offload_and_read_back() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = store(dir.path(), 3600);
+
+        let mut small = json!({ "content": [{ "type": "text", "text": "ok" }] });
+        assert!(!store.offload(&mut small, Some("echo"), None).await.unwrap());
+
+        let original = json!({ "content": [{ "type": "text", "text": "x".repeat(1000) }], "isError": false });
+        let mut result = original.clone();
+        assert!(store.offload(&mut result, Some("dump"), Some("alice")).await.unwrap());
+        let link = &result["content"][0];
+        assert_eq!(link["type"], "resource_link");
+        assert_eq!(link["name"], "dump-result.json");
+        assert_eq!(result["isError"], false);
+
+        let uri = link["uri"].as_str().unwrap();
+        assert!(store.read_resource(uri, Some("bob")).await.is_err());
+        let read = store.read_resource(uri, Some("alice")).await.unwrap();
+        let text = read["contents"][0]["text"].as_str().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(text).unwrap(), original);
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].owner.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_owned_artifacts_are_sealed() {
+        use crate::cloud::tenant_keys::LocalKms;
+        use crate::cloud::InMemoryBackend;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let keys = TenantKeys::new(Arc::new(LocalKms::new([2u8; 32])), Arc::new(InMemoryBackend::new()));
+        let store = store(dir.path(), 3600).with_tenant_keys(Arc::new(keys));
+
+        let owned = store.put(b"secret".to_vec(), "text/plain", None, Some("alice")).await.unwrap();
+        assert!(owned.sealed);
+        assert_ne!(std::fs::read(dir.path().join(&owned.id)).unwrap(), b"secret");
+        assert_eq!(store.get(&owned.id).await.unwrap().unwrap().1, b"secret");
+
+        let shared = store.put(b"public".to_vec(), "text/plain", None, None).await.unwrap();
+        assert!(!shared.sealed);
+        assert_eq!(std::fs::read(dir.path().join(&shared.id)).unwrap(), b"public");
+    }
+
+    #[tokio::test]
+    async fn test_expired_artifacts_are_collected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = store(dir.path(), 0);
+
+        let meta = store.put(b"old".to_vec(), "text/plain", None, None).await.unwrap();
+        assert!(store.get(&meta.id).await.unwrap().is_none());
+        assert_eq!(store.collect_garbage().await.unwrap(), 1);
+        assert!(store.list().await.unwrap().is_empty());
+        assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+
+        assert!(store.get("../../etc/passwd").await.unwrap().is_none());
+    }
+}
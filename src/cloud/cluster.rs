@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tokio::time::interval;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
@@ -94,6 +94,28 @@ pub struct ClusterConfig {
     pub min_quorum: usize,
     /// Enable read replicas
     pub enable_read_replicas: bool,
+    /// Join as a read replica, which is never elected leader
+    pub read_replica: bool,
+}
+
+impl ClusterConfig {
+    /// Settings for the `[cluster]` section
+    pub fn from_config(config: &crate::config::ClusterConfig) -> McpResult<Self> {
+        let bind_addr = config.bind_addr.parse().map_err(|e| {
+            McpError::ConfigError(format!("Invalid cluster.bind_addr {}: {}", config.bind_addr, e))
+        })?;
+        Ok(Self {
+            node_id: config.node_id.clone(),
+            bind_addr,
+            seed_nodes: config.seed_nodes.clone(),
+            heartbeat_interval: Duration::from_millis(config.heartbeat_interval_ms),
+            heartbeat_timeout: Duration::from_millis(config.heartbeat_timeout_ms),
+            election_timeout: Duration::from_millis(config.election_timeout_ms),
+            min_quorum: config.min_quorum,
+            read_replica: config.read_replica,
+            ..Default::default()
+        })
+    }
 }
 
 impl Default for ClusterConfig {
@@ -107,6 +129,7 @@ impl Default for ClusterConfig {
             election_timeout: Duration::from_secs(10),
             min_quorum: 3,
             enable_read_replicas: true,
+            read_replica: false,
         }
     }
 }
@@ -118,13 +141,15 @@ pub struct ClusterManager {
     /// Cluster configuration
     config: ClusterConfig,
     /// All nodes in cluster
-    nodes: DashMap<String, NodeInfo>,
+    nodes: Arc<DashMap<String, NodeInfo>>,
     /// Current leader
     current_leader: Arc<RwLock<Option<String>>>,
     /// Local node role
     role: Arc<RwLock<NodeRole>>,
     /// Cluster state
     state: Arc<RwLock<ClusterState>>,
+    /// Whether this node leads, for tasks following leadership changes
+    leadership: Arc<watch::Sender<bool>>,
 }
 
 /// Cluster state
@@ -140,24 +165,30 @@ impl ClusterManager {
     /// Create a new cluster manager
     pub fn new(config: ClusterConfig) -> Self {
         let node_id = config.node_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
-        
+        let initial_role = if config.read_replica {
+            NodeRole::ReadReplica
+        } else {
+            NodeRole::Follower
+        };
+
         let manager = Self {
             node_id: node_id.clone(),
             config,
-            nodes: DashMap::new(),
+            nodes: Arc::new(DashMap::new()),
             current_leader: Arc::new(RwLock::new(None)),
-            role: Arc::new(RwLock::new(NodeRole::Follower)),
+            role: Arc::new(RwLock::new(initial_role)),
             state: Arc::new(RwLock::new(ClusterState {
                 term: 0,
                 voted_for: None,
             })),
+            leadership: Arc::new(watch::channel(false).0),
         };
 
         // Add self to nodes
         let self_node = NodeInfo {
             id: node_id,
             address: manager.config.bind_addr,
-            role: NodeRole::Follower,
+            role: initial_role,
             status: NodeStatus::Joining,
             last_heartbeat: chrono::Utc::now(),
             metadata: NodeMetadata {
@@ -270,9 +301,11 @@ impl ClusterManager {
     /// Start leader election task
     fn start_election_task(&self) {
         let node_id = self.node_id.clone();
+        let nodes = self.nodes.clone();
         let current_leader = self.current_leader.clone();
         let role = self.role.clone();
         let state = self.state.clone();
+        let leadership = self.leadership.clone();
         let election_timeout = self.config.election_timeout;
 
         tokio::spawn(async move {
@@ -281,9 +314,19 @@ impl ClusterManager {
             loop {
                 ticker.tick().await;
 
-                let leader = current_leader.read().await.clone();
+                let mut leader = current_leader.read().await.clone();
                 let r = *role.read().await;
 
+                // A leader that stopped sending heartbeats is gone
+                if let Some(id) = &leader {
+                    let alive = nodes.get(id).is_some_and(|n| n.status != NodeStatus::Unhealthy);
+                    if *id != node_id && !alive {
+                        warn!("Leader {} is unreachable", id);
+                        *current_leader.write().await = None;
+                        leader = None;
+                    }
+                }
+
                 // If no leader and we're a follower, start election
                 if leader.is_none() && r == NodeRole::Follower {
                     info!("No leader detected, starting election");
@@ -297,6 +340,10 @@ impl ClusterManager {
                     // In production, this would run the full Raft election
                     *role.write().await = NodeRole::Leader;
                     *current_leader.write().await = Some(node_id.clone());
+                    if let Some(mut node) = nodes.get_mut(&node_id) {
+                        node.role = NodeRole::Leader;
+                    }
+                    leadership.send_replace(true);
                     
                     info!("Elected as leader for term {}", state.read().await.term);
                 }
@@ -306,6 +353,10 @@ impl ClusterManager {
 
     /// Become leader
     async fn become_leader(&self) {
+        if *self.role.read().await == NodeRole::ReadReplica {
+            info!("Read replica joined without a leader");
+            return;
+        }
         info!("Becoming cluster leader");
         
         *self.role.write().await = NodeRole::Leader;
@@ -315,6 +366,27 @@ impl ClusterManager {
             node.role = NodeRole::Leader;
             node.status = NodeStatus::Healthy;
         }
+        self.leadership.send_replace(true);
+    }
+
+    /// Give up leadership so another node is elected
+    pub async fn step_down(&self) {
+        if !self.is_leader().await {
+            return;
+        }
+        info!("Stepping down as cluster leader");
+
+        *self.role.write().await = NodeRole::Follower;
+        *self.current_leader.write().await = None;
+        if let Some(mut node) = self.nodes.get_mut(&self.node_id) {
+            node.role = NodeRole::Follower;
+        }
+        self.leadership.send_replace(false);
+    }
+
+    /// Follow leadership changes: the value is whether this node leads
+    pub fn subscribe_leadership(&self) -> watch::Receiver<bool> {
+        self.leadership.subscribe()
     }
 
     /// Get current leader
@@ -395,9 +467,7 @@ impl ClusterManager {
         }
         
         // Step down if leader
-        if self.is_leader().await {
-            *self.current_leader.write().await = None;
-        }
+        self.step_down().await;
     }
 }
 
@@ -457,7 +527,7 @@ mod tests {
         let manager = ClusterManager::new(config);
         
         assert_eq!(manager.node_count(), 1);
-        assert!(manager.get_nodes().len() > 0);
+        assert!(!manager.get_nodes().is_empty());
     }
 
     #[tokio::test]
@@ -472,6 +542,35 @@ mod tests {
         assert_eq!(manager.get_leader().await, Some(manager.node_id().to_string()));
     }
 
+    #[tokio::test]
+    async fn test_leadership_changes() {
+        let manager = ClusterManager::new(ClusterConfig::default());
+        let mut leadership = manager.subscribe_leadership();
+        assert!(!*leadership.borrow());
+
+        manager.become_leader().await;
+        assert!(leadership.has_changed().unwrap());
+        assert!(*leadership.borrow_and_update());
+
+        manager.step_down().await;
+        assert!(!manager.is_leader().await);
+        assert_eq!(manager.get_leader().await, None);
+        assert!(!*leadership.borrow_and_update());
+    }
+
+    #[tokio::test]
+    async fn test_read_replica_never_leads() {
+        let manager = ClusterManager::new(ClusterConfig {
+            read_replica: true,
+            ..Default::default()
+        });
+        manager.init().await.unwrap();
+
+        assert_eq!(manager.role().await, NodeRole::ReadReplica);
+        assert!(!manager.is_leader().await);
+        assert_eq!(manager.get_leader().await, None);
+    }
+
     #[test]
     fn test_node_metadata_default() {
         let metadata = NodeMetadata::default();
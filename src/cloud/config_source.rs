@@ -0,0 +1,135 @@
+//! Config files kept in object storage
+//!
+//! `supermcp serve --config s3://bucket/fleet/config.toml` fetches the
+//! object into a local cache file and loads that. The object is fetched
+//! again when its ETag changes, either on a polling interval or when
+//! `POST /admin/v1/config/refresh` is called (e.g. by a bucket
+//! notification); rewriting the cache file triggers the usual config
+//! reload.
+
+use crate::cloud::location::Location;
+use crate::config::{ConfigRefresh, ConfigSourceConfig};
+use crate::utils::errors::{McpError, McpResult};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Directory remote config files are cached in
+const CACHE_DIR: &str = "~/.cache/supermcp/config";
+
+/// A config file fetched from an object storage URL
+pub struct RemoteConfig {
+    url: String,
+    location: Location,
+    name: String,
+    local_path: PathBuf,
+    version: Mutex<Option<String>>,
+}
+
+impl RemoteConfig {
+    pub fn open(url: &str) -> McpResult<Self> {
+        let (parent, name) = url
+            .rsplit_once('/')
+            .filter(|(parent, name)| !name.is_empty() && !parent.ends_with(":/"))
+            .ok_or_else(|| {
+                McpError::ConfigError(format!("Config URL {} does not name an object", url))
+            })?;
+
+        // Keep the object name so the format is still detected by extension
+        let digest = ring::digest::digest(&ring::digest::SHA256, url.as_bytes());
+        let prefix: String = digest.as_ref()[..6].iter().map(|b| format!("{:02x}", b)).collect();
+        let local_path = PathBuf::from(shellexpand::tilde(CACHE_DIR).to_string())
+            .join(format!("{}-{}", prefix, name));
+
+        Ok(Self {
+            url: url.to_string(),
+            location: Location::parse(parent)?,
+            name: name.to_string(),
+            local_path,
+            version: Mutex::new(None),
+        })
+    }
+
+    /// Local copy of the config, current as of the last fetch
+    pub fn local_path(&self) -> &Path {
+        &self.local_path
+    }
+
+    /// Download the object if it changed since the last fetch
+    ///
+    /// Returns whether the local copy was rewritten.
+    pub async fn fetch(&self) -> McpResult<bool> {
+        let version = self.location.version(&self.name).await?;
+        if version.is_some() && *self.version.lock() == version {
+            return Ok(false);
+        }
+
+        let content = self
+            .location
+            .read(&self.name)
+            .await?
+            .ok_or_else(|| McpError::ConfigError(format!("Config object {} not found", self.url)))?;
+        if let Some(dir) = self.local_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&self.local_path, content).await?;
+
+        info!("Fetched config from {}", self.url);
+        *self.version.lock() = version;
+        Ok(true)
+    }
+
+    /// Keep the local copy current according to `config`
+    pub fn spawn_refresh(self: Arc<Self>, config: &ConfigSourceConfig) {
+        if config.refresh != ConfigRefresh::Poll {
+            debug!("Config from {} refreshes on request only", self.url);
+            return;
+        }
+
+        let period = Duration::from_secs(config.poll_interval_seconds.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.fetch().await {
+                    warn!("Failed to refresh config from {}: {}", self.url, e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_only_when_changed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("config.toml");
+        std::fs::write(&source, "[server]\nport = 3000\n").unwrap();
+
+        let mut remote = RemoteConfig::open(&source.display().to_string()).unwrap();
+        remote.local_path = dir.path().join("cache").join("config.toml");
+
+        assert!(remote.fetch().await.unwrap());
+        assert!(!remote.fetch().await.unwrap());
+        assert_eq!(
+            std::fs::read_to_string(remote.local_path()).unwrap(),
+            "[server]\nport = 3000\n"
+        );
+
+        std::fs::write(&source, "[server]\nport = 4000\n").unwrap();
+        assert!(remote.fetch().await.unwrap());
+        assert!(std::fs::read_to_string(remote.local_path()).unwrap().contains("4000"));
+    }
+
+    #[test]
+    fn test_url_must_name_an_object() {
+        assert!(RemoteConfig::open("s3://bucket").is_err());
+        assert!(RemoteConfig::open("s3://bucket/").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Consistent hashing of sessions to cluster nodes
+//!
+//! Every node publishes the URL of its listener in the state backend under
+//! `cluster/nodes/` and builds a hash ring from the nodes seen recently.
+//! A session belongs to the node its ID hashes to; the node creating a
+//! session picks an ID it owns, so sessions only move when nodes join or
+//! leave. Read replicas publish themselves too but own no sessions; they
+//! forward to the owner, or to the leader for requests outside a session.
+
+use crate::cloud::StateBackend;
+use crate::config::ClusterConfig;
+use crate::utils::errors::McpResult;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// State backend key prefix for node records
+const KEY_PREFIX: &str = "cluster/nodes/";
+
+/// Points each node has on the ring, evening out the share of sessions
+const VIRTUAL_NODES: usize = 64;
+
+/// Attempts at an ID owned by this node before taking any ID
+const MAX_ID_ATTEMPTS: usize = 256;
+
+fn hash(value: &str) -> u64 {
+    let digest = ring::digest::digest(&ring::digest::SHA256, value.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest.as_ref()[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Nodes placed on a hash ring
+#[derive(Debug, Default)]
+pub struct HashRing {
+    points: BTreeMap<u64, String>,
+}
+
+impl HashRing {
+    pub fn new<'a>(nodes: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut points = BTreeMap::new();
+        for node in nodes {
+            for i in 0..VIRTUAL_NODES {
+                points.insert(hash(&format!("{}#{}", node, i)), node.to_string());
+            }
+        }
+        Self { points }
+    }
+
+    /// Node owning `key`: the first one clockwise from its hash
+    pub fn owner(&self, key: &str) -> Option<&str> {
+        let hash = hash(key);
+        self.points
+            .range(hash..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, node)| node.as_str())
+    }
+}
+
+/// A node as published in the state backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NodeRecord {
+    id: String,
+    url: String,
+    last_seen: DateTime<Utc>,
+    #[serde(default)]
+    replica: bool,
+    #[serde(default)]
+    leader: bool,
+}
+
+/// Live nodes and the ring built from them
+#[derive(Default)]
+struct Members {
+    ring: HashRing,
+    urls: HashMap<String, String>,
+    leader: Option<String>,
+}
+
+/// Which node owns which session
+pub struct SessionRing {
+    backend: Arc<dyn StateBackend>,
+    node_id: String,
+    url: String,
+    replica: bool,
+    interval: Duration,
+    timeout: chrono::Duration,
+    members: RwLock<Members>,
+}
+
+impl SessionRing {
+    /// Ring for `[cluster]`, publishing this node as `node_id` at
+    /// `advertise_url`
+    pub fn new(backend: Arc<dyn StateBackend>, config: &ClusterConfig, node_id: &str) -> Self {
+        Self {
+            backend,
+            node_id: node_id.to_string(),
+            url: config.advertise_url.clone().unwrap_or_default(),
+            replica: config.read_replica,
+            interval: Duration::from_millis(config.heartbeat_interval_ms),
+            timeout: chrono::Duration::milliseconds(config.heartbeat_timeout_ms as i64),
+            members: RwLock::new(Members::default()),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Node owning a session, with its URL; `None` when it is this node or
+    /// the owner is unknown
+    pub fn owner(&self, session_id: &str) -> Option<(String, String)> {
+        let members = self.members.read();
+        let owner = members.ring.owner(session_id)?;
+        if owner == self.node_id {
+            return None;
+        }
+        let url = members.urls.get(owner)?;
+        Some((owner.to_string(), url.clone()))
+    }
+
+    /// Node a read replica forwards to: the session's owner, else the
+    /// leader, else any node owning sessions
+    pub fn forward_target(&self, session_id: Option<&str>) -> Option<(String, String)> {
+        let members = self.members.read();
+        let key = uuid::Uuid::new_v4().to_string();
+        let node = match (session_id, &members.leader) {
+            (Some(id), _) => members.ring.owner(id)?,
+            (None, Some(leader)) => leader.as_str(),
+            (None, None) => members.ring.owner(&key)?,
+        };
+        let url = members.urls.get(node)?;
+        Some((node.to_string(), url.clone()))
+    }
+
+    /// A new session ID owned by this node
+    pub fn local_session_id(&self) -> String {
+        let members = self.members.read();
+        let mut id = uuid::Uuid::new_v4().to_string();
+        for _ in 1..MAX_ID_ATTEMPTS {
+            match members.ring.owner(&id) {
+                Some(owner) if owner != self.node_id => id = uuid::Uuid::new_v4().to_string(),
+                _ => break,
+            }
+        }
+        id
+    }
+
+    /// Publish this node and rebuild the ring from the live nodes
+    pub async fn refresh(&self) -> McpResult<()> {
+        let now = Utc::now();
+        let record = NodeRecord {
+            id: self.node_id.clone(),
+            url: self.url.clone(),
+            last_seen: now,
+            replica: self.replica,
+            leader: !self.replica && crate::cloud::leader::is_leader().await,
+        };
+        let key = format!("{}{}", KEY_PREFIX, URL_SAFE_NO_PAD.encode(&self.node_id));
+        self.backend.set(&key, serde_json::to_vec(&record)?).await?;
+
+        let mut urls = HashMap::new();
+        let mut leader = None;
+        for key in self.backend.list(KEY_PREFIX).await? {
+            let Some(data) = self.backend.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<NodeRecord>(&data) {
+                Ok(node) if now - node.last_seen <= self.timeout && !node.replica => {
+                    if node.leader {
+                        leader = Some(node.id.clone());
+                    }
+                    urls.insert(node.id, node.url);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Ignoring unreadable node record {}: {}", key, e),
+            }
+        }
+
+        let mut members = self.members.write();
+        if members.urls.len() != urls.len() {
+            info!("Session ring has {} node(s)", urls.len());
+        }
+        members.ring = HashRing::new(urls.keys().map(String::as_str));
+        members.urls = urls;
+        members.leader = leader;
+        Ok(())
+    }
+
+    /// Keep this node published and the ring current
+    pub fn spawn_refresh(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh().await {
+                    warn!("Failed to refresh the session ring: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::InMemoryBackend;
+
+    #[test]
+    fn test_hash_ring_moves_few_keys() {
+        let before = HashRing::new(["a", "b", "c"]);
+        let after = HashRing::new(["a", "b", "c", "d"]);
+        let keys: Vec<String> = (0..1000).map(|i| format!("session-{}", i)).collect();
+
+        let mut moved = 0;
+        for key in &keys {
+            let (old, new) = (before.owner(key).unwrap(), after.owner(key).unwrap());
+            if old != new {
+                // Keys only move to the new node
+                assert_eq!(new, "d");
+                moved += 1;
+            }
+        }
+        assert!(moved > 100 && moved < 400, "moved {}", moved);
+        assert_eq!(HashRing::default().owner("x"), None);
+    }
+
+    #[tokio::test]
+    async fn test_session_ring() {
+        let backend: Arc<dyn StateBackend> = Arc::new(InMemoryBackend::new());
+        let config = |url: &str| ClusterConfig {
+            advertise_url: Some(url.to_string()),
+            ..Default::default()
+        };
+        let a = SessionRing::new(backend.clone(), &config("http://a:3000"), "a");
+        let b = SessionRing::new(backend.clone(), &config("http://b:3000"), "b");
+        a.refresh().await.unwrap();
+        b.refresh().await.unwrap();
+        a.refresh().await.unwrap();
+
+        let id = a.local_session_id();
+        assert_eq!(a.owner(&id), None);
+        assert_eq!(b.owner(&id), Some(("a".to_string(), "http://a:3000".to_string())));
+
+        // A replica owns no sessions and forwards to the owner
+        let replica = SessionRing::new(
+            backend.clone(),
+            &ClusterConfig {
+                read_replica: true,
+                ..config("http://r:3000")
+            },
+            "r",
+        );
+        replica.refresh().await.unwrap();
+        b.refresh().await.unwrap();
+        for _ in 0..20 {
+            assert_ne!(b.owner(&uuid::Uuid::new_v4().to_string()).map(|(id, _)| id).as_deref(), Some("r"));
+        }
+        assert_eq!(replica.forward_target(Some(&id)).unwrap().0, "a");
+        assert!(replica.forward_target(None).is_some());
+    }
+}
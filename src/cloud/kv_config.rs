@@ -0,0 +1,332 @@
+//! Config stored under an etcd or Consul key
+//!
+//! `supermcp serve --config etcd://host:2379/supermcp/config.toml` (or
+//! `consul://host:8500/...`, with `+https` after the scheme for TLS) reads
+//! the config from the key and watches it: etcd through the v3 JSON
+//! gateway's watch stream, Consul through blocking queries. Each change is
+//! applied through [`ConfigManager::apply_revision`], the same reload path
+//! as file changes, and audited with the key's revision.
+
+use crate::config::ConfigManager;
+use crate::utils::errors::{McpError, McpResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Directory the config is cached in for the initial load
+const CACHE_DIR: &str = "~/.cache/supermcp/config";
+
+/// How long a Consul blocking query waits for a change
+const CONSUL_WAIT: &str = "5m";
+
+/// Pause before watching again after an error
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Whether a config location names an etcd or Consul key
+pub fn is_kv_url(location: &str) -> bool {
+    ["etcd://", "etcd+https://", "consul://", "consul+https://"]
+        .iter()
+        .any(|scheme| location.starts_with(scheme))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KvStore {
+    Etcd,
+    Consul,
+}
+
+/// One revision of the key's value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvRevision {
+    pub content: String,
+    pub revision: u64,
+}
+
+/// Integers in etcd's JSON gateway responses are strings
+fn as_u64(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str()?.parse().ok())
+}
+
+fn decode_value(encoded: &str) -> McpResult<String> {
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|e| McpError::ConfigError(format!("Invalid config value encoding: {}", e)))?;
+    String::from_utf8(bytes)
+        .map_err(|e| McpError::ConfigError(format!("Config value is not UTF-8: {}", e)))
+}
+
+/// Revision from an etcd key-value (`kvs[]` of a range, `kv` of an event)
+fn etcd_revision(kv: &Value) -> McpResult<KvRevision> {
+    Ok(KvRevision {
+        content: decode_value(kv.get("value").and_then(Value::as_str).unwrap_or_default())?,
+        revision: kv.get("mod_revision").and_then(as_u64).unwrap_or_default(),
+    })
+}
+
+/// Revision from a Consul `/v1/kv` response
+fn consul_revision(body: &Value) -> McpResult<Option<KvRevision>> {
+    let Some(entry) = body.as_array().and_then(|entries| entries.first()) else {
+        return Ok(None);
+    };
+    Ok(Some(KvRevision {
+        content: decode_value(entry.get("Value").and_then(Value::as_str).unwrap_or_default())?,
+        revision: entry.get("ModifyIndex").and_then(as_u64).unwrap_or_default(),
+    }))
+}
+
+/// A config key in etcd or Consul
+pub struct KvConfigSource {
+    url: String,
+    store: KvStore,
+    base_url: String,
+    key: String,
+    client: reqwest::Client,
+}
+
+impl KvConfigSource {
+    pub fn open(url: &str) -> McpResult<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| McpError::ConfigError(format!("Invalid config URL: {}", url)))?;
+        let (store, tls) = match scheme {
+            "etcd" => (KvStore::Etcd, false),
+            "etcd+https" => (KvStore::Etcd, true),
+            "consul" => (KvStore::Consul, false),
+            "consul+https" => (KvStore::Consul, true),
+            _ => {
+                return Err(McpError::ConfigError(format!(
+                    "Unsupported config source '{}'; use etcd:// or consul://",
+                    scheme
+                )))
+            }
+        };
+        let (host, key) = rest
+            .split_once('/')
+            .filter(|(host, key)| !host.is_empty() && !key.is_empty())
+            .ok_or_else(|| McpError::ConfigError(format!("Config URL {} does not name a key", url)))?;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        if store == KvStore::Consul {
+            if let Ok(token) = std::env::var("CONSUL_HTTP_TOKEN") {
+                let value = reqwest::header::HeaderValue::from_str(&token)
+                    .map_err(|e| McpError::ConfigError(format!("Invalid CONSUL_HTTP_TOKEN: {}", e)))?;
+                headers.insert("X-Consul-Token", value);
+            }
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| McpError::TransportError(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            store,
+            base_url: format!("{}://{}", if tls { "https" } else { "http" }, host),
+            key: key.to_string(),
+            client,
+        })
+    }
+
+    /// Local copy for [`ConfigManager`] to load at startup; keeps the key's
+    /// last segment so the format is detected by extension
+    pub fn cache_path(&self) -> PathBuf {
+        let digest = ring::digest::digest(&ring::digest::SHA256, self.url.as_bytes());
+        let prefix: String = digest.as_ref()[..6].iter().map(|b| format!("{:02x}", b)).collect();
+        let name = self.key.rsplit('/').next().unwrap_or("config");
+        PathBuf::from(shellexpand::tilde(CACHE_DIR).to_string()).join(format!("{}-{}", prefix, name))
+    }
+
+    /// Current value of the key
+    pub async fn fetch(&self) -> McpResult<KvRevision> {
+        let revision = match self.store {
+            KvStore::Etcd => {
+                let body = self
+                    .post_etcd("/v3/kv/range", json!({ "key": STANDARD.encode(&self.key) }))
+                    .await?;
+                match body.get("kvs").and_then(Value::as_array).and_then(|kvs| kvs.first()) {
+                    Some(kv) => Some(etcd_revision(kv)?),
+                    None => None,
+                }
+            }
+            KvStore::Consul => self.get_consul(None).await?,
+        };
+        revision.ok_or_else(|| McpError::ConfigError(format!("Config key {} not found", self.url)))
+    }
+
+    /// Write the current value to [`cache_path`](Self::cache_path)
+    pub async fn fetch_to_cache(&self) -> McpResult<(PathBuf, KvRevision)> {
+        let current = self.fetch().await?;
+        let path = self.cache_path();
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, &current.content).await?;
+        info!("Loaded config from {} at revision {}", self.url, current.revision);
+        Ok((path, current))
+    }
+
+    /// Wait for the key to change after `revision`
+    ///
+    /// Returns `None` when the wait ended without a change (a Consul
+    /// blocking query timing out, or the etcd stream closing).
+    pub async fn wait_for_change(&self, revision: u64) -> McpResult<Option<KvRevision>> {
+        match self.store {
+            KvStore::Etcd => self.watch_etcd(revision).await,
+            KvStore::Consul => {
+                // A missing key answers at once; report it so the watch
+                // backs off instead of spinning
+                let current = self.get_consul(Some(revision)).await?.ok_or_else(|| {
+                    McpError::ConfigError(format!("Config key {} not found", self.url))
+                })?;
+                Ok(Some(current).filter(|current| current.revision != revision))
+            }
+        }
+    }
+
+    /// Apply every change of the key to `manager` in the background
+    pub fn spawn_watch(self: Arc<Self>, manager: Arc<ConfigManager>, mut revision: u64) {
+        tokio::spawn(async move {
+            loop {
+                match self.wait_for_change(revision).await {
+                    Ok(Some(current)) => {
+                        info!("Config {} changed (revision {})", self.url, current.revision);
+                        revision = current.revision;
+                        let _ = manager
+                            .apply_revision(&self.url, &current.content, &revision.to_string())
+                            .await;
+                    }
+                    Ok(None) => debug!("No change to {}", self.url),
+                    Err(e) => {
+                        warn!("Failed to watch config {}: {}", self.url, e);
+                        tokio::time::sleep(RETRY_DELAY).await;
+                    }
+                }
+            }
+        });
+    }
+
+    async fn post_etcd(&self, path: &str, body: Value) -> McpResult<Value> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| McpError::TransportError(format!("etcd request failed: {}", e)))?
+            .error_for_status()
+            .map_err(|e| McpError::TransportError(format!("etcd request failed: {}", e)))?;
+        response
+            .json()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Invalid etcd response: {}", e)))
+    }
+
+    /// Read the first event from a watch stream starting after `revision`
+    async fn watch_etcd(&self, revision: u64) -> McpResult<Option<KvRevision>> {
+        let request = json!({
+            "create_request": {
+                "key": STANDARD.encode(&self.key),
+                "start_revision": (revision + 1).to_string(),
+            }
+        });
+        let mut response = self
+            .client
+            .post(format!("{}/v3/watch", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| McpError::TransportError(format!("etcd watch failed: {}", e)))?
+            .error_for_status()
+            .map_err(|e| McpError::TransportError(format!("etcd watch failed: {}", e)))?;
+
+        // The gateway streams one JSON object per line
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| McpError::TransportError(format!("etcd watch failed: {}", e)))?
+        {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let Ok(message) = serde_json::from_slice::<Value>(&line) else {
+                    continue;
+                };
+                // Of several changes in one batch only the last matters
+                let events = message.pointer("/result/events").and_then(Value::as_array);
+                let Some(event) = events.and_then(|events| events.last()) else {
+                    continue;
+                };
+                if event.get("type").and_then(Value::as_str) == Some("DELETE") {
+                    warn!("Config key {} was deleted; keeping the current config", self.url);
+                    continue;
+                }
+                if let Some(kv) = event.get("kv") {
+                    return Ok(Some(etcd_revision(kv)?));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// `GET /v1/kv/<key>`, as a blocking query when `index` is given
+    async fn get_consul(&self, index: Option<u64>) -> McpResult<Option<KvRevision>> {
+        let mut request = self.client.get(format!("{}/v1/kv/{}", self.base_url, self.key));
+        if let Some(index) = index {
+            request = request.query(&[("index", index.to_string()), ("wait", CONSUL_WAIT.to_string())]);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Consul request failed: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: Value = response
+            .error_for_status()
+            .map_err(|e| McpError::TransportError(format!("Consul request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Invalid Consul response: {}", e)))?;
+        consul_revision(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_urls() {
+        let etcd = KvConfigSource::open("etcd+https://etcd.internal:2379/supermcp/config.toml").unwrap();
+        assert_eq!(etcd.store, KvStore::Etcd);
+        assert_eq!(etcd.base_url, "https://etcd.internal:2379");
+        assert_eq!(etcd.key, "supermcp/config.toml");
+        assert!(etcd.cache_path().to_string_lossy().ends_with("-config.toml"));
+
+        let consul = KvConfigSource::open("consul://127.0.0.1:8500/supermcp/config").unwrap();
+        assert_eq!(consul.store, KvStore::Consul);
+        assert_eq!(consul.base_url, "http://127.0.0.1:8500");
+
+        assert!(KvConfigSource::open("consul://127.0.0.1:8500/").is_err());
+        assert!(KvConfigSource::open("zookeeper://host/key").is_err());
+        assert!(is_kv_url("etcd://host/key"));
+        assert!(!is_kv_url("s3://bucket/key"));
+    }
+
+    #[test]
+    fn test_decode_responses() {
+        let kv = json!({ "key": "a2V5", "value": STANDARD.encode("[server]\nport = 1\n"), "mod_revision": "42" });
+        assert_eq!(
+            etcd_revision(&kv).unwrap(),
+            KvRevision { content: "[server]\nport = 1\n".to_string(), revision: 42 }
+        );
+
+        let body = json!([{ "Key": "supermcp/config", "Value": STANDARD.encode("{}"), "ModifyIndex": 7 }]);
+        assert_eq!(consul_revision(&body).unwrap().unwrap().revision, 7);
+        assert_eq!(consul_revision(&json!([])).unwrap(), None);
+    }
+}
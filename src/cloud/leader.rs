@@ -0,0 +1,126 @@
+//! Singleton background jobs in a cluster
+//!
+//! Jobs that work on state shared by every instance, like artifact GC,
+//! run only on the cluster leader when `[cluster]` is enabled. Followers
+//! keep waiting, and whichever node is elected next takes the jobs over.
+//! Without a cluster every job runs locally.
+
+use crate::cloud::ClusterManager;
+use once_cell::sync::OnceCell;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+static CLUSTER: OnceCell<Arc<ClusterManager>> = OnceCell::new();
+
+/// Install the cluster whose leader runs singleton jobs; false if one was
+/// already installed
+pub fn install(cluster: Arc<ClusterManager>) -> bool {
+    CLUSTER.set(cluster).is_ok()
+}
+
+/// The installed cluster, if any
+pub fn cluster() -> Option<&'static Arc<ClusterManager>> {
+    CLUSTER.get()
+}
+
+/// Whether this instance should run singleton jobs right now
+pub async fn is_leader() -> bool {
+    match CLUSTER.get() {
+        Some(cluster) => cluster.is_leader().await,
+        None => true,
+    }
+}
+
+/// Run `job` every `period`, only while this instance is the leader
+pub fn spawn_leader_only<F, Fut>(name: &'static str, period: Duration, job: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let leadership = CLUSTER.get().map(|cluster| cluster.subscribe_leadership());
+    tokio::spawn(run_while_leader(name, period, leadership, job))
+}
+
+async fn run_while_leader<F, Fut>(
+    name: &'static str,
+    period: Duration,
+    leadership: Option<watch::Receiver<bool>>,
+    mut job: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let Some(mut leadership) = leadership else {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            job().await;
+        }
+    };
+
+    loop {
+        if leadership.wait_for(|leader| *leader).await.is_err() {
+            return;
+        }
+        info!("Running {} as cluster leader", name);
+
+        let mut interval = tokio::time::interval(period);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => job().await,
+                changed = leadership.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    if !*leadership.borrow_and_update() {
+                        info!("Pausing {}, no longer cluster leader", name);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_runs_only_while_leader() {
+        let (leadership, receiver) = watch::channel(false);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let period = Duration::from_millis(20);
+        tokio::spawn(run_while_leader("test", period, Some(receiver), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+
+        tokio::time::sleep(period * 5).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        leadership.send_replace(true);
+        tokio::time::sleep(period * 5).await;
+        let led = runs.load(Ordering::SeqCst);
+        assert!(led >= 2);
+
+        leadership.send_replace(false);
+        tokio::time::sleep(period).await;
+        let paused = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(period * 5).await;
+        assert_eq!(runs.load(Ordering::SeqCst), paused);
+
+        // Elected again after a failover
+        leadership.send_replace(true);
+        tokio::time::sleep(period * 2).await;
+        assert!(runs.load(Ordering::SeqCst) > paused);
+    }
+}
@@ -0,0 +1,181 @@
+//! Storage locations shared by the artifact store, registry cache and
+//! remote config
+//!
+//! A location is a local directory or, with the object-storage feature, an
+//! `s3://`, `gs://` or `az://` prefix. Objects in it are addressed by name.
+
+use crate::utils::errors::{McpError, McpResult};
+use std::path::PathBuf;
+#[cfg(feature = "object-storage")]
+use std::sync::Arc;
+
+/// A directory of named objects
+pub enum Location {
+    Local(PathBuf),
+    #[cfg(feature = "object-storage")]
+    Object {
+        store: Arc<dyn object_store::ObjectStore>,
+        prefix: object_store::path::Path,
+    },
+}
+
+#[cfg(feature = "object-storage")]
+fn object_error(action: &str, name: &str, e: object_store::Error) -> McpError {
+    McpError::InternalError(format!("Failed to {} {}: {}", action, name, e))
+}
+
+impl Location {
+    /// Local path (`~` is expanded) or object storage URL
+    pub fn parse(location: &str) -> McpResult<Self> {
+        if !crate::cloud::is_object_url(location) {
+            return Ok(Location::Local(PathBuf::from(shellexpand::tilde(location).to_string())));
+        }
+
+        #[cfg(feature = "object-storage")]
+        {
+            let (store, prefix) = crate::cloud::object_storage::open(location)?;
+            Ok(Location::Object { store, prefix })
+        }
+        #[cfg(not(feature = "object-storage"))]
+        Err(McpError::ConfigError(format!(
+            "{} needs supermcp built with the object-storage feature",
+            location
+        )))
+    }
+
+    pub async fn write(&self, name: &str, bytes: Vec<u8>) -> McpResult<()> {
+        match self {
+            Location::Local(dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                tokio::fs::write(dir.join(name), bytes).await?;
+            }
+            #[cfg(feature = "object-storage")]
+            Location::Object { store, prefix } => {
+                store
+                    .put(&prefix.child(name), bytes.into())
+                    .await
+                    .map_err(|e| object_error("write", name, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Contents of an object, or `None` if there is none by that name
+    pub async fn read(&self, name: &str) -> McpResult<Option<Vec<u8>>> {
+        match self {
+            Location::Local(dir) => match tokio::fs::read(dir.join(name)).await {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            #[cfg(feature = "object-storage")]
+            Location::Object { store, prefix } => match store.get(&prefix.child(name)).await {
+                Ok(result) => Ok(Some(
+                    result.bytes().await.map_err(|e| object_error("read", name, e))?.to_vec(),
+                )),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(e) => Err(object_error("read", name, e)),
+            },
+        }
+    }
+
+    /// Opaque version of an object (ETag, or modification time for local
+    /// files) that changes whenever the object does
+    pub async fn version(&self, name: &str) -> McpResult<Option<String>> {
+        match self {
+            Location::Local(dir) => match tokio::fs::metadata(dir.join(name)).await {
+                Ok(metadata) => {
+                    let modified = metadata
+                        .modified()?
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default();
+                    Ok(Some(format!("{}-{}", modified.as_nanos(), metadata.len())))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            #[cfg(feature = "object-storage")]
+            Location::Object { store, prefix } => match store.head(&prefix.child(name)).await {
+                Ok(meta) => Ok(Some(
+                    meta.e_tag.unwrap_or_else(|| meta.last_modified.to_rfc3339()),
+                )),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(e) => Err(object_error("check", name, e)),
+            },
+        }
+    }
+
+    /// Remove an object; a missing one is not an error
+    pub async fn remove(&self, name: &str) -> McpResult<()> {
+        match self {
+            Location::Local(dir) => match tokio::fs::remove_file(dir.join(name)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            },
+            #[cfg(feature = "object-storage")]
+            Location::Object { store, prefix } => match store.delete(&prefix.child(name)).await {
+                Err(e) if !matches!(e, object_store::Error::NotFound { .. }) => {
+                    Err(object_error("delete", name, e))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// Names of all objects
+    pub async fn list(&self) -> McpResult<Vec<String>> {
+        match self {
+            Location::Local(dir) => {
+                let mut names = Vec::new();
+                let mut entries = match tokio::fs::read_dir(dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+                    Err(e) => return Err(e.into()),
+                };
+                while let Some(entry) = entries.next_entry().await? {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+                Ok(names)
+            }
+            #[cfg(feature = "object-storage")]
+            Location::Object { store, prefix } => {
+                use futures::TryStreamExt;
+                let objects: Vec<_> = store
+                    .list(Some(prefix))
+                    .try_collect()
+                    .await
+                    .map_err(|e| object_error("list", prefix.as_ref(), e))?;
+                Ok(objects
+                    .into_iter()
+                    .filter_map(|meta| meta.location.filename().map(str::to_string))
+                    .collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_location() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let location = Location::parse(&dir.path().join("nested").display().to_string()).unwrap();
+
+        assert!(location.list().await.unwrap().is_empty());
+        assert_eq!(location.version("a").await.unwrap(), None);
+
+        location.write("a", b"one".to_vec()).await.unwrap();
+        let first = location.version("a").await.unwrap().unwrap();
+        assert_eq!(location.read("a").await.unwrap().as_deref(), Some(&b"one"[..]));
+
+        location.write("a", b"three".to_vec()).await.unwrap();
+        assert_ne!(location.version("a").await.unwrap().unwrap(), first);
+        assert_eq!(location.list().await.unwrap(), vec!["a".to_string()]);
+
+        location.remove("a").await.unwrap();
+        location.remove("a").await.unwrap();
+        assert_eq!(location.read("a").await.unwrap(), None);
+    }
+}
@@ -2,10 +2,29 @@
 //!
 //! Provides multi-tenancy, horizontal scaling, and distributed operation.
 
+pub mod artifacts;
 pub mod cluster;
+pub mod config_source;
+pub mod hash_ring;
+pub mod kv_config;
+pub mod leader;
+pub mod location;
 pub mod multi_tenant;
+#[cfg(feature = "object-storage")]
+pub mod object_storage;
 pub mod state;
+pub mod tenant_keys;
 
 pub use cluster::{ClusterManager, ClusterConfig, NodeInfo};
 pub use multi_tenant::{TenantManager, Tenant, TenantConfig};
-pub use state::{DistributedState, StateBackend};
+pub use artifacts::{ArtifactMeta, ArtifactStore};
+pub use config_source::RemoteConfig;
+pub use kv_config::{is_kv_url, KvConfigSource};
+pub use location::Location;
+pub use state::{create_state_backend, DistributedState, FileBackend, InMemoryBackend, StateBackend};
+
+/// Whether a location names object storage (S3, GCS, Azure Blob) rather
+/// than a local path
+pub fn is_object_url(location: &str) -> bool {
+    ["s3://", "gs://", "az://"].iter().any(|scheme| location.starts_with(scheme))
+}
@@ -0,0 +1,36 @@
+//! S3, GCS and Azure Blob locations
+//!
+//! Locations are URLs: `s3://bucket/prefix`, `gs://bucket/prefix` or
+//! `az://container/prefix`. Credentials are never configured here; each
+//! client reads its provider's usual environment variables and falls back
+//! to the instance's IAM role, workload identity or managed identity.
+
+use crate::utils::errors::{McpError, McpResult};
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use std::sync::Arc;
+
+/// Client for the bucket in `url`, and the prefix within it
+pub fn open(url: &str) -> McpResult<(Arc<dyn ObjectStore>, Path)> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| McpError::ConfigError(format!("Invalid object storage URL {}: {}", url, e)))?;
+    let error = |e: object_store::Error| {
+        McpError::ConfigError(format!("Failed to open {}: {}", url, e))
+    };
+
+    let store: Arc<dyn ObjectStore> = match parsed.scheme() {
+        "s3" => Arc::new(AmazonS3Builder::from_env().with_url(url).build().map_err(error)?),
+        "gs" => Arc::new(GoogleCloudStorageBuilder::from_env().with_url(url).build().map_err(error)?),
+        "az" => Arc::new(MicrosoftAzureBuilder::from_env().with_url(url).build().map_err(error)?),
+        scheme => {
+            return Err(McpError::ConfigError(format!(
+                "Unsupported object storage scheme '{}'; use s3://, gs:// or az://",
+                scheme
+            )))
+        }
+    };
+    Ok((store, Path::from(parsed.path().trim_matches('/'))))
+}
@@ -299,6 +299,188 @@ impl StateBackend for InMemoryBackend {
     }
 }
 
+/// File-backed state backend (single node, persistent across restarts)
+///
+/// Each key is stored as a file beneath the root directory, with `/` in
+/// keys mapping to subdirectories. Watchers only observe writes made
+/// through this process.
+pub struct FileBackend {
+    root: std::path::PathBuf,
+    watchers: Arc<RwLock<HashMap<String, Vec<tokio::sync::mpsc::Sender<StateEvent>>>>>,
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl FileBackend {
+    /// Create a new file backend rooted at `root`
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            watchers: Arc::new(RwLock::new(HashMap::new())),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn key_path(&self, key: &str) -> McpResult<std::path::PathBuf> {
+        if key.is_empty() || key.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return Err(McpError::InvalidRequest(format!("Invalid state key: {}", key)));
+        }
+        Ok(self.root.join(key))
+    }
+
+    async fn notify_watchers(&self, key: &str, event: StateEvent) {
+        let watchers = self.watchers.read().await;
+
+        for (prefix, senders) in watchers.iter() {
+            if key.starts_with(prefix) {
+                for sender in senders {
+                    let _ = sender.send(event.clone()).await;
+                }
+            }
+        }
+    }
+
+    async fn write_value(&self, key: &str, value: &[u8]) -> McpResult<()> {
+        let path = self.key_path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write atomically so readers never observe a partial value
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, value).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StateBackend for FileBackend {
+    async fn get(&self, key: &str) -> McpResult<Option<Vec<u8>>> {
+        let path = self.key_path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(McpError::Io(e)),
+        }
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>) -> McpResult<()> {
+        let existed = {
+            let _guard = self.write_lock.lock().await;
+            let existed = self.get(key).await?.is_some();
+            self.write_value(key, &value).await?;
+            existed
+        };
+
+        let event = if existed {
+            StateEvent::Updated {
+                key: key.to_string(),
+                value,
+            }
+        } else {
+            StateEvent::Created {
+                key: key.to_string(),
+                value,
+            }
+        };
+
+        self.notify_watchers(key, event).await;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> McpResult<()> {
+        let path = self.key_path(key)?;
+        {
+            let _guard = self.write_lock.lock().await;
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(McpError::Io(e)),
+            }
+        }
+
+        self.notify_watchers(
+            key,
+            StateEvent::Deleted {
+                key: key.to_string(),
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    async fn watch(&self, key: &str) -> McpResult<tokio::sync::mpsc::Receiver<StateEvent>> {
+        let (tx, rx) = tokio::sync::mpsc::channel(100);
+
+        let mut watchers = self.watchers.write().await;
+        watchers
+            .entry(key.to_string())
+            .or_insert_with(Vec::new)
+            .push(tx);
+
+        Ok(rx)
+    }
+
+    async fn cas(&self, key: &str, expected: Option<Vec<u8>>, new: Vec<u8>) -> McpResult<bool> {
+        let _guard = self.write_lock.lock().await;
+
+        if self.get(key).await? == expected {
+            self.write_value(key, &new).await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> McpResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(McpError::Io(e)),
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                if path.extension().is_some_and(|ext| ext == "tmp") {
+                    continue;
+                }
+                if let Ok(relative) = path.strip_prefix(&self.root) {
+                    let key = relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    if key.starts_with(prefix) {
+                        keys.push(key);
+                    }
+                }
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Create the state backend selected in configuration
+pub fn create_state_backend(config: &crate::config::StateConfig) -> Arc<dyn StateBackend> {
+    match config.backend {
+        crate::config::StateBackendType::Memory => Arc::new(InMemoryBackend::new()),
+        crate::config::StateBackendType::File => {
+            let path = shellexpand::tilde(&config.path).to_string();
+            Arc::new(FileBackend::new(path))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -388,6 +570,35 @@ mod tests {
         assert!(keys.contains(&"prefix/key2".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_file_backend_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path());
+
+        backend.set("acme/example.com/cert", b"pem".to_vec()).await.unwrap();
+        backend.set("acme/account", b"key".to_vec()).await.unwrap();
+        backend.set("other/key", b"x".to_vec()).await.unwrap();
+
+        assert_eq!(
+            backend.get("acme/example.com/cert").await.unwrap(),
+            Some(b"pem".to_vec())
+        );
+        assert_eq!(
+            backend.list("acme/").await.unwrap(),
+            vec!["acme/account".to_string(), "acme/example.com/cert".to_string()]
+        );
+
+        assert!(!backend.cas("acme/account", None, b"new".to_vec()).await.unwrap());
+        assert!(backend
+            .cas("acme/account", Some(b"key".to_vec()), b"new".to_vec())
+            .await
+            .unwrap());
+
+        backend.delete("acme/account").await.unwrap();
+        assert_eq!(backend.get("acme/account").await.unwrap(), None);
+        assert!(backend.get("../escape").await.is_err());
+    }
+
     #[tokio::test]
     async fn test_watch() {
         let backend = InMemoryBackend::new();
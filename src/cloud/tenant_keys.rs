@@ -0,0 +1,310 @@
+//! Per-tenant data keys for encryption at rest
+//!
+//! Every tenant (authenticated user) gets a random data key the first
+//! time something of theirs is stored. The key is wrapped by a KMS and
+//! kept in the state backend under `tenant-keys/`, so nodes sharing the
+//! backend use the same key and only the KMS can unwrap it. Data is
+//! sealed as `MAGIC || nonce || ciphertext || tag` with ChaCha20-Poly1305.
+
+use crate::cloud::{create_state_backend, StateBackend};
+use crate::config::{Config, KmsProvider, VaultTransitConfig};
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::info;
+
+/// Prefix of data sealed with a tenant key
+pub const MAGIC: &[u8] = b"SMCPT1";
+
+/// State backend key prefix for wrapped data keys
+const KEY_PREFIX: &str = "tenant-keys/";
+
+/// Wraps and unwraps tenant data keys
+#[async_trait]
+pub trait KeyWrapper: Send + Sync {
+    async fn wrap(&self, tenant: &str, key: &[u8; 32]) -> McpResult<String>;
+    async fn unwrap(&self, tenant: &str, wrapped: &str) -> McpResult<[u8; 32]>;
+}
+
+fn random_bytes<const N: usize>() -> McpResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| McpError::InternalError("Failed to generate random bytes".to_string()))?;
+    Ok(bytes)
+}
+
+fn aead_key(key: &[u8; 32]) -> McpResult<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key)
+        .map_err(|_| McpError::InternalError("Invalid tenant key".to_string()))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn seal_with(key: &LessSafeKey, aad: &[u8], plaintext: &[u8]) -> McpResult<Vec<u8>> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let mut sealed = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut sealed)
+        .map_err(|_| McpError::InternalError("Encryption failed".to_string()))?;
+    Ok([nonce.as_slice(), &sealed].concat())
+}
+
+fn open_with(key: &LessSafeKey, aad: &[u8], sealed: &[u8]) -> McpResult<Vec<u8>> {
+    let invalid = || McpError::AuthError("Data is corrupt or sealed with another key".to_string());
+    if sealed.len() < NONCE_LEN {
+        return Err(invalid());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+    let mut buffer = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(aad), &mut buffer)
+        .map_err(|_| invalid())?;
+    Ok(plaintext.to_vec())
+}
+
+fn decode_key(encoded: &str, what: &str) -> McpResult<[u8; 32]> {
+    STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| McpError::ConfigError(format!("{} must be 32 bytes of base64", what)))
+}
+
+/// Wraps data keys in process with a key derived per tenant from a master key
+pub struct LocalKms {
+    master_key: [u8; 32],
+}
+
+impl LocalKms {
+    pub fn new(master_key: [u8; 32]) -> Self {
+        Self { master_key }
+    }
+
+    fn tenant_key(&self, tenant: &str) -> McpResult<LessSafeKey> {
+        let prk = Salt::new(HKDF_SHA256, b"supermcp tenant kek v1").extract(&self.master_key);
+        let info = [tenant.as_bytes()];
+        let okm = prk
+            .expand(&info, &CHACHA20_POLY1305)
+            .map_err(|_| McpError::InternalError("Key derivation failed".to_string()))?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+}
+
+#[async_trait]
+impl KeyWrapper for LocalKms {
+    async fn wrap(&self, tenant: &str, key: &[u8; 32]) -> McpResult<String> {
+        let sealed = seal_with(&self.tenant_key(tenant)?, tenant.as_bytes(), key)?;
+        Ok(STANDARD.encode(sealed))
+    }
+
+    async fn unwrap(&self, tenant: &str, wrapped: &str) -> McpResult<[u8; 32]> {
+        let sealed = STANDARD
+            .decode(wrapped)
+            .map_err(|e| McpError::AuthError(format!("Invalid wrapped tenant key: {}", e)))?;
+        let key = open_with(&self.tenant_key(tenant)?, tenant.as_bytes(), &sealed)?;
+        <[u8; 32]>::try_from(key)
+            .map_err(|_| McpError::AuthError("Invalid wrapped tenant key".to_string()))
+    }
+}
+
+/// Wraps data keys with Vault's transit engine, the tenant as key context
+pub struct VaultTransitKms {
+    client: reqwest::Client,
+    config: VaultTransitConfig,
+}
+
+impl VaultTransitKms {
+    pub fn new(config: VaultTransitConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn call(&self, operation: &str, body: serde_json::Value) -> McpResult<serde_json::Value> {
+        let url = format!(
+            "{}/v1/{}/{}/{}",
+            self.config.address.trim_end_matches('/'),
+            self.config.mount.trim_matches('/'),
+            operation,
+            self.config.key
+        );
+        let mut request = self.client.post(&url).json(&body);
+        if let Some(token) = &self.config.token {
+            request = request.header("X-Vault-Token", token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Vault transit: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(McpError::AuthError(format!(
+                "Vault transit {} failed: {}",
+                operation,
+                response.status()
+            )));
+        }
+        let mut reply: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Vault transit: {}", e)))?;
+        Ok(reply["data"].take())
+    }
+}
+
+#[async_trait]
+impl KeyWrapper for VaultTransitKms {
+    async fn wrap(&self, tenant: &str, key: &[u8; 32]) -> McpResult<String> {
+        let data = self
+            .call(
+                "encrypt",
+                json!({ "plaintext": STANDARD.encode(key), "context": STANDARD.encode(tenant) }),
+            )
+            .await?;
+        data["ciphertext"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| McpError::AuthError("Vault transit returned no ciphertext".to_string()))
+    }
+
+    async fn unwrap(&self, tenant: &str, wrapped: &str) -> McpResult<[u8; 32]> {
+        let data = self
+            .call(
+                "decrypt",
+                json!({ "ciphertext": wrapped, "context": STANDARD.encode(tenant) }),
+            )
+            .await?;
+        let plaintext = data["plaintext"]
+            .as_str()
+            .ok_or_else(|| McpError::AuthError("Vault transit returned no plaintext".to_string()))?;
+        decode_key(plaintext, "Unwrapped tenant key")
+            .map_err(|_| McpError::AuthError("Vault transit returned an invalid key".to_string()))
+    }
+}
+
+/// Data keys of every tenant
+pub struct TenantKeys {
+    kms: Arc<dyn KeyWrapper>,
+    backend: Arc<dyn StateBackend>,
+    keys: DashMap<String, Arc<LessSafeKey>>,
+}
+
+impl TenantKeys {
+    pub fn new(kms: Arc<dyn KeyWrapper>, backend: Arc<dyn StateBackend>) -> Self {
+        Self {
+            kms,
+            backend,
+            keys: DashMap::new(),
+        }
+    }
+
+    /// Keys for `[tenant_keys]`, `None` when it is disabled
+    pub fn from_config(config: &Config) -> McpResult<Option<Self>> {
+        let tenant_keys = &config.tenant_keys;
+        if !tenant_keys.enabled {
+            return Ok(None);
+        }
+        let kms: Arc<dyn KeyWrapper> = match tenant_keys.provider {
+            KmsProvider::Local => {
+                let master_key = tenant_keys.master_key.as_deref().ok_or_else(|| {
+                    McpError::ConfigError("tenant_keys.master_key is required".to_string())
+                })?;
+                Arc::new(LocalKms::new(decode_key(master_key, "tenant_keys.master_key")?))
+            }
+            KmsProvider::VaultTransit => Arc::new(VaultTransitKms::new(tenant_keys.vault.clone())),
+        };
+        Ok(Some(Self::new(kms, create_state_backend(&config.state))))
+    }
+
+    /// The tenant's data key, created on first use
+    async fn data_key(&self, tenant: &str) -> McpResult<Arc<LessSafeKey>> {
+        if let Some(key) = self.keys.get(tenant) {
+            return Ok(key.clone());
+        }
+
+        let id = format!("{}{}", KEY_PREFIX, URL_SAFE_NO_PAD.encode(tenant));
+        let key = match self.backend.get(&id).await? {
+            Some(wrapped) => self.kms.unwrap(tenant, &String::from_utf8_lossy(&wrapped)).await?,
+            None => {
+                let key = random_bytes::<32>()?;
+                let wrapped = self.kms.wrap(tenant, &key).await?;
+                // Another node may have created the key in the meantime
+                if self.backend.cas(&id, None, wrapped.into_bytes()).await? {
+                    info!("Created data key for tenant {}", tenant);
+                    key
+                } else {
+                    let wrapped = self.backend.get(&id).await?.ok_or_else(|| {
+                        McpError::InternalError(format!("Data key of {} vanished", tenant))
+                    })?;
+                    self.kms.unwrap(tenant, &String::from_utf8_lossy(&wrapped)).await?
+                }
+            }
+        };
+        let key = Arc::new(aead_key(&key)?);
+        self.keys.insert(tenant.to_string(), key.clone());
+        Ok(key)
+    }
+
+    /// Seal data of a tenant, bound to `aad`
+    pub async fn seal(&self, tenant: &str, aad: &[u8], plaintext: &[u8]) -> McpResult<Vec<u8>> {
+        let sealed = seal_with(&*self.data_key(tenant).await?, aad, plaintext)?;
+        Ok([MAGIC, &sealed].concat())
+    }
+
+    /// Open data sealed by [`seal`](Self::seal) with the same `aad`
+    pub async fn open(&self, tenant: &str, aad: &[u8], sealed: &[u8]) -> McpResult<Vec<u8>> {
+        let sealed = sealed
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| McpError::AuthError("Data is not sealed with a tenant key".to_string()))?;
+        open_with(&*self.data_key(tenant).await?, aad, sealed)
+    }
+}
+
+static TENANT_KEYS: OnceCell<Arc<TenantKeys>> = OnceCell::new();
+
+/// Install the process-wide tenant keys; false if already installed
+pub fn install(keys: Arc<TenantKeys>) -> bool {
+    TENANT_KEYS.set(keys).is_ok()
+}
+
+/// The installed tenant keys, if any
+pub fn global() -> Option<&'static Arc<TenantKeys>> {
+    TENANT_KEYS.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::InMemoryBackend;
+
+    #[tokio::test]
+    async fn test_tenants_are_separated() {
+        let backend: Arc<dyn StateBackend> = Arc::new(InMemoryBackend::new());
+        let kms = Arc::new(LocalKms::new([7u8; 32]));
+        let keys = TenantKeys::new(kms.clone(), backend.clone());
+
+        let sealed = keys.seal("alice", b"a1", b"secret").await.unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert_eq!(keys.open("alice", b"a1", &sealed).await.unwrap(), b"secret");
+        assert!(keys.open("alice", b"a2", &sealed).await.is_err());
+        assert!(keys.open("bob", b"a1", &sealed).await.is_err());
+
+        // Another node sharing the backend unwraps the same key
+        let other = TenantKeys::new(kms, backend.clone());
+        assert_eq!(other.open("alice", b"a1", &sealed).await.unwrap(), b"secret");
+
+        // A different master key cannot
+        let stranger = TenantKeys::new(Arc::new(LocalKms::new([8u8; 32])), backend);
+        assert!(stranger.open("alice", b"a1", &sealed).await.is_err());
+    }
+}
@@ -13,21 +13,41 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
-use crate::core::ServerManager;
+use crate::config::PresetConfig;
+use crate::core::{ServerManager, ServerStatus};
+use crate::utils::errors::McpError;
+
+/// State behind the 1MCP API
+pub struct OneMcpApiState {
+    server_manager: Arc<ServerManager>,
+    presets: RwLock<Vec<PresetConfig>>,
+}
+
+type ApiState = State<Arc<OneMcpApiState>>;
+type ApiResult<T> = Result<T, (StatusCode, Json<ApiError>)>;
 
 /// 1MCP-compatible API routes
-pub fn one_mcp_routes(server_manager: Arc<ServerManager>) -> Router {
+pub fn one_mcp_routes(server_manager: Arc<ServerManager>, presets: Vec<PresetConfig>) -> Router {
+    let state = Arc::new(OneMcpApiState {
+        server_manager,
+        presets: RwLock::new(presets),
+    });
+
     Router::new()
         .route("/v1/servers", get(list_servers).post(create_server))
-        .route("/v1/servers/:name", get(get_server).put(update_server).delete(delete_server))
-        .route("/v1/servers/:name/start", post(start_server))
-        .route("/v1/servers/:name/stop", post(stop_server))
-        .route("/v1/servers/:name/restart", post(restart_server))
-        .route("/v1/servers/:name/status", get(server_status))
+        .route("/v1/servers/bulk", post(bulk_servers))
+        .route("/v1/servers/{name}", get(get_server).put(update_server).delete(delete_server))
+        .route("/v1/servers/{name}/start", post(start_server))
+        .route("/v1/servers/{name}/stop", post(stop_server))
+        .route("/v1/servers/{name}/restart", post(restart_server))
+        .route("/v1/servers/{name}/status", get(server_status))
+        .route("/v1/presets", get(list_presets).post(create_preset))
+        .route("/v1/presets/{name}", get(get_preset).put(update_preset).delete(delete_preset))
         .route("/v1/health", get(health_check))
         .route("/v1/info", get(system_info))
-        .with_state(server_manager)
+        .with_state(state)
 }
 
 /// Server list response (1MCP format)
@@ -82,26 +102,72 @@ pub struct ApiError {
     pub message: String,
 }
 
-/// List all servers (1MCP compatible)
-async fn list_servers(
-    State(server_manager): State<Arc<ServerManager>>,
-) -> Result<Json<OneMcpServerList>, (StatusCode, Json<ApiError>)> {
-    let server_names = server_manager.list_servers();
-    let mut servers = Vec::new();
-
-    for name in &server_names {
-        // In production, fetch actual server info
-        servers.push(OneMcpServerInfo {
-            name: name.clone(),
-            command: "n/a".to_string(),
-            status: "running".to_string(),
-            enabled: true,
+fn api_error(status: StatusCode, error: &str, message: impl Into<String>) -> (StatusCode, Json<ApiError>) {
+    (
+        status,
+        Json(ApiError {
+            error: error.to_string(),
+            message: message.into(),
+        }),
+    )
+}
+
+fn server_not_found(name: &str) -> (StatusCode, Json<ApiError>) {
+    api_error(
+        StatusCode::NOT_FOUND,
+        "SERVER_NOT_FOUND",
+        format!("Server '{}' not found", name),
+    )
+}
+
+/// Map a manager error, keeping unknown servers as 404s
+fn server_error(error: McpError, code: &str) -> (StatusCode, Json<ApiError>) {
+    match error {
+        McpError::ServerNotFound(name) => server_not_found(&name),
+        e => api_error(StatusCode::INTERNAL_SERVER_ERROR, code, e.to_string()),
+    }
+}
+
+impl From<ServerStatus> for OneMcpServerInfo {
+    fn from(status: ServerStatus) -> Self {
+        let state = if status.degraded {
+            "degraded"
+        } else if status.connected {
+            "running"
+        } else {
+            "stopped"
+        };
+        Self {
+            name: status.name,
+            command: status.command.trim_end().to_string(),
+            status: state.to_string(),
+            enabled: status.connected,
             pid: None,
             uptime_seconds: None,
-            restarts: 0,
+            restarts: status.restarts as u32,
             last_error: None,
-        });
+        }
     }
+}
+
+async fn server_info(server_manager: &ServerManager, name: &str) -> ApiResult<OneMcpServerInfo> {
+    server_manager
+        .get_server_status(name)
+        .await
+        .map(OneMcpServerInfo::from)
+        .map_err(|e| server_error(e, "STATUS_FAILED"))
+}
+
+/// List all servers (1MCP compatible)
+async fn list_servers(State(state): ApiState) -> ApiResult<Json<OneMcpServerList>> {
+    let mut servers: Vec<OneMcpServerInfo> = state
+        .server_manager
+        .get_all_server_status()
+        .await
+        .into_iter()
+        .map(OneMcpServerInfo::from)
+        .collect();
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
 
     Ok(Json(OneMcpServerList {
         total: servers.len(),
@@ -111,38 +177,35 @@ async fn list_servers(
 
 /// Get server details
 async fn get_server(
-    State(server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<Json<OneMcpServerInfo>, (StatusCode, Json<ApiError>)> {
-    if let Some(_server) = server_manager.get_server(&name) {
-        // In production, fetch actual server details
-        Ok(Json(OneMcpServerInfo {
-            name: name.clone(),
-            command: "n/a".to_string(),
-            status: "running".to_string(),
-            enabled: true,
-            pid: None,
-            uptime_seconds: None,
-            restarts: 0,
-            last_error: None,
-        }))
-    } else {
-        Err((
-            StatusCode::NOT_FOUND,
-            Json(ApiError {
-                error: "SERVER_NOT_FOUND".to_string(),
-                message: format!("Server '{}' not found", name),
-            }),
-        ))
-    }
+) -> ApiResult<Json<OneMcpServerInfo>> {
+    server_info(&state.server_manager, &name).await.map(Json)
 }
 
 /// Create new server
 async fn create_server(
-    State(server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Json(req): Json<CreateServerRequest>,
-) -> Result<Json<OneMcpServerInfo>, (StatusCode, Json<ApiError>)> {
+) -> ApiResult<(StatusCode, Json<OneMcpServerInfo>)> {
+    if state.server_manager.get_server(&req.name).is_some() {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "SERVER_EXISTS",
+            format!("Server '{}' already exists", req.name),
+        ));
+    }
+
     // Convert request to Super MCP config
+    let mut sandbox = crate::config::SandboxConfig::default();
+    if let Some(requested) = &req.sandbox {
+        if let Some(enabled) = requested.enabled {
+            sandbox.enabled = enabled;
+        }
+        if let Some(network) = requested.network {
+            sandbox.network = network;
+        }
+    }
     let server_config = crate::config::McpServerConfig {
         name: req.name.clone(),
         command: req.command.clone(),
@@ -150,115 +213,140 @@ async fn create_server(
         env: req.env.unwrap_or_default(),
         tags: req.tags.unwrap_or_default(),
         description: None,
-        sandbox: crate::config::SandboxConfig::default(),
+        sandbox,
+        ..Default::default()
     };
 
     // Add server to manager
-    match server_manager.add_server(server_config).await {
-        Ok(_) => Ok(Json(OneMcpServerInfo {
-            name: req.name,
-            command: req.command,
-            status: "created".to_string(),
-            enabled: true,
-            pid: None,
-            uptime_seconds: None,
-            restarts: 0,
-            last_error: None,
-        })),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiError {
-                error: "CREATE_FAILED".to_string(),
-                message: e.to_string(),
-            }),
-        )),
-    }
+    state
+        .server_manager
+        .add_server(server_config)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "CREATE_FAILED", e.to_string()))?;
+
+    let info = server_info(&state.server_manager, &req.name).await?;
+    Ok((StatusCode::CREATED, Json(info)))
 }
 
 /// Update server
+///
+/// Changing the command or args restarts the server; `enabled` starts or
+/// stops it.
 async fn update_server(
-    State(_server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
     Json(req): Json<UpdateServerRequest>,
-) -> Result<Json<OneMcpServerInfo>, (StatusCode, Json<ApiError>)> {
-    // In production, implement actual update logic
-    Ok(Json(OneMcpServerInfo {
-        name,
-        command: req.command.unwrap_or_else(|| "n/a".to_string()),
-        status: "updated".to_string(),
-        enabled: req.enabled.unwrap_or(true),
-        pid: None,
-        uptime_seconds: None,
-        restarts: 0,
-        last_error: None,
-    }))
+) -> ApiResult<Json<OneMcpServerInfo>> {
+    let mut config = state
+        .server_manager
+        .get_server(&name)
+        .map(|server| server.config.clone())
+        .ok_or_else(|| server_not_found(&name))?;
+
+    let changed = req.command.is_some() || req.args.is_some();
+    if let Some(command) = req.command {
+        config.command = command;
+    }
+    if let Some(args) = req.args {
+        config.args = args;
+    }
+
+    if req.enabled == Some(false) {
+        if changed {
+            // Keep the new config for the next start
+            state
+                .server_manager
+                .update_server(config)
+                .await
+                .map_err(|e| server_error(e, "UPDATE_FAILED"))?;
+        }
+        stop(&state.server_manager, &name).await?;
+    } else if changed || req.enabled == Some(true) {
+        state
+            .server_manager
+            .update_server(config)
+            .await
+            .map_err(|e| server_error(e, "UPDATE_FAILED"))?;
+    }
+
+    server_info(&state.server_manager, &name).await.map(Json)
 }
 
 /// Delete server
 async fn delete_server(
-    State(server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
-    match server_manager.remove_server(&name).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiError {
-                error: "DELETE_FAILED".to_string(),
-                message: e.to_string(),
-            }),
-        )),
+) -> ApiResult<StatusCode> {
+    state
+        .server_manager
+        .remove_server(&name)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(|e| server_error(e, "DELETE_FAILED"))
+}
+
+async fn stop(server_manager: &ServerManager, name: &str) -> ApiResult<()> {
+    let server = server_manager
+        .get_server(name)
+        .map(|server| server.clone())
+        .ok_or_else(|| server_not_found(name))?;
+    server
+        .stop()
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "STOP_FAILED", e.to_string()))
+}
+
+async fn start(server_manager: &ServerManager, name: &str) -> ApiResult<&'static str> {
+    let server = server_manager
+        .get_server(name)
+        .map(|server| server.clone())
+        .ok_or_else(|| server_not_found(name))?;
+    if server.is_connected().await {
+        return Ok("running");
     }
+    server_manager
+        .restart_server(name)
+        .await
+        .map(|_| "started")
+        .map_err(|e| server_error(e, "START_FAILED"))
 }
 
 /// Start server
 async fn start_server(
-    State(_server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+) -> ApiResult<Json<serde_json::Value>> {
+    let status = start(&state.server_manager, &name).await?;
     Ok(Json(json!({
         "name": name,
         "action": "start",
-        "status": "started"
+        "status": status
     })))
 }
 
 /// Stop server
 async fn stop_server(
-    State(server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    if let Some(server) = server_manager.get_server(&name) {
-        match server.stop().await {
-            Ok(_) => Ok(Json(json!({
-                "name": name,
-                "action": "stop",
-                "status": "stopped"
-            }))),
-            Err(e) => Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError {
-                    error: "STOP_FAILED".to_string(),
-                    message: e.to_string(),
-                }),
-            )),
-        }
-    } else {
-        Err((
-            StatusCode::NOT_FOUND,
-            Json(ApiError {
-                error: "SERVER_NOT_FOUND".to_string(),
-                message: format!("Server '{}' not found", name),
-            }),
-        ))
-    }
+) -> ApiResult<Json<serde_json::Value>> {
+    stop(&state.server_manager, &name).await?;
+    Ok(Json(json!({
+        "name": name,
+        "action": "stop",
+        "status": "stopped"
+    })))
 }
 
 /// Restart server
 async fn restart_server(
-    State(_server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+) -> ApiResult<Json<serde_json::Value>> {
+    state
+        .server_manager
+        .restart_server(&name)
+        .await
+        .map_err(|e| server_error(e, "RESTART_FAILED"))?;
     Ok(Json(json!({
         "name": name,
         "action": "restart",
@@ -268,25 +356,273 @@ async fn restart_server(
 
 /// Get server status
 async fn server_status(
-    State(server_manager): State<Arc<ServerManager>>,
+    State(state): ApiState,
     Path(name): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    if let Some(server) = server_manager.get_server(&name) {
-        let connected = server.is_connected().await;
-        Ok(Json(json!({
-            "name": name,
-            "status": if connected { "healthy" } else { "unhealthy" },
-            "connected": connected,
-        })))
+) -> ApiResult<Json<serde_json::Value>> {
+    let status = state
+        .server_manager
+        .get_server_status(&name)
+        .await
+        .map_err(|e| server_error(e, "STATUS_FAILED"))?;
+    Ok(Json(json!({
+        "name": name,
+        "status": if status.connected { "healthy" } else { "unhealthy" },
+        "connected": status.connected,
+        "restarts": status.restarts,
+        "degraded": status.degraded,
+        "disk": status.disk,
+    })))
+}
+
+/// Action applied by `POST /v1/servers/bulk`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkAction {
+    Start,
+    Stop,
+    Restart,
+    Delete,
+}
+
+/// Bulk operation request
+///
+/// Targets the named servers, else servers with any of the tags, else all.
+#[derive(Debug, Deserialize)]
+pub struct BulkServerRequest {
+    pub action: BulkAction,
+    #[serde(default)]
+    pub servers: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Outcome for one server of a bulk operation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkResult {
+    pub name: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Bulk operation response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkResponse {
+    pub action: BulkAction,
+    pub results: Vec<BulkResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Start, stop, restart or delete several servers
+///
+/// Each server is handled independently; failures are reported per
+/// server rather than failing the request.
+async fn bulk_servers(
+    State(state): ApiState,
+    Json(req): Json<BulkServerRequest>,
+) -> Json<BulkResponse> {
+    let manager = &state.server_manager;
+    let mut targets = if !req.servers.is_empty() {
+        req.servers
+    } else if !req.tags.is_empty() {
+        manager.get_servers_by_tags(&req.tags).await
     } else {
-        Err((
-            StatusCode::NOT_FOUND,
-            Json(ApiError {
-                error: "SERVER_NOT_FOUND".to_string(),
-                message: format!("Server '{}' not found", name),
-            }),
-        ))
+        manager.list_servers()
+    };
+    targets.sort();
+    targets.dedup();
+
+    let mut results = Vec::with_capacity(targets.len());
+    for name in targets {
+        let outcome = match req.action {
+            BulkAction::Start => start(manager, &name).await.map(|_| ()),
+            BulkAction::Stop => stop(manager, &name).await,
+            BulkAction::Restart => manager
+                .restart_server(&name)
+                .await
+                .map_err(|e| server_error(e, "RESTART_FAILED")),
+            BulkAction::Delete => manager
+                .remove_server(&name)
+                .await
+                .map_err(|e| server_error(e, "DELETE_FAILED")),
+        };
+        let success = outcome.is_ok();
+        results.push(BulkResult {
+            name,
+            success,
+            error: outcome.err().map(|(_, Json(error))| error.message),
+        });
+    }
+
+    let succeeded = results.iter().filter(|r| r.success).count();
+    Json(BulkResponse {
+        action: req.action,
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+    })
+}
+
+/// Preset (1MCP format), with the servers its tags currently select
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OneMcpPreset {
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub servers: Vec<String>,
+}
+
+/// Preset list response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OneMcpPresetList {
+    pub presets: Vec<OneMcpPreset>,
+    pub total: usize,
+}
+
+/// Create preset request
+#[derive(Debug, Deserialize)]
+pub struct CreatePresetRequest {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+}
+
+/// Update preset request
+#[derive(Debug, Deserialize)]
+pub struct UpdatePresetRequest {
+    pub tags: Option<Vec<String>>,
+    pub description: Option<String>,
+}
+
+fn preset_not_found(name: &str) -> (StatusCode, Json<ApiError>) {
+    api_error(
+        StatusCode::NOT_FOUND,
+        "PRESET_NOT_FOUND",
+        format!("Preset '{}' not found", name),
+    )
+}
+
+fn check_preset_tags(tags: &[String]) -> ApiResult<()> {
+    if tags.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_PRESET",
+            "Preset must have at least one tag",
+        ));
+    }
+    Ok(())
+}
+
+async fn preset_info(server_manager: &ServerManager, preset: &PresetConfig) -> OneMcpPreset {
+    let mut servers = server_manager.get_servers_by_tags(&preset.tags).await;
+    servers.sort();
+    OneMcpPreset {
+        name: preset.name.clone(),
+        description: preset.description.clone(),
+        tags: preset.tags.clone(),
+        servers,
+    }
+}
+
+/// List presets
+async fn list_presets(State(state): ApiState) -> Json<OneMcpPresetList> {
+    let presets = state.presets.read().await.clone();
+    let mut infos = Vec::with_capacity(presets.len());
+    for preset in &presets {
+        infos.push(preset_info(&state.server_manager, preset).await);
+    }
+    Json(OneMcpPresetList {
+        total: infos.len(),
+        presets: infos,
+    })
+}
+
+/// Get preset details
+async fn get_preset(
+    State(state): ApiState,
+    Path(name): Path<String>,
+) -> ApiResult<Json<OneMcpPreset>> {
+    let preset = state
+        .presets
+        .read()
+        .await
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| preset_not_found(&name))?;
+    Ok(Json(preset_info(&state.server_manager, &preset).await))
+}
+
+/// Create preset
+async fn create_preset(
+    State(state): ApiState,
+    Json(req): Json<CreatePresetRequest>,
+) -> ApiResult<(StatusCode, Json<OneMcpPreset>)> {
+    check_preset_tags(&req.tags)?;
+    let preset = PresetConfig {
+        name: req.name,
+        tags: req.tags,
+        description: req.description,
+    };
+
+    {
+        let mut presets = state.presets.write().await;
+        if presets.iter().any(|p| p.name == preset.name) {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                "PRESET_EXISTS",
+                format!("Preset '{}' already exists", preset.name),
+            ));
+        }
+        presets.push(preset.clone());
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(preset_info(&state.server_manager, &preset).await),
+    ))
+}
+
+/// Update preset
+async fn update_preset(
+    State(state): ApiState,
+    Path(name): Path<String>,
+    Json(req): Json<UpdatePresetRequest>,
+) -> ApiResult<Json<OneMcpPreset>> {
+    if let Some(tags) = &req.tags {
+        check_preset_tags(tags)?;
+    }
+
+    let preset = {
+        let mut presets = state.presets.write().await;
+        let preset = presets
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| preset_not_found(&name))?;
+        if let Some(tags) = req.tags {
+            preset.tags = tags;
+        }
+        if let Some(description) = req.description {
+            preset.description = Some(description);
+        }
+        preset.clone()
+    };
+
+    Ok(Json(preset_info(&state.server_manager, &preset).await))
+}
+
+/// Delete preset
+async fn delete_preset(
+    State(state): ApiState,
+    Path(name): Path<String>,
+) -> ApiResult<StatusCode> {
+    let mut presets = state.presets.write().await;
+    let before = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == before {
+        return Err(preset_not_found(&name));
     }
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// Health check endpoint
@@ -299,10 +635,10 @@ async fn health_check() -> Json<serde_json::Value> {
 }
 
 /// System info endpoint
-async fn system_info(
-    State(server_manager): State<Arc<ServerManager>>,
-) -> Json<serde_json::Value> {
-    let server_count = server_manager.list_servers().len();
+async fn system_info(State(state): ApiState) -> Json<serde_json::Value> {
+    let statuses = state.server_manager.get_all_server_status().await;
+    let server_count = statuses.len();
+    let healthy = statuses.iter().filter(|s| s.connected).count();
 
     Json(json!({
         "name": "super-mcp",
@@ -311,7 +647,7 @@ async fn system_info(
         "compatible_with": "1mcp",
         "servers": {
             "total": server_count,
-            "healthy": server_count, // Simplified
+            "healthy": healthy,
         },
         "features": {
             "sandboxing": true,
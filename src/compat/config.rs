@@ -72,6 +72,7 @@ pub struct OneMcpAuth {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OneMcpSandboxing {
     pub enabled: bool,
+    #[serde(default)]
     pub default_profile: String,
 }
 
@@ -174,6 +175,7 @@ impl OneMcpConfigAdapter {
             tags: server.tags.clone().unwrap_or_default(),
             description: server.description.clone(),
             sandbox,
+            ..Default::default()
         }
     }
 
@@ -195,6 +197,9 @@ impl OneMcpConfigAdapter {
             client_id: auth.oauth_client_id.clone(),
             client_secret: None,
             jwt_secret: auth.jwt_secret.clone(),
+            tokens: Vec::new(),
+            jwt_keys: Vec::new(),
+            publish_jwks: false,
             auth_url: None,
             token_url: None,
             introspection_url: None,
@@ -205,6 +210,8 @@ impl OneMcpConfigAdapter {
             jwks_cache_ttl_seconds: 300,
             allow_unverified_jwt: false,
             required_scopes: Vec::new(),
+            delegation: Default::default(),
+            cache: Default::default(),
         }
     }
 
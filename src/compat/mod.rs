@@ -66,6 +66,12 @@ impl MigrationHelper {
 ║  • POST /v1/servers/:name/stop   Stop server                                  ║
 ║  • POST /v1/servers/:name/restart Restart server                              ║
 ║  • GET  /v1/servers/:name/status Health status                                ║
+║  • POST /v1/servers/bulk         Start/stop/restart/delete many servers       ║
+║  • GET  /v1/presets              List presets                                 ║
+║  • POST /v1/presets              Create preset                                ║
+║  • GET  /v1/presets/:name        Get preset (with matching servers)           ║
+║  • PUT  /v1/presets/:name        Update preset                                ║
+║  • DEL  /v1/presets/:name        Delete preset                                ║
 ║  • GET  /v1/health               System health                                ║
 ║  • GET  /v1/info                 System info                                  ║
 ║                                                                               ║
@@ -225,7 +225,11 @@ impl StandardMcpConfigAdapter {
 
         let mut super_mcp = SuperMcpConfig::default();
 
-        for (name, server_config) in &config.mcp_servers {
+        let mut names: Vec<&String> = config.mcp_servers.keys().collect();
+        names.sort();
+
+        for name in names {
+            let server_config = &config.mcp_servers[name];
             if server_config.disabled {
                 debug!("Skipping disabled server: {}", name);
                 continue;
@@ -239,6 +243,7 @@ impl StandardMcpConfigAdapter {
                 tags: vec![name.clone()],
                 description: Some("MCP server from mcp.json".to_string()),
                 sandbox: SandboxConfig::default(),
+                ..Default::default()
             };
 
             super_mcp.servers.push(server);
@@ -275,6 +280,7 @@ impl StandardMcpConfigAdapter {
                 tags: server.tags.clone(),
                 description: server.description.clone(),
                 sandbox: SandboxConfig::default(),
+                ..Default::default()
             };
 
             super_mcp.servers.push(server_config);
@@ -313,6 +319,7 @@ impl StandardMcpConfigAdapter {
                     tags: mcp_server.scope.clone().map(|s| vec![s]).unwrap_or_default(),
                     description: Some("MCP server from Smithery config".to_string()),
                     sandbox: SandboxConfig::default(),
+                    ..Default::default()
                 };
 
                 super_mcp.servers.push(server);
@@ -349,6 +356,7 @@ impl StandardMcpConfigAdapter {
                     max_memory_mb: s.max_memory_mb.unwrap_or(512),
                     max_cpu_percent: s.max_cpu_percent.unwrap_or(50),
                     env_inherit: true,
+                    ..Default::default()
                 },
                 None => SandboxConfig::default(),
             };
@@ -361,6 +369,7 @@ impl StandardMcpConfigAdapter {
                 tags: server.tags.clone(),
                 description: server.description.clone(),
                 sandbox,
+                ..Default::default()
             };
 
             super_mcp.servers.push(server_config);
@@ -390,6 +399,9 @@ impl StandardMcpConfigAdapter {
             client_id: None,
             client_secret: None,
             jwt_secret: auth.jwt_secret.clone(),
+            tokens: Vec::new(),
+            jwt_keys: Vec::new(),
+            publish_jwks: false,
             auth_url: None,
             token_url: None,
             introspection_url: None,
@@ -400,6 +412,8 @@ impl StandardMcpConfigAdapter {
             jwks_cache_ttl_seconds: 300,
             allow_unverified_jwt: false,
             required_scopes: Vec::new(),
+            delegation: Default::default(),
+            cache: Default::default(),
         }
     }
 
@@ -460,6 +474,7 @@ impl StandardMcpConfigAdapter {
                             tags: vec![],
                             description: Some("MCP server".to_string()),
                             sandbox: SandboxConfig::default(),
+                            ..Default::default()
                         })
                         .collect()
                 } else {
@@ -478,6 +493,7 @@ impl StandardMcpConfigAdapter {
                                 tags: s.tags,
                                 description: s.description,
                                 sandbox: SandboxConfig::default(),
+                                ..Default::default()
                             })
                             .collect()
                     } else {
@@ -493,6 +509,7 @@ impl StandardMcpConfigAdapter {
                             tags: s.tags,
                             description: s.description,
                             sandbox: SandboxConfig::default(),
+                            ..Default::default()
                         })
                         .collect()
                 } else {
@@ -511,6 +528,7 @@ impl StandardMcpConfigAdapter {
                             tags: vec![],
                             description: Some("MCP server".to_string()),
                             sandbox: SandboxConfig::default(),
+                            ..Default::default()
                         })
                         .collect()
                 } else {
@@ -539,9 +557,11 @@ impl StandardMcpConfigAdapter {
                                     max_memory_mb: sb.max_memory_mb.unwrap_or(512),
                                     max_cpu_percent: sb.max_cpu_percent.unwrap_or(50),
                                     env_inherit: true,
+                                    ..Default::default()
                                 },
                                 None => SandboxConfig::default(),
                             },
+                            ..Default::default()
                         })
                         .collect()
                 } else {
@@ -794,6 +814,7 @@ servers:
             tags: vec![],
             description: None,
             sandbox: SandboxConfig::default(),
+            ..Default::default()
         });
 
         let output = StandardMcpConfigWriter::to_mcp_json(&super_mcp);
@@ -814,6 +835,7 @@ servers:
             tags: vec!["test".to_string()],
             description: None,
             sandbox: SandboxConfig::default(),
+            ..Default::default()
         });
         super_mcp.presets.push(PresetConfig {
             name: "development".to_string(),
@@ -0,0 +1,168 @@
+//! Structured differences between two configs
+//!
+//! Configs are compared as JSON. Lists of named entries, such as `servers`
+//! and `presets`, are matched by name rather than position, so reordering
+//! them is not a change and paths read `servers[github].command`.
+
+use crate::config::Config;
+use crate::utils::errors::McpResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What happened to a setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One setting that differs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+impl ConfigChange {
+    /// Top-level section the change is in, such as `servers`
+    pub fn section(&self) -> &str {
+        self.path
+            .split(['.', '['])
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+/// Changes that turn `before` into `after`
+pub fn diff_configs(before: &Config, after: &Config) -> McpResult<Vec<ConfigChange>> {
+    Ok(diff(&serde_json::to_value(before)?, &serde_json::to_value(after)?))
+}
+
+/// Changes that turn one JSON value into another
+pub fn diff(before: &Value, after: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_at("", before, after, &mut changes);
+    changes
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Entries of a list keyed by their `name`, if every entry has one
+fn by_name(items: &[Value]) -> Option<Vec<(&str, &Value)>> {
+    items
+        .iter()
+        .map(|item| Some((item.get("name")?.as_str()?, item)))
+        .collect()
+}
+
+fn diff_at(path: &str, before: &Value, after: &Value, changes: &mut Vec<ConfigChange>) {
+    if before == after {
+        return;
+    }
+    let change = |kind, before: Option<&Value>, after: Option<&Value>| ConfigChange {
+        path: path.to_string(),
+        kind,
+        before: before.cloned(),
+        after: after.cloned(),
+    };
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                match new.get(key) {
+                    Some(new_value) => diff_at(&join(path, key), old_value, new_value, changes),
+                    None => changes.push(ConfigChange {
+                        path: join(path, key),
+                        kind: ChangeKind::Removed,
+                        before: Some(old_value.clone()),
+                        after: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                changes.push(ConfigChange {
+                    path: join(path, key),
+                    kind: ChangeKind::Added,
+                    before: None,
+                    after: Some(new_value.clone()),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => match (by_name(old), by_name(new)) {
+            (Some(old), Some(new)) if !old.is_empty() || !new.is_empty() => {
+                for (name, old_value) in &old {
+                    let entry = format!("{}[{}]", path, name);
+                    match new.iter().find(|(n, _)| n == name) {
+                        Some((_, new_value)) => diff_at(&entry, old_value, new_value, changes),
+                        None => changes.push(ConfigChange {
+                            path: entry,
+                            kind: ChangeKind::Removed,
+                            before: Some((*old_value).clone()),
+                            after: None,
+                        }),
+                    }
+                }
+                for (name, new_value) in new.iter().filter(|(n, _)| !old.iter().any(|(o, _)| o == n)) {
+                    changes.push(ConfigChange {
+                        path: format!("{}[{}]", path, name),
+                        kind: ChangeKind::Added,
+                        before: None,
+                        after: Some((*new_value).clone()),
+                    });
+                }
+            }
+            _ => changes.push(change(ChangeKind::Changed, Some(before), Some(after))),
+        },
+        _ => changes.push(change(ChangeKind::Changed, Some(before), Some(after))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_matches_named_entries() {
+        let before = json!({
+            "server": { "port": 3000 },
+            "servers": [
+                { "name": "fs", "command": "npx", "tags": ["local"] },
+                { "name": "web", "command": "fetch" },
+            ],
+        });
+        let after = json!({
+            "server": { "port": 3001, "host": "0.0.0.0" },
+            "servers": [
+                { "name": "git", "command": "uvx" },
+                { "name": "fs", "command": "npx", "tags": ["local", "files"] },
+            ],
+        });
+        let changes = diff(&before, &after);
+        let summary: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("server.port", ChangeKind::Changed),
+                ("server.host", ChangeKind::Added),
+                ("servers[fs].tags", ChangeKind::Changed),
+                ("servers[web]", ChangeKind::Removed),
+                ("servers[git]", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(changes[0].after, Some(json!(3001)));
+        assert_eq!(changes[3].section(), "servers");
+        assert!(diff(&before, &before).is_empty());
+    }
+}
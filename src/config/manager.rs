@@ -1,5 +1,6 @@
+use crate::audit::{self, AuditEvent, AuditEventType};
 use crate::compat::{OneMcpConfigAdapter, StandardMcpConfigAdapter};
-use crate::config::Config;
+use crate::config::{migration, redact, Config, ConfigValidator};
 use crate::utils::errors::{McpError, McpResult};
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::RwLock;
@@ -11,6 +12,8 @@ use tracing::{debug, error, info};
 /// Supported config file formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
+    /// TOML format (Super MCP's own)
+    Toml,
     /// JSON format
     Json,
     /// YAML format
@@ -23,11 +26,14 @@ impl ConfigFormat {
         let ext = path.extension().and_then(|ext| ext.to_str());
 
         match ext {
+            Some("toml") => ConfigFormat::Toml,
             Some("json") => ConfigFormat::Json,
             Some("yml") | Some("yaml") => ConfigFormat::Yaml,
             _ => {
                 if content.trim_start().starts_with('{') {
                     ConfigFormat::Json
+                } else if Self::looks_like_toml(content) {
+                    ConfigFormat::Toml
                 } else {
                     ConfigFormat::Yaml
                 }
@@ -38,11 +44,100 @@ impl ConfigFormat {
     /// Detect format from file extension only
     pub fn from_path(path: &std::path::Path) -> Self {
         match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
             Some("json") => ConfigFormat::Json,
             Some("yml") | Some("yaml") => ConfigFormat::Yaml,
             _ => ConfigFormat::Yaml,
         }
     }
+
+    /// Parse config content in this format
+    pub fn parse_value(self, content: &str) -> McpResult<serde_json::Value> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content)
+                .map_err(|e| McpError::ConfigError(format!("Failed to parse TOML config: {}", e))),
+            ConfigFormat::Json => serde_json::from_str(content)
+                .map_err(|e| McpError::ConfigError(format!("Failed to parse JSON config: {}", e))),
+            ConfigFormat::Yaml => serde_yaml::from_str(content)
+                .map_err(|e| McpError::ConfigError(format!("Failed to parse YAML config: {}", e))),
+        }
+    }
+
+    /// Serialize a config in this format
+    pub fn to_string<T: serde::Serialize>(self, config: &T) -> McpResult<String> {
+        match self {
+            ConfigFormat::Toml => toml::to_string_pretty(config)
+                .map_err(|e| McpError::ConfigError(format!("Failed to serialize TOML: {}", e))),
+            ConfigFormat::Json => serde_json::to_string_pretty(config)
+                .map_err(|e| McpError::ConfigError(format!("Failed to serialize JSON: {}", e))),
+            ConfigFormat::Yaml => serde_yaml::to_string(config)
+                .map_err(|e| McpError::ConfigError(format!("Failed to serialize YAML: {}", e))),
+        }
+    }
+
+    /// First meaningful line is a `[table]` header or a `key = value` pair
+    fn looks_like_toml(content: &str) -> bool {
+        content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .is_some_and(|line| {
+                line.starts_with('[')
+                    || line
+                        .split_once('=')
+                        .is_some_and(|(key, _)| !key.trim().is_empty() && !key.contains(':'))
+            })
+    }
+}
+
+/// Schema of a JSON or YAML config, told apart by its keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigDialect {
+    /// Super MCP's own schema
+    Native,
+    /// 1MCP server config
+    OneMcp,
+    /// Claude/Cursor style `mcpServers` map
+    McpJson,
+    /// Smithery config
+    Smithery,
+    /// Presets file with only `presets` and `servers`
+    Presets,
+}
+
+impl ConfigDialect {
+    /// Keys only found in 1MCP configs
+    const ONE_MCP_TOP_LEVEL: &'static [&'static str] = &["sandboxing", "rate_limiting", "logging"];
+    const ONE_MCP_SERVER: &'static [&'static str] = &["tls_enabled", "tls_cert", "tls_key"];
+    const ONE_MCP_AUTH: &'static [&'static str] = &["oauth_issuer", "oauth_client_id", "static_token"];
+
+    pub fn detect(value: &serde_json::Value) -> Self {
+        let Some(root) = value.as_object() else {
+            return ConfigDialect::Native;
+        };
+        let has_any = |section: Option<&serde_json::Value>, keys: &[&str]| {
+            section
+                .and_then(|v| v.as_object())
+                .is_some_and(|obj| keys.iter().any(|key| obj.contains_key(*key)))
+        };
+
+        if root.contains_key("mcpServers") {
+            ConfigDialect::McpJson
+        } else if root.get("mcp").is_some_and(|v| v.is_object()) {
+            ConfigDialect::Smithery
+        } else if root.contains_key("presets")
+            && root.keys().all(|key| key == "presets" || key == "servers")
+        {
+            ConfigDialect::Presets
+        } else if has_any(Some(value), Self::ONE_MCP_TOP_LEVEL)
+            || has_any(root.get("server"), Self::ONE_MCP_SERVER)
+            || has_any(root.get("auth"), Self::ONE_MCP_AUTH)
+        {
+            ConfigDialect::OneMcp
+        } else {
+            ConfigDialect::Native
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +149,7 @@ pub enum ConfigEvent {
 pub struct ConfigManager {
     path: PathBuf,
     format: ConfigFormat,
+    dialect: ConfigDialect,
     config: Arc<RwLock<Config>>,
     event_tx: broadcast::Sender<ConfigEvent>,
     _watcher: RecommendedWatcher,
@@ -69,7 +165,14 @@ impl ConfigManager {
         let format = ConfigFormat::detect(&path, &content);
         debug!("Detected config format: {:?}", format);
 
-        let config = Self::parse_content(&path, &content, format).await?;
+        let (config, dialect) = Self::parse_content(&path, &content, format).await?;
+        if dialect != ConfigDialect::Native {
+            info!(
+                "Converted {:?} config {} to Super MCP format; `supermcp migrate` writes it out as TOML",
+                dialect,
+                path.display()
+            );
+        }
         let config = Arc::new(RwLock::new(config));
 
         let (event_tx, _) = broadcast::channel(16);
@@ -98,16 +201,15 @@ impl ConfigManager {
                                 }
                             };
                             let format = ConfigFormat::detect(&path_clone, &content);
-                            match Self::parse_content(&path_clone, &content, format).await {
-                                Ok(new_config) => {
-                                    *config_clone.write() = new_config;
-                                    let _ = event_tx_clone.send(ConfigEvent::Reloaded);
-                                }
-                                Err(e) => {
-                                    error!("Failed to reload config: {}", e);
-                                    let _ = event_tx_clone.send(ConfigEvent::Error(e.to_string()));
-                                }
-                            }
+                            let _ = Self::apply_content(
+                                &config_clone,
+                                &event_tx_clone,
+                                &path_clone,
+                                &content,
+                                format,
+                                None,
+                            )
+                            .await;
                         });
                     }
                 }
@@ -121,6 +223,7 @@ impl ConfigManager {
         let mut manager = Self {
             path,
             format,
+            dialect,
             config,
             event_tx,
             _watcher: watcher,
@@ -130,39 +233,91 @@ impl ConfigManager {
         Ok(manager)
     }
 
-    async fn parse_content(_path: &std::path::Path, content: &str, format: ConfigFormat) -> McpResult<Config> {
-        match format {
-            ConfigFormat::Json => {
-                if content.contains("\"mcpServers\"") {
-                    let mcp_config = serde_json::from_str::<crate::compat::McpJsonConfig>(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse mcp.json: {}", e)))?;
-                    Ok(StandardMcpConfigAdapter::convert_mcp_json(&mcp_config))
-                } else if content.contains("\"mcp\"") && content.contains("\"server\"") {
-                    let smithery_config: crate::compat::SmitheryConfig = serde_json::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse Smithery config: {}", e)))?;
-                    Ok(StandardMcpConfigAdapter::convert_smithery(&smithery_config))
-                } else if content.contains("\"presets\"") && content.contains("\"servers\"") {
-                    let presets_config: crate::compat::PresetsConfig = serde_json::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse presets.json: {}", e)))?;
-                    Ok(StandardMcpConfigAdapter::convert_presets_json(&presets_config))
-                } else {
-                    serde_json::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse JSON config: {}", e)))
-                }
+    async fn parse_content(
+        path: &std::path::Path,
+        content: &str,
+        format: ConfigFormat,
+    ) -> McpResult<(Config, ConfigDialect)> {
+        let mut value = format.parse_value(content)?;
+
+        // Only JSON and YAML are shared with other tools
+        let dialect = match format {
+            ConfigFormat::Toml => ConfigDialect::Native,
+            _ => ConfigDialect::detect(&value),
+        };
+        debug!("Detected config dialect: {:?}", dialect);
+
+        if dialect == ConfigDialect::Native {
+            let version = migration::version_of(&value)?;
+            let applied = migration::migrate(&mut value, migration::CURRENT_CONFIG_VERSION)?;
+            if !applied.is_empty() {
+                info!(
+                    "{} uses config version {} ({}); `supermcp migrate-config --to-latest` upgrades it",
+                    path.display(),
+                    version,
+                    applied.join("; ")
+                );
             }
-            ConfigFormat::Yaml => {
-                if content.contains("presets:") && content.contains("servers:") {
-                    let presets_config: crate::compat::PresetsConfig = serde_yaml::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse presets.yaml: {}", e)))?;
-                    Ok(StandardMcpConfigAdapter::convert_presets_json(&presets_config))
-                } else if content.contains("sandboxing:") || content.contains("rate_limiting:") {
-                    let one_mcp_config: crate::compat::config::OneMcpConfig = serde_yaml::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse 1MCP config: {}", e)))?;
-                    Ok(OneMcpConfigAdapter::convert(&one_mcp_config))
-                } else {
-                    serde_yaml::from_str(content)
-                        .map_err(|e| McpError::ConfigError(format!("Failed to parse YAML config: {}", e)))
-                }
+        }
+
+        fn from_value<T: serde::de::DeserializeOwned>(value: serde_json::Value, what: &str) -> McpResult<T> {
+            serde_json::from_value(value)
+                .map_err(|e| McpError::ConfigError(format!("Failed to parse {}: {}", what, e)))
+        }
+
+        let config = match dialect {
+            ConfigDialect::Native => from_value(value, "config")?,
+            ConfigDialect::OneMcp => {
+                let one_mcp_config: crate::compat::config::OneMcpConfig = from_value(value, "1MCP config")?;
+                OneMcpConfigAdapter::convert(&one_mcp_config)
+            }
+            ConfigDialect::McpJson => {
+                let mcp_config: crate::compat::McpJsonConfig = from_value(value, "mcp.json")?;
+                StandardMcpConfigAdapter::convert_mcp_json(&mcp_config)
+            }
+            ConfigDialect::Smithery => {
+                let smithery_config: crate::compat::SmitheryConfig = from_value(value, "Smithery config")?;
+                StandardMcpConfigAdapter::convert_smithery(&smithery_config)
+            }
+            ConfigDialect::Presets => {
+                let presets_config: crate::compat::PresetsConfig = from_value(value, "presets config")?;
+                StandardMcpConfigAdapter::convert_presets_json(&presets_config)
+            }
+        };
+        Ok((config, dialect))
+    }
+
+    /// Parse new config content and swap it in, notifying subscribers
+    ///
+    /// Shared by file changes and external sources; every reload is
+    /// audited, with the source and revision for external sources.
+    async fn apply_content(
+        config: &RwLock<Config>,
+        event_tx: &broadcast::Sender<ConfigEvent>,
+        path: &std::path::Path,
+        content: &str,
+        format: ConfigFormat,
+        source: Option<(&str, &str)>,
+    ) -> McpResult<()> {
+        let mut details = serde_json::json!({ "path": path.display().to_string() });
+        if let Some((source, revision)) = source {
+            details["source"] = source.into();
+            details["revision"] = revision.into();
+        }
+        let event = AuditEvent::new(AuditEventType::ConfigReload).with_details(details);
+
+        match Self::parse_content(path, content, format).await {
+            Ok((new_config, _)) => {
+                *config.write() = new_config;
+                audit::record(event);
+                let _ = event_tx.send(ConfigEvent::Reloaded);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to reload config: {}", e);
+                audit::record(event.with_error(e.to_string()));
+                let _ = event_tx.send(ConfigEvent::Error(e.to_string()));
+                Err(e)
             }
         }
     }
@@ -184,19 +339,87 @@ impl ConfigManager {
     pub async fn reload(&self) -> McpResult<()> {
         let content = tokio::fs::read_to_string(&self.path).await
             .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
-        let new_config = Self::parse_content(&self.path, &content, self.format).await?;
-        *self.config.write() = new_config;
-        let _ = self.event_tx.send(ConfigEvent::Reloaded);
-        Ok(())
+        Self::apply_content(&self.config, &self.event_tx, &self.path, &content, self.format, None).await
+    }
+
+    /// Apply a revision of the config received from an external source
+    /// such as etcd or Consul
+    pub async fn apply_revision(&self, source: &str, content: &str, revision: &str) -> McpResult<()> {
+        Self::apply_content(
+            &self.config,
+            &self.event_tx,
+            &self.path,
+            content,
+            self.format,
+            Some((source, revision)),
+        )
+        .await
+    }
+
+    /// Parse and validate candidate content for the config file without
+    /// applying it
+    pub async fn check_candidate(&self, content: &str) -> McpResult<Config> {
+        let (config, _) = Self::parse_content(&self.path, content, self.format)
+            .await
+            .map_err(|e| McpError::InvalidRequest(e.to_string()))?;
+        ConfigValidator::new().validate_config(&config).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            McpError::InvalidRequest(format!("Invalid config: {}", errors.join("; ")))
+        })?;
+        Ok(config)
+    }
+
+    /// Candidate `content` with [`redact::REDACTED`] placeholders filled in
+    /// from the current config file
+    pub async fn fill_redacted(&self, content: &str) -> McpResult<String> {
+        if !content.contains(redact::REDACTED) {
+            return Ok(content.to_string());
+        }
+        let current = tokio::fs::read_to_string(&self.path).await
+            .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+        let mut candidate = self.format.parse_value(content)?;
+        redact::restore(&mut candidate, &self.format.parse_value(&current)?)?;
+        self.format.to_string(&candidate)
+    }
+
+    /// Write `content` over the config file and reload it
+    ///
+    /// Returns the previous content, for [`restore`](Self::restore). If the
+    /// new content does not load, the previous content is put back.
+    pub async fn replace(&self, content: &str) -> McpResult<String> {
+        let previous = tokio::fs::read_to_string(&self.path).await
+            .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+        tokio::fs::write(&self.path, content).await
+            .map_err(|e| McpError::ConfigError(format!("Failed to write config: {}", e)))?;
+        if let Err(e) = Self::apply_content(&self.config, &self.event_tx, &self.path, content, self.format, None).await {
+            self.restore(&previous).await?;
+            return Err(e);
+        }
+        Ok(previous)
+    }
+
+    /// Put back content returned by [`replace`](Self::replace)
+    pub async fn restore(&self, previous: &str) -> McpResult<()> {
+        tokio::fs::write(&self.path, previous).await
+            .map_err(|e| McpError::ConfigError(format!("Failed to restore config: {}", e)))?;
+        Self::apply_content(&self.config, &self.event_tx, &self.path, previous, self.format, None).await
+    }
+
+    /// Schema the config file was written in
+    pub fn dialect(&self) -> ConfigDialect {
+        self.dialect
     }
 
     pub async fn save(&self, config: &Config) -> McpResult<()> {
-        let content = match self.format {
-            ConfigFormat::Json => serde_json::to_string_pretty(config)
-                .map_err(|e| McpError::ConfigError(format!("Failed to serialize JSON: {}", e)))?,
-            ConfigFormat::Yaml => serde_yaml::to_string(config)
-                .map_err(|e| McpError::ConfigError(format!("Failed to serialize YAML: {}", e)))?,
-        };
+        // Writing our schema over a foreign config would break its other users
+        if self.dialect != ConfigDialect::Native {
+            return Err(McpError::ConfigError(format!(
+                "{} is a {:?} config; run `supermcp migrate` before saving changes",
+                self.path.display(),
+                self.dialect
+            )));
+        }
+        let content = self.format.to_string(config)?;
         tokio::fs::write(&self.path, content).await
             .map_err(|e| McpError::ConfigError(format!("Failed to write config: {}", e)))?;
         *self.config.write() = config.clone();
@@ -244,9 +467,67 @@ mod tests {
         assert_eq!(config.servers[0].name, "filesystem");
     }
 
+    #[tokio::test]
+    async fn test_load_toml_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let content = "[server]\nhost = \"127.0.0.1\"\nport = 3000\n\n[[servers]]\nname = \"test\"\ncommand = \"echo\"\n";
+        fs::write(&config_path, content).await.unwrap();
+        let manager = ConfigManager::new(&config_path).await.unwrap();
+        assert_eq!(manager.dialect(), ConfigDialect::Native);
+        let config = manager.get_config();
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.servers[0].name, "test");
+    }
+
+    #[tokio::test]
+    async fn test_load_one_mcp_yaml_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("1mcp.yaml");
+        let content = r#"
+server:
+  host: 0.0.0.0
+  port: 3050
+servers:
+  - name: filesystem
+    command: npx
+    args: ["-y", "@modelcontextprotocol/server-filesystem"]
+    tags: [fs]
+sandboxing:
+  enabled: true
+"#;
+        fs::write(&config_path, content).await.unwrap();
+        let manager = ConfigManager::new(&config_path).await.unwrap();
+        assert_eq!(manager.dialect(), ConfigDialect::OneMcp);
+        let config = manager.get_config();
+        assert_eq!(config.server.port, 3050);
+        assert_eq!(config.servers[0].name, "filesystem");
+        assert!(manager.save(&config).await.is_err());
+    }
+
+    #[test]
+    fn test_config_dialect_detection() {
+        let native = serde_json::json!({
+            "server": { "host": "127.0.0.1", "port": 3000 },
+            "presets": [{ "name": "dev", "tags": ["dev"] }],
+            "servers": [{ "name": "a", "command": "echo" }],
+        });
+        assert_eq!(ConfigDialect::detect(&native), ConfigDialect::Native);
+
+        let presets = serde_json::json!({ "presets": [], "servers": [] });
+        assert_eq!(ConfigDialect::detect(&presets), ConfigDialect::Presets);
+
+        let one_mcp = serde_json::json!({ "server": { "host": "h", "port": 1, "tls_enabled": false }, "servers": [] });
+        assert_eq!(ConfigDialect::detect(&one_mcp), ConfigDialect::OneMcp);
+
+        let smithery = serde_json::json!({ "mcp": {} });
+        assert_eq!(ConfigDialect::detect(&smithery), ConfigDialect::Smithery);
+    }
+
     #[test]
     fn test_config_format_detection() {
         let cases = vec![
+            ("config.toml", ConfigFormat::Toml),
             ("config.json", ConfigFormat::Json),
             ("mcp.json", ConfigFormat::Json),
             ("config.yaml", ConfigFormat::Yaml),
@@ -0,0 +1,238 @@
+//! Versioned config schema and in-place upgrades
+//!
+//! Every config file carries a top-level `version`; files written before it
+//! existed are version 1. When a release renames or restructures fields it
+//! bumps [`CURRENT_CONFIG_VERSION`] and appends a [`Migration`] from the
+//! previous version. Older files keep loading (the migrations run in memory)
+//! until `supermcp migrate-config --to-latest` rewrites them.
+//!
+//! Converting 1MCP or editor configs is a separate path (`supermcp migrate`).
+
+use crate::config::manager::{ConfigDialect, ConfigFormat};
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Schema version written by this release
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// Version of files without a `version` field
+const UNVERSIONED: u32 = 1;
+
+/// Upgrade of a config from `from` to `from + 1`
+struct Migration {
+    from: u32,
+    summary: &'static str,
+    apply: fn(&mut Map<String, Value>),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    summary: "auth.auth_type renamed to auth.type",
+    apply: rename_auth_type,
+}];
+
+fn rename_auth_type(root: &mut Map<String, Value>) {
+    if let Some(Value::Object(auth)) = root.get_mut("auth") {
+        if let Some(auth_type) = auth.remove("auth_type") {
+            auth.entry("type").or_insert(auth_type);
+        }
+    }
+}
+
+/// Schema version a parsed config declares
+pub fn version_of(value: &Value) -> McpResult<u32> {
+    match value.get("version") {
+        None => Ok(UNVERSIONED),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= UNVERSIONED)
+            .ok_or_else(|| McpError::ConfigError(format!("Invalid config version: {}", version))),
+    }
+}
+
+/// Upgrade a parsed config to `target`, returning what changed
+pub fn migrate(value: &mut Value, target: u32) -> McpResult<Vec<&'static str>> {
+    let from = version_of(value)?;
+    if from > CURRENT_CONFIG_VERSION {
+        return Err(McpError::ConfigError(format!(
+            "Config version {} is newer than this release supports ({}); upgrade supermcp",
+            from, CURRENT_CONFIG_VERSION
+        )));
+    }
+    if target > CURRENT_CONFIG_VERSION || target < from {
+        return Err(McpError::ConfigError(format!(
+            "Cannot migrate config from version {} to {} (latest is {})",
+            from, target, CURRENT_CONFIG_VERSION
+        )));
+    }
+    let Some(root) = value.as_object_mut() else {
+        return Err(McpError::ConfigError("Config must be a table".to_string()));
+    };
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from && m.from < target) {
+        (migration.apply)(root);
+        applied.push(migration.summary);
+    }
+    if target != from || root.contains_key("version") {
+        root.insert("version".to_string(), Value::from(target));
+    }
+    Ok(applied)
+}
+
+/// Outcome of [`migrate_file`]
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    pub applied: Vec<&'static str>,
+    /// Copy of the original file, when it was rewritten
+    pub backup: Option<PathBuf>,
+    /// The upgraded config, serialized in the file's format
+    pub content: String,
+}
+
+/// Upgrade a config file to `target` in place, keeping a backup
+///
+/// Comments are not preserved in the rewritten file; the backup keeps
+/// them. With `dry_run` nothing is written.
+pub async fn migrate_file(path: &Path, target: u32, dry_run: bool) -> McpResult<MigrationReport> {
+    let original = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| McpError::ConfigError(format!("Failed to read config: {}", e)))?;
+    let format = ConfigFormat::detect(path, &original);
+    let mut value = parse(&original, format)?;
+
+    let dialect = ConfigDialect::detect(&value);
+    if format != ConfigFormat::Toml && dialect != ConfigDialect::Native {
+        return Err(McpError::ConfigError(format!(
+            "{} is a {:?} config; convert it with `supermcp migrate` first",
+            path.display(),
+            dialect
+        )));
+    }
+
+    let from = version_of(&value)?;
+    let applied = migrate(&mut value, target)?;
+    let content = serialize(&value, format)?;
+
+    let mut backup = None;
+    if from != target && !dry_run {
+        let backup_path = backup_path(path, from);
+        tokio::fs::write(&backup_path, &original)
+            .await
+            .map_err(|e| McpError::ConfigError(format!("Failed to write backup: {}", e)))?;
+        tokio::fs::write(path, &content)
+            .await
+            .map_err(|e| McpError::ConfigError(format!("Failed to write config: {}", e)))?;
+        info!(
+            "Migrated {} from config version {} to {} (backup: {})",
+            path.display(),
+            from,
+            target,
+            backup_path.display()
+        );
+        backup = Some(backup_path);
+    }
+
+    Ok(MigrationReport {
+        from,
+        to: target,
+        applied,
+        backup,
+        content,
+    })
+}
+
+pub(crate) fn parse(content: &str, format: ConfigFormat) -> McpResult<Value> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(content)
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse TOML config: {}", e))),
+        ConfigFormat::Json => serde_json::from_str(content)
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse JSON config: {}", e))),
+        ConfigFormat::Yaml => serde_yaml::from_str(content)
+            .map_err(|e| McpError::ConfigError(format!("Failed to parse YAML config: {}", e))),
+    }
+}
+
+pub(crate) fn serialize(value: &Value, format: ConfigFormat) -> McpResult<String> {
+    match format {
+        ConfigFormat::Toml => toml::to_string_pretty(value)
+            .map_err(|e| McpError::ConfigError(format!("Failed to serialize TOML: {}", e))),
+        ConfigFormat::Json => serde_json::to_string_pretty(value)
+            .map_err(|e| McpError::ConfigError(format!("Failed to serialize JSON: {}", e))),
+        ConfigFormat::Yaml => serde_yaml::to_string(value)
+            .map_err(|e| McpError::ConfigError(format!("Failed to serialize YAML: {}", e))),
+    }
+}
+
+/// `config.toml.v1.bak`, or `config.toml.v1.2.bak` and so on if taken
+fn backup_path(path: &Path, from: u32) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string());
+    let mut candidate = path.with_file_name(format!("{}.v{}.bak", name, from));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{}.v{}.{}.bak", name, from, n));
+        n += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_unversioned() {
+        let mut value = json!({ "auth": { "auth_type": "jwt", "issuer": "https://idp" } });
+        assert_eq!(version_of(&value).unwrap(), 1);
+
+        let applied = migrate(&mut value, CURRENT_CONFIG_VERSION).unwrap();
+        assert_eq!(applied, vec!["auth.auth_type renamed to auth.type"]);
+        assert_eq!(value["version"], CURRENT_CONFIG_VERSION);
+        assert_eq!(value["auth"], json!({ "type": "jwt", "issuer": "https://idp" }));
+
+        // Already current: nothing to do
+        assert!(migrate(&mut value, CURRENT_CONFIG_VERSION).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_versions() {
+        let mut newer = json!({ "version": CURRENT_CONFIG_VERSION + 1 });
+        assert!(migrate(&mut newer, CURRENT_CONFIG_VERSION).is_err());
+
+        let mut current = json!({ "version": CURRENT_CONFIG_VERSION });
+        assert!(migrate(&mut current, 1).is_err());
+        assert!(version_of(&json!({ "version": "two" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_file_keeps_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let original = "# tuned for prod\n[auth]\nauth_type = \"static\"\n";
+        tokio::fs::write(&path, original).await.unwrap();
+
+        let report = migrate_file(&path, CURRENT_CONFIG_VERSION, true).await.unwrap();
+        assert!(report.backup.is_none());
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), original);
+
+        let report = migrate_file(&path, CURRENT_CONFIG_VERSION, false).await.unwrap();
+        assert_eq!((report.from, report.to), (1, CURRENT_CONFIG_VERSION));
+        let backup = report.backup.unwrap();
+        assert_eq!(backup, dir.path().join("config.toml.v1.bak"));
+        assert_eq!(tokio::fs::read_to_string(&backup).await.unwrap(), original);
+
+        let migrated: crate::config::Config =
+            toml::from_str(&tokio::fs::read_to_string(&path).await.unwrap()).unwrap();
+        assert_eq!(migrated.version.0, CURRENT_CONFIG_VERSION);
+        assert!(matches!(migrated.auth.auth_type, crate::config::AuthType::Static));
+    }
+}
@@ -1,7 +1,11 @@
+pub mod diff;
 pub mod manager;
+pub mod migration;
+pub mod redact;
 pub mod types;
 pub mod validation;
 
-pub use manager::{ConfigEvent, ConfigManager};
+pub use manager::{ConfigDialect, ConfigEvent, ConfigFormat, ConfigManager};
+pub use migration::{MigrationReport, CURRENT_CONFIG_VERSION};
 pub use types::*;
 pub use validation::ConfigValidator;
@@ -0,0 +1,208 @@
+//! Secrets hidden from configs read back over the admin API
+//!
+//! `GET /admin/v1/config` and config diffs show [`REDACTED`] in place of
+//! the settings in [`SECRET_PATHS`]. A config applied with the placeholder
+//! still in place keeps the value from the current config file, so a
+//! fetched config can be edited and sent back.
+
+use crate::config::diff::ConfigChange;
+use crate::config::Config;
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::Value;
+
+/// Placeholder shown instead of a secret
+pub const REDACTED: &str = "<redacted>";
+
+/// Settings holding secrets; `*` matches every key or list entry
+const SECRET_PATHS: &[&str] = &[
+    "auth.client_secret",
+    "auth.token",
+    "auth.jwt_secret",
+    "auth.tokens.*.token",
+    "auth.jwt_keys.*.secret",
+    "admin.oidc.client_secret",
+    "federation.private_key",
+    "tenant_keys.master_key",
+    "tenant_keys.vault.token",
+    "servers.*.env.*",
+    "servers.*.headers.*",
+    "servers.*.versions.*.env.*",
+    "templates.*.server.env.*",
+    "templates.*.server.headers.*",
+    "notifications.webhooks.*.headers.*",
+    "usage.headers.*",
+];
+
+fn patterns() -> impl Iterator<Item = Vec<&'static str>> {
+    SECRET_PATHS.iter().map(|path| path.split('.').collect())
+}
+
+/// Replace every secret under `value` matching `pattern`
+fn redact_at(value: &mut Value, pattern: &[&str]) {
+    let Some((first, rest)) = pattern.split_first() else {
+        if matches!(value, Value::String(s) if !s.is_empty()) {
+            *value = Value::String(REDACTED.to_string());
+        }
+        return;
+    };
+    match (value, *first) {
+        (Value::Object(map), "*") => map.values_mut().for_each(|v| redact_at(v, rest)),
+        (Value::Array(items), "*") => items.iter_mut().for_each(|v| redact_at(v, rest)),
+        (Value::Object(map), key) => {
+            if let Some(v) = map.get_mut(key) {
+                redact_at(v, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A config as JSON, with its secrets redacted
+pub fn redact(config: &Config) -> McpResult<Value> {
+    let mut value = serde_json::to_value(config)?;
+    for pattern in patterns() {
+        redact_at(&mut value, &pattern);
+    }
+    Ok(value)
+}
+
+/// Segments of a diff path such as `servers[github].env.TOKEN`
+fn segments(path: &str) -> Vec<&str> {
+    path.split(['.', '[', ']']).filter(|s| !s.is_empty()).collect()
+}
+
+/// Redact secrets in the values of diff changes
+pub fn redact_changes(changes: &mut [ConfigChange]) {
+    for change in changes {
+        let path = segments(&change.path);
+        for pattern in patterns() {
+            let matched = path
+                .iter()
+                .zip(&pattern)
+                .all(|(segment, expected)| *expected == "*" || segment == expected);
+            if !matched {
+                continue;
+            }
+            // Past the end of the pattern the whole value is the secret
+            let rest = pattern.get(path.len()..).unwrap_or_default();
+            for value in [&mut change.before, &mut change.after].into_iter().flatten() {
+                redact_at(value, rest);
+            }
+        }
+    }
+}
+
+/// Entry of `items` matching `item`, by `name` when entries have one
+fn counterpart<'a>(items: &'a [Value], index: usize, item: &Value) -> Option<&'a Value> {
+    match item.get("name").and_then(Value::as_str) {
+        Some(name) => items
+            .iter()
+            .find(|other| other.get("name").and_then(Value::as_str) == Some(name)),
+        None => items.get(index),
+    }
+}
+
+fn restore_at(value: &mut Value, current: Option<&Value>, pattern: &[&str]) {
+    let Some((first, rest)) = pattern.split_first() else {
+        if value.as_str() == Some(REDACTED) {
+            if let Some(current) = current {
+                *value = current.clone();
+            }
+        }
+        return;
+    };
+    match (value, *first) {
+        (Value::Object(map), "*") => {
+            for (key, v) in map.iter_mut() {
+                restore_at(v, current.and_then(|c| c.get(key)), rest);
+            }
+        }
+        (Value::Array(items), "*") => {
+            let current = current.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+            for (index, v) in items.iter_mut().enumerate() {
+                let other = counterpart(current, index, v);
+                restore_at(v, other, rest);
+            }
+        }
+        (Value::Object(map), key) => {
+            if let Some(v) = map.get_mut(key) {
+                restore_at(v, current.and_then(|c| c.get(key)), rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Put the values of `current` back where `candidate` holds [`REDACTED`]
+///
+/// Fails if a placeholder is left with no current value to take its place.
+pub fn restore(candidate: &mut Value, current: &Value) -> McpResult<()> {
+    for pattern in patterns() {
+        restore_at(candidate, Some(current), &pattern);
+    }
+    if candidate.to_string().contains(REDACTED) {
+        return Err(McpError::InvalidRequest(format!(
+            "The config has a {} placeholder where no current value exists",
+            REDACTED
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::diff::diff;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_and_restore() {
+        let current = json!({
+            "auth": { "client_secret": "s3cret", "tokens": [{ "label": "ci", "token": "t0k" }] },
+            "servers": [
+                { "name": "a", "command": "a", "env": { "TOKEN": "${A_TOKEN}" } },
+                { "name": "b", "command": "b", "env": { "TOKEN": "b-token" } },
+            ],
+        });
+        let mut redacted = current.clone();
+        for pattern in patterns() {
+            redact_at(&mut redacted, &pattern);
+        }
+        assert_eq!(redacted["auth"]["client_secret"], REDACTED);
+        assert_eq!(redacted["auth"]["tokens"][0]["token"], REDACTED);
+        assert_eq!(redacted["auth"]["tokens"][0]["label"], "ci");
+        assert_eq!(redacted["servers"][1]["env"]["TOKEN"], REDACTED);
+        assert!(!redacted.to_string().contains("b-token"));
+
+        // Reordered servers get their own secrets back
+        let mut candidate = redacted.clone();
+        candidate["servers"].as_array_mut().unwrap().reverse();
+        candidate["servers"][0]["command"] = json!("b2");
+        restore(&mut candidate, &current).unwrap();
+        assert_eq!(candidate["servers"][0]["env"]["TOKEN"], "b-token");
+        assert_eq!(candidate["servers"][1]["env"]["TOKEN"], "${A_TOKEN}");
+        assert_eq!(candidate["auth"]["client_secret"], "s3cret");
+
+        candidate["servers"][0]["env"]["NEW"] = json!(REDACTED);
+        assert!(restore(&mut candidate, &current).is_err());
+    }
+
+    #[test]
+    fn test_redact_changes() {
+        let before = json!({ "servers": [{ "name": "a", "env": { "TOKEN": "old-secret" } }] });
+        let after = json!({
+            "federation": { "private_key": "federation-key" },
+            "servers": [
+                { "name": "a", "env": { "TOKEN": "new-secret" } },
+                { "name": "b", "env": { "TOKEN": "added-secret" } },
+            ],
+        });
+        let mut changes = diff(&before, &after);
+        redact_changes(&mut changes);
+        let shown = serde_json::to_string(&changes).unwrap();
+        for secret in ["old-secret", "new-secret", "added-secret", "federation-key"] {
+            assert!(!shown.contains(secret), "{} in {}", secret, shown);
+        }
+        assert!(changes.iter().any(|c| c.path == "servers[a].env.TOKEN"));
+    }
+}
@@ -1,6 +1,9 @@
 //! Configuration validation using JSON Schema
 
-use crate::config::Config;
+use crate::config::{
+    Config, FilesystemAccess, KmsProvider, LazyLoadingMode, RemoteTransport, ServerType, StateBackendType, UsageSink,
+};
+use crate::sandbox::limits::{parse_cpu_list, MAX_IO_WEIGHT};
 #[allow(unused_imports)]
 use crate::utils::errors::McpResult;
 use schemars::schema_for;
@@ -71,20 +74,24 @@ impl ConfigValidator {
 
     /// Validate TOML content
     pub fn validate_toml(&self, content: &str) -> Result<(), Vec<ValidationError>> {
-        let mut errors = Vec::new();
-
         // Parse TOML
         let config: Config = match toml::from_str(content) {
             Ok(c) => c,
             Err(e) => {
-                errors.push(ValidationError {
+                return Err(vec![ValidationError {
                     path: "root".to_string(),
                     message: format!("TOML parse error: {}", e),
-                });
-                return Err(errors);
+                }]);
             }
         };
 
+        self.validate_config(&config)
+    }
+
+    /// Validate a parsed configuration
+    pub fn validate_config(&self, config: &Config) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
         // Validate using validator crate
         if let Err(validation_errors) = config.validate() {
             for error in validation_errors.field_errors() {
@@ -96,9 +103,30 @@ impl ConfigValidator {
         }
 
         // Additional custom validations
-        self.validate_server_configs(&config, &mut errors);
-        self.validate_preset_configs(&config, &mut errors);
-        self.validate_auth_config(&config, &mut errors);
+        self.validate_server_configs(config, &mut errors);
+        self.validate_preset_configs(config, &mut errors);
+        self.validate_template_configs(config, &mut errors);
+        self.validate_auth_config(config, &mut errors);
+        self.validate_import_config(config, &mut errors);
+        self.validate_config_source(config, &mut errors);
+        self.validate_chaos_config(config, &mut errors);
+        self.validate_nats_listener(config, &mut errors);
+        self.validate_federation_config(config, &mut errors);
+        self.validate_slos(config, &mut errors);
+        self.validate_scheduling(config, &mut errors);
+        self.validate_backpressure(config, &mut errors);
+        self.validate_drift(config, &mut errors);
+        self.validate_ebpf(config, &mut errors);
+        self.validate_elevation(config, &mut errors);
+        self.validate_proxies(config, &mut errors);
+        self.validate_rbac(config, &mut errors);
+        self.validate_admin_oidc(config, &mut errors);
+        self.validate_consent(config, &mut errors);
+        self.validate_cluster(config, &mut errors);
+        self.validate_schema_replication(config, &mut errors);
+        self.validate_tenant_keys(config, &mut errors);
+        self.validate_usage(config, &mut errors);
+        self.validate_content(config, &mut errors);
 
         if errors.is_empty() {
             Ok(())
@@ -127,14 +155,226 @@ impl ConfigValidator {
                 });
             }
 
-            // Validate command
-            if server.command.is_empty() {
+            // Validate command (not needed when connecting to a named pipe or URL)
+            if server.command.is_empty()
+                && server.command_candidates.is_empty()
+                && server.named_pipe.is_none()
+                && server.url.is_none()
+            {
                 errors.push(ValidationError {
                     path: format!("servers[{}].command", idx),
                     message: "Server command cannot be empty".to_string(),
                 });
             }
 
+            // Validate SSH target
+            if server.server_type == ServerType::Ssh {
+                match &server.ssh {
+                    Some(ssh) if !ssh.host.is_empty() => {}
+                    _ => errors.push(ValidationError {
+                        path: format!("servers[{}].ssh.host", idx),
+                        message: "SSH servers need an ssh.host".to_string(),
+                    }),
+                }
+                if server.sandbox.enabled && !server.sandbox.network {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.network", idx),
+                        message: "Sandboxed SSH servers need network access for the local ssh client"
+                            .to_string(),
+                    });
+                }
+                if server.command.is_empty() || !server.command_candidates.is_empty() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].command", idx),
+                        message: "SSH servers need a remote command (command_candidates are resolved locally)"
+                            .to_string(),
+                    });
+                }
+            }
+
+            if server.server_type == ServerType::Supermcp && server.url.is_none() {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].url", idx),
+                    message: "Federated super-mcp servers need a url".to_string(),
+                });
+            }
+
+            // Validate remote endpoint
+            if let Some(url) = &server.url {
+                let nats = url.starts_with("nats://");
+                if !(url.starts_with("http://") || url.starts_with("https://") || nats) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].url", idx),
+                        message: format!("Server URL must use http://, https:// or nats://: {}", url),
+                    });
+                } else if nats != (server.transport.resolve(url) == RemoteTransport::Nats) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].transport", idx),
+                        message: "The nats transport needs a nats:// url".to_string(),
+                    });
+                }
+            }
+
+            // Validate version constraints
+            for (req_idx, requirement) in server.requires.iter().enumerate() {
+                if let Err(e) = requirement.parse::<crate::core::command::Requirement>() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].requires[{}]", idx, req_idx),
+                        message: e.to_string(),
+                    });
+                }
+            }
+
+            // Validate versions
+            let mut versions = std::collections::HashSet::new();
+            for (v_idx, version) in server.versions.iter().enumerate() {
+                if version.version.is_empty() || version.version.contains('@') {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].versions[{}].version", idx, v_idx),
+                        message: "Version must be non-empty and must not contain '@'".to_string(),
+                    });
+                }
+                if !versions.insert(&version.version) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].versions[{}].version", idx, v_idx),
+                        message: format!("Duplicate version: {}", version.version),
+                    });
+                }
+            }
+            if !server.versions.is_empty() && server.versions.iter().all(|v| v.weight == 0) {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].versions", idx),
+                    message: "At least one version needs a weight above 0".to_string(),
+                });
+            }
+            if !(0.0..=1.0).contains(&server.rollout.max_error_rate) {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].rollout.max_error_rate", idx),
+                    message: "Error rate must be between 0 and 1".to_string(),
+                });
+            }
+
+            // Validate shadow target
+            if let Some(shadow) = &server.shadow {
+                if shadow.server == server.name || !config.servers.iter().any(|s| s.name == shadow.server) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].shadow.server", idx),
+                        message: format!("Shadow must be another configured server: {}", shadow.server),
+                    });
+                }
+                if !(0.0..=1.0).contains(&shadow.fraction) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].shadow.fraction", idx),
+                        message: "Mirrored fraction must be between 0 and 1".to_string(),
+                    });
+                }
+            }
+
+            // Validate initialize overrides
+            if let Some(version) = &server.initialize.protocol_version {
+                if crate::core::protocol::ProtocolVersion::parse(version).is_none() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].initialize.protocol_version", idx),
+                        message: format!("Unknown MCP protocol version: {}", version),
+                    });
+                }
+            }
+            if server.initialize.capabilities.as_ref().is_some_and(|c| !c.is_object()) {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].initialize.capabilities", idx),
+                    message: "Capabilities must be a table".to_string(),
+                });
+            }
+
+            for nameserver in &server.http.dns.nameservers {
+                if crate::transport::dns::parse_nameserver(nameserver).is_err() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].http.dns.nameservers", idx),
+                        message: format!("Nameservers are IP addresses with an optional port, got {:?}", nameserver),
+                    });
+                }
+            }
+            if server.http.dns.timeout_ms == 0 {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].http.dns.timeout_ms", idx),
+                    message: "DNS timeout must be greater than 0".to_string(),
+                });
+            }
+
+            // Stdio requests time out after 30s
+            if server.stdio.probe_ms >= 30_000 {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].stdio.probe_ms", idx),
+                    message: "The framing probe must fire before requests time out at 30000ms".to_string(),
+                });
+            }
+
+            if server.tls.client_cert.is_some() != server.tls.client_key.is_some() {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].tls", idx),
+                    message: "client_cert and client_key must be set together".to_string(),
+                });
+            }
+
+            // Validate process pool
+            if server.pool.enabled {
+                if server.url.is_some() || server.named_pipe.is_some() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].pool.enabled", idx),
+                        message: "Only stdio servers can be pooled".to_string(),
+                    });
+                }
+                if server.pool.max_size == 0 {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].pool.max_size", idx),
+                        message: "Pool size must be greater than 0".to_string(),
+                    });
+                }
+                if server.pool.checkout_timeout_ms == 0 {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].pool.checkout_timeout_ms", idx),
+                        message: "Checkout timeout must be greater than 0".to_string(),
+                    });
+                }
+            }
+
+            // The standby is kept by the stdio supervisor
+            if server.supervision.warm_standby
+                && (!server.supervision.enabled
+                    || server.url.is_some()
+                    || server.named_pipe.is_some()
+                    || server.ssh.is_some())
+            {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].supervision.warm_standby", idx),
+                    message: "A warm standby needs a supervised stdio server".to_string(),
+                });
+            }
+
+            // Workers are extra stdio processes, dealt requests in place of a pool
+            if server.workers > 1 {
+                if server.url.is_some() || server.named_pipe.is_some() || server.ssh.is_some() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].workers", idx),
+                        message: "Only stdio servers can have workers".to_string(),
+                    });
+                }
+                if server.pool.enabled {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].workers", idx),
+                        message: "workers and pool cannot both be used".to_string(),
+                    });
+                }
+            }
+
+            // Hedges go to a second process of the pool
+            if server.hedge.enabled && (!server.pool.enabled || server.pool.max_size < 2) {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].hedge.enabled", idx),
+                    message: "Hedging needs a pool with max_size of at least 2".to_string(),
+                });
+            }
+
             // Validate sandbox memory limits
             if server.sandbox.max_memory_mb == 0 {
                 errors.push(ValidationError {
@@ -150,6 +390,104 @@ impl ConfigValidator {
                     message: "CPU percentage must be between 1 and 100".to_string(),
                 });
             }
+
+            if let Some(cpuset) = &server.sandbox.cpuset {
+                if let Err(message) = parse_cpu_list(cpuset) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.cpuset", idx),
+                        message,
+                    });
+                }
+            }
+            if server
+                .sandbox
+                .io_weight
+                .is_some_and(|weight| weight == 0 || weight > MAX_IO_WEIGHT)
+            {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].sandbox.io_weight", idx),
+                    message: format!("IO weight must be between 1 and {}", MAX_IO_WEIGHT),
+                });
+            }
+
+            for (field, limit) in [
+                ("max_processes", server.sandbox.max_processes),
+                ("max_open_files", server.sandbox.max_open_files),
+            ] {
+                if limit == Some(0) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.{}", idx, field),
+                        message: "Process and file limits must be greater than 0".to_string(),
+                    });
+                }
+            }
+
+            if !server.sandbox.gpus.is_empty() && !server.sandbox.allow_gpu {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].sandbox.gpus", idx),
+                    message: "gpus has no effect without allow_gpu".to_string(),
+                });
+            }
+            if server.sandbox.seccomp_allow.iter().any(|syscall| *syscall < 0) {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].sandbox.seccomp_allow", idx),
+                    message: "Syscall numbers cannot be negative".to_string(),
+                });
+            }
+
+            let broker = &server.sandbox.broker;
+            if broker.enabled {
+                if broker.read.is_empty() && broker.write.is_empty() {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.broker", idx),
+                        message: "The file broker needs read or write paths".to_string(),
+                    });
+                }
+                if broker.max_bytes == 0 {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.broker.max_bytes", idx),
+                        message: "max_bytes must be greater than 0".to_string(),
+                    });
+                }
+                if !crate::sandbox::broker::confines(&server.sandbox) {
+                    errors.push(ValidationError {
+                        path: format!("servers[{}].sandbox.filesystem", idx),
+                        message: "Brokered servers need an enabled sandbox with filesystem = [] or only paths \
+                                  beneath the broker's write paths"
+                            .to_string(),
+                    });
+                }
+            }
+
+            // Disk limits bound the explicitly writable paths
+            for (field, limit) in [
+                ("disk_quota_mb", server.sandbox.disk_quota_mb),
+                ("max_inodes", server.sandbox.max_inodes),
+            ] {
+                let Some(limit) = limit else {
+                    continue;
+                };
+                let message = if limit == 0 {
+                    "Disk limits must be greater than 0"
+                } else if !matches!(server.sandbox.filesystem, FilesystemAccess::Paths(_)) {
+                    "Disk limits need the writable paths listed in sandbox.filesystem"
+                } else {
+                    continue;
+                };
+                errors.push(ValidationError {
+                    path: format!("servers[{}].sandbox.{}", idx, field),
+                    message: message.to_string(),
+                });
+            }
+
+            // Full access writes anywhere, and listed paths are writable already
+            let read_only = matches!(&server.sandbox.filesystem, FilesystemAccess::Simple(access) if access != "full");
+            if !server.sandbox.writable.is_empty() && !read_only {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].sandbox.writable", idx),
+                    message: "Writable paths only apply to a readonly filesystem".to_string(),
+                });
+            }
         }
     }
 
@@ -176,8 +514,543 @@ impl ConfigValidator {
             // Validate that tags are not empty
             if preset.tags.is_empty() {
                 errors.push(ValidationError {
-                    path: format!("presets[{}].tags", idx),
-                    message: "Preset must have at least one tag".to_string(),
+                    path: format!("presets[{}].tags", idx),
+                    message: "Preset must have at least one tag".to_string(),
+                });
+            }
+        }
+    }
+
+    fn validate_template_configs(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let mut names = std::collections::HashSet::new();
+
+        for (idx, template) in config.templates.iter().enumerate() {
+            let name = &template.server.name;
+            if name.is_empty() {
+                errors.push(ValidationError {
+                    path: format!("templates[{}].name", idx),
+                    message: "Template name cannot be empty".to_string(),
+                });
+            } else if !names.insert(name) {
+                errors.push(ValidationError {
+                    path: format!("templates[{}].name", idx),
+                    message: format!("Duplicate template name: {}", name),
+                });
+            } else if config.servers.iter().any(|s| &s.name == name) {
+                errors.push(ValidationError {
+                    path: format!("templates[{}].name", idx),
+                    message: format!("Template name '{}' is also used by a server", name),
+                });
+            }
+
+            for placeholder in crate::core::template::undeclared_placeholders(template) {
+                errors.push(ValidationError {
+                    path: format!("templates[{}]", idx),
+                    message: format!("Placeholder '{{{{{}}}}}' is not a declared parameter", placeholder),
+                });
+            }
+        }
+    }
+
+    fn validate_import_config(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let watch = &config.import.watch;
+        if !watch.enabled {
+            return;
+        }
+
+        if watch.sources.is_empty() {
+            errors.push(ValidationError {
+                path: "import.watch.sources".to_string(),
+                message: "At least one source is required when import.watch is enabled".to_string(),
+            });
+        }
+        for (idx, source) in watch.sources.iter().enumerate() {
+            if !crate::cli::discover::IMPORT_SOURCES.contains(&source.as_str()) {
+                errors.push(ValidationError {
+                    path: format!("import.watch.sources[{}]", idx),
+                    message: format!("Unknown import source: {}", source),
+                });
+            }
+        }
+        if watch.interval_secs == 0 {
+            errors.push(ValidationError {
+                path: "import.watch.interval_secs".to_string(),
+                message: "Scan interval must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_config_source(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        use crate::config::ConfigRefresh;
+
+        if config.config_source.refresh == ConfigRefresh::Poll
+            && config.config_source.poll_interval_seconds == 0
+        {
+            errors.push(ValidationError {
+                path: "config_source.poll_interval_seconds".to_string(),
+                message: "Poll interval must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_chaos_config(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        for (i, rule) in config.chaos.rules.iter().enumerate() {
+            for (field, rate) in [
+                ("latency_rate", rule.latency_rate),
+                ("error_rate", rule.error_rate),
+                ("drop_rate", rule.drop_rate),
+            ] {
+                if !(0.0..=1.0).contains(&rate) {
+                    errors.push(ValidationError {
+                        path: format!("chaos.rules[{}].{}", i, field),
+                        message: "Rate must be between 0 and 1".to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_slos(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        for (i, slo) in config.slos.iter().enumerate() {
+            let mut error = |field: &str, message: &str| {
+                errors.push(ValidationError {
+                    path: format!("slos[{}].{}", i, field),
+                    message: message.to_string(),
+                });
+            };
+            if slo.latency_ms.is_none() && slo.error_rate.is_none() {
+                error("latency_ms", "An SLO needs latency_ms, error_rate or both");
+            }
+            if !(slo.latency_percentile > 0.0 && slo.latency_percentile < 100.0) {
+                error("latency_percentile", "Must be between 0 and 100, exclusive");
+            }
+            if slo.error_rate.is_some_and(|rate| !(rate > 0.0 && rate <= 1.0)) {
+                error("error_rate", "Must be above 0 and at most 1");
+            }
+            if slo.window_seconds == 0 {
+                error("window_seconds", "Must be positive");
+            }
+            if slo.burn_rate_threshold <= 0.0 {
+                error("burn_rate_threshold", "Must be positive");
+            }
+        }
+    }
+
+    fn validate_scheduling(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let scheduling = &config.scheduling;
+        if !scheduling.enabled {
+            return;
+        }
+        if axum::http::HeaderName::from_bytes(scheduling.header.as_bytes()).is_err() {
+            errors.push(ValidationError {
+                path: "scheduling.header".to_string(),
+                message: format!("Invalid header name: {}", scheduling.header),
+            });
+        }
+        let mut names = std::collections::HashSet::new();
+        for (i, class) in scheduling.classes.iter().enumerate() {
+            if class.name.is_empty() || !names.insert(&class.name) {
+                errors.push(ValidationError {
+                    path: format!("scheduling.classes[{}].name", i),
+                    message: "Class names must be unique and non-empty".to_string(),
+                });
+            }
+            if class.weight == 0 {
+                errors.push(ValidationError {
+                    path: format!("scheduling.classes[{}].weight", i),
+                    message: "Weight must be greater than 0".to_string(),
+                });
+            }
+        }
+        if !scheduling.classes.iter().any(|c| c.name == scheduling.default_class) {
+            errors.push(ValidationError {
+                path: "scheduling.default_class".to_string(),
+                message: format!("No class named {}", scheduling.default_class),
+            });
+        }
+    }
+
+    fn validate_backpressure(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let backpressure = &config.backpressure;
+        if !backpressure.enabled {
+            return;
+        }
+        let pairs = [
+            ("high_watermark", backpressure.high_watermark, "low_watermark", backpressure.low_watermark),
+            (
+                "pool_queue_high_watermark",
+                backpressure.pool_queue_high_watermark,
+                "pool_queue_low_watermark",
+                backpressure.pool_queue_low_watermark,
+            ),
+        ];
+        for (high_name, high, low_name, low) in pairs {
+            if high == 0 {
+                errors.push(ValidationError {
+                    path: format!("backpressure.{}", high_name),
+                    message: "High watermark must be greater than 0".to_string(),
+                });
+            } else if low >= high {
+                errors.push(ValidationError {
+                    path: format!("backpressure.{}", low_name),
+                    message: format!("Low watermark must be below {} ({})", high_name, high),
+                });
+            }
+        }
+    }
+
+    fn validate_drift(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let drift = &config.drift;
+        if !drift.enabled {
+            return;
+        }
+        if drift.interval_secs == 0 {
+            errors.push(ValidationError {
+                path: "drift.interval_secs".to_string(),
+                message: "Interval must be greater than 0".to_string(),
+            });
+        }
+        if drift.trusted_key.is_some() && drift.baseline.is_none() {
+            errors.push(ValidationError {
+                path: "drift.trusted_key".to_string(),
+                message: "trusted_key requires a baseline snapshot".to_string(),
+            });
+        }
+    }
+
+    fn validate_ebpf(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let ebpf = &config.ebpf;
+        if !ebpf.enabled {
+            return;
+        }
+        if ebpf.program.is_empty() {
+            errors.push(ValidationError {
+                path: "ebpf.program".to_string(),
+                message: "The eBPF observer needs its compiled program".to_string(),
+            });
+        }
+        if !cfg!(target_os = "linux") {
+            errors.push(ValidationError {
+                path: "ebpf.enabled".to_string(),
+                message: "The eBPF observer is only available on Linux".to_string(),
+            });
+        }
+    }
+
+    fn validate_content(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let content = &config.content;
+        if content.spool_threshold_bytes == 0 {
+            return;
+        }
+        if content.spool_ttl_seconds == 0 {
+            errors.push(ValidationError {
+                path: "content.spool_ttl_seconds".to_string(),
+                message: "Must be greater than 0".to_string(),
+            });
+        }
+        if content.spool_gc_interval_seconds == 0 {
+            errors.push(ValidationError {
+                path: "content.spool_gc_interval_seconds".to_string(),
+                message: "Must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_elevation(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        if config.elevation.enabled && config.elevation.max_duration_secs == 0 {
+            errors.push(ValidationError {
+                path: "elevation.max_duration_secs".to_string(),
+                message: "max_duration_secs must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_proxies(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let servers = config
+            .servers
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, server)| Some((format!("servers[{}].proxy.url", idx), server.proxy.as_ref()?)));
+        for (path, proxy) in std::iter::once(("proxy.url".to_string(), &config.proxy)).chain(servers) {
+            let Some(url) = &proxy.url else { continue };
+            let scheme = url::Url::parse(url).map(|u| u.scheme().to_string());
+            if !matches!(scheme.as_deref(), Ok("http" | "https" | "socks5" | "socks5h")) {
+                errors.push(ValidationError {
+                    path,
+                    message: "Must be an http://, https://, socks5:// or socks5h:// URL".to_string(),
+                });
+            }
+        }
+    }
+
+    fn validate_rbac(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let rbac = &config.rbac;
+        let known = |role: &str| {
+            matches!(role, "admin" | "operator" | "user") || rbac.roles.contains_key(role)
+        };
+        let mut check = |path: String, role: &str| {
+            if !known(role) {
+                errors.push(ValidationError {
+                    path,
+                    message: format!("Unknown role '{}'", role),
+                });
+            }
+        };
+        if let Some(role) = &rbac.default_role {
+            check("rbac.default_role".to_string(), role);
+        }
+        for (label, roles) in &rbac.token_roles {
+            for role in roles {
+                check(format!("rbac.token_roles.{}", label), role);
+            }
+        }
+        for (name, role) in &rbac.roles {
+            for preset in &role.presets {
+                if !config.presets.iter().any(|p| &p.name == preset) {
+                    errors.push(ValidationError {
+                        path: format!("rbac.roles.{}.presets", name),
+                        message: format!("Unknown preset '{}'", preset),
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_admin_oidc(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let oidc = &config.admin.oidc;
+        if oidc.enabled && !crate::http_server::admin_oidc::is_cookie_name(&oidc.cookie_name) {
+            errors.push(ValidationError {
+                path: "admin.oidc.cookie_name".to_string(),
+                message: "Must be a cookie token: visible ASCII without spaces or ()<>@,;:\\\"/[]?={}"
+                    .to_string(),
+            });
+        }
+    }
+
+    fn validate_consent(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let consent = &config.consent;
+        if consent.enabled && consent.tools.is_empty() {
+            errors.push(ValidationError {
+                path: "consent.tools".to_string(),
+                message: "List the server.tool patterns that need consent".to_string(),
+            });
+        }
+        if consent.ttl_days == Some(0) {
+            errors.push(ValidationError {
+                path: "consent.ttl_days".to_string(),
+                message: "Must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_cluster(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let cluster = &config.cluster;
+        if !cluster.enabled {
+            return;
+        }
+        // Followers leave shared jobs to the leader, so they must share its state
+        if config.state.backend == StateBackendType::Memory {
+            errors.push(ValidationError {
+                path: "cluster.enabled".to_string(),
+                message: "Needs a state backend shared by every node, not memory".to_string(),
+            });
+        }
+        if cluster.bind_addr.parse::<std::net::SocketAddr>().is_err() {
+            errors.push(ValidationError {
+                path: "cluster.bind_addr".to_string(),
+                message: format!("Invalid address: {}", cluster.bind_addr),
+            });
+        }
+        if cluster.heartbeat_interval_ms == 0 || cluster.election_timeout_ms == 0 {
+            errors.push(ValidationError {
+                path: "cluster".to_string(),
+                message: "heartbeat_interval_ms and election_timeout_ms must be greater than 0"
+                    .to_string(),
+            });
+        }
+        if cluster.heartbeat_timeout_ms <= cluster.heartbeat_interval_ms {
+            errors.push(ValidationError {
+                path: "cluster.heartbeat_timeout_ms".to_string(),
+                message: "Must be longer than heartbeat_interval_ms".to_string(),
+            });
+        }
+        if cluster.session_routing {
+            if !config.sessions.enabled {
+                errors.push(ValidationError {
+                    path: "cluster.session_routing".to_string(),
+                    message: "Needs sessions.enabled".to_string(),
+                });
+            }
+            let advertised = cluster.advertise_url.as_deref().map(url::Url::parse);
+            if !matches!(advertised, Some(Ok(ref url)) if matches!(url.scheme(), "http" | "https")) {
+                errors.push(ValidationError {
+                    path: "cluster.advertise_url".to_string(),
+                    message: "Session routing needs the http(s) URL other nodes reach this one at"
+                        .to_string(),
+                });
+            }
+        }
+        if cluster.read_replica {
+            if !config.sessions.enabled {
+                errors.push(ValidationError {
+                    path: "cluster.read_replica".to_string(),
+                    message: "Needs sessions.enabled".to_string(),
+                });
+            }
+            // Lists are only shared by nodes replicating their schemas
+            if !config.lazy_loading.replicate {
+                errors.push(ValidationError {
+                    path: "cluster.read_replica".to_string(),
+                    message: "Needs lazy_loading.replicate".to_string(),
+                });
+            }
+        }
+    }
+
+    fn validate_schema_replication(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let lazy = &config.lazy_loading;
+        if !lazy.replicate {
+            return;
+        }
+        if lazy.mode == LazyLoadingMode::Disabled {
+            errors.push(ValidationError {
+                path: "lazy_loading.replicate".to_string(),
+                message: "Schemas are only cached with a lazy loading mode".to_string(),
+            });
+        }
+        if config.state.backend == StateBackendType::Memory {
+            errors.push(ValidationError {
+                path: "lazy_loading.replicate".to_string(),
+                message: "Needs a state backend shared by every node, not memory".to_string(),
+            });
+        }
+    }
+
+    fn validate_tenant_keys(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let tenant_keys = &config.tenant_keys;
+        if !tenant_keys.enabled {
+            return;
+        }
+        match tenant_keys.provider {
+            KmsProvider::Local => {
+                // Keys given as ${VAR} are only known at startup
+                let valid = tenant_keys.master_key.as_deref().is_some_and(|key| {
+                    key.contains("${")
+                        || STANDARD.decode(key.trim()).is_ok_and(|bytes| bytes.len() == 32)
+                });
+                if !valid {
+                    errors.push(ValidationError {
+                        path: "tenant_keys.master_key".to_string(),
+                        message: "The local provider needs a base64 32-byte key".to_string(),
+                    });
+                }
+            }
+            KmsProvider::VaultTransit => {
+                if !tenant_keys.vault.address.starts_with("http://")
+                    && !tenant_keys.vault.address.starts_with("https://")
+                {
+                    errors.push(ValidationError {
+                        path: "tenant_keys.vault.address".to_string(),
+                        message: "Must be an http(s) URL".to_string(),
+                    });
+                }
+            }
+        }
+        if config.state.backend == StateBackendType::Memory {
+            errors.push(ValidationError {
+                path: "tenant_keys.enabled".to_string(),
+                message: "Data keys would be lost on restart; use a persistent state backend".to_string(),
+            });
+        }
+    }
+
+    fn validate_usage(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let usage = &config.usage;
+        if !usage.enabled {
+            return;
+        }
+        if !usage.url.starts_with("http://") && !usage.url.starts_with("https://") {
+            errors.push(ValidationError {
+                path: "usage.url".to_string(),
+                message: "Must be an http(s) URL".to_string(),
+            });
+        }
+        if usage.sink == UsageSink::Kafka && usage.topic.as_deref().is_none_or(str::is_empty) {
+            errors.push(ValidationError {
+                path: "usage.topic".to_string(),
+                message: "The kafka sink needs a topic".to_string(),
+            });
+        }
+        if usage.interval_seconds == 0 {
+            errors.push(ValidationError {
+                path: "usage.interval_seconds".to_string(),
+                message: "Must be greater than 0".to_string(),
+            });
+        }
+    }
+
+    fn validate_federation_config(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        use crate::transport::envelope::decode_key;
+
+        let federation = &config.federation;
+        if federation.require_encryption && federation.private_key.is_none() {
+            errors.push(ValidationError {
+                path: "federation.require_encryption".to_string(),
+                message: "Opening encrypted requests needs federation.private_key".to_string(),
+            });
+        }
+
+        // Keys given as ${VAR} are only known at startup
+        let mut check = |path: String, key: &str| {
+            if !key.contains("${") && decode_key(key).is_err() {
+                errors.push(ValidationError {
+                    path,
+                    message: "Must be a base64 X25519 key (supermcp generate federation-key)".to_string(),
+                });
+            }
+        };
+        if let Some(key) = &federation.private_key {
+            check("federation.private_key".to_string(), key);
+        }
+        for (i, peer) in federation.peers.iter().enumerate() {
+            check(format!("federation.peers[{}].public_key", i), &peer.public_key);
+        }
+        for (i, server) in config.servers.iter().enumerate() {
+            if let Some(key) = &server.peer_public_key {
+                check(format!("servers[{}].peer_public_key", i), key);
+            }
+        }
+
+        for (i, server) in config.servers.iter().enumerate() {
+            if server.peer_public_key.is_none() {
+                continue;
+            }
+            if server.server_type != ServerType::Supermcp {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].peer_public_key", i),
+                    message: "Only federated super-mcp servers take a peer_public_key".to_string(),
+                });
+            } else if federation.private_key.is_none() {
+                errors.push(ValidationError {
+                    path: format!("servers[{}].peer_public_key", i),
+                    message: "Encrypting to a peer needs federation.private_key".to_string(),
+                });
+            }
+        }
+    }
+
+    fn validate_nats_listener(&self, config: &Config, errors: &mut Vec<ValidationError>) {
+        let nats = &config.server.nats;
+        if !nats.enabled {
+            return;
+        }
+        for (field, value) in [("subject", &nats.subject), ("queue_group", &nats.queue_group)] {
+            if value.is_empty() || value.contains(char::is_whitespace) {
+                errors.push(ValidationError {
+                    path: format!("server.nats.{}", field),
+                    message: "Must be non-empty and contain no whitespace".to_string(),
                 });
             }
         }
@@ -188,12 +1061,20 @@ impl ConfigValidator {
 
         match config.auth.auth_type {
             AuthType::Static => {
-                if config.auth.token.is_none() {
+                if config.auth.token.is_none() && config.auth.tokens.is_empty() {
                     errors.push(ValidationError {
                         path: "auth.token".to_string(),
                         message: "Static auth requires a token".to_string(),
                     });
                 }
+                for (idx, token) in config.auth.tokens.iter().enumerate() {
+                    if token.label.is_empty() || token.token.is_empty() {
+                        errors.push(ValidationError {
+                            path: format!("auth.tokens[{}]", idx),
+                            message: "Labelled tokens need a label and a token".to_string(),
+                        });
+                    }
+                }
             }
             AuthType::Jwt => {
                 if config.auth.issuer.is_none() {
@@ -202,10 +1083,18 @@ impl ConfigValidator {
                         message: "JWT auth requires an issuer".to_string(),
                     });
                 }
-                if config.auth.jwt_secret.is_none() {
+                if config.auth.jwt_secret.is_none() && config.auth.jwt_keys.is_empty() {
                     errors.push(ValidationError {
                         path: "auth.jwt_secret".to_string(),
-                        message: "JWT auth requires a jwt_secret".to_string(),
+                        message: "JWT auth requires a jwt_secret or jwt_keys".to_string(),
+                    });
+                }
+                if !config.auth.jwt_keys.is_empty()
+                    && config.auth.jwt_keys.iter().all(|key| key.retired)
+                {
+                    errors.push(ValidationError {
+                        path: "auth.jwt_keys".to_string(),
+                        message: "At least one JWT key must not be retired".to_string(),
                     });
                 }
             }
@@ -242,7 +1131,57 @@ impl ConfigValidator {
                     });
                 }
             }
-            AuthType::None => {}
+            AuthType::AnonymousReadonly | AuthType::None => {}
+        }
+
+        let delegation = &config.auth.delegation;
+        if delegation.enabled {
+            // The proxy only accepts delegated tokens it can verify itself
+            if !matches!(config.auth.auth_type, AuthType::Jwt) || !config.features.auth {
+                errors.push(ValidationError {
+                    path: "auth.delegation.enabled".to_string(),
+                    message: "Delegated tokens require features.auth with jwt auth".to_string(),
+                });
+            }
+            if delegation.ttl_seconds == 0 {
+                errors.push(ValidationError {
+                    path: "auth.delegation.ttl_seconds".to_string(),
+                    message: "Must be greater than 0".to_string(),
+                });
+            }
+            if delegation.env_var.is_empty() {
+                errors.push(ValidationError {
+                    path: "auth.delegation.env_var".to_string(),
+                    message: "Must not be empty".to_string(),
+                });
+            }
+        }
+
+        let cache = &config.auth.cache;
+        if cache.enabled {
+            if cache.ttl_seconds == 0 {
+                errors.push(ValidationError {
+                    path: "auth.cache.ttl_seconds".to_string(),
+                    message: "Must be greater than 0".to_string(),
+                });
+            }
+            if cache.max_size == 0 {
+                errors.push(ValidationError {
+                    path: "auth.cache.max_size".to_string(),
+                    message: "Must be greater than 0".to_string(),
+                });
+            }
+        } else if cache.persist {
+            errors.push(ValidationError {
+                path: "auth.cache.persist".to_string(),
+                message: "Requires auth.cache.enabled".to_string(),
+            });
+        }
+        if cache.persist_path.as_deref().is_some_and(|path| path.trim().is_empty()) {
+            errors.push(ValidationError {
+                path: "auth.cache.persist_path".to_string(),
+                message: "Must not be empty".to_string(),
+            });
         }
     }
 }
@@ -306,6 +1245,563 @@ command = "echo"
         assert!(errors.iter().any(|e| e.path.contains("name")));
     }
 
+    #[test]
+    fn test_validate_workers() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "browser"
+command = "npx"
+workers = 4
+
+[[servers]]
+name = "remote"
+url = "https://mcp.example.com/mcp"
+workers = 2
+
+[servers.pool]
+enabled = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert!(paths.iter().all(|path| path.starts_with("servers[1]")));
+        assert_eq!(paths.iter().filter(|path| **path == "servers[1].workers").count(), 2);
+    }
+
+    #[test]
+    fn test_validate_warm_standby() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "critical"
+command = "npx"
+
+[servers.supervision]
+warm_standby = true
+
+[[servers]]
+name = "unsupervised"
+command = "npx"
+
+[servers.supervision]
+enabled = false
+warm_standby = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["servers[1].supervision.warm_standby"]);
+    }
+
+    #[test]
+    fn test_validate_stdio_probe() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "quick"
+command = "npx"
+
+[servers.stdio]
+probe_ms = 1000
+
+[[servers]]
+name = "slow"
+command = "npx"
+
+[servers.stdio]
+probe_ms = 30000
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["servers[1].stdio.probe_ms"]);
+    }
+
+    #[test]
+    fn test_validate_ssh_server_network() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "remote"
+type = "ssh"
+command = "uvx"
+
+[servers.ssh]
+host = "build.internal"
+
+[[servers]]
+name = "remote-net"
+type = "ssh"
+command = "uvx"
+
+[servers.ssh]
+host = "build.internal"
+
+[servers.sandbox]
+network = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["servers[0].sandbox.network"]);
+    }
+
+    #[test]
+    fn test_validate_sandbox_cpuset_and_io_weight() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "search"
+command = "npx"
+
+[servers.sandbox]
+cpuset = "0-3,8"
+io_weight = 500
+
+[[servers]]
+name = "indexer"
+command = "npx"
+
+[servers.sandbox]
+cpuset = "4-"
+io_weight = 0
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["servers[1].sandbox.cpuset", "servers[1].sandbox.io_weight"]);
+    }
+
+    #[test]
+    fn test_validate_sandbox_limits() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "writer"
+command = "npx"
+
+[servers.sandbox]
+filesystem = ["~/scratch"]
+disk_quota_mb = 1024
+max_inodes = 100000
+seccomp_mode = "audit"
+writable = ["/srv/out"]
+
+[[servers]]
+name = "readonly"
+command = "npx"
+
+[servers.sandbox]
+disk_quota_mb = 1024
+max_inodes = 0
+max_processes = 0
+max_open_files = 1024
+gpus = [0]
+seccomp_allow = [-1]
+writable = ["/srv/out"]
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "servers[0].sandbox.writable",
+                "servers[1].sandbox.max_processes",
+                "servers[1].sandbox.gpus",
+                "servers[1].sandbox.seccomp_allow",
+                "servers[1].sandbox.disk_quota_mb",
+                "servers[1].sandbox.max_inodes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_file_broker() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "notes"
+command = "npx"
+
+[servers.sandbox]
+filesystem = []
+
+[servers.sandbox.broker]
+enabled = true
+read = ["~/notes"]
+journal = "/var/log/supermcp/notes.jsonl"
+
+[[servers]]
+name = "drafts"
+command = "npx"
+
+[servers.sandbox]
+filesystem = ["~/drafts/cache"]
+
+[servers.sandbox.broker]
+enabled = true
+write = ["~/drafts"]
+
+[[servers]]
+name = "readonly"
+command = "npx"
+
+[servers.sandbox.broker]
+enabled = true
+read = ["~/notes"]
+
+[[servers]]
+name = "escapes"
+command = "npx"
+
+[servers.sandbox]
+filesystem = ["~/drafts/../.ssh", "~/notes"]
+
+[servers.sandbox.broker]
+enabled = true
+read = ["~/notes"]
+write = ["~/drafts"]
+
+[[servers]]
+name = "open"
+command = "npx"
+
+[servers.sandbox]
+filesystem = "full"
+
+[servers.sandbox.broker]
+enabled = true
+max_bytes = 0
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "servers[2].sandbox.filesystem",
+                "servers[3].sandbox.filesystem",
+                "servers[4].sandbox.broker",
+                "servers[4].sandbox.broker.max_bytes",
+                "servers[4].sandbox.filesystem"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_federation_encryption() {
+        let validator = ConfigValidator::new();
+        let errors = validator
+            .validate_toml("[federation]\nrequire_encryption = true\n")
+            .unwrap_err();
+        assert_eq!(errors[0].path, "federation.require_encryption");
+        assert!(validator
+            .validate_toml("[federation]\nprivate_key = \"${KEY}\"\nrequire_encryption = true\n")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_ebpf() {
+        let validator = ConfigValidator::new();
+        assert_eq!(
+            validator.validate_toml("[ebpf]\nenabled = true\n").is_ok(),
+            cfg!(target_os = "linux")
+        );
+
+        let errors = validator
+            .validate_toml("[ebpf]\nenabled = true\nprogram = \"\"\n")
+            .unwrap_err();
+        assert_eq!(errors[0].path, "ebpf.program");
+    }
+
+    #[test]
+    fn test_validate_elevation() {
+        let validator = ConfigValidator::new();
+        assert!(validator
+            .validate_toml("[elevation]\nenabled = true\nmax_duration_secs = 900\n")
+            .is_ok());
+
+        let errors = validator
+            .validate_toml("[elevation]\nenabled = true\nmax_duration_secs = 0\n")
+            .unwrap_err();
+        assert_eq!(errors[0].path, "elevation.max_duration_secs");
+    }
+
+    #[test]
+    fn test_validate_initialize_overrides() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[servers]]
+name = "legacy"
+command = "echo"
+
+[servers.initialize]
+client_name = "claude-ai"
+protocol_version = "2023-01-01"
+capabilities = ["roots"]
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        assert!(errors.iter().any(|e| e.path == "servers[0].initialize.protocol_version"));
+        assert!(errors.iter().any(|e| e.path == "servers[0].initialize.capabilities"));
+    }
+
+    #[test]
+    fn test_validate_proxies() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[proxy]
+url = "socks5://proxy.corp:1080"
+no_proxy = ["localhost", ".internal"]
+
+[[servers]]
+name = "remote"
+url = "https://mcp.example.com/mcp"
+
+[servers.proxy]
+url = "ftp://proxy.corp"
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "servers[0].proxy.url");
+    }
+
+    #[test]
+    fn test_validate_delegation() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[auth]
+type = "static"
+token = "secret"
+
+[auth.delegation]
+enabled = true
+ttl_seconds = 0
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["auth.delegation.enabled", "auth.delegation.ttl_seconds"]);
+    }
+
+    #[test]
+    fn test_validate_content_spool() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[content]
+spool_ttl_seconds = 0
+"#;
+        assert!(validator.validate_toml(toml).is_ok());
+
+        let toml = r#"
+[content]
+spool_threshold_bytes = 1048576
+spool_ttl_seconds = 0
+spool_gc_interval_seconds = 0
+"#;
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["content.spool_ttl_seconds", "content.spool_gc_interval_seconds"]
+        );
+    }
+
+    #[test]
+    fn test_validate_auth_cache() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[auth.cache]
+enabled = true
+ttl_seconds = 0
+persist = true
+persist_path = ""
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["auth.cache.ttl_seconds", "auth.cache.persist_path"]);
+
+        let toml = r#"
+[auth.cache]
+persist = true
+"#;
+        let errors = validator.validate_toml(toml).unwrap_err();
+        assert_eq!(errors[0].path, "auth.cache.persist");
+
+        let toml = r#"
+[auth.cache]
+enabled = true
+persist = true
+use_keyring = false
+"#;
+        assert!(validator.validate_toml(toml).is_ok());
+    }
+
+    #[test]
+    fn test_validate_cluster() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[cluster]
+enabled = true
+bind_addr = "0.0.0.0"
+heartbeat_timeout_ms = 500
+session_routing = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "cluster.enabled",
+                "cluster.bind_addr",
+                "cluster.heartbeat_timeout_ms",
+                "cluster.advertise_url"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_read_replica() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[state]
+backend = "file"
+
+[sessions]
+enabled = false
+
+[cluster]
+enabled = true
+read_replica = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["cluster.read_replica", "cluster.read_replica"]);
+    }
+
+    #[test]
+    fn test_validate_schema_replication() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[lazy_loading]
+mode = "disabled"
+replicate = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["lazy_loading.replicate", "lazy_loading.replicate"]);
+    }
+
+    #[test]
+    fn test_validate_tenant_keys() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[tenant_keys]
+enabled = true
+master_key = "c2hvcnQ="
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["tenant_keys.master_key", "tenant_keys.enabled"]);
+
+        let toml = r#"
+[state]
+backend = "file"
+
+[tenant_keys]
+enabled = true
+master_key = "${SUPERMCP_TENANT_MASTER_KEY}"
+"#;
+        assert!(validator.validate_toml(toml).is_ok());
+    }
+
+    #[test]
+    fn test_validate_admin_oidc_cookie_name() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[admin.oidc]
+enabled = true
+cookie_name = "admin session"
+"#;
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["admin.oidc.cookie_name"]);
+
+        let toml = r#"
+[admin.oidc]
+enabled = true
+cookie_name = "supermcp_admin"
+"#;
+        assert!(validator.validate_toml(toml).is_ok());
+    }
+
+    #[test]
+    fn test_validate_usage() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[usage]
+enabled = true
+sink = "kafka"
+interval_seconds = 0
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["usage.url", "usage.topic", "usage.interval_seconds"]);
+    }
+
+    #[test]
+    fn test_validate_rbac() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[presets]]
+name = "research"
+tags = ["search"]
+
+[rbac]
+enabled = true
+
+[rbac.roles.analyst]
+permissions = ["admin:read"]
+presets = ["research", "ops"]
+
+[rbac.token_roles]
+ci = ["operator", "auditor"]
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["rbac.token_roles.ci", "rbac.roles.analyst.presets"]);
+    }
+
+    #[test]
+    fn test_validate_template_placeholders() {
+        let validator = ConfigValidator::new();
+        let toml = r#"
+[[templates]]
+name = "github"
+command = "npx"
+args = ["-y", "@modelcontextprotocol/server-github"]
+env = { GITHUB_PERSONAL_ACCESS_TOKEN = "{{token}}", GITHUB_ORG = "{{org}}" }
+
+[[templates.parameters]]
+name = "token"
+secret = true
+"#;
+
+        let errors = validator.validate_toml(toml).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("{{org}}"));
+    }
+
     #[test]
     fn test_schema_generation() {
         let validator = ConfigValidator::new();
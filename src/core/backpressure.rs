@@ -0,0 +1,190 @@
+//! Load shedding
+//!
+//! Counts requests in flight through the proxy and queued for each process
+//! pool. Reaching a level's high watermark starts refusing new requests with
+//! [`McpError::Overloaded`]; refusal continues until the level drains to the
+//! low watermark, so the proxy doesn't flap at the threshold.
+
+use crate::config::BackpressureConfig;
+use crate::utils::errors::{McpError, McpResult};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// High/low watermark pair with the shedding state between them
+pub struct Watermark {
+    name: String,
+    high: usize,
+    low: usize,
+    shedding: AtomicBool,
+}
+
+impl Watermark {
+    pub fn new(name: impl Into<String>, high: usize, low: usize) -> Self {
+        Self {
+            name: name.into(),
+            high,
+            low: low.min(high),
+            shedding: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether work arriving at `level` should be refused
+    pub fn saturated(&self, level: usize) -> bool {
+        let shedding = self.shedding.load(Ordering::Relaxed);
+        let saturated = if shedding {
+            level > self.low
+        } else {
+            level >= self.high
+        };
+        if saturated != shedding && self.shedding.swap(saturated, Ordering::Relaxed) != saturated {
+            if saturated {
+                warn!("{} saturated at {}, shedding load", self.name, level);
+            } else {
+                info!("{} drained to {}, accepting requests again", self.name, level);
+            }
+        }
+        saturated
+    }
+
+    pub fn is_shedding(&self) -> bool {
+        self.shedding.load(Ordering::Relaxed)
+    }
+}
+
+/// Proxy-wide in-flight gate and drop counters
+pub struct Backpressure {
+    config: BackpressureConfig,
+    in_flight: Arc<AtomicUsize>,
+    gate: Watermark,
+    dropped_in_flight: AtomicU64,
+    dropped_pool_queue: AtomicU64,
+}
+
+/// Counts a request as in flight until dropped
+pub struct InFlight {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Current load and requests refused so far
+#[derive(Debug, Clone, Serialize)]
+pub struct BackpressureStats {
+    pub in_flight: usize,
+    pub shedding: bool,
+    pub high_watermark: usize,
+    pub low_watermark: usize,
+    /// Refused because too many requests were in flight
+    pub dropped_in_flight: u64,
+    /// Refused because a process pool's queue was full
+    pub dropped_pool_queue: u64,
+}
+
+impl Backpressure {
+    pub fn new(config: BackpressureConfig) -> Self {
+        Self {
+            gate: Watermark::new("Proxy", config.high_watermark, config.low_watermark),
+            config,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            dropped_in_flight: AtomicU64::new(0),
+            dropped_pool_queue: AtomicU64::new(0),
+        }
+    }
+
+    /// Admit a request, or refuse it while the proxy is saturated
+    pub fn admit(&self) -> McpResult<InFlight> {
+        if self.gate.saturated(self.in_flight.load(Ordering::SeqCst)) {
+            self.dropped_in_flight.fetch_add(1, Ordering::Relaxed);
+            return Err(McpError::Overloaded(self.config.retry_after_secs));
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        Ok(InFlight {
+            counter: self.in_flight.clone(),
+        })
+    }
+
+    /// Watermarks for the queue of one server's process pool
+    pub fn pool_gate(&self, server: &str) -> Watermark {
+        Watermark::new(
+            format!("Pool queue of {}", server),
+            self.config.pool_queue_high_watermark,
+            self.config.pool_queue_low_watermark,
+        )
+    }
+
+    /// Count a request refused by a saturated pool
+    pub fn pool_rejected(&self) -> McpError {
+        self.dropped_pool_queue.fetch_add(1, Ordering::Relaxed);
+        McpError::Overloaded(self.config.retry_after_secs)
+    }
+
+    pub fn stats(&self) -> BackpressureStats {
+        BackpressureStats {
+            in_flight: self.in_flight.load(Ordering::SeqCst),
+            shedding: self.gate.is_shedding(),
+            high_watermark: self.config.high_watermark,
+            low_watermark: self.config.low_watermark,
+            dropped_in_flight: self.dropped_in_flight.load(Ordering::Relaxed),
+            dropped_pool_queue: self.dropped_pool_queue.load(Ordering::Relaxed),
+        }
+    }
+}
+
+static GLOBAL: OnceCell<Arc<Backpressure>> = OnceCell::new();
+
+/// Install the process-wide load shedding state
+pub fn install_global(backpressure: Arc<Backpressure>) -> bool {
+    GLOBAL.set(backpressure).is_ok()
+}
+
+/// Load shedding state, when `[backpressure]` is enabled
+pub fn global() -> Option<Arc<Backpressure>> {
+    GLOBAL.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_hysteresis() {
+        let gate = Watermark::new("test", 10, 5);
+        assert!(!gate.saturated(9));
+        assert!(gate.saturated(10));
+        // Stays saturated until drained to the low watermark
+        assert!(gate.saturated(7));
+        assert!(!gate.saturated(5));
+        assert!(!gate.saturated(9));
+    }
+
+    #[test]
+    fn test_admit_and_drop_counts() {
+        let backpressure = Backpressure::new(BackpressureConfig {
+            enabled: true,
+            high_watermark: 2,
+            low_watermark: 1,
+            retry_after_secs: 3,
+            ..Default::default()
+        });
+        let first = backpressure.admit().unwrap();
+        let second = backpressure.admit().unwrap();
+        assert!(matches!(backpressure.admit(), Err(McpError::Overloaded(3))));
+
+        drop(second);
+        // One in flight is the low watermark, so requests are admitted again
+        let _third = backpressure.admit().unwrap();
+        drop(first);
+
+        let stats = backpressure.stats();
+        assert_eq!(stats.in_flight, 1);
+        assert_eq!(stats.dropped_in_flight, 1);
+        assert!(!stats.shedding);
+    }
+}
@@ -0,0 +1,186 @@
+//! Chaos injection for resilience testing
+//!
+//! With `[chaos]` enabled, requests to upstream servers matching a rule can
+//! be delayed, answered with an error, or failed as if the connection had
+//! dropped. Faults are injected in front of the transport, so circuit
+//! breakers, retries and clients see them as real upstream failures.
+
+use crate::config::{ChaosConfig, ChaosRuleConfig};
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
+use crate::utils::errors::{McpError, McpResult};
+use rand::Rng;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// JSON-RPC error code of injected errors (internal error)
+const INJECTED_ERROR_CODE: i32 = -32603;
+
+/// Fault chosen for one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    Error,
+    Drop,
+}
+
+/// Configured chaos rules
+pub struct ChaosInjector {
+    rules: Vec<ChaosRuleConfig>,
+}
+
+impl ChaosInjector {
+    /// Injector for an enabled config with at least one rule
+    pub fn from_config(config: &ChaosConfig) -> Option<Self> {
+        if !config.enabled || config.rules.is_empty() {
+            return None;
+        }
+        warn!(
+            "Chaos injection is enabled with {} rule(s)",
+            config.rules.len()
+        );
+        Some(Self {
+            rules: config.rules.clone(),
+        })
+    }
+
+    fn matches(rule: &ChaosRuleConfig, server: &str, request: &JsonRpcRequest) -> bool {
+        if !rule.servers.is_empty() && !rule.servers.iter().any(|s| s == server) {
+            return false;
+        }
+        if rule.tools.is_empty() {
+            return true;
+        }
+        request.method == "tools/call"
+            && request
+                .params
+                .as_ref()
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .is_some_and(|tool| rule.tools.iter().any(|t| t == tool))
+    }
+
+    /// Delay and fault for a request, given a source of rolls in [0, 1)
+    fn plan(
+        &self,
+        server: &str,
+        request: &JsonRpcRequest,
+        mut roll: impl FnMut() -> f64,
+    ) -> (Duration, Option<Fault>) {
+        let mut delay = Duration::ZERO;
+        let mut fault = None;
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| Self::matches(rule, server, request))
+        {
+            if rule.latency_ms > 0 && roll() < rule.latency_rate {
+                delay += Duration::from_millis(rule.latency_ms);
+            }
+            if fault.is_none() && roll() < rule.drop_rate {
+                fault = Some(Fault::Drop);
+            }
+            if fault.is_none() && roll() < rule.error_rate {
+                fault = Some(Fault::Error);
+            }
+        }
+        (delay, fault)
+    }
+
+    /// Apply faults to a request about to be sent to `server`
+    ///
+    /// Returns the response to answer with instead of forwarding, if any.
+    pub async fn inject(
+        &self,
+        server: &str,
+        request: &JsonRpcRequest,
+    ) -> McpResult<Option<JsonRpcResponse>> {
+        let (delay, fault) = {
+            let mut rng = rand::rng();
+            self.plan(server, request, || rng.random::<f64>())
+        };
+
+        if !delay.is_zero() {
+            debug!(
+                "Chaos: delaying {} to {} by {:?}",
+                request.method, server, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+        match fault {
+            Some(Fault::Drop) => {
+                debug!("Chaos: dropping {} to {}", request.method, server);
+                Err(McpError::TransportError(format!(
+                    "Connection to {} dropped (chaos injection)",
+                    server
+                )))
+            }
+            Some(Fault::Error) => {
+                debug!("Chaos: failing {} to {}", request.method, server);
+                Ok(Some(JsonRpcResponse::error(
+                    request.id.clone().unwrap_or(RequestId::Number(0)),
+                    INJECTED_ERROR_CODE,
+                    "Injected error (chaos)",
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn injector(rule: ChaosRuleConfig) -> ChaosInjector {
+        ChaosInjector::from_config(&ChaosConfig {
+            enabled: true,
+            rules: vec![rule],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules_match_servers_and_tools() {
+        let chaos = injector(ChaosRuleConfig {
+            servers: vec!["fs".to_string()],
+            tools: vec!["read_file".to_string()],
+            error_rate: 1.0,
+            ..Default::default()
+        });
+        let read = JsonRpcRequest::new("tools/call", Some(json!({ "name": "read_file" })));
+        let write = JsonRpcRequest::new("tools/call", Some(json!({ "name": "write_file" })));
+
+        assert_eq!(chaos.plan("fs", &read, || 0.5).1, Some(Fault::Error));
+        assert_eq!(chaos.plan("fs", &write, || 0.5).1, None);
+        assert_eq!(chaos.plan("other", &read, || 0.5).1, None);
+        assert_eq!(
+            chaos
+                .plan("fs", &JsonRpcRequest::new("tools/list", None), || 0.5)
+                .1,
+            None
+        );
+    }
+
+    #[test]
+    fn test_rates() {
+        let chaos = injector(ChaosRuleConfig {
+            latency_ms: 200,
+            latency_rate: 0.5,
+            drop_rate: 0.1,
+            error_rate: 0.3,
+            ..Default::default()
+        });
+        let request = JsonRpcRequest::new("tools/list", None);
+
+        assert_eq!(
+            chaos.plan("fs", &request, || 0.05),
+            (Duration::from_millis(200), Some(Fault::Drop))
+        );
+        assert_eq!(
+            chaos.plan("fs", &request, || 0.2),
+            (Duration::from_millis(200), Some(Fault::Error))
+        );
+        assert_eq!(chaos.plan("fs", &request, || 0.9), (Duration::ZERO, None));
+        assert!(ChaosInjector::from_config(&ChaosConfig::default()).is_none());
+    }
+}
@@ -157,6 +157,7 @@ impl CircuitBreaker {
                         "Circuit breaker '{}' opened after {} failures",
                         self.name, failures
                     );
+                    self.emit_open(format!("opened after {} failures", failures));
                 }
             }
             CircuitState::HalfOpen => {
@@ -168,6 +169,7 @@ impl CircuitBreaker {
                     "Circuit breaker '{}' re-opened after failure in half-open state",
                     self.name
                 );
+                self.emit_open("re-opened after failure in half-open state".to_string());
             }
             CircuitState::Open => {
                 *self.last_failure_time.write().await = Some(Instant::now());
@@ -175,6 +177,16 @@ impl CircuitBreaker {
         }
     }
 
+    fn emit_open(&self, message: String) {
+        crate::events::emit(
+            crate::events::Event::new(crate::events::EventKind::CircuitOpen, message)
+                .with_server(&self.name)
+                .with_details(serde_json::json!({
+                    "reset_timeout_secs": self.config.reset_timeout.as_secs(),
+                })),
+        );
+    }
+
     /// Execute a function with circuit breaker protection
     pub async fn call<F, Fut, T, E>(&self, f: F) -> Result<T, CircuitBreakerError>
     where
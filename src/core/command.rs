@@ -0,0 +1,333 @@
+//! Command resolution for stdio servers
+//!
+//! Picks the first available launcher from `command_candidates`, caches
+//! PATH lookups, and checks `requires` version constraints before a server
+//! is spawned, so one config works across machines with different
+//! toolchains and fails early with a clear message when it can't.
+
+use crate::config::{McpServerConfig, ServerType};
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::debug;
+
+static PATH_CACHE: Lazy<DashMap<String, Option<PathBuf>>> = Lazy::new(DashMap::new);
+
+static VERSION_CACHE: Lazy<DashMap<String, Version>> = Lazy::new(DashMap::new);
+
+/// Timeout for `<tool> --version` probes
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolve an executable on PATH, caching the result
+pub fn resolve_executable(name: &str) -> Option<PathBuf> {
+    if let Some(cached) = PATH_CACHE.get(name) {
+        return cached.clone();
+    }
+
+    let resolved = which::which(name).ok();
+    debug!("Resolved {} to {:?}", name, resolved);
+    PATH_CACHE.insert(name.to_string(), resolved.clone());
+    resolved
+}
+
+/// Forget cached PATH lookups and versions (e.g. after installing tools)
+pub fn clear_resolution_cache() {
+    PATH_CACHE.clear();
+    VERSION_CACHE.clear();
+}
+
+/// Dotted numeric version, compared component-wise; missing parts count
+/// as zero, so `18` equals `18.0.0`
+#[derive(Debug, Clone)]
+pub struct Version(Vec<u64>);
+
+impl Version {
+    /// Find the first dotted number in free-form text such as
+    /// "v18.17.0" or "Python 3.11.4"
+    pub fn find_in(text: &str) -> Option<Self> {
+        let start = text.find(|c: char| c.is_ascii_digit())?;
+        let candidate: String = text[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        candidate.trim_end_matches('.').parse().ok()
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| McpError::ConfigError(format!("Invalid version: {}", s)))?;
+        Ok(Self(parts))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        (0..len)
+            .map(|i| {
+                let a = self.0.get(i).copied().unwrap_or(0);
+                let b = other.0.get(i).copied().unwrap_or(0);
+                a.cmp(&b)
+            })
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// A `requires` entry such as "node >= 18" or just "uv"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub tool: String,
+    pub constraint: Option<(String, Version)>,
+}
+
+impl std::str::FromStr for Requirement {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let op_start = s.find(['<', '>', '=']);
+
+        let Some(op_start) = op_start else {
+            if s.is_empty() || s.contains(char::is_whitespace) {
+                return Err(McpError::ConfigError(format!("Invalid requirement: '{}'", s)));
+            }
+            return Ok(Self {
+                tool: s.to_string(),
+                constraint: None,
+            });
+        };
+
+        let tool = s[..op_start].trim();
+        let rest = &s[op_start..];
+        let op_len = rest
+            .find(|c: char| !matches!(c, '<' | '>' | '='))
+            .unwrap_or(rest.len());
+        let op = &rest[..op_len];
+
+        if tool.is_empty() || !matches!(op, ">=" | ">" | "<=" | "<" | "=" | "==") {
+            return Err(McpError::ConfigError(format!("Invalid requirement: '{}'", s)));
+        }
+
+        let version = rest[op_len..].parse()?;
+        Ok(Self {
+            tool: tool.to_string(),
+            constraint: Some((op.to_string(), version)),
+        })
+    }
+}
+
+impl Requirement {
+    /// Whether an installed version satisfies the constraint
+    pub fn is_satisfied_by(&self, installed: &Version) -> bool {
+        let Some((op, required)) = &self.constraint else {
+            return true;
+        };
+        let ordering = installed.cmp(required);
+        match op.as_str() {
+            ">=" => ordering != Ordering::Less,
+            ">" => ordering == Ordering::Greater,
+            "<=" => ordering != Ordering::Greater,
+            "<" => ordering == Ordering::Less,
+            _ => ordering == Ordering::Equal,
+        }
+    }
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.constraint {
+            Some((op, version)) => write!(f, "{} {} {}", self.tool, op, version),
+            None => write!(f, "{}", self.tool),
+        }
+    }
+}
+
+/// Installed version of a tool, from `<tool> --version`
+async fn tool_version(tool: &str, path: &PathBuf) -> McpResult<Version> {
+    if let Some(version) = VERSION_CACHE.get(tool) {
+        return Ok(version.clone());
+    }
+
+    let output = tokio::time::timeout(
+        VERSION_PROBE_TIMEOUT,
+        tokio::process::Command::new(path).arg("--version").output(),
+    )
+    .await
+    .map_err(|_| McpError::Timeout(VERSION_PROBE_TIMEOUT.as_millis() as u64))??;
+
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let version = Version::find_in(&text).ok_or_else(|| {
+        McpError::ConfigError(format!(
+            "Could not determine {} version from '{}'",
+            tool,
+            text.trim()
+        ))
+    })?;
+
+    VERSION_CACHE.insert(tool.to_string(), version.clone());
+    Ok(version)
+}
+
+/// Check every `requires` constraint of a server
+pub async fn check_requirements(config: &McpServerConfig) -> McpResult<()> {
+    for entry in &config.requires {
+        let requirement: Requirement = entry.parse()?;
+
+        let path = resolve_executable(&requirement.tool).ok_or_else(|| {
+            McpError::ConfigError(format!(
+                "Server '{}' requires {}, but {} was not found on PATH",
+                config.name, requirement, requirement.tool
+            ))
+        })?;
+
+        if requirement.constraint.is_none() {
+            continue;
+        }
+
+        let installed = tool_version(&requirement.tool, &path).await?;
+        if !requirement.is_satisfied_by(&installed) {
+            return Err(McpError::ConfigError(format!(
+                "Server '{}' requires {}, found {} {}",
+                config.name, requirement, requirement.tool, installed
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the launcher for a server and check its requirements
+///
+/// When `command_candidates` is set, the first candidate whose executable
+/// is on PATH replaces `command`; any extra words in the candidate (e.g.
+/// "pnpm dlx") are prepended to `args`. Deno servers get permission flags
+/// derived from their sandbox config, and SSH servers become a local
+/// `ssh` invocation.
+pub async fn resolve_server_command(config: &McpServerConfig) -> McpResult<McpServerConfig> {
+    let mut resolved = config.clone();
+
+    // Launchers and requirements describe the remote host, not this one
+    if config.server_type == ServerType::Ssh {
+        crate::transport::ssh::apply_ssh_command(&mut resolved)?;
+        return Ok(resolved);
+    }
+
+    if !config.command_candidates.is_empty() {
+        let mut chosen = None;
+        for candidate in &config.command_candidates {
+            let words = shell_words::split(candidate).map_err(|e| {
+                McpError::ConfigError(format!("Invalid command candidate '{}': {}", candidate, e))
+            })?;
+            let Some((program, prefix)) = words.split_first() else {
+                continue;
+            };
+            if resolve_executable(program).is_some() {
+                chosen = Some((program.clone(), prefix.to_vec()));
+                break;
+            }
+        }
+
+        let (program, prefix) = chosen.ok_or_else(|| {
+            McpError::ConfigError(format!(
+                "Server '{}': none of the command candidates are installed ({})",
+                config.name,
+                config.command_candidates.join(", ")
+            ))
+        })?;
+
+        debug!("Server {} using launcher {}", config.name, program);
+        resolved.command = program;
+        resolved.args = prefix.into_iter().chain(config.args.iter().cloned()).collect();
+    }
+
+    crate::runtime::deno::apply_permission_flags(&mut resolved);
+
+    check_requirements(&resolved).await?;
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering() {
+        let v = |s: &str| s.parse::<Version>().unwrap();
+        assert!(v("18.17.0") > v("18"));
+        assert!(v("3.9") < v("3.10"));
+        assert_eq!(v("v20.0"), v("20.0.0"));
+    }
+
+    #[test]
+    fn test_version_find_in() {
+        assert_eq!(Version::find_in("v18.17.0\n"), Some("18.17.0".parse().unwrap()));
+        assert_eq!(Version::find_in("Python 3.11.4"), Some("3.11.4".parse().unwrap()));
+        assert_eq!(Version::find_in("no digits"), None);
+    }
+
+    #[test]
+    fn test_requirement_parsing() {
+        let req: Requirement = "node >= 18".parse().unwrap();
+        assert_eq!(req.tool, "node");
+        assert!(req.is_satisfied_by(&"18.0.1".parse().unwrap()));
+        assert!(!req.is_satisfied_by(&"16.20.0".parse().unwrap()));
+
+        let req: Requirement = "python<3.13".parse().unwrap();
+        assert!(req.is_satisfied_by(&"3.12.1".parse().unwrap()));
+
+        let req: Requirement = "uv".parse().unwrap();
+        assert!(req.constraint.is_none());
+
+        assert!("node >>= 18".parse::<Requirement>().is_err());
+        assert!(">= 18".parse::<Requirement>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_candidates_reported() {
+        let config = McpServerConfig {
+            name: "test".to_string(),
+            command_candidates: vec!["definitely-not-a-real-launcher-xyz".to_string()],
+            ..Default::default()
+        };
+        let err = resolve_server_command(&config).await.unwrap_err();
+        assert!(err.to_string().contains("none of the command candidates"));
+    }
+}
@@ -0,0 +1,537 @@
+//! Binary content policies
+//!
+//! Image, audio and embedded blob content travels base64 encoded inside
+//! JSON-RPC results. The proxy never re-encodes it unless a policy changes
+//! the bytes: oversized blocks are replaced by a notice, image metadata can
+//! be stripped, and large blocks can be spooled to disk and handed to the
+//! client as a resource link served from `/content/<id>`. Spooled content
+//! is only readable by the tenant it was spooled for, and is deleted once
+//! it expires.
+
+use crate::config::ContentConfig;
+use crate::utils::errors::{McpError, McpResult};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// URI scheme of spooled content, e.g. `supermcp://content/<id>`
+pub const SPOOL_URI_PREFIX: &str = "supermcp://content/";
+
+/// Content policy for one request
+#[derive(Debug, Clone)]
+pub struct ContentPolicy {
+    pub max_content_bytes: usize,
+    pub strip_image_metadata: bool,
+    pub spool_threshold_bytes: usize,
+    pub spool_dir: PathBuf,
+    pub spool_ttl: chrono::Duration,
+    /// Tenant that spooled content is readable by
+    pub owner: Option<String>,
+}
+
+impl ContentPolicy {
+    /// The global policy with the tenant's overrides applied
+    pub fn for_tenant(config: &ContentConfig, tenant: Option<&str>) -> Self {
+        let overrides = tenant.and_then(|t| config.tenants.get(t));
+        Self {
+            max_content_bytes: overrides
+                .and_then(|o| o.max_content_bytes)
+                .unwrap_or(config.max_content_bytes),
+            strip_image_metadata: overrides
+                .and_then(|o| o.strip_image_metadata)
+                .unwrap_or(config.strip_image_metadata),
+            spool_threshold_bytes: config.spool_threshold_bytes,
+            spool_dir: spool_dir(config),
+            spool_ttl: chrono::Duration::seconds(config.spool_ttl_seconds as i64),
+            owner: tenant.map(str::to_string),
+        }
+    }
+
+    /// Whether results of `method` can carry binary content the policy
+    /// applies to
+    pub fn inspects(method: &str) -> bool {
+        matches!(method, "tools/call" | "prompts/get" | "resources/read")
+    }
+
+    /// Apply the policy to the binary content in a result
+    pub async fn apply(&self, method: &str, result: &mut Value) {
+        match method {
+            "tools/call" => {
+                if let Some(Value::Array(content)) = result.get_mut("content") {
+                    for block in content.iter_mut() {
+                        self.apply_block(block).await;
+                    }
+                }
+            }
+            "prompts/get" => {
+                if let Some(Value::Array(messages)) = result.get_mut("messages") {
+                    for message in messages.iter_mut() {
+                        if let Some(block) = message.get_mut("content") {
+                            self.apply_block(block).await;
+                        }
+                    }
+                }
+            }
+            "resources/read" => {
+                if let Some(Value::Array(contents)) = result.get_mut("contents") {
+                    for resource in contents.iter_mut() {
+                        // Reads of spooled content are what the links lead to
+                        let spool = !resource
+                            .get("uri")
+                            .and_then(Value::as_str)
+                            .is_some_and(|uri| uri.starts_with(SPOOL_URI_PREFIX));
+                        if let Some(replacement) = self.apply_data(resource, "blob", spool).await {
+                            let notice = match replacement.strip_prefix(SPOOL_URI_PREFIX) {
+                                Some(id) => format!(
+                                    "[Content stored as {}, downloadable from /content/{}]",
+                                    replacement, id
+                                ),
+                                None => replacement,
+                            };
+                            if let Some(resource) = resource.as_object_mut() {
+                                resource.remove("blob");
+                                resource.insert("text".to_string(), json!(notice));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn apply_block(&self, block: &mut Value) {
+        let replacement = match block.get("type").and_then(Value::as_str) {
+            Some("image") | Some("audio") => self.apply_data(block, "data", true).await,
+            Some("resource") => match block.get_mut("resource") {
+                Some(resource) => self.apply_data(resource, "blob", true).await,
+                None => None,
+            },
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            *block = match replacement.strip_prefix(SPOOL_URI_PREFIX) {
+                Some(id) => {
+                    let mut link = json!({ "type": "resource_link", "uri": replacement, "name": id });
+                    if let Some(mime_type) =
+                        block.get("mimeType").or_else(|| block.pointer("/resource/mimeType"))
+                    {
+                        link["mimeType"] = mime_type.clone();
+                    }
+                    link
+                }
+                None => json!({ "type": "text", "text": replacement }),
+            };
+        }
+    }
+
+    /// Check and rewrite the base64 field `key` of `object`
+    ///
+    /// Returns what should replace the enclosing block: a notice when the
+    /// data is too large, or the spooled URI.
+    async fn apply_data(&self, object: &mut Value, key: &str, spool: bool) -> Option<String> {
+        let encoded = object.get(key)?.as_str()?;
+        let mime_type = object
+            .get("mimeType")
+            .and_then(Value::as_str)
+            .unwrap_or("application/octet-stream")
+            .to_string();
+
+        // Data we can't decode is passed through untouched
+        let mut bytes = match STANDARD.decode(encoded) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Passing through {} content that isn't valid base64: {}", mime_type, e);
+                return None;
+            }
+        };
+
+        if bytes.len() > self.max_content_bytes {
+            debug!("Dropping {} content of {} bytes", mime_type, bytes.len());
+            return Some(format!(
+                "[{} content of {} bytes exceeds the {} byte limit]",
+                mime_type,
+                bytes.len(),
+                self.max_content_bytes
+            ));
+        }
+
+        let mut changed = false;
+        if self.strip_image_metadata {
+            if let Some(stripped) = strip_image_metadata(&mime_type, &bytes) {
+                bytes = stripped;
+                changed = true;
+            }
+        }
+
+        if spool && self.spool_threshold_bytes > 0 && bytes.len() > self.spool_threshold_bytes {
+            let meta = SpoolMeta {
+                mime_type: mime_type.clone(),
+                owner: self.owner.clone(),
+                expires_at: Utc::now() + self.spool_ttl,
+            };
+            match spool_content(&self.spool_dir, &bytes, &meta).await {
+                Ok(id) => return Some(format!("{}{}", SPOOL_URI_PREFIX, id)),
+                Err(e) => warn!("Failed to spool {} content, returning it inline: {}", mime_type, e),
+            }
+        }
+
+        if changed {
+            object[key] = Value::String(STANDARD.encode(&bytes));
+        }
+        None
+    }
+}
+
+/// Expanded spool directory
+pub fn spool_dir(config: &ContentConfig) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&config.spool_dir).to_string())
+}
+
+/// Sidecar of a spooled file, stored as `<id>.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct SpoolMeta {
+    mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    expires_at: DateTime<Utc>,
+}
+
+impl SpoolMeta {
+    /// Content spooled for a tenant is only readable by that tenant
+    fn readable_by(&self, user: Option<&str>) -> bool {
+        self.owner.is_none() || self.owner.as_deref() == user
+    }
+}
+
+/// Write content to the spool, named by its hash and owner so a tenant's
+/// repeats share a file
+///
+/// The sidecar is rewritten on repeats, so the file lives as long as the
+/// newest link to it.
+async fn spool_content(dir: &Path, bytes: &[u8], meta: &SpoolMeta) -> McpResult<String> {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    context.update(&serde_json::to_vec(&meta.owner)?);
+    context.update(bytes);
+    let id: String = context.finish().as_ref()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(&id);
+    // Sidecar first, so garbage collection finds every spooled file
+    tokio::fs::write(path.with_extension("json"), serde_json::to_vec(meta)?).await?;
+    if !tokio::fs::try_exists(&path).await? {
+        tokio::fs::write(&path, bytes).await?;
+    }
+    Ok(id)
+}
+
+/// Path and MIME type of unexpired spooled content readable by `user`
+///
+/// IDs are hashes; anything else is refused so it can't name other files.
+/// Content spooled for other tenants is reported as unknown.
+pub async fn spooled(dir: &Path, id: &str, user: Option<&str>) -> McpResult<(PathBuf, String)> {
+    if id.is_empty() || id.len() > 64 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(McpError::InvalidRequest(format!("Invalid content ID: {}", id)));
+    }
+    let unknown = || McpError::InvalidRequest(format!("Unknown content: {}", id));
+    let path = dir.join(id);
+    let meta: SpoolMeta = match tokio::fs::read(path.with_extension("json")).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(unknown()),
+        Err(e) => return Err(e.into()),
+    };
+    if meta.expires_at <= Utc::now()
+        || !meta.readable_by(user)
+        || !tokio::fs::try_exists(&path).await?
+    {
+        return Err(unknown());
+    }
+    Ok((path, meta.mime_type))
+}
+
+/// `resources/read` result for spooled content, as read by `user`
+pub async fn read_spooled(dir: &Path, uri: &str, user: Option<&str>) -> McpResult<Value> {
+    let id = uri.strip_prefix(SPOOL_URI_PREFIX).unwrap_or(uri);
+    let (path, mime_type) = spooled(dir, id, user).await?;
+    let bytes = tokio::fs::read(path).await?;
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": mime_type, "blob": STANDARD.encode(bytes) }]
+    }))
+}
+
+/// Delete expired spooled content, returning how many files were removed
+pub async fn collect_spool_garbage(dir: &Path) -> McpResult<usize> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let now = Utc::now();
+    let mut removed = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let sidecar = entry.path();
+        if sidecar.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let expired = match tokio::fs::read(&sidecar).await {
+            // Sidecars that don't parse can't be served either
+            Ok(bytes) => serde_json::from_slice::<SpoolMeta>(&bytes)
+                .map_or(true, |meta| meta.expires_at <= now),
+            Err(_) => continue,
+        };
+        if !expired {
+            continue;
+        }
+        match tokio::fs::remove_file(sidecar.with_extension("")).await {
+            Ok(()) => removed += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        tokio::fs::remove_file(&sidecar).await?;
+    }
+    Ok(removed)
+}
+
+/// Run [`collect_spool_garbage`] every `period`
+///
+/// The spool is local to each instance, so every instance collects its own.
+pub fn spawn_spool_gc(dir: PathBuf, period: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match collect_spool_garbage(&dir).await {
+                Ok(0) => {}
+                Ok(n) => info!("Deleted {} expired spooled file(s)", n),
+                Err(e) => warn!("Failed to delete expired spooled content: {}", e),
+            }
+        }
+    });
+}
+
+/// Image bytes without metadata, or None when there was nothing to strip
+/// or the image isn't a well-formed JPEG or PNG
+pub fn strip_image_metadata(mime_type: &str, bytes: &[u8]) -> Option<Vec<u8>> {
+    match mime_type {
+        "image/jpeg" | "image/jpg" => strip_jpeg(bytes),
+        "image/png" => strip_png(bytes),
+        _ => None,
+    }
+}
+
+/// Drop EXIF/XMP (APP1), IPTC (APP13) and comment segments
+///
+/// Colour profiles (APP2) and JFIF/Adobe markers are kept.
+fn strip_jpeg(bytes: &[u8]) -> Option<Vec<u8>> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len());
+    out.extend_from_slice(&bytes[..2]);
+    let mut pos = 2;
+    let mut stripped = false;
+
+    while pos + 4 <= bytes.len() {
+        if bytes[pos] != 0xFF {
+            return None;
+        }
+        let marker = bytes[pos + 1];
+        // Start of scan: the compressed image runs to the end
+        if marker == 0xDA {
+            out.extend_from_slice(&bytes[pos..]);
+            return stripped.then_some(out);
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let end = pos + 2 + len;
+        if len < 2 || end > bytes.len() {
+            return None;
+        }
+        if matches!(marker, 0xE1 | 0xED | 0xFE) {
+            stripped = true;
+        } else {
+            out.extend_from_slice(&bytes[pos..end]);
+        }
+        pos = end;
+    }
+    None
+}
+
+/// Drop text, EXIF and timestamp chunks
+fn strip_png(bytes: &[u8]) -> Option<Vec<u8>> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !bytes.starts_with(SIGNATURE) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len());
+    out.extend_from_slice(SIGNATURE);
+    let mut pos = SIGNATURE.len();
+    let mut stripped = false;
+
+    while pos + 12 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().ok()?) as usize;
+        let end = pos.checked_add(12 + len)?;
+        if end > bytes.len() {
+            return None;
+        }
+        let kind = &bytes[pos + 4..pos + 8];
+        if matches!(kind, b"tEXt" | b"zTXt" | b"iTXt" | b"eXIf" | b"tIME") {
+            stripped = true;
+        } else {
+            out.extend_from_slice(&bytes[pos..end]);
+        }
+        if kind == b"IEND" {
+            return stripped.then_some(out);
+        }
+        pos = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TenantContentConfig;
+
+    /// SOI, APP0 (JFIF), APP1 (EXIF), SOS with two bytes of scan data, EOI
+    fn jpeg_with_exif() -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x07, b'J', b'F', b'I', b'F', 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x08, b'E', b'x', b'i', b'f', 0x00, 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn policy(config: &ContentConfig, dir: &Path) -> ContentPolicy {
+        ContentPolicy {
+            spool_dir: dir.to_path_buf(),
+            ..ContentPolicy::for_tenant(config, None)
+        }
+    }
+
+    #[test]
+    fn test_strip_jpeg_metadata() {
+        let stripped = strip_image_metadata("image/jpeg", &jpeg_with_exif()).unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"Exif"));
+        assert!(stripped.windows(4).any(|w| w == b"JFIF"));
+        assert!(stripped.ends_with(&[0x12, 0x34, 0xFF, 0xD9]));
+
+        // Nothing left to strip
+        assert!(strip_image_metadata("image/jpeg", &stripped).is_none());
+        assert!(strip_image_metadata("image/jpeg", b"not a jpeg").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_binary_content_passes_through() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = STANDARD.encode((0..=255u8).collect::<Vec<_>>());
+        let original = json!({
+            "content": [
+                { "type": "image", "data": data, "mimeType": "image/png" },
+                { "type": "resource", "resource": { "uri": "file:///a", "blob": data } },
+                { "type": "audio", "data": "not base64!", "mimeType": "audio/wav" },
+            ]
+        });
+
+        let mut result = original.clone();
+        policy(&ContentConfig::default(), dir.path()).apply("tools/call", &mut result).await;
+        assert_eq!(result, original);
+    }
+
+    #[tokio::test]
+    async fn test_size_limits_and_spooling() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = ContentConfig {
+            max_content_bytes: 1024,
+            spool_threshold_bytes: 100,
+            strip_image_metadata: true,
+            tenants: [(
+                "small".to_string(),
+                TenantContentConfig {
+                    max_content_bytes: Some(10),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let block = |len: usize| {
+            json!({ "content": [{ "type": "image", "data": STANDARD.encode(vec![7u8; len]), "mimeType": "image/png" }] })
+        };
+
+        let mut result = block(2048);
+        policy(&config, dir.path()).apply("tools/call", &mut result).await;
+        assert_eq!(result["content"][0]["type"], "text");
+        assert!(result["content"][0]["text"].as_str().unwrap().contains("exceeds the 1024 byte limit"));
+
+        let mut result = block(50);
+        let tenant = ContentPolicy::for_tenant(&config, Some("small"));
+        assert_eq!(tenant.max_content_bytes, 10);
+        ContentPolicy { spool_dir: dir.path().to_path_buf(), ..tenant }
+            .apply("tools/call", &mut result)
+            .await;
+        assert_eq!(result["content"][0]["type"], "text");
+
+        let mut result = block(500);
+        policy(&config, dir.path()).apply("tools/call", &mut result).await;
+        let link = &result["content"][0];
+        assert_eq!(link["type"], "resource_link");
+        assert_eq!(link["mimeType"], "image/png");
+
+        let read = read_spooled(dir.path(), link["uri"].as_str().unwrap(), None).await.unwrap();
+        assert_eq!(read["contents"][0]["blob"], STANDARD.encode(vec![7u8; 500]));
+        assert!(spooled(dir.path(), "../etc/passwd", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spooled_content_is_owned_and_expires() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = ContentConfig {
+            spool_threshold_bytes: 100,
+            ..Default::default()
+        };
+        let blob = STANDARD.encode(vec![9u8; 500]);
+        let owned = |tenant: &str| ContentPolicy {
+            spool_dir: dir.path().to_path_buf(),
+            ..ContentPolicy::for_tenant(&config, Some(tenant))
+        };
+
+        // Large resource blobs are spooled too, and reading the spooled
+        // copy returns it inline
+        let mut result = json!({ "contents": [{ "uri": "file:///big.bin", "blob": blob }] });
+        owned("alice").apply("resources/read", &mut result).await;
+        let notice = result["contents"][0]["text"].as_str().unwrap();
+        let uri = notice.split_whitespace().find(|w| w.starts_with(SPOOL_URI_PREFIX)).unwrap();
+        let uri = uri.trim_end_matches(',');
+        let mut read = read_spooled(dir.path(), uri, Some("alice")).await.unwrap();
+        owned("alice").apply("resources/read", &mut read).await;
+        assert_eq!(read["contents"][0]["blob"], blob);
+
+        // Other tenants can't read it, and the same bytes spooled for them
+        // get their own copy
+        let id = uri.strip_prefix(SPOOL_URI_PREFIX).unwrap();
+        assert!(spooled(dir.path(), id, Some("bob")).await.is_err());
+        assert!(spooled(dir.path(), id, None).await.is_err());
+        let mut result = json!({ "contents": [{ "uri": "file:///big.bin", "blob": blob }] });
+        owned("bob").apply("resources/read", &mut result).await;
+        assert!(!result["contents"][0]["text"].as_str().unwrap().contains(id));
+
+        assert_eq!(collect_spool_garbage(dir.path()).await.unwrap(), 0);
+        let expired = ContentPolicy {
+            spool_ttl: chrono::Duration::zero(),
+            ..owned("carol")
+        };
+        let mut result = json!({ "contents": [{ "uri": "file:///big.bin", "blob": blob }] });
+        expired.apply("resources/read", &mut result).await;
+        assert_eq!(collect_spool_garbage(dir.path()).await.unwrap(), 1);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 4);
+        assert!(spooled(dir.path(), id, Some("alice")).await.is_ok());
+    }
+}
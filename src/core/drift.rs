@@ -0,0 +1,272 @@
+//! Tool catalog drift detection
+//!
+//! With `[drift]` enabled, each server's tools are listed periodically and
+//! compared with its approved catalog, taken from the baseline snapshot or
+//! else from the first listing. Drift raises a `tool_drift` event and audit
+//! entry once per distinct set of changes. With `quarantine_new_tools`,
+//! tools missing from the approved catalog are hidden from `tools/list` and
+//! refused by `tools/call` until an operator approves the server's catalog.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::cli::capabilities::{diff_items, list_all, read_snapshot, Change, ChangeKind};
+use crate::config::DriftConfig;
+use crate::core::protocol::JsonRpcResponse;
+use crate::core::ServerManager;
+use crate::events::{self, Event, EventKind};
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Unapproved changes to one server's tools
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDrift {
+    pub server: String,
+    pub detected_at: DateTime<Utc>,
+    pub changes: Vec<Change>,
+    /// Catalog that approving accepts
+    #[serde(skip)]
+    tools: BTreeMap<String, Value>,
+}
+
+/// Approved tool catalogs and the drift detected from them
+pub struct DriftMonitor {
+    config: DriftConfig,
+    /// Approved tools of each server, by name
+    approved: RwLock<HashMap<String, BTreeMap<String, Value>>>,
+    /// Drift as of each server's last check
+    pending: RwLock<BTreeMap<String, ServerDrift>>,
+}
+
+impl DriftMonitor {
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            approved: RwLock::new(HashMap::new()),
+            pending: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Monitor for `[drift]`, with the baseline snapshot's catalogs
+    /// approved, or `None` when disabled
+    pub async fn from_config(config: &DriftConfig) -> McpResult<Option<Arc<Self>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let monitor = Self::new(config.clone());
+        if let Some(path) = &config.baseline {
+            let snapshot = read_snapshot(path).await?;
+            if snapshot.signature.is_some() || config.trusted_key.is_some() {
+                let signer = snapshot.verify(config.trusted_key.as_deref())?;
+                info!("Drift baseline {} is signed by {}", path, signer);
+            }
+            let mut approved = monitor.approved.write();
+            for (name, server) in snapshot.servers {
+                if server.error.is_none() {
+                    approved.insert(name, server.tools);
+                }
+            }
+        }
+        Ok(Some(Arc::new(monitor)))
+    }
+
+    /// Check every server each `interval_secs`
+    pub fn spawn(self: Arc<Self>, server_manager: Arc<ServerManager>) -> JoinHandle<()> {
+        info!("Checking tool catalogs for drift every {}s", self.config.interval_secs);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                ticker.tick().await;
+                self.check_all(&server_manager).await;
+            }
+        })
+    }
+
+    /// List every server's tools and alert on new drift
+    pub async fn check_all(&self, server_manager: &ServerManager) {
+        for name in server_manager.list_servers() {
+            let Some(server) = server_manager.get_server(&name).map(|s| s.clone()) else {
+                continue;
+            };
+            match list_all(&server, "tools/list", "tools", "name").await {
+                Ok(tools) => {
+                    if let Some(drift) = self.check(&name, tools) {
+                        alert(&drift);
+                    }
+                }
+                Err(e) => debug!("Could not list tools of {} for drift: {}", name, e),
+            }
+        }
+    }
+
+    /// Compare a server's current tools with its approved ones, returning
+    /// the drift if it differs from what was last reported
+    pub fn check(&self, server: &str, tools: BTreeMap<String, Value>) -> Option<ServerDrift> {
+        let mut changes = Vec::new();
+        {
+            let mut catalogs = self.approved.write();
+            let Some(approved) = catalogs.get(server) else {
+                info!("Approved {} tools of {} on first sight", tools.len(), server);
+                catalogs.insert(server.to_string(), tools);
+                return None;
+            };
+            diff_items(server, "tool", approved, &tools, &mut changes);
+        }
+
+        let mut pending = self.pending.write();
+        if changes.is_empty() {
+            pending.remove(server);
+            return None;
+        }
+        if pending.get(server).is_some_and(|drift| drift.changes == changes) {
+            pending.get_mut(server)?.tools = tools;
+            return None;
+        }
+        let drift = ServerDrift {
+            server: server.to_string(),
+            detected_at: Utc::now(),
+            changes,
+            tools,
+        };
+        pending.insert(server.to_string(), drift.clone());
+        Some(drift)
+    }
+
+    /// Whether calls to `tool` are held back pending approval
+    pub fn is_quarantined(&self, server: &str, tool: &str) -> bool {
+        self.config.quarantine_new_tools
+            && self
+                .approved
+                .read()
+                .get(server)
+                .is_some_and(|tools| !tools.contains_key(tool))
+    }
+
+    /// Remove quarantined tools from a `tools/list` response
+    pub fn filter_tool_list(&self, server: &str, response: &mut JsonRpcResponse) {
+        if !self.config.quarantine_new_tools {
+            return;
+        }
+        let tools = response
+            .result
+            .as_mut()
+            .and_then(|result| result.get_mut("tools"))
+            .and_then(Value::as_array_mut);
+        if let Some(tools) = tools {
+            tools.retain(|tool| {
+                let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+                !self.is_quarantined(server, name)
+            });
+        }
+    }
+
+    /// Unapproved drift of every server
+    pub fn pending(&self) -> Vec<ServerDrift> {
+        self.pending.read().values().cloned().collect()
+    }
+
+    /// Accept a server's drifted catalog as approved
+    pub fn approve(&self, server: &str) -> McpResult<ServerDrift> {
+        let drift = self
+            .pending
+            .write()
+            .remove(server)
+            .ok_or_else(|| McpError::InvalidRequest(format!("No pending drift for {}", server)))?;
+        self.approved.write().insert(server.to_string(), drift.tools.clone());
+        info!("Approved drifted tool catalog of {}", server);
+        Ok(drift)
+    }
+}
+
+/// Log, notify and audit newly detected drift
+fn alert(drift: &ServerDrift) {
+    let count = |kind: &ChangeKind| drift.changes.iter().filter(|c| &c.change == kind).count();
+    let added = count(&ChangeKind::Added);
+    let removed = count(&ChangeKind::Removed);
+    let changed = drift.changes.len() - added - removed;
+    let message = format!(
+        "Tool catalog of {} drifted: {} added, {} removed, {} changed",
+        drift.server, added, removed, changed
+    );
+    warn!("{}", message);
+
+    let details = serde_json::json!({ "changes": drift.changes });
+    events::emit(
+        Event::new(EventKind::ToolDrift, message)
+            .with_server(&drift.server)
+            .with_details(details.clone()),
+    );
+    audit::record(
+        AuditEvent::new(AuditEventType::ToolDrift)
+            .with_server_name(&drift.server)
+            .with_details(details),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::protocol::RequestId;
+    use serde_json::json;
+
+    fn tools(names: &[&str]) -> BTreeMap<String, Value> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), json!({ "name": name, "inputSchema": { "type": "object" } })))
+            .collect()
+    }
+
+    fn monitor(quarantine: bool) -> DriftMonitor {
+        DriftMonitor::new(DriftConfig {
+            enabled: true,
+            quarantine_new_tools: quarantine,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_drift_reported_once() {
+        let monitor = monitor(false);
+        // The first listing is approved
+        assert!(monitor.check("fs", tools(&["read"])).is_none());
+        assert!(monitor.check("fs", tools(&["read"])).is_none());
+
+        let drift = monitor.check("fs", tools(&["read", "delete"])).unwrap();
+        assert_eq!(drift.changes.len(), 1);
+        assert_eq!(drift.changes[0].change, ChangeKind::Added);
+        // Same drift again isn't reported twice
+        assert!(monitor.check("fs", tools(&["read", "delete"])).is_none());
+        assert_eq!(monitor.pending().len(), 1);
+
+        // Reverting clears it
+        assert!(monitor.check("fs", tools(&["read"])).is_none());
+        assert!(monitor.pending().is_empty());
+    }
+
+    #[test]
+    fn test_quarantine_until_approved() {
+        let monitor = monitor(true);
+        monitor.check("fs", tools(&["read"]));
+        monitor.check("fs", tools(&["read", "delete"]));
+        assert!(monitor.is_quarantined("fs", "delete"));
+        assert!(!monitor.is_quarantined("fs", "read"));
+        assert!(!monitor.is_quarantined("web", "fetch"));
+
+        let mut response = JsonRpcResponse::success(
+            RequestId::Number(1),
+            json!({ "tools": [{ "name": "read" }, { "name": "delete" }] }),
+        );
+        monitor.filter_tool_list("fs", &mut response);
+        assert_eq!(response.result.unwrap()["tools"], json!([{ "name": "read" }]));
+
+        monitor.approve("fs").unwrap();
+        assert!(!monitor.is_quarantined("fs", "delete"));
+        assert!(monitor.approve("fs").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Dry-run tool calls
+//!
+//! A `tools/call` sent with `X-SuperMCP-Dry-Run: true` goes through routing
+//! and every policy check as usual, but instead of reaching the tool it is
+//! answered with a report: the server and tool that would be called,
+//! whether the arguments match the tool's input schema, and the audit entry
+//! the call would produce.
+
+use crate::audit::{AuditEvent, AuditEventType};
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
+use crate::core::request_id::current_correlation_id;
+use serde_json::{json, Value};
+use std::future::Future;
+
+/// Header asking for a dry run
+pub const DRY_RUN_HEADER: &str = "x-supermcp-dry-run";
+
+tokio::task_local! {
+    static DRY_RUN: bool;
+}
+
+/// Run `f` with tool calls answered by dry-run reports
+pub async fn with_dry_run<F: Future>(f: F) -> F::Output {
+    DRY_RUN.scope(true, f).await
+}
+
+/// Whether the request this task is serving is a dry run
+pub fn is_dry_run() -> bool {
+    DRY_RUN.try_with(|dry_run| *dry_run).unwrap_or(false)
+}
+
+/// Whether a header value asks for a dry run
+pub fn is_enabled(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes")
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Problems with `arguments` against a tool's input schema
+///
+/// Checks the parts of JSON Schema tool arguments rely on: required
+/// properties, property types and enums, and `additionalProperties: false`.
+pub fn validate_arguments(schema: &Value, arguments: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Some(arguments) = arguments.as_object() else {
+        return vec!["Arguments must be an object".to_string()];
+    };
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(name) = name.as_str() {
+            if !arguments.contains_key(name) {
+                problems.push(format!("Missing required argument {}", name));
+            }
+        }
+    }
+    for (name, value) in arguments {
+        let Some(property) = properties.and_then(|p| p.get(name)) else {
+            if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                problems.push(format!("Unexpected argument {}", name));
+            }
+            continue;
+        };
+        let types: Vec<&str> = match property.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+            problems.push(format!("Argument {} should be {}", name, types.join(" or ")));
+        }
+        if let Some(allowed) = property.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                problems.push(format!("Argument {} is not one of the allowed values", name));
+            }
+        }
+    }
+    problems
+}
+
+/// Report on a tool call that would be made
+///
+/// `schema` is the tool's input schema, when it could be found.
+pub fn report(server: &str, tool: &str, arguments: &Value, schema: Option<&Value>) -> Value {
+    let problems = match schema {
+        Some(schema) => validate_arguments(schema, arguments),
+        None => vec![format!("Tool {} is not listed by {}", tool, server)],
+    };
+    let mut audit = AuditEvent::new(AuditEventType::Request)
+        .with_server_name(server)
+        .with_details(json!({ "method": "tools/call", "tool": tool }));
+    if let Some(id) = current_correlation_id() {
+        audit = audit.with_request_id(id.to_string());
+    }
+    json!({
+        "dryRun": true,
+        "server": server,
+        "tool": tool,
+        "arguments": arguments,
+        "valid": problems.is_empty(),
+        "problems": problems,
+        "audit": audit,
+    })
+}
+
+/// `tools/call` result carrying a dry-run report
+pub fn result(report: Value) -> Value {
+    let valid = report["valid"].as_bool().unwrap_or(false);
+    let text = format!(
+        "Dry run: would call {} on {}{}",
+        report["tool"].as_str().unwrap_or_default(),
+        report["server"].as_str().unwrap_or_default(),
+        if valid { "" } else { " (arguments are invalid)" }
+    );
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "structuredContent": report,
+        "isError": !valid,
+    })
+}
+
+/// Answer a `tools/call` with its dry-run report
+pub fn response(request: &JsonRpcRequest, report: Value) -> JsonRpcResponse {
+    JsonRpcResponse::success(request.id.clone().unwrap_or(RequestId::Number(0)), result(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_arguments() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "mode": { "type": "string", "enum": ["read", "write"] },
+                "limit": { "type": ["integer", "null"] },
+            },
+            "required": ["path"],
+            "additionalProperties": false,
+        });
+        assert!(validate_arguments(&schema, &json!({ "path": "/tmp", "limit": 5 })).is_empty());
+        assert_eq!(
+            validate_arguments(&schema, &json!({ "mode": "delete", "limit": 1.5, "force": true })),
+            vec![
+                "Missing required argument path",
+                "Unexpected argument force",
+                "Argument limit should be integer or null",
+                "Argument mode is not one of the allowed values",
+            ]
+        );
+        assert!(!validate_arguments(&schema, &json!([])).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_scope() {
+        assert!(!is_dry_run());
+        assert!(with_dry_run(async { is_dry_run() }).await);
+        assert!(is_enabled("True") && !is_enabled("0"));
+    }
+}
@@ -5,7 +5,7 @@
 use crate::core::protocol::{
     JsonRpcRequest, JsonRpcResponse, RequestId,
 };
-use crate::utils::errors::McpResult;
+use crate::utils::errors::{McpError, McpResult};
 use std::collections::HashSet;
 use tracing::debug;
 
@@ -117,15 +117,12 @@ impl CapabilityFilter {
 
     /// Create an error response for denied access
     pub fn create_denied_response(&self, request_id: Option<RequestId>, resource: &str) -> JsonRpcResponse {
+        let error = McpError::AuthorizationError(format!("'{}' is not allowed by your scopes", resource));
         JsonRpcResponse {
             jsonrpc: "2.0".to_string(),
             id: request_id,
             result: None,
-            error: Some(crate::core::protocol::JsonRpcError {
-                code: -32000,
-                message: format!("Access denied: '{}' is not allowed by your scopes", resource),
-                data: None,
-            }),
+            error: Some((&error).into()),
         }
     }
 }
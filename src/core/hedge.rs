@@ -0,0 +1,137 @@
+//! Hedged requests
+//!
+//! Servers with `[servers.hedge]` enabled keep recent latencies per method,
+//! or per tool for `tools/call`. A read-only request still unanswered after
+//! the p95 of its key is sent to a second pooled process as well; whichever
+//! answers first wins.
+
+use crate::config::HedgeConfig;
+use crate::core::protocol::JsonRpcRequest;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Latencies kept per key
+const SAMPLES: usize = 256;
+
+/// Methods safe to send twice
+const HEDGEABLE_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "prompts/list",
+    "prompts/get",
+];
+
+/// Hedging policy and latency history of one server
+pub struct HedgePolicy {
+    config: HedgeConfig,
+    latencies: DashMap<String, Mutex<VecDeque<Duration>>>,
+}
+
+impl HedgePolicy {
+    pub fn new(config: HedgeConfig) -> Self {
+        Self {
+            config,
+            latencies: DashMap::new(),
+        }
+    }
+
+    /// Latency key of a request that may be hedged: the tool name for
+    /// `tools/call`, otherwise the method
+    pub fn key(&self, request: &JsonRpcRequest) -> Option<String> {
+        if request.method == "tools/call" {
+            let tool = request.params.as_ref()?.get("name")?.as_str()?;
+            return self
+                .config
+                .tools
+                .iter()
+                .any(|t| t == tool)
+                .then(|| format!("tools/call:{}", tool));
+        }
+        HEDGEABLE_METHODS
+            .contains(&request.method.as_str())
+            .then(|| request.method.clone())
+    }
+
+    /// How long to wait before hedging, once enough latencies are known
+    pub fn delay(&self, key: &str) -> Option<Duration> {
+        if let Some(ms) = self.config.delay_ms {
+            return Some(Duration::from_millis(ms));
+        }
+        let entry = self.latencies.get(key)?;
+        let samples = entry.lock();
+        if samples.len() < self.config.min_samples.max(1) {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort();
+        // Nearest rank
+        let rank = (sorted.len() as f64 * 0.95).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+
+    /// Record how long a request under `key` took
+    pub fn record(&self, key: &str, latency: Duration) {
+        let entry = self.latencies.entry(key.to_string()).or_default();
+        let mut samples = entry.lock();
+        if samples.len() == SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(delay_ms: Option<u64>) -> HedgePolicy {
+        HedgePolicy::new(HedgeConfig {
+            enabled: true,
+            tools: vec!["search".to_string()],
+            delay_ms,
+            min_samples: 20,
+        })
+    }
+
+    #[test]
+    fn test_key() {
+        let policy = policy(None);
+        let call = |name: &str| {
+            JsonRpcRequest::new("tools/call", Some(json!({ "name": name, "arguments": {} })))
+        };
+        assert_eq!(policy.key(&call("search")).as_deref(), Some("tools/call:search"));
+        assert!(policy.key(&call("delete_file")).is_none());
+        assert_eq!(
+            policy.key(&JsonRpcRequest::new("tools/list", None)).as_deref(),
+            Some("tools/list")
+        );
+        assert!(policy.key(&JsonRpcRequest::new("initialize", None)).is_none());
+    }
+
+    #[test]
+    fn test_delay_is_p95() {
+        let policy = policy(None);
+        for ms in 1..=19 {
+            policy.record("tools/list", Duration::from_millis(ms));
+        }
+        // Too few samples to judge
+        assert!(policy.delay("tools/list").is_none());
+
+        for ms in 20..=100 {
+            policy.record("tools/list", Duration::from_millis(ms));
+        }
+        assert_eq!(policy.delay("tools/list"), Some(Duration::from_millis(95)));
+        assert!(policy.delay("prompts/list").is_none());
+    }
+
+    #[test]
+    fn test_fixed_delay() {
+        assert_eq!(policy(Some(250)).delay("ping"), Some(Duration::from_millis(250)));
+    }
+}
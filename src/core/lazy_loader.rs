@@ -1,5 +1,6 @@
 //! Lazy loading for MCP tools - on-demand schema fetching with caching
 
+use crate::cache::replication::SchemaReplicator;
 use crate::cache::schema_cache::SchemaCache;
 use crate::cache::schema_cache::SchemaType;
 use crate::config::types::LazyLoadingMode;
@@ -75,6 +76,8 @@ pub struct LazyToolLoader {
     metrics: Arc<LoadMetrics>,
     /// Server capabilities cache
     capabilities_cache: Arc<RwLock<HashMap<String, ServerCapabilities>>>,
+    /// Schemas shared with other nodes, with `lazy_loading.replicate`
+    replicator: Option<Arc<SchemaReplicator>>,
 }
 
 /// Server capabilities from initialization
@@ -129,6 +132,21 @@ impl LazyToolLoader {
             cache_ttl,
             metrics: Arc::new(LoadMetrics::default()),
             capabilities_cache: Arc::new(RwLock::new(HashMap::new())),
+            replicator: None,
+        }
+    }
+
+    /// Share fetched schemas with other nodes
+    pub fn with_replication(mut self, replicator: Arc<SchemaReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    /// Load the schemas other nodes already fetched
+    pub async fn warm_cache(&self) -> McpResult<usize> {
+        match &self.replicator {
+            Some(replicator) => replicator.warm(&self.cache).await,
+            None => Ok(0),
         }
     }
 
@@ -362,29 +380,48 @@ impl LazyToolLoader {
         Ok(all_tools)
     }
 
+    /// Tools of a cached `tools/list` result
+    fn cached_tool_list(&self, server_name: &str) -> Option<Vec<ToolSchema>> {
+        let cached = self.cache.get(server_name, "list", SchemaType::Tool)?;
+        let tools = cached.schema.get("tools").and_then(|t| t.as_array())?;
+        Some(tools
+            .iter()
+            .map(|t| ToolSchema {
+                name: t.get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                description: t.get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                input_schema: t.get("inputSchema")
+                    .cloned()
+                    .unwrap_or(json!({})),
+                server_name: server_name.to_string(),
+            })
+            .collect())
+    }
+
     /// Fetch tools from a specific server
     pub async fn fetch_tools_from_server(&self, server_name: &str) -> McpResult<Vec<ToolSchema>> {
         // Check cache first
-        if let Some(cached) = self.cache.get(server_name, "list", SchemaType::Tool) {
-            if let Some(tools) = cached.schema.get("tools").and_then(|t| t.as_array()) {
-                self.metrics.cache_hits.increment();
-                return Ok(tools
-                    .iter()
-                    .map(|t| ToolSchema {
-                        name: t.get("name")
-                            .and_then(|n| n.as_str())
-                            .unwrap_or("")
-                            .to_string(),
-                        description: t.get("description")
-                            .and_then(|d| d.as_str())
-                            .unwrap_or("")
-                            .to_string(),
-                        input_schema: t.get("inputSchema")
-                            .cloned()
-                            .unwrap_or(json!({})),
-                        server_name: server_name.to_string(),
-                    })
-                    .collect());
+        if let Some(tools) = self.cached_tool_list(server_name) {
+            self.metrics.cache_hits.increment();
+            return Ok(tools);
+        }
+
+        // Another node may have fetched them already
+        if let Some(replicator) = &self.replicator {
+            match replicator.load(&self.cache, server_name, "list", SchemaType::Tool).await {
+                Ok(true) => {
+                    if let Some(tools) = self.cached_tool_list(server_name) {
+                        self.metrics.cache_hits.increment();
+                        return Ok(tools);
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Failed to read shared schemas of {}: {}", server_name, e),
             }
         }
 
@@ -402,12 +439,20 @@ impl LazyToolLoader {
 
                 if let Some(tools_array) = tools {
                     // Cache the result
-                    self.cache.insert(
+                    let cached = self.cache.insert(
                         server_name,
                         "list",
                         json!({ "tools": tools_array }),
                         SchemaType::Tool,
                     );
+                    if let Some(replicator) = &self.replicator {
+                        if let Err(e) = replicator
+                            .publish(server_name, "list", SchemaType::Tool, &cached.schema, cached.ttl)
+                            .await
+                        {
+                            warn!("Failed to share schemas of {}: {}", server_name, e);
+                        }
+                    }
 
                     // Convert to ToolSchema
                     Ok(tools_array
@@ -586,9 +631,27 @@ impl LazyToolLoader {
     /// Invalidate cached schemas for a server
     pub fn invalidate_cache(&self, server_name: &str) {
         self.cache.clear_server(server_name);
+        self.clear_shared(Some(server_name.to_string()));
         debug!("Invalidated cache for server: {}", server_name);
     }
 
+    /// Invalidate all cached schemas
+    pub fn invalidate_all(&self) {
+        self.cache.clear_all();
+        self.clear_shared(None);
+    }
+
+    /// Drop the shared copies too, so no node loads them again
+    fn clear_shared(&self, server_name: Option<String>) {
+        if let Some(replicator) = self.replicator.clone() {
+            tokio::spawn(async move {
+                if let Err(e) = replicator.clear(server_name.as_deref()).await {
+                    warn!("Failed to clear shared schemas: {}", e);
+                }
+            });
+        }
+    }
+
     /// Get loader metrics
     pub fn metrics(&self) -> &Arc<LoadMetrics> {
         &self.metrics
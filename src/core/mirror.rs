@@ -0,0 +1,164 @@
+//! Shadow traffic mirroring
+//!
+//! A server with `shadow` set copies a share of its `tools/call` requests to
+//! another server in the background. The shadow's responses are discarded,
+//! and optionally compared with the primary's so a new implementation can
+//! be validated against production traffic.
+
+use crate::config::ShadowConfig;
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::core::server::ManagedServer;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Differences listed in one log line
+const MAX_LOGGED_DIFFS: usize = 10;
+
+/// Mirroring state of one primary server
+pub struct ShadowMirror {
+    config: ShadowConfig,
+    requests: AtomicU64,
+    mirrored: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+impl ShadowMirror {
+    pub fn new(config: ShadowConfig) -> Self {
+        Self {
+            config,
+            requests: AtomicU64::new(0),
+            mirrored: AtomicU64::new(0),
+            mismatches: AtomicU64::new(0),
+        }
+    }
+
+    pub fn shadow_server(&self) -> &str {
+        &self.config.server
+    }
+
+    /// Whether the next request is mirrored
+    ///
+    /// Exactly `fraction` of requests are picked, spread evenly.
+    pub fn sample(&self) -> bool {
+        let fraction = self.config.fraction.clamp(0.0, 1.0);
+        let n = self.requests.fetch_add(1, Ordering::Relaxed) as f64;
+        (n * fraction).floor() != ((n + 1.0) * fraction).floor()
+    }
+
+    /// Requests mirrored and responses that differed, so far
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.mirrored.load(Ordering::Relaxed),
+            self.mismatches.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Send a copy of `request` to the shadow in the background
+    pub fn mirror(
+        self: Arc<Self>,
+        primary: String,
+        shadow: ManagedServer,
+        request: JsonRpcRequest,
+        primary_response: Option<JsonRpcResponse>,
+    ) {
+        tokio::spawn(async move {
+            self.mirrored.fetch_add(1, Ordering::Relaxed);
+            let shadow_response = match shadow.send_request(request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    debug!("Shadow {} of {} failed: {}", self.config.server, primary, e);
+                    return;
+                }
+            };
+            let Some(primary_response) = primary_response else {
+                return;
+            };
+
+            let diffs = diff_paths(&outcome(&primary_response), &outcome(&shadow_response));
+            if diffs.is_empty() {
+                return;
+            }
+            self.mismatches.fetch_add(1, Ordering::Relaxed);
+            if self.config.log_diffs {
+                let shown: Vec<_> = diffs.iter().take(MAX_LOGGED_DIFFS).map(String::as_str).collect();
+                warn!(
+                    "Shadow {} differs from {} at {} path(s): {}",
+                    self.config.server,
+                    primary,
+                    diffs.len(),
+                    shown.join(", ")
+                );
+            } else {
+                info!("Shadow {} differs from {}", self.config.server, primary);
+            }
+        });
+    }
+}
+
+/// What a response says, without its ID
+fn outcome(response: &JsonRpcResponse) -> Value {
+    match &response.error {
+        Some(error) => serde_json::json!({ "error": { "code": error.code, "message": error.message } }),
+        None => serde_json::json!({ "result": response.result }),
+    }
+}
+
+/// JSON pointers at which two values differ
+pub fn diff_paths(a: &Value, b: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    diff_into(a, b, String::new(), &mut paths);
+    paths
+}
+
+fn diff_into(a: &Value, b: &Value, path: String, paths: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<_> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+            keys.sort();
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_into(a, b, child, paths),
+                    _ => paths.push(child),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                diff_into(a, b, format!("{}/{}", path, i), paths);
+            }
+        }
+        _ if a != b => paths.push(if path.is_empty() { "/".to_string() } else { path }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sample_fraction() {
+        let mirror = ShadowMirror::new(ShadowConfig {
+            server: "shadow".to_string(),
+            fraction: 0.25,
+            log_diffs: true,
+        });
+        assert_eq!((0..100).filter(|_| mirror.sample()).count(), 25);
+
+        let none = ShadowMirror::new(ShadowConfig { fraction: 0.0, ..Default::default() });
+        assert!((0..100).all(|_| !none.sample()));
+    }
+
+    #[test]
+    fn test_diff_paths() {
+        let a = json!({ "content": [{ "type": "text", "text": "a" }], "isError": false });
+        let b = json!({ "content": [{ "type": "text", "text": "b" }], "meta": 1, "isError": false });
+        assert_eq!(diff_paths(&a, &b), vec!["/content/0/text", "/meta"]);
+        assert!(diff_paths(&a, &a).is_empty());
+        assert_eq!(diff_paths(&json!([1]), &json!([1, 2])), vec!["/"]);
+    }
+}
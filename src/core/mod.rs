@@ -1,20 +1,36 @@
+pub mod backpressure;
 pub mod capability;
+pub mod chaos;
 pub mod circuit_breaker;
+pub mod command;
+pub mod content;
+pub mod drift;
+pub mod dry_run;
 pub mod filter;
+pub mod hedge;
 pub mod lazy_loader;
+pub mod mirror;
 pub mod pool;
 pub mod protocol;
 pub mod provider;
 pub mod request_id;
+pub mod resource_uri;
 pub mod routing;
+pub mod scheduler;
+pub mod schema_lock;
 pub mod server;
+pub mod slo;
+pub mod supervisor;
+pub mod template;
+pub mod workers;
 
 pub use capability::{CapabilityManager, CapabilityManagerConfig, CachedCapabilities};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerManager, CircuitState};
 pub use filter::CapabilityFilter;
 pub use lazy_loader::{LazyToolLoader, LoadMetrics, PromptArgument, PromptSchema, ResourceSchema, ToolSchema};
-pub use pool::{ConnectionPoolManager, PoolConfig, PooledConnection};
+pub use pool::{ConnectionPoolManager, PoolConfig, PoolStats, PooledConnection};
 pub use provider::{McpProvider, ParameterSchema, Provider, ProviderRegistry, ProviderType, Tool, ToolResult};
 pub use request_id::{RequestIdGenerator, SharedRequestIdGenerator};
-pub use routing::{RequestRouter, RoutingMiddleware, RoutingStrategy};
-pub use server::{ManagedServer, ServerManager, ServerStatus, TransportType};
+pub use routing::{RequestRouter, RoutingMiddleware, RoutingStrategy, TrafficSplit};
+pub use server::{ManagedServer, ServerManager, ServerStatus, SessionRoute, TransportType};
+pub use supervisor::{SupervisorSnapshot, SupervisorState};
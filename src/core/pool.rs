@@ -2,16 +2,24 @@
 //!
 //! This module implements connection pooling to maintain persistent connections
 //! to downstream MCP servers, reducing latency by avoiding process spawning overhead.
-
-use crate::config::McpServerConfig;
-use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
+//! Each server's pool is sized and tuned by its own `[servers.pool]` section,
+//! and admits waiting requests by priority class (see [`crate::core::scheduler`]).
+
+use crate::auth::delegation;
+use crate::config::{McpServerConfig, ServerPoolConfig};
+use crate::core::backpressure::{self, Watermark};
+use crate::core::command::resolve_server_command;
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, ProtocolVersion, RequestId};
+use crate::core::scheduler::{FairScheduler, PriorityClasses, SchedulerPermit};
 use crate::sandbox::create_sandbox;
 use crate::transport::{StdioTransport, Transport};
-use crate::utils::errors::McpResult;
+use crate::utils::errors::{McpError, McpResult};
 use dashmap::DashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
@@ -29,24 +37,78 @@ pub struct PooledConnection {
     healthy: Arc<RwLock<bool>>,
     /// Whether this connection is currently in use
     in_use: Arc<AtomicBool>,
+    /// Checkout slot, held while the connection is handed out
+    _permit: Option<SchedulerPermit>,
+}
+
+/// Spawn a stdio server process and complete the MCP handshake with it
+///
+/// `config` must already have its command resolved. See [`initialize`] for
+/// `params`.
+pub(crate) async fn spawn_initialized(
+    config: &McpServerConfig,
+    sandbox: Arc<dyn crate::sandbox::Sandbox>,
+    params: Option<serde_json::Value>,
+) -> McpResult<Box<dyn Transport>> {
+    let transport: Box<dyn Transport> = Box::new(
+        StdioTransport::with_config(
+            config.command.clone(),
+            config.args.clone(),
+            delegation::server_env(config).await?,
+            sandbox,
+            &config.stdio,
+        )
+        .await?,
+    );
+
+    if let Err(e) = initialize(transport.as_ref(), config, params).await {
+        let _ = transport.close().await;
+        return Err(e);
+    }
+    Ok(transport)
+}
+
+/// Complete the MCP handshake with a newly spawned process
+///
+/// `params` replays a downstream client's own `initialize`; without one
+/// super-mcp introduces itself.
+pub(crate) async fn initialize(
+    transport: &dyn Transport,
+    config: &McpServerConfig,
+    params: Option<serde_json::Value>,
+) -> McpResult<()> {
+    let mut params = params.unwrap_or_else(|| {
+        serde_json::json!({
+            "protocolVersion": ProtocolVersion::LATEST.as_str(),
+            "capabilities": {},
+            "clientInfo": {
+                "name": "super-mcp",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        })
+    });
+    config.initialize.apply(&mut params);
+    let initialize = JsonRpcRequest::new("initialize", Some(params));
+    let response = transport.send_request(initialize).await?;
+    if let Some(error) = response.error {
+        return Err(McpError::TransportError(format!(
+            "{} failed to initialize: {}",
+            config.name, error.message
+        )));
+    }
+    transport
+        .send_notification(JsonRpcRequest::new("notifications/initialized", None))
+        .await
 }
 
 impl PooledConnection {
-    /// Create a new pooled connection
+    /// Create a new pooled connection and initialize the server on it
     pub async fn new(config: McpServerConfig, id: String) -> McpResult<Self> {
+        let config = resolve_server_command(&config).await?;
         let sandbox = create_sandbox(&config);
         let sandbox_arc: Arc<dyn crate::sandbox::Sandbox> = Arc::from(sandbox);
 
-        let transport: Box<dyn Transport> = Box::new(
-            StdioTransport::new(
-                config.command.clone(),
-                config.args.clone(),
-                config.env.clone(),
-                sandbox_arc,
-            )
-            .await?,
-        );
-
+        let transport = spawn_initialized(&config, sandbox_arc, None).await?;
         let now = Instant::now();
 
         Ok(Self {
@@ -56,9 +118,23 @@ impl PooledConnection {
             last_used: Arc::new(RwLock::new(now)),
             healthy: Arc::new(RwLock::new(true)),
             in_use: Arc::new(AtomicBool::new(false)),
+            _permit: None,
         })
     }
 
+    /// Another handle to the same connection
+    pub(crate) fn handle(&self, permit: Option<SchedulerPermit>) -> Self {
+        Self {
+            id: self.id.clone(),
+            transport: self.transport.clone(),
+            created_at: self.created_at,
+            last_used: self.last_used.clone(),
+            healthy: self.healthy.clone(),
+            in_use: self.in_use.clone(),
+            _permit: permit,
+        }
+    }
+
     /// Send a request through this connection
     pub async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
         let transport = self.transport.read().await;
@@ -75,6 +151,18 @@ impl PooledConnection {
         response
     }
 
+    /// Ask the server to abandon a request, e.g. one answered elsewhere
+    pub async fn cancel(&self, request_id: &RequestId, reason: &str) {
+        let notification = JsonRpcRequest::new(
+            "notifications/cancelled",
+            Some(serde_json::json!({ "requestId": request_id, "reason": reason })),
+        );
+        let transport = self.transport.read().await;
+        if let Err(e) = transport.send_notification(notification).await {
+            debug!("Failed to cancel request on {}: {}", self.id, e);
+        }
+    }
+
     /// Check if connection is still healthy
     pub async fn is_healthy(&self) -> bool {
         let transport = self.transport.read().await;
@@ -110,239 +198,344 @@ impl Drop for PooledConnection {
 pub struct PoolConfig {
     /// Maximum number of connections per server
     pub max_connections: usize,
-    /// Minimum number of connections to maintain
-    pub min_connections: usize,
     /// Maximum connection age before forced recycle
     pub max_connection_age: Duration,
     /// Maximum idle time before connection is closed
     pub max_idle_time: Duration,
-    /// Health check interval
-    pub health_check_interval: Duration,
+    /// How long a checkout waits for a free connection
+    pub checkout_timeout: Duration,
+    /// Check connections are alive before handing them out
+    pub validate_on_checkout: bool,
     /// Whether to enable connection pooling
     pub enabled: bool,
 }
 
 impl Default for PoolConfig {
     fn default() -> Self {
+        Self::from(&ServerPoolConfig::default())
+    }
+}
+
+impl From<&ServerPoolConfig> for PoolConfig {
+    fn from(config: &ServerPoolConfig) -> Self {
         Self {
-            max_connections: 10,
-            min_connections: 1,
-            max_connection_age: Duration::from_secs(3600), // 1 hour
-            max_idle_time: Duration::from_secs(300),       // 5 minutes
-            health_check_interval: Duration::from_secs(30),
-            enabled: true,
+            max_connections: config.max_size.max(1),
+            max_connection_age: Duration::from_secs(config.max_age_secs),
+            max_idle_time: Duration::from_secs(config.max_idle_secs),
+            checkout_timeout: Duration::from_millis(config.checkout_timeout_ms),
+            validate_on_checkout: config.validate_on_checkout,
+            enabled: config.enabled,
         }
     }
 }
 
 /// Connection pool for a single MCP server
-type ConnectionPool = Arc<RwLock<Vec<PooledConnection>>>;
+struct ConnectionPool {
+    config: PoolConfig,
+    connections: RwLock<Vec<PooledConnection>>,
+    /// One slot per connection that may be checked out at once
+    scheduler: Arc<FairScheduler>,
+    waiters: AtomicUsize,
+    /// Bounds `waiters` when `[backpressure]` is enabled
+    queue_gate: Option<Watermark>,
+    created: AtomicU64,
+    destroyed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl ConnectionPool {
+    fn new(server_name: &str, config: PoolConfig, classes: PriorityClasses) -> Self {
+        Self {
+            scheduler: FairScheduler::new(config.max_connections, classes),
+            config,
+            connections: RwLock::new(Vec::new()),
+            waiters: AtomicUsize::new(0),
+            queue_gate: backpressure::global().map(|bp| bp.pool_gate(server_name)),
+            created: AtomicU64::new(0),
+            destroyed: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    async fn destroy(&self, conn: PooledConnection) {
+        self.destroyed.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = conn.close().await {
+            debug!("Error closing connection {}: {}", conn.id, e);
+        }
+    }
+}
 
 /// Manages connection pools for all MCP servers
 pub struct ConnectionPoolManager {
     /// Pools keyed by server name
-    pools: DashMap<String, ConnectionPool>,
-    /// Pool configuration
-    config: PoolConfig,
-}
-
-#[derive(Debug)]
-enum PoolMaintenanceTask {
-    HealthCheck,
+    pools: DashMap<String, Arc<ConnectionPool>>,
+    /// Priority classes waiting requests are admitted by
+    classes: PriorityClasses,
 }
 
 impl ConnectionPoolManager {
     /// Create a new connection pool manager
-    pub fn new(config: PoolConfig) -> Self {
-        let (maintenance_tx, mut maintenance_rx) = mpsc::channel(100);
+    pub fn new() -> Self {
+        Self::with_classes(PriorityClasses::default())
+    }
 
-        let manager = Self {
+    /// Pool manager admitting waiting requests fairly between `classes`
+    pub fn with_classes(classes: PriorityClasses) -> Self {
+        Self {
             pools: DashMap::new(),
-            config,
-        };
-
-        // Spawn maintenance task
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(30));
-            
-            loop {
-                tokio::select! {
-                    _ = interval.tick() => {
-                        let _ = maintenance_tx.send(PoolMaintenanceTask::HealthCheck).await;
-                    }
-                    Some(task) = maintenance_rx.recv() => {
-                        match task {
-                            PoolMaintenanceTask::HealthCheck => {
-                                // Health check is done per-pool
-                            }
-                        }
-                    }
-                }
-            }
-        });
-
-        manager
+            classes,
+        }
     }
 
-    /// Get or create a pool for a server
-    pub fn get_or_create_pool(&self, server_name: &str) -> ConnectionPool {
+    /// The server's pool, created with `config` on first use
+    fn pool(&self, server_name: &str, config: PoolConfig) -> Arc<ConnectionPool> {
         self.pools
             .entry(server_name.to_string())
-            .or_insert_with(|| Arc::new(RwLock::new(Vec::new())))
+            .or_insert_with(|| Arc::new(ConnectionPool::new(server_name, config, self.classes.clone())))
             .clone()
     }
 
-    /// Acquire a connection from the pool
+    /// Acquire a connection from the server's pool, waiting up to its
+    /// checkout timeout for one to become free
+    ///
+    /// While the pool is exhausted, waiting requests are admitted by their
+    /// priority `class`. The connection returns to the pool when dropped.
+    /// A request finding the queue past its high watermark is refused with
+    /// [`McpError::Overloaded`] rather than queued.
     pub async fn acquire_connection(
         &self,
         server_name: &str,
         config: &McpServerConfig,
+        class: Option<&str>,
     ) -> McpResult<PooledConnection> {
-        if !self.config.enabled {
+        let pool_config = PoolConfig::from(&config.pool);
+        if !pool_config.enabled {
             // If pooling is disabled, create a new connection each time
             let conn = PooledConnection::new(config.clone(), format!("{}-ephemeral", server_name)).await?;
             conn.in_use.store(true, Ordering::SeqCst);
             return Ok(conn);
         }
 
-        let pool = self.get_or_create_pool(server_name);
+        let pool = self.pool(server_name, pool_config);
+        self.cleanup_pool(server_name).await;
+
+        if let (Some(gate), Some(bp)) = (&pool.queue_gate, backpressure::global()) {
+            if gate.saturated(pool.waiters.load(Ordering::SeqCst)) {
+                pool.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(bp.pool_rejected());
+            }
+        }
 
+        pool.waiters.fetch_add(1, Ordering::SeqCst);
+        let permit = tokio::time::timeout(
+            pool.config.checkout_timeout,
+            pool.scheduler.acquire(class),
+        )
+        .await;
+        pool.waiters.fetch_sub(1, Ordering::SeqCst);
+        let permit = match permit {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => {
+                return Err(McpError::TransportError(format!(
+                    "Connection pool for {} is shut down",
+                    server_name
+                )))
+            }
+            Err(_) => return Err(McpError::Timeout(pool.config.checkout_timeout.as_millis() as u64)),
+        };
+        self.checkout(server_name, config, &pool, permit).await
+    }
+
+    /// A second connection from an enabled pool, only if one is free now
+    pub async fn try_acquire_connection(
+        &self,
+        server_name: &str,
+        config: &McpServerConfig,
+    ) -> Option<PooledConnection> {
+        let pool = self.pools.get(server_name)?.clone();
+        let permit = pool.scheduler.try_acquire()?;
+        match self.checkout(server_name, config, &pool, permit).await {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                debug!("No second connection for {}: {}", server_name, e);
+                None
+            }
+        }
+    }
+
+    /// Hand out an idle connection, or open one, under a held permit
+    async fn checkout(
+        &self,
+        server_name: &str,
+        config: &McpServerConfig,
+        pool: &ConnectionPool,
+        permit: SchedulerPermit,
+    ) -> McpResult<PooledConnection> {
         // Try to find an existing healthy connection
         {
-            let pool_read = pool.read().await;
-            for conn in pool_read.iter() {
+            let mut connections = pool.connections.write().await;
+            let mut i = 0;
+            while i < connections.len() {
+                let conn = &connections[i];
                 if conn.in_use.load(Ordering::SeqCst) {
+                    i += 1;
                     continue;
                 }
-                if conn.is_healthy().await && conn.idle_duration().await < self.config.max_idle_time {
-                    if conn
-                        .in_use
-                        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-                        .is_err()
-                    {
-                        continue;
-                    }
-                    debug!("Reusing existing connection {} for {}", conn.id, server_name);
-                    return Ok(PooledConnection {
-                        id: conn.id.clone(),
-                        transport: conn.transport.clone(),
-                        created_at: conn.created_at,
-                        last_used: conn.last_used.clone(),
-                        healthy: conn.healthy.clone(),
-                        in_use: conn.in_use.clone(),
-                    });
+                let usable = if pool.config.validate_on_checkout {
+                    conn.is_healthy().await
+                } else {
+                    *conn.healthy.read().await
+                };
+                if !usable {
+                    let conn = connections.swap_remove(i);
+                    debug!("Dropping unhealthy connection {} for {}", conn.id, server_name);
+                    pool.destroy(conn).await;
+                    continue;
                 }
+                conn.in_use.store(true, Ordering::SeqCst);
+                debug!("Reusing existing connection {} for {}", conn.id, server_name);
+                return Ok(conn.handle(Some(permit)));
             }
         }
 
-        // Create a new connection
+        // Holding a permit means fewer than max_connections are in use, and
+        // none are idle, so there is room for another
         let conn_id = format!("{}-{}", server_name, uuid::Uuid::new_v4());
         let conn = PooledConnection::new(config.clone(), conn_id).await?;
         conn.in_use.store(true, Ordering::SeqCst);
+        pool.created.fetch_add(1, Ordering::Relaxed);
 
-        // Add to pool if under limit
-        {
-            let mut pool_write = pool.write().await;
-            if pool_write.len() < self.config.max_connections {
-                pool_write.push(PooledConnection {
-                    id: conn.id.clone(),
-                    transport: conn.transport.clone(),
-                    created_at: conn.created_at,
-                    last_used: conn.last_used.clone(),
-                    healthy: conn.healthy.clone(),
-                    in_use: conn.in_use.clone(),
-                });
-            }
-        }
+        let checked_out = conn.handle(Some(permit));
+        pool.connections.write().await.push(conn);
 
-        info!("Created new connection {} for {}", conn.id, server_name);
-        Ok(conn)
+        info!("Created new connection {} for {}", checked_out.id, server_name);
+        Ok(checked_out)
     }
 
-    /// Release a connection back to the pool (no-op for now, connections stay in pool)
-    pub async fn release_connection(&self, _server_name: &str, _conn: PooledConnection) {
-        _conn.in_use.store(false, Ordering::SeqCst);
+    /// Return a connection to the pool; dropping it does the same
+    pub async fn release_connection(&self, _server_name: &str, conn: PooledConnection) {
+        drop(conn);
     }
 
-    /// Clean up stale connections for a server
+    /// Close idle connections past their idle time or age
     pub async fn cleanup_pool(&self, server_name: &str) {
         let pool = match self.pools.get(server_name) {
             Some(p) => p.clone(),
             None => return,
         };
 
-        let mut pool_write = pool.write().await;
-        let before_count = pool_write.len();
-
-        // Remove stale or unhealthy connections
-        pool_write.retain(|conn| {
-            if conn.in_use.load(Ordering::SeqCst) {
-                return true;
+        let mut stale = Vec::new();
+        {
+            let mut connections = pool.connections.write().await;
+            let mut i = 0;
+            while i < connections.len() {
+                let conn = &connections[i];
+                let expired = !conn.in_use.load(Ordering::SeqCst)
+                    && (conn.age() >= pool.config.max_connection_age
+                        || conn.idle_duration().await >= pool.config.max_idle_time);
+                if expired {
+                    stale.push(connections.swap_remove(i));
+                } else {
+                    i += 1;
+                }
             }
-            let age = conn.age();
-            age < self.config.max_connection_age
-        });
-
-        let after_count = pool_write.len();
-        if before_count != after_count {
-            debug!(
-                "Cleaned up {} connections for {}",
-                before_count - after_count,
-                server_name
-            );
+        }
+
+        if !stale.is_empty() {
+            debug!("Cleaned up {} connections for {}", stale.len(), server_name);
+        }
+        for conn in stale {
+            pool.destroy(conn).await;
         }
     }
 
     /// Clean up all pools
     pub async fn cleanup_all_pools(&self) {
-        for entry in self.pools.iter() {
-            self.cleanup_pool(entry.key()).await;
+        let names: Vec<String> = self.pools.iter().map(|entry| entry.key().clone()).collect();
+        for name in names {
+            self.cleanup_pool(&name).await;
         }
     }
 
-    /// Get pool statistics
-    pub fn get_pool_stats(&self, server_name: &str) -> Option<PoolStats> {
-        self.pools.get(server_name).map(|_pool| {
-            // Note: This is synchronous - for accurate stats we'd need async
-            PoolStats {
-                total_connections: 0, // Would need async block
-                healthy_connections: 0,
-                idle_connections: 0,
+    /// Close a server's pool, e.g. after its config changed
+    pub async fn remove_pool(&self, server_name: &str) {
+        if let Some((_, pool)) = self.pools.remove(server_name) {
+            pool.scheduler.close();
+            for conn in pool.connections.write().await.drain(..) {
+                if let Err(e) = conn.close().await {
+                    warn!("Error closing connection {}: {}", conn.id, e);
+                }
             }
+        }
+    }
+
+    /// Get pool statistics
+    pub async fn get_pool_stats(&self, server_name: &str) -> Option<PoolStats> {
+        let pool = self.pools.get(server_name)?.clone();
+        let connections = pool.connections.read().await;
+        let busy = connections
+            .iter()
+            .filter(|conn| conn.in_use.load(Ordering::SeqCst))
+            .count();
+        Some(PoolStats {
+            server: server_name.to_string(),
+            max_size: pool.config.max_connections,
+            idle: connections.len() - busy,
+            busy,
+            waiters: pool.waiters.load(Ordering::SeqCst),
+            waiters_by_class: pool.scheduler.waiting().into_iter().collect(),
+            created: pool.created.load(Ordering::Relaxed),
+            destroyed: pool.destroyed.load(Ordering::Relaxed),
+            rejected: pool.rejected.load(Ordering::Relaxed),
         })
     }
 
+    /// Statistics of every pool, by server name
+    pub async fn all_pool_stats(&self) -> Vec<PoolStats> {
+        let mut names: Vec<String> = self.pools.iter().map(|entry| entry.key().clone()).collect();
+        names.sort();
+        let mut stats = Vec::with_capacity(names.len());
+        for name in names {
+            stats.extend(self.get_pool_stats(&name).await);
+        }
+        stats
+    }
+
     /// Shutdown all pools
     pub async fn shutdown(&self) {
         info!("Shutting down connection pools...");
 
-        for entry in self.pools.iter() {
-            let pool = entry.value().clone();
-            let pool_read = pool.read().await;
-
-            for conn in pool_read.iter() {
-                if let Err(e) = conn.close().await {
-                    warn!("Error closing connection {}: {}", conn.id, e);
-                }
-            }
+        let names: Vec<String> = self.pools.iter().map(|entry| entry.key().clone()).collect();
+        for name in names {
+            self.remove_pool(&name).await;
         }
 
-        self.pools.clear();
         info!("All connection pools shut down");
     }
 }
 
-/// Pool statistics
-#[derive(Debug, Clone)]
+/// Live statistics of one server's pool
+#[derive(Debug, Clone, Serialize)]
 pub struct PoolStats {
-    pub total_connections: usize,
-    pub healthy_connections: usize,
-    pub idle_connections: usize,
+    pub server: String,
+    pub max_size: usize,
+    pub idle: usize,
+    pub busy: usize,
+    /// Requests waiting for a free connection
+    pub waiters: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub waiters_by_class: BTreeMap<String, usize>,
+    /// Connections opened since the pool was created
+    pub created: u64,
+    /// Connections closed for being stale or unhealthy
+    pub destroyed: u64,
+    /// Requests refused because the queue was saturated
+    pub rejected: u64,
 }
 
 impl Default for ConnectionPoolManager {
     fn default() -> Self {
-        Self::new(PoolConfig::default())
+        Self::new()
     }
 }
 
@@ -353,26 +546,58 @@ mod tests {
     #[test]
     fn test_pool_config_default() {
         let config = PoolConfig::default();
-        assert_eq!(config.max_connections, 10);
-        assert_eq!(config.min_connections, 1);
-        assert!(config.enabled);
+        assert_eq!(config.max_connections, 4);
+        assert_eq!(config.checkout_timeout, Duration::from_secs(5));
+        assert!(config.validate_on_checkout);
+        assert!(!config.enabled);
     }
 
     #[tokio::test]
     async fn test_pool_manager_creation() {
-        let manager = ConnectionPoolManager::new(PoolConfig::default());
+        let manager = ConnectionPoolManager::new();
         assert!(manager.pools.is_empty());
+        assert!(manager.all_pool_stats().await.is_empty());
     }
 
-    #[test]
-    fn test_pool_stats() {
-        let stats = PoolStats {
-            total_connections: 5,
-            healthy_connections: 4,
-            idle_connections: 3,
+    #[tokio::test]
+    async fn test_pool_stats() {
+        let manager = ConnectionPoolManager::new();
+        let pool = manager.pool(
+            "github",
+            PoolConfig::from(&ServerPoolConfig {
+                enabled: true,
+                max_size: 2,
+                ..Default::default()
+            }),
+        );
+        pool.created.fetch_add(3, Ordering::Relaxed);
+        pool.destroyed.fetch_add(1, Ordering::Relaxed);
+
+        let stats = manager.get_pool_stats("github").await.unwrap();
+        assert_eq!(stats.max_size, 2);
+        assert_eq!((stats.idle, stats.busy, stats.waiters), (0, 0, 0));
+        assert_eq!((stats.created, stats.destroyed), (3, 1));
+        assert!(manager.get_pool_stats("other").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_checkout_times_out_when_exhausted() {
+        let manager = ConnectionPoolManager::new();
+        let mut config = McpServerConfig {
+            name: "slow".to_string(),
+            ..Default::default()
         };
-        assert_eq!(stats.total_connections, 5);
-        assert_eq!(stats.healthy_connections, 4);
-        assert_eq!(stats.idle_connections, 3);
+        config.pool = ServerPoolConfig {
+            enabled: true,
+            max_size: 1,
+            checkout_timeout_ms: 20,
+            ..Default::default()
+        };
+        let pool = manager.pool("slow", PoolConfig::from(&config.pool));
+        let _held = pool.scheduler.try_acquire().unwrap();
+
+        let result = manager.acquire_connection("slow", &config, None).await;
+        assert!(matches!(result, Err(McpError::Timeout(20))));
+        assert_eq!(manager.get_pool_stats("slow").await.unwrap().waiters, 0);
     }
 }
@@ -1,7 +1,67 @@
+use crate::utils::errors::McpError;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+/// HTTP header clients send with the negotiated protocol version
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// MCP protocol revisions the proxy translates between, oldest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ProtocolVersion {
+    #[serde(rename = "2024-11-05")]
+    V2024_11_05,
+    /// Adds audio content, tool annotations and the completions capability
+    #[serde(rename = "2025-03-26")]
+    V2025_03_26,
+    /// Adds titles, tool output schemas, structured content, resource links
+    /// and elicitation
+    #[serde(rename = "2025-06-18")]
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const LATEST: Self = Self::V2025_06_18;
+
+    /// Assumed for HTTP clients that send no version header, per the spec
+    pub const HTTP_DEFAULT: Self = Self::V2025_03_26;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V2024_11_05 => "2024-11-05",
+            Self::V2025_03_26 => "2025-03-26",
+            Self::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    pub fn parse(version: &str) -> Option<Self> {
+        match version {
+            "2024-11-05" => Some(Self::V2024_11_05),
+            "2025-03-26" => Some(Self::V2025_03_26),
+            "2025-06-18" => Some(Self::V2025_06_18),
+            _ => None,
+        }
+    }
+
+    /// Version to answer an `initialize` with: the one the client asked for
+    /// when supported, otherwise the latest
+    pub fn negotiate(requested: Option<&str>) -> Self {
+        requested.and_then(Self::parse).unwrap_or(Self::LATEST)
+    }
+
+    /// Version requested in `initialize` params or answered in its result
+    pub fn of_initialize(value: Option<&Value>) -> Option<Self> {
+        value?.get("protocolVersion")?.as_str().and_then(Self::parse)
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// JSON-RPC 2.0 request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
@@ -34,6 +94,16 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+impl From<&McpError> for JsonRpcError {
+    fn from(error: &McpError) -> Self {
+        Self {
+            code: error.code().json_rpc_code(),
+            message: error.to_string(),
+            data: Some(error.data()),
+        }
+    }
+}
+
 /// Request ID can be string or number
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(untagged)]
@@ -152,6 +222,16 @@ impl JsonRpcResponse {
         }
     }
 
+    /// Error response carrying the error's taxonomy code in `error.data`
+    pub fn from_error(id: RequestId, error: &McpError) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            result: None,
+            error: Some(error.into()),
+        }
+    }
+
     pub fn error(id: RequestId, code: i32, message: impl Into<String>) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
@@ -165,3 +245,210 @@ impl JsonRpcResponse {
         }
     }
 }
+
+/// Remove fields from every object in `items` under `key`
+fn strip_list_fields(result: &mut Value, key: &str, fields: &[&str]) {
+    if let Some(Value::Array(items)) = result.get_mut(key) {
+        for item in items.iter_mut().filter_map(Value::as_object_mut) {
+            for field in fields {
+                item.remove(*field);
+            }
+        }
+    }
+}
+
+/// Rewrite a content block a `version` peer doesn't understand into one it
+/// does
+fn downgrade_content(content: &mut Value, index: usize, version: ProtocolVersion) {
+    let Some(block) = content.as_object_mut() else {
+        return;
+    };
+    match block.get("type").and_then(Value::as_str) {
+        // A link to a resource becomes its description
+        Some("resource_link") if version < ProtocolVersion::V2025_06_18 => {
+            let uri = block.get("uri").and_then(Value::as_str).unwrap_or_default();
+            let name = block.get("name").and_then(Value::as_str).unwrap_or(uri);
+            *content = json!({ "type": "text", "text": format!("{}: {}", name, uri) });
+        }
+        // Audio travels as an embedded binary resource
+        Some("audio") if version < ProtocolVersion::V2025_03_26 => {
+            let mut resource = Map::new();
+            resource.insert("uri".to_string(), json!(format!("urn:supermcp:audio:{}", index)));
+            if let Some(mime_type) = block.remove("mimeType") {
+                resource.insert("mimeType".to_string(), mime_type);
+            }
+            resource.insert("blob".to_string(), block.remove("data").unwrap_or(json!("")));
+            *content = json!({ "type": "resource", "resource": resource });
+        }
+        _ => {}
+    }
+}
+
+/// Rewrite a result for a client speaking an older `version`
+///
+/// Fields the client's revision doesn't define are dropped and newer
+/// content types are mapped onto older ones. Results already at or below
+/// `version` pass through unchanged.
+pub fn downgrade_result(method: &str, result: &mut Value, version: ProtocolVersion) {
+    if version >= ProtocolVersion::LATEST {
+        return;
+    }
+    let before_2025_06 = version < ProtocolVersion::V2025_06_18;
+    let before_2025_03 = version < ProtocolVersion::V2025_03_26;
+
+    match method {
+        "initialize" => {
+            if let (true, Some(Value::Object(capabilities))) =
+                (before_2025_03, result.get_mut("capabilities"))
+            {
+                capabilities.remove("completions");
+            }
+            if let (true, Some(Value::Object(info))) = (before_2025_06, result.get_mut("serverInfo")) {
+                info.remove("title");
+            }
+        }
+        "tools/list" => {
+            if before_2025_06 {
+                strip_list_fields(result, "tools", &["title", "outputSchema"]);
+            }
+            if before_2025_03 {
+                strip_list_fields(result, "tools", &["annotations"]);
+            }
+        }
+        "resources/list" | "resources/templates/list" | "prompts/list" if before_2025_06 => {
+            for key in ["resources", "resourceTemplates", "prompts"] {
+                strip_list_fields(result, key, &["title"]);
+            }
+        }
+        "tools/call" => {
+            let Some(object) = result.as_object_mut() else {
+                return;
+            };
+            if before_2025_06 {
+                // Tools returning structured content should also return it as
+                // text; when one didn't, the text is added here
+                if let Some(structured) = object.remove("structuredContent") {
+                    let content = object.entry("content").or_insert_with(|| json!([]));
+                    if content.as_array().is_some_and(Vec::is_empty) {
+                        *content = json!([{ "type": "text", "text": structured.to_string() }]);
+                    }
+                }
+            }
+            if let Some(Value::Array(content)) = object.get_mut("content") {
+                for (index, block) in content.iter_mut().enumerate() {
+                    downgrade_content(block, index, version);
+                }
+            }
+        }
+        "prompts/get" => {
+            if let Some(Value::Array(messages)) = result.get_mut("messages") {
+                for (index, message) in messages.iter_mut().enumerate() {
+                    if let Some(content) = message.get_mut("content") {
+                        downgrade_content(content, index, version);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrite a request for an upstream speaking an older `version`
+pub fn downgrade_request(request: &mut JsonRpcRequest, version: ProtocolVersion) {
+    if version >= ProtocolVersion::LATEST {
+        return;
+    }
+    let Some(Value::Object(params)) = request.params.as_mut() else {
+        return;
+    };
+    match request.method.as_str() {
+        "initialize" => {
+            if let Some(Value::Object(capabilities)) = params.get_mut("capabilities") {
+                capabilities.remove("elicitation");
+            }
+        }
+        "completion/complete" => {
+            params.remove("context");
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ProtocolVersion::negotiate(Some("2025-03-26")), ProtocolVersion::V2025_03_26);
+        assert_eq!(ProtocolVersion::negotiate(Some("2099-01-01")), ProtocolVersion::LATEST);
+        assert_eq!(ProtocolVersion::negotiate(None), ProtocolVersion::LATEST);
+        assert!(ProtocolVersion::V2024_11_05 < ProtocolVersion::V2025_03_26);
+    }
+
+    #[test]
+    fn test_downgrade_tool_list() {
+        let mut result = json!({
+            "tools": [{
+                "name": "search",
+                "title": "Search",
+                "inputSchema": { "type": "object" },
+                "outputSchema": { "type": "object" },
+                "annotations": { "readOnlyHint": true },
+            }]
+        });
+
+        downgrade_result("tools/list", &mut result, ProtocolVersion::V2025_03_26);
+        assert_eq!(
+            result["tools"][0],
+            json!({
+                "name": "search",
+                "inputSchema": { "type": "object" },
+                "annotations": { "readOnlyHint": true },
+            })
+        );
+
+        downgrade_result("tools/list", &mut result, ProtocolVersion::V2024_11_05);
+        assert!(result["tools"][0].get("annotations").is_none());
+    }
+
+    #[test]
+    fn test_downgrade_tool_call_content() {
+        let original = json!({
+            "content": [
+                { "type": "audio", "data": "UklGRg==", "mimeType": "audio/wav" },
+                { "type": "resource_link", "uri": "file:///report.pdf", "name": "report" },
+            ],
+            "structuredContent": { "rows": 3 },
+        });
+
+        let mut result = original.clone();
+        downgrade_result("tools/call", &mut result, ProtocolVersion::LATEST);
+        assert_eq!(result, original);
+
+        downgrade_result("tools/call", &mut result, ProtocolVersion::V2024_11_05);
+        assert!(result.get("structuredContent").is_none());
+        assert_eq!(result["content"][0]["type"], "resource");
+        assert_eq!(result["content"][0]["resource"]["blob"], "UklGRg==");
+        assert_eq!(result["content"][0]["resource"]["mimeType"], "audio/wav");
+        assert_eq!(result["content"][1], json!({ "type": "text", "text": "report: file:///report.pdf" }));
+
+        // Structured content alone still reaches older clients as text
+        let mut result = json!({ "structuredContent": { "rows": 3 } });
+        downgrade_result("tools/call", &mut result, ProtocolVersion::V2025_03_26);
+        assert_eq!(result, json!({ "content": [{ "type": "text", "text": "{\"rows\":3}" }] }));
+    }
+
+    #[test]
+    fn test_downgrade_initialize_request() {
+        let mut request = JsonRpcRequest::new(
+            "initialize",
+            Some(json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": { "elicitation": {}, "roots": {} },
+            })),
+        );
+        downgrade_request(&mut request, ProtocolVersion::V2025_03_26);
+        assert_eq!(request.params.unwrap()["capabilities"], json!({ "roots": {} }));
+    }
+}
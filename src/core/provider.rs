@@ -3,6 +3,7 @@
 //! This module provides a common interface for different types of tool providers:
 //! - MCP servers (stdio, SSE, HTTP)
 //! - Kimi CLI skills
+//! - WASM plugins (see `crate::plugins`)
 //! - Future provider types
 
 use crate::core::protocol::JsonRpcRequest;
@@ -23,6 +24,12 @@ pub enum ProviderType {
     McpHttp,
     /// Kimi CLI skill
     Skill,
+    /// WASM component plugin
+    Plugin,
+    /// CLI commands wrapped as tools
+    Command,
+    /// Read-only SQL queries
+    Sql,
     /// Custom provider
     Custom,
 }
@@ -49,6 +56,9 @@ impl std::fmt::Display for ProviderType {
             ProviderType::McpSse => write!(f, "mcp-sse"),
             ProviderType::McpHttp => write!(f, "mcp-http"),
             ProviderType::Skill => write!(f, "skill"),
+            ProviderType::Plugin => write!(f, "plugin"),
+            ProviderType::Command => write!(f, "command"),
+            ProviderType::Sql => write!(f, "sql"),
             ProviderType::Custom => write!(f, "custom"),
         }
     }
@@ -281,7 +291,7 @@ impl Provider for McpProvider {
 }
 
 /// Parse MCP tool schema into our ParameterSchema format
-fn parse_mcp_schema(tool: &serde_json::Value) -> Vec<ParameterSchema> {
+pub(crate) fn parse_mcp_schema(tool: &serde_json::Value) -> Vec<ParameterSchema> {
     let schema = tool.get("inputSchema");
     let properties = schema.and_then(|s| s.get("properties"));
     let required: Vec<String> = schema
@@ -373,15 +383,10 @@ impl ProviderRegistry {
     /// Find a tool by full name (provider.tool)
     pub async fn find_tool(&self, full_name: &str) -> McpResult<Option<(Tool, String)>> {
         // Parse provider name from full tool name
-        let parts: Vec<&str> = full_name.splitn(2, '.').collect();
-        if parts.len() != 2 {
-            return Err(crate::utils::errors::McpError::InvalidRequest(
-                format!("Invalid tool name format: {}. Use provider.tool_name", full_name)
-            ));
-        }
-
-        let provider_name = parts[0];
-        let tool_name = parts[1];
+        // A name without a provider prefix cannot match any registered tool
+        let Some((provider_name, tool_name)) = full_name.split_once('.') else {
+            return Ok(None);
+        };
 
         if let Some(provider) = self.get(provider_name) {
             let tools = provider.list_tools().await?;
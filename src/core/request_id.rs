@@ -1,11 +1,29 @@
 //! Unique request ID generation
 //!
-//! Provides thread-safe generation of unique request IDs for JSON-RPC requests.
+//! Provides thread-safe generation of unique request IDs for JSON-RPC requests,
+//! and the correlation ID of the HTTP request a task is serving. Clients may
+//! supply the correlation ID in `X-Request-Id`; it is logged with every line
+//! the request produces, sent upstream in `_meta`, and echoed in the response.
 
+use serde_json::Value;
+use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Header carrying the correlation ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// `_meta` key carrying the correlation ID upstream
+pub const CORRELATION_META_KEY: &str = "requestId";
+
+/// Longest client-provided correlation ID accepted
+const MAX_CORRELATION_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CORRELATION_ID: Arc<str>;
+}
+
 /// Request ID generator
 pub struct RequestIdGenerator {
     /// Atomic counter for sequential IDs
@@ -77,6 +95,18 @@ impl RequestIdGenerator {
         }
     }
 
+    /// Correlation ID for an incoming request: the client's own if it is
+    /// usable, otherwise a fresh one
+    pub fn correlation_id(&self, client: Option<&str>) -> String {
+        match client.map(str::trim) {
+            Some(id) if is_valid_correlation_id(id) => id.to_string(),
+            _ => match &self.prefix {
+                Some(prefix) => format!("{}-{}", prefix, Uuid::new_v4()),
+                None => Uuid::new_v4().to_string(),
+            },
+        }
+    }
+
     /// Get current counter value (for debugging)
     pub fn current_value(&self) -> u64 {
         self.counter.load(Ordering::SeqCst)
@@ -127,6 +157,11 @@ impl SharedRequestIdGenerator {
         self.inner.next_id()
     }
 
+    /// Correlation ID for an incoming request
+    pub fn correlation_id(&self, client: Option<&str>) -> String {
+        self.inner.correlation_id(client)
+    }
+
     /// Get current counter value
     pub fn current_value(&self) -> u64 {
         self.inner.current_value()
@@ -139,6 +174,44 @@ impl Default for SharedRequestIdGenerator {
     }
 }
 
+/// Client IDs are kept only if short and free of characters that could
+/// forge log lines or headers
+pub fn is_valid_correlation_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_CORRELATION_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+/// Run `f` as the request with correlation ID `id`
+pub async fn with_correlation_id<F: Future>(id: impl Into<Arc<str>>, f: F) -> F::Output {
+    CORRELATION_ID.scope(id.into(), f).await
+}
+
+/// Correlation ID of the request this task is serving, if any
+pub fn current_correlation_id() -> Option<Arc<str>> {
+    CORRELATION_ID.try_with(Arc::clone).ok()
+}
+
+/// Put the correlation ID in the request's `params._meta`, unless the
+/// client already set one there
+pub fn attach_correlation_id(request: &mut crate::core::protocol::JsonRpcRequest, id: &str) {
+    let params = request
+        .params
+        .get_or_insert_with(|| Value::Object(Default::default()));
+    let Value::Object(params) = params else {
+        return;
+    };
+    let meta = params
+        .entry("_meta")
+        .or_insert_with(|| Value::Object(Default::default()));
+    if let Value::Object(meta) = meta {
+        meta.entry(CORRELATION_META_KEY)
+            .or_insert_with(|| Value::String(id.to_string()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,4 +288,35 @@ mod tests {
         let id = generator.next_id();
         assert!(matches!(id, crate::core::protocol::RequestId::Number(1)));
     }
+
+    #[test]
+    fn test_correlation_id() {
+        let generator = RequestIdGenerator::with_prefix("node1");
+
+        assert_eq!(generator.correlation_id(Some("client-42")), "client-42");
+        assert!(generator.correlation_id(None).starts_with("node1-"));
+        // Unsafe or oversized client IDs are replaced
+        assert!(generator.correlation_id(Some("a\nb")).starts_with("node1-"));
+        assert!(generator.correlation_id(Some(&"x".repeat(200))).starts_with("node1-"));
+    }
+
+    #[test]
+    fn test_attach_correlation_id() {
+        let mut request = crate::core::protocol::JsonRpcRequest::new("tools/list", None);
+        attach_correlation_id(&mut request, "req-1");
+        assert_eq!(request.params.as_ref().unwrap()["_meta"]["requestId"], "req-1");
+
+        // The client's own value is kept
+        attach_correlation_id(&mut request, "req-2");
+        assert_eq!(request.params.as_ref().unwrap()["_meta"]["requestId"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn test_correlation_scope() {
+        assert!(current_correlation_id().is_none());
+        with_correlation_id("req-1", async {
+            assert_eq!(current_correlation_id().as_deref(), Some("req-1"));
+        })
+        .await;
+    }
 }
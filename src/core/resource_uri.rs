@@ -0,0 +1,236 @@
+//! Proxy-mediated resource URIs
+//!
+//! Upstream servers often name resources by host path (`file:///home/...`).
+//! Before results reach a client those URIs are replaced by opaque
+//! `supermcp://resource/<id>` URIs, and only such URIs are accepted back:
+//! reading one resolves it to the server that issued it, so the read is
+//! routed and access-checked by the proxy like any other request.
+
+use crate::core::protocol::JsonRpcRequest;
+use crate::utils::errors::{McpError, McpResult};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use dashmap::DashMap;
+use ring::hmac;
+use ring::rand::SystemRandom;
+use serde_json::Value;
+use tracing::debug;
+
+/// Prefix of mediated resource URIs
+pub const MEDIATED_URI_PREFIX: &str = "supermcp://resource/";
+
+/// Upstream URI schemes that are never shown to clients
+const HIDDEN_SCHEMES: &[&str] = &["file://"];
+
+/// Methods whose params name a resource by `uri`
+const URI_METHODS: &[&str] = &["resources/read", "resources/subscribe", "resources/unsubscribe"];
+
+fn is_hidden(uri: &str) -> bool {
+    HIDDEN_SCHEMES.iter().any(|scheme| uri.starts_with(scheme))
+}
+
+/// Upstream resource a mediated URI stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediatedResource {
+    pub server: String,
+    pub uri: String,
+}
+
+/// Issues mediated URIs and resolves them back
+///
+/// IDs are keyed hashes of (server, URI), so the same resource keeps the
+/// same ID for the life of the process and IDs can't be guessed.
+pub struct ResourceUriMapper {
+    key: hmac::Key,
+    resources: DashMap<String, MediatedResource>,
+}
+
+impl ResourceUriMapper {
+    pub fn new() -> McpResult<Self> {
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+            .map_err(|_| McpError::InternalError("Failed to generate resource URI key".to_string()))?;
+        Ok(Self {
+            key,
+            resources: DashMap::new(),
+        })
+    }
+
+    /// Mediated URI for an upstream URI
+    fn mediate(&self, server: &str, uri: &str) -> String {
+        let mut context = hmac::Context::with_key(&self.key);
+        context.update(server.as_bytes());
+        context.update(&[0]);
+        context.update(uri.as_bytes());
+        let id = URL_SAFE_NO_PAD.encode(&context.sign().as_ref()[..16]);
+
+        self.resources.entry(id.clone()).or_insert_with(|| MediatedResource {
+            server: server.to_string(),
+            uri: uri.to_string(),
+        });
+        format!("{}{}", MEDIATED_URI_PREFIX, id)
+    }
+
+    fn rewrite_field(&self, server: &str, object: &mut Value, field: &str) {
+        if let Some(Value::String(uri)) = object.get_mut(field) {
+            if is_hidden(uri) {
+                *uri = self.mediate(server, uri);
+            }
+        }
+    }
+
+    fn rewrite_content(&self, server: &str, block: &mut Value) {
+        match block.get("type").and_then(Value::as_str) {
+            Some("resource_link") => self.rewrite_field(server, block, "uri"),
+            Some("resource") => {
+                if let Some(resource) = block.get_mut("resource") {
+                    self.rewrite_field(server, resource, "uri");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace host URIs in a result from `server`
+    pub fn rewrite_result(&self, server: &str, method: &str, result: &mut Value) {
+        match method {
+            "resources/list" | "resources/read" => {
+                let key = if method == "resources/list" { "resources" } else { "contents" };
+                if let Some(Value::Array(items)) = result.get_mut(key) {
+                    for item in items.iter_mut() {
+                        self.rewrite_field(server, item, "uri");
+                    }
+                }
+            }
+            "resources/templates/list" => {
+                // Templates expand to host URIs that would be refused
+                if let Some(Value::Array(templates)) = result.get_mut("resourceTemplates") {
+                    templates.retain(|t| {
+                        let hidden = t.get("uriTemplate").and_then(Value::as_str).is_some_and(is_hidden);
+                        if hidden {
+                            debug!("Hiding resource template from {}", server);
+                        }
+                        !hidden
+                    });
+                }
+            }
+            "tools/call" => {
+                if let Some(Value::Array(content)) = result.get_mut("content") {
+                    for block in content.iter_mut() {
+                        self.rewrite_content(server, block);
+                    }
+                }
+            }
+            "prompts/get" => {
+                if let Some(Value::Array(messages)) = result.get_mut("messages") {
+                    for message in messages.iter_mut() {
+                        if let Some(block) = message.get_mut("content") {
+                            self.rewrite_content(server, block);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolve the resource a client request names
+    ///
+    /// A mediated URI is swapped for the upstream one and its server is
+    /// returned. Host URIs are refused, since clients only ever see
+    /// mediated ones.
+    pub fn resolve_request(&self, request: &mut JsonRpcRequest) -> McpResult<Option<String>> {
+        if !URI_METHODS.contains(&request.method.as_str()) {
+            return Ok(None);
+        }
+        let Some(Value::String(uri)) = request.params.as_mut().and_then(|p| p.get_mut("uri")) else {
+            return Ok(None);
+        };
+
+        if is_hidden(uri) {
+            return Err(McpError::AuthorizationError(
+                "Host resource URIs are not accepted; use the supermcp://resource/ URI from the listing"
+                    .to_string(),
+            ));
+        }
+        let Some(id) = uri.strip_prefix(MEDIATED_URI_PREFIX) else {
+            return Ok(None);
+        };
+        let resource = self
+            .resources
+            .get(id)
+            .map(|r| r.clone())
+            .ok_or_else(|| McpError::InvalidRequest(format!("Unknown resource: {}", uri)))?;
+
+        *uri = resource.uri;
+        Ok(Some(resource.server))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let mapper = ResourceUriMapper::new().unwrap();
+        let mut listing = json!({
+            "resources": [
+                { "uri": "file:///home/alice/notes.md", "name": "notes" },
+                { "uri": "https://example.com/a", "name": "remote" },
+            ]
+        });
+        mapper.rewrite_result("fs", "resources/list", &mut listing);
+
+        let mediated = listing["resources"][0]["uri"].as_str().unwrap().to_string();
+        assert!(mediated.starts_with(MEDIATED_URI_PREFIX));
+        assert!(!mediated.contains("alice"));
+        assert_eq!(listing["resources"][1]["uri"], "https://example.com/a");
+
+        let mut read = JsonRpcRequest::new("resources/read", Some(json!({ "uri": mediated })));
+        assert_eq!(mapper.resolve_request(&mut read).unwrap().as_deref(), Some("fs"));
+        assert_eq!(read.params.unwrap()["uri"], "file:///home/alice/notes.md");
+
+        // The same resource keeps its URI
+        let mut again = json!({ "contents": [{ "uri": "file:///home/alice/notes.md", "text": "hi" }] });
+        mapper.rewrite_result("fs", "resources/read", &mut again);
+        assert_eq!(again["contents"][0]["uri"], mediated);
+    }
+
+    #[test]
+    fn test_host_uris_refused() {
+        let mapper = ResourceUriMapper::new().unwrap();
+        let mut read = JsonRpcRequest::new("resources/read", Some(json!({ "uri": "file:///etc/passwd" })));
+        assert!(matches!(mapper.resolve_request(&mut read), Err(McpError::AuthorizationError(_))));
+
+        let mut forged = JsonRpcRequest::new(
+            "resources/read",
+            Some(json!({ "uri": format!("{}AAAA", MEDIATED_URI_PREFIX) })),
+        );
+        assert!(mapper.resolve_request(&mut forged).is_err());
+
+        let mut remote = JsonRpcRequest::new("resources/read", Some(json!({ "uri": "https://example.com/a" })));
+        assert_eq!(mapper.resolve_request(&mut remote).unwrap(), None);
+    }
+
+    #[test]
+    fn test_tool_results_and_templates() {
+        let mapper = ResourceUriMapper::new().unwrap();
+        let mut result = json!({
+            "content": [
+                { "type": "resource_link", "uri": "file:///srv/report.pdf", "name": "report" },
+                { "type": "resource", "resource": { "uri": "file:///srv/a.txt", "text": "a" } },
+            ]
+        });
+        mapper.rewrite_result("fs", "tools/call", &mut result);
+        assert!(!result.to_string().contains("file://"));
+
+        let mut templates = json!({
+            "resourceTemplates": [
+                { "uriTemplate": "file:///srv/{path}", "name": "files" },
+                { "uriTemplate": "db://{table}", "name": "tables" },
+            ]
+        });
+        mapper.rewrite_result("fs", "resources/templates/list", &mut templates);
+        assert_eq!(templates["resourceTemplates"].as_array().unwrap().len(), 1);
+    }
+}
@@ -1,9 +1,16 @@
 //! Smart request routing for MCP servers
 
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::config::{McpServerConfig, RolloutConfig};
 use crate::core::protocol::JsonRpcRequest;
 use crate::utils::errors::{McpError, McpResult};
+use parking_lot::Mutex;
+use serde::Serialize;
 use std::collections::HashMap;
-use tracing::debug;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
 
 /// Routing strategy
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -26,12 +33,194 @@ pub struct ServerRoute {
     pub load: u32,
 }
 
+/// Requests and failures of one version in the current window
+struct OutcomeWindow {
+    started: Instant,
+    requests: u64,
+    errors: u64,
+}
+
+struct VersionRoute {
+    version: String,
+    server: String,
+    weight: AtomicU32,
+    rolled_back: AtomicBool,
+    window: Mutex<OutcomeWindow>,
+}
+
+/// State of one version as reported by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionStatus {
+    pub version: String,
+    pub server: String,
+    pub weight: u32,
+    pub rolled_back: bool,
+    pub requests: u64,
+    pub errors: u64,
+}
+
+/// Traffic shared by weight between the versions of one server
+///
+/// The first version is the stable one. Any other version whose error rate
+/// in a window exceeds the rollout policy has its weight moved to the
+/// stable version until weights are set again.
+pub struct TrafficSplit {
+    name: String,
+    versions: Vec<VersionRoute>,
+    policy: RolloutConfig,
+    counter: AtomicU64,
+}
+
+impl TrafficSplit {
+    pub fn new(config: &McpServerConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            name: config.name.clone(),
+            versions: config
+                .versions
+                .iter()
+                .map(|version| VersionRoute {
+                    version: version.version.clone(),
+                    server: config.version_config(version).name,
+                    weight: AtomicU32::new(version.weight),
+                    rolled_back: AtomicBool::new(false),
+                    window: Mutex::new(OutcomeWindow { started: now, requests: 0, errors: 0 }),
+                })
+                .collect(),
+            policy: config.rollout.clone(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Server names of the versions, stable first
+    pub fn servers(&self) -> impl Iterator<Item = &str> {
+        self.versions.iter().map(|v| v.server.as_str())
+    }
+
+    pub fn stable_server(&self) -> &str {
+        &self.versions[0].server
+    }
+
+    pub fn contains(&self, server: &str) -> bool {
+        self.versions.iter().any(|v| v.server == server)
+    }
+
+    /// Version server for the next request, in proportion to the weights
+    pub fn pick(&self) -> &str {
+        let weights: Vec<u64> = self
+            .versions
+            .iter()
+            .map(|v| v.weight.load(Ordering::Relaxed) as u64)
+            .collect();
+        let total: u64 = weights.iter().sum();
+        if total == 0 {
+            return self.stable_server();
+        }
+
+        let mut slot = self.counter.fetch_add(1, Ordering::Relaxed) % total;
+        for (version, weight) in self.versions.iter().zip(weights) {
+            if slot < weight {
+                return &version.server;
+            }
+            slot -= weight;
+        }
+        self.stable_server()
+    }
+
+    /// Record the outcome of a request to a version server
+    ///
+    /// Returns whether the version was rolled back as a result.
+    pub fn record(&self, server: &str, success: bool) -> bool {
+        let Some(index) = self.versions.iter().position(|v| v.server == server) else {
+            return false;
+        };
+        let version = &self.versions[index];
+
+        let (requests, errors) = {
+            let mut window = version.window.lock();
+            if window.started.elapsed() >= Duration::from_secs(self.policy.window_seconds) {
+                *window = OutcomeWindow { started: Instant::now(), requests: 0, errors: 0 };
+            }
+            window.requests += 1;
+            if !success {
+                window.errors += 1;
+            }
+            (window.requests, window.errors)
+        };
+
+        let elevated = requests >= self.policy.min_requests
+            && errors as f64 / requests as f64 > self.policy.max_error_rate;
+        if index == 0 || !elevated || version.rolled_back.swap(true, Ordering::Relaxed) {
+            return false;
+        }
+
+        let weight = version.weight.swap(0, Ordering::Relaxed);
+        self.versions[0].weight.fetch_add(weight, Ordering::Relaxed);
+        warn!(
+            "Rolled back {}: {} of {} requests failed",
+            version.server, errors, requests
+        );
+        audit::record(
+            AuditEvent::new(AuditEventType::VersionRollback)
+                .with_server_name(&self.name)
+                .with_details(serde_json::json!({
+                    "version": version.version,
+                    "requests": requests,
+                    "errors": errors,
+                    "weight_shifted": weight,
+                })),
+        );
+        true
+    }
+
+    /// Set the weights of the named versions, clearing any rollback
+    pub fn set_weights(&self, weights: &HashMap<String, u32>) -> McpResult<()> {
+        if let Some(unknown) = weights.keys().find(|name| !self.versions.iter().any(|v| &v.version == *name)) {
+            return Err(McpError::InvalidRequest(format!(
+                "{} has no version '{}'",
+                self.name, unknown
+            )));
+        }
+        for version in &self.versions {
+            if let Some(weight) = weights.get(&version.version) {
+                version.weight.store(*weight, Ordering::Relaxed);
+                version.rolled_back.store(false, Ordering::Relaxed);
+                *version.window.lock() = OutcomeWindow { started: Instant::now(), requests: 0, errors: 0 };
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> Vec<VersionStatus> {
+        self.versions
+            .iter()
+            .map(|version| {
+                let window = version.window.lock();
+                VersionStatus {
+                    version: version.version.clone(),
+                    server: version.server.clone(),
+                    weight: version.weight.load(Ordering::Relaxed),
+                    rolled_back: version.rolled_back.load(Ordering::Relaxed),
+                    requests: window.requests,
+                    errors: window.errors,
+                }
+            })
+            .collect()
+    }
+}
+
 /// Request router
 pub struct RequestRouter {
     strategy: RoutingStrategy,
     routes: HashMap<String, ServerRoute>,
     round_robin_counter: std::sync::atomic::AtomicUsize,
     method_prefixes: HashMap<String, Vec<String>>,
+    /// Versioned servers, routed to one of their versions by weight
+    splits: HashMap<String, Arc<TrafficSplit>>,
 }
 
 impl RequestRouter {
@@ -41,6 +230,24 @@ impl RequestRouter {
             routes: HashMap::new(),
             round_robin_counter: std::sync::atomic::AtomicUsize::new(0),
             method_prefixes: HashMap::new(),
+            splits: HashMap::new(),
+        }
+    }
+
+    /// Share a registered server's traffic between its versions
+    pub fn register_split(&mut self, split: Arc<TrafficSplit>) {
+        self.splits.insert(split.name().to_string(), split);
+    }
+
+    /// The version server a routed name stands for
+    fn resolve(&self, name: String) -> String {
+        match self.splits.get(&name) {
+            Some(split) => {
+                let server = split.pick().to_string();
+                debug!("Routed {} to version {}", name, server);
+                server
+            }
+            None => name,
         }
     }
 
@@ -71,7 +278,7 @@ impl RequestRouter {
     }
 
     pub fn route(&self, request: &JsonRpcRequest) -> McpResult<String> {
-        match self.strategy {
+        let name = match self.strategy {
             RoutingStrategy::FirstAvailable => self.route_first_available(),
             RoutingStrategy::MethodPrefix => self.route_by_method_prefix(request),
             RoutingStrategy::Capability => self.route_by_capability(request),
@@ -79,7 +286,8 @@ impl RequestRouter {
             RoutingStrategy::Direct => Err(McpError::InvalidRequest(
                 "Direct routing requires explicit server name".to_string()
             )),
-        }
+        }?;
+        Ok(self.resolve(name))
     }
 
     fn route_first_available(&self) -> McpResult<String> {
@@ -168,7 +376,7 @@ impl RequestRouter {
     pub fn route_to_server(&self, server_name: &str) -> McpResult<String> {
         if let Some(route) = self.routes.get(server_name) {
             if route.healthy {
-                Ok(server_name.to_string())
+                Ok(self.resolve(server_name.to_string()))
             } else {
                 Err(McpError::ServerNotFound(format!(
                     "Server '{}' is not healthy",
@@ -249,6 +457,65 @@ mod tests {
         assert_eq!(result.unwrap(), "tools-server");
     }
 
+    fn versioned(weights: &[u32]) -> McpServerConfig {
+        McpServerConfig {
+            name: "filesystem".to_string(),
+            versions: weights
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| crate::config::ServerVersionConfig {
+                    version: format!("v{}", i + 1),
+                    weight: *weight,
+                    ..Default::default()
+                })
+                .collect(),
+            rollout: RolloutConfig {
+                max_error_rate: 0.5,
+                min_requests: 4,
+                window_seconds: 60,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_traffic_split_by_weight() {
+        let split = Arc::new(TrafficSplit::new(&versioned(&[75, 25])));
+        let mut router = RequestRouter::new(RoutingStrategy::Capability);
+        router.register_server("filesystem", vec!["tools".to_string()]);
+        router.register_split(split);
+
+        let request = JsonRpcRequest::new("tools/list", None);
+        let picks: Vec<_> = (0..100).map(|_| router.route(&request).unwrap()).collect();
+        assert_eq!(picks.iter().filter(|s| *s == "filesystem@v1").count(), 75);
+        assert_eq!(picks.iter().filter(|s| *s == "filesystem@v2").count(), 25);
+    }
+
+    #[test]
+    fn test_canary_rolled_back_on_errors() {
+        let split = TrafficSplit::new(&versioned(&[90, 10]));
+
+        // The stable version is never rolled back
+        for _ in 0..10 {
+            assert!(!split.record("filesystem@v1", false));
+        }
+        assert!(!split.record("filesystem@v2", false));
+        assert!(!split.record("filesystem@v2", true));
+        assert!(!split.record("filesystem@v2", false));
+        assert!(split.record("filesystem@v2", false));
+        assert!(!split.record("filesystem@v2", false));
+
+        let status = split.status();
+        assert_eq!((status[0].weight, status[1].weight), (100, 0));
+        assert!(status[1].rolled_back);
+        assert!((0..20).all(|_| split.pick() == "filesystem@v1"));
+
+        let weights = HashMap::from([("v2".to_string(), 50)]);
+        split.set_weights(&weights).unwrap();
+        assert!(!split.status()[1].rolled_back);
+        assert!(split.set_weights(&HashMap::from([("v3".to_string(), 1)])).is_err());
+    }
+
     #[test]
     fn test_route_to_unhealthy_server_fails() {
         let mut router = RequestRouter::new(RoutingStrategy::FirstAvailable);
@@ -0,0 +1,306 @@
+//! Weighted fair scheduling of pooled upstream requests
+//!
+//! Each request runs under a priority class (see `[scheduling]`). Process
+//! pools admit requests through a [`FairScheduler`]: while a slot is free a
+//! request takes it at once; under congestion, waiting requests are admitted
+//! by stride scheduling, so a class of weight 8 gets eight slots for every
+//! one taken by a class of weight 1, and no class starves.
+
+use crate::config::SchedulingConfig;
+use crate::utils::errors::{McpError, McpResult};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+tokio::task_local! {
+    static PRIORITY: Arc<str>;
+}
+
+/// Run `f` as a request of priority class `class`
+pub async fn with_priority<F: Future>(class: impl Into<Arc<str>>, f: F) -> F::Output {
+    PRIORITY.scope(class.into(), f).await
+}
+
+/// Priority class of the request this task is serving, if any
+pub fn current_priority() -> Option<Arc<str>> {
+    PRIORITY.try_with(Arc::clone).ok()
+}
+
+/// Pass advanced per admission is `STRIDE / weight`
+const STRIDE: u64 = 1 << 20;
+
+#[derive(Debug, Clone)]
+struct PriorityClass {
+    name: String,
+    weight: u32,
+    scopes: Vec<String>,
+}
+
+/// Configured priority classes
+#[derive(Debug, Clone)]
+pub struct PriorityClasses {
+    classes: Vec<PriorityClass>,
+    default: usize,
+}
+
+impl Default for PriorityClasses {
+    /// One class, admitting in arrival order
+    fn default() -> Self {
+        Self {
+            classes: vec![PriorityClass {
+                name: "default".to_string(),
+                weight: 1,
+                scopes: Vec::new(),
+            }],
+            default: 0,
+        }
+    }
+}
+
+impl PriorityClasses {
+    pub fn from_config(config: &SchedulingConfig) -> Self {
+        let classes: Vec<PriorityClass> = config
+            .classes
+            .iter()
+            .map(|class| PriorityClass {
+                name: class.name.clone(),
+                weight: class.weight.max(1),
+                scopes: class.scopes.clone(),
+            })
+            .collect();
+        if classes.is_empty() {
+            return Self::default();
+        }
+        let default = classes
+            .iter()
+            .position(|class| class.name == config.default_class)
+            .unwrap_or(0);
+        Self { classes, default }
+    }
+
+    fn index(&self, name: Option<&str>) -> usize {
+        name.and_then(|name| self.classes.iter().position(|class| class.name == name))
+            .unwrap_or(self.default)
+    }
+
+    /// Class of a request: the first class granted by the caller's scopes,
+    /// or the default; `requested` may lower it to a class of no greater
+    /// weight
+    pub fn classify(&self, requested: Option<&str>, scopes: &[String]) -> &str {
+        let granted = self
+            .classes
+            .iter()
+            .position(|class| class.scopes.iter().any(|scope| scopes.contains(scope)))
+            .unwrap_or(self.default);
+        let class = match requested.and_then(|name| self.classes.iter().position(|c| c.name == name)) {
+            Some(requested) if self.classes[requested].weight <= self.classes[granted].weight => {
+                requested
+            }
+            _ => granted,
+        };
+        &self.classes[class].name
+    }
+}
+
+struct State {
+    /// Free slots; only nonzero while nobody is queued
+    available: usize,
+    queues: Vec<VecDeque<oneshot::Sender<SchedulerPermit>>>,
+    /// Stride scheduling pass of each class
+    pass: Vec<u64>,
+    /// Pass of the last admitted request
+    virtual_time: u64,
+    closed: bool,
+}
+
+/// Admits requests to a fixed number of slots, fairly between classes
+pub struct FairScheduler {
+    classes: PriorityClasses,
+    state: Mutex<State>,
+}
+
+/// A slot, given back when dropped
+pub struct SchedulerPermit {
+    scheduler: Option<Arc<FairScheduler>>,
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.release();
+        }
+    }
+}
+
+impl FairScheduler {
+    pub fn new(capacity: usize, classes: PriorityClasses) -> Arc<Self> {
+        let count = classes.classes.len();
+        Arc::new(Self {
+            classes,
+            state: Mutex::new(State {
+                available: capacity,
+                queues: (0..count).map(|_| VecDeque::new()).collect(),
+                pass: vec![0; count],
+                virtual_time: 0,
+                closed: false,
+            }),
+        })
+    }
+
+    fn permit(self: &Arc<Self>) -> SchedulerPermit {
+        SchedulerPermit {
+            scheduler: Some(self.clone()),
+        }
+    }
+
+    /// A slot if one is free right now
+    pub fn try_acquire(self: &Arc<Self>) -> Option<SchedulerPermit> {
+        let mut state = self.state.lock();
+        if state.closed || state.available == 0 {
+            return None;
+        }
+        state.available -= 1;
+        Some(self.permit())
+    }
+
+    /// Wait for a slot as a request of class `class` (unknown classes use
+    /// the default)
+    pub async fn acquire(self: &Arc<Self>, class: Option<&str>) -> McpResult<SchedulerPermit> {
+        let index = self.classes.index(class);
+        let rx = {
+            let mut state = self.state.lock();
+            if state.closed {
+                return Err(McpError::TransportError("Scheduler is closed".to_string()));
+            }
+            if state.available > 0 {
+                state.available -= 1;
+                return Ok(self.permit());
+            }
+            // A class returning from idle doesn't get credit for the idle time
+            if state.queues[index].is_empty() {
+                state.pass[index] = state.pass[index].max(state.virtual_time);
+            }
+            let (tx, rx) = oneshot::channel();
+            state.queues[index].push_back(tx);
+            rx
+        };
+        rx.await
+            .map_err(|_| McpError::TransportError("Scheduler is closed".to_string()))
+    }
+
+    /// Hand a freed slot to the next waiter, or mark it free
+    fn release(self: &Arc<Self>) {
+        loop {
+            let tx = {
+                let mut state = self.state.lock();
+                let next = (0..state.queues.len())
+                    .filter(|&i| !state.queues[i].is_empty())
+                    .min_by_key(|&i| (state.pass[i], i));
+                let Some(class) = next else {
+                    state.available += 1;
+                    return;
+                };
+                state.virtual_time = state.pass[class];
+                state.pass[class] += STRIDE / u64::from(self.classes.classes[class].weight);
+                match state.queues[class].pop_front() {
+                    Some(tx) => tx,
+                    None => continue,
+                }
+            };
+            // A waiter that gave up leaves the slot for the next one
+            match tx.send(self.permit()) {
+                Ok(()) => return,
+                Err(mut permit) => permit.scheduler = None,
+            }
+        }
+    }
+
+    /// Fail every waiter and refuse new ones
+    pub fn close(&self) {
+        let mut state = self.state.lock();
+        state.closed = true;
+        for queue in &mut state.queues {
+            queue.clear();
+        }
+    }
+
+    /// Requests waiting per class, for classes with any
+    pub fn waiting(&self) -> Vec<(String, usize)> {
+        let state = self.state.lock();
+        state
+            .queues
+            .iter()
+            .enumerate()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(i, queue)| (self.classes.classes[i].name.clone(), queue.len()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes() -> PriorityClasses {
+        PriorityClasses::from_config(&SchedulingConfig::default())
+    }
+
+    #[test]
+    fn test_classify() {
+        let classes = classes();
+        assert_eq!(classes.classify(None, &[]), "interactive");
+        assert_eq!(classes.classify(None, &["batch".to_string()]), "batch");
+        // Clients may lower their priority but not raise it
+        assert_eq!(classes.classify(Some("batch"), &[]), "batch");
+        assert_eq!(classes.classify(Some("interactive"), &["batch".to_string()]), "batch");
+        assert_eq!(classes.classify(Some("unknown"), &[]), "interactive");
+    }
+
+    #[tokio::test]
+    async fn test_weighted_admission() {
+        let scheduler = FairScheduler::new(1, classes());
+        let held = scheduler.try_acquire().unwrap();
+        assert!(scheduler.try_acquire().is_none());
+
+        // Queue 16 batch and 16 interactive requests behind the held slot
+        let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel();
+        for class in ["batch", "interactive"] {
+            for _ in 0..16 {
+                let scheduler = scheduler.clone();
+                let done_tx = done_tx.clone();
+                tokio::spawn(async move {
+                    let _permit = scheduler.acquire(Some(class)).await.unwrap();
+                    done_tx.send(class).unwrap();
+                });
+            }
+        }
+        while scheduler.waiting().iter().map(|(_, n)| n).sum::<usize>() < 32 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(held);
+        let mut order = Vec::new();
+        for _ in 0..9 {
+            order.push(done_rx.recv().await.unwrap());
+        }
+        let batch = order.iter().filter(|&&class| class == "batch").count();
+        assert_eq!(batch, 1, "admitted {:?}", order);
+    }
+
+    #[tokio::test]
+    async fn test_close_fails_waiters() {
+        let scheduler = FairScheduler::new(1, PriorityClasses::default());
+        let _held = scheduler.try_acquire().unwrap();
+        let waiter = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.acquire(None).await.is_err() }
+        });
+        while scheduler.waiting().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        scheduler.close();
+        assert!(waiter.await.unwrap());
+    }
+}
@@ -0,0 +1,252 @@
+//! Pinned tool schemas
+//!
+//! `supermcp capabilities lock` records every tool's definition and a hash
+//! of its input schema. For servers with `pinned_schemas = true`, the proxy
+//! lists only tools whose live schema still hashes to the pinned value,
+//! serving the recorded definition, and refuses calls to any other tool.
+//! Live hashes are taken from the server's latest `tools/list`.
+
+use crate::cli::capabilities::{canonical, list_all, CapabilitySnapshot};
+use crate::cli::expand_path;
+use crate::config::Config;
+use crate::core::protocol::JsonRpcResponse;
+use crate::core::server::ManagedServer;
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Lockfile format version
+pub const LOCK_VERSION: u32 = 1;
+
+/// A tool as recorded in the lockfile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedTool {
+    /// `sha256:` and the hex digest of the canonical input schema
+    pub hash: String,
+    /// Definition served in place of the live one
+    pub tool: Value,
+}
+
+/// Pinned tools of each server, by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaLock {
+    pub version: u32,
+    pub servers: BTreeMap<String, BTreeMap<String, PinnedTool>>,
+}
+
+/// Hash of a tool's input schema, independent of key order
+pub fn schema_hash(tool: &Value) -> String {
+    let schema = canonical(tool.get("inputSchema").unwrap_or(&Value::Null));
+    let digest = ring::digest::digest(&ring::digest::SHA256, schema.to_string().as_bytes());
+    let mut hash = String::from("sha256:");
+    for byte in digest.as_ref() {
+        let _ = write!(hash, "{:02x}", byte);
+    }
+    hash
+}
+
+impl SchemaLock {
+    /// Pin every tool of the servers listed in a snapshot
+    pub fn from_snapshot(snapshot: &CapabilitySnapshot) -> Self {
+        let servers = snapshot
+            .servers
+            .iter()
+            .filter(|(_, server)| server.error.is_none())
+            .map(|(name, server)| {
+                let tools = server
+                    .tools
+                    .iter()
+                    .map(|(tool_name, tool)| {
+                        let pinned = PinnedTool {
+                            hash: schema_hash(tool),
+                            tool: tool.clone(),
+                        };
+                        (tool_name.clone(), pinned)
+                    })
+                    .collect();
+                (name.clone(), tools)
+            })
+            .collect();
+        Self {
+            version: LOCK_VERSION,
+            servers,
+        }
+    }
+
+    pub async fn load(path: &str) -> McpResult<Self> {
+        let path = expand_path(path);
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| McpError::ConfigError(format!("Failed to read schema lockfile {}: {}", path, e)))?;
+        let lock: Self = serde_json::from_str(&content)?;
+        if lock.version != LOCK_VERSION {
+            return Err(McpError::ConfigError(format!(
+                "Unsupported schema lockfile version {} in {}",
+                lock.version, path
+            )));
+        }
+        Ok(lock)
+    }
+}
+
+/// Enforces the lockfile for servers with `pinned_schemas`
+pub struct SchemaPins {
+    lock: SchemaLock,
+    /// Schema hash of each tool in a server's latest `tools/list`
+    live: DashMap<String, HashMap<String, String>>,
+}
+
+impl SchemaPins {
+    pub fn new(lock: SchemaLock) -> Self {
+        Self {
+            lock,
+            live: DashMap::new(),
+        }
+    }
+
+    /// Pins from `schema_lock.path`, when any server has `pinned_schemas`
+    pub async fn from_config(config: &Config) -> McpResult<Option<Arc<Self>>> {
+        if !config.servers.iter().any(|server| server.pinned_schemas) {
+            return Ok(None);
+        }
+        let lock = SchemaLock::load(&config.schema_lock.path).await?;
+        info!(
+            "Pinned tool schemas of {} servers from {}",
+            lock.servers.len(),
+            config.schema_lock.path
+        );
+        Ok(Some(Arc::new(Self::new(lock))))
+    }
+
+    fn record_live<'a>(&self, server: &str, tools: impl IntoIterator<Item = &'a Value>) {
+        let hashes = tools
+            .into_iter()
+            .filter_map(|tool| {
+                let name = tool.get("name")?.as_str()?;
+                Some((name.to_string(), schema_hash(tool)))
+            })
+            .collect();
+        self.live.insert(server.to_string(), hashes);
+    }
+
+    /// Replace a `tools/list` response with the pinned definitions of the
+    /// tools whose live schema still matches
+    pub fn apply_tool_list(&self, server: &str, response: &mut JsonRpcResponse) {
+        let Some(tools) = response
+            .result
+            .as_mut()
+            .and_then(|result| result.get_mut("tools"))
+            .and_then(Value::as_array_mut)
+        else {
+            return;
+        };
+        self.record_live(server, tools.iter());
+
+        let pinned = self.lock.servers.get(server);
+        let mut served = Vec::with_capacity(tools.len());
+        for tool in tools.iter() {
+            let name = tool.get("name").and_then(Value::as_str).unwrap_or_default();
+            match pinned.and_then(|pinned| pinned.get(name)) {
+                Some(pin) if pin.hash == schema_hash(tool) => served.push(pin.tool.clone()),
+                Some(_) => warn!("Hiding {} of {}: schema differs from the pinned one", name, server),
+                None => warn!("Hiding {} of {}: not in the schema lockfile", name, server),
+            }
+        }
+        *tools = served;
+    }
+
+    /// Pinned definition of a tool
+    pub fn pinned_tool(&self, server: &str, tool: &str) -> Option<&Value> {
+        self.lock.servers.get(server)?.get(tool).map(|pin| &pin.tool)
+    }
+
+    /// Refuse a call unless the tool is pinned and its live schema matches,
+    /// listing the server's tools first if they haven't been seen yet
+    pub async fn check_call(&self, server_name: &str, server: &ManagedServer, tool: &str) -> McpResult<()> {
+        if !self.live.contains_key(server_name) {
+            let tools = list_all(server, "tools/list", "tools", "name").await?;
+            self.record_live(server_name, tools.values());
+        }
+        self.check_live(server_name, tool)
+    }
+
+    fn check_live(&self, server: &str, tool: &str) -> McpResult<()> {
+        let Some(pin) = self.lock.servers.get(server).and_then(|tools| tools.get(tool)) else {
+            return Err(McpError::AuthorizationError(format!(
+                "Tool {} of {} is not in the schema lockfile",
+                tool, server
+            )));
+        };
+        let live = self.live.get(server).and_then(|hashes| hashes.get(tool).cloned());
+        match live {
+            Some(hash) if hash == pin.hash => Ok(()),
+            Some(hash) => Err(McpError::AuthorizationError(format!(
+                "Schema of tool {} of {} changed upstream (pinned {}, live {}); review it and \
+                 update the lockfile with `supermcp capabilities lock`",
+                tool, server, pin.hash, hash
+            ))),
+            None => Err(McpError::AuthorizationError(format!(
+                "Tool {} of {} is pinned but no longer offered upstream",
+                tool, server
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::protocol::RequestId;
+    use serde_json::json;
+
+    fn lock(tools: &[Value]) -> SchemaLock {
+        let pinned = tools
+            .iter()
+            .map(|tool| {
+                let name = tool["name"].as_str().unwrap().to_string();
+                (name, PinnedTool { hash: schema_hash(tool), tool: tool.clone() })
+            })
+            .collect();
+        SchemaLock {
+            version: LOCK_VERSION,
+            servers: BTreeMap::from([("fs".to_string(), pinned)]),
+        }
+    }
+
+    #[test]
+    fn test_schema_hash_ignores_key_order() {
+        let a = json!({ "name": "read", "inputSchema": { "type": "object", "required": ["path"] } });
+        let b = json!({ "inputSchema": { "required": ["path"], "type": "object" }, "name": "read" });
+        assert_eq!(schema_hash(&a), schema_hash(&b));
+        let c = json!({ "name": "read", "inputSchema": { "type": "object" } });
+        assert_ne!(schema_hash(&a), schema_hash(&c));
+    }
+
+    #[test]
+    fn test_serves_and_allows_only_matching_tools() {
+        let read = json!({ "name": "read", "description": "Read a file", "inputSchema": { "type": "object" } });
+        let write = json!({ "name": "write", "inputSchema": { "type": "object" } });
+        let pins = SchemaPins::new(lock(&[read.clone(), write]));
+
+        let live_read = json!({ "name": "read", "description": "Read anything", "inputSchema": { "type": "object" } });
+        let live_write = json!({ "name": "write", "inputSchema": { "type": "object", "properties": { "sudo": {} } } });
+        let live_delete = json!({ "name": "delete", "inputSchema": {} });
+        let mut response = JsonRpcResponse::success(
+            RequestId::Number(1),
+            json!({ "tools": [live_read, live_write, live_delete] }),
+        );
+        pins.apply_tool_list("fs", &mut response);
+
+        // The pinned definition is served, not the live description
+        assert_eq!(response.result.unwrap()["tools"], json!([read]));
+        assert!(pins.check_live("fs", "read").is_ok());
+        assert!(pins.check_live("fs", "write").is_err());
+        assert!(pins.check_live("fs", "delete").is_err());
+        assert!(pins.check_live("web", "fetch").is_err());
+    }
+}
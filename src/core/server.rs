@@ -1,12 +1,40 @@
-use crate::config::McpServerConfig;
-use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::auth::delegation;
+use crate::cli::capabilities::list_all;
+use crate::config::{
+    ChaosConfig, McpServerConfig, RemoteTransport, SchedulingConfig, ServerAffinity, ServerType,
+    SloConfig,
+};
+use crate::core::protocol::{downgrade_request, JsonRpcRequest, JsonRpcResponse, ProtocolVersion};
+use crate::core::chaos::ChaosInjector;
+use crate::core::drift::DriftMonitor;
+use crate::core::dry_run;
+use crate::core::hedge::HedgePolicy;
+use crate::core::command::resolve_server_command;
+use crate::core::mirror::ShadowMirror;
+use crate::core::pool::{ConnectionPoolManager, PoolStats, PooledConnection};
+use crate::core::routing::TrafficSplit;
+use crate::core::schema_lock::SchemaPins;
+use crate::core::scheduler::{current_priority, PriorityClasses};
+use crate::core::slo::{SloReport, SloTracker};
+use crate::core::supervisor::{spawn_supervisor, SupervisorSnapshot, SupervisorState};
+use crate::core::workers::{WorkerSet, WorkerStatus};
+use crate::events::{self, Event, EventKind};
+use crate::sandbox::disk_quota::{DiskQuota, DiskQuotaReport};
+use crate::sandbox::syscall_audit::{self, SyscallReport};
 use crate::sandbox::{create_sandbox, Sandbox};
-use crate::transport::{Transport, StdioTransport, SseTransport, StreamableHttpTransport};
+use crate::transport::{
+    http_client, nats, NamedPipeTransport, SseTransport, StdioTransport, StreamableHttpTransport, SuperMcpTransport,
+    Transport, TransportResponse,
+};
 use crate::utils::errors::{McpError, McpResult};
+use crate::core::request_id::{attach_correlation_id, current_correlation_id};
+use crate::utils::request_trace;
 use dashmap::DashMap;
+use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use tokio::sync::{OnceCell, RwLock};
+use tracing::{debug, error, info, warn};
 
 /// Transport type for MCP servers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -18,6 +46,12 @@ pub enum TransportType {
     Sse,
     /// Streamable HTTP transport
     StreamableHttp,
+    /// Windows named pipe transport
+    NamedPipe,
+    /// Another super-mcp instance's HTTP API
+    SuperMcp,
+    /// NATS request/reply
+    Nats,
 }
 
 impl std::str::FromStr for TransportType {
@@ -28,6 +62,9 @@ impl std::str::FromStr for TransportType {
             "stdio" => Ok(TransportType::Stdio),
             "sse" => Ok(TransportType::Sse),
             "streamable" | "streamable-http" | "streamable_http" => Ok(TransportType::StreamableHttp),
+            "pipe" | "named-pipe" | "named_pipe" => Ok(TransportType::NamedPipe),
+            "supermcp" | "super-mcp" => Ok(TransportType::SuperMcp),
+            "nats" => Ok(TransportType::Nats),
             _ => Err(McpError::ConfigError(format!("Unknown transport type: {}", s))),
         }
     }
@@ -41,6 +78,12 @@ pub struct ServerStatus {
     pub transport_type: TransportType,
     pub tags: Vec<String>,
     pub command: String,
+    /// Automatic restarts after unexpected exits
+    pub restarts: u64,
+    /// Restart budget exhausted; the server stays down until re-added
+    pub degraded: bool,
+    /// Disk usage under the sandbox's writable paths, when limited
+    pub disk: Option<DiskQuotaReport>,
 }
 
 /// Managed MCP server instance
@@ -50,11 +93,32 @@ pub struct ManagedServer {
     transport: Arc<RwLock<Box<dyn Transport>>>,
     _sandbox: Arc<dyn Sandbox>,
     transport_type: TransportType,
+    /// URL or pipe path for non-stdio transports, kept for restarts
+    endpoint: Option<String>,
+    supervisor: Arc<SupervisorState>,
+    disk_quota: Option<Arc<DiskQuota>>,
 }
 
 impl ManagedServer {
-    /// Create a new managed server with stdio transport (default)
+    /// Create a new managed server with stdio transport (default), the
+    /// named pipe transport when the config names a pipe, a remote
+    /// transport when it names a URL, or federation with another super-mcp
     pub async fn new(config: McpServerConfig) -> McpResult<Self> {
+        if let Some(pipe) = config.named_pipe.clone() {
+            return Self::with_transport(config, TransportType::NamedPipe, Some(pipe)).await;
+        }
+        if config.server_type == ServerType::Supermcp {
+            let url = config.url.clone();
+            return Self::with_transport(config, TransportType::SuperMcp, url).await;
+        }
+        if let Some(url) = config.url.clone() {
+            let transport_type = match config.transport.resolve(&url) {
+                RemoteTransport::Sse => TransportType::Sse,
+                RemoteTransport::Nats => TransportType::Nats,
+                _ => TransportType::StreamableHttp,
+            };
+            return Self::with_transport(config, transport_type, Some(url)).await;
+        }
         Self::with_transport(config, TransportType::Stdio, None).await
     }
 
@@ -64,21 +128,32 @@ impl ManagedServer {
         transport_type: TransportType,
         endpoint: Option<String>,
     ) -> McpResult<Self> {
+        let transport_endpoint = endpoint.clone();
+
+        // Pick a launcher and verify tool versions before spawning
+        let config = if transport_type == TransportType::Stdio {
+            resolve_server_command(&config).await?
+        } else {
+            config
+        };
+
         let sandbox = create_sandbox(&config);
         let sandbox_arc: Arc<dyn Sandbox> = Arc::from(sandbox);
 
         // Command and args are already set in config (auto-detect handles package runners)
         let command = config.command.clone();
         let args = config.args.clone();
+        let proxy = config.proxy.clone().unwrap_or_else(http_client::global_proxy);
 
         let transport: Box<dyn Transport> = match transport_type {
             TransportType::Stdio => {
                 Box::new(
-                    StdioTransport::new(
+                    StdioTransport::with_config(
                         command,
                         args,
-                        config.env.clone(),
+                        delegation::server_env(&config).await?,
                         sandbox_arc.clone(),
+                        &config.stdio,
                     )
                     .await?,
                 )
@@ -87,21 +162,86 @@ impl ManagedServer {
                 let endpoint = endpoint.ok_or_else(|| {
                     McpError::ConfigError("SSE transport requires an endpoint URL".to_string())
                 })?;
-                Box::new(SseTransport::new(endpoint).await?)
+                Box::new(SseTransport::with_tls(endpoint, &config.headers, &config.tls, &proxy).await?)
             }
             TransportType::StreamableHttp => {
                 let endpoint = endpoint.ok_or_else(|| {
                     McpError::ConfigError("Streamable HTTP transport requires an endpoint URL".to_string())
                 })?;
-                Box::new(StreamableHttpTransport::new(endpoint).await?)
+                Box::new(
+                    StreamableHttpTransport::with_tls(
+                        endpoint,
+                        &config.http,
+                        &config.tls,
+                        &proxy,
+                        &config.headers,
+                        &config.initialize,
+                    )
+                    .await?,
+                )
+            }
+            TransportType::NamedPipe => {
+                let endpoint = endpoint.ok_or_else(|| {
+                    McpError::ConfigError("Named pipe transport requires a pipe path".to_string())
+                })?;
+                Box::new(NamedPipeTransport::connect(&endpoint).await?)
+            }
+            TransportType::SuperMcp => {
+                let endpoint = endpoint.ok_or_else(|| {
+                    McpError::ConfigError("Federated super-mcp servers require a url".to_string())
+                })?;
+                Box::new(
+                    SuperMcpTransport::connect(
+                        endpoint,
+                        &config.http,
+                        &config.headers,
+                        config.peer_public_key.as_deref(),
+                    )
+                    .await?,
+                )
+            }
+            TransportType::Nats => {
+                let endpoint = endpoint.ok_or_else(|| {
+                    McpError::ConfigError("NATS transport requires a nats:// url".to_string())
+                })?;
+                nats::connect(
+                    &endpoint,
+                    &config.name,
+                    std::time::Duration::from_secs(config.http.request_timeout_secs),
+                    &config.headers,
+                )
+                .await?
             }
         };
 
+        let transport = Arc::new(RwLock::new(transport));
+        let supervisor = Arc::new(SupervisorState::new());
+
+        let disk_quota = match transport_type {
+            TransportType::Stdio => DiskQuota::from_config(&config),
+            _ => None,
+        };
+        if let Some(quota) = &disk_quota {
+            quota.start().await;
+        }
+
+        if transport_type == TransportType::Stdio && config.supervision.enabled {
+            spawn_supervisor(
+                config.clone(),
+                sandbox_arc.clone(),
+                transport.clone(),
+                supervisor.clone(),
+            );
+        }
+
         Ok(Self {
             config,
-            transport: Arc::new(RwLock::new(transport)),
+            transport,
             _sandbox: sandbox_arc,
             transport_type,
+            endpoint: transport_endpoint,
+            supervisor,
+            disk_quota,
         })
     }
 
@@ -110,30 +250,133 @@ impl ManagedServer {
         transport.send_request(request).await
     }
 
+    pub async fn send_notification(&self, request: JsonRpcRequest) -> McpResult<()> {
+        let transport = self.transport.read().await;
+        transport.send_notification(request).await
+    }
+
+    /// Send a request, streaming upstream bodies larger than `max_in_memory_bytes`
+    pub async fn send_request_streaming(
+        &self,
+        request: JsonRpcRequest,
+        max_in_memory_bytes: usize,
+    ) -> McpResult<TransportResponse> {
+        let transport = self.transport.read().await;
+        transport
+            .send_request_streaming(request, max_in_memory_bytes)
+            .await
+    }
+
     pub async fn is_connected(&self) -> bool {
         self.transport.read().await.is_connected().await
     }
 
     pub async fn stop(&self) -> McpResult<()> {
-        let transport = self.transport.read().await;
-        transport.close().await
+        self.supervisor.stop();
+        let result = self.transport.read().await.close().await;
+        if let Some(quota) = &self.disk_quota {
+            quota.stop().await;
+        }
+        result
     }
 
     /// Get the transport type used by this server
     pub fn transport_type(&self) -> TransportType {
         self.transport_type
     }
+
+    /// Restart bookkeeping for supervised servers
+    pub fn supervisor(&self) -> &SupervisorState {
+        &self.supervisor
+    }
+
+    /// Whether the process is restarted when it exits
+    pub fn is_supervised(&self) -> bool {
+        self.transport_type == TransportType::Stdio && self.config.supervision.enabled
+    }
+
+    /// Disk limits of the sandbox's writable paths, if any
+    pub fn disk_quota(&self) -> Option<&DiskQuota> {
+        self.disk_quota.as_deref()
+    }
 }
 
+/// Refuse tool calls to a server over its disk quota
+fn check_disk_quota(server: &ManagedServer, request: &JsonRpcRequest) -> McpResult<()> {
+    match (server.disk_quota(), called_tool(request)) {
+        (Some(quota), Some(_)) => quota.check(),
+        _ => Ok(()),
+    }
+}
+
+/// Tool named by a `tools/call` request
+fn called_tool(request: &JsonRpcRequest) -> Option<&str> {
+    if request.method != "tools/call" {
+        return None;
+    }
+    Some(
+        request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or_default(),
+    )
+}
+
+/// Downstream session a request belongs to
+pub struct SessionRoute<'a> {
+    pub id: &'a str,
+    /// Replayed as `initialize` to a newly spawned dedicated process
+    pub initialize_params: Value,
+}
+
+/// Dedicated process for one session, spawned on first use
+type SessionServer = Arc<OnceCell<ManagedServer>>;
+
 /// Manages multiple MCP servers
 pub struct ServerManager {
     servers: DashMap<String, ManagedServer>,
+    /// Processes of `affinity = "session"` servers, keyed by (session, server)
+    session_servers: DashMap<(String, String), SessionServer>,
+    /// Protocol version each server answered `initialize` with
+    protocol_versions: DashMap<String, ProtocolVersion>,
+    /// Servers with several versions, keyed by the server's own name; the
+    /// versions themselves are in `servers` as `<name>@<version>`
+    splits: DashMap<String, Arc<TrafficSplit>>,
+    /// Mirroring state of servers with a shadow, created on first use
+    mirrors: DashMap<String, Arc<ShadowMirror>>,
+    /// Fault injection, when `[chaos]` is enabled
+    chaos: Option<Arc<ChaosInjector>>,
+    /// Tool call objectives, when `[[slos]]` are configured
+    slos: Option<Arc<SloTracker>>,
+    /// Process pools of stdio servers with `[servers.pool]` enabled
+    pools: Arc<ConnectionPoolManager>,
+    /// Latency history of servers with hedging, created on first use
+    hedges: DashMap<String, Arc<HedgePolicy>>,
+    /// Pre-forked processes of stdio servers with `workers` > 1
+    workers: DashMap<String, Arc<WorkerSet>>,
+    /// Approved tool catalogs, when `[drift]` is enabled
+    drift: Option<Arc<DriftMonitor>>,
+    /// Lockfile of servers with `pinned_schemas`
+    schema_pins: Option<Arc<SchemaPins>>,
 }
 
 impl Clone for ServerManager {
     fn clone(&self) -> Self {
         Self {
             servers: self.servers.clone(),
+            session_servers: self.session_servers.clone(),
+            protocol_versions: self.protocol_versions.clone(),
+            splits: self.splits.clone(),
+            mirrors: self.mirrors.clone(),
+            chaos: self.chaos.clone(),
+            slos: self.slos.clone(),
+            pools: self.pools.clone(),
+            hedges: self.hedges.clone(),
+            workers: self.workers.clone(),
+            drift: self.drift.clone(),
+            schema_pins: self.schema_pins.clone(),
         }
     }
 }
@@ -142,14 +385,289 @@ impl ServerManager {
     pub fn new() -> Self {
         Self {
             servers: DashMap::new(),
+            session_servers: DashMap::new(),
+            protocol_versions: DashMap::new(),
+            splits: DashMap::new(),
+            mirrors: DashMap::new(),
+            chaos: None,
+            slos: None,
+            pools: Arc::new(ConnectionPoolManager::new()),
+            hedges: DashMap::new(),
+            workers: DashMap::new(),
+            drift: None,
+            schema_pins: None,
+        }
+    }
+
+    /// Inject faults into upstream requests as configured by `[chaos]`
+    pub fn with_chaos(mut self, config: &ChaosConfig) -> Self {
+        self.chaos = ChaosInjector::from_config(config).map(Arc::new);
+        self
+    }
+
+    /// Track tool call latency and errors against `[[slos]]`
+    pub fn with_slos(mut self, slos: &[SloConfig]) -> Self {
+        self.slos = SloTracker::from_config(slos).map(Arc::new);
+        self
+    }
+
+    /// Admit requests waiting on exhausted pools by `[scheduling]` class
+    pub fn with_scheduling(mut self, config: &SchedulingConfig) -> Self {
+        if config.enabled {
+            self.pools = Arc::new(ConnectionPoolManager::with_classes(PriorityClasses::from_config(
+                config,
+            )));
         }
+        self
+    }
+
+    /// Hold back tools outside the approved catalogs of `[drift]`
+    pub fn with_drift(mut self, drift: Option<Arc<DriftMonitor>>) -> Self {
+        self.drift = drift;
+        self
+    }
+
+    /// Enforce the schema lockfile for servers with `pinned_schemas`
+    pub fn with_schema_pins(mut self, pins: Option<Arc<SchemaPins>>) -> Self {
+        self.schema_pins = pins;
+        self
+    }
+
+    /// Drift detection state, when `[drift]` is enabled
+    pub fn drift(&self) -> Option<&Arc<DriftMonitor>> {
+        self.drift.as_ref()
+    }
+
+    /// Refuse calls to tools quarantined by drift detection
+    fn check_quarantine(&self, server_name: &str, request: &JsonRpcRequest) -> McpResult<()> {
+        let Some(drift) = &self.drift else {
+            return Ok(());
+        };
+        let Some(tool) = called_tool(request) else {
+            return Ok(());
+        };
+        if drift.is_quarantined(server_name, tool) {
+            return Err(McpError::AuthorizationError(format!(
+                "Tool {} of {} is quarantined until its catalog is approved",
+                tool, server_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse calls to pinned servers' tools whose schema changed
+    async fn check_pinned_schema(
+        &self,
+        requested: &str,
+        server: &ManagedServer,
+        request: &JsonRpcRequest,
+    ) -> McpResult<()> {
+        match (&self.schema_pins, called_tool(request)) {
+            (Some(pins), Some(tool)) if server.config.pinned_schemas => {
+                pins.check_call(requested, server, tool).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Dry-run report standing in for a `tools/call`'s response
+    ///
+    /// The tool's schema comes from the lockfile for pinned servers, else
+    /// from listing the server's tools; the tool itself is never called.
+    async fn dry_run(
+        &self,
+        requested: &str,
+        server_name: &str,
+        server: &ManagedServer,
+        request: &JsonRpcRequest,
+    ) -> McpResult<Option<JsonRpcResponse>> {
+        let Some(tool) = called_tool(request).filter(|_| dry_run::is_dry_run()) else {
+            return Ok(None);
+        };
+        let pinned = self
+            .schema_pins
+            .as_ref()
+            .filter(|_| server.config.pinned_schemas)
+            .and_then(|pins| pins.pinned_tool(requested, tool).cloned());
+        let definition = match pinned {
+            Some(definition) => Some(definition),
+            None => list_all(server, "tools/list", "tools", "name").await?.remove(tool),
+        };
+        let schema = definition.map(|d| d.get("inputSchema").cloned().unwrap_or_else(|| json!({})));
+        let arguments = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("arguments"))
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let report = dry_run::report(server_name, tool, &arguments, schema.as_ref());
+        Ok(Some(dry_run::response(request, report)))
+    }
+
+    /// Compliance of each configured SLO
+    pub fn slo_report(&self) -> Vec<SloReport> {
+        self.slos.as_ref().map(|slos| slos.report()).unwrap_or_default()
+    }
+
+    /// Restart counters of every supervised server
+    pub fn supervision_report(&self) -> Vec<SupervisorSnapshot> {
+        let mut report: Vec<_> = self
+            .servers
+            .iter()
+            .filter(|entry| entry.is_supervised())
+            .map(|entry| entry.supervisor().snapshot(entry.key()))
+            .collect();
+        report.sort_by(|a, b| a.server.cmp(&b.server));
+        report
+    }
+
+    /// Live statistics of every server's process pool
+    pub async fn pool_stats(&self) -> Vec<PoolStats> {
+        self.pools.all_pool_stats().await
+    }
+
+    /// Health and load of a server's workers, if it has any
+    pub async fn worker_status(&self, name: &str) -> Option<Vec<WorkerStatus>> {
+        let workers = self.workers.get(name).map(|set| set.clone())?;
+        Some(workers.status().await)
+    }
+
+    /// Syscalls a server in seccomp audit mode was seen attempting
+    pub fn syscall_report(&self, name: &str) -> McpResult<SyscallReport> {
+        let server = self
+            .get_server(name)
+            .ok_or_else(|| McpError::ServerNotFound(name.to_string()))?;
+        Ok(syscall_audit::global().report(&server.config))
+    }
+
+    /// Start the workers of a stdio server with `workers` > 1; without
+    /// them the server's own process takes every request
+    async fn start_workers(&self, server: &ManagedServer) {
+        if server.config.workers <= 1 || server.transport_type() != TransportType::Stdio {
+            return;
+        }
+        match WorkerSet::start(server.config.clone()).await {
+            Ok(workers) => {
+                self.workers.insert(server.config.name.clone(), workers);
+            }
+            Err(e) => warn!("{}, serving from its own process", e),
+        }
+    }
+
+    async fn stop_workers(&self, name: &str) {
+        if let Some((_, workers)) = self.workers.remove(name) {
+            workers.shutdown().await;
+        }
+    }
+
+    /// A process from the server's workers or pool, for stdio servers
+    /// that have them
+    ///
+    /// `initialize` goes to the shared process, whose answer the client
+    /// sees; workers and pooled processes are initialized as they are
+    /// spawned.
+    async fn checkout_pooled(
+        &self,
+        server_name: &str,
+        server: &ManagedServer,
+        request: &JsonRpcRequest,
+    ) -> McpResult<Option<PooledConnection>> {
+        if server.transport_type() != TransportType::Stdio || request.method == "initialize" {
+            return Ok(None);
+        }
+        let workers = self.workers.get(server_name).map(|set| set.clone());
+        if let Some(workers) = workers {
+            return match workers.pick().await {
+                Ok(conn) => Ok(Some(conn)),
+                Err(e) => {
+                    warn!("{}, falling back to its own process", e);
+                    Ok(None)
+                }
+            };
+        }
+        if !server.config.pool.enabled {
+            return Ok(None);
+        }
+        self.pools
+            .acquire_connection(server_name, &server.config, current_priority().as_deref())
+            .await
+            .map(Some)
+    }
+
+    /// Send on a pooled process; slow read-only requests to servers with
+    /// hedging are sent to a second process too, and the loser cancelled
+    async fn send_pooled(
+        &self,
+        server_name: &str,
+        config: &McpServerConfig,
+        conn: &PooledConnection,
+        request: JsonRpcRequest,
+    ) -> McpResult<JsonRpcResponse> {
+        let hedge = config
+            .hedge
+            .enabled
+            .then(|| {
+                self.hedges
+                    .entry(server_name.to_string())
+                    .or_insert_with(|| Arc::new(HedgePolicy::new(config.hedge.clone())))
+                    .clone()
+            })
+            .and_then(|policy| Some((policy.key(&request)?, policy)));
+        let (Some((key, policy)), Some(request_id)) = (hedge, request.id.clone()) else {
+            return conn.send_request(request).await;
+        };
+
+        let started = std::time::Instant::now();
+        let primary = conn.send_request(request.clone());
+        tokio::pin!(primary);
+        if let Some(delay) = policy.delay(&key) {
+            tokio::select! {
+                result = &mut primary => {
+                    policy.record(&key, started.elapsed());
+                    return result;
+                }
+                _ = tokio::time::sleep(delay) => {}
+            }
+            if let Some(second) = self.pools.try_acquire_connection(server_name, config).await {
+                debug!("Hedging {} to {} after {:?}", key, second.id, delay);
+                let (result, loser) = {
+                    let hedged = second.send_request(request);
+                    tokio::pin!(hedged);
+                    tokio::select! {
+                        result = &mut primary => (result, &second),
+                        result = &mut hedged => (result, conn),
+                    }
+                };
+                policy.record(&key, started.elapsed());
+                loser.cancel(&request_id, "answered by a hedged request").await;
+                return result;
+            }
+        }
+        let result = primary.await;
+        policy.record(&key, started.elapsed());
+        result
+    }
+
+    /// Injected outcome of a request, if chaos replaces it
+    async fn inject_chaos(
+        &self,
+        server_name: &str,
+        request: &JsonRpcRequest,
+    ) -> Option<McpResult<JsonRpcResponse>> {
+        let chaos = self.chaos.as_ref()?;
+        chaos.inject(server_name, request).await.transpose()
     }
 
     pub async fn add_server(&self, config: McpServerConfig) -> McpResult<()> {
         let name = config.name.clone();
         info!("Adding server: {}", name);
 
+        if !config.versions.is_empty() {
+            return self.add_versioned_server(config).await;
+        }
+
         let server = ManagedServer::new(config).await?;
+        self.start_workers(&server).await;
         self.servers.insert(name, server);
 
         Ok(())
@@ -171,10 +689,111 @@ impl ServerManager {
         Ok(())
     }
 
+    /// Start every version of a server and split its traffic between them
+    async fn add_versioned_server(&self, config: McpServerConfig) -> McpResult<()> {
+        let split = Arc::new(TrafficSplit::new(&config));
+        for version in &config.versions {
+            let version_config = config.version_config(version);
+            info!("Adding {} with weight {}", version_config.name, version.weight);
+            let server = ManagedServer::new(version_config.clone()).await?;
+            self.start_workers(&server).await;
+            self.servers.insert(version_config.name, server);
+        }
+        self.splits.insert(config.name, split);
+        Ok(())
+    }
+
+    /// Traffic split of a server with several versions
+    pub fn traffic_split(&self, name: &str) -> Option<Arc<TrafficSplit>> {
+        self.splits.get(name).map(|split| split.clone())
+    }
+
+    pub fn traffic_splits(&self) -> Vec<Arc<TrafficSplit>> {
+        self.splits.iter().map(|split| split.clone()).collect()
+    }
+
+    /// Split a version server belongs to
+    fn split_of(&self, server: &str) -> Option<Arc<TrafficSplit>> {
+        self.splits
+            .iter()
+            .find(|split| split.contains(server))
+            .map(|split| split.clone())
+    }
+
+    /// Server to send a request to: one of the versions, by weight, for a
+    /// versioned server
+    fn pick_server(&self, name: &str) -> String {
+        match self.splits.get(name) {
+            Some(split) => split.pick().to_string(),
+            None => name.to_string(),
+        }
+    }
+
+    /// A copy of the request for the server's shadow, if this one is
+    /// sampled for mirroring
+    fn shadow_copy(
+        &self,
+        server: &ManagedServer,
+        request: &JsonRpcRequest,
+    ) -> Option<(Arc<ShadowMirror>, ManagedServer, JsonRpcRequest)> {
+        let config = server.config.shadow.as_ref()?;
+        if request.method != "tools/call" {
+            return None;
+        }
+        let mirror = self
+            .mirrors
+            .entry(server.config.name.clone())
+            .or_insert_with(|| Arc::new(ShadowMirror::new(config.clone())))
+            .clone();
+        if !mirror.sample() {
+            return None;
+        }
+        let Some(shadow) = self.get_server(mirror.shadow_server()).map(|s| s.clone()) else {
+            debug!("Shadow {} of {} is not running", config.server, server.config.name);
+            return None;
+        };
+
+        let mut copy = request.clone();
+        if let Some(version) = self.protocol_version(&shadow.config.name) {
+            downgrade_request(&mut copy, version);
+        }
+        Some((mirror, shadow, copy))
+    }
+
+    /// Mirroring state of a server with a shadow
+    pub fn shadow_mirror(&self, name: &str) -> Option<Arc<ShadowMirror>> {
+        self.mirrors.get(name).map(|mirror| mirror.clone())
+    }
+
+    fn record_outcome(&self, server: &str, success: bool) {
+        if let Some(split) = self.split_of(server) {
+            split.record(server, success);
+        }
+    }
+
     pub async fn remove_server(&self, name: &str) -> McpResult<()> {
         info!("Removing server: {}", name);
+        self.mirrors.remove(name);
+
+        if let Some((_, split)) = self.splits.remove(name) {
+            for server in split.servers() {
+                if let Some((_, server)) = self.servers.remove(server) {
+                    server.stop().await?;
+                }
+                delegation::revoke(server);
+                self.protocol_versions.remove(server);
+                self.pools.remove_pool(server).await;
+                self.stop_workers(server).await;
+            }
+            return Ok(());
+        }
 
         if let Some((_, server)) = self.servers.remove(name) {
+            self.protocol_versions.remove(name);
+            self.pools.remove_pool(name).await;
+            self.stop_workers(name).await;
+            self.hedges.remove(name);
+            delegation::revoke(name);
             server.stop().await?;
         } else {
             return Err(McpError::ServerNotFound(name.to_string()));
@@ -183,8 +802,113 @@ impl ServerManager {
         Ok(())
     }
 
+    /// Stop a server and start it again on the same transport
+    pub async fn restart_server(&self, name: &str) -> McpResult<()> {
+        if let Some(split) = self.traffic_split(name) {
+            for server in split.servers() {
+                Box::pin(self.restart_server(server)).await?;
+            }
+            return Ok(());
+        }
+        let config = self
+            .servers
+            .get(name)
+            .map(|server| server.config.clone())
+            .ok_or_else(|| McpError::ServerNotFound(name.to_string()))?;
+        self.update_server(config).await
+    }
+
+    /// Replace a server's config and restart it on the same transport
+    ///
+    /// The old process is stopped first; if the new one fails to start the
+    /// server stays registered, stopped, under its old config.
+    pub async fn update_server(&self, config: McpServerConfig) -> McpResult<()> {
+        let name = config.name.clone();
+        // Versions may have been added or dropped, so start over
+        if !config.versions.is_empty() || self.splits.contains_key(&name) {
+            if self.splits.contains_key(&name) || self.servers.contains_key(&name) {
+                self.remove_server(&name).await?;
+            }
+            return self.add_server(config).await;
+        }
+        let (transport_type, endpoint) = {
+            let server = self
+                .servers
+                .get(&name)
+                .ok_or_else(|| McpError::ServerNotFound(name.clone()))?;
+            (server.transport_type, server.endpoint.clone())
+        };
+        info!("Restarting server: {}", name);
+
+        let old = self.servers.get(&name).map(|server| server.clone());
+        if let Some(old) = old {
+            if let Err(e) = old.stop().await {
+                debug!("Stopping {} before restart: {}", name, e);
+            }
+        }
+
+        // Pooled processes and workers run the old config too
+        self.pools.remove_pool(&name).await;
+        self.stop_workers(&name).await;
+        self.hedges.remove(&name);
+
+        let server = ManagedServer::with_transport(config, transport_type, endpoint).await?;
+        self.start_workers(&server).await;
+        self.mirrors.remove(&name);
+        self.servers.insert(name, server);
+        Ok(())
+    }
+
+    /// Bring registered servers from `before` to `after`
+    ///
+    /// Removes servers that are gone or disabled, adds new ones and
+    /// restarts changed ones, stopping at the first failure; unchanged
+    /// servers are left alone. Running it again with the lists swapped
+    /// undoes a partial run.
+    pub async fn apply_servers(&self, before: &[McpServerConfig], after: &[McpServerConfig]) -> McpResult<()> {
+        let before: Vec<&McpServerConfig> = before.iter().filter(|s| s.enabled).collect();
+        let after: Vec<&McpServerConfig> = after.iter().filter(|s| s.enabled).collect();
+        let registered = |name: &str| self.servers.contains_key(name) || self.splits.contains_key(name);
+        for old in before.iter().filter(|old| !after.iter().any(|new| new.name == old.name)) {
+            if registered(&old.name) {
+                self.remove_server(&old.name).await?;
+            }
+        }
+        for &new in &after {
+            let unchanged = before.iter().find(|old| old.name == new.name).is_some_and(|old| {
+                serde_json::to_value(old).ok() == serde_json::to_value(new).ok()
+            });
+            if unchanged {
+                continue;
+            }
+            if registered(&new.name) {
+                self.update_server(new.clone()).await?;
+            } else {
+                self.add_server(new.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A server by name; for a versioned server, its stable version
     pub fn get_server(&self, name: &str) -> Option<dashmap::mapref::one::Ref<'_, String, ManagedServer>> {
-        self.servers.get(name)
+        match self.splits.get(name) {
+            Some(split) => self.servers.get(split.stable_server()),
+            None => self.servers.get(name),
+        }
+    }
+
+    /// Protocol version a server negotiated, once it has been initialized
+    pub fn protocol_version(&self, name: &str) -> Option<ProtocolVersion> {
+        self.protocol_versions.get(name).map(|v| *v)
+    }
+
+    fn record_protocol_version(&self, name: &str, response: &JsonRpcResponse) {
+        if let Some(version) = ProtocolVersion::of_initialize(response.result.as_ref()) {
+            if self.protocol_versions.insert(name.to_string(), version) != Some(version) {
+                debug!("{} speaks MCP {}", name, version);
+            }
+        }
     }
 
     pub async fn send_request(
@@ -192,35 +916,274 @@ impl ServerManager {
         server_name: &str,
         request: JsonRpcRequest,
     ) -> McpResult<JsonRpcResponse> {
+        self.send_session_request(server_name, None, request).await
+    }
+
+    /// Send a request on behalf of a downstream session
+    ///
+    /// Servers with `affinity = "session"` answer from the session's own
+    /// process; everything else, and sessionless requests, use the shared one.
+    pub async fn send_session_request(
+        &self,
+        server_name: &str,
+        session: Option<&SessionRoute<'_>>,
+        request: JsonRpcRequest,
+    ) -> McpResult<JsonRpcResponse> {
+        match self.dispatch(server_name, session, request, None).await? {
+            TransportResponse::Buffered(response) => Ok(response),
+            TransportResponse::Streamed { .. } => Err(McpError::InternalError(
+                "Unexpected streamed response".to_string(),
+            )),
+        }
+    }
+
+    /// Send a request to a server, allowing the response body to be streamed
+    pub async fn send_request_streaming(
+        &self,
+        server_name: &str,
+        request: JsonRpcRequest,
+        max_in_memory_bytes: usize,
+    ) -> McpResult<TransportResponse> {
+        self.dispatch(server_name, None, request, Some(max_in_memory_bytes)).await
+    }
+
+    /// Everything around sending a request upstream, shared by buffered and
+    /// streamed requests: quarantine, pinning, dry runs, process selection,
+    /// shadowing, response filtering, outcomes, SLOs and tool call events
+    ///
+    /// Without `max_in_memory_bytes` the response is always buffered.
+    async fn dispatch(
+        &self,
+        server_name: &str,
+        session: Option<&SessionRoute<'_>>,
+        mut request: JsonRpcRequest,
+        max_in_memory_bytes: Option<usize>,
+    ) -> McpResult<TransportResponse> {
+        let requested = server_name;
+        let checkout = request_trace::phase("pool_checkout");
+        let server_name = &self.pick_server(server_name);
         let server = self
             .servers
             .get(server_name)
-            .ok_or_else(|| McpError::ServerNotFound(server_name.to_string()))?;
+            .ok_or_else(|| McpError::ServerNotFound(server_name.to_string()))?
+            .clone();
+        self.check_quarantine(server_name, &request)?;
+        check_disk_quota(&server, &request)?;
+        self.check_pinned_schema(requested, &server, &request).await?;
+        if let Some(report) = self.dry_run(requested, server_name, &server, &request).await? {
+            return Ok(TransportResponse::Buffered(report));
+        }
+        let pinned = server.config.pinned_schemas;
+
+        let (server, pooled) = match session {
+            Some(session)
+                if server.config.affinity == ServerAffinity::Session
+                    && server.transport_type() == TransportType::Stdio
+                    && request.method != "initialize" =>
+            {
+                (self.session_server(&server.config, session).await?, None)
+            }
+            _ => {
+                let pooled = self.checkout_pooled(server_name, &server, &request).await?;
+                (server, pooled)
+            }
+        };
+        drop(checkout);
+
+        let tool = (request.method == "tools/call"
+            && (self.slos.is_some() || events::is_subscribed(EventKind::ToolCall)))
+        .then(|| {
+            request
+                .params
+                .as_ref()
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .unwrap_or_default()
+                .to_string()
+        });
+        let started = std::time::Instant::now();
+        let shadow = self.shadow_copy(&server, &request);
+
+        if let Some(version) = self.protocol_version(server_name) {
+            downgrade_request(&mut request, version);
+        }
+        if let Some(id) = current_correlation_id() {
+            attach_correlation_id(&mut request, &id);
+        }
+        let is_initialize = request.method == "initialize";
+        if is_initialize {
+            server.config.initialize.apply(request.params.get_or_insert_with(|| json!({})));
+        }
+        let replay = (is_initialize && pooled.is_none())
+            .then(|| request.params.clone().unwrap_or_else(|| json!({})));
+        let is_tool_list = request.method == "tools/list";
+        // Tool lists are buffered so quarantined or unpinned tools can be
+        // filtered out
+        let max_in_memory_bytes =
+            max_in_memory_bytes.filter(|_| !(is_tool_list && (self.drift.is_some() || pinned)));
+        let upstream = request_trace::phase("upstream");
+        let mut result = match (self.inject_chaos(requested, &request).await, &pooled) {
+            (Some(injected), _) => injected.map(TransportResponse::Buffered),
+            (None, Some(conn)) => self
+                .send_pooled(server_name, &server.config, conn, request)
+                .await
+                .map(TransportResponse::Buffered),
+            (None, None) => match max_in_memory_bytes {
+                Some(max) => server.send_request_streaming(request, max).await,
+                None => server.send_request(request).await.map(TransportResponse::Buffered),
+            },
+        };
+        drop(upstream);
+        drop(pooled);
+        if let Ok(TransportResponse::Buffered(response)) = &mut result {
+            if is_initialize {
+                self.record_protocol_version(server_name, response);
+                if let (Some(params), None) = (replay, &response.error) {
+                    server.supervisor().record_initialize(params);
+                }
+            }
+            if let (true, Some(pins)) = (is_tool_list && pinned, &self.schema_pins) {
+                pins.apply_tool_list(requested, response);
+            }
+            if let (true, Some(drift)) = (is_tool_list, &self.drift) {
+                drift.filter_tool_list(server_name, response);
+            }
+        }
+        let success = match &result {
+            Ok(TransportResponse::Buffered(response)) => response.error.is_none(),
+            Ok(TransportResponse::Streamed { .. }) => true,
+            Err(_) => false,
+        };
+        self.record_outcome(server_name, success);
+        if let Some((mirror, shadow, copy)) = shadow {
+            // A streamed response is passed on unread, so there is nothing to compare
+            let primary = match &result {
+                Ok(TransportResponse::Buffered(response)) => Some(response.clone()),
+                _ => None,
+            };
+            mirror.mirror(server_name.clone(), shadow, copy, primary);
+        }
 
-        server.send_request(request).await
+        if let Some(tool) = tool {
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if let Some(slos) = &self.slos {
+                slos.record(requested, &tool, duration_ms, success);
+            }
+            if events::is_subscribed(EventKind::ToolCall) {
+                events::emit(
+                    Event::new(EventKind::ToolCall, if success { "succeeded" } else { "failed" })
+                        .with_server(server_name)
+                        .with_tool(tool)
+                        .with_details(serde_json::json!({
+                            "success": success,
+                            "duration_ms": duration_ms,
+                        })),
+                );
+            }
+        }
+        result
     }
 
-    pub fn list_servers(&self) -> Vec<String> {
-        self.servers.iter().map(|entry| entry.key().clone()).collect()
+    /// The session's dedicated process for a server, spawning and
+    /// initializing it on first use
+    async fn session_server(
+        &self,
+        config: &McpServerConfig,
+        session: &SessionRoute<'_>,
+    ) -> McpResult<ManagedServer> {
+        let cell = self
+            .session_servers
+            .entry((session.id.to_string(), config.name.clone()))
+            .or_default()
+            .clone();
+
+        let server = cell
+            .get_or_try_init(|| async {
+                info!("Starting {} for session {}", config.name, session.id);
+                let server = ManagedServer::new(config.clone()).await?;
+
+                let mut params = session.initialize_params.clone();
+                config.initialize.apply(&mut params);
+                let initialize = JsonRpcRequest::new("initialize", Some(params));
+                let response = server.send_request(initialize).await?;
+                self.record_protocol_version(&config.name, &response);
+                if let Some(error) = response.error {
+                    let _ = server.stop().await;
+                    return Err(McpError::TransportError(format!(
+                        "{} failed to initialize for session {}: {}",
+                        config.name, session.id, error.message
+                    )));
+                }
+                server
+                    .send_notification(JsonRpcRequest::new("notifications/initialized", None))
+                    .await?;
+                Ok(server)
+            })
+            .await?;
+
+        Ok(server.clone())
     }
 
-    pub async fn get_servers_by_tags(&self, tags: &[String]) -> Vec<String> {
+    /// Stop every dedicated process belonging to a session
+    pub async fn end_session(&self, session_id: &str) {
+        let keys: Vec<_> = self
+            .session_servers
+            .iter()
+            .filter(|entry| entry.key().0 == session_id)
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for key in keys {
+            if let Some((_, cell)) = self.session_servers.remove(&key) {
+                if let Some(server) = cell.get() {
+                    debug!("Stopping {} for ended session {}", key.1, key.0);
+                    if let Err(e) = server.stop().await {
+                        error!("Failed to stop {} for session {}: {}", key.1, key.0, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// End every session with dedicated processes that isn't in `live`
+    pub async fn retain_sessions(&self, live: &HashSet<String>) {
+        let ended: HashSet<String> = self
+            .session_servers
+            .iter()
+            .map(|entry| entry.key().0.clone())
+            .filter(|id| !live.contains(id))
+            .collect();
+
+        for id in ended {
+            self.end_session(&id).await;
+        }
+    }
+
+    /// Names of all servers; a versioned server is listed once, by its
+    /// own name
+    pub fn list_servers(&self) -> Vec<String> {
         self.servers
             .iter()
-            .filter(|entry| {
-                let server_tags: std::collections::HashSet<_> =
-                    entry.config.tags.iter().cloned().collect();
-                tags.iter().any(|tag| server_tags.contains(tag))
-            })
             .map(|entry| entry.key().clone())
+            .filter(|name| self.split_of(name).is_none())
+            .chain(self.splits.iter().map(|split| split.key().clone()))
+            .collect()
+    }
+
+    pub async fn get_servers_by_tags(&self, tags: &[String]) -> Vec<String> {
+        self.list_servers()
+            .into_iter()
+            .filter(|name| {
+                self.get_server(name)
+                    .is_some_and(|server| tags.iter().any(|tag| server.config.tags.contains(tag)))
+            })
             .collect()
     }
 
     /// Get server status information
     pub async fn get_server_status(&self, name: &str) -> McpResult<ServerStatus> {
         let server = self
-            .servers
-            .get(name)
+            .get_server(name)
             .ok_or_else(|| McpError::ServerNotFound(name.to_string()))?;
 
         Ok(ServerStatus {
@@ -229,6 +1192,9 @@ impl ServerManager {
             transport_type: server.transport_type(),
             tags: server.config.tags.clone(),
             command: format!("{} {}", server.config.command, server.config.args.join(" ")),
+            restarts: server.supervisor().restarts(),
+            degraded: server.supervisor().is_degraded(),
+            disk: server.disk_quota().map(DiskQuota::report),
         })
     }
 
@@ -243,6 +1209,9 @@ impl ServerManager {
                 transport_type: entry.transport_type(),
                 tags: entry.config.tags.clone(),
                 command: format!("{} {}", entry.config.command, entry.config.args.join(" ")),
+                restarts: entry.supervisor().restarts(),
+                degraded: entry.supervisor().is_degraded(),
+                disk: entry.disk_quota().map(DiskQuota::report),
             };
             statuses.push(status);
         }
@@ -251,12 +1220,19 @@ impl ServerManager {
     }
 
     pub async fn stop_all(&self) {
+        self.retain_sessions(&HashSet::new()).await;
         for entry in self.servers.iter() {
             if let Err(e) = entry.stop().await {
                 error!("Failed to stop server {}: {}", entry.key(), e);
             }
         }
+        self.pools.shutdown().await;
+        for entry in self.workers.iter() {
+            entry.shutdown().await;
+        }
+        self.workers.clear();
         self.servers.clear();
+        self.splits.clear();
     }
 }
 
@@ -271,6 +1247,149 @@ mod tests {
     use super::*;
     use std::str::FromStr;
 
+    /// Answers every request with an empty result
+    const ECHO_SERVER: &str = r#"
+import json, sys
+for line in sys.stdin:
+    message = json.loads(line)
+    if "id" in message:
+        print(json.dumps({"jsonrpc": "2.0", "id": message["id"], "result": {}}), flush=True)
+"#;
+
+    fn echo_server(name: &str) -> McpServerConfig {
+        let mut config = McpServerConfig {
+            name: name.to_string(),
+            command: "python3".to_string(),
+            args: vec!["-c".to_string(), ECHO_SERVER.to_string()],
+            ..Default::default()
+        };
+        config.sandbox.enabled = false;
+        config.supervision.enabled = false;
+        config
+    }
+
+    fn tool_call() -> JsonRpcRequest {
+        JsonRpcRequest::new("tools/call", Some(json!({ "name": "echo", "arguments": {} })))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streamed_requests_are_mirrored() {
+        let manager = ServerManager::new();
+        manager.add_server(echo_server("shadow")).await.unwrap();
+        let mut primary = echo_server("primary");
+        primary.shadow = Some(crate::config::ShadowConfig {
+            server: "shadow".to_string(),
+            ..Default::default()
+        });
+        manager.add_server(primary).await.unwrap();
+
+        manager
+            .send_request_streaming("primary", tool_call(), 1024 * 1024)
+            .await
+            .unwrap();
+        let mirror = manager.shadow_mirror("primary").unwrap();
+        for _ in 0..50 {
+            if mirror.stats().0 == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(mirror.stats(), (1, 0));
+        manager.stop_all().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streamed_calls_count_against_slos() {
+        let manager = ServerManager::new().with_slos(&[crate::config::SloConfig {
+            server: "tracked".to_string(),
+            error_rate: Some(0.01),
+            ..Default::default()
+        }]);
+        manager.add_server(echo_server("tracked")).await.unwrap();
+
+        manager
+            .send_request_streaming("tracked", tool_call(), 1024 * 1024)
+            .await
+            .unwrap();
+        manager.send_request("tracked", tool_call()).await.unwrap();
+        assert_eq!(manager.slo_report()[0].calls, 2);
+        manager.stop_all().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streamed_calls_emit_tool_call_events() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<serde_json::Value>();
+        let app = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let _ = tx.send(body);
+                async {}
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let notifier = events::Notifier::from_config(&crate::config::NotificationsConfig {
+            enabled: true,
+            webhooks: vec![crate::config::WebhookConfig {
+                name: "calls".to_string(),
+                url,
+                events: vec!["tool_call".to_string()],
+                ..Default::default()
+            }],
+        })
+        .unwrap();
+        assert!(events::install_global(notifier));
+
+        let manager = ServerManager::new();
+        manager.add_server(echo_server("notified")).await.unwrap();
+        manager
+            .send_request_streaming("notified", tool_call(), 1024 * 1024)
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                let event = rx.recv().await.unwrap();
+                if event["server"] == "notified" {
+                    return event;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!((event["event"].as_str(), event["tool"].as_str()), (Some("tool_call"), Some("echo")));
+        manager.stop_all().await;
+    }
+
+    #[test]
+    fn test_initialize_overrides() {
+        let overrides = crate::config::InitializeOverrides {
+            client_name: Some("claude-ai".to_string()),
+            protocol_version: Some("2024-11-05".to_string()),
+            capabilities: Some(serde_json::json!({ "roots": {} })),
+            ..Default::default()
+        };
+        let mut params = serde_json::json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": { "sampling": {} },
+            "clientInfo": { "name": "cursor", "version": "1.0" },
+        });
+        overrides.apply(&mut params);
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "roots": {} },
+                "clientInfo": { "name": "claude-ai", "version": "1.0" },
+            })
+        );
+    }
+
     #[test]
     fn test_transport_type_from_str() {
         assert_eq!(
@@ -285,6 +1404,18 @@ mod tests {
             TransportType::from_str("streamable-http").unwrap(),
             TransportType::StreamableHttp
         );
+        assert_eq!(
+            TransportType::from_str("named-pipe").unwrap(),
+            TransportType::NamedPipe
+        );
+        assert_eq!(
+            TransportType::from_str("supermcp").unwrap(),
+            TransportType::SuperMcp
+        );
+        assert_eq!(
+            TransportType::from_str("nats").unwrap(),
+            TransportType::Nats
+        );
         assert!(TransportType::from_str("unknown").is_err());
     }
 
@@ -296,6 +1427,9 @@ mod tests {
             transport_type: TransportType::Stdio,
             tags: vec!["test".to_string()],
             command: "echo hello".to_string(),
+            restarts: 0,
+            degraded: false,
+            disk: None,
         };
 
         assert_eq!(status.name, "test");
@@ -0,0 +1,295 @@
+//! Service level objectives for tool calls
+//!
+//! Each `[[slos]]` entry counts matching calls in time buckets covering its
+//! window. The burn rate is the share of bad calls (slower than the latency
+//! objective, or failed) divided by the share the objective allows; 1.0
+//! spends the error budget exactly over the window. Crossing
+//! `burn_rate_threshold` emits an `slo_burn` event, as does recovering.
+
+use crate::config::SloConfig;
+use crate::events::{self, Event, EventKind};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Buckets per window
+const BUCKETS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    /// Bucket index since the tracker started
+    index: u64,
+    calls: u64,
+    slow: u64,
+    errors: u64,
+}
+
+#[derive(Default)]
+struct Window {
+    buckets: VecDeque<Bucket>,
+    latency_burning: bool,
+    errors_burning: bool,
+}
+
+struct Objective {
+    config: SloConfig,
+    bucket_seconds: u64,
+    window: Mutex<Window>,
+}
+
+/// Compliance of one objective over its window
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SloReport {
+    pub server: String,
+    pub tool: String,
+    pub window_seconds: u64,
+    pub calls: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<SloCompliance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<SloCompliance>,
+}
+
+/// Compliance with the latency or error rate part of an objective
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SloCompliance {
+    /// Share of calls meeting the objective
+    pub compliance: f64,
+    /// Share of calls the objective requires to meet it
+    pub target: f64,
+    pub burn_rate: f64,
+    pub burning: bool,
+}
+
+fn compliance(bad: u64, calls: u64, budget: f64, burning: bool) -> SloCompliance {
+    let bad_share = if calls == 0 { 0.0 } else { bad as f64 / calls as f64 };
+    SloCompliance {
+        compliance: 1.0 - bad_share,
+        target: 1.0 - budget,
+        burn_rate: bad_share / budget,
+        burning,
+    }
+}
+
+impl Objective {
+    fn matches(&self, server: &str, tool: &str) -> bool {
+        (self.config.server.is_empty() || self.config.server == server)
+            && (self.config.tool.is_empty() || self.config.tool == tool)
+    }
+
+    fn latency_budget(&self) -> f64 {
+        1.0 - self.config.latency_percentile / 100.0
+    }
+
+    /// Totals over the window ending at `now` (seconds since start)
+    fn totals(window: &mut Window, bucket_seconds: u64, now: u64) -> (u64, u64, u64) {
+        let current = now / bucket_seconds;
+        while window
+            .buckets
+            .front()
+            .is_some_and(|b| b.index + BUCKETS <= current)
+        {
+            window.buckets.pop_front();
+        }
+        window.buckets.iter().fold((0, 0, 0), |(calls, slow, errors), b| {
+            (calls + b.calls, slow + b.slow, errors + b.errors)
+        })
+    }
+
+    fn record(&self, server: &str, tool: &str, duration_ms: u64, success: bool, now: u64) {
+        let index = now / self.bucket_seconds;
+        let slow = self
+            .config
+            .latency_ms
+            .is_some_and(|limit| duration_ms > limit);
+
+        let mut window = self.window.lock();
+        if window.buckets.back().is_none_or(|b| b.index != index) {
+            window.buckets.push_back(Bucket {
+                index,
+                ..Default::default()
+            });
+        }
+        if let Some(bucket) = window.buckets.back_mut() {
+            bucket.calls += 1;
+            bucket.slow += u64::from(slow);
+            bucket.errors += u64::from(!success);
+        }
+
+        let (calls, slow, errors) = Self::totals(&mut window, self.bucket_seconds, now);
+        if calls < self.config.min_calls {
+            return;
+        }
+        let threshold = self.config.burn_rate_threshold;
+
+        if self.config.latency_ms.is_some() {
+            let burn = compliance(slow, calls, self.latency_budget(), false).burn_rate;
+            if (burn >= threshold) != window.latency_burning {
+                window.latency_burning = burn >= threshold;
+                self.alert("latency", window.latency_burning, burn, server, tool);
+            }
+        }
+        if let Some(error_rate) = self.config.error_rate {
+            let burn = compliance(errors, calls, error_rate, false).burn_rate;
+            if (burn >= threshold) != window.errors_burning {
+                window.errors_burning = burn >= threshold;
+                self.alert("error rate", window.errors_burning, burn, server, tool);
+            }
+        }
+    }
+
+    fn alert(&self, objective: &str, burning: bool, burn: f64, server: &str, tool: &str) {
+        let message = if burning {
+            warn!(
+                "SLO burn on {}.{}: {} budget burning {:.1}x",
+                server, tool, objective, burn
+            );
+            format!("{} error budget burning {:.1}x too fast", objective, burn)
+        } else {
+            info!("SLO on {}.{} recovered: {}", server, tool, objective);
+            format!("{} error budget burn recovered", objective)
+        };
+        events::emit(
+            Event::new(EventKind::SloBurn, message)
+                .with_server(server)
+                .with_tool(tool)
+                .with_details(serde_json::json!({
+                    "objective": objective,
+                    "burning": burning,
+                    "burn_rate": burn,
+                    "threshold": self.config.burn_rate_threshold,
+                    "window_seconds": self.config.window_seconds,
+                })),
+        );
+    }
+
+    fn report(&self, now: u64) -> SloReport {
+        let mut window = self.window.lock();
+        let (calls, slow, errors) = Self::totals(&mut window, self.bucket_seconds, now);
+        SloReport {
+            server: self.config.server.clone(),
+            tool: self.config.tool.clone(),
+            window_seconds: self.config.window_seconds,
+            calls,
+            latency: self
+                .config
+                .latency_ms
+                .map(|_| compliance(slow, calls, self.latency_budget(), window.latency_burning)),
+            errors: self
+                .config
+                .error_rate
+                .map(|rate| compliance(errors, calls, rate, window.errors_burning)),
+        }
+    }
+}
+
+/// Configured objectives and their windows
+pub struct SloTracker {
+    objectives: Vec<Objective>,
+    started: Instant,
+}
+
+impl SloTracker {
+    /// Tracker for the configured objectives, if any
+    pub fn from_config(slos: &[SloConfig]) -> Option<Self> {
+        if slos.is_empty() {
+            return None;
+        }
+        Some(Self {
+            objectives: slos
+                .iter()
+                .map(|config| Objective {
+                    bucket_seconds: (config.window_seconds / BUCKETS).max(1),
+                    config: config.clone(),
+                    window: Mutex::new(Window::default()),
+                })
+                .collect(),
+            started: Instant::now(),
+        })
+    }
+
+    /// Whether any objective covers calls to this tool
+    pub fn tracks(&self, server: &str, tool: &str) -> bool {
+        self.objectives.iter().any(|o| o.matches(server, tool))
+    }
+
+    /// Record a finished tool call
+    pub fn record(&self, server: &str, tool: &str, duration_ms: u64, success: bool) {
+        self.record_at(server, tool, duration_ms, success, self.started.elapsed().as_secs());
+    }
+
+    fn record_at(&self, server: &str, tool: &str, duration_ms: u64, success: bool, now: u64) {
+        for objective in self.objectives.iter().filter(|o| o.matches(server, tool)) {
+            objective.record(server, tool, duration_ms, success, now);
+        }
+    }
+
+    /// Current compliance of every objective
+    pub fn report(&self) -> Vec<SloReport> {
+        let now = self.started.elapsed().as_secs();
+        self.objectives.iter().map(|o| o.report(now)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> SloTracker {
+        SloTracker::from_config(&[SloConfig {
+            server: "github".to_string(),
+            tool: "create_issue".to_string(),
+            latency_ms: Some(2000),
+            error_rate: Some(0.01),
+            window_seconds: 600,
+            min_calls: 10,
+            ..Default::default()
+        }])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compliance_and_burn() {
+        let slo = tracker();
+        assert!(slo.tracks("github", "create_issue"));
+        assert!(!slo.tracks("github", "list_issues"));
+
+        for _ in 0..97 {
+            slo.record_at("github", "create_issue", 100, true, 0);
+        }
+        slo.record_at("github", "create_issue", 5000, true, 0);
+        slo.record_at("github", "create_issue", 100, false, 0);
+        slo.record_at("github", "list_issues", 9000, false, 0);
+
+        let report = slo.objectives[0].report(0);
+        assert_eq!(report.calls, 99);
+        let latency = report.latency.unwrap();
+        assert!((latency.burn_rate - 1.0 / 99.0 / 0.01).abs() < 1e-9);
+        assert!(!latency.burning);
+
+        // Three more failures put the error budget past 2x
+        for _ in 0..3 {
+            slo.record_at("github", "create_issue", 100, false, 1);
+        }
+        assert!(slo.objectives[0].report(1).errors.unwrap().burning);
+    }
+
+    #[test]
+    fn test_window_expires() {
+        let slo = tracker();
+        for _ in 0..20 {
+            slo.record_at("github", "create_issue", 100, false, 0);
+        }
+        assert!(slo.objectives[0].report(0).errors.unwrap().burning);
+        assert_eq!(slo.objectives[0].report(599).calls, 20);
+        assert_eq!(slo.objectives[0].report(600).calls, 0);
+
+        // Recovery needs enough new calls to judge
+        for _ in 0..10 {
+            slo.record_at("github", "create_issue", 100, true, 700);
+        }
+        assert!(!slo.objectives[0].report(700).errors.unwrap().burning);
+    }
+}
@@ -0,0 +1,480 @@
+//! Supervision for stdio MCP servers
+//!
+//! Watches a server's transport for unexpected exits and restarts it with
+//! exponential backoff and jitter. Restarts are capped per time window;
+//! once the cap is hit the server is left disconnected and marked degraded
+//! until it is removed or re-added.
+//!
+//! With `warm_standby`, a second process is kept spawned and initialized.
+//! When the primary exits the standby takes over at once, without backoff,
+//! and a new standby is spawned in the background. Failovers still count
+//! against the restart budget.
+//!
+//! Once a client has initialized the server, every replacement process is
+//! sent the same `initialize` before it takes requests, so a restart is
+//! invisible to clients that don't reconnect.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::delegation;
+use crate::core::pool::{self, spawn_initialized};
+use crate::config::{McpServerConfig, SupervisionConfig};
+use crate::events::{self, Event, EventKind};
+use crate::sandbox::Sandbox;
+use crate::utils::errors::McpResult;
+use crate::transport::{StdioTransport, Transport};
+use parking_lot::Mutex;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+/// How often the supervisor checks the transport
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Restart bookkeeping for one server
+#[derive(Debug, Default)]
+pub struct SupervisorState {
+    restarts: AtomicU64,
+    failovers: AtomicU64,
+    failed_restarts: AtomicU64,
+    degraded: AtomicBool,
+    stopped: AtomicBool,
+    standby_ready: AtomicBool,
+    recent: Mutex<VecDeque<Instant>>,
+    last_error: Mutex<Option<String>>,
+    last_restart: Mutex<Option<DateTime<Utc>>>,
+    /// Params of the client's `initialize`, replayed to new processes
+    initialize: Mutex<Option<Value>>,
+}
+
+/// Point-in-time view of a server's restart counters
+#[derive(Debug, Clone, Serialize)]
+pub struct SupervisorSnapshot {
+    pub server: String,
+    /// Restarts that brought the server back, failovers included
+    pub restarts: u64,
+    /// Restarts where the warm standby took over
+    pub failovers: u64,
+    /// Restart attempts that failed to spawn or initialize a process
+    pub failed_restarts: u64,
+    /// Restart attempts counted against the current window's budget
+    pub recent_restarts: usize,
+    pub degraded: bool,
+    pub standby_ready: bool,
+    pub last_restart: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl SupervisorState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total restarts since the server was added
+    pub fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Whether the restart budget was exhausted
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Whether a warm standby is ready to take over
+    pub fn standby_ready(&self) -> bool {
+        self.standby_ready.load(Ordering::Relaxed)
+    }
+
+    /// Last restart failure, if any
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().clone()
+    }
+
+    /// Remember a successful `initialize` to replay after restarts
+    pub fn record_initialize(&self, params: Value) {
+        *self.initialize.lock() = Some(params);
+    }
+
+    fn initialize_params(&self) -> Option<Value> {
+        self.initialize.lock().clone()
+    }
+
+    /// Counters for the admin API
+    pub fn snapshot(&self, server: &str) -> SupervisorSnapshot {
+        SupervisorSnapshot {
+            server: server.to_string(),
+            restarts: self.restarts(),
+            failovers: self.failovers.load(Ordering::Relaxed),
+            failed_restarts: self.failed_restarts.load(Ordering::Relaxed),
+            recent_restarts: self.recent.lock().len(),
+            degraded: self.is_degraded(),
+            standby_ready: self.standby_ready(),
+            last_restart: *self.last_restart.lock(),
+            last_error: self.last_error(),
+        }
+    }
+
+    fn restarted(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+        *self.last_restart.lock() = Some(Utc::now());
+        *self.last_error.lock() = None;
+    }
+
+    /// Stop supervising; called before an intentional shutdown
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Reserve a restart slot, returning the attempt number within the
+    /// window, or `None` when the budget is spent
+    fn begin_restart(&self, config: &SupervisionConfig, now: Instant) -> Option<u32> {
+        let window = Duration::from_secs(config.window_secs);
+        let mut recent = self.recent.lock();
+        while recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > window)
+        {
+            recent.pop_front();
+        }
+
+        if recent.len() >= config.max_restarts as usize {
+            return None;
+        }
+
+        recent.push_back(now);
+        Some(recent.len() as u32)
+    }
+}
+
+/// Delay before restart `attempt` (1-based), with jitter applied
+pub fn backoff_delay(config: &SupervisionConfig, attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(20);
+    let base = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << exponent)
+        .min(config.max_backoff_ms);
+
+    let jitter = config.jitter.clamp(0.0, 1.0);
+    let factor = if jitter > 0.0 {
+        rand::rng().random_range(1.0 - jitter..=1.0 + jitter)
+    } else {
+        1.0
+    };
+
+    Duration::from_millis((base as f64 * factor) as u64)
+}
+
+/// Start a standby process in the background
+fn spawn_standby(
+    config: &McpServerConfig,
+    sandbox: &Arc<dyn Sandbox>,
+    state: &SupervisorState,
+) -> tokio::task::JoinHandle<McpResult<Box<dyn Transport>>> {
+    let config = config.clone();
+    let sandbox = sandbox.clone();
+    let params = state.initialize_params();
+    tokio::spawn(async move { spawn_initialized(&config, sandbox, params).await })
+}
+
+/// Spawn a replacement process, replaying the client's `initialize` if
+/// the old one had been initialized
+async fn respawn(
+    config: &McpServerConfig,
+    sandbox: &Arc<dyn Sandbox>,
+    state: &SupervisorState,
+) -> McpResult<Box<dyn Transport>> {
+    // A fresh delegated token for the new process
+    let env = delegation::server_env(config).await?;
+    let transport: Box<dyn Transport> = Box::new(
+        StdioTransport::with_config(
+            config.command.clone(),
+            config.args.clone(),
+            env,
+            sandbox.clone(),
+            &config.stdio,
+        )
+        .await?,
+    );
+    if let Some(params) = state.initialize_params() {
+        if let Err(e) = pool::initialize(transport.as_ref(), config, Some(params)).await {
+            let _ = transport.close().await;
+            return Err(e);
+        }
+    }
+    Ok(transport)
+}
+
+/// Watch a stdio transport and restart it when the process exits
+pub fn spawn_supervisor(
+    config: McpServerConfig,
+    sandbox: Arc<dyn Sandbox>,
+    transport: Arc<RwLock<Box<dyn Transport>>>,
+    state: Arc<SupervisorState>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let policy = config.supervision.clone();
+        let mut standby: Option<Box<dyn Transport>> = None;
+        let mut pending: Option<tokio::task::JoinHandle<McpResult<Box<dyn Transport>>>> = None;
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            if state.is_stopped() {
+                break;
+            }
+
+            if policy.warm_standby {
+                if pending.as_ref().is_some_and(|task| task.is_finished()) {
+                    match pending.take().expect("checked above").await {
+                        Ok(Ok(ready)) => {
+                            info!("Warm standby for {} is ready", config.name);
+                            standby = Some(ready);
+                        }
+                        Ok(Err(e)) => warn!("Failed to start warm standby for {}: {}", config.name, e),
+                        Err(e) => warn!("Warm standby task for {} failed: {}", config.name, e),
+                    }
+                }
+                if let Some(ready) = &standby {
+                    if !ready.is_connected().await {
+                        warn!("Warm standby for {} exited", config.name);
+                        standby = None;
+                    }
+                }
+                if standby.is_none() && pending.is_none() && !state.is_degraded() {
+                    pending = Some(spawn_standby(&config, &sandbox, &state));
+                }
+                state.standby_ready.store(standby.is_some(), Ordering::Relaxed);
+            }
+
+            if transport.read().await.is_connected().await {
+                continue;
+            }
+
+            let Some(attempt) = state.begin_restart(&policy, Instant::now()) else {
+                state.degraded.store(true, Ordering::Relaxed);
+                error!(
+                    "Server {} exited {} times within {}s, giving up",
+                    config.name, policy.max_restarts, policy.window_secs
+                );
+                events::emit(
+                    Event::new(EventKind::ServerFailure, "restart limit reached, server degraded")
+                        .with_server(&config.name)
+                        .with_details(serde_json::json!({ "restarts": state.restarts() })),
+                );
+                audit::record(
+                    AuditEvent::new(AuditEventType::ServerDegraded)
+                        .with_server_name(&config.name)
+                        .with_details(serde_json::json!({
+                            "restarts": state.restarts(),
+                            "max_restarts": policy.max_restarts,
+                            "window_secs": policy.window_secs,
+                        }))
+                        .with_error("restart limit reached"),
+                );
+                break;
+            };
+
+            if let Some(ready) = standby.take() {
+                let old = std::mem::replace(&mut *transport.write().await, ready);
+                let _ = old.close().await;
+                state.restarted();
+                state.failovers.fetch_add(1, Ordering::Relaxed);
+                state.standby_ready.store(false, Ordering::Relaxed);
+                warn!("Server {} exited unexpectedly, warm standby took over", config.name);
+                events::emit(
+                    Event::new(EventKind::ServerFailure, "exited unexpectedly, standby took over")
+                        .with_server(&config.name)
+                        .with_details(serde_json::json!({ "attempt": attempt })),
+                );
+                audit::record(
+                    AuditEvent::new(AuditEventType::ServerRestart)
+                        .with_server_name(&config.name)
+                        .with_details(serde_json::json!({
+                            "attempt": attempt,
+                            "standby": true,
+                        })),
+                );
+                pending = Some(spawn_standby(&config, &sandbox, &state));
+                continue;
+            }
+
+            let delay = backoff_delay(&policy, attempt);
+            warn!(
+                "Server {} exited unexpectedly, restarting in {:?} (attempt {})",
+                config.name, delay, attempt
+            );
+            events::emit(
+                Event::new(EventKind::ServerFailure, "exited unexpectedly")
+                    .with_server(&config.name)
+                    .with_details(serde_json::json!({ "attempt": attempt })),
+            );
+            tokio::time::sleep(delay).await;
+            if state.is_stopped() {
+                break;
+            }
+
+            let result = respawn(&config, &sandbox, &state).await;
+
+            let event = AuditEvent::new(AuditEventType::ServerRestart)
+                .with_server_name(&config.name)
+                .with_details(serde_json::json!({
+                    "attempt": attempt,
+                    "delay_ms": delay.as_millis() as u64,
+                }));
+
+            match result {
+                Ok(new_transport) => {
+                    *transport.write().await = new_transport;
+                    state.restarted();
+                    info!("Server {} restarted", config.name);
+                    audit::record(event);
+                }
+                Err(e) => {
+                    error!("Failed to restart server {}: {}", config.name, e);
+                    events::emit(
+                        Event::new(EventKind::ServerFailure, format!("restart failed: {}", e))
+                            .with_server(&config.name)
+                            .with_details(serde_json::json!({ "attempt": attempt })),
+                    );
+                    state.failed_restarts.fetch_add(1, Ordering::Relaxed);
+                    *state.last_error.lock() = Some(e.to_string());
+                    audit::record(event.with_error(e.to_string()));
+                }
+            }
+        }
+
+        if let Some(task) = pending {
+            task.abort();
+        }
+        if let Some(standby) = standby {
+            let _ = standby.close().await;
+        }
+        state.standby_ready.store(false, Ordering::Relaxed);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::protocol::JsonRpcRequest;
+
+    fn policy() -> SupervisionConfig {
+        SupervisionConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            jitter: 0.0,
+            max_restarts: 3,
+            window_secs: 60,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = policy();
+        assert_eq!(backoff_delay(&policy, 1), Duration::from_millis(100));
+        assert_eq!(backoff_delay(&policy, 2), Duration::from_millis(200));
+        assert_eq!(backoff_delay(&policy, 4), Duration::from_millis(800));
+        assert_eq!(backoff_delay(&policy, 10), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let policy = SupervisionConfig {
+            jitter: 0.5,
+            ..policy()
+        };
+        for _ in 0..100 {
+            let delay = backoff_delay(&policy, 1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+
+    /// Says whether it was initialized, and exits on a `crash` notification
+    const SERVER: &str = r#"
+import json, sys
+initialized = None
+for line in sys.stdin:
+    message = json.loads(line)
+    if message["method"] == "crash":
+        sys.exit(1)
+    if message["method"] == "initialize":
+        initialized = message["params"]["clientInfo"]["name"]
+    if "id" in message:
+        print(json.dumps({"jsonrpc": "2.0", "id": message["id"], "result": {"client": initialized}}), flush=True)
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_restart_replays_initialize() {
+        let config = McpServerConfig {
+            name: "flaky".to_string(),
+            command: "python3".to_string(),
+            args: vec!["-c".to_string(), SERVER.to_string()],
+            supervision: SupervisionConfig {
+                initial_backoff_ms: 10,
+                ..policy()
+            },
+            ..Default::default()
+        };
+        let sandbox: Arc<dyn Sandbox> = Arc::new(crate::sandbox::NoSandbox::new());
+        let state = Arc::new(SupervisorState::new());
+        let first = respawn(&config, &sandbox, &state).await.unwrap();
+        let transport = Arc::new(RwLock::new(first));
+        let supervisor = spawn_supervisor(config, sandbox, transport.clone(), state.clone());
+
+        let params = serde_json::json!({ "clientInfo": { "name": "editor" } });
+        let initialize = JsonRpcRequest::new("initialize", Some(params.clone()));
+        transport.read().await.send_request(initialize).await.unwrap();
+        state.record_initialize(params);
+        transport
+            .read()
+            .await
+            .send_notification(JsonRpcRequest::new("crash", None))
+            .await
+            .unwrap();
+
+        for _ in 0..50 {
+            if state.restarts() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let snapshot = state.snapshot("flaky");
+        assert_eq!((snapshot.restarts, snapshot.failed_restarts), (1, 0));
+        assert!(snapshot.last_restart.is_some());
+
+        let ping = JsonRpcRequest::new("ping", None);
+        let response = transport.read().await.send_request(ping).await.unwrap();
+        assert_eq!(response.result.unwrap()["client"], "editor");
+
+        state.stop();
+        supervisor.await.unwrap();
+        transport.read().await.close().await.unwrap();
+    }
+
+    #[test]
+    fn test_restart_budget_per_window() {
+        let policy = policy();
+        let state = SupervisorState::new();
+        let start = Instant::now();
+
+        assert_eq!(state.begin_restart(&policy, start), Some(1));
+        assert_eq!(state.begin_restart(&policy, start), Some(2));
+        assert_eq!(state.begin_restart(&policy, start), Some(3));
+        assert_eq!(state.begin_restart(&policy, start), None);
+
+        // Old restarts age out of the window
+        let later = start + Duration::from_secs(61);
+        assert_eq!(state.begin_restart(&policy, later), Some(1));
+    }
+}
@@ -0,0 +1,251 @@
+//! Template servers
+//!
+//! A template is a server definition whose `command`, `args`, `env` and
+//! `headers` values, `url` and `description` contain `{{parameter}}`
+//! placeholders. Instantiating it with parameter values yields an ordinary
+//! server named `<template>-<hash of the values>`, so the same values map
+//! to the same running instance and secrets never show up in the name.
+
+use crate::config::{McpServerConfig, ServerTemplateConfig};
+use crate::core::ServerManager;
+use crate::utils::errors::{McpError, McpResult};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Placeholder names in a templated string, e.g. `token` for `{{ token }}`
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 4 + len..];
+    }
+    names
+}
+
+/// Substitute parameter values into a templated string
+fn render(text: &str, values: &BTreeMap<String, String>) -> McpResult<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        let value = values
+            .get(name)
+            .ok_or_else(|| McpError::ConfigError(format!("Undeclared template parameter: {}", name)))?;
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[start + 4 + len..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Apply `f` to every string in a server config that may hold placeholders
+fn for_each_field(
+    config: &mut McpServerConfig,
+    mut f: impl FnMut(&mut String) -> McpResult<()>,
+) -> McpResult<()> {
+    f(&mut config.command)?;
+    for value in config
+        .args
+        .iter_mut()
+        .chain(config.env.values_mut())
+        .chain(config.headers.values_mut())
+        .chain(config.url.iter_mut())
+        .chain(config.description.iter_mut())
+    {
+        f(value)?;
+    }
+    Ok(())
+}
+
+/// Placeholders a template uses without declaring them as parameters
+pub fn undeclared_placeholders(template: &ServerTemplateConfig) -> Vec<String> {
+    let mut server = template.server.clone();
+    let mut undeclared = Vec::new();
+    let _ = for_each_field(&mut server, |value| {
+        for name in placeholders(value) {
+            let declared = template.parameters.iter().any(|p| p.name == name);
+            if !declared && !undeclared.iter().any(|u| u == name) {
+                undeclared.push(name.to_string());
+            }
+        }
+        Ok(())
+    });
+    undeclared
+}
+
+/// Check the given values against the template's parameters and fill in
+/// defaults
+pub fn resolve_params(
+    template: &ServerTemplateConfig,
+    given: &HashMap<String, String>,
+) -> McpResult<BTreeMap<String, String>> {
+    if let Some(unknown) = given
+        .keys()
+        .find(|key| !template.parameters.iter().any(|p| &p.name == *key))
+    {
+        return Err(McpError::InvalidRequest(format!(
+            "Template '{}' has no parameter '{}'",
+            template.server.name, unknown
+        )));
+    }
+
+    let mut values = BTreeMap::new();
+    for parameter in &template.parameters {
+        let value = given
+            .get(&parameter.name)
+            .or(parameter.default.as_ref())
+            .ok_or_else(|| {
+                McpError::InvalidRequest(format!(
+                    "Template '{}' requires parameter '{}'",
+                    template.server.name, parameter.name
+                ))
+            })?;
+        values.insert(parameter.name.clone(), value.clone());
+    }
+    Ok(values)
+}
+
+/// Name of the instance a set of parameter values maps to
+pub fn instance_name(template: &str, values: &BTreeMap<String, String>) -> String {
+    let mut canonical = String::new();
+    for (key, value) in values {
+        canonical.push_str(key);
+        canonical.push('\0');
+        canonical.push_str(value);
+        canonical.push('\0');
+    }
+    let digest = ring::digest::digest(&ring::digest::SHA256, canonical.as_bytes());
+    let hash: String = digest.as_ref()[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", template, hash)
+}
+
+/// Build a server config from a template
+///
+/// Without `name` the instance is named by [`instance_name`].
+pub fn instantiate(
+    template: &ServerTemplateConfig,
+    name: Option<&str>,
+    given: &HashMap<String, String>,
+) -> McpResult<McpServerConfig> {
+    let values = resolve_params(template, given)?;
+    let mut server = template.server.clone();
+    for_each_field(&mut server, |value| {
+        *value = render(value, &values)?;
+        Ok(())
+    })?;
+    server.name = match name {
+        Some(name) => name.to_string(),
+        None => instance_name(&template.server.name, &values),
+    };
+    Ok(server)
+}
+
+/// Serializes instance creation so concurrent calls with the same values
+/// start a single process
+fn instance_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+/// Start the instance for these values unless it is already running, and
+/// return its server name
+pub async fn ensure_instance(
+    server_manager: &ServerManager,
+    template: &ServerTemplateConfig,
+    given: &HashMap<String, String>,
+) -> McpResult<String> {
+    let server = instantiate(template, None, given)?;
+    let name = server.name.clone();
+
+    let _guard = instance_lock().lock().await;
+    if server_manager.get_server(&name).is_none() {
+        info!("Instantiating template {} as {}", template.server.name, name);
+        server_manager.add_server(server).await?;
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TemplateParameterConfig;
+
+    fn github_template() -> ServerTemplateConfig {
+        ServerTemplateConfig {
+            server: McpServerConfig {
+                name: "github".to_string(),
+                command: "npx".to_string(),
+                args: vec!["-y".to_string(), "@modelcontextprotocol/server-github".to_string()],
+                env: HashMap::from([
+                    ("GITHUB_PERSONAL_ACCESS_TOKEN".to_string(), "{{ token }}".to_string()),
+                    ("GITHUB_API_URL".to_string(), "https://{{host}}/api/v3".to_string()),
+                ]),
+                description: Some("GitHub ({{host}})".to_string()),
+                ..Default::default()
+            },
+            parameters: vec![
+                TemplateParameterConfig {
+                    name: "token".to_string(),
+                    secret: true,
+                    ..Default::default()
+                },
+                TemplateParameterConfig {
+                    name: "host".to_string(),
+                    default: Some("github.com".to_string()),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let template = github_template();
+        let server = instantiate(&template, None, &params(&[("token", "ghp_secret")])).unwrap();
+
+        assert_eq!(server.env["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_secret");
+        assert_eq!(server.env["GITHUB_API_URL"], "https://github.com/api/v3");
+        assert_eq!(server.description.as_deref(), Some("GitHub (github.com)"));
+        assert!(server.name.starts_with("github-"));
+        assert!(!server.name.contains("ghp_secret"));
+
+        // Same values, same instance; different values, different instance
+        let again = instantiate(&template, None, &params(&[("token", "ghp_secret")])).unwrap();
+        assert_eq!(again.name, server.name);
+        let other = instantiate(&template, None, &params(&[("token", "ghp_other")])).unwrap();
+        assert_ne!(other.name, server.name);
+
+        let named = instantiate(&template, Some("github-work"), &params(&[("token", "t")])).unwrap();
+        assert_eq!(named.name, "github-work");
+    }
+
+    #[test]
+    fn test_instantiate_checks_parameters() {
+        let template = github_template();
+        assert!(instantiate(&template, None, &HashMap::new()).is_err());
+        assert!(instantiate(&template, None, &params(&[("token", "t"), ("org", "x")])).is_err());
+    }
+
+    #[test]
+    fn test_undeclared_placeholders() {
+        let mut template = github_template();
+        assert!(undeclared_placeholders(&template).is_empty());
+
+        template.server.args.push("--org={{org}}".to_string());
+        assert_eq!(undeclared_placeholders(&template), vec!["org".to_string()]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Pre-forked worker processes for heavyweight stdio servers
+//!
+//! A stdio server with `workers = N` (N > 1) gets N extra processes,
+//! started and initialized together with the server. `initialize` still
+//! goes to the server's own process, whose answer the client sees; every
+//! other request is dealt round-robin to the healthy workers. A worker
+//! whose process exited or failed a request is skipped until a background
+//! check replaces it.
+
+use crate::config::McpServerConfig;
+use crate::core::pool::PooledConnection;
+use crate::utils::errors::{McpError, McpResult};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+/// How often dead workers are looked for and replaced
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Health and load of one worker
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerStatus {
+    pub index: usize,
+    pub healthy: bool,
+    /// Requests dealt to the worker
+    pub requests: u64,
+    /// Times its process was replaced
+    pub restarts: u64,
+}
+
+#[derive(Default)]
+struct Worker {
+    conn: RwLock<Option<PooledConnection>>,
+    requests: AtomicU64,
+    restarts: AtomicU64,
+}
+
+/// A server's worker processes
+pub struct WorkerSet {
+    config: McpServerConfig,
+    workers: Vec<Worker>,
+    next: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl WorkerSet {
+    /// Worker slots for `config.workers` processes, none started yet
+    pub fn new(config: McpServerConfig) -> Self {
+        let workers = (0..config.workers.max(1)).map(|_| Worker::default()).collect();
+        Self {
+            config,
+            workers,
+            next: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Start every worker, and keep replacing the ones that die
+    ///
+    /// Fails only if no worker could be started at all.
+    pub async fn start(config: McpServerConfig) -> McpResult<Arc<Self>> {
+        let set = Arc::new(Self::new(config));
+        set.check().await;
+        if set.status().await.iter().all(|worker| !worker.healthy) {
+            return Err(McpError::TransportError(format!(
+                "No worker of {} started",
+                set.config.name
+            )));
+        }
+        info!("Started {} workers for {}", set.workers.len(), set.config.name);
+        Self::spawn_checks(Arc::downgrade(&set));
+        Ok(set)
+    }
+
+    fn spawn_checks(set: Weak<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(set) = set.upgrade() else {
+                    return;
+                };
+                if set.closed.load(Ordering::SeqCst) {
+                    return;
+                }
+                set.check().await;
+            }
+        });
+    }
+
+    /// The next healthy worker, round-robin
+    pub async fn pick(&self) -> McpResult<PooledConnection> {
+        let count = self.workers.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..count {
+            let worker = &self.workers[(start + offset) % count];
+            let conn = worker.conn.read().await;
+            if let Some(conn) = conn.as_ref() {
+                if conn.is_healthy().await {
+                    worker.requests.fetch_add(1, Ordering::Relaxed);
+                    return Ok(conn.handle(None));
+                }
+            }
+        }
+        Err(McpError::TransportError(format!(
+            "No healthy worker for {}",
+            self.config.name
+        )))
+    }
+
+    /// Start the workers that are missing or unhealthy, all at once
+    async fn check(&self) {
+        let mut replacing = Vec::new();
+        for (index, worker) in self.workers.iter().enumerate() {
+            let healthy = match worker.conn.read().await.as_ref() {
+                Some(conn) => conn.is_healthy().await,
+                None => false,
+            };
+            if !healthy {
+                replacing.push(index);
+            }
+        }
+        if replacing.is_empty() {
+            return;
+        }
+
+        let spawns = replacing.iter().map(|&index| {
+            let id = format!("{}-worker-{}", self.config.name, index);
+            PooledConnection::new(self.config.clone(), id)
+        });
+        let spawned = futures::future::join_all(spawns).await;
+        for (index, result) in replacing.into_iter().zip(spawned) {
+            let worker = &self.workers[index];
+            match result {
+                Ok(conn) => {
+                    if self.closed.load(Ordering::SeqCst) {
+                        let _ = conn.close().await;
+                        continue;
+                    }
+                    let old = worker.conn.write().await.replace(conn);
+                    if let Some(old) = old {
+                        worker.restarts.fetch_add(1, Ordering::Relaxed);
+                        warn!("Replaced worker {} of {}", index, self.config.name);
+                        if let Err(e) = old.close().await {
+                            debug!("Closing dead worker {} of {}: {}", index, self.config.name, e);
+                        }
+                    }
+                }
+                Err(e) => warn!("Worker {} of {} failed to start: {}", index, self.config.name, e),
+            }
+        }
+    }
+
+    /// Health and load of each worker
+    pub async fn status(&self) -> Vec<WorkerStatus> {
+        let mut statuses = Vec::with_capacity(self.workers.len());
+        for (index, worker) in self.workers.iter().enumerate() {
+            let healthy = match worker.conn.read().await.as_ref() {
+                Some(conn) => conn.is_healthy().await,
+                None => false,
+            };
+            statuses.push(WorkerStatus {
+                index,
+                healthy,
+                requests: worker.requests.load(Ordering::Relaxed),
+                restarts: worker.restarts.load(Ordering::Relaxed),
+            });
+        }
+        statuses
+    }
+
+    /// Stop every worker for good
+    pub async fn shutdown(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        for worker in &self.workers {
+            if let Some(conn) = worker.conn.write().await.take() {
+                if let Err(e) = conn.close().await {
+                    debug!("Error closing worker {}: {}", conn.id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_no_healthy_worker() {
+        let set = WorkerSet::new(McpServerConfig {
+            name: "browser".to_string(),
+            workers: 3,
+            ..Default::default()
+        });
+
+        let status = set.status().await;
+        assert_eq!(status.len(), 3);
+        assert!(status.iter().all(|worker| !worker.healthy && worker.requests == 0));
+        assert!(matches!(set.pick().await, Err(McpError::TransportError(_))));
+    }
+}
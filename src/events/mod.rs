@@ -0,0 +1,139 @@
+//! Proxy events and outbound notifications
+//!
+//! Components report notable events with [`emit`]. When `[notifications]`
+//! is enabled, each event is routed to the webhooks subscribed to its type
+//! and delivered in the background with retries. Per-tenant usage for
+//! billing is reported separately by [`usage`].
+
+pub mod usage;
+pub mod webhook;
+
+pub use webhook::Notifier;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Type of proxy event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A `tools/call` was forwarded upstream
+    ToolCall,
+    /// An upstream server exited or could not be restarted
+    ServerFailure,
+    /// A circuit breaker opened
+    CircuitOpen,
+    /// A client exceeded a rate limit or quota
+    QuotaViolation,
+    /// A tool's SLO error budget started or stopped burning too fast
+    SloBurn,
+    /// A server's tool catalog no longer matches the approved one
+    ToolDrift,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::ToolCall => "tool_call",
+            EventKind::ServerFailure => "server_failure",
+            EventKind::CircuitOpen => "circuit_open",
+            EventKind::QuotaViolation => "quota_violation",
+            EventKind::SloBurn => "slo_burn",
+            EventKind::ToolDrift => "tool_drift",
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tool_call" => Ok(EventKind::ToolCall),
+            "server_failure" => Ok(EventKind::ServerFailure),
+            "circuit_open" => Ok(EventKind::CircuitOpen),
+            "quota_violation" => Ok(EventKind::QuotaViolation),
+            "slo_burn" => Ok(EventKind::SloBurn),
+            "tool_drift" => Ok(EventKind::ToolDrift),
+            _ => Err(format!("Unknown event type: {}", s)),
+        }
+    }
+}
+
+/// A proxy event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub event: EventKind,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub details: serde_json::Value,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, message: impl Into<String>) -> Self {
+        Self {
+            event: kind,
+            timestamp: Utc::now(),
+            server: None,
+            tool: None,
+            message: message.into(),
+            details: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_server(mut self, server: impl Into<String>) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+static GLOBAL_NOTIFIER: OnceCell<Arc<Notifier>> = OnceCell::new();
+
+/// Install the process-wide notifier used by [`emit`]
+pub fn install_global(notifier: Arc<Notifier>) -> bool {
+    GLOBAL_NOTIFIER.set(notifier).is_ok()
+}
+
+/// Whether any webhook would receive events of this type
+///
+/// Lets hot paths skip building events nobody is listening for.
+pub fn is_subscribed(kind: EventKind) -> bool {
+    GLOBAL_NOTIFIER
+        .get()
+        .is_some_and(|notifier| notifier.is_subscribed(kind))
+}
+
+/// Send an event to its subscribed webhooks, if a notifier is installed
+///
+/// Delivery happens in the background so callers never wait on the network.
+pub fn emit(event: Event) {
+    if let Some(notifier) = GLOBAL_NOTIFIER.get() {
+        if notifier.is_subscribed(event.event) {
+            let notifier = notifier.clone();
+            tokio::spawn(async move { notifier.dispatch(event).await });
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Per-tenant usage reports for billing
+//!
+//! With `[usage]` enabled, the MCP endpoint records each request against
+//! the caller's tenant: tool calls, seconds spent waiting on upstream
+//! servers, and bytes received and sent. Every `interval_seconds` the
+//! totals become reports, written to the spool directory and then
+//! delivered oldest first to the billing webhook or Kafka topic. A spool
+//! file is removed only once the sink accepts it, so reports survive
+//! outages and restarts and may be delivered more than once; consumers
+//! deduplicate on the report `id`.
+
+use crate::config::{UsageConfig, UsageSink};
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Tenant that requests without an authenticated session are billed to
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Content type of the Kafka REST proxy v2 JSON API
+const KAFKA_JSON: &str = "application/vnd.kafka.json.v2+json";
+
+/// One tenant's usage over one reporting period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Unique per report, for deduplicating redeliveries
+    pub id: String,
+    pub tenant: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub tool_calls: u64,
+    pub upstream_seconds: f64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+#[derive(Debug, Default)]
+struct Totals {
+    tool_calls: u64,
+    upstream_seconds: f64,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+/// Usage since the last report
+struct Period {
+    start: DateTime<Utc>,
+    totals: HashMap<String, Totals>,
+}
+
+/// Totals usage per tenant and delivers the reports
+pub struct UsageMeter {
+    config: UsageConfig,
+    client: reqwest::Client,
+    spool_dir: PathBuf,
+    period: Mutex<Period>,
+}
+
+impl UsageMeter {
+    pub fn new(config: UsageConfig) -> Self {
+        let spool_dir = PathBuf::from(shellexpand::tilde(&config.spool_dir).to_string());
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            spool_dir,
+            period: Mutex::new(Period {
+                start: Utc::now(),
+                totals: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Count one request against a tenant
+    pub fn record(
+        &self,
+        tenant: Option<&str>,
+        tool_call: bool,
+        upstream: Duration,
+        bytes_in: u64,
+        bytes_out: u64,
+    ) {
+        let mut period = self.period.lock();
+        let totals = period
+            .totals
+            .entry(tenant.unwrap_or(ANONYMOUS).to_string())
+            .or_default();
+        totals.tool_calls += tool_call as u64;
+        totals.upstream_seconds += upstream.as_secs_f64();
+        totals.bytes_in += bytes_in;
+        totals.bytes_out += bytes_out;
+    }
+
+    /// Close the current period and deliver every spooled report,
+    /// returning how many were delivered
+    pub async fn flush(&self) -> McpResult<usize> {
+        self.spool_period().await?;
+        self.deliver_spooled().await
+    }
+
+    /// Write the current period's reports to the spool
+    async fn spool_period(&self) -> McpResult<()> {
+        let now = Utc::now();
+        let (start, totals) = {
+            let mut period = self.period.lock();
+            let start = std::mem::replace(&mut period.start, now);
+            (start, std::mem::take(&mut period.totals))
+        };
+        if totals.is_empty() {
+            return Ok(());
+        }
+
+        let reports: Vec<UsageReport> = totals
+            .into_iter()
+            .map(|(tenant, totals)| UsageReport {
+                id: uuid::Uuid::new_v4().to_string(),
+                tenant,
+                period_start: start,
+                period_end: now,
+                tool_calls: totals.tool_calls,
+                upstream_seconds: totals.upstream_seconds,
+                bytes_in: totals.bytes_in,
+                bytes_out: totals.bytes_out,
+            })
+            .collect();
+
+        // Named by time so delivery goes oldest first; written under a
+        // temporary name so a crash never leaves half a file to deliver
+        tokio::fs::create_dir_all(&self.spool_dir).await?;
+        let name = format!("{:016}-{}", now.timestamp_millis(), uuid::Uuid::new_v4());
+        let partial = self.spool_dir.join(format!("{}.tmp", name));
+        tokio::fs::write(&partial, serde_json::to_vec(&reports)?).await?;
+        tokio::fs::rename(&partial, self.spool_dir.join(format!("{}.json", name))).await?;
+        Ok(())
+    }
+
+    /// Deliver spooled reports until the sink fails
+    async fn deliver_spooled(&self) -> McpResult<usize> {
+        let mut files = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.spool_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        let mut delivered = 0;
+        for (index, path) in files.iter().enumerate() {
+            let reports: Vec<UsageReport> = match serde_json::from_slice(&tokio::fs::read(path).await?) {
+                Ok(reports) => reports,
+                Err(e) => {
+                    warn!("Ignoring unreadable usage spool {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.send(&reports).await {
+                warn!(
+                    "Usage sink unavailable, {} spool files kept: {}",
+                    files.len() - index,
+                    e
+                );
+                break;
+            }
+            tokio::fs::remove_file(path).await?;
+            delivered += reports.len();
+        }
+        if delivered > 0 {
+            debug!("Delivered {} usage reports", delivered);
+        }
+        Ok(delivered)
+    }
+
+    async fn send(&self, reports: &[UsageReport]) -> McpResult<()> {
+        let request = match self.config.sink {
+            UsageSink::Webhook => self
+                .client
+                .post(&self.config.url)
+                .json(&json!({ "reports": reports })),
+            UsageSink::Kafka => {
+                let url = format!(
+                    "{}/topics/{}",
+                    self.config.url.trim_end_matches('/'),
+                    self.config.topic.as_deref().unwrap_or_default()
+                );
+                let records: Vec<_> = reports
+                    .iter()
+                    .map(|report| json!({ "key": report.tenant, "value": report }))
+                    .collect();
+                self.client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, KAFKA_JSON)
+                    .body(json!({ "records": records }).to_string())
+            }
+        };
+        let mut request = request.timeout(Duration::from_secs(self.config.timeout_seconds));
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| McpError::TransportError(format!("Usage delivery failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(McpError::TransportError(format!(
+                "Usage sink returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Report every `interval_seconds`, starting with whatever is left in
+    /// the spool from before a restart
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let meter = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(meter.config.interval_seconds.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = meter.flush().await {
+                    warn!("Failed to report usage: {}", e);
+                }
+            }
+        })
+    }
+}
+
+static GLOBAL_METER: OnceCell<Arc<UsageMeter>> = OnceCell::new();
+
+/// Install the process-wide meter used by [`record`]
+pub fn install_global(meter: Arc<UsageMeter>) -> bool {
+    GLOBAL_METER.set(meter).is_ok()
+}
+
+/// Whether usage is being metered, so callers can skip measuring
+pub fn is_enabled() -> bool {
+    GLOBAL_METER.get().is_some()
+}
+
+/// Count a request against a tenant, if a meter is installed
+pub fn record(tenant: Option<&str>, tool_call: bool, upstream: Duration, bytes_in: u64, bytes_out: u64) {
+    if let Some(meter) = GLOBAL_METER.get() {
+        meter.record(tenant, tool_call, upstream, bytes_in, bytes_out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_reports_spooled_until_delivered() {
+        let spool = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        let meter = UsageMeter::new(UsageConfig {
+            enabled: true,
+            sink: UsageSink::Kafka,
+            url: server.uri(),
+            topic: Some("usage".to_string()),
+            spool_dir: spool.path().to_string_lossy().to_string(),
+            ..Default::default()
+        });
+
+        meter.record(Some("acme"), true, Duration::from_millis(1500), 100, 2000);
+        meter.record(Some("acme"), false, Duration::from_millis(500), 50, 50);
+        meter.record(None, true, Duration::ZERO, 10, 10);
+
+        // Sink down: the reports stay in the spool
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        assert_eq!(meter.flush().await.unwrap(), 0);
+        assert_eq!(std::fs::read_dir(spool.path()).unwrap().count(), 1);
+
+        Mock::given(method("POST"))
+            .and(path("/topics/usage"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        assert_eq!(meter.flush().await.unwrap(), 2);
+        assert_eq!(std::fs::read_dir(spool.path()).unwrap().count(), 0);
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests.last().unwrap().body).unwrap();
+        let acme = body["records"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|record| record["key"] == "acme")
+            .unwrap();
+        assert_eq!(acme["value"]["tool_calls"], 1);
+        assert_eq!(acme["value"]["upstream_seconds"], 2.0);
+        assert_eq!(acme["value"]["bytes_in"], 150);
+        assert_eq!(acme["value"]["bytes_out"], 2050);
+    }
+}
@@ -0,0 +1,295 @@
+//! Webhook delivery with templated payloads and retries
+
+use crate::config::{NotificationsConfig, WebhookConfig, WebhookFormat};
+use crate::events::{Event, EventKind};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Default Slack message when a webhook has no template
+const SLACK_TEMPLATE: &str = "[{{event}}] {{server}} {{tool}}: {{message}}";
+
+/// A webhook with its parsed event subscriptions
+struct Route {
+    config: WebhookConfig,
+    /// `None` subscribes to every event type
+    events: Option<HashSet<EventKind>>,
+}
+
+impl Route {
+    fn matches(&self, kind: EventKind) -> bool {
+        self.events.as_ref().is_none_or(|events| events.contains(&kind))
+    }
+}
+
+/// Routes events to webhooks
+pub struct Notifier {
+    client: reqwest::Client,
+    routes: Vec<Route>,
+}
+
+impl Notifier {
+    /// Build the notifier, or `None` unless notifications are enabled
+    ///
+    /// Webhooks with unknown event types are skipped with a warning.
+    pub fn from_config(config: &NotificationsConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+
+        let routes = config
+            .webhooks
+            .iter()
+            .filter_map(|webhook| {
+                let events = if webhook.events.is_empty() {
+                    None
+                } else {
+                    match webhook.events.iter().map(|e| e.parse()).collect() {
+                        Ok(events) => Some(events),
+                        Err(e) => {
+                            warn!("Skipping webhook {}: {}", webhook.name, e);
+                            return None;
+                        }
+                    }
+                };
+                Some(Route {
+                    config: webhook.clone(),
+                    events,
+                })
+            })
+            .collect();
+
+        Some(Arc::new(Self {
+            client: reqwest::Client::new(),
+            routes,
+        }))
+    }
+
+    /// Whether any webhook receives events of this type
+    pub fn is_subscribed(&self, kind: EventKind) -> bool {
+        self.routes.iter().any(|route| route.matches(kind))
+    }
+
+    /// Deliver an event to every subscribed webhook concurrently
+    pub async fn dispatch(&self, event: Event) {
+        let deliveries = self
+            .routes
+            .iter()
+            .filter(|route| route.matches(event.event))
+            .map(|route| self.deliver(&route.config, &event));
+        futures::future::join_all(deliveries).await;
+    }
+
+    /// POST an event to one webhook, retrying with exponential backoff
+    async fn deliver(&self, webhook: &WebhookConfig, event: &Event) {
+        let body = render_body(webhook, event);
+        let mut backoff = Duration::from_millis(webhook.initial_backoff_ms);
+
+        for attempt in 0..=webhook.max_retries {
+            let mut request = self
+                .client
+                .post(&webhook.url)
+                .timeout(Duration::from_secs(webhook.timeout_seconds))
+                .json(&body);
+            for (name, value) in &webhook.headers {
+                request = request.header(name, value);
+            }
+
+            let retryable = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Delivered {} event to webhook {}", event.event, webhook.name);
+                    return;
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    warn!("Webhook {} returned {}", webhook.name, status);
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    warn!("Webhook {} delivery failed: {}", webhook.name, e);
+                    true
+                }
+            };
+
+            if !retryable || attempt == webhook.max_retries {
+                break;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2);
+        }
+
+        warn!("Dropping {} event for webhook {}", event.event, webhook.name);
+    }
+}
+
+/// JSON payload for a webhook
+///
+/// Generic webhooks get the event itself, or the rendered template parsed
+/// as JSON; Slack webhooks get the rendered text as `{"text": ...}`.
+pub fn render_body(webhook: &WebhookConfig, event: &Event) -> Value {
+    match webhook.format {
+        WebhookFormat::Slack => {
+            let template = webhook.template.as_deref().unwrap_or(SLACK_TEMPLATE);
+            json!({ "text": render_template(template, event, false) })
+        }
+        WebhookFormat::Generic => match &webhook.template {
+            Some(template) => {
+                let rendered = render_template(template, event, true);
+                serde_json::from_str(&rendered).unwrap_or(Value::String(rendered))
+            }
+            None => serde_json::to_value(event).unwrap_or(Value::Null),
+        },
+    }
+}
+
+/// Substitute event fields into a template
+///
+/// With `json_escape`, values are escaped for use inside JSON strings.
+/// `{{details}}` is always inserted as raw JSON. Placeholders are only
+/// read from the template, never from inserted values, and unknown ones are
+/// left as they are.
+pub fn render_template(template: &str, event: &Event, json_escape: bool) -> String {
+    let escape = |value: &str| {
+        if json_escape {
+            let quoted = Value::String(value.to_string()).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        } else {
+            value.to_string()
+        }
+    };
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(len) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let value = match &after[..len] {
+            "event" => Some(event.event.as_str().to_string()),
+            "server" => Some(escape(event.server.as_deref().unwrap_or(""))),
+            "tool" => Some(escape(event.tool.as_deref().unwrap_or(""))),
+            "message" => Some(escape(&event.message)),
+            "timestamp" => Some(event.timestamp.to_rfc3339()),
+            "details" => Some(event.details.to_string()),
+            _ => None,
+        };
+        match value {
+            Some(value) => {
+                rendered.push_str(&value);
+                rest = &after[len + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> Event {
+        Event::new(EventKind::CircuitOpen, "opened after 5 \"failures\"")
+            .with_server("github")
+            .with_details(json!({ "failures": 5 }))
+    }
+
+    #[test]
+    fn test_routing() {
+        let config = NotificationsConfig {
+            enabled: true,
+            webhooks: vec![
+                WebhookConfig {
+                    name: "ops".to_string(),
+                    events: vec!["circuit_open".to_string()],
+                    ..Default::default()
+                },
+                WebhookConfig {
+                    name: "broken".to_string(),
+                    events: vec!["nope".to_string()],
+                    ..Default::default()
+                },
+            ],
+        };
+        let notifier = Notifier::from_config(&config).unwrap();
+        assert!(notifier.is_subscribed(EventKind::CircuitOpen));
+        assert!(!notifier.is_subscribed(EventKind::ToolCall));
+        assert!(Notifier::from_config(&NotificationsConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_generic_template_escapes_values() {
+        let webhook = WebhookConfig {
+            template: Some(r#"{"summary": "{{server}}: {{message}}", "data": {{details}}}"#.to_string()),
+            ..Default::default()
+        };
+        let body = render_body(&webhook, &event());
+        assert_eq!(body["summary"], "github: opened after 5 \"failures\"");
+        assert_eq!(body["data"]["failures"], 5);
+    }
+
+    #[test]
+    fn test_template_rendered_in_one_pass() {
+        let event = Event::new(EventKind::CircuitOpen, "{{details}} {{server}}")
+            .with_server("{{tool}}")
+            .with_tool("search")
+            .with_details(json!({ "secret": "x" }));
+        assert_eq!(
+            render_template("{{server}}/{{tool}}: {{message}} {{unknown}} {{", &event, false),
+            "{{tool}}/search: {{details}} {{server}} {{unknown}} {{"
+        );
+    }
+
+    #[test]
+    fn test_default_bodies() {
+        let body = render_body(&WebhookConfig::default(), &event());
+        assert_eq!(body["event"], "circuit_open");
+        assert_eq!(body["server"], "github");
+
+        let slack = WebhookConfig {
+            format: WebhookFormat::Slack,
+            ..Default::default()
+        };
+        let body = render_body(&slack, &Event::new(EventKind::QuotaViolation, "429").with_server("api"));
+        assert_eq!(body["text"], "[quota_violation] api : 429");
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = NotificationsConfig {
+            enabled: true,
+            webhooks: vec![WebhookConfig {
+                name: "hook".to_string(),
+                url: server.uri(),
+                initial_backoff_ms: 1,
+                ..Default::default()
+            }],
+        };
+        Notifier::from_config(&config).unwrap().dispatch(event()).await;
+    }
+}
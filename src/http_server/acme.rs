@@ -0,0 +1,1075 @@
+//! ACME certificate automation (RFC 8555)
+//!
+//! Provisions and renews the serving certificate from an ACME CA such as
+//! Let's Encrypt, proving domain control with HTTP-01 or TLS-ALPN-01.
+//! Account keys and issued certificates are kept in the state backend so
+//! every node in a cluster serves the same certificate; a short-lived lock
+//! in the backend stops nodes from issuing concurrently. Challenge
+//! responses are published there too, since the CA's validation request
+//! may reach any node behind the load balancer.
+
+use crate::cloud::StateBackend;
+use crate::config::{AcmeChallengeType, AcmeConfig};
+use crate::http_server::tls::{certified_key_from_der, certified_key_from_pem, CertStore, ChallengeSource};
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use axum::{extract::Path, extract::State, http::StatusCode, routing::get, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// How often the renewal task re-checks the certificate
+const RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+/// Retry delay after a failed issuance
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Polling interval while waiting on the CA
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Maximum polls before giving up on an authorization or order
+const MAX_POLLS: u32 = 30;
+/// Lifetime of the cluster issuance lock
+const LOCK_TTL_SECS: i64 = 10 * 60;
+/// Lifetime of a published challenge response, past the longest validation
+const CHALLENGE_TTL_SECS: i64 = 10 * 60;
+
+/// ACME certificate manager
+pub struct AcmeManager {
+    config: AcmeConfig,
+    state: Arc<dyn StateBackend>,
+    certs: Arc<CertStore>,
+    challenges: Arc<AcmeChallenges>,
+    http: reqwest::Client,
+    node_id: String,
+}
+
+impl AcmeManager {
+    pub fn new(
+        config: AcmeConfig,
+        state: Arc<dyn StateBackend>,
+        certs: Arc<CertStore>,
+    ) -> McpResult<Self> {
+        if config.domains.is_empty() {
+            return Err(McpError::ConfigError(
+                "server.acme.domains must list at least one domain".to_string(),
+            ));
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| McpError::InternalError(e.to_string()))?;
+
+        let challenges = Arc::new(AcmeChallenges {
+            state: state.clone(),
+            prefix: config.state_prefix.clone(),
+        });
+
+        Ok(Self {
+            config,
+            state,
+            certs,
+            challenges,
+            http,
+            node_id: uuid::Uuid::new_v4().to_string(),
+        })
+    }
+
+    /// Router answering HTTP-01 challenges at `/.well-known/acme-challenge/{token}`
+    ///
+    /// Responses are looked up in the state backend, so every node answers
+    /// the challenges of whichever node is issuing.
+    pub fn http01_router(&self) -> Router {
+        async fn challenge(
+            State(challenges): State<Arc<AcmeChallenges>>,
+            Path(token): Path<String>,
+        ) -> Result<String, StatusCode> {
+            challenges
+                .lookup(&challenges.key(HTTP_01, &token))
+                .await
+                .ok_or(StatusCode::NOT_FOUND)
+        }
+
+        Router::new()
+            .route("/.well-known/acme-challenge/{token}", get(challenge))
+            .with_state(self.challenges.clone())
+    }
+
+    /// TLS-ALPN-01 challenge certificates published by any node
+    pub fn challenges(&self) -> Arc<AcmeChallenges> {
+        self.challenges.clone()
+    }
+
+    fn primary_domain(&self) -> &str {
+        &self.config.domains[0]
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}/{}/{}", self.config.state_prefix, self.primary_domain(), name)
+    }
+
+    fn account_key(&self) -> String {
+        format!("{}/account.key", self.config.state_prefix)
+    }
+
+    /// Load the stored certificate and key, if any
+    async fn load_stored(&self) -> McpResult<Option<StoredCertificate>> {
+        let cert = self.state.get(&self.key("cert.pem")).await?;
+        let key = self.state.get(&self.key("key.pem")).await?;
+
+        Ok(match (cert, key) {
+            (Some(cert), Some(key)) => Some(StoredCertificate {
+                not_after: certificate_expiry(&cert),
+                cert,
+                key,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Whether `stored` should be replaced with a newly issued certificate
+    fn is_due(&self, stored: &StoredCertificate) -> bool {
+        match stored.not_after {
+            Some(not_after) => {
+                let renew_at =
+                    not_after - chrono::Duration::days(self.config.renew_before_days as i64);
+                if chrono::Utc::now() >= renew_at {
+                    info!("Stored certificate expires at {}, renewing", not_after);
+                    true
+                } else {
+                    false
+                }
+            }
+            None => {
+                warn!("Stored certificate could not be parsed, reissuing");
+                true
+            }
+        }
+    }
+
+    /// Make sure a valid certificate is installed, issuing one if needed
+    ///
+    /// A stored certificate that has not expired is served while it is
+    /// renewed, and stays in place if the renewal fails.
+    pub async fn ensure_certificate(&self) -> McpResult<()> {
+        if let Some(stored) = self.load_stored().await? {
+            if stored.is_valid() {
+                match certified_key_from_pem(&stored.cert, &stored.key) {
+                    Ok(key) => self.certs.set_certificate(key),
+                    Err(e) => warn!("Stored certificate could not be loaded: {}", e),
+                }
+            }
+            if !self.is_due(&stored) {
+                return Ok(());
+            }
+        }
+
+        let Some(lock) = self.acquire_lock().await? else {
+            info!("Another node is issuing the certificate, waiting for it");
+            return Ok(());
+        };
+
+        let result = async {
+            // Another node may have finished while we waited for the lock
+            if let Some(stored) = self.load_stored().await? {
+                if !self.is_due(&stored) {
+                    return Ok((stored.cert, stored.key));
+                }
+            }
+            let (cert, key) = self.issue().await?;
+            self.state.set(&self.key("key.pem"), key.clone()).await?;
+            self.state.set(&self.key("cert.pem"), cert.clone()).await?;
+            Ok::<_, McpError>((cert, key))
+        }
+        .await;
+
+        self.release_lock(lock).await;
+
+        let (cert, key) = result?;
+        self.certs.set_certificate(certified_key_from_pem(&cert, &key)?);
+        info!("Installed ACME certificate for {}", self.config.domains.join(", "));
+        Ok(())
+    }
+
+    /// Keep the certificate issued and renewed in the background
+    pub fn spawn_renewal(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let delay = match self.ensure_certificate().await {
+                    Ok(()) if self.certs.has_certificate() => RENEWAL_CHECK_INTERVAL,
+                    Ok(()) => POLL_INTERVAL * 15,
+                    Err(e) => {
+                        error!("ACME certificate provisioning failed: {}", e);
+                        RETRY_INTERVAL
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+
+    /// Take the cluster issuance lock, returning the lock record written
+    ///
+    /// Each acquisition carries its own token, so a release only removes
+    /// the lock it took and never one another node has since taken over.
+    async fn acquire_lock(&self) -> McpResult<Option<Vec<u8>>> {
+        let key = self.key("lock");
+        let new = serde_json::to_vec(&Lock {
+            holder: self.node_id.clone(),
+            token: uuid::Uuid::new_v4().to_string(),
+            expires_at: chrono::Utc::now().timestamp() + LOCK_TTL_SECS,
+        })?;
+
+        if self.state.cas(&key, None, new.clone()).await? {
+            return Ok(Some(new));
+        }
+
+        // Take over a lock left behind by a node that died mid-issuance
+        if let Some(current) = self.state.get(&key).await? {
+            let stale = serde_json::from_slice::<Lock>(&current)
+                .map(|lock| lock.expires_at < chrono::Utc::now().timestamp())
+                .unwrap_or(true);
+            if stale && self.state.cas(&key, Some(current), new.clone()).await? {
+                return Ok(Some(new));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Release the lock written by [`Self::acquire_lock`] if we still hold it
+    async fn release_lock(&self, ours: Vec<u8>) {
+        let released = serde_json::to_vec(&Lock {
+            holder: self.node_id.clone(),
+            token: String::new(),
+            expires_at: 0,
+        });
+        let result = match released {
+            Ok(released) => self.state.cas(&self.key("lock"), Some(ours), released).await,
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(true) => {}
+            Ok(false) => warn!("ACME issuance lock was taken over before it was released"),
+            Err(e) => warn!("Failed to release ACME issuance lock: {}", e),
+        }
+    }
+
+    async fn load_or_create_account_key(&self) -> McpResult<Vec<u8>> {
+        if let Some(pkcs8) = self.state.get(&self.account_key()).await? {
+            return Ok(pkcs8);
+        }
+
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map_err(|_| McpError::InternalError("Failed to generate ACME account key".to_string()))?
+            .as_ref()
+            .to_vec();
+
+        if self.state.cas(&self.account_key(), None, pkcs8.clone()).await? {
+            Ok(pkcs8)
+        } else {
+            // Lost a race with another node; use its key
+            self.state
+                .get(&self.account_key())
+                .await?
+                .ok_or_else(|| McpError::InternalError("ACME account key disappeared".to_string()))
+        }
+    }
+
+    /// Run a full order and return the PEM certificate chain and key
+    async fn issue(&self) -> McpResult<(Vec<u8>, Vec<u8>)> {
+        info!("Requesting ACME certificate for {}", self.config.domains.join(", "));
+
+        let account_key = self.load_or_create_account_key().await?;
+        let mut client =
+            AcmeClient::connect(self.http.clone(), &self.config.directory_url, &account_key).await?;
+        client.register(&self.config.contacts).await?;
+
+        let (order_url, order) = client.new_order(&self.config.domains).await?;
+
+        for authz_url in &order.authorizations {
+            self.complete_authorization(&mut client, authz_url).await?;
+        }
+
+        let cert_key = rcgen::KeyPair::generate()
+            .map_err(|e| McpError::InternalError(format!("Failed to generate key: {}", e)))?;
+        let csr = rcgen::CertificateParams::new(self.config.domains.clone())
+            .and_then(|params| params.serialize_request(&cert_key))
+            .map_err(|e| McpError::InternalError(format!("Failed to build CSR: {}", e)))?;
+
+        client
+            .post(&order.finalize, Some(&json!({ "csr": URL_SAFE_NO_PAD.encode(csr.der()) })))
+            .await?;
+
+        let mut polls = 0;
+        let certificate_url = loop {
+            let order: Order = client.post(&order_url, None).await?.json().await.map_err(http_error)?;
+            match (order.status.as_str(), order.certificate) {
+                ("valid", Some(url)) => break url,
+                ("invalid", _) => {
+                    return Err(McpError::InternalError("ACME order became invalid".to_string()))
+                }
+                _ => {}
+            }
+            polls += 1;
+            if polls >= MAX_POLLS {
+                return Err(McpError::Timeout(POLL_INTERVAL.as_millis() as u64 * MAX_POLLS as u64));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+
+        let chain = client
+            .post(&certificate_url, None)
+            .await?
+            .bytes()
+            .await
+            .map_err(http_error)?
+            .to_vec();
+
+        Ok((chain, cert_key.serialize_pem().into_bytes()))
+    }
+
+    async fn complete_authorization(&self, client: &mut AcmeClient, url: &str) -> McpResult<()> {
+        let authz: Authorization = client.post(url, None).await?.json().await.map_err(http_error)?;
+        if authz.status == "valid" {
+            return Ok(());
+        }
+
+        let challenge_type = match self.config.challenge {
+            AcmeChallengeType::Http01 => "http-01",
+            AcmeChallengeType::TlsAlpn01 => "tls-alpn-01",
+        };
+        let challenge = authz
+            .challenges
+            .iter()
+            .find(|c| c.challenge_type == challenge_type)
+            .ok_or_else(|| {
+                McpError::InternalError(format!(
+                    "CA offered no {} challenge for {}",
+                    challenge_type, authz.identifier.value
+                ))
+            })?;
+
+        let key_authorization = format!("{}.{}", challenge.token, client.thumbprint());
+        let domain = authz.identifier.value.clone();
+
+        let published = match self.config.challenge {
+            AcmeChallengeType::Http01 => self.challenges.key(HTTP_01, &challenge.token),
+            AcmeChallengeType::TlsAlpn01 => {
+                self.challenges.key(TLS_ALPN_01, &domain.to_ascii_lowercase())
+            }
+        };
+        self.challenges.publish(&published, &key_authorization).await?;
+
+        let result = async {
+            client.post(&challenge.url, Some(&json!({}))).await?;
+
+            for _ in 0..MAX_POLLS {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                let authz: Authorization =
+                    client.post(url, None).await?.json().await.map_err(http_error)?;
+                match authz.status.as_str() {
+                    "valid" => return Ok(()),
+                    "invalid" => {
+                        return Err(McpError::AuthorizationError(format!(
+                            "ACME validation failed for {}",
+                            domain
+                        )))
+                    }
+                    _ => {}
+                }
+            }
+            Err(McpError::Timeout(POLL_INTERVAL.as_millis() as u64 * MAX_POLLS as u64))
+        }
+        .await;
+
+        self.challenges.withdraw(&published).await;
+        result
+    }
+}
+
+/// State key segment of HTTP-01 responses, keyed by token
+const HTTP_01: &str = "http-01";
+/// State key segment of TLS-ALPN-01 responses, keyed by domain
+const TLS_ALPN_01: &str = "tls-alpn-01";
+
+/// Challenge responses shared through the state backend
+pub struct AcmeChallenges {
+    state: Arc<dyn StateBackend>,
+    prefix: String,
+}
+
+impl AcmeChallenges {
+    fn key(&self, challenge_type: &str, name: &str) -> String {
+        format!("{}/challenges/{}/{}", self.prefix, challenge_type, name)
+    }
+
+    /// Publish a key authorization for the CA to validate
+    async fn publish(&self, key: &str, key_authorization: &str) -> McpResult<()> {
+        let record = serde_json::to_vec(&PendingChallenge {
+            key_authorization: key_authorization.to_string(),
+            expires_at: chrono::Utc::now().timestamp() + CHALLENGE_TTL_SECS,
+        })?;
+        self.state.set(key, record).await
+    }
+
+    /// Key authorization published under `key`, unless it has expired
+    async fn lookup(&self, key: &str) -> Option<String> {
+        let record = match self.state.get(key).await {
+            Ok(record) => record?,
+            Err(e) => {
+                warn!("Failed to read ACME challenge {}: {}", key, e);
+                return None;
+            }
+        };
+        serde_json::from_slice::<PendingChallenge>(&record)
+            .ok()
+            .filter(|pending| pending.expires_at >= chrono::Utc::now().timestamp())
+            .map(|pending| pending.key_authorization)
+    }
+
+    async fn withdraw(&self, key: &str) {
+        if let Err(e) = self.state.delete(key).await {
+            warn!("Failed to withdraw ACME challenge {}: {}", key, e);
+        }
+    }
+}
+
+#[async_trait]
+impl ChallengeSource for AcmeChallenges {
+    async fn challenge_certificate(&self, domain: &str) -> Option<Arc<rustls::sign::CertifiedKey>> {
+        let domain = domain.to_ascii_lowercase();
+        let key_authorization = self.lookup(&self.key(TLS_ALPN_01, &domain)).await?;
+        tls_alpn_certificate(&domain, &key_authorization)
+            .map_err(|e| warn!("Failed to build TLS-ALPN-01 certificate for {}: {}", domain, e))
+            .ok()
+    }
+}
+
+/// Build the self-signed certificate answering a TLS-ALPN-01 challenge
+fn tls_alpn_certificate(
+    domain: &str,
+    key_authorization: &str,
+) -> McpResult<Arc<rustls::sign::CertifiedKey>> {
+    let digest = ring::digest::digest(&ring::digest::SHA256, key_authorization.as_bytes());
+
+    let key = rcgen::KeyPair::generate()
+        .map_err(|e| McpError::InternalError(format!("Failed to generate key: {}", e)))?;
+    let mut params = rcgen::CertificateParams::new(vec![domain.to_string()])
+        .map_err(|e| McpError::InternalError(e.to_string()))?;
+    params.custom_extensions = vec![rcgen::CustomExtension::new_acme_identifier(digest.as_ref())];
+    let cert = params
+        .self_signed(&key)
+        .map_err(|e| McpError::InternalError(e.to_string()))?;
+
+    certified_key_from_der(
+        vec![cert.der().clone()],
+        PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der())),
+    )
+}
+
+/// Expiry of the first certificate in a PEM chain
+fn certificate_expiry(cert_pem: &[u8]) -> Option<chrono::DateTime<chrono::Utc>> {
+    let der: CertificateDer<'static> = rustls_pemfile::certs(&mut &cert_pem[..]).next()?.ok()?;
+    let (_, cert) = x509_parser::parse_x509_certificate(der.as_ref()).ok()?;
+    chrono::DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+}
+
+/// Certificate and key kept in the state backend
+struct StoredCertificate {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+    not_after: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl StoredCertificate {
+    /// Whether the certificate can still be served
+    fn is_valid(&self) -> bool {
+        self.not_after.is_some_and(|not_after| chrono::Utc::now() < not_after)
+    }
+}
+
+/// Challenge response record; expired ones are never served
+#[derive(Serialize, Deserialize)]
+struct PendingChallenge {
+    key_authorization: String,
+    expires_at: i64,
+}
+
+/// Cluster issuance lock record
+#[derive(Serialize, Deserialize)]
+struct Lock {
+    holder: String,
+    #[serde(default)]
+    token: String,
+    expires_at: i64,
+}
+
+fn http_error(e: reqwest::Error) -> McpError {
+    McpError::TransportError(format!("ACME request failed: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Directory {
+    new_nonce: String,
+    new_account: String,
+    new_order: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Order {
+    status: String,
+    #[serde(default)]
+    authorizations: Vec<String>,
+    #[serde(default)]
+    finalize: String,
+    certificate: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Authorization {
+    status: String,
+    identifier: Identifier,
+    #[serde(default)]
+    challenges: Vec<Challenge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Identifier {
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Challenge {
+    #[serde(rename = "type")]
+    challenge_type: String,
+    url: String,
+    #[serde(default)]
+    token: String,
+}
+
+/// Minimal ACME protocol client signing requests with an ES256 account key
+struct AcmeClient {
+    http: reqwest::Client,
+    directory: Directory,
+    key: EcdsaKeyPair,
+    rng: SystemRandom,
+    kid: Option<String>,
+    nonce: Option<String>,
+}
+
+impl AcmeClient {
+    async fn connect(http: reqwest::Client, directory_url: &str, pkcs8: &[u8]) -> McpResult<Self> {
+        let rng = SystemRandom::new();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng)
+            .map_err(|_| McpError::ConfigError("Invalid ACME account key".to_string()))?;
+
+        let directory = http
+            .get(directory_url)
+            .send()
+            .await
+            .map_err(http_error)?
+            .json::<Directory>()
+            .await
+            .map_err(http_error)?;
+
+        Ok(Self {
+            http,
+            directory,
+            key,
+            rng,
+            kid: None,
+            nonce: None,
+        })
+    }
+
+    fn jwk(&self) -> Value {
+        // Uncompressed point: 0x04 || x || y
+        let public = self.key.public_key().as_ref();
+        json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": URL_SAFE_NO_PAD.encode(&public[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&public[33..65]),
+        })
+    }
+
+    /// RFC 7638 JWK thumbprint used in key authorizations
+    fn thumbprint(&self) -> String {
+        jwk_thumbprint(&self.jwk())
+    }
+
+    async fn nonce(&mut self) -> McpResult<String> {
+        if let Some(nonce) = self.nonce.take() {
+            return Ok(nonce);
+        }
+
+        let response = self
+            .http
+            .head(&self.directory.new_nonce)
+            .send()
+            .await
+            .map_err(http_error)?;
+        replay_nonce(&response)
+            .ok_or_else(|| McpError::TransportError("ACME server returned no nonce".to_string()))
+    }
+
+    /// Send a signed request; `None` payload is a POST-as-GET
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> McpResult<reqwest::Response> {
+        let mut retried = false;
+        loop {
+            let nonce = self.nonce().await?;
+            let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+            match &self.kid {
+                Some(kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = self.jwk(),
+            }
+
+            let protected = URL_SAFE_NO_PAD.encode(protected.to_string());
+            let payload = payload
+                .map(|p| URL_SAFE_NO_PAD.encode(p.to_string()))
+                .unwrap_or_default();
+            let signature = self
+                .key
+                .sign(&self.rng, format!("{}.{}", protected, payload).as_bytes())
+                .map_err(|_| McpError::InternalError("Failed to sign ACME request".to_string()))?;
+
+            let response = self
+                .http
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/jose+json")
+                .body(
+                    json!({
+                        "protected": protected,
+                        "payload": payload,
+                        "signature": URL_SAFE_NO_PAD.encode(signature.as_ref()),
+                    })
+                    .to_string(),
+                )
+                .send()
+                .await
+                .map_err(http_error)?;
+
+            self.nonce = replay_nonce(&response);
+
+            if response.status().is_success() {
+                return Ok(response);
+            }
+
+            let status = response.status();
+            let problem: Value = response.json().await.unwrap_or(Value::Null);
+            let problem_type = problem.get("type").and_then(|t| t.as_str()).unwrap_or("");
+
+            if problem_type == "urn:ietf:params:acme:error:badNonce" && !retried {
+                retried = true;
+                continue;
+            }
+
+            return Err(McpError::TransportError(format!(
+                "ACME request to {} failed ({}): {}",
+                url,
+                status,
+                problem
+                    .get("detail")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or(problem_type)
+            )));
+        }
+    }
+
+    async fn register(&mut self, contacts: &[String]) -> McpResult<()> {
+        let url = self.directory.new_account.clone();
+        let response = self
+            .post(
+                &url,
+                Some(&json!({ "termsOfServiceAgreed": true, "contact": contacts })),
+            )
+            .await?;
+
+        let kid = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| McpError::TransportError("ACME account has no location".to_string()))?;
+        self.kid = Some(kid.to_string());
+        Ok(())
+    }
+
+    async fn new_order(&mut self, domains: &[String]) -> McpResult<(String, Order)> {
+        let identifiers: Vec<Value> = domains
+            .iter()
+            .map(|d| json!({ "type": "dns", "value": d }))
+            .collect();
+
+        let url = self.directory.new_order.clone();
+        let response = self
+            .post(&url, Some(&json!({ "identifiers": identifiers })))
+            .await?;
+
+        let order_url = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| McpError::TransportError("ACME order has no location".to_string()))?
+            .to_string();
+        let order = response.json().await.map_err(http_error)?;
+        Ok((order_url, order))
+    }
+}
+
+fn replay_nonce(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("replay-nonce")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+fn jwk_thumbprint(jwk: &Value) -> String {
+    // Required members in lexicographic order, no whitespace
+    let canonical = format!(
+        r#"{{"crv":"{}","kty":"{}","x":"{}","y":"{}"}}"#,
+        jwk["crv"].as_str().unwrap_or(""),
+        jwk["kty"].as_str().unwrap_or(""),
+        jwk["x"].as_str().unwrap_or(""),
+        jwk["y"].as_str().unwrap_or(""),
+    );
+    URL_SAFE_NO_PAD.encode(ring::digest::digest(&ring::digest::SHA256, canonical.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::InMemoryBackend;
+
+    fn manager() -> AcmeManager {
+        AcmeManager::new(
+            AcmeConfig {
+                enabled: true,
+                domains: vec!["example.com".to_string()],
+                ..Default::default()
+            },
+            Arc::new(InMemoryBackend::new()),
+            Arc::new(CertStore::new()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_requires_domains() {
+        let result = AcmeManager::new(
+            AcmeConfig::default(),
+            Arc::new(InMemoryBackend::new()),
+            Arc::new(CertStore::new()),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_jwk_thumbprint() {
+        let jwk = json!({ "kty": "EC", "crv": "P-256", "x": "abc", "y": "def" });
+        let thumbprint = jwk_thumbprint(&jwk);
+        assert_eq!(thumbprint.len(), 43);
+        assert_eq!(thumbprint, jwk_thumbprint(&jwk));
+    }
+
+    #[test]
+    fn test_certificate_expiry() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["example.com".to_string()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+        assert!(certificate_expiry(cert.pem().as_bytes()).is_some());
+        assert!(certificate_expiry(b"garbage").is_none());
+    }
+
+    #[test]
+    fn test_tls_alpn_certificate() {
+        assert!(tls_alpn_certificate("example.com", "token.thumb").is_ok());
+    }
+
+    /// Self-signed certificate for example.com expiring at `not_after`
+    fn certificate(not_after: chrono::DateTime<chrono::Utc>) -> (String, String) {
+        use chrono::Datelike;
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["example.com".to_string()]).unwrap();
+        params.not_after =
+            rcgen::date_time_ymd(not_after.year(), not_after.month() as u8, not_after.day() as u8);
+        let cert = params.self_signed(&key).unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+
+    async fn store(manager: &AcmeManager, (cert, key): (String, String)) {
+        manager
+            .state
+            .set("acme/example.com/cert.pem", cert.into_bytes())
+            .await
+            .unwrap();
+        manager
+            .state
+            .set("acme/example.com/key.pem", key.into_bytes())
+            .await
+            .unwrap();
+    }
+
+    async fn stored_expiry(manager: &AcmeManager) -> chrono::DateTime<chrono::Utc> {
+        let cert = manager.state.get("acme/example.com/cert.pem").await.unwrap().unwrap();
+        certificate_expiry(&cert).unwrap()
+    }
+
+    /// ACME CA accepting any account and issuing a certificate valid for a year
+    async fn mock_ca() -> String {
+        use axum::{response::IntoResponse, routing::post, Json};
+        use reqwest::header::LOCATION;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        #[derive(Clone)]
+        struct Ca {
+            base: String,
+            validated: Arc<AtomicBool>,
+            finalized: Arc<AtomicBool>,
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let ca = Ca {
+            base: base.clone(),
+            validated: Arc::new(AtomicBool::new(false)),
+            finalized: Arc::new(AtomicBool::new(false)),
+        };
+
+        let app = Router::new()
+            .route(
+                "/directory",
+                get(|State(ca): State<Ca>| async move {
+                    Json(json!({
+                        "newNonce": format!("{}/nonce", ca.base),
+                        "newAccount": format!("{}/account", ca.base),
+                        "newOrder": format!("{}/order", ca.base),
+                    }))
+                }),
+            )
+            .route("/nonce", get(|| async { [("replay-nonce", "nonce")] }))
+            .route(
+                "/account",
+                post(|State(ca): State<Ca>| async move {
+                    let location = format!("{}/account/1", ca.base);
+                    (StatusCode::CREATED, [(LOCATION, location)], Json(json!({ "status": "valid" })))
+                }),
+            )
+            .route(
+                "/order",
+                post(|State(ca): State<Ca>| async move {
+                    let location = format!("{}/order/1", ca.base);
+                    let order = json!({
+                        "status": "pending",
+                        "authorizations": [format!("{}/authz/1", ca.base)],
+                        "finalize": format!("{}/finalize", ca.base),
+                    });
+                    (StatusCode::CREATED, [(LOCATION, location)], Json(order))
+                }),
+            )
+            .route(
+                "/authz/1",
+                post(|State(ca): State<Ca>| async move {
+                    let status = if ca.validated.load(Ordering::SeqCst) { "valid" } else { "pending" };
+                    Json(json!({
+                        "status": status,
+                        "identifier": { "type": "dns", "value": "example.com" },
+                        "challenges": [{
+                            "type": "http-01",
+                            "url": format!("{}/challenge/1", ca.base),
+                            "token": "token",
+                        }],
+                    }))
+                }),
+            )
+            .route(
+                "/challenge/1",
+                post(|State(ca): State<Ca>| async move {
+                    ca.validated.store(true, Ordering::SeqCst);
+                    Json(json!({ "status": "processing" }))
+                }),
+            )
+            .route(
+                "/finalize",
+                post(|State(ca): State<Ca>| async move {
+                    ca.finalized.store(true, Ordering::SeqCst);
+                    Json(json!({ "status": "processing" }))
+                }),
+            )
+            .route(
+                "/order/1",
+                post(|State(ca): State<Ca>| async move {
+                    if ca.finalized.load(Ordering::SeqCst) {
+                        Json(json!({
+                            "status": "valid",
+                            "certificate": format!("{}/certificate", ca.base),
+                        }))
+                    } else {
+                        Json(json!({ "status": "processing" }))
+                    }
+                }),
+            )
+            .route(
+                "/certificate",
+                post(|| async {
+                    certificate(chrono::Utc::now() + chrono::Duration::days(365))
+                        .0
+                        .into_response()
+                }),
+            )
+            .with_state(ca);
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("{}/directory", base)
+    }
+
+    fn manager_for(directory_url: String) -> AcmeManager {
+        AcmeManager::new(
+            AcmeConfig {
+                enabled: true,
+                domains: vec!["example.com".to_string()],
+                directory_url,
+                ..Default::default()
+            },
+            Arc::new(InMemoryBackend::new()),
+            Arc::new(CertStore::new()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stored_certificate_installed() {
+        let manager = manager();
+        store(&manager, certificate(chrono::Utc::now() + chrono::Duration::days(90))).await;
+
+        manager.ensure_certificate().await.unwrap();
+        assert!(manager.certs.has_certificate());
+    }
+
+    #[tokio::test]
+    async fn test_issuance() {
+        let manager = manager_for(mock_ca().await);
+
+        manager.ensure_certificate().await.unwrap();
+        assert!(manager.certs.has_certificate());
+        assert!(stored_expiry(&manager).await > chrono::Utc::now() + chrono::Duration::days(300));
+        assert!(manager.challenges.lookup(&manager.challenges.key(HTTP_01, "token")).await.is_none());
+        // The lock is free for the next renewal
+        assert!(manager.acquire_lock().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_renewal() {
+        let manager = manager_for(mock_ca().await);
+        store(&manager, certificate(chrono::Utc::now() + chrono::Duration::days(10))).await;
+
+        manager.ensure_certificate().await.unwrap();
+        assert!(manager.certs.has_certificate());
+        assert!(stored_expiry(&manager).await > chrono::Utc::now() + chrono::Duration::days(300));
+    }
+
+    #[tokio::test]
+    async fn test_valid_certificate_served_while_renewal_fails() {
+        let manager = manager_for("http://127.0.0.1:1/directory".to_string());
+        store(&manager, certificate(chrono::Utc::now() + chrono::Duration::days(10))).await;
+
+        assert!(manager.ensure_certificate().await.is_err());
+        assert!(manager.certs.has_certificate());
+
+        // An expired certificate is never served
+        let manager = manager_for("http://127.0.0.1:1/directory".to_string());
+        store(&manager, certificate(chrono::Utc::now() - chrono::Duration::days(2))).await;
+        assert!(manager.ensure_certificate().await.is_err());
+        assert!(!manager.certs.has_certificate());
+    }
+
+    #[tokio::test]
+    async fn test_issuance_lock() {
+        let manager = manager();
+        let first = manager.acquire_lock().await.unwrap().unwrap();
+        assert!(manager.acquire_lock().await.unwrap().is_none());
+        manager.release_lock(first).await;
+        assert!(manager.acquire_lock().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_release_keeps_lock_taken_over() {
+        let manager = manager();
+        let first = manager.acquire_lock().await.unwrap().unwrap();
+
+        // The lock outlives its TTL and another node takes it over
+        let mut expired: Lock = serde_json::from_slice(&first).unwrap();
+        expired.expires_at = 0;
+        let expired = serde_json::to_vec(&expired).unwrap();
+        manager.state.set(&manager.key("lock"), expired).await.unwrap();
+        let second = manager.acquire_lock().await.unwrap().unwrap();
+
+        manager.release_lock(first).await;
+        assert!(manager.acquire_lock().await.unwrap().is_none());
+        manager.release_lock(second).await;
+        assert!(manager.acquire_lock().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_challenges_answered_by_every_node() {
+        use tower::ServiceExt;
+
+        let state: Arc<dyn StateBackend> = Arc::new(InMemoryBackend::new());
+        let node = || {
+            AcmeManager::new(
+                AcmeConfig {
+                    enabled: true,
+                    domains: vec!["example.com".to_string()],
+                    ..Default::default()
+                },
+                state.clone(),
+                Arc::new(CertStore::new()),
+            )
+            .unwrap()
+        };
+        let (issuer, other) = (node(), node());
+
+        let http01 = issuer.challenges.key(HTTP_01, "token");
+        issuer.challenges.publish(&http01, "token.thumb").await.unwrap();
+        let tls_alpn = issuer.challenges.key(TLS_ALPN_01, "example.com");
+        issuer.challenges.publish(&tls_alpn, "token.thumb").await.unwrap();
+
+        // The CA's validation requests reach the node not issuing
+        let fetch = |token: &str| {
+            axum::http::Request::get(format!("/.well-known/acme-challenge/{}", token))
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let response = other.http01_router().oneshot(fetch("token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"token.thumb");
+        assert!(other.challenges().challenge_certificate("Example.com").await.is_some());
+
+        issuer.challenges.withdraw(&http01).await;
+        issuer.challenges.withdraw(&tls_alpn).await;
+        let response = other.http01_router().oneshot(fetch("token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(other.challenges().challenge_certificate("example.com").await.is_none());
+
+        // Responses left behind by a node that died mid-validation expire
+        let expired = serde_json::to_vec(&PendingChallenge {
+            key_authorization: "token.thumb".to_string(),
+            expires_at: chrono::Utc::now().timestamp() - 1,
+        })
+        .unwrap();
+        state.set(&http01, expired).await.unwrap();
+        let response = other.http01_router().oneshot(fetch("token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
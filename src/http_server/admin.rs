@@ -0,0 +1,624 @@
+//! Admin API under `/admin/v1`
+//!
+//! Operator endpoints for inspecting and managing a running proxy. The
+//! router is mounted behind authentication and the configured admin scope.
+
+use crate::cloud::{ArtifactMeta, ArtifactStore};
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::Session;
+use crate::config::diff::{diff_configs, ConfigChange};
+use crate::config::{redact, ConfigManager};
+use crate::core::backpressure::{self, BackpressureStats};
+use crate::core::drift::{DriftMonitor, ServerDrift};
+use crate::core::pool::PoolStats;
+use crate::core::routing::VersionStatus;
+use crate::http_server::consent::{audit_change, consent_not_found, consent_store, Consent};
+use crate::http_server::server::AppState;
+use crate::http_server::session::{McpSession, SessionStore};
+use crate::sandbox::elevation::{self, ElevationGrant, ElevationRequest};
+use crate::sandbox::learn::{self, LearnReport};
+use crate::sandbox::syscall_audit::SyscallReport;
+use crate::utils::errors::{McpError, McpResult};
+use crate::utils::request_trace::{SlowRequest, SlowRequestLog};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post, put},
+    Extension, Router,
+};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Admin API routes
+pub fn admin_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/admin/v1/sessions", get(list_sessions))
+        .route("/admin/v1/sessions/{id}", delete(delete_session))
+        .route("/admin/v1/artifacts", get(list_artifacts))
+        .route("/admin/v1/artifacts/{id}", delete(delete_artifact))
+        .route("/admin/v1/config", get(get_config).put(apply_config))
+        .route("/admin/v1/config/refresh", post(refresh_config))
+        .route("/admin/v1/rollouts", get(list_rollouts))
+        .route("/admin/v1/rollouts/{name}", put(set_rollout_weights))
+        .route("/admin/v1/slow-requests", get(list_slow_requests))
+        .route("/admin/v1/slow-requests/{id}", get(get_slow_request))
+        .route("/admin/v1/servers/{name}/restart", post(restart_server))
+        .route("/admin/v1/servers/{name}/syscalls", get(get_syscalls))
+        .route(
+            "/admin/v1/servers/{name}/learn",
+            post(start_learning).get(get_learning).delete(stop_learning),
+        )
+        .route(
+            "/admin/v1/servers/{name}/elevation",
+            post(elevate_server).get(get_elevation).delete(revoke_elevation),
+        )
+        .route("/admin/v1/pools", get(list_pools))
+        .route("/admin/v1/backpressure", get(get_backpressure))
+        .route("/admin/v1/drift", get(list_drift))
+        .route("/admin/v1/drift/{server}/approve", post(approve_drift))
+        .route("/admin/v1/consents/{user}", get(list_consents))
+        .route("/admin/v1/consents/{user}/{tool}", delete(revoke_consent))
+        .with_state(state)
+}
+
+/// An active MCP session as reported by the admin API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub identity: Option<String>,
+    pub transport: String,
+    pub preset: Option<String>,
+    pub protocol_version: Option<String>,
+    pub client: Value,
+    pub request_count: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub age_seconds: i64,
+}
+
+impl SessionSummary {
+    fn new(session: McpSession, now: DateTime<Utc>) -> Self {
+        Self {
+            age_seconds: (now - session.created_at).num_seconds(),
+            id: session.id,
+            identity: session.identity,
+            transport: session.transport,
+            preset: session.preset,
+            protocol_version: session.protocol_version,
+            client: session.client_info,
+            request_count: session.request_count,
+            created_at: session.created_at,
+            last_seen: session.last_seen,
+        }
+    }
+}
+
+/// Session list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionList {
+    pub sessions: Vec<SessionSummary>,
+    pub total: usize,
+}
+
+fn session_store(state: &AppState) -> McpResult<&SessionStore> {
+    state
+        .sessions
+        .as_deref()
+        .ok_or_else(|| McpError::InvalidRequest("MCP sessions are disabled".to_string()))
+}
+
+/// `GET /admin/v1/sessions`
+async fn list_sessions(State(state): State<Arc<AppState>>) -> McpResult<Json<SessionList>> {
+    let now = Utc::now();
+    let mut sessions: Vec<_> = session_store(&state)?
+        .list()
+        .await?
+        .into_iter()
+        .map(|session| SessionSummary::new(session, now))
+        .collect();
+    sessions.sort_by_key(|session| session.created_at);
+
+    Ok(Json(SessionList {
+        total: sessions.len(),
+        sessions,
+    }))
+}
+
+/// `DELETE /admin/v1/sessions/{id}`
+///
+/// Ends the session and stops any upstream processes dedicated to it; the
+/// client has to initialize again.
+async fn delete_session(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> McpResult<Response> {
+    if !session_store(&state)?.remove(&id).await? {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "SESSION_NOT_FOUND",
+                "message": format!("No active session {}", id),
+            })),
+        )
+            .into_response());
+    }
+
+    info!("Terminated MCP session {} via admin API", id);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Artifact list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactList {
+    pub artifacts: Vec<ArtifactMeta>,
+    pub total: usize,
+    pub total_bytes: usize,
+}
+
+fn artifact_store(state: &AppState) -> McpResult<&ArtifactStore> {
+    state
+        .artifacts
+        .as_deref()
+        .ok_or_else(|| McpError::InvalidRequest("Artifact store is disabled".to_string()))
+}
+
+/// `GET /admin/v1/artifacts`
+async fn list_artifacts(State(state): State<Arc<AppState>>) -> McpResult<Json<ArtifactList>> {
+    let artifacts = artifact_store(&state)?.list().await?;
+    Ok(Json(ArtifactList {
+        total: artifacts.len(),
+        total_bytes: artifacts.iter().map(|a| a.size).sum(),
+        artifacts,
+    }))
+}
+
+/// `DELETE /admin/v1/artifacts/{id}`
+async fn delete_artifact(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> McpResult<Response> {
+    if !artifact_store(&state)?.delete(&id).await? {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "ARTIFACT_NOT_FOUND",
+                "message": format!("No artifact {}", id),
+            })),
+        )
+            .into_response());
+    }
+
+    info!("Deleted artifact {} via admin API", id);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Body of `PUT /admin/v1/config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigCandidate {
+    /// New content for the config file, in the file's format
+    pub content: String,
+    /// Validate and diff without applying
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of `PUT /admin/v1/config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigApplyResult {
+    pub applied: bool,
+    pub changes: Vec<ConfigChange>,
+    /// Changed sections that only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+/// One config apply at a time
+static APPLY_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+fn config_manager(state: &AppState) -> McpResult<&ConfigManager> {
+    state
+        .config_manager
+        .as_deref()
+        .ok_or_else(|| McpError::InvalidRequest("Config is not managed by this server".to_string()))
+}
+
+/// `GET /admin/v1/config`, the config the server is running, with its
+/// secrets redacted
+async fn get_config(State(state): State<Arc<AppState>>) -> McpResult<Json<Value>> {
+    Ok(Json(redact::redact(&config_manager(&state)?.get_config())?))
+}
+
+/// `PUT /admin/v1/config`
+///
+/// Validates the candidate, writes it over the config file, then adds,
+/// removes and restarts servers to match. If a server fails to start, the
+/// old file and servers are put back. Redacted secrets keep their current
+/// values, and stay redacted in the reported changes.
+async fn apply_config(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ConfigCandidate>,
+) -> McpResult<Json<ConfigApplyResult>> {
+    let manager = config_manager(&state)?;
+    let _guard = APPLY_LOCK.lock().await;
+
+    let content = manager.fill_redacted(&body.content).await?;
+    let candidate = manager.check_candidate(&content).await?;
+    let current = manager.get_config();
+    let mut changes = diff_configs(&current, &candidate)?;
+    redact::redact_changes(&mut changes);
+    let mut restart_required: Vec<String> = changes
+        .iter()
+        .map(ConfigChange::section)
+        .filter(|section| *section != "servers")
+        .map(String::from)
+        .collect();
+    restart_required.dedup();
+    if body.dry_run || changes.is_empty() {
+        return Ok(Json(ConfigApplyResult {
+            applied: false,
+            changes,
+            restart_required,
+        }));
+    }
+
+    let previous = manager.replace(&content).await?;
+    let servers = &state.server_manager;
+    if let Err(e) = servers.apply_servers(&current.servers, &candidate.servers).await {
+        warn!("Rolling back config applied via admin API: {}", e);
+        if let Err(e) = servers.apply_servers(&candidate.servers, &current.servers).await {
+            error!("Failed to restore servers after rollback: {}", e);
+        }
+        manager.restore(&previous).await?;
+        return Err(McpError::ConfigError(format!("Rolled back, a server failed to start: {}", e)));
+    }
+
+    info!("Applied config via admin API: {} change(s)", changes.len());
+    Ok(Json(ConfigApplyResult {
+        applied: true,
+        changes,
+        restart_required,
+    }))
+}
+
+/// `POST /admin/v1/config/refresh`
+///
+/// Fetches a config served from object storage if it changed; meant as the
+/// target of bucket notifications when `config_source.refresh = "event"`.
+async fn refresh_config(State(state): State<Arc<AppState>>) -> McpResult<Json<Value>> {
+    let remote = state.remote_config.as_deref().ok_or_else(|| {
+        McpError::InvalidRequest("Config is not loaded from object storage".to_string())
+    })?;
+    let changed = remote.fetch().await?;
+    Ok(Json(serde_json::json!({ "changed": changed })))
+}
+
+/// Traffic split of a server with several versions
+#[derive(Debug, Clone, Serialize)]
+pub struct RolloutSummary {
+    pub server: String,
+    pub versions: Vec<VersionStatus>,
+}
+
+/// `GET /admin/v1/rollouts`
+async fn list_rollouts(State(state): State<Arc<AppState>>) -> Json<Vec<RolloutSummary>> {
+    let mut rollouts: Vec<_> = state
+        .server_manager
+        .traffic_splits()
+        .into_iter()
+        .map(|split| RolloutSummary {
+            server: split.name().to_string(),
+            versions: split.status(),
+        })
+        .collect();
+    rollouts.sort_by(|a, b| a.server.cmp(&b.server));
+    Json(rollouts)
+}
+
+/// Body of `PUT /admin/v1/rollouts/{name}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RolloutWeights {
+    /// New weight per version; versions left out keep theirs
+    pub weights: HashMap<String, u32>,
+}
+
+/// `PUT /admin/v1/rollouts/{name}`
+///
+/// Shifts traffic between versions. Setting a rolled-back version's weight
+/// re-enables it.
+async fn set_rollout_weights(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<RolloutWeights>,
+) -> McpResult<Json<RolloutSummary>> {
+    let split = state
+        .server_manager
+        .traffic_split(&name)
+        .ok_or_else(|| McpError::ServerNotFound(format!("{} has no versions", name)))?;
+    split.set_weights(&body.weights)?;
+
+    info!("Set traffic weights of {} via admin API: {:?}", name, body.weights);
+    Ok(Json(RolloutSummary {
+        server: name,
+        versions: split.status(),
+    }))
+}
+
+/// Slow request list response
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowRequestList {
+    pub requests: Vec<SlowRequest>,
+    pub total: usize,
+    pub threshold_ms: u64,
+}
+
+fn slow_request_log(state: &AppState) -> McpResult<&SlowRequestLog> {
+    state
+        .slow_requests
+        .as_deref()
+        .ok_or_else(|| McpError::InvalidRequest("Slow request tracing is disabled".to_string()))
+}
+
+/// `GET /admin/v1/slow-requests`, newest first
+async fn list_slow_requests(State(state): State<Arc<AppState>>) -> McpResult<Json<SlowRequestList>> {
+    let log = slow_request_log(&state)?;
+    let requests = log.list();
+    Ok(Json(SlowRequestList {
+        total: requests.len(),
+        threshold_ms: log.threshold().as_millis() as u64,
+        requests,
+    }))
+}
+
+/// `GET /admin/v1/slow-requests/{id}`
+async fn get_slow_request(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> McpResult<Response> {
+    match slow_request_log(&state)?.get(&id) {
+        Some(request) => Ok(Json(request).into_response()),
+        None => Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "SLOW_REQUEST_NOT_FOUND",
+                "message": format!("No slow request {}", id),
+            })),
+        )
+            .into_response()),
+    }
+}
+
+/// `POST /admin/v1/servers/{name}/restart`, stop a server and start it again
+async fn restart_server(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> McpResult<StatusCode> {
+    state.server_manager.restart_server(&name).await?;
+    info!("Restarted server {} via admin API", name);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /admin/v1/servers/{name}/syscalls`, syscalls seen in seccomp audit
+/// mode and the enforcing profile they suggest
+async fn get_syscalls(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> McpResult<Json<SyscallReport>> {
+    Ok(Json(state.server_manager.syscall_report(&name)?))
+}
+
+/// Body of `POST /admin/v1/servers/{name}/learn`
+#[derive(Debug, Clone, Deserialize)]
+pub struct LearnRequest {
+    /// How long to run the server permissively and watch it
+    pub duration_secs: u64,
+}
+
+/// `POST /admin/v1/servers/{name}/learn`
+///
+/// Restarts the server under a permissive, auditing sandbox and watches
+/// it for the given time, then restarts it under its own config again.
+async fn start_learning(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(body): Json<LearnRequest>,
+) -> McpResult<Json<LearnReport>> {
+    let config = state
+        .server_manager
+        .get_server(&name)
+        .map(|server| server.config.clone())
+        .ok_or_else(|| McpError::ServerNotFound(name.clone()))?;
+    if elevation::grant(&name).is_some() {
+        return Err(McpError::InvalidRequest(format!("{} is elevated", name)));
+    }
+    let session = learn::LearningSession::start(config, Duration::from_secs(body.duration_secs))?;
+    if let Err(e) = state
+        .server_manager
+        .update_server(learn::permissive(session.original()))
+        .await
+    {
+        session.finish();
+        return Err(e);
+    }
+    audit::record(
+        AuditEvent::new(AuditEventType::ConfigChange)
+            .with_server_name(&name)
+            .with_details(serde_json::json!({
+                "learning": true,
+                "duration_secs": body.duration_secs,
+            })),
+    );
+    info!("Learning the sandbox profile of {} for {}s", name, body.duration_secs);
+
+    let report = session.report();
+    let manager = state.server_manager.clone();
+    tokio::spawn(async move {
+        session.observe().await;
+        let name = &session.original().name;
+        match manager.update_server(session.original().clone()).await {
+            Ok(()) => info!("Finished learning {}, restarted it under its config", name),
+            Err(e) => error!("Failed to restart {} after learning: {}", name, e),
+        }
+        session.finish();
+    });
+    Ok(Json(report))
+}
+
+fn learning_session(name: &str) -> McpResult<Arc<learn::LearningSession>> {
+    learn::session(name)
+        .ok_or_else(|| McpError::ServerNotFound(format!("{} has not been learned", name)))
+}
+
+/// `GET /admin/v1/servers/{name}/learn`, what a learning session has seen
+/// so far and the sandbox it suggests
+async fn get_learning(Path(name): Path<String>) -> McpResult<Json<LearnReport>> {
+    Ok(Json(learning_session(&name)?.report()))
+}
+
+/// `DELETE /admin/v1/servers/{name}/learn`, end a learning session early
+async fn stop_learning(Path(name): Path<String>) -> McpResult<Json<LearnReport>> {
+    let session = learning_session(&name)?;
+    session.finish();
+    Ok(Json(session.report()))
+}
+
+/// `POST /admin/v1/servers/{name}/elevation`
+///
+/// Restarts the server with the requested access on top of its sandbox,
+/// and under its own config again when the grant expires.
+async fn elevate_server(
+    State(state): State<Arc<AppState>>,
+    session: Option<Extension<Session>>,
+    Path(name): Path<String>,
+    Json(body): Json<ElevationRequest>,
+) -> McpResult<Json<ElevationGrant>> {
+    let config = state
+        .server_manager
+        .get_server(&name)
+        .map(|server| server.config.clone())
+        .ok_or_else(|| McpError::ServerNotFound(name.clone()))?;
+    let by = session.map(|session| session.user_id.clone());
+    let grant = elevation::Grant::start(config, body, by.clone(), &state.elevation)?;
+    if let Err(e) = state.server_manager.update_server(grant.elevated()).await {
+        grant.end();
+        return Err(e);
+    }
+    let info = grant.info().clone();
+    let mut event = AuditEvent::new(AuditEventType::SandboxElevated)
+        .with_server_name(&name)
+        .with_details(serde_json::to_value(&info)?);
+    if let Some(by) = by {
+        event = event.with_user_id(by);
+    }
+    audit::record(event);
+    info!("Elevated the sandbox of {} until {}", name, info.expires_at);
+
+    let manager = state.server_manager.clone();
+    tokio::spawn(async move {
+        let revoked = grant.expire().await;
+        let name = &grant.original().name;
+        match manager.update_server(grant.original().clone()).await {
+            Ok(()) => info!("Elevation of {} ended, restarted it under its config", name),
+            Err(e) => error!("Failed to restart {} after elevation: {}", name, e),
+        }
+        audit::record(
+            AuditEvent::new(AuditEventType::SandboxElevationReverted)
+                .with_server_name(name)
+                .with_details(serde_json::json!({
+                    "expires_at": grant.info().expires_at,
+                    "revoked": revoked,
+                })),
+        );
+        grant.end();
+    });
+    Ok(Json(info))
+}
+
+fn elevation_grant(name: &str) -> McpResult<Arc<elevation::Grant>> {
+    elevation::grant(name)
+        .ok_or_else(|| McpError::ServerNotFound(format!("{} is not elevated", name)))
+}
+
+/// `GET /admin/v1/servers/{name}/elevation`, the server's active grant
+async fn get_elevation(Path(name): Path<String>) -> McpResult<Json<ElevationGrant>> {
+    Ok(Json(elevation_grant(&name)?.info().clone()))
+}
+
+/// `DELETE /admin/v1/servers/{name}/elevation`, end a grant early
+async fn revoke_elevation(
+    session: Option<Extension<Session>>,
+    Path(name): Path<String>,
+) -> McpResult<Response> {
+    let grant = elevation_grant(&name)?;
+    grant.revoke();
+    let by = session.map(|session| session.user_id.clone()).unwrap_or_default();
+    info!("Revoked the elevation of {} via admin API ({})", name, by);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// `GET /admin/v1/pools`, live process pool statistics per server
+async fn list_pools(State(state): State<Arc<AppState>>) -> Json<Vec<PoolStats>> {
+    Json(state.server_manager.pool_stats().await)
+}
+
+/// `GET /admin/v1/backpressure`, current load and requests shed
+async fn get_backpressure() -> McpResult<Json<BackpressureStats>> {
+    backpressure::global()
+        .map(|backpressure| Json(backpressure.stats()))
+        .ok_or_else(|| McpError::InvalidRequest("Backpressure is disabled".to_string()))
+}
+
+fn drift_monitor(state: &AppState) -> McpResult<&DriftMonitor> {
+    state
+        .server_manager
+        .drift()
+        .map(Arc::as_ref)
+        .ok_or_else(|| McpError::InvalidRequest("Drift detection is disabled".to_string()))
+}
+
+/// `GET /admin/v1/drift`, tool catalogs awaiting approval
+async fn list_drift(State(state): State<Arc<AppState>>) -> McpResult<Json<Vec<ServerDrift>>> {
+    Ok(Json(drift_monitor(&state)?.pending()))
+}
+
+/// `POST /admin/v1/drift/{server}/approve`, accept a server's current tools
+async fn approve_drift(
+    State(state): State<Arc<AppState>>,
+    Path(server): Path<String>,
+) -> McpResult<Json<ServerDrift>> {
+    let drift = drift_monitor(&state)?.approve(&server)?;
+    audit::record(
+        AuditEvent::new(AuditEventType::ToolDriftApproved)
+            .with_server_name(&server)
+            .with_details(serde_json::json!({ "changes": drift.changes })),
+    );
+    Ok(Json(drift))
+}
+
+/// `GET /admin/v1/consents/{user}`, a user's remembered tool consents
+async fn list_consents(
+    State(state): State<Arc<AppState>>,
+    Path(user): Path<String>,
+) -> McpResult<Json<Vec<Consent>>> {
+    Ok(Json(consent_store(&state)?.list(&user).await?))
+}
+
+/// `DELETE /admin/v1/consents/{user}/{tool}`, make a user consent again
+async fn revoke_consent(
+    State(state): State<Arc<AppState>>,
+    session: Option<Extension<Session>>,
+    Path((user, tool)): Path<(String, String)>,
+) -> McpResult<Response> {
+    if !consent_store(&state)?.revoke(&user, &tool).await? {
+        return Ok(consent_not_found(&tool));
+    }
+    let by = session.map(|session| session.user_id.clone()).unwrap_or_default();
+    info!("Revoked consent of {} to {} via admin API", user, tool);
+    audit_change(false, &user, &tool, &by);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,467 @@
+//! OIDC browser login for the admin API
+//!
+//! `/admin/login` redirects to the provider with a PKCE challenge and a
+//! state value that is also pinned in a short-lived cookie. The callback
+//! exchanges the code, reads the user's groups from userinfo, maps them to
+//! roles and sets an `HttpOnly` session cookie scoped to `/admin`. The
+//! cookie then authenticates admin requests in place of a bearer token.
+//!
+//! Login sessions are kept in memory; a restart logs everyone out.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::{AuthMethod, OAuthAuth, Session};
+use crate::config::AdminOidcConfig;
+use crate::http_server::middleware::access::ClientIp;
+use crate::utils::errors::{McpError, McpResult};
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use dashmap::DashMap;
+use oauth2::PkceCodeVerifier;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Path of the redirect handler; `redirect_url` must point here
+pub const CALLBACK_PATH: &str = "/admin/oidc/callback";
+
+/// Where users land after login when no `return_to` is given
+const DEFAULT_RETURN_TO: &str = "/admin/v1/sessions";
+
+/// How long a user has to complete the provider's login page
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+struct PendingLogin {
+    verifier: PkceCodeVerifier,
+    return_to: String,
+    started_at: Instant,
+}
+
+/// OIDC login flow and the sessions it creates
+pub struct AdminLogin {
+    oauth: OAuthAuth,
+    config: AdminOidcConfig,
+    pending: DashMap<String, PendingLogin>,
+    sessions: DashMap<String, Session>,
+}
+
+impl AdminLogin {
+    /// Configure the flow from the issuer's discovery document
+    pub async fn from_config(config: &AdminOidcConfig) -> McpResult<Self> {
+        let required = |value: &Option<String>, name: &str| {
+            value
+                .clone()
+                .ok_or_else(|| McpError::ConfigError(format!("admin.oidc.{} is required", name)))
+        };
+        let issuer = required(&config.issuer, "issuer")?;
+        let client_id = required(&config.client_id, "client_id")?;
+        let client_secret = required(&config.client_secret, "client_secret")?;
+        let redirect_url = required(&config.redirect_url, "redirect_url")?;
+        if !redirect_url.ends_with(CALLBACK_PATH) {
+            warn!("admin.oidc.redirect_url should end with {}", CALLBACK_PATH);
+        }
+
+        let oauth = OAuthAuth::from_discovery(client_id, client_secret, issuer)
+            .await?
+            .with_redirect_url(redirect_url)?;
+
+        Ok(Self::new(oauth, config.clone()))
+    }
+
+    fn new(oauth: OAuthAuth, config: AdminOidcConfig) -> Self {
+        Self {
+            oauth,
+            config,
+            pending: DashMap::new(),
+            sessions: DashMap::new(),
+        }
+    }
+
+    /// Login, callback and logout routes
+    pub fn routes(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/admin/login", get(login))
+            .route(CALLBACK_PATH, get(callback))
+            .route("/admin/logout", post(logout))
+            .with_state(self)
+    }
+
+    fn state_cookie_name(&self) -> String {
+        format!("{}_state", self.config.cookie_name)
+    }
+
+    /// `Set-Cookie` value; an empty value with `max_age` 0 clears the cookie
+    fn cookie(&self, name: &str, value: &str, path: &str, max_age: u64) -> McpResult<HeaderValue> {
+        let mut cookie = format!(
+            "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite=Lax",
+            name, value, path, max_age
+        );
+        if self.config.cookie_secure {
+            cookie.push_str("; Secure");
+        }
+        HeaderValue::from_str(&cookie)
+            .map_err(|_| McpError::ConfigError(format!("Invalid admin.oidc.cookie_name: {}", name)))
+    }
+
+    /// Session for the request's cookie, if it is still valid
+    pub fn session(&self, headers: &HeaderMap) -> Option<Session> {
+        let id = cookie_value(headers, &self.config.cookie_name)?;
+        let session = self.sessions.get(id)?.clone();
+        if session.expires_at.is_some_and(|at| at <= Utc::now()) {
+            self.sessions.remove(id);
+            return None;
+        }
+        Some(session)
+    }
+
+    /// Roles granted by the groups in a user's claims
+    fn roles_for(&self, claims: &Value) -> Vec<String> {
+        let mut roles: Vec<String> = claim_values(claims, &self.config.groups_claim)
+            .iter()
+            .filter_map(|group| self.config.role_mappings.get(group))
+            .flatten()
+            .cloned()
+            .collect();
+        roles.sort();
+        roles.dedup();
+        roles
+    }
+
+    fn purge_expired(&self) {
+        self.pending
+            .retain(|_, login| login.started_at.elapsed() < LOGIN_TIMEOUT);
+        let now = Utc::now();
+        self.sessions
+            .retain(|_, session| session.expires_at.is_none_or(|at| at > now));
+    }
+}
+
+/// Whether `name` is a valid cookie name (an RFC 6265 token)
+pub fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+/// Value of a cookie from the `Cookie` headers
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// String values at a dotted claim path; a single string counts as one
+fn claim_values(claims: &Value, path: &str) -> Vec<String> {
+    let value = path
+        .split('.')
+        .try_fold(claims, |value, key| value.get(key));
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Only same-site admin paths, so the login can't be used as an open redirect
+fn safe_return_to(return_to: Option<&str>) -> String {
+    match return_to {
+        Some(path) if path.starts_with("/admin") && !path.contains("//") && !path.contains('\\') => {
+            path.to_string()
+        }
+        _ => DEFAULT_RETURN_TO.to_string(),
+    }
+}
+
+fn random_token() -> McpResult<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| McpError::InternalError("Failed to generate session id".to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn redirect(location: &str) -> Response {
+    (StatusCode::SEE_OTHER, [(header::LOCATION, location.to_string())]).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct LoginQuery {
+    return_to: Option<String>,
+}
+
+/// `GET /admin/login`
+async fn login(
+    State(login): State<Arc<AdminLogin>>,
+    Query(query): Query<LoginQuery>,
+) -> McpResult<Response> {
+    login.purge_expired();
+
+    let (url, state, verifier) = login
+        .oauth
+        .get_authorization_url_with_pkce(login.config.scopes.clone());
+    login.pending.insert(
+        state.secret().clone(),
+        PendingLogin {
+            verifier,
+            return_to: safe_return_to(query.return_to.as_deref()),
+            started_at: Instant::now(),
+        },
+    );
+
+    let mut response = redirect(&url);
+    response.headers_mut().insert(
+        header::SET_COOKIE,
+        login.cookie(
+            &login.state_cookie_name(),
+            state.secret(),
+            CALLBACK_PATH,
+            LOGIN_TIMEOUT.as_secs(),
+        )?,
+    );
+    Ok(response)
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// `GET /admin/oidc/callback`
+async fn callback(
+    State(login): State<Arc<AdminLogin>>,
+    client_ip: Option<Extension<ClientIp>>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> McpResult<Response> {
+    let client_ip = client_ip.map(|Extension(ClientIp(ip))| ip.to_string());
+    let fail = |error: McpError| {
+        let mut event = AuditEvent::new(AuditEventType::AuthFailure)
+            .with_details(json!({ "method": "oidc" }))
+            .with_error(error.to_string());
+        if let Some(ip) = &client_ip {
+            event = event.with_client_ip(ip.clone());
+        }
+        audit::record(event);
+        error
+    };
+
+    if let Some(error) = query.error {
+        return Err(fail(McpError::AuthError(format!(
+            "Login failed: {}",
+            query.error_description.unwrap_or(error)
+        ))));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(fail(McpError::InvalidRequest("Missing code or state".to_string())));
+    };
+
+    // The state must come back to the browser that started the login
+    if cookie_value(&headers, &login.state_cookie_name()) != Some(state.as_str()) {
+        return Err(fail(McpError::AuthError("Login state mismatch".to_string())));
+    }
+    let pending = login
+        .pending
+        .remove(&state)
+        .map(|(_, pending)| pending)
+        .filter(|pending| pending.started_at.elapsed() < LOGIN_TIMEOUT)
+        .ok_or_else(|| fail(McpError::AuthError("Login expired; start again".to_string())))?;
+
+    let tokens = login
+        .oauth
+        .exchange_code_with_pkce(&code, pending.verifier)
+        .await
+        .map_err(&fail)?;
+    let claims = login
+        .oauth
+        .userinfo_claims(&tokens.access_token)
+        .await
+        .map_err(&fail)?;
+
+    // `sub` is the only claim the IdP keeps stable and unique; the username
+    // and email are user-editable, so they are only shown, never trusted
+    let Some(user) = claims.get("sub").and_then(Value::as_str).map(str::to_string) else {
+        return Err(fail(McpError::AuthError("Userinfo has no sub claim".to_string())));
+    };
+    let name = ["preferred_username", "email"]
+        .iter()
+        .find_map(|key| claims.get(key).and_then(Value::as_str))
+        .unwrap_or(&user)
+        .to_string();
+    let roles = login.roles_for(&claims);
+    if roles.is_empty() {
+        let mut event = AuditEvent::new(AuditEventType::AuthorizationFailure)
+            .with_user_id(user.clone())
+            .with_details(json!({ "method": "oidc", "name": name }))
+            .with_error("No role mapped to the user's groups");
+        if let Some(ip) = &client_ip {
+            event = event.with_client_ip(ip.clone());
+        }
+        audit::record(event);
+        return Err(McpError::AuthorizationError(format!(
+            "{} is not in any group with admin access",
+            name
+        )));
+    }
+
+    let id = random_token()?;
+    let ttl = login.config.session_ttl_seconds;
+    login.sessions.insert(
+        id.clone(),
+        Session {
+            user_id: user.clone(),
+            token: id.clone(),
+            scopes: roles.clone(),
+            expires_at: Some(Utc::now() + chrono::Duration::seconds(ttl as i64)),
+            method: AuthMethod::AdminOidc,
+            token_label: None,
+        },
+    );
+
+    info!("Admin login for {} ({}) with roles {:?}", name, user, roles);
+    let mut event = AuditEvent::new(AuditEventType::AuthSuccess)
+        .with_user_id(user)
+        .with_details(json!({ "method": "oidc", "name": name, "roles": roles }));
+    if let Some(ip) = &client_ip {
+        event = event.with_client_ip(ip.clone());
+    }
+    audit::record(event);
+
+    let mut response = redirect(&pending.return_to);
+    let cookies = response.headers_mut();
+    cookies.append(
+        header::SET_COOKIE,
+        login.cookie(&login.config.cookie_name, &id, "/admin", ttl)?,
+    );
+    cookies.append(
+        header::SET_COOKIE,
+        login.cookie(&login.state_cookie_name(), "", CALLBACK_PATH, 0)?,
+    );
+    Ok(response)
+}
+
+/// `POST /admin/logout`
+async fn logout(State(login): State<Arc<AdminLogin>>, headers: HeaderMap) -> McpResult<Response> {
+    if let Some(id) = cookie_value(&headers, &login.config.cookie_name) {
+        login.sessions.remove(id);
+    }
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    response.headers_mut().insert(
+        header::SET_COOKIE,
+        login.cookie(&login.config.cookie_name, "", "/admin", 0)?,
+    );
+    Ok(response)
+}
+
+/// Authenticate admin requests carrying a login cookie
+///
+/// Runs ahead of the bearer token check, which lets requests through
+/// once a session is attached.
+pub async fn admin_session_middleware(
+    State(login): State<Arc<AdminLogin>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(session) = login.session(request.headers()) {
+        request.extensions_mut().insert(session);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn login(role_mappings: &[(&str, &[&str])]) -> AdminLogin {
+        let oauth = OAuthAuth::new("id", "secret", "https://idp.test/auth", "https://idp.test/token").unwrap();
+        AdminLogin::new(
+            oauth,
+            AdminOidcConfig {
+                groups_claim: "realm_access.roles".to_string(),
+                role_mappings: role_mappings
+                    .iter()
+                    .map(|(group, roles)| (group.to_string(), roles.iter().map(|r| r.to_string()).collect()))
+                    .collect::<HashMap<_, _>>(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_group_role_mapping() {
+        let login = login(&[("ops", &["admin"]), ("sre", &["admin", "viewer"])]);
+        let claims = json!({ "realm_access": { "roles": ["sre", "ops", "dev"] } });
+        assert_eq!(login.roles_for(&claims), vec!["admin", "viewer"]);
+        assert!(login.roles_for(&json!({ "groups": ["ops"] })).is_empty());
+        assert_eq!(claim_values(&json!({ "g": "ops" }), "g"), vec!["ops"]);
+    }
+
+    #[test]
+    fn test_session_cookie() {
+        let login = login(&[]);
+        login.sessions.insert(
+            "abc".to_string(),
+            Session {
+                user_id: "alice".to_string(),
+                token: "abc".to_string(),
+                scopes: vec!["admin".to_string()],
+                expires_at: Some(Utc::now() + chrono::Duration::minutes(5)),
+                method: AuthMethod::AdminOidc,
+                token_label: None,
+            },
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, "theme=dark; supermcp_admin=abc".parse().unwrap());
+        assert_eq!(login.session(&headers).unwrap().user_id, "alice");
+
+        headers.insert(header::COOKIE, "supermcp_admin=other".parse().unwrap());
+        assert!(login.session(&headers).is_none());
+
+        let cookie = login.cookie("supermcp_admin", "abc", "/admin", 60).unwrap();
+        assert_eq!(
+            cookie.to_str().unwrap(),
+            "supermcp_admin=abc; Path=/admin; Max-Age=60; HttpOnly; SameSite=Lax; Secure"
+        );
+        assert!(login.cookie("admin\nsession", "abc", "/admin", 60).is_err());
+    }
+
+    #[test]
+    fn test_cookie_name() {
+        assert!(is_cookie_name("supermcp_admin"));
+        assert!(!is_cookie_name(""));
+        assert!(!is_cookie_name("admin session"));
+        assert!(!is_cookie_name("admin;path=/"));
+        assert!(!is_cookie_name("sessão"));
+    }
+
+    #[test]
+    fn test_safe_return_to() {
+        assert_eq!(safe_return_to(Some("/admin/v1/sessions?x=1")), "/admin/v1/sessions?x=1");
+        assert_eq!(safe_return_to(Some("https://evil.test/admin")), DEFAULT_RETURN_TO);
+        assert_eq!(safe_return_to(Some("/admin//evil.test")), DEFAULT_RETURN_TO);
+        assert_eq!(safe_return_to(None), DEFAULT_RETURN_TO);
+    }
+}
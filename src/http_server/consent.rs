@@ -0,0 +1,272 @@
+//! Remembered consent for tools that need approval
+//!
+//! Calls to tools matching `consent.tools` are refused with
+//! `CONSENT_REQUIRED` until the caller consents. A consent is remembered
+//! per user in the state backend under `consents/`, so it holds on every
+//! node sharing the backend and across restarts, and later calls go
+//! straight through. Users manage their own consents under `/consents`;
+//! admins can list and revoke anyone's under `/admin/v1/consents`.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::provider::Session;
+use crate::cli::mcp::glob_match;
+use crate::cloud::StateBackend;
+use crate::config::ConsentConfig;
+use crate::http_server::server::AppState;
+use crate::utils::errors::{McpError, McpResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    routing::{get, put},
+    Extension, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// State backend key prefix for consents
+const KEY_PREFIX: &str = "consents/";
+
+/// Whose consents requests without an authenticated session share
+pub const ANONYMOUS: &str = "anonymous";
+
+/// A user's standing consent to call a tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Consent {
+    pub user: String,
+    /// `server.tool`
+    pub tool: String,
+    pub granted_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Consent {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// User prefix, encoded so any user ID makes a valid key
+fn user_prefix(user: &str) -> String {
+    format!("{}{}/", KEY_PREFIX, URL_SAFE_NO_PAD.encode(user))
+}
+
+fn key(user: &str, tool: &str) -> String {
+    format!("{}{}", user_prefix(user), URL_SAFE_NO_PAD.encode(tool))
+}
+
+/// Consents kept in the state backend
+pub struct ConsentStore {
+    backend: Arc<dyn StateBackend>,
+    tools: Vec<String>,
+    ttl: Option<chrono::Duration>,
+}
+
+impl ConsentStore {
+    pub fn new(backend: Arc<dyn StateBackend>, config: &ConsentConfig) -> Self {
+        Self {
+            backend,
+            tools: config.tools.clone(),
+            ttl: config.ttl_days.map(|days| chrono::Duration::days(days as i64)),
+        }
+    }
+
+    /// Whether calls to `tool` (`server.tool`) need consent
+    pub fn requires_consent(&self, tool: &str) -> bool {
+        self.tools.iter().any(|pattern| glob_match(pattern, tool))
+    }
+
+    /// Let a call through if it needs no consent or the user already gave it
+    pub async fn check(&self, user: Option<&str>, tool: &str) -> McpResult<()> {
+        if !self.requires_consent(tool) {
+            return Ok(());
+        }
+        let user = user.unwrap_or(ANONYMOUS);
+        if self.get(user, tool).await?.is_some() {
+            return Ok(());
+        }
+        Err(McpError::ConsentRequired(tool.to_string()))
+    }
+
+    /// A user's live consent for a tool
+    pub async fn get(&self, user: &str, tool: &str) -> McpResult<Option<Consent>> {
+        let Some(data) = self.backend.get(&key(user, tool)).await? else {
+            return Ok(None);
+        };
+        let consent: Consent = serde_json::from_slice(&data)?;
+        if consent.is_expired(Utc::now()) {
+            self.backend.delete(&key(user, tool)).await?;
+            return Ok(None);
+        }
+        Ok(Some(consent))
+    }
+
+    /// Remember that a user allows a tool
+    pub async fn grant(&self, user: &str, tool: &str) -> McpResult<Consent> {
+        if !self.requires_consent(tool) {
+            return Err(McpError::InvalidRequest(format!("{} does not need consent", tool)));
+        }
+        let now = Utc::now();
+        let consent = Consent {
+            user: user.to_string(),
+            tool: tool.to_string(),
+            granted_at: now,
+            expires_at: self.ttl.map(|ttl| now + ttl),
+        };
+        self.backend
+            .set(&key(user, tool), serde_json::to_vec(&consent)?)
+            .await?;
+        Ok(consent)
+    }
+
+    /// Forget a consent, returning whether there was one
+    pub async fn revoke(&self, user: &str, tool: &str) -> McpResult<bool> {
+        let key = key(user, tool);
+        if self.backend.get(&key).await?.is_none() {
+            return Ok(false);
+        }
+        self.backend.delete(&key).await?;
+        Ok(true)
+    }
+
+    /// A user's live consents
+    pub async fn list(&self, user: &str) -> McpResult<Vec<Consent>> {
+        let now = Utc::now();
+        let mut consents = Vec::new();
+        for key in self.backend.list(&user_prefix(user)).await? {
+            let Some(data) = self.backend.get(&key).await? else {
+                continue;
+            };
+            let consent: Consent = serde_json::from_slice(&data)?;
+            if !consent.is_expired(now) {
+                consents.push(consent);
+            }
+        }
+        consents.sort_by(|a, b| a.tool.cmp(&b.tool));
+        Ok(consents)
+    }
+}
+
+/// Self-service consent routes, mounted behind authentication
+pub fn consent_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/consents", get(list_own))
+        .route("/consents/{tool}", put(grant_own).delete(revoke_own))
+        .with_state(state)
+}
+
+pub(crate) fn consent_store(state: &AppState) -> McpResult<&ConsentStore> {
+    state
+        .consents
+        .as_deref()
+        .ok_or_else(|| McpError::InvalidRequest("Tool consent is disabled".to_string()))
+}
+
+fn user_of(session: &Option<Extension<Session>>) -> String {
+    session
+        .as_ref()
+        .map(|Extension(session)| session.user_id.clone())
+        .unwrap_or_else(|| ANONYMOUS.to_string())
+}
+
+/// Record a consent change in the audit log
+pub(crate) fn audit_change(granted: bool, user: &str, tool: &str, by: &str) {
+    let event_type = if granted {
+        AuditEventType::ConsentGranted
+    } else {
+        AuditEventType::ConsentRevoked
+    };
+    audit::record(
+        AuditEvent::new(event_type)
+            .with_user_id(by.to_string())
+            .with_details(serde_json::json!({ "user": user, "tool": tool })),
+    );
+}
+
+/// `GET /consents`, the caller's remembered consents
+async fn list_own(
+    State(state): State<Arc<AppState>>,
+    session: Option<Extension<Session>>,
+) -> McpResult<Json<Vec<Consent>>> {
+    Ok(Json(consent_store(&state)?.list(&user_of(&session)).await?))
+}
+
+/// `PUT /consents/{tool}`, always allow a tool for the caller
+async fn grant_own(
+    State(state): State<Arc<AppState>>,
+    session: Option<Extension<Session>>,
+    Path(tool): Path<String>,
+) -> McpResult<Json<Consent>> {
+    let user = user_of(&session);
+    let consent = consent_store(&state)?.grant(&user, &tool).await?;
+    info!("{} consented to {}", user, tool);
+    audit_change(true, &user, &tool, &user);
+    Ok(Json(consent))
+}
+
+/// `DELETE /consents/{tool}`, ask again before the next call
+async fn revoke_own(
+    State(state): State<Arc<AppState>>,
+    session: Option<Extension<Session>>,
+    Path(tool): Path<String>,
+) -> McpResult<Response> {
+    let user = user_of(&session);
+    if !consent_store(&state)?.revoke(&user, &tool).await? {
+        return Ok(consent_not_found(&tool));
+    }
+    audit_change(false, &user, &tool, &user);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+pub(crate) fn consent_not_found(tool: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "error": "CONSENT_NOT_FOUND",
+            "message": format!("No consent for {}", tool),
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::InMemoryBackend;
+
+    #[tokio::test]
+    async fn test_remembered_consent() {
+        let store = ConsentStore::new(
+            Arc::new(InMemoryBackend::new()),
+            &ConsentConfig {
+                enabled: true,
+                tools: vec!["filesystem.write_*".to_string()],
+                ttl_days: None,
+            },
+        );
+
+        assert!(store.check(Some("alice"), "filesystem.read_file").await.is_ok());
+        let refused = store.check(Some("alice"), "filesystem.write_file").await;
+        assert!(matches!(refused, Err(McpError::ConsentRequired(_))));
+        assert!(store.grant("alice", "filesystem.read_file").await.is_err());
+
+        store.grant("alice", "filesystem.write_file").await.unwrap();
+        assert!(store.check(Some("alice"), "filesystem.write_file").await.is_ok());
+        // Consent is per user
+        assert!(store.check(Some("bob"), "filesystem.write_file").await.is_err());
+        assert!(store.check(None, "filesystem.write_file").await.is_err());
+
+        let consents = store.list("alice").await.unwrap();
+        assert_eq!(consents.len(), 1);
+        assert_eq!(consents[0].tool, "filesystem.write_file");
+        assert!(store.list("bob").await.unwrap().is_empty());
+
+        assert!(store.revoke("alice", "filesystem.write_file").await.unwrap());
+        assert!(!store.revoke("alice", "filesystem.write_file").await.unwrap());
+        assert!(store.check(Some("alice"), "filesystem.write_file").await.is_err());
+    }
+}
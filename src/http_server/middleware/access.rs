@@ -0,0 +1,322 @@
+//! Client IP resolution and network access control
+//!
+//! The client IP is the TCP peer, unless the peer is a trusted proxy: then
+//! it comes from the PROXY protocol header or `X-Forwarded-For`. The
+//! resolved address is stored as [`ClientIp`] for the rate limiter and
+//! audit log, and checked against the allow/deny lists.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::config::AccessConfig;
+use crate::http_server::proxy_protocol;
+use crate::utils::errors::{McpError, McpResult};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ipnet::IpNet;
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tower_governor::{key_extractor::KeyExtractor, GovernorError};
+use tracing::{debug, warn};
+
+/// How long a trusted proxy has to send its PROXY header
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolved address of the client behind any trusted proxies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Parse a CIDR block or a single address
+fn parse_network(value: &str) -> McpResult<IpNet> {
+    value
+        .parse::<IpNet>()
+        .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| McpError::ConfigError(format!("Invalid network '{}'", value)))
+}
+
+fn parse_networks(values: &[String]) -> McpResult<Vec<IpNet>> {
+    values.iter().map(|v| parse_network(v.trim())).collect()
+}
+
+fn contains(networks: &[IpNet], ip: IpAddr) -> bool {
+    networks.iter().any(|net| net.contains(&ip))
+}
+
+/// Parsed `[server.access]` settings
+#[derive(Debug, Clone, Default)]
+pub struct AccessControl {
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+    proxy_protocol: bool,
+}
+
+impl AccessControl {
+    pub fn from_config(config: &AccessConfig) -> McpResult<Self> {
+        Ok(Self {
+            allow: parse_networks(&config.allow)?,
+            deny: parse_networks(&config.deny)?,
+            trusted_proxies: parse_networks(&config.trusted_proxies)?,
+            proxy_protocol: config.proxy_protocol,
+        })
+    }
+
+    pub fn proxy_protocol(&self) -> bool {
+        self.proxy_protocol
+    }
+
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        contains(&self.trusted_proxies, ip.to_canonical())
+    }
+
+    /// Whether a client may connect; deny entries win over allow entries
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        !contains(&self.deny, ip) && (self.allow.is_empty() || contains(&self.allow, ip))
+    }
+
+    /// Client IP for a request from `peer`
+    ///
+    /// `X-Forwarded-For` is walked from the right, skipping trusted
+    /// proxies; the first untrusted hop is the client. Headers from
+    /// untrusted peers are ignored, since anyone can set them. `None` when
+    /// a hop before the first untrusted one isn't an address: the client
+    /// is unknown, and must not be taken for the proxy that forwarded it.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> Option<IpAddr> {
+        let mut client = peer.to_canonical();
+        if !self.is_trusted_proxy(client) {
+            return Some(client);
+        }
+
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+
+        for hop in hops.into_iter().rev() {
+            client = hop.parse::<IpAddr>().ok()?.to_canonical();
+            if !self.is_trusted_proxy(client) {
+                break;
+            }
+        }
+        Some(client)
+    }
+
+    /// Client address for a new connection, consuming the PROXY header
+    /// when a trusted proxy is required to send one
+    pub async fn resolve_peer<S: AsyncRead + Unpin>(
+        &self,
+        stream: &mut S,
+        peer: SocketAddr,
+    ) -> std::io::Result<SocketAddr> {
+        if !self.proxy_protocol || !self.is_trusted_proxy(peer.ip()) {
+            return Ok(peer);
+        }
+
+        match tokio::time::timeout(PROXY_HEADER_TIMEOUT, proxy_protocol::read_header(stream)).await {
+            Ok(Ok(source)) => Ok(source.unwrap_or(peer)),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "PROXY protocol: header not received",
+            )),
+        }
+    }
+}
+
+/// Resolve the client IP and enforce the allow/deny lists
+///
+/// Runs outside the rate limiting and auth layers so they see the
+/// resolved address.
+pub async fn access_control_middleware(
+    State(access): State<Arc<AccessControl>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(peer) = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+    else {
+        return next.run(request).await;
+    };
+
+    let Some(client) = access.client_ip(peer, request.headers()) else {
+        debug!("Refused request via {} with an unparseable X-Forwarded-For", peer);
+        audit::record(
+            AuditEvent::new(AuditEventType::AuthorizationFailure)
+                .with_client_ip(peer.to_string())
+                .with_details(json!({ "path": request.uri().path() }))
+                .with_error("Unparseable X-Forwarded-For from a trusted proxy"),
+        );
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "INVALID_FORWARDED_FOR",
+                "message": "X-Forwarded-For must list IP addresses",
+            })),
+        )
+            .into_response();
+    };
+    if !access.is_allowed(client) {
+        debug!("Refused request from {}", client);
+        audit::record(
+            AuditEvent::new(AuditEventType::AuthorizationFailure)
+                .with_client_ip(client.to_string())
+                .with_details(json!({ "path": request.uri().path() }))
+                .with_error("Client address not allowed"),
+        );
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "ADDRESS_NOT_ALLOWED",
+                "message": "Requests from this address are not allowed",
+            })),
+        )
+            .into_response();
+    }
+
+    request.extensions_mut().insert(ClientIp(client));
+    next.run(request).await
+}
+
+/// Rate limiter key: the resolved [`ClientIp`], or the peer address
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIpKeyExtractor;
+
+impl KeyExtractor for ClientIpKeyExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &axum::http::Request<T>) -> Result<Self::Key, GovernorError> {
+        req.extensions()
+            .get::<ClientIp>()
+            .map(|ClientIp(ip)| *ip)
+            .or_else(|| {
+                req.extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            })
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+/// Build the access control, logging a warning for risky settings
+pub fn create_access_control(config: &AccessConfig) -> McpResult<Arc<AccessControl>> {
+    let access = AccessControl::from_config(config)?;
+    if access.proxy_protocol && access.trusted_proxies.is_empty() {
+        warn!("server.access.proxy_protocol has no effect without trusted_proxies");
+    }
+    Ok(Arc::new(access))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(allow: &[&str], deny: &[&str], trusted: &[&str]) -> AccessControl {
+        let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        AccessControl::from_config(&AccessConfig {
+            allow: strings(allow),
+            deny: strings(deny),
+            trusted_proxies: strings(trusted),
+            proxy_protocol: false,
+        })
+        .unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_allow_deny() {
+        let access = access(&["10.0.0.0/8", "192.168.1.5"], &["10.1.0.0/16"], &[]);
+        assert!(access.is_allowed(ip("10.2.3.4")));
+        assert!(access.is_allowed(ip("::ffff:192.168.1.5")));
+        assert!(!access.is_allowed(ip("10.1.2.3")));
+        assert!(!access.is_allowed(ip("8.8.8.8")));
+        assert!(AccessControl::from_config(&AccessConfig {
+            deny: vec!["not-a-network".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_forwarded_for_only_from_trusted_proxies() {
+        let access = access(&[], &[], &["10.0.0.0/8"]);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.2.3.4, 203.0.113.9, 10.0.0.2".parse().unwrap());
+
+        assert_eq!(access.client_ip(ip("10.0.0.1"), &headers), Some(ip("203.0.113.9")));
+        assert_eq!(access.client_ip(ip("198.51.100.1"), &headers), Some(ip("198.51.100.1")));
+    }
+
+    #[test]
+    fn test_garbage_forwarded_for_not_taken_for_proxy() {
+        let access = access(&[], &[], &["10.0.0.0/8"]);
+        let mut headers = HeaderMap::new();
+
+        // Behind the trusted hops, the client is unknown
+        headers.insert("x-forwarded-for", "garbage, 10.0.0.3".parse().unwrap());
+        assert_eq!(access.client_ip(ip("10.0.0.1"), &headers), None);
+
+        // Garbage left of the first untrusted hop is never reached
+        headers.insert("x-forwarded-for", "garbage, 203.0.113.9, 10.0.0.3".parse().unwrap());
+        assert_eq!(access.client_ip(ip("10.0.0.1"), &headers), Some(ip("203.0.113.9")));
+
+        // Untrusted peers' headers are ignored altogether
+        headers.insert("x-forwarded-for", "garbage".parse().unwrap());
+        assert_eq!(access.client_ip(ip("198.51.100.1"), &headers), Some(ip("198.51.100.1")));
+    }
+
+    #[tokio::test]
+    async fn test_garbage_forwarded_for_refused() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(access(&[], &[], &["10.0.0.0/8"])),
+                access_control_middleware,
+            ));
+        let request = Request::get("/")
+            .header("x-forwarded-for", "not-an-ip")
+            .extension(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_peer() {
+        let access = AccessControl {
+            proxy_protocol: true,
+            ..access(&[], &[], &["127.0.0.1"])
+        };
+        let (mut client, mut server) = tokio::io::duplex(256);
+        tokio::io::AsyncWriteExt::write_all(&mut client, b"PROXY TCP4 203.0.113.7 127.0.0.1 4000 80\r\n")
+            .await
+            .unwrap();
+
+        let peer = access
+            .resolve_peer(&mut server, "127.0.0.1:5000".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(peer, "203.0.113.7:4000".parse().unwrap());
+
+        let direct = "198.51.100.1:5000".parse().unwrap();
+        assert_eq!(access.resolve_peer(&mut server, direct).await.unwrap(), direct);
+    }
+}
@@ -10,8 +10,11 @@ use axum::{
 use serde_json::json;
 use std::sync::Arc;
 
+use crate::audit::{self, AuditEvent, AuditEventType};
 use crate::auth::provider::{AuthProvider, Session};
+use crate::http_server::middleware::access::ClientIp;
 use crate::utils::errors::McpError;
+use crate::utils::request_trace;
 
 /// Extract authentication token from request headers
 fn extract_token(request: &Request) -> Option<String> {
@@ -42,10 +45,19 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Response {
+    // Already authenticated by an earlier layer (admin login cookie)
+    if request.extensions().get::<Session>().is_some() {
+        return next.run(request).await;
+    }
+
     // Try to extract and validate token
     match extract_token(&request) {
         Some(token) => {
-            match state.provider.validate_token(&token).await {
+            let validated = {
+                let _phase = request_trace::phase("auth");
+                state.provider.validate_token(&token).await
+            };
+            match validated {
                 Ok(session) => {
                     // Store session in request extensions for downstream handlers
                     request.extensions_mut().insert(session);
@@ -53,6 +65,14 @@ pub async fn auth_middleware(
                 }
                 Err(e) => {
                     if state.required {
+                        let mut event = AuditEvent::new(AuditEventType::AuthFailure)
+                            .with_details(json!({ "path": request.uri().path() }))
+                            .with_error(e.to_string());
+                        if let Some(ClientIp(ip)) = request.extensions().get::<ClientIp>() {
+                            event = event.with_client_ip(ip.to_string());
+                        }
+                        audit::record(event);
+
                         let error = McpError::AuthError(format!("Invalid token: {}", e));
                         error.into_response()
                     } else {
@@ -0,0 +1,25 @@
+//! Load shedding
+//!
+//! Counts MCP requests in flight and refuses new ones with 503 and
+//! `Retry-After` while the proxy is past its `[backpressure]` high
+//! watermark, rather than queueing them without bound.
+
+use crate::core::backpressure::Backpressure;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+/// Admit the request or shed it
+pub async fn backpressure_middleware(
+    State(backpressure): State<Arc<Backpressure>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match backpressure.admit() {
+        Ok(_in_flight) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,156 @@
+//! Response compression middleware
+//!
+//! Negotiates gzip, brotli or zstd with the client via `Accept-Encoding`.
+//! Small bodies and already-compressed media are sent as-is, and individual
+//! routes can opt out entirely.
+
+use crate::config::CompressionConfig;
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    http::{header, Response as HttpResponse},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use tower_http::compression::{CompressionLayer, Predicate};
+
+/// Response extension marking a response that must not be compressed
+#[derive(Debug, Clone, Copy)]
+pub struct SkipCompression;
+
+/// Decides which responses are worth compressing
+#[derive(Debug, Clone)]
+pub struct CompressionPredicate {
+    min_size_bytes: u64,
+    include_event_streams: bool,
+}
+
+impl CompressionPredicate {
+    pub fn new(config: &CompressionConfig) -> Self {
+        Self {
+            min_size_bytes: config.min_size_bytes,
+            include_event_streams: config.include_event_streams,
+        }
+    }
+}
+
+impl Predicate for CompressionPredicate {
+    fn should_compress<B>(&self, response: &HttpResponse<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        if response.extensions().get::<SkipCompression>().is_some() {
+            return false;
+        }
+
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+
+        // Event streams are only compressed on request, since the encoder
+        // buffers output and delays individual events.
+        if content_type.starts_with("text/event-stream") && !self.include_event_streams {
+            return false;
+        }
+
+        // Media types that are already compressed gain nothing
+        if (content_type.starts_with("image/") && content_type != "image/svg+xml")
+            || content_type.starts_with("audio/")
+            || content_type.starts_with("video/")
+            || content_type.starts_with("application/grpc")
+        {
+            return false;
+        }
+
+        let size = response.body().size_hint().exact().or_else(|| {
+            response
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+        });
+
+        // Streams of unknown length are always worth compressing
+        size.is_none_or(|size| size >= self.min_size_bytes)
+    }
+}
+
+/// Create the compression layer from configuration
+pub fn create_compression_layer(
+    config: &CompressionConfig,
+) -> CompressionLayer<CompressionPredicate> {
+    CompressionLayer::new()
+        .gzip(config.gzip)
+        .br(config.br)
+        .zstd(config.zstd)
+        .compress_when(CompressionPredicate::new(config))
+}
+
+/// Mark responses for opted-out route prefixes so the compression layer
+/// leaves them untouched
+pub async fn compression_opt_out_middleware(
+    State(excluded_paths): State<Arc<Vec<String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let excluded = excluded_paths
+        .iter()
+        .any(|prefix| request.uri().path().starts_with(prefix.as_str()));
+
+    let mut response = next.run(request).await;
+    if excluded {
+        response.extensions_mut().insert(SkipCompression);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn predicate() -> CompressionPredicate {
+        CompressionPredicate::new(&CompressionConfig::default())
+    }
+
+    fn response(content_type: &str, body: String) -> HttpResponse<Body> {
+        HttpResponse::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_small_body_not_compressed() {
+        assert!(!predicate().should_compress(&response("application/json", "{}".to_string())));
+    }
+
+    #[test]
+    fn test_large_json_compressed() {
+        let body = "x".repeat(4096);
+        assert!(predicate().should_compress(&response("application/json", body)));
+    }
+
+    #[test]
+    fn test_event_stream_skipped_by_default() {
+        let body = "x".repeat(4096);
+        assert!(!predicate().should_compress(&response("text/event-stream", body.clone())));
+
+        let predicate = CompressionPredicate::new(&CompressionConfig {
+            include_event_streams: true,
+            ..Default::default()
+        });
+        assert!(predicate.should_compress(&response("text/event-stream", body)));
+    }
+
+    #[test]
+    fn test_opt_out_marker() {
+        let body = "x".repeat(4096);
+        let mut response = response("application/json", body);
+        response.extensions_mut().insert(SkipCompression);
+        assert!(!predicate().should_compress(&response));
+    }
+}
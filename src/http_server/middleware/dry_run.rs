@@ -0,0 +1,22 @@
+//! Dry-run header
+//!
+//! Requests sent with `X-SuperMCP-Dry-Run: true` have their tool calls
+//! answered with a dry-run report instead of reaching the tool.
+
+use crate::core::dry_run::{is_enabled, with_dry_run, DRY_RUN_HEADER};
+use axum::{extract::Request, middleware::Next, response::Response};
+
+/// Run the request in dry-run mode when the header asks for it
+pub async fn dry_run_middleware(request: Request, next: Next) -> Response {
+    let dry_run = request
+        .headers()
+        .get(DRY_RUN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(is_enabled);
+
+    if dry_run {
+        with_dry_run(next.run(request)).await
+    } else {
+        next.run(request).await
+    }
+}
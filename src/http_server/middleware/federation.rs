@@ -0,0 +1,306 @@
+//! Loop detection and payload encryption for federated super-mcp instances
+//!
+//! Requests from another instance list the instances they passed through
+//! in `X-SuperMCP-Via`. Seeing our own ID there means the topology has a
+//! cycle; the request is refused with 508 instead of being forwarded again.
+//!
+//! Requests carrying `X-SuperMCP-Key` come from an instance with a pinned
+//! key; their bodies are opened before the other layers see them, and the
+//! responses sealed frame by frame on the way out. With
+//! `require_encryption`, every other request is refused.
+
+use crate::config::FederationConfig;
+use crate::transport::envelope::{
+    decode_key, Binding, Envelope, FederationKey, FrameSealer, ReplayCache, BINDING_HEADER,
+    KEY_HEADER, SEALED_CONTENT_TYPE,
+};
+use crate::transport::supermcp::{instance_id, parse_via, with_via_chain, MAX_HOPS, VIA_HEADER};
+use crate::utils::errors::McpResult;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Refuse looping or overly deep federated requests, and remember the
+/// chain for requests this instance forwards
+pub async fn federation_middleware(request: Request, next: Next) -> Response {
+    let chain = request
+        .headers()
+        .get(VIA_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(parse_via)
+        .unwrap_or_default();
+
+    let refusal = if chain.iter().any(|id| id == instance_id()) {
+        Some(("FEDERATION_LOOP", "Request already passed through this instance"))
+    } else if chain.len() >= MAX_HOPS {
+        Some(("FEDERATION_TOO_DEEP", "Request crossed too many federated instances"))
+    } else {
+        None
+    };
+    if let Some((error, message)) = refusal {
+        warn!("{} on {} (via {})", message, request.uri().path(), chain.join(","));
+        return (
+            StatusCode::LOOP_DETECTED,
+            Json(json!({ "error": error, "message": message })),
+        )
+            .into_response();
+    }
+
+    with_via_chain(chain, next.run(request)).await
+}
+
+/// This instance's key and the pinned keys of its federated peers
+pub struct EnvelopeState {
+    key: Arc<FederationKey>,
+    /// Public key to peer name
+    peers: HashMap<[u8; 32], String>,
+    require: bool,
+    max_body_bytes: usize,
+    replays: ReplayCache,
+}
+
+impl EnvelopeState {
+    pub fn new(
+        key: Arc<FederationKey>,
+        config: &FederationConfig,
+        max_body_bytes: usize,
+    ) -> McpResult<Self> {
+        let peers = config
+            .peers
+            .iter()
+            .map(|peer| Ok((decode_key(&peer.public_key)?, peer.name.clone())))
+            .collect::<McpResult<_>>()?;
+        Ok(Self {
+            key,
+            peers,
+            require: config.require_encryption,
+            max_body_bytes,
+            replays: ReplayCache::default(),
+        })
+    }
+}
+
+fn refuse(status: StatusCode, error: &str, message: &str) -> Response {
+    (status, Json(json!({ "error": error, "message": message }))).into_response()
+}
+
+/// Open sealed requests from pinned peers and seal their responses
+pub async fn envelope_middleware(
+    State(state): State<Arc<EnvelopeState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(sender) = request.headers().get(KEY_HEADER) else {
+        // Any client can leave out X-SuperMCP-Via, so it can't tell
+        // federated requests apart
+        if state.require {
+            return refuse(
+                StatusCode::FORBIDDEN,
+                "FEDERATION_ENCRYPTION_REQUIRED",
+                "Requests must be encrypted by a federation peer",
+            );
+        }
+        return next.run(request).await;
+    };
+
+    let pinned = sender
+        .to_str()
+        .ok()
+        .and_then(|key| decode_key(key).ok())
+        .and_then(|key| state.peers.get(&key).map(|name| (key, name)));
+    let Some((sender, peer)) = pinned else {
+        warn!("Refused encrypted request from an unpinned key");
+        return refuse(
+            StatusCode::FORBIDDEN,
+            "FEDERATION_PEER_NOT_PINNED",
+            "The sender's key is not a configured federation peer",
+        );
+    };
+    let envelope = Arc::new(Envelope::server(state.key.clone(), sender));
+
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), |p| p.as_str().to_string());
+    let binding = request
+        .headers()
+        .get(BINDING_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Binding::parse(v, request.method().as_str(), &path).ok());
+    let Some(binding) = binding else {
+        return refuse(StatusCode::BAD_REQUEST, "INVALID_ENVELOPE", "Request has no valid envelope binding");
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let Ok(sealed) = axum::body::to_bytes(body, state.max_body_bytes).await else {
+        return refuse(StatusCode::PAYLOAD_TOO_LARGE, "PAYLOAD_TOO_LARGE", "Request body is too large");
+    };
+    let plaintext = match envelope.open_request(&binding, &sealed) {
+        Ok(plaintext) => plaintext,
+        Err(e) => {
+            debug!("Failed to open request from {}: {}", peer, e);
+            return refuse(StatusCode::BAD_REQUEST, "INVALID_ENVELOPE", "Request could not be decrypted");
+        }
+    };
+    // Only authenticated bindings enter the cache
+    if let Err(e) = state.replays.check(&binding) {
+        warn!("Refused request from {}: {}", peer, e);
+        return refuse(StatusCode::FORBIDDEN, "FEDERATION_REPLAY", &e.to_string());
+    }
+    let body = if plaintext.is_empty() {
+        parts.headers.remove(header::CONTENT_TYPE);
+        Body::empty()
+    } else {
+        parts
+            .headers
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Body::from(plaintext)
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    // Seal each chunk as it comes, so streamed responses keep streaming
+    let (mut parts, body) = response.into_parts();
+    let sealer = FrameSealer::new(envelope, binding, parts.status.as_u16());
+    let peer = peer.clone();
+    let frames = futures::stream::unfold(Some((body.into_data_stream(), sealer)), move |state| {
+        let peer = peer.clone();
+        async move {
+            let (mut body, mut sealer) = state?;
+            let (frame, next) = match body.next().await {
+                Some(Ok(chunk)) => (sealer.seal(&chunk, false), Some((body, sealer))),
+                Some(Err(e)) => {
+                    // Ending without a last frame tells the peer it was cut short
+                    debug!("Failed to read response for {}: {}", peer, e);
+                    return None;
+                }
+                None => (sealer.seal(&[], true), None),
+            };
+            match frame {
+                Ok(frame) => Some((Ok::<_, std::io::Error>(frame), next)),
+                Err(e) => {
+                    warn!("Failed to seal response for {}: {}", peer, e);
+                    None
+                }
+            }
+        }
+    });
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(SEALED_CONTENT_TYPE));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from_stream(frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FederationPeerConfig;
+    use axum::{routing::get, routing::post, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_loop_is_refused() {
+        let app = Router::new()
+            .route("/tools", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(federation_middleware));
+
+        let request = |via: String| {
+            Request::builder()
+                .uri("/tools")
+                .header(VIA_HEADER, via)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("other".to_string())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request(format!("other,{}", instance_id())))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::LOOP_DETECTED);
+
+        let deep = (0..MAX_HOPS).map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        let response = app.oneshot(request(deep)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::LOOP_DETECTED);
+    }
+
+    #[tokio::test]
+    async fn test_sealed_requests_from_pinned_peers() {
+        let server_key = Arc::new(FederationKey::generate().unwrap());
+        let client_key = Arc::new(FederationKey::generate().unwrap());
+        let config = FederationConfig {
+            peers: vec![FederationPeerConfig {
+                name: "edge".to_string(),
+                public_key: client_key.public_base64(),
+            }],
+            require_encryption: true,
+            ..Default::default()
+        };
+        let state = Arc::new(EnvelopeState::new(server_key.clone(), &config, 1024).unwrap());
+        let app = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn_with_state(state, envelope_middleware));
+
+        let client = Envelope::client(client_key.clone(), server_key.public_key());
+        let sealed_request = |binding: &Binding| {
+            Request::post("/echo")
+                .header(KEY_HEADER, client_key.public_base64())
+                .header(BINDING_HEADER, binding.header_value())
+                .body(Body::from(client.seal_request(binding, b"hello").unwrap()))
+                .unwrap()
+        };
+        let binding = Binding::new("POST", "/echo");
+        let response = app.clone().oneshot(sealed_request(&binding)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(client.open_response(&binding, 200, &body).unwrap(), b"hello");
+
+        // The same sealed request can't be sent twice, or to another path
+        let response = app.clone().oneshot(sealed_request(&binding)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .clone()
+            .oneshot(sealed_request(&Binding::new("POST", "/other")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Unpinned keys and plaintext federated requests are refused
+        let stranger = FederationKey::generate().unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/echo")
+                    .header(KEY_HEADER, stranger.public_base64())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        for via in [Some("edge"), None] {
+            let mut request = Request::post("/echo");
+            if let Some(via) = via {
+                request = request.header(VIA_HEADER, via);
+            }
+            let response = app.clone().oneshot(request.body(Body::from("hello")).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+}
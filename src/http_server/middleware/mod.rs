@@ -1,17 +1,50 @@
 //! HTTP server middleware
 
+pub mod access;
 pub mod auth;
+pub mod backpressure;
+pub mod compression;
+pub mod dry_run;
+pub mod federation;
+pub mod priority;
 pub mod rate_limit;
+pub mod rbac;
+pub mod read_replica;
+pub mod readonly;
+pub mod request_id;
 pub mod security;
+pub mod session_routing;
 pub mod size_limit;
+pub mod slow_requests;
 
+pub use access::{
+    access_control_middleware, create_access_control, AccessControl, ClientIp,
+    ClientIpKeyExtractor,
+};
 pub use auth::{
     auth_middleware, scope_validation_middleware, AuthMiddlewareState, ScopeValidationState,
     get_session,
 };
-pub use rate_limit::{rate_limit_middleware, RateLimitConfig, RateLimitManager, create_rate_limit_layer};
+pub use backpressure::backpressure_middleware;
+pub use compression::{
+    compression_opt_out_middleware, create_compression_layer, CompressionPredicate,
+    SkipCompression,
+};
+pub use dry_run::dry_run_middleware;
+pub use federation::{envelope_middleware, federation_middleware, EnvelopeState};
+pub use priority::{priority_middleware, PriorityState};
+pub use rate_limit::{
+    rate_limit_event_middleware, rate_limit_middleware, RateLimitConfig, RateLimitManager,
+    RateLimitStatus, create_rate_limit_layer, replenish_interval,
+};
+pub use rbac::rbac_middleware;
+pub use read_replica::{read_replica_middleware, ReplicaMiss};
+pub use readonly::{readonly_middleware, READ_ONLY_METHODS};
+pub use request_id::request_id_middleware;
 pub use security::{
     security_headers_middleware, SecurityHeadersConfig, FrameOptions, HstsConfig,
     XssProtection, ReferrerPolicy, permissive_cors, restrictive_cors,
 };
+pub use session_routing::{session_routing_middleware, SessionRoutingState};
 pub use size_limit::{size_limit_middleware, SizeLimitConfig, SizeLimitError};
+pub use slow_requests::slow_request_middleware;
@@ -0,0 +1,38 @@
+//! Request priority classes
+//!
+//! Places each request in a `[scheduling]` class from the caller's scopes
+//! and the priority header, for the schedulers in front of process pools.
+
+use crate::core::scheduler::{with_priority, PriorityClasses};
+use crate::http_server::middleware::auth::get_session;
+use axum::{
+    extract::{Request, State},
+    http::HeaderName,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Priority header name and configured classes
+pub struct PriorityState {
+    pub header: HeaderName,
+    pub classes: PriorityClasses,
+}
+
+/// Run the request under its priority class
+pub async fn priority_middleware(
+    State(state): State<Arc<PriorityState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let requested = request
+        .headers()
+        .get(&state.header)
+        .and_then(|v| v.to_str().ok());
+    let scopes = get_session(&request)
+        .map(|session| session.scopes.as_slice())
+        .unwrap_or_default();
+    let class = state.classes.classify(requested, scopes).to_string();
+
+    with_priority(class, next.run(request)).await
+}
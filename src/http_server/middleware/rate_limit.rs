@@ -1,9 +1,11 @@
 //! Rate limiting middleware using tower-governor
 
 use axum::{
-    extract::{ConnectInfo, Request},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+    Json,
 };
 use governor::{
     clock::DefaultClock,
@@ -14,7 +16,10 @@ use governor::{
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tower_governor::key_extractor::SmartIpKeyExtractor;
+use crate::http_server::middleware::access::{ClientIp, ClientIpKeyExtractor};
+use crate::core::protocol::JsonRpcError;
+use crate::utils::errors::McpError;
+use serde_json::{json, Value};
 
 /// Rate limiter configuration
 type GovernorRateLimiter =
@@ -108,18 +113,161 @@ pub async fn rate_limit_middleware(
     next.run(request).await
 }
 
-/// Create a tower-governor layer for Axum
+/// Token bucket state reported with each rate-limited response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Bucket size
+    pub limit: u64,
+    /// Requests left before the client is throttled
+    pub remaining: u64,
+    /// Seconds until the next request is allowed; only set on 429
+    pub retry_after_secs: Option<u64>,
+    /// Seconds until the bucket is full again
+    pub reset_secs: u64,
+}
+
+impl RateLimitStatus {
+    /// Read the governor's headers, with `interval` the time to replenish
+    /// one token
+    pub fn from_headers(headers: &HeaderMap, interval: Duration, throttled: bool) -> Option<Self> {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+        };
+        let limit = number("x-ratelimit-limit")?;
+        let remaining = number("x-ratelimit-remaining").unwrap_or(0).min(limit);
+
+        // The governor rounds the wait down to whole seconds
+        let retry_after_secs = throttled.then(|| number("x-ratelimit-after").unwrap_or(0) + 1);
+        let refill = interval.as_secs_f64() * (limit - remaining) as f64;
+        let reset_secs = (refill.ceil() as u64).max(retry_after_secs.unwrap_or(0));
+
+        Some(Self {
+            limit,
+            remaining,
+            retry_after_secs,
+            reset_secs,
+        })
+    }
+
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", self.limit.into());
+        headers.insert("x-ratelimit-remaining", self.remaining.into());
+        headers.insert("x-ratelimit-reset", self.reset_secs.into());
+        if let Some(retry_after) = self.retry_after_secs {
+            headers.insert(header::RETRY_AFTER, retry_after.into());
+            headers.insert("x-ratelimit-after", retry_after.into());
+        }
+    }
+
+    /// Error data for a throttled request: the `QUOTA_EXCEEDED` taxonomy
+    /// fields plus the bucket state
+    pub fn error_data(&self, error: &McpError) -> Value {
+        let mut data = error.data();
+        data["limit"] = json!(self.limit);
+        data["remaining"] = json!(self.remaining);
+        data["retry_after_secs"] = json!(self.retry_after_secs);
+        data["reset_secs"] = json!(self.reset_secs);
+        data
+    }
+}
+
+/// Body for a throttled request: a JSON-RPC error on the MCP endpoints,
+/// the usual error object elsewhere
+fn quota_exceeded_body(path: &str, status: Option<&RateLimitStatus>) -> Value {
+    let error = McpError::QuotaExceeded("Rate limit exceeded".to_string());
+    let data = match status {
+        Some(status) => status.error_data(&error),
+        None => error.data(),
+    };
+
+    if path == "/mcp" || path.starts_with("/mcp/") {
+        let mut rpc_error = JsonRpcError::from(&error);
+        rpc_error.data = Some(data);
+        json!({ "jsonrpc": "2.0", "id": null, "error": rpc_error })
+    } else {
+        json!({
+            "error": error.error_code(),
+            "message": error.to_string(),
+            "retryable": true,
+            "data": data,
+        })
+    }
+}
+
+/// Report requests rejected by the rate limiter as quota violations, and
+/// complete the rate limit headers
+///
+/// Must wrap the governor layer so it sees the 429 responses it produces.
+/// Their plain-text body is replaced with a `QUOTA_EXCEEDED` error carrying
+/// the same `Retry-After` and `X-RateLimit-*` values as the headers.
+/// `interval` is the time the bucket takes to replenish one request.
+pub async fn rate_limit_event_middleware(
+    State(interval): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let client_ip = request.extensions().get::<ClientIp>().map(|ClientIp(ip)| ip.to_string());
+    let mut response = next.run(request).await;
+
+    let throttled = response.status() == StatusCode::TOO_MANY_REQUESTS;
+    let status = RateLimitStatus::from_headers(response.headers(), interval, throttled);
+
+    if !throttled {
+        if let Some(status) = status {
+            status.apply(response.headers_mut());
+        }
+        return response;
+    }
+
+    let mut audit_event = crate::audit::AuditEvent::new(crate::audit::AuditEventType::RateLimitHit)
+        .with_details(serde_json::json!({ "path": path }));
+    if let Some(ip) = &client_ip {
+        audit_event = audit_event.with_client_ip(ip);
+    }
+    crate::audit::record(audit_event);
+
+    crate::events::emit(
+        crate::events::Event::new(crate::events::EventKind::QuotaViolation, "Rate limit exceeded")
+            .with_details(serde_json::json!({ "path": path, "client_ip": client_ip })),
+    );
+
+    let mut replaced = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(quota_exceeded_body(&path, status.as_ref())),
+    )
+        .into_response();
+    for (name, value) in response.headers() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            replaced.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+    if let Some(status) = status {
+        status.apply(replaced.headers_mut());
+    }
+    replaced
+}
+
+/// Time for the bucket to replenish one request
+pub fn replenish_interval(config: &RateLimitConfig) -> Duration {
+    let requests_per_minute = config.requests_per_minute.max(1);
+    let nanos_per_request = (60_000_000_000f64 / requests_per_minute as f64).max(1.0);
+    Duration::from_nanos(nanos_per_request.round() as u64)
+}
+
+/// Create a tower-governor layer for Axum, keyed by the resolved client IP
 pub fn create_rate_limit_layer(
     config: &RateLimitConfig,
-) -> tower_governor::GovernorLayer<SmartIpKeyExtractor, StateInformationMiddleware> {
-    let requests_per_minute = config.requests_per_minute.max(1);
+) -> tower_governor::GovernorLayer<ClientIpKeyExtractor, StateInformationMiddleware> {
     let burst_size = config.burst_size.max(1);
-    let nanos_per_request = (60_000_000_000f64 / requests_per_minute as f64).max(1.0);
-    let period = Duration::from_nanos(nanos_per_request.round() as u64);
+    let period = replenish_interval(config);
 
     let mut builder = GovernorConfigBuilder::default();
     builder.period(period).burst_size(burst_size);
-    let mut builder = builder.key_extractor(SmartIpKeyExtractor).use_headers();
+    let mut builder = builder.key_extractor(ClientIpKeyExtractor).use_headers();
 
     let governor = builder
         .finish()
@@ -153,4 +301,35 @@ mod tests {
         assert_eq!(config.requests_per_minute, 100);
         assert_eq!(config.burst_size, 10);
     }
+
+    #[test]
+    fn test_rate_limit_status_from_headers() {
+        let interval = replenish_interval(&RateLimitConfig {
+            requests_per_minute: 30,
+            burst_size: 5,
+        });
+        assert_eq!(interval, Duration::from_secs(2));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", 5.into());
+        headers.insert("x-ratelimit-remaining", 3.into());
+        let status = RateLimitStatus::from_headers(&headers, interval, false).unwrap();
+        assert_eq!(status.retry_after_secs, None);
+        assert_eq!(status.reset_secs, 4);
+
+        // Throttled: the governor reports a wait rounded down to 0s
+        headers.insert("x-ratelimit-remaining", 0.into());
+        headers.insert("x-ratelimit-after", 0.into());
+        let status = RateLimitStatus::from_headers(&headers, interval, true).unwrap();
+        assert_eq!(status.retry_after_secs, Some(1));
+        assert_eq!(status.reset_secs, 10);
+
+        let body = quota_exceeded_body("/mcp", Some(&status));
+        assert_eq!(body["id"], Value::Null);
+        assert_eq!(body["error"]["data"]["code"], "QUOTA_EXCEEDED");
+        assert_eq!(body["error"]["data"]["retry_after_secs"], 1);
+        let body = quota_exceeded_body("/tools", Some(&status));
+        assert_eq!(body["error"], "QUOTA_EXCEEDED");
+        assert_eq!(body["data"]["reset_secs"], 10);
+    }
 }
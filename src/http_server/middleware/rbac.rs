@@ -0,0 +1,95 @@
+//! Role checks for the admin API
+//!
+//! Replaces the `admin.required_scope` check when `[rbac]` is enabled.
+//! Each admin request needs the permission its method and path call for.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::provider::Session;
+use crate::auth::rbac::Rbac;
+use crate::config::Permission;
+use crate::http_server::middleware::access::ClientIp;
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Admin paths whose changes manage upstream servers
+const SERVER_PATHS: &[&str] = &["/admin/v1/servers/", "/admin/v1/rollouts/", "/admin/v1/drift/"];
+
+/// Permission an admin request needs
+pub fn admin_permission(method: &Method, path: &str) -> Permission {
+    if matches!(*method, Method::GET | Method::HEAD) {
+        Permission::AdminRead
+    } else if path.starts_with("/admin/v1/servers/") && path.trim_end_matches('/').ends_with("/elevation") {
+        // Managing a server doesn't extend to loosening its sandbox
+        Permission::SandboxElevate
+    } else if SERVER_PATHS.iter().any(|prefix| path.starts_with(prefix)) {
+        Permission::ServersManage
+    } else {
+        Permission::AdminWrite
+    }
+}
+
+/// Refuse admin requests whose session lacks the permission they need
+pub async fn rbac_middleware(
+    State(rbac): State<Arc<Rbac>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let permission = admin_permission(request.method(), request.uri().path());
+    let session = request.extensions().get::<Session>();
+    if session.is_some_and(|session| rbac.allows(session, permission)) {
+        return next.run(request).await;
+    }
+
+    let mut event = AuditEvent::new(AuditEventType::AuthorizationFailure)
+        .with_details(json!({ "path": request.uri().path(), "permission": permission.as_str() }))
+        .with_error("Missing role permission");
+    if let Some(session) = session {
+        event = event.with_user_id(session.user_id.clone());
+    }
+    if let Some(ClientIp(ip)) = request.extensions().get::<ClientIp>() {
+        event = event.with_client_ip(ip.to_string());
+    }
+    audit::record(event);
+
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "INSUFFICIENT_ROLE",
+            "message": format!("No role grants {}", permission.as_str()),
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_permission() {
+        assert_eq!(admin_permission(&Method::GET, "/admin/v1/config"), Permission::AdminRead);
+        assert_eq!(admin_permission(&Method::PUT, "/admin/v1/config"), Permission::AdminWrite);
+        assert_eq!(
+            admin_permission(&Method::POST, "/admin/v1/servers/files/restart"),
+            Permission::ServersManage
+        );
+        assert_eq!(admin_permission(&Method::DELETE, "/admin/v1/sessions/1"), Permission::AdminWrite);
+        for method in [Method::POST, Method::DELETE] {
+            assert_eq!(
+                admin_permission(&method, "/admin/v1/servers/files/elevation"),
+                Permission::SandboxElevate
+            );
+        }
+        assert_eq!(
+            admin_permission(&Method::GET, "/admin/v1/servers/files/elevation"),
+            Permission::AdminRead
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Serving list requests on a read replica
+//!
+//! With `cluster.read_replica`, only `POST /mcp` list requests are handled
+//! here, from the results other nodes shared (see
+//! [`crate::cache::replication::SharedLists`]). Everything else, and lists
+//! nobody has shared yet, is forwarded to the session's owner or the
+//! leader, so the replica never needs an upstream server.
+
+use crate::cache::replication::SHARED_LIST_METHODS;
+use crate::http_server::middleware::session_routing::{forward, SessionRoutingState, FORWARDED_BY_HEADER};
+use crate::http_server::session::MCP_SESSION_ID_HEADER;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{request::Parts, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Marks a response from a replica that had no shared result to serve
+#[derive(Debug, Clone, Copy)]
+pub struct ReplicaMiss;
+
+#[derive(Deserialize)]
+struct MethodProbe {
+    method: String,
+}
+
+fn is_shared_list(parts: &Parts, body: &[u8]) -> bool {
+    parts.method == Method::POST
+        && parts.uri.path() == "/mcp"
+        && serde_json::from_slice::<MethodProbe>(body)
+            .is_ok_and(|probe| SHARED_LIST_METHODS.contains(&probe.method.as_str()))
+}
+
+fn unavailable(message: &str) -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({ "error": "NO_STATEFUL_NODE", "message": message })),
+    )
+        .into_response()
+}
+
+/// Forward to the session's owner, or the leader outside a session
+async fn forward_to_target(state: &SessionRoutingState, parts: &Parts, body: Bytes) -> Response {
+    let session = parts
+        .headers
+        .get(MCP_SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let Some((node, url)) = state.ring.forward_target(session) else {
+        return unavailable("No node is available to handle the request");
+    };
+    debug!("Replica forwarding {} {} to {}", parts.method, parts.uri.path(), node);
+    match forward(state, &url, parts, body).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Node {} unreachable from replica: {}", node, e);
+            unavailable(&format!("Node {} is unreachable", node))
+        }
+    }
+}
+
+/// Answer shared list requests locally and forward the rest
+pub async fn read_replica_middleware(
+    State(state): State<Arc<SessionRoutingState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.headers().contains_key(FORWARDED_BY_HEADER) {
+        return next.run(request).await;
+    }
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, state.max_request_bytes).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    if is_shared_list(&parts, &body) {
+        let response = next
+            .run(Request::from_parts(parts.clone(), Body::from(body.clone())))
+            .await;
+        if response.extensions().get::<ReplicaMiss>().is_none() {
+            return response;
+        }
+    }
+    forward_to_target(&state, &parts, body).await
+}
@@ -0,0 +1,143 @@
+//! Read-only enforcement for anonymous clients
+//!
+//! Used by `auth.type = "anonymous_readonly"`. Requests without an
+//! authenticated session may read catalogs but not call tools or change
+//! proxy state.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::auth::provider::Session;
+use crate::http_server::middleware::access::ClientIp;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+/// JSON-RPC methods anonymous clients may call
+pub const READ_ONLY_METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "prompts/list",
+];
+
+#[derive(Deserialize)]
+struct MethodProbe {
+    #[serde(default)]
+    method: Option<String>,
+}
+
+fn is_read_only(method: &str) -> bool {
+    READ_ONLY_METHODS.contains(&method) || method.starts_with("notifications/")
+}
+
+/// First method in a JSON-RPC message or batch that isn't read-only
+///
+/// Messages without a method (responses to server requests) are allowed;
+/// bodies that don't parse are refused.
+pub fn read_only_violation(body: &[u8]) -> Option<String> {
+    let probes = match body.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => serde_json::from_slice::<Vec<MethodProbe>>(body).ok(),
+        _ => serde_json::from_slice::<MethodProbe>(body).ok().map(|p| vec![p]),
+    };
+    let Some(probes) = probes else {
+        return Some("<unparseable>".to_string());
+    };
+    probes
+        .into_iter()
+        .filter_map(|probe| probe.method)
+        .find(|method| !is_read_only(method))
+}
+
+fn forbidden(request: &Request, what: &str) -> Response {
+    let mut event = AuditEvent::new(AuditEventType::AuthorizationFailure)
+        .with_details(json!({ "path": request.uri().path(), "denied": what }))
+        .with_error("Anonymous clients are read-only");
+    if let Some(ClientIp(ip)) = request.extensions().get::<ClientIp>() {
+        event = event.with_client_ip(ip.to_string());
+    }
+    audit::record(event);
+
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "READ_ONLY_ACCESS",
+            "message": format!("Anonymous clients may not call {}", what),
+        })),
+    )
+        .into_response()
+}
+
+/// Hold requests without a session to read-only operations
+///
+/// Runs inside the auth layer (so authenticated sessions are visible) and
+/// inside the size limit layer (so the body is already bounded). The state
+/// is the largest body to buffer.
+pub async fn readonly_middleware(
+    State(max_body_bytes): State<usize>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<Session>().is_some() {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path();
+    let is_mcp = path == "/mcp" || path.starts_with("/mcp/");
+    match *request.method() {
+        Method::GET | Method::HEAD => return next.run(request).await,
+        // Ending one's own MCP session changes nothing shared
+        Method::DELETE if path == "/mcp" => return next.run(request).await,
+        Method::POST if is_mcp => {}
+        _ => {
+            let what = format!("{} {}", request.method(), path);
+            return forbidden(&request, &what);
+        }
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, max_body_bytes).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let request = Request::from_parts(parts, Body::empty());
+            return forbidden(&request, "oversized requests");
+        }
+    };
+    let request = Request::from_parts(parts, Body::from(bytes.clone()));
+
+    match read_only_violation(&bytes) {
+        Some(method) => forbidden(&request, &method),
+        None => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_violation() {
+        assert_eq!(read_only_violation(br#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#), None);
+        assert_eq!(
+            read_only_violation(br#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#),
+            None
+        );
+        assert_eq!(read_only_violation(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#), None);
+        assert_eq!(
+            read_only_violation(br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{}}"#).as_deref(),
+            Some("tools/call")
+        );
+        assert_eq!(
+            read_only_violation(br#"[{"method":"resources/list"},{"method":"resources/read"}]"#).as_deref(),
+            Some("resources/read")
+        );
+        assert!(read_only_violation(b"not json").is_some());
+    }
+}
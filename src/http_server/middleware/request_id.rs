@@ -0,0 +1,74 @@
+//! Request correlation IDs
+//!
+//! Takes the client's `X-Request-Id` (or generates one), runs the request
+//! inside a tracing span carrying it, and returns it in the response.
+
+use crate::core::request_id::{with_correlation_id, SharedRequestIdGenerator, REQUEST_ID_HEADER};
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info_span, Instrument};
+
+/// Attach a correlation ID to the request and its response
+pub async fn request_id_middleware(
+    State(generator): State<SharedRequestIdGenerator>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let id = generator.correlation_id(
+        request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+    );
+    let span = info_span!("request", request_id = %id);
+
+    let mut response = with_correlation_id(id.as_str(), next.run(request).instrument(span)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::request_id::current_correlation_id;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|| async { current_correlation_id().unwrap_or_default().to_string() }),
+            )
+            .layer(middleware::from_fn_with_state(
+                SharedRequestIdGenerator::with_uuid(),
+                request_id_middleware,
+            ))
+    }
+
+    #[tokio::test]
+    async fn test_client_id_propagates() {
+        let request = Request::builder()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "trace-abc")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "trace-abc");
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"trace-abc");
+    }
+
+    #[tokio::test]
+    async fn test_generated_when_missing() {
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert!(!response.headers()[REQUEST_ID_HEADER].is_empty());
+    }
+}
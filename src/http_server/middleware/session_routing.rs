@@ -0,0 +1,136 @@
+//! Forwarding session requests to the node owning the session
+//!
+//! With `cluster.session_routing`, a request whose `Mcp-Session-Id`
+//! hashes to another node is proxied there, response streams included.
+//! Forwarded requests carry `X-SuperMCP-Forwarded-By` and are always
+//! handled by the node receiving them. When the owner cannot be reached
+//! the request is served locally from the shared session state.
+
+use crate::cloud::hash_ring::SessionRing;
+use crate::http_server::middleware::access::ClientIp;
+use crate::http_server::session::MCP_SESSION_ID_HEADER;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, request::Parts, HeaderMap, HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Header naming the node a request was forwarded by
+pub const FORWARDED_BY_HEADER: &str = "x-supermcp-forwarded-by";
+
+/// Headers that only apply to one connection
+const HOP_BY_HOP: &[HeaderName] = &[
+    header::CONNECTION,
+    header::HOST,
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+    header::TE,
+    header::TRAILER,
+];
+
+/// Session ring plus the client requests are forwarded with
+pub struct SessionRoutingState {
+    pub(crate) ring: Arc<SessionRing>,
+    client: reqwest::Client,
+    pub(crate) max_request_bytes: usize,
+}
+
+impl SessionRoutingState {
+    pub fn new(ring: Arc<SessionRing>, max_request_bytes: usize) -> Self {
+        // Nodes talk directly, never through the corporate proxy; no
+        // timeout since SSE responses stay open
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap_or_default();
+        Self {
+            ring,
+            client,
+            max_request_bytes,
+        }
+    }
+}
+
+fn copy_headers(from: &HeaderMap, to: &mut HeaderMap) {
+    for (name, value) in from {
+        if !HOP_BY_HOP.contains(name) {
+            to.append(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Send a request to another node, streaming its response back
+pub(crate) async fn forward(
+    state: &SessionRoutingState,
+    url: &str,
+    parts: &Parts,
+    body: Bytes,
+) -> Result<Response, reqwest::Error> {
+    let path = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let target = format!("{}{}", url.trim_end_matches('/'), path);
+
+    let mut headers = HeaderMap::new();
+    copy_headers(&parts.headers, &mut headers);
+    if let Ok(value) = state.ring.node_id().parse() {
+        headers.insert(FORWARDED_BY_HEADER, value);
+    }
+    if let Some(ClientIp(ip)) = parts.extensions.get::<ClientIp>() {
+        if let Ok(value) = ip.to_string().parse() {
+            headers.insert("x-forwarded-for", value);
+        }
+    }
+
+    let upstream = state
+        .client
+        .request(parts.method.clone(), &target)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await?;
+    let mut response = Response::builder().status(upstream.status());
+    if let Some(headers) = response.headers_mut() {
+        copy_headers(upstream.headers(), headers);
+    }
+    Ok(response
+        .body(Body::from_stream(upstream.bytes_stream()))
+        .unwrap_or_else(|_| StatusCode::BAD_GATEWAY.into_response()))
+}
+
+/// Forward the request if another node owns its session
+pub async fn session_routing_middleware(
+    State(state): State<Arc<SessionRoutingState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.headers().contains_key(FORWARDED_BY_HEADER) {
+        return next.run(request).await;
+    }
+    let owner = request
+        .headers()
+        .get(MCP_SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|id| state.ring.owner(id));
+    let Some((node, url)) = owner else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, state.max_request_bytes).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    debug!("Forwarding {} {} to session owner {}", parts.method, parts.uri.path(), node);
+    match forward(&state, &url, &parts, body.clone()).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Session owner {} unreachable, serving locally: {}", node, e);
+            next.run(Request::from_parts(parts, Body::from(body))).await
+        }
+    }
+}
@@ -1,15 +1,25 @@
 //! Request/Response size limit middleware
+//!
+//! JSON bodies are buffered and checked for nesting depth and array
+//! length before anything parses them, then held to the size limits of
+//! the JSON-RPC method they call.
 
+use crate::config::{LimitsConfig, MethodLimitsConfig};
 use axum::{
+    body::Body,
     extract::{Request, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use futures::StreamExt;
+use serde::Deserialize;
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Size limit configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SizeLimitConfig {
     /// Maximum request body size in bytes
     pub max_request_size: usize,
@@ -17,6 +27,12 @@ pub struct SizeLimitConfig {
     pub max_response_size: usize,
     /// Maximum header size in bytes
     pub max_header_size: usize,
+    /// Maximum nesting of JSON arrays and objects
+    pub max_json_depth: usize,
+    /// Maximum elements in a JSON array
+    pub max_array_length: usize,
+    /// Per-method overrides of the request and response limits
+    pub methods: HashMap<String, MethodLimitsConfig>,
 }
 
 impl Default for SizeLimitConfig {
@@ -25,16 +41,62 @@ impl Default for SizeLimitConfig {
             max_request_size: 10 * 1024 * 1024,  // 10 MB
             max_response_size: 50 * 1024 * 1024, // 50 MB
             max_header_size: 64 * 1024,          // 64 KB
+            max_json_depth: 64,
+            max_array_length: 10_000,
+            methods: HashMap::new(),
         }
     }
 }
 
+impl From<&LimitsConfig> for SizeLimitConfig {
+    fn from(config: &LimitsConfig) -> Self {
+        Self {
+            max_request_size: config.max_request_bytes,
+            max_response_size: config.max_response_bytes,
+            max_header_size: config.max_header_bytes,
+            max_json_depth: config.max_json_depth,
+            max_array_length: config.max_array_length,
+            methods: config.methods.clone(),
+        }
+    }
+}
+
+impl SizeLimitConfig {
+    /// Largest request any method may send; bodies are read up to this
+    fn read_limit(&self) -> usize {
+        self.methods
+            .values()
+            .filter_map(|m| m.max_request_bytes)
+            .fold(self.max_request_size, usize::max)
+    }
+
+    /// Request and response limits for a set of methods (one, or a batch)
+    ///
+    /// A batch gets the tightest request limit and the loosest response
+    /// limit of its methods.
+    fn limits_for(&self, methods: &[String]) -> (usize, usize) {
+        if methods.is_empty() {
+            return (self.max_request_size, self.max_response_size);
+        }
+        methods.iter().fold((usize::MAX, 0), |(request, response), method| {
+            let limits = self.methods.get(method);
+            (
+                request.min(limits.and_then(|l| l.max_request_bytes).unwrap_or(self.max_request_size)),
+                response.max(limits.and_then(|l| l.max_response_bytes).unwrap_or(self.max_response_size)),
+            )
+        })
+    }
+}
+
 /// Size limit error
 #[derive(Debug)]
 pub enum SizeLimitError {
     RequestTooLarge { size: usize, limit: usize },
     ResponseTooLarge { size: usize, limit: usize },
     HeadersTooLarge { size: usize, limit: usize },
+    JsonTooDeep { limit: usize },
+    ArrayTooLong { length: usize, limit: usize },
+    UnreadableBody(String),
 }
 
 impl std::fmt::Display for SizeLimitError {
@@ -49,6 +111,13 @@ impl std::fmt::Display for SizeLimitError {
             SizeLimitError::HeadersTooLarge { size, limit } => {
                 write!(f, "Headers too large: {} bytes (limit: {} bytes)", size, limit)
             }
+            SizeLimitError::JsonTooDeep { limit } => {
+                write!(f, "JSON nested deeper than {} levels", limit)
+            }
+            SizeLimitError::ArrayTooLong { length, limit } => {
+                write!(f, "JSON array too long: {} elements (limit: {})", length, limit)
+            }
+            SizeLimitError::UnreadableBody(e) => write!(f, "Failed to read request body: {}", e),
         }
     }
 }
@@ -67,6 +136,15 @@ impl IntoResponse for SizeLimitError {
             SizeLimitError::HeadersTooLarge { .. } => {
                 (StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, "HEADERS_TOO_LARGE", self.to_string())
             }
+            SizeLimitError::JsonTooDeep { .. } => {
+                (StatusCode::BAD_REQUEST, "JSON_TOO_DEEP", self.to_string())
+            }
+            SizeLimitError::ArrayTooLong { .. } => {
+                (StatusCode::BAD_REQUEST, "ARRAY_TOO_LONG", self.to_string())
+            }
+            SizeLimitError::UnreadableBody(_) => {
+                (StatusCode::BAD_REQUEST, "UNREADABLE_BODY", self.to_string())
+            }
         };
 
         let body = axum::Json(json!({
@@ -78,9 +156,86 @@ impl IntoResponse for SizeLimitError {
     }
 }
 
+/// Reject JSON nested deeper than `max_depth` or with an array longer
+/// than `max_array_length`
+///
+/// A single pass over the bytes, so abusive input is refused without
+/// building anything. Malformed JSON is left for the parser to report.
+pub fn check_json_structure(
+    bytes: &[u8],
+    max_depth: usize,
+    max_array_length: usize,
+) -> Result<(), SizeLimitError> {
+    // Element count of each open array; `None` for objects
+    let mut stack: Vec<Option<usize>> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in bytes {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                stack.push((byte == b'[').then_some(1));
+                if stack.len() > max_depth {
+                    return Err(SizeLimitError::JsonTooDeep { limit: max_depth });
+                }
+            }
+            b']' | b'}' => {
+                stack.pop();
+            }
+            b',' => {
+                if let Some(Some(count)) = stack.last_mut() {
+                    *count += 1;
+                    if *count > max_array_length {
+                        return Err(SizeLimitError::ArrayTooLong {
+                            length: *count,
+                            limit: max_array_length,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Just the method of a JSON-RPC message
+#[derive(Deserialize)]
+struct MethodProbe {
+    #[serde(default)]
+    method: Option<String>,
+}
+
+/// Methods called by a JSON-RPC message or batch
+fn rpc_methods(bytes: &[u8]) -> Vec<String> {
+    let probes = match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => serde_json::from_slice::<Vec<MethodProbe>>(bytes).unwrap_or_default(),
+        _ => serde_json::from_slice::<MethodProbe>(bytes).into_iter().collect(),
+    };
+    probes.into_iter().filter_map(|p| p.method).collect()
+}
+
+fn content_length(headers: &axum::http::HeaderMap) -> Option<usize> {
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
 /// Size limit middleware
 pub async fn size_limit_middleware(
-    State(config): State<SizeLimitConfig>,
+    State(config): State<Arc<SizeLimitConfig>>,
     request: Request,
     next: Next,
 ) -> Result<Response, SizeLimitError> {
@@ -99,35 +254,64 @@ pub async fn size_limit_middleware(
     }
 
     // Check content-length header if present
-    if let Some(content_length) = request.headers().get("content-length") {
-        if let Ok(length_str) = content_length.to_str() {
-            if let Ok(length) = length_str.parse::<usize>() {
-                if length > config.max_request_size {
-                    return Err(SizeLimitError::RequestTooLarge {
-                        size: length,
-                        limit: config.max_request_size,
-                    });
-                }
-            }
+    let read_limit = config.read_limit();
+    if let Some(length) = content_length(request.headers()) {
+        if length > read_limit {
+            return Err(SizeLimitError::RequestTooLarge {
+                size: length,
+                limit: read_limit,
+            });
         }
     }
 
+    let is_json = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("json"));
+
+    let (request, methods) = if is_json {
+        let (parts, body) = request.into_parts();
+        let mut stream = body.into_data_stream();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| SizeLimitError::UnreadableBody(e.to_string()))?;
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > read_limit {
+                return Err(SizeLimitError::RequestTooLarge {
+                    size: bytes.len(),
+                    limit: read_limit,
+                });
+            }
+        }
+
+        check_json_structure(&bytes, config.max_json_depth, config.max_array_length)?;
+        let methods = rpc_methods(&bytes);
+        let (request_limit, _) = config.limits_for(&methods);
+        if bytes.len() > request_limit {
+            return Err(SizeLimitError::RequestTooLarge {
+                size: bytes.len(),
+                limit: request_limit,
+            });
+        }
+        (Request::from_parts(parts, Body::from(bytes)), methods)
+    } else {
+        (request, Vec::new())
+    };
+
     // Process request and check response size
     let response = next.run(request).await;
 
     // Check response body size
     // Note: This is a simplified check. In production, you'd want to check the actual body size.
     // For now, we rely on the content-length header.
-    if let Some(content_length) = response.headers().get("content-length") {
-        if let Ok(length_str) = content_length.to_str() {
-            if let Ok(length) = length_str.parse::<usize>() {
-                if length > config.max_response_size {
-                    return Err(SizeLimitError::ResponseTooLarge {
-                        size: length,
-                        limit: config.max_response_size,
-                    });
-                }
-            }
+    let (_, response_limit) = config.limits_for(&methods);
+    if let Some(length) = content_length(response.headers()) {
+        if length > response_limit {
+            return Err(SizeLimitError::ResponseTooLarge {
+                size: length,
+                limit: response_limit,
+            });
         }
     }
 
@@ -146,6 +330,42 @@ mod tests {
         assert_eq!(config.max_header_size, 64 * 1024);
     }
 
+    #[test]
+    fn test_json_structure() {
+        assert!(check_json_structure(br#"{"a": [1, 2, {"b": "[[[,,,"}]}"#, 3, 3).is_ok());
+        assert!(matches!(
+            check_json_structure(b"[[[[1]]]]", 3, 10),
+            Err(SizeLimitError::JsonTooDeep { limit: 3 })
+        ));
+        assert!(matches!(
+            check_json_structure(br#"{"ids": [1, 2, 3, 4]}"#, 3, 3),
+            Err(SizeLimitError::ArrayTooLong { length: 4, limit: 3 })
+        ));
+        assert!(check_json_structure(br#"{"a": 1, "b": 2, "c": 3, "d": 4}"#, 3, 3).is_ok());
+    }
+
+    #[test]
+    fn test_method_limits() {
+        let mut config = SizeLimitConfig::default();
+        config.methods.insert(
+            "initialize".to_string(),
+            MethodLimitsConfig { max_request_bytes: Some(1024), max_response_bytes: None },
+        );
+        config.methods.insert(
+            "resources/read".to_string(),
+            MethodLimitsConfig { max_request_bytes: None, max_response_bytes: Some(100 << 20) },
+        );
+
+        let methods = rpc_methods(br#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#);
+        assert_eq!(config.limits_for(&methods), (1024, 50 << 20));
+
+        let methods = rpc_methods(br#"[{"method": "initialize"}, {"method": "resources/read"}]"#);
+        assert_eq!(methods.len(), 2);
+        assert_eq!(config.limits_for(&methods), (1024, 100 << 20));
+
+        assert_eq!(config.limits_for(&rpc_methods(b"not json")), (10 << 20, 50 << 20));
+    }
+
     #[test]
     fn test_size_limit_error_display() {
         let err = SizeLimitError::RequestTooLarge {
@@ -0,0 +1,57 @@
+//! Slow request tracing
+//!
+//! Runs each request inside a [`RequestTrace`]; requests at or over the
+//! threshold are logged with their phase breakdown and kept in the
+//! [`SlowRequestLog`] served by the admin API, under the request's
+//! correlation ID.
+
+use crate::core::request_id::current_correlation_id;
+use crate::utils::request_trace::{folded, traced, RequestTrace, SlowRequest, SlowRequestLog};
+use axum::{extract::Request, extract::State, middleware::Next, response::Response};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Trace the request and keep it if it was slow
+pub async fn slow_request_middleware(
+    State(log): State<Arc<SlowRequestLog>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let id = current_correlation_id().map_or_else(|| uuid::Uuid::new_v4().to_string(), |id| id.to_string());
+    let trace = Arc::new(RequestTrace::new(id));
+    let started_at = Utc::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+
+    let response = traced(trace.clone(), next.run(request)).await;
+
+    let total = trace.elapsed();
+    if total >= log.threshold() {
+        let phases = trace.phases();
+        let breakdown = phases
+            .iter()
+            .map(|p| format!("{}={:.1}ms", p.stack, p.duration_ms))
+            .collect::<Vec<_>>()
+            .join(" ");
+        warn!(
+            request_id = %trace.id(),
+            "Slow request {} {} took {:.1}ms: {}",
+            method,
+            path,
+            total.as_secs_f64() * 1000.0,
+            breakdown
+        );
+        log.record(SlowRequest {
+            id: trace.id().to_string(),
+            method,
+            path,
+            status: response.status().as_u16(),
+            started_at,
+            total_ms: total.as_secs_f64() * 1000.0,
+            folded: folded(&phases, total),
+            phases,
+        });
+    }
+    response
+}
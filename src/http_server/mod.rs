@@ -1,5 +1,15 @@
+pub mod acme;
+pub mod admin;
+pub mod admin_oidc;
+pub mod consent;
+pub mod named_pipe;
+pub mod nats;
+pub mod proxy_protocol;
 pub mod routes;
 pub mod server;
+pub mod session;
+pub mod spiffe;
 pub mod middleware;
+pub mod tls;
 
 pub use server::HttpServer;
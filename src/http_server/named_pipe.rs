@@ -0,0 +1,67 @@
+//! Named pipe listener
+//!
+//! Serves the router on a Windows named pipe for local clients where unix
+//! sockets are unavailable and binding TCP ports is restricted. Only local
+//! clients are accepted.
+
+use crate::utils::errors::{McpError, McpResult};
+use axum::Router;
+
+/// Serve the router on a named pipe until the listener fails
+#[cfg(windows)]
+pub async fn serve_named_pipe(path: &str, app: Router) -> McpResult<()> {
+    use crate::transport::named_pipe::normalize_pipe_path;
+    use axum::extract::ConnectInfo;
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder as ConnectionBuilder;
+    use std::net::SocketAddr;
+    use tokio::net::windows::named_pipe::ServerOptions;
+    use tower::Service;
+    use tracing::debug;
+
+    let path = normalize_pipe_path(path);
+    let mut listener = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&path)
+        .map_err(|e| McpError::ConfigError(format!("Failed to create pipe {}: {}", path, e)))?;
+
+    // Pipe clients have no socket address; report them as loopback
+    let peer = SocketAddr::from(([127, 0, 0, 1], 0));
+
+    loop {
+        listener.connect().await?;
+
+        // Create the next instance before handing this one off so clients
+        // never find the pipe missing
+        let connected = std::mem::replace(
+            &mut listener,
+            ServerOptions::new().reject_remote_clients(true).create(&path)?,
+        );
+
+        let app = app.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(
+                move |mut request: hyper::Request<hyper::body::Incoming>| {
+                    request.extensions_mut().insert(ConnectInfo(peer));
+                    app.clone().call(request)
+                },
+            );
+
+            if let Err(e) = ConnectionBuilder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(connected), service)
+                .await
+            {
+                debug!("Named pipe connection closed with error: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(not(windows))]
+pub async fn serve_named_pipe(path: &str, _app: Router) -> McpResult<()> {
+    Err(McpError::ConfigError(format!(
+        "Named pipe listener is only available on Windows: {}",
+        path
+    )))
+}
@@ -0,0 +1,115 @@
+//! NATS listener
+//!
+//! Answers JSON-RPC requests published on a NATS subject by running them
+//! through the router as `POST /mcp`. All instances subscribe in one queue
+//! group, so NATS spreads requests across replicas.
+
+use crate::config::NatsListenerConfig;
+use crate::utils::errors::{McpError, McpResult};
+use axum::Router;
+
+/// Largest response body relayed back over NATS
+#[cfg(feature = "nats")]
+const MAX_REPLY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Serve the router on the configured subject until the subscription ends
+#[cfg(feature = "nats")]
+pub async fn serve_nats(config: &NatsListenerConfig, app: Router) -> McpResult<()> {
+    use futures::StreamExt;
+    use tracing::{debug, info};
+
+    let client = async_nats::connect(config.url.as_str())
+        .await
+        .map_err(|e| McpError::TransportError(format!("Failed to connect to {}: {}", config.url, e)))?;
+    let mut subscriber = client
+        .queue_subscribe(config.subject.clone(), config.queue_group.clone())
+        .await
+        .map_err(|e| McpError::TransportError(format!("Failed to subscribe to {}: {}", config.subject, e)))?;
+
+    info!(
+        "Serving MCP over NATS on {} (queue group {})",
+        config.subject, config.queue_group
+    );
+
+    while let Some(message) = subscriber.next().await {
+        let client = client.clone();
+        let app = app.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_message(client, app, message).await {
+                debug!("NATS request failed: {}", e);
+            }
+        });
+    }
+
+    Err(McpError::TransportError(format!(
+        "NATS subscription to {} ended",
+        config.subject
+    )))
+}
+
+#[cfg(feature = "nats")]
+async fn handle_message(
+    client: async_nats::Client,
+    app: Router,
+    message: async_nats::Message,
+) -> McpResult<()> {
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::{header, Method, Request};
+    use std::net::SocketAddr;
+    use tower::Service;
+
+    // NATS clients have no socket address; report them as unspecified
+    let peer = SocketAddr::from(([0, 0, 0, 0], 0));
+
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri("/mcp")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, "application/json");
+    if let Some(headers) = &message.headers {
+        for (name, values) in headers.iter() {
+            for value in values {
+                request = request.header(AsRef::<str>::as_ref(name), value.as_str());
+            }
+        }
+    }
+    let mut request = request
+        .body(Body::from(message.payload))
+        .map_err(|e| McpError::InvalidRequest(e.to_string()))?;
+    request.extensions_mut().insert(ConnectInfo(peer));
+
+    let response = match app.clone().call(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    };
+
+    // Notifications are published without a reply subject
+    let Some(reply) = message.reply else {
+        return Ok(());
+    };
+
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("Http-Status", response.status().as_str());
+    for (name, value) in response.headers() {
+        if let Ok(value) = value.to_str() {
+            headers.insert(name.as_str(), value);
+        }
+    }
+    let body = axum::body::to_bytes(response.into_body(), MAX_REPLY_BYTES)
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))?;
+
+    client
+        .publish_with_headers(reply, headers, body)
+        .await
+        .map_err(|e| McpError::TransportError(e.to_string()))
+}
+
+#[cfg(not(feature = "nats"))]
+pub async fn serve_nats(config: &NatsListenerConfig, _app: Router) -> McpResult<()> {
+    Err(McpError::ConfigError(format!(
+        "NATS listener on {} needs supermcp built with the nats feature",
+        config.url
+    )))
+}
@@ -0,0 +1,185 @@
+//! PROXY protocol (v1 and v2) headers
+//!
+//! Load balancers that terminate TCP prepend a header carrying the
+//! original client address. Only connections from trusted proxies are
+//! expected to send one; see [`AccessControl::resolve_peer`].
+//!
+//! [`AccessControl::resolve_peer`]: crate::http_server::middleware::AccessControl::resolve_peer
+
+use crate::http_server::middleware::AccessControl;
+use crate::utils::errors::McpResult;
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder as ConnectionBuilder;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::TcpListener;
+use tower::Service;
+use tracing::{debug, warn};
+
+/// Binary header signature (v2)
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Longest v1 header including CRLF
+const V1_MAX_LEN: usize = 107;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("PROXY protocol: {}", message))
+}
+
+/// Read a PROXY header from the start of a connection
+///
+/// Returns the client address it carries, or `None` for headers that
+/// don't carry one (`LOCAL`, `UNKNOWN`, non-IP families). Exactly the
+/// header bytes are consumed.
+pub async fn read_header<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    // Both versions are at least 12 bytes long ("PROXY UNKNOWN\r\n" is 15)
+    let mut prefix = [0u8; 12];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == V2_SIGNATURE {
+        read_v2(stream).await
+    } else if prefix.starts_with(b"PROXY ") {
+        read_v1(stream, &prefix).await
+    } else {
+        Err(invalid("missing header"))
+    }
+}
+
+async fn read_v1<R: AsyncRead + Unpin>(stream: &mut R, prefix: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(invalid("v1 header too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2]).map_err(|_| invalid("v1 header is not ASCII"))?;
+    let parts: Vec<&str> = line.split(' ').collect();
+    match parts.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", "TCP4" | "TCP6", source, _, port, _] => {
+            let ip: IpAddr = source.parse().map_err(|_| invalid("bad v1 source address"))?;
+            let port: u16 = port.parse().map_err(|_| invalid("bad v1 source port"))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(invalid("malformed v1 header")),
+    }
+}
+
+async fn read_v2<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let version_command = stream.read_u8().await?;
+    let family = stream.read_u8().await?;
+    let len = stream.read_u16().await? as usize;
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body).await?;
+
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported version"));
+    }
+    // LOCAL connections (health checks from the proxy itself) keep the peer
+    if version_command & 0x0f == 0 {
+        return Ok(None);
+    }
+
+    match family >> 4 {
+        // AF_INET
+        1 if body.len() >= 12 => {
+            let ip = Ipv4Addr::new(body[0], body[1], body[2], body[3]);
+            let port = u16::from_be_bytes([body[8], body[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        // AF_INET6
+        2 if body.len() >= 36 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&body[..16]);
+            let port = u16::from_be_bytes([body[32], body[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), port)))
+        }
+        1 | 2 => Err(invalid("truncated v2 address block")),
+        _ => Ok(None),
+    }
+}
+
+/// Serve plain HTTP, reading PROXY headers from trusted proxies
+///
+/// Used instead of `axum::serve` when `proxy_protocol` is enabled, since
+/// the header has to be consumed before hyper sees the connection.
+pub async fn serve_http(listener: TcpListener, app: Router, access: Arc<AccessControl>) -> McpResult<()> {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+
+        let app = app.clone();
+        let access = access.clone();
+
+        tokio::spawn(async move {
+            let peer = match access.resolve_peer(&mut stream, peer).await {
+                Ok(peer) => peer,
+                Err(e) => {
+                    debug!("Dropping connection from {}: {}", peer, e);
+                    return;
+                }
+            };
+
+            let service = hyper::service::service_fn(move |mut request: hyper::Request<hyper::body::Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                app.clone().call(request)
+            });
+
+            if let Err(e) = ConnectionBuilder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Connection from {} closed with error: {}", peer, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_v1() {
+        let mut input: &[u8] = b"PROXY TCP4 203.0.113.7 10.0.0.1 56324 443\r\nGET / HTTP/1.1\r\n";
+        let addr = read_header(&mut input).await.unwrap();
+        assert_eq!(addr, Some("203.0.113.7:56324".parse().unwrap()));
+        assert_eq!(input, b"GET / HTTP/1.1\r\n");
+
+        let mut input: &[u8] = b"PROXY UNKNOWN\r\n";
+        assert_eq!(read_header(&mut input).await.unwrap(), None);
+
+        let mut input: &[u8] = b"GET / HTTP/1.1\r\nHost: x\r\n";
+        assert!(read_header(&mut input).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_v2() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        header.extend_from_slice(&[198, 51, 100, 9, 10, 0, 0, 1]);
+        header.extend_from_slice(&443u16.to_be_bytes());
+        header.extend_from_slice(&8443u16.to_be_bytes());
+        header.extend_from_slice(b"rest");
+
+        let mut input = header.as_slice();
+        let addr = read_header(&mut input).await.unwrap();
+        assert_eq!(addr, Some("198.51.100.9:443".parse().unwrap()));
+        assert_eq!(input, b"rest");
+
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(read_header(&mut local.as_slice()).await.unwrap(), None);
+    }
+}
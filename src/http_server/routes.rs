@@ -1,14 +1,40 @@
+use crate::auth::provider::Session;
+use crate::auth::rbac::Rbac;
+use crate::cache::replication::SHARED_LIST_METHODS;
+use crate::cloud::artifacts::ARTIFACT_URI_PREFIX;
+use crate::config::{Permission, PresetConfig};
+use crate::core::content::{self, ContentPolicy, SPOOL_URI_PREFIX};
 use crate::core::lazy_loader::ToolSchema;
-use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
-use crate::core::{RequestRouter, RoutingStrategy};
+use crate::core::protocol::{
+    downgrade_result, JsonRpcRequest, JsonRpcResponse, ProtocolVersion, RequestId,
+    PROTOCOL_VERSION_HEADER,
+};
+use crate::core::{RequestRouter, RoutingStrategy, SessionRoute};
+use crate::events::usage;
+use crate::http_server::middleware::ReplicaMiss;
 use crate::http_server::server::AppState;
+use crate::http_server::session::{McpSession, MCP_SESSION_ID_HEADER};
+use crate::transport::TransportResponse;
+use crate::utils::errors::{McpError, McpResult};
+use crate::utils::request_trace;
 use axum::{
-    extract::{Json, Path, Query, State},
-    response::Json as AxumJson,
+    body::Body,
+    extract::{Extension, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json as AxumJson, Response},
 };
+use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, info, warn};
+
+/// Pseudo server name runtime tools are listed under by the meta-tools
+const RUNTIME_TOOLS_SERVER: &str = "runtime";
+
+/// Header pinning a `/mcp` request to one upstream server, bypassing
+/// routing; honoured only for sessions with the admin scope
+pub const TARGET_HEADER: &str = "x-supermcp-target";
 
 /// Health check endpoint
 pub async fn health() -> AxumJson<serde_json::Value> {
@@ -18,44 +44,651 @@ pub async fn health() -> AxumJson<serde_json::Value> {
     }))
 }
 
+/// Query parameters accepted on `/mcp`
+#[derive(Debug, Default, Deserialize)]
+pub struct McpQuery {
+    /// Preset to route this session to, chosen at initialize
+    pub preset: Option<String>,
+}
+
+/// Response for a missing, expired or foreign session ID
+fn session_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        AxumJson(json!({
+            "error": "SESSION_NOT_FOUND",
+            "message": "Unknown or expired MCP session; initialize a new one",
+        })),
+    )
+        .into_response()
+}
+
+/// Upstream named by `X-SuperMCP-Target`, or the response refusing it
+///
+/// With `[rbac]` the session needs `servers:manage` rather than the admin
+/// scope.
+fn pinned_target(
+    headers: &HeaderMap,
+    session: Option<&Session>,
+    admin_scope: &str,
+    rbac: Option<&Rbac>,
+) -> Result<Option<String>, Box<Response>> {
+    let Some(target) = headers.get(TARGET_HEADER) else {
+        return Ok(None);
+    };
+
+    let (allowed, needed) = match rbac {
+        Some(rbac) => (
+            session.is_some_and(|session| rbac.allows(session, Permission::ServersManage)),
+            format!("the {} permission", Permission::ServersManage.as_str()),
+        ),
+        None => (
+            session.is_some_and(|session| {
+                session.scopes.iter().any(|scope| scope == "*" || scope == admin_scope)
+            }),
+            format!("the {} scope", admin_scope),
+        ),
+    };
+    if !allowed {
+        return Err(Box::new(
+            (
+                StatusCode::FORBIDDEN,
+                AxumJson(json!({
+                    "error": "INSUFFICIENT_SCOPE",
+                    "message": format!("{} requires {}", TARGET_HEADER, needed),
+                })),
+            )
+                .into_response(),
+        ));
+    }
+
+    match target.to_str().map(str::trim) {
+        Ok(name) if !name.is_empty() => Ok(Some(name.to_string())),
+        _ => Err(Box::new(
+            (
+                StatusCode::BAD_REQUEST,
+                AxumJson(json!({
+                    "error": "INVALID_TARGET",
+                    "message": format!("{} must name a server", TARGET_HEADER),
+                })),
+            )
+                .into_response(),
+        )),
+    }
+}
+
+/// Refuse `tools/call` to a tool needing consent the caller hasn't given
+///
+/// Remembered consents let the call straight through.
+async fn check_consent(
+    state: &AppState,
+    identity: Option<&str>,
+    server: &str,
+    request: &JsonRpcRequest,
+) -> McpResult<()> {
+    let Some(consents) = &state.consents else {
+        return Ok(());
+    };
+    if request.method != "tools/call" {
+        return Ok(());
+    }
+    let tool = request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("name"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    consents.check(identity, &format!("{}.{}", server, tool)).await
+}
+
+/// Refuse a server outside every preset the session's roles allow
+///
+/// Routes that name their server skip preset routing, so without this a
+/// preset-limited role could reach any server by name. Runtime tools are
+/// answered by the proxy under every preset, as on `/mcp`.
+fn check_preset_server(state: &AppState, session: Option<&Session>, server: &str) -> McpResult<()> {
+    let (Some(rbac), Some(session)) = (&state.rbac, session) else {
+        return Ok(());
+    };
+    let Some(allowed) = rbac.allowed_presets(session) else {
+        return Ok(());
+    };
+    if server == RUNTIME_TOOLS_SERVER {
+        return Ok(());
+    }
+    let tags = match state.server_manager.get_server(server) {
+        Some(server) => server.config.tags.clone(),
+        None => state
+            .templates
+            .iter()
+            .find(|template| template.server.name == server)
+            .map(|template| template.server.tags.clone())
+            .unwrap_or_default(),
+    };
+    let in_preset = state
+        .presets
+        .iter()
+        .filter(|preset| allowed.contains(preset.name.as_str()))
+        .any(|preset| preset.tags.iter().any(|tag| tags.contains(tag)));
+    if in_preset {
+        Ok(())
+    } else {
+        Err(McpError::AuthorizationError(format!(
+            "No role allows a preset containing {}",
+            server
+        )))
+    }
+}
+
+/// Answer a failed request with a JSON-RPC error carrying its error code
+/// in `error.data`
+///
+/// Notifications have nobody to answer, so their failures stay HTTP errors.
+/// So does shedding load, for the 503 and `Retry-After` clients back off on.
+fn json_rpc_result(
+    id: Option<RequestId>,
+    result: McpResult<JsonRpcResponse>,
+) -> McpResult<JsonRpcResponse> {
+    match (result, id) {
+        (Err(error @ McpError::Overloaded(_)), _) => Err(error),
+        (Err(error), Some(id)) => {
+            debug!("Request {:?} failed: {}", id, error);
+            Ok(JsonRpcResponse::from_error(id, &error))
+        }
+        (result, _) => result,
+    }
+}
+
+/// Protocol version the client speaks on this request
+///
+/// `initialize` negotiates it; afterwards it comes from the session, or
+/// from the `MCP-Protocol-Version` header on sessionless requests.
+fn client_protocol_version(
+    request: &JsonRpcRequest,
+    headers: &HeaderMap,
+    session: Option<&McpSession>,
+) -> ProtocolVersion {
+    if request.method == "initialize" {
+        let requested = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str());
+        return ProtocolVersion::negotiate(requested);
+    }
+    session
+        .and_then(|s| s.protocol_version.as_deref())
+        .or_else(|| headers.get(PROTOCOL_VERSION_HEADER).and_then(|v| v.to_str().ok()))
+        .and_then(ProtocolVersion::parse)
+        .unwrap_or(ProtocolVersion::HTTP_DEFAULT)
+}
+
+/// Answer in the client's protocol version, whatever the upstream speaks
+fn negotiate_response(method: &str, response: &mut JsonRpcResponse, version: ProtocolVersion) {
+    let Some(result) = response.result.as_mut() else {
+        return;
+    };
+    if let (true, Some(object)) = (method == "initialize", result.as_object_mut()) {
+        object.insert("protocolVersion".to_string(), json!(version.as_str()));
+    }
+    downgrade_result(method, result, version);
+}
+
+/// Swap a mediated resource URI in the request for the upstream one,
+/// returning the server it belongs to
+fn resolve_resource(
+    state: &AppState,
+    request: &mut JsonRpcRequest,
+) -> McpResult<Option<String>> {
+    match &state.resource_uris {
+        Some(mapper) => mapper.resolve_request(request),
+        None => Ok(None),
+    }
+}
+
+/// Hide upstream host URIs in a result from `server`
+fn mediate_resource_uris(
+    state: &AppState,
+    server: &str,
+    method: &str,
+    response: &mut McpResult<JsonRpcResponse>,
+) {
+    if let (Some(mapper), Ok(JsonRpcResponse { result: Some(result), .. })) =
+        (&state.resource_uris, response)
+    {
+        mapper.rewrite_result(server, method, result);
+    }
+}
+
 /// Main MCP handler - routes requests to appropriate servers
+///
+/// A successful `initialize` opens a session whose ID is returned in the
+/// `Mcp-Session-Id` header. Later requests carrying that header are routed
+/// with the session's preset, even after a proxy restart. Admins can skip
+/// routing altogether with `X-SuperMCP-Target: <server>`.
 pub async fn mcp_handler(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<JsonRpcRequest>,
-) -> Result<Json<JsonRpcResponse>, crate::utils::errors::McpError> {
-    let servers = state.server_manager.list_servers();
+    Query(query): Query<McpQuery>,
+    headers: HeaderMap,
+    auth: Option<Extension<Session>>,
+    Json(mut request): Json<JsonRpcRequest>,
+) -> Result<Response, crate::utils::errors::McpError> {
+    let target = match pinned_target(&headers, auth.as_deref(), &state.admin_scope, state.rbac.as_deref()) {
+        Ok(target) => target,
+        Err(response) => return Ok(*response),
+    };
+    let identity = auth.as_ref().map(|Extension(session)| session.user_id.clone());
+    let is_initialize = request.method == "initialize";
+
+    let session = match (&state.sessions, headers.get(MCP_SESSION_ID_HEADER)) {
+        (Some(sessions), Some(id)) if !is_initialize => {
+            let id = id.to_str().unwrap_or_default();
+            match sessions.resume(id, identity.as_deref()).await? {
+                Some(session) => Some(session),
+                None => return Ok(session_not_found()),
+            }
+        }
+        _ => None,
+    };
+
+    let preset_name = match &session {
+        Some(session) => session.preset.clone(),
+        None => query.preset,
+    };
+    let preset = match &preset_name {
+        Some(name) => Some(state.presets.iter().find(|p| &p.name == name).ok_or_else(|| {
+            crate::utils::errors::McpError::InvalidRequest(format!("Unknown preset: {}", name))
+        })?),
+        None => None,
+    };
+
+    // Roles may limit the presets a session can select
+    if let (Some(rbac), Some(Extension(session))) = (&state.rbac, &auth) {
+        if !rbac.allows_preset(session, preset_name.as_deref()) {
+            return Err(McpError::AuthorizationError(match &preset_name {
+                Some(name) => format!("No role allows preset {}", name),
+                None => "Select a preset allowed by your roles".to_string(),
+            }));
+        }
+    }
+
+    let params = request.params.clone();
+    let route = session.as_ref().map(McpSession::route);
+    let id = request.id.clone();
+    let method = request.method.clone();
+    let version = client_protocol_version(&request, &headers, session.as_ref());
+    // Links to spooled content and artifacts are answered here rather
+    // than upstream
+    let local_uri = (method == "resources/read")
+        .then(|| request.params.as_ref()?.get("uri")?.as_str().map(str::to_string))
+        .flatten()
+        .filter(|uri| uri.starts_with(SPOOL_URI_PREFIX) || uri.starts_with(ARTIFACT_URI_PREFIX));
+
+    // Mediated resource URIs go to the server that issued them, within
+    // the session's preset like any routed request
+    let resource_server = match resolve_resource(&state, &mut request) {
+        Ok(server) => server,
+        Err(error) => return Ok(Json(json_rpc_result(id, Err(error))?).into_response()),
+    };
+    let target = match resource_server {
+        Some(server) => {
+            let allowed = preset.is_none_or(|preset| {
+                state.server_manager.get_server(&server).is_some_and(|s| {
+                    preset.tags.iter().any(|tag| s.config.tags.contains(tag))
+                })
+            });
+            if !allowed {
+                let error = crate::utils::errors::McpError::AuthorizationError(
+                    "Resource is outside this session's preset".to_string(),
+                );
+                return Ok(Json(json_rpc_result(id, Err(error))?).into_response());
+            }
+            Some(server)
+        }
+        None => {
+            if let Some(target) = &target {
+                info!("Request {} pinned to {} by {}", method, target, TARGET_HEADER);
+            }
+            target
+        }
+    };
+    // Only measured for billing when usage is metered
+    let bytes_in = usage::is_enabled()
+        .then(|| serde_json::to_vec(&request).map_or(0, |body| body.len() as u64));
+    let started = std::time::Instant::now();
+    let routing = request_trace::phase("routing");
+    let result = match (local_uri, target) {
+        (Some(uri), _) => {
+            let request_id = id.clone().unwrap_or(RequestId::Number(0));
+            let result = match &state.artifacts {
+                Some(artifacts) if uri.starts_with(ARTIFACT_URI_PREFIX) => {
+                    artifacts.read_resource(&uri, identity.as_deref()).await
+                }
+                _ => {
+                    let dir = content::spool_dir(&state.content);
+                    content::read_spooled(&dir, &uri, identity.as_deref()).await
+                }
+            };
+            result.map(|result| JsonRpcResponse::success(request_id, result))
+        }
+        (None, Some(target)) => {
+            let mut result = match check_consent(&state, identity.as_deref(), &target, &request).await {
+                Ok(()) => {
+                    state
+                        .server_manager
+                        .send_session_request(&target, route.as_ref(), request)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            mediate_resource_uris(&state, &target, &method, &mut result);
+            result
+        }
+        (None, None) => {
+            let shared = state
+                .shared_lists
+                .clone()
+                .filter(|_| SHARED_LIST_METHODS.contains(&method.as_str()));
+            if state.read_replica {
+                // Replicas answer lists other nodes shared, and hand the
+                // request back for forwarding when there is none
+                let result = match &shared {
+                    Some(lists) => lists.load(preset_name.as_deref(), &method).await.unwrap_or_else(|e| {
+                        warn!("Failed to read shared {} result: {}", method, e);
+                        None
+                    }),
+                    None => None,
+                };
+                let Some(result) = result else {
+                    return Ok((StatusCode::SERVICE_UNAVAILABLE, Extension(ReplicaMiss)).into_response());
+                };
+                Ok(JsonRpcResponse::success(id.clone().unwrap_or(RequestId::Number(0)), result))
+            } else {
+                let result =
+                    route_mcp_request(&state, request, preset, route.as_ref(), identity.as_deref()).await;
+                if let (Some(lists), Ok(JsonRpcResponse { result: Some(listed), error: None, .. })) =
+                    (shared, &result)
+                {
+                    let (preset, method, listed) = (preset_name.clone(), method.clone(), listed.clone());
+                    tokio::spawn(async move {
+                        if let Err(e) = lists.publish(preset.as_deref(), &method, &listed).await {
+                            warn!("Failed to share {} result: {}", method, e);
+                        }
+                    });
+                }
+                result
+            }
+        }
+    };
+    drop(routing);
+    let upstream = started.elapsed();
+    let mut response = json_rpc_result(id, result)?;
+    if let Some(result) = response.result.as_mut() {
+        let _phase = request_trace::phase("content");
+        ContentPolicy::for_tenant(&state.content, identity.as_deref())
+            .apply(&method, result)
+            .await;
+        offload_result(&state, &method, params.as_ref(), identity.as_deref(), result).await;
+    }
+    negotiate_response(&method, &mut response, version);
+    if let Some(bytes_in) = bytes_in {
+        let bytes_out = serde_json::to_vec(&response).map_or(0, |body| body.len() as u64);
+        usage::record(identity.as_deref(), method == "tools/call", upstream, bytes_in, bytes_out);
+    }
+
+    if let (true, Some(sessions), None) = (is_initialize, &state.sessions, &response.error) {
+        let mut session = McpSession::from_initialize(
+            params.as_ref(),
+            response.result.as_ref(),
+            preset_name,
+            identity,
+        );
+        // Later requests in the session are routed back to this node
+        if let Some(ring) = &state.session_ring {
+            session.id = ring.local_session_id();
+        }
+        let id = session.id.clone();
+        sessions.insert(session).await?;
+        let _phase = request_trace::phase("serialization");
+        return Ok(([(MCP_SESSION_ID_HEADER, id)], Json(response)).into_response());
+    }
+
+    let _phase = request_trace::phase("serialization");
+    Ok(Json(response).into_response())
+}
+
+/// Forward a request to the server chosen by capability routing
+///
+/// With a preset, only servers carrying one of its tags are candidates.
+async fn route_mcp_request(
+    state: &AppState,
+    request: JsonRpcRequest,
+    preset: Option<&PresetConfig>,
+    session: Option<&SessionRoute<'_>>,
+    identity: Option<&str>,
+) -> Result<JsonRpcResponse, crate::utils::errors::McpError> {
+    if let Some(runtime_tools) = &state.runtime_tools {
+        if let Some(response) = runtime_tools.handle_request(&request).await? {
+            return Ok(response);
+        }
+    }
+
+    let servers: Vec<_> = state
+        .server_manager
+        .list_servers()
+        .into_iter()
+        .filter_map(|name| state.server_manager.get_server(&name).map(|server| (name, server)))
+        .filter(|(_, server)| {
+            preset.is_none_or(|preset| preset.tags.iter().any(|tag| server.config.tags.contains(tag)))
+        })
+        .collect();
+    // Broker tools act for a server, so they follow the same preset filter
+    let visible = |name: &str| servers.iter().any(|(server, _)| server == name);
+    if let Some(broker) = &state.file_broker {
+        if let Some(response) = broker.handle_request(&request, identity, visible).await? {
+            return Ok(response);
+        }
+    }
     if servers.is_empty() {
+        // Runtime tools can still be listed without any upstream servers
+        if let (Some(runtime_tools), Some(id)) = (&state.runtime_tools, &request.id) {
+            if request.method == "tools/list" {
+                return Ok(JsonRpcResponse::success(
+                    id.clone(),
+                    json!({ "tools": runtime_tools.tools() }),
+                ));
+            }
+        }
         return Err(crate::utils::errors::McpError::ServerNotFound(
             "No servers configured".to_string(),
         ));
     }
 
     let mut router = RequestRouter::new(RoutingStrategy::Capability);
-    for name in &servers {
-        if let Some(server) = state.server_manager.get_server(name) {
-            router.register_server(name.clone(), server.config.tags.clone());
+    for (name, server) in &servers {
+        router.register_server(name.clone(), server.config.tags.clone());
+        if let Some(split) = state.server_manager.traffic_split(name) {
+            router.register_split(split);
         }
     }
 
     let server_name = router.route(&request)?;
+    check_consent(state, identity, &server_name, &request).await?;
+
+    let method = request.method.clone();
+    let is_tool_list = method == "tools/list";
+    let mut response = state
+        .server_manager
+        .send_session_request(&server_name, session, request)
+        .await;
+    mediate_resource_uris(state, &server_name, &method, &mut response);
+    let mut response = response?;
 
-    let response = state.server_manager.send_request(&server_name, request).await?;
+    if let (true, Some(runtime_tools)) = (is_tool_list, &state.runtime_tools) {
+        runtime_tools.extend_tool_list(&mut response);
+    }
+    if let (true, Some(broker)) = (is_tool_list, &state.file_broker) {
+        broker.extend_tool_list(&mut response, visible);
+    }
 
-    Ok(Json(response))
+    Ok(response)
+}
+
+/// Terminate an MCP session (`DELETE /mcp` with `Mcp-Session-Id`)
+pub async fn mcp_session_delete_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    auth: Option<Extension<Session>>,
+) -> Result<Response, crate::utils::errors::McpError> {
+    let Some(sessions) = &state.sessions else {
+        return Ok(StatusCode::METHOD_NOT_ALLOWED.into_response());
+    };
+    let Some(id) = headers.get(MCP_SESSION_ID_HEADER) else {
+        return Err(crate::utils::errors::McpError::InvalidRequest(
+            "Missing Mcp-Session-Id header".to_string(),
+        ));
+    };
+
+    let id = id.to_str().unwrap_or_default();
+    let identity = auth.map(|Extension(session)| session.user_id);
+    if sessions.resume(id, identity.as_deref()).await?.is_none() {
+        return Ok(session_not_found());
+    }
+
+    sessions.remove(id).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 /// Server-specific MCP handler
+///
+/// Large or chunked upstream bodies are forwarded to the client as they
+/// arrive instead of being buffered, when streaming is enabled. Streamed
+/// bodies are passed through byte for byte, without protocol version
+/// translation, so results the content policy inspects are never streamed.
 pub async fn server_handler(
     Path(server_name): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<JsonRpcRequest>,
-) -> Result<Json<JsonRpcResponse>, crate::utils::errors::McpError> {
-    let response = state
+    headers: HeaderMap,
+    auth: Option<Extension<Session>>,
+    Json(mut request): Json<JsonRpcRequest>,
+) -> Result<Response, crate::utils::errors::McpError> {
+    let id = request.id.clone();
+    let method = request.method.clone();
+    let version = client_protocol_version(&request, &headers, None);
+    let user = auth.as_ref().map(|a| a.user_id.as_str());
+    let policy = ContentPolicy::for_tenant(&state.content, user);
+    let params = request.params.clone();
+    check_preset_server(&state, auth.as_deref(), &server_name)?;
+
+    match resolve_resource(&state, &mut request) {
+        Ok(Some(owner)) if owner != server_name => {
+            let error = crate::utils::errors::McpError::InvalidRequest(format!(
+                "Resource does not belong to {}",
+                server_name
+            ));
+            return Ok(Json(json_rpc_result(id, Err(error))?).into_response());
+        }
+        Ok(_) => {}
+        Err(error) => return Ok(Json(json_rpc_result(id, Err(error))?).into_response()),
+    }
+    if let Err(error) = check_consent(&state, user, &server_name, &request).await {
+        return Ok(Json(json_rpc_result(id, Err(error))?).into_response());
+    }
+
+    // Results that can carry host URIs are buffered so they can be
+    // rewritten, tool results so large ones can be stored, and binary
+    // content so the tenant's size and type limits hold
+    let carries_uris = state.resource_uris.is_some()
+        && (method.starts_with("resources/") || method == "tools/call" || method == "prompts/get");
+    let offloads = state.artifacts.is_some() && method == "tools/call";
+    if !state.streaming.enabled || carries_uris || offloads || ContentPolicy::inspects(&method) {
+        let mut result = state.server_manager.send_request(&server_name, request).await;
+        mediate_resource_uris(&state, &server_name, &method, &mut result);
+        let mut response = json_rpc_result(id, result)?;
+        if let Some(result) = response.result.as_mut() {
+            policy.apply(&method, result).await;
+            offload_result(&state, &method, params.as_ref(), user, result).await;
+        }
+        negotiate_response(&method, &mut response, version);
+        return Ok(Json(response).into_response());
+    }
+
+    let response = match state
         .server_manager
-        .send_request(&server_name, request)
-        .await?;
+        .send_request_streaming(&server_name, request, state.streaming.max_in_memory_bytes)
+        .await
+    {
+        Ok(response) => response,
+        Err(error) => return Ok(Json(json_rpc_result(id, Err(error))?).into_response()),
+    };
 
-    Ok(Json(response))
+    match response {
+        TransportResponse::Buffered(mut response) => {
+            negotiate_response(&method, &mut response, version);
+            Ok(Json(response).into_response())
+        }
+        TransportResponse::Streamed { content_type, body } => {
+            debug!("Streaming response from {} ({})", server_name, content_type);
+            Ok((
+                [(header::CONTENT_TYPE, content_type)],
+                Body::from_stream(body),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Spooled binary content, streamed from disk
+pub async fn content_handler(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<Session>>,
+) -> Result<Response, crate::utils::errors::McpError> {
+    let user = auth.as_ref().map(|a| a.user_id.as_str());
+    let (path, mime_type) = content::spooled(&content::spool_dir(&state.content), &id, user).await?;
+    let file = tokio::fs::File::open(path).await?;
+    Ok((
+        [(header::CONTENT_TYPE, mime_type)],
+        Body::from_stream(tokio_util::io::ReaderStream::new(file)),
+    )
+        .into_response())
+}
+
+/// Stored tool result, for clients that follow artifact links over HTTP
+pub async fn artifact_handler(
+    Path(id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<Session>>,
+) -> Result<Response, crate::utils::errors::McpError> {
+    let Some(artifacts) = &state.artifacts else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let user = auth.as_ref().map(|a| a.user_id.as_str());
+    match artifacts.get(&id).await? {
+        Some((meta, data)) if meta.readable_by(user) => {
+            Ok(([(header::CONTENT_TYPE, meta.mime_type)], data).into_response())
+        }
+        _ => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+/// Replace a large `tools/call` result by a link to a stored artifact
+///
+/// Storage failures leave the result inline.
+async fn offload_result(
+    state: &AppState,
+    method: &str,
+    params: Option<&Value>,
+    user: Option<&str>,
+    result: &mut Value,
+) {
+    let (Some(artifacts), "tools/call") = (&state.artifacts, method) else {
+        return;
+    };
+    let tool = params.and_then(|p| p.get("name")).and_then(Value::as_str);
+    if let Err(e) = artifacts.offload(result, tool, user).await {
+        warn!("Returning tool result inline, failed to store artifact: {}", e);
+    }
 }
 
 /// Tool list meta-tool - lists available tools with optional filtering
@@ -142,15 +775,41 @@ pub async fn tool_list_handler(
     };
 
     match tools_result {
-        Ok(tools) => AxumJson(json!({
-            "tools": tools.iter().map(|t| json!({
+        Ok(tools) => {
+            let mut listed: Vec<Value> = tools.iter().map(|t| json!({
                 "name": t.name,
                 "description": t.description,
                 "inputSchema": t.input_schema,
                 "server": t.server_name,
-            })).collect::<Vec<_>>(),
-            "count": tools.len(),
-        })),
+            })).collect();
+
+            let include_runtimes = server_filter
+                .as_ref()
+                .is_none_or(|servers| servers.iter().any(|s| s == RUNTIME_TOOLS_SERVER));
+            if let (true, None, Some(runtime_tools)) =
+                (include_runtimes, &tag_filter, &state.runtime_tools)
+            {
+                listed.extend(runtime_tools.tools().into_iter().map(|mut tool| {
+                    tool["server"] = json!(RUNTIME_TOOLS_SERVER);
+                    tool
+                }));
+            }
+            if let (None, Some(broker)) = (&tag_filter, &state.file_broker) {
+                for server in broker.servers() {
+                    if server_filter.as_ref().is_none_or(|servers| servers.iter().any(|s| s == server)) {
+                        listed.extend(broker.tools_for(server).into_iter().map(|mut tool| {
+                            tool["server"] = json!(server);
+                            tool
+                        }));
+                    }
+                }
+            }
+
+            AxumJson(json!({
+                "count": listed.len(),
+                "tools": listed,
+            }))
+        }
         Err(e) => AxumJson(json!({
             "error": e.to_string(),
         })),
@@ -239,6 +898,7 @@ pub async fn tool_schema_handler(
 /// Tool invoke meta-tool - invokes a tool on a specific server
 pub async fn tool_invoke_handler(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<Session>>,
     Json(body): Json<Value>,
 ) -> Result<AxumJson<serde_json::Value>, crate::utils::errors::McpError> {
     let server = match body.get("server").and_then(|s| s.as_str()) {
@@ -260,25 +920,80 @@ pub async fn tool_invoke_handler(
     };
 
     let arguments = body.get("arguments").cloned().or(Some(json!({})));
+    check_preset_server(&state, auth.as_deref(), &server)?;
 
-    let request = JsonRpcRequest::new(
-        "tools/call",
-        Some(json!({
-            "name": tool,
-            "arguments": arguments,
-        })),
-    );
+    if let Some(template) = state.templates.iter().find(|t| t.server.name == server) {
+        let params: HashMap<String, String> = match body.get("params") {
+            Some(params) => serde_json::from_value(params.clone()).map_err(|_| {
+                crate::utils::errors::McpError::InvalidRequest(
+                    "params must be an object of strings".to_string(),
+                )
+            })?,
+            None => HashMap::new(),
+        };
+        let instance =
+            crate::core::template::ensure_instance(&state.server_manager, template, &params).await?;
+        if let Some(loader) = &state.lazy_loader {
+            loader.metrics().template_invocations.increment();
+        }
+        return invoke_tool(&state, auth.as_deref(), &instance, tool, arguments).await;
+    }
 
-    let response = state.server_manager.send_request(&server, request).await?;
+    if server == RUNTIME_TOOLS_SERVER {
+        if let Some(runtime_tools) = &state.runtime_tools {
+            return Ok(AxumJson(runtime_tools.call(&tool, arguments.as_ref()).await?));
+        }
+    }
+    if let Some(broker) = state
+        .file_broker
+        .as_ref()
+        .filter(|broker| broker.server_of(&tool) == Some(server.as_str()))
+    {
+        let identity = auth.as_deref().map(|session| session.user_id.as_str());
+        return Ok(AxumJson(broker.call(&tool, arguments.as_ref(), identity).await?));
+    }
+
+    invoke_tool(&state, auth.as_deref(), &server, tool, arguments).await
+}
+
+/// Call a tool on a running server, flattening JSON-RPC errors into the body
+async fn invoke_tool(
+    state: &AppState,
+    auth: Option<&Session>,
+    server: &str,
+    tool: String,
+    arguments: Option<Value>,
+) -> Result<AxumJson<serde_json::Value>, crate::utils::errors::McpError> {
+    let params = json!({
+        "name": tool,
+        "arguments": arguments,
+    });
+    let request = JsonRpcRequest::new("tools/call", Some(params.clone()));
+    check_consent(state, auth.map(|a| a.user_id.as_str()), server, &request).await?;
+
+    let mut response = state.server_manager.send_request(server, request).await;
+    mediate_resource_uris(state, server, "tools/call", &mut response);
+    let response = response?;
 
     match response.result {
-        Some(result) => Ok(AxumJson(result)),
+        Some(mut result) => {
+            let user = auth.map(|a| a.user_id.as_str());
+            ContentPolicy::for_tenant(&state.content, user)
+                .apply("tools/call", &mut result)
+                .await;
+            offload_result(state, "tools/call", Some(&params), user, &mut result).await;
+            Ok(AxumJson(result))
+        }
         None => {
             if let Some(error) = response.error {
-                Ok(AxumJson(json!({
+                let mut body = json!({
                     "error": error.message,
                     "code": error.code,
-                })))
+                });
+                if let Some(data) = error.data {
+                    body["data"] = data;
+                }
+                Ok(AxumJson(body))
             } else {
                 Ok(AxumJson(json!({
                     "error": "Unknown error occurred"
@@ -318,19 +1033,75 @@ pub async fn server_status_handler(
     State(state): State<Arc<AppState>>,
 ) -> AxumJson<serde_json::Value> {
     match state.server_manager.get_server_status(&server_name).await {
-        Ok(status) => AxumJson(json!({
-            "name": status.name,
-            "connected": status.connected,
-            "transport_type": format!("{:?}", status.transport_type),
-            "tags": status.tags,
-            "command": status.command,
-        })),
+        Ok(status) => {
+            let mut body = json!({
+                "name": status.name,
+                "connected": status.connected,
+                "transport_type": format!("{:?}", status.transport_type),
+                "tags": status.tags,
+                "command": status.command,
+                "restarts": status.restarts,
+                "degraded": status.degraded,
+            });
+            if let Some(disk) = &status.disk {
+                body["disk"] = json!(disk);
+            }
+            if let Some(mirror) = state.server_manager.shadow_mirror(&status.name) {
+                let (mirrored, mismatches) = mirror.stats();
+                body["shadow"] = json!({
+                    "server": mirror.shadow_server(),
+                    "mirrored": mirrored,
+                    "mismatches": mismatches,
+                });
+            }
+            if let Some(server) = state.server_manager.get_server(&status.name) {
+                if server.config.supervision.warm_standby {
+                    body["standby_ready"] = json!(server.supervisor().standby_ready());
+                }
+                if server.is_supervised() {
+                    body["supervision"] = json!(server.supervisor().snapshot(&status.name));
+                }
+            }
+            if let Some(workers) = state.server_manager.worker_status(&status.name).await {
+                body["workers"] = json!(workers);
+            }
+            AxumJson(body)
+        }
         Err(e) => AxumJson(json!({
             "error": e.to_string(),
         })),
     }
 }
 
+/// Get upstream HTTP connection statistics
+pub async fn upstream_stats_handler() -> AxumJson<serde_json::Value> {
+    let upstreams = crate::transport::upstream_metrics_snapshot();
+    AxumJson(json!({
+        "upstreams": upstreams,
+        "count": upstreams.len(),
+    }))
+}
+
+/// Restart counters of supervised stdio servers
+pub async fn supervision_stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> AxumJson<serde_json::Value> {
+    let servers = state.server_manager.supervision_report();
+    AxumJson(json!({
+        "servers": servers,
+        "count": servers.len(),
+    }))
+}
+
+/// Compliance of the configured tool call SLOs
+pub async fn slo_handler(State(state): State<Arc<AppState>>) -> AxumJson<serde_json::Value> {
+    let slos = state.server_manager.slo_report();
+    AxumJson(json!({
+        "slos": slos,
+        "count": slos.len(),
+    }))
+}
+
 /// Get cache statistics
 pub async fn cache_stats_handler(
     State(state): State<Arc<AppState>>,
@@ -348,6 +1119,7 @@ pub async fn cache_stats_handler(
                 "insertions": stats.metrics.insertions,
                 "hit_rate_percent": stats.metrics.hit_rate(),
             },
+            "template_invocations": loader.metrics().template_invocations.get(),
         }))
     } else {
         AxumJson(json!({
@@ -363,6 +1135,15 @@ pub async fn cache_clear_handler(
 ) -> AxumJson<serde_json::Value> {
     let server = params.get("server").and_then(|s| s.as_str());
 
+    // Shared list results may include any server's entries
+    if let Some(lists) = state.shared_lists.clone() {
+        tokio::spawn(async move {
+            if let Err(e) = lists.clear().await {
+                warn!("Failed to clear shared list results: {}", e);
+            }
+        });
+    }
+
     if let Some(loader) = &state.lazy_loader {
         if let Some(server_name) = server {
             loader.invalidate_cache(server_name);
@@ -371,7 +1152,7 @@ pub async fn cache_clear_handler(
             }))
         } else {
             // Clear all caches
-            loader.cache().clear_all();
+            loader.invalidate_all();
             AxumJson(json!({
                 "message": "All caches cleared",
             }))
@@ -382,3 +1163,73 @@ pub async fn cache_clear_handler(
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(scopes: &[&str]) -> Session {
+        Session {
+            user_id: "ops".to_string(),
+            token: String::new(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: None,
+            method: crate::auth::AuthMethod::Jwt,
+            token_label: None,
+        }
+    }
+
+    #[test]
+    fn test_pinned_target_requires_admin_scope() {
+        assert!(matches!(pinned_target(&HeaderMap::new(), None, "admin", None), Ok(None)));
+
+        let pinned = |target: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(TARGET_HEADER, target.parse().unwrap());
+            headers
+        };
+        let refused = |target: &str, session: Option<&Session>| {
+            match pinned_target(&pinned(target), session, "admin", None) {
+                Err(response) => response.status(),
+                Ok(_) => StatusCode::OK,
+            }
+        };
+        assert_eq!(refused("github", None), StatusCode::FORBIDDEN);
+        assert_eq!(refused("github", Some(&session(&["tools"]))), StatusCode::FORBIDDEN);
+
+        let target = pinned_target(&pinned("github"), Some(&session(&["admin"])), "admin", None);
+        assert_eq!(target.ok().flatten().as_deref(), Some("github"));
+
+        assert_eq!(refused(" ", Some(&session(&["*"]))), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_initialize_answers_in_client_version() {
+        let request = JsonRpcRequest::new(
+            "initialize",
+            Some(json!({ "protocolVersion": "2024-11-05", "capabilities": {} })),
+        );
+        let version = client_protocol_version(&request, &HeaderMap::new(), None);
+        assert_eq!(version, ProtocolVersion::V2024_11_05);
+
+        // An upstream on a newer revision answers with it and a newer capability
+        let mut response = JsonRpcResponse::success(
+            RequestId::Number(1),
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": { "tools": {}, "completions": {} },
+            }),
+        );
+        negotiate_response("initialize", &mut response, version);
+        assert_eq!(
+            response.result.unwrap(),
+            json!({ "protocolVersion": "2024-11-05", "capabilities": { "tools": {} } })
+        );
+
+        let mut headers = HeaderMap::new();
+        let list = JsonRpcRequest::new("tools/list", None);
+        assert_eq!(client_protocol_version(&list, &headers, None), ProtocolVersion::HTTP_DEFAULT);
+        headers.insert(PROTOCOL_VERSION_HEADER, "2025-06-18".parse().unwrap());
+        assert_eq!(client_protocol_version(&list, &headers, None), ProtocolVersion::V2025_06_18);
+    }
+}
@@ -1,45 +1,123 @@
-use crate::auth::{AuthProvider, JwtAuth, OAuthAuth, StaticTokenAuth};
-use crate::config::{AuthConfig, AuthType, Config, LazyLoadingMode};
+use crate::auth::rbac::Rbac;
+use crate::auth::{
+    AnonymousReadonlyAuth, AuthProvider, CachedAuth, JwtAuth, JwtKey, OAuthAuth, StaticTokenAuth,
+    TokenCache, TokenCacheConfig,
+};
+use crate::cache::replication::{SchemaReplicator, SharedLists};
+use crate::cloud::hash_ring::SessionRing;
+use crate::cloud::{create_state_backend, ArtifactStore, RemoteConfig};
+use crate::config::{
+    AcmeChallengeType, AuthConfig, AuthType, Config, ConfigManager, ContentConfig, ElevationConfig, LazyLoadingMode, PresetConfig,
+    ServerTemplateConfig, StreamingConfig,
+};
+use crate::core::backpressure;
+use crate::core::request_id::SharedRequestIdGenerator;
+use crate::core::scheduler::PriorityClasses;
+use crate::core::resource_uri::ResourceUriMapper;
 use crate::core::{LazyToolLoader, ServerManager};
 use crate::http_server::middleware::{
-    auth_middleware, create_rate_limit_layer, security_headers_middleware, size_limit_middleware,
-    AuthMiddlewareState, RateLimitConfig as HttpRateLimitConfig, ScopeValidationState,
-    SecurityHeadersConfig, SizeLimitConfig,
+    access_control_middleware, auth_middleware, backpressure_middleware, compression_opt_out_middleware, dry_run_middleware,
+    create_access_control, create_compression_layer, create_rate_limit_layer, rate_limit_event_middleware, replenish_interval, security_headers_middleware,
+    envelope_middleware, federation_middleware, priority_middleware, rbac_middleware, readonly_middleware,
+    request_id_middleware, size_limit_middleware,
+    read_replica_middleware, session_routing_middleware, slow_request_middleware,
+    AccessControl, AuthMiddlewareState, EnvelopeState, PriorityState, RateLimitConfig as HttpRateLimitConfig, ScopeValidationState,
+    SecurityHeadersConfig, SessionRoutingState, SizeLimitConfig,
 };
+use crate::http_server::acme::AcmeManager;
+use crate::http_server::admin::admin_routes;
+use crate::http_server::admin_oidc::{admin_session_middleware, AdminLogin};
+use crate::http_server::consent::{consent_routes, ConsentStore};
+use crate::http_server::named_pipe::serve_named_pipe;
+use crate::http_server::nats::serve_nats;
+use crate::http_server::proxy_protocol::serve_http;
 use crate::http_server::routes;
+use crate::http_server::session::SessionStore;
+use crate::http_server::spiffe::SpiffeIdentity;
+use crate::http_server::tls::{
+    build_acceptor, build_mtls_acceptor, listen_addr, load_certified_key, serve_tls, CertStore, ChallengeSource,
+};
+use crate::runtime::RuntimeTools;
+use crate::sandbox::broker::FileBroker;
+use crate::utils::request_trace::SlowRequestLog;
 use axum::{
+    http::HeaderName,
     middleware,
     routing::{get, post},
-    Router,
+    Json, Router,
 };
 use jsonwebtoken::Algorithm;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::info;
+use tracing::{error, info, warn};
 
 /// Application state shared across all routes
 pub struct AppState {
     pub server_manager: Arc<ServerManager>,
     pub lazy_loader: Option<Arc<LazyToolLoader>>,
+    pub streaming: StreamingConfig,
+    /// Size, metadata and spooling policies for binary content
+    pub content: ContentConfig,
+    /// Limits of temporary sandbox elevation
+    pub elevation: ElevationConfig,
+    /// Opaque resource URIs handed to clients, when mediation is on
+    pub resource_uris: Option<Arc<ResourceUriMapper>>,
+    /// Store for large tool results, when enabled
+    pub artifacts: Option<Arc<ArtifactStore>>,
+    /// Config file fetched from object storage, when serving one
+    pub remote_config: Option<Arc<RemoteConfig>>,
+    /// Config file the server was started from, when it manages one
+    pub config_manager: Option<Arc<ConfigManager>>,
+    /// Runtimes exposed as `runtime_exec_<name>` tools, when opted in
+    pub runtime_tools: Option<Arc<RuntimeTools>>,
+    /// `fs_read_<server>`/`fs_write_<server>` tools of brokered servers
+    pub file_broker: Option<Arc<FileBroker>>,
+    /// Downstream MCP sessions, when enabled
+    pub sessions: Option<Arc<SessionStore>>,
+    /// Which cluster node owns each session, with `cluster.session_routing`
+    pub session_ring: Option<Arc<SessionRing>>,
+    pub presets: Vec<PresetConfig>,
+    /// Servers instantiated on demand by `/tools/invoke`
+    pub templates: Vec<ServerTemplateConfig>,
+    /// Scope allowed to pin requests with `X-SuperMCP-Target`
+    pub admin_scope: String,
+    /// Roles gating admin routes, presets and pinning, when enabled
+    pub rbac: Option<Arc<Rbac>>,
+    /// Remembered tool consents, when consent is required for some tools
+    pub consents: Option<Arc<ConsentStore>>,
+    /// Recent slow requests, when tracing them is enabled
+    pub slow_requests: Option<Arc<SlowRequestLog>>,
+    /// List results shared with read replicas
+    pub shared_lists: Option<Arc<SharedLists>>,
+    /// Serve lists from `shared_lists` only, never from upstream servers
+    pub read_replica: bool,
 }
 
 pub struct HttpServer {
     config: Config,
     server_manager: Arc<ServerManager>,
     lazy_loader: Option<Arc<LazyToolLoader>>,
+    remote_config: Option<Arc<RemoteConfig>>,
+    config_manager: Option<Arc<ConfigManager>>,
 }
 
 impl HttpServer {
     pub fn new(config: Config, server_manager: Arc<ServerManager>) -> Self {
         let lazy_loader = if config.lazy_loading.mode != LazyLoadingMode::Disabled {
             let cache_ttl = Duration::from_secs(config.lazy_loading.schema_cache_ttl_seconds);
-            Some(Arc::new(LazyToolLoader::new(
+            let mut loader = LazyToolLoader::new(
                 server_manager.clone(),
                 config.lazy_loading.mode,
                 config.lazy_loading.preload_servers.clone(),
                 cache_ttl,
-            )))
+            );
+            if config.lazy_loading.replicate {
+                loader = loader.with_replication(Arc::new(SchemaReplicator::new(
+                    create_state_backend(&config.state),
+                )));
+            }
+            Some(Arc::new(loader))
         } else {
             None
         };
@@ -48,46 +126,254 @@ impl HttpServer {
             config,
             server_manager,
             lazy_loader,
+            remote_config: None,
+            config_manager: None,
         }
     }
 
+    /// Serve a config fetched from object storage, refreshable through the
+    /// admin API
+    pub fn with_remote_config(mut self, remote_config: Arc<RemoteConfig>) -> Self {
+        self.remote_config = Some(remote_config);
+        self
+    }
+
+    /// Expose the config file through the admin API, so it can be diffed
+    /// against and replaced while running
+    pub fn with_config_manager(mut self, config_manager: Arc<ConfigManager>) -> Self {
+        self.config_manager = Some(config_manager);
+        self
+    }
+
     pub async fn run(self) -> anyhow::Result<()> {
-        let app = self.create_router().await?;
+        let access = create_access_control(&self.config.server.access)?;
+        let app = self.create_router(access.clone()).await?;
 
         let addr = SocketAddr::from((
             self.config.server.host.parse::<std::net::IpAddr>()?,
             self.config.server.port,
         ));
 
-        info!("Starting HTTP server on {}", addr);
+        let server = &self.config.server;
+
+        if server.nats.enabled {
+            let nats = server.nats.clone();
+            let app = app.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_nats(&nats, app).await {
+                    error!("NATS listener failed: {}", e);
+                }
+            });
+        }
+
+        if let Some(pipe) = &server.named_pipe {
+            info!("Starting HTTP server on named pipe {}", pipe);
+            serve_named_pipe(pipe, app).await?;
+            return Ok(());
+        }
 
         let listener = tokio::net::TcpListener::bind(addr).await?;
-        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
-            .await?;
+
+        if server.spiffe.enabled {
+            let store = Arc::new(CertStore::new());
+            let identity = Arc::new(SpiffeIdentity::new(&server.spiffe, store.clone()));
+            identity.clone().start(&server.spiffe).await?;
+
+            info!("Starting HTTPS server on {} (SPIFFE mTLS)", addr);
+            serve_tls(listener, build_mtls_acceptor(store, identity)?, None, app, access).await?;
+        } else if server.acme.enabled {
+            let store = Arc::new(CertStore::new());
+            let acme = Arc::new(AcmeManager::new(
+                server.acme.clone(),
+                create_state_backend(&self.config.state),
+                store.clone(),
+            )?);
+
+            if server.acme.challenge == AcmeChallengeType::Http01 {
+                let challenge_addr = listen_addr(&server.host, server.acme.http01_port)?;
+                let challenge_listener = tokio::net::TcpListener::bind(challenge_addr).await?;
+                let challenge_router = acme.http01_router();
+                info!("Serving ACME HTTP-01 challenges on {}", challenge_addr);
+                tokio::spawn(async move {
+                    if let Err(e) = axum::serve(challenge_listener, challenge_router).await {
+                        error!("ACME challenge listener failed: {}", e);
+                    }
+                });
+            }
+
+            let challenges = (server.acme.challenge == AcmeChallengeType::TlsAlpn01)
+                .then(|| acme.challenges() as Arc<dyn ChallengeSource>);
+            acme.spawn_renewal();
+
+            info!("Starting HTTPS server on {} (ACME)", addr);
+            serve_tls(listener, build_acceptor(store)?, challenges, app, access).await?;
+        } else if let (Some(cert_path), Some(key_path)) = (&server.cert_path, &server.key_path) {
+            let store = Arc::new(CertStore::new());
+            store.set_certificate(load_certified_key(cert_path, key_path).await?);
+
+            info!("Starting HTTPS server on {}", addr);
+            serve_tls(listener, build_acceptor(store)?, None, app, access).await?;
+        } else if access.proxy_protocol() {
+            info!("Starting HTTP server on {} (PROXY protocol)", addr);
+            serve_http(listener, app, access).await?;
+        } else {
+            info!("Starting HTTP server on {}", addr);
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
 
         Ok(())
     }
 
-    async fn create_router(&self) -> anyhow::Result<Router> {
+    async fn create_router(&self, access: Arc<AccessControl>) -> anyhow::Result<Router> {
         let server_manager = self.server_manager.clone();
         let lazy_loader = self.lazy_loader.clone();
 
+        // Start with the schemas other nodes already fetched
+        if let Some(loader) = &lazy_loader {
+            match loader.warm_cache().await {
+                Ok(0) => {}
+                Ok(n) => info!("Loaded {} shared schema(s)", n),
+                Err(e) => warn!("Failed to load shared schemas: {}", e),
+            }
+        }
+
+        let sessions = self.config.sessions.enabled.then(|| {
+            let sessions = Arc::new(
+                SessionStore::new(
+                    create_state_backend(&self.config.state),
+                    Duration::from_secs(self.config.sessions.idle_timeout_seconds),
+                )
+                .with_server_manager(server_manager.clone()),
+            );
+            sessions.clone().spawn_sweeper();
+            sessions
+        });
+
+        // Sessions hashed to cluster nodes, identified as in the cluster
+        let cluster = &self.config.cluster;
+        let routed = cluster.session_routing || cluster.read_replica;
+        let session_ring = match (cluster.enabled && routed, &sessions) {
+            (true, Some(_)) => {
+                let node_id = match crate::cloud::leader::cluster() {
+                    Some(manager) => manager.node_id().to_string(),
+                    None => cluster
+                        .node_id
+                        .clone()
+                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                };
+                let ring = Arc::new(SessionRing::new(
+                    create_state_backend(&self.config.state),
+                    cluster,
+                    &node_id,
+                ));
+                ring.refresh().await?;
+                ring.clone().spawn_refresh();
+                Some(ring)
+            }
+            _ => None,
+        };
+
+        let artifacts = if self.config.artifacts.enabled {
+            let mut artifacts = ArtifactStore::open(&self.config.artifacts)?;
+            if self.config.tenant_keys.encrypt_artifacts {
+                if let Some(keys) = crate::cloud::tenant_keys::global() {
+                    artifacts = artifacts.with_tenant_keys(keys.clone());
+                }
+            }
+            let artifacts = Arc::new(artifacts);
+            artifacts.clone().spawn_gc();
+            Some(artifacts)
+        } else {
+            None
+        };
+        if self.config.content.spool_threshold_bytes > 0 {
+            crate::core::content::spawn_spool_gc(
+                crate::core::content::spool_dir(&self.config.content),
+                Duration::from_secs(self.config.content.spool_gc_interval_seconds.max(1)),
+            );
+        }
+
+        let slow_requests = self.config.slow_requests.enabled.then(|| {
+            Arc::new(SlowRequestLog::new(
+                Duration::from_millis(self.config.slow_requests.threshold_ms),
+                self.config.slow_requests.retain,
+            ))
+        });
+
+        let rbac = self
+            .config
+            .rbac
+            .enabled
+            .then(|| Arc::new(Rbac::from_config(&self.config.rbac)));
+
         let app_state = Arc::new(AppState {
             server_manager: server_manager.clone(),
             lazy_loader,
+            streaming: self.config.streaming.clone(),
+            content: self.config.content.clone(),
+            elevation: self.config.elevation.clone(),
+            resource_uris: if self.config.resources.mediate_file_uris {
+                Some(Arc::new(ResourceUriMapper::new()?))
+            } else {
+                None
+            },
+            artifacts,
+            remote_config: self.remote_config.clone(),
+            config_manager: self.config_manager.clone(),
+            runtime_tools: RuntimeTools::from_config(&self.config),
+            file_broker: FileBroker::from_config(&self.config),
+            sessions,
+            session_ring: session_ring.clone(),
+            presets: self.config.presets.clone(),
+            templates: self.config.templates.clone(),
+            admin_scope: self.config.admin.required_scope.clone(),
+            rbac: rbac.clone(),
+            consents: self.config.consent.enabled.then(|| {
+                Arc::new(ConsentStore::new(
+                    create_state_backend(&self.config.state),
+                    &self.config.consent,
+                ))
+            }),
+            slow_requests: slow_requests.clone(),
+            shared_lists: (cluster.enabled && self.config.lazy_loading.replicate).then(|| {
+                Arc::new(SharedLists::new(
+                    create_state_backend(&self.config.state),
+                    Duration::from_secs(self.config.lazy_loading.schema_cache_ttl_seconds),
+                ))
+            }),
+            read_replica: cluster.enabled && cluster.read_replica,
         });
+        let admin_router = self
+            .config
+            .admin
+            .enabled
+            .then(|| admin_routes(app_state.clone()));
 
         let mut mcp_router = Router::new()
-            .route("/mcp", post(routes::mcp_handler))
-            .route("/mcp/:server", post(routes::server_handler))
+            .route(
+                "/mcp",
+                post(routes::mcp_handler).delete(routes::mcp_session_delete_handler),
+            )
+            .route("/mcp/{server}", post(routes::server_handler))
             .route("/tools", get(routes::tool_list_handler))
             .route("/tools/schema", get(routes::tool_schema_handler))
             .route("/tools/invoke", post(routes::tool_invoke_handler))
             .route("/servers", get(routes::list_servers_handler))
-            .route("/servers/:server_name", get(routes::server_status_handler))
+            .route("/servers/{server_name}", get(routes::server_status_handler))
+            .route("/upstream/stats", get(routes::upstream_stats_handler))
+            .route("/supervision/stats", get(routes::supervision_stats_handler))
+            .route("/slo", get(routes::slo_handler))
             .route("/cache/stats", get(routes::cache_stats_handler))
+            .route("/content/{id}", get(routes::content_handler))
+            .route("/artifacts/{id}", get(routes::artifact_handler))
             .route("/cache/clear", post(routes::cache_clear_handler))
-            .with_state(app_state);
+            .with_state(app_state.clone());
+
+        // Self-service consent management, behind the same auth as /mcp
+        if self.config.consent.enabled {
+            mcp_router = mcp_router.merge(consent_routes(app_state));
+        }
 
         // Rate limiting
         let rate_limit_config = HttpRateLimitConfig {
@@ -95,17 +381,62 @@ impl HttpServer {
             burst_size: self.config.rate_limit.burst_size,
         };
         mcp_router = mcp_router.layer(create_rate_limit_layer(&rate_limit_config));
+        mcp_router = mcp_router.layer(middleware::from_fn_with_state(
+            replenish_interval(&rate_limit_config),
+            rate_limit_event_middleware,
+        ));
+
+        // Dry-run tool calls, inside auth and the read-only check so they
+        // are refused or allowed like real ones
+        mcp_router = mcp_router.layer(middleware::from_fn(dry_run_middleware));
+
+        // Anonymous clients are held to read-only methods. Applies even
+        // without features.auth, where nobody gets a session.
+        let anonymous_readonly = matches!(self.config.auth.auth_type, AuthType::AnonymousReadonly);
+        if anonymous_readonly {
+            mcp_router = mcp_router.layer(middleware::from_fn_with_state(
+                self.config.limits.max_request_bytes,
+                readonly_middleware,
+            ));
+        }
 
         // Size limits
-        let size_limit_config = SizeLimitConfig::default();
+        let size_limit_config = Arc::new(SizeLimitConfig::from(&self.config.limits));
         mcp_router = mcp_router.layer(middleware::from_fn_with_state(
             size_limit_config,
             size_limit_middleware,
         ));
 
+        // Priority classes, inside auth so the caller's scopes are known
+        if self.config.scheduling.enabled {
+            let header = HeaderName::from_bytes(self.config.scheduling.header.as_bytes())
+                .map_err(|e| anyhow::anyhow!("Invalid scheduling.header: {}", e))?;
+            let priority = Arc::new(PriorityState {
+                header,
+                classes: PriorityClasses::from_config(&self.config.scheduling),
+            });
+            mcp_router = mcp_router.layer(middleware::from_fn_with_state(priority, priority_middleware));
+        }
+
         // Authentication and scope validation
-        if self.config.features.auth {
-            if self.config.features.scope_validation && !self.config.auth.required_scopes.is_empty()
+        let auth_provider = if self.config.features.auth {
+            let provider = build_auth_provider(&self.config.auth).await?;
+            let cache = &self.config.auth.cache;
+            Some(if cache.enabled {
+                let cache = TokenCache::new(TokenCacheConfig::from_config(cache));
+                Arc::new(CachedAuth::new(provider, Arc::new(cache))) as Arc<dyn AuthProvider>
+            } else {
+                provider
+            })
+        } else {
+            None
+        };
+
+        if let Some(provider) = auth_provider.clone() {
+            // Anonymous sessions carry no scopes to validate
+            if self.config.features.scope_validation
+                && !self.config.auth.required_scopes.is_empty()
+                && !anonymous_readonly
             {
                 let scope_state = Arc::new(ScopeValidationState {
                     required_scopes: self.config.auth.required_scopes.clone(),
@@ -116,18 +447,120 @@ impl HttpServer {
                 ));
             }
 
-            let provider = build_auth_provider(&self.config.auth).await?;
-            let auth_state = Arc::new(AuthMiddlewareState::new(provider, true));
+            let auth_state = Arc::new(AuthMiddlewareState::new(provider, !anonymous_readonly));
             mcp_router = mcp_router.layer(middleware::from_fn_with_state(
                 auth_state,
                 auth_middleware,
             ));
         }
 
+        // Refuse requests that looped back through federated instances
+        mcp_router = mcp_router.layer(middleware::from_fn(federation_middleware));
+
+        // Open encrypted payloads from pinned federation peers, before the
+        // layers that read request bodies
+        if let Some(key) = crate::transport::envelope::identity() {
+            let envelope = Arc::new(EnvelopeState::new(
+                key,
+                &self.config.federation,
+                self.config.limits.max_request_bytes,
+            )?);
+            mcp_router = mcp_router.layer(middleware::from_fn_with_state(envelope, envelope_middleware));
+        } else if self.config.federation.require_encryption {
+            anyhow::bail!("federation.require_encryption needs federation.private_key");
+        }
+
+        // Trace phases of every request, outside auth so it is timed too
+        if let Some(log) = slow_requests {
+            mcp_router = mcp_router.layer(middleware::from_fn_with_state(log, slow_request_middleware));
+        }
+
+        // Shed load before doing any work for the request
+        if let Some(backpressure) = backpressure::global() {
+            mcp_router = mcp_router.layer(middleware::from_fn_with_state(backpressure, backpressure_middleware));
+        }
+
+        // Hand session requests to the owning node before any local work;
+        // replicas hand over everything they cannot answer from shared lists
+        if let Some(ring) = session_ring {
+            let routing = Arc::new(SessionRoutingState::new(ring, self.config.limits.max_request_bytes));
+            mcp_router = if cluster.read_replica {
+                mcp_router.layer(middleware::from_fn_with_state(routing, read_replica_middleware))
+            } else {
+                mcp_router.layer(middleware::from_fn_with_state(routing, session_routing_middleware))
+            };
+        }
+
         let mut app = Router::new()
             .route("/health", get(routes::health))
             .merge(mcp_router);
 
+        // Public JWT keys for downstream validators
+        if self.config.auth.publish_jwks {
+            if matches!(self.config.auth.auth_type, AuthType::Jwt) {
+                let jwks = build_jwt_auth(&self.config.auth)?.jwks();
+                if jwks.keys.is_empty() {
+                    warn!("auth.publish_jwks is set but no asymmetric JWT keys are configured");
+                }
+                app = app.route(
+                    "/.well-known/jwks.json",
+                    get(move || async move { Json(jwks) }),
+                );
+            } else {
+                warn!("auth.publish_jwks only applies to jwt auth");
+            }
+        }
+
+        // Admin API: always behind auth and the admin scope, except on a
+        // loopback-only listener without auth. Browser logins through OIDC
+        // authenticate with a cookie instead of a bearer token.
+        if let Some(mut admin_router) = admin_router {
+            let oidc = &self.config.admin.oidc;
+            let admin_login = if oidc.enabled {
+                Some(Arc::new(AdminLogin::from_config(oidc).await?))
+            } else {
+                None
+            };
+
+            if auth_provider.is_some() || admin_login.is_some() {
+                admin_router = match rbac {
+                    Some(rbac) => admin_router.layer(middleware::from_fn_with_state(rbac, rbac_middleware)),
+                    None => admin_router.layer(middleware::from_fn_with_state(
+                        Arc::new(ScopeValidationState {
+                            required_scopes: vec![self.config.admin.required_scope.clone()],
+                        }),
+                        crate::http_server::middleware::scope_validation_middleware,
+                    )),
+                };
+                if let Some(provider) = auth_provider {
+                    admin_router = admin_router.layer(middleware::from_fn_with_state(
+                        Arc::new(AuthMiddlewareState::new(provider, true)),
+                        auth_middleware,
+                    ));
+                }
+                if let Some(login) = admin_login {
+                    admin_router = admin_router
+                        .layer(middleware::from_fn_with_state(
+                            login.clone(),
+                            admin_session_middleware,
+                        ))
+                        .merge(login.routes());
+                }
+                app = app.merge(admin_router);
+            } else if is_loopback(&self.config.server.host) && !anonymous_readonly {
+                warn!("Admin API is enabled without authentication on a loopback listener");
+                app = app.merge(admin_router);
+            } else {
+                error!("Admin API disabled: it requires authentication (features.auth or admin.oidc)");
+            }
+        }
+
+        // Client IP resolution and allow/deny lists, ahead of rate limiting and auth
+        app = app.layer(middleware::from_fn_with_state(
+            access,
+            access_control_middleware,
+        ));
+
         // Security headers for all responses
         let security_config = SecurityHeadersConfig::default();
         app = app.layer(middleware::from_fn_with_state(
@@ -135,61 +568,128 @@ impl HttpServer {
             security_headers_middleware,
         ));
 
+        // Correlation IDs, outside everything else so every log line and
+        // response carries one
+        app = app.layer(middleware::from_fn_with_state(
+            SharedRequestIdGenerator::with_uuid(),
+            request_id_middleware,
+        ));
+
+        // Negotiated response compression
+        if self.config.compression.enabled {
+            let excluded_paths = Arc::new(self.config.compression.exclude_paths.clone());
+            app = app
+                .layer(middleware::from_fn_with_state(
+                    excluded_paths,
+                    compression_opt_out_middleware,
+                ))
+                .layer(create_compression_layer(&self.config.compression));
+        }
+
         Ok(app)
     }
 }
 
-async fn build_auth_provider(auth: &AuthConfig) -> anyhow::Result<Arc<dyn AuthProvider>> {
-    fn parse_algorithms(algs: &[String]) -> anyhow::Result<Vec<Algorithm>> {
-        let mut parsed = Vec::new();
-        for alg in algs {
-            let normalized = alg.trim().to_ascii_uppercase();
-            let parsed_alg = match normalized.as_str() {
-                "HS256" => Algorithm::HS256,
-                "HS384" => Algorithm::HS384,
-                "HS512" => Algorithm::HS512,
-                "RS256" => Algorithm::RS256,
-                "RS384" => Algorithm::RS384,
-                "RS512" => Algorithm::RS512,
-                "ES256" => Algorithm::ES256,
-                "ES384" => Algorithm::ES384,
-                "PS256" => Algorithm::PS256,
-                "PS384" => Algorithm::PS384,
-                "PS512" => Algorithm::PS512,
-                "EDDSA" => Algorithm::EdDSA,
-                _ => {
-                    return Err(anyhow::anyhow!(format!(
-                        "Unsupported JWT algorithm: {}",
-                        alg
-                    )))
-                }
-            };
-            parsed.push(parsed_alg);
+fn is_loopback(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+fn parse_algorithms(algs: &[String]) -> anyhow::Result<Vec<Algorithm>> {
+    let mut parsed = Vec::new();
+    for alg in algs {
+        let normalized = alg.trim().to_ascii_uppercase();
+        let parsed_alg = match normalized.as_str() {
+            "HS256" => Algorithm::HS256,
+            "HS384" => Algorithm::HS384,
+            "HS512" => Algorithm::HS512,
+            "RS256" => Algorithm::RS256,
+            "RS384" => Algorithm::RS384,
+            "RS512" => Algorithm::RS512,
+            "ES256" => Algorithm::ES256,
+            "ES384" => Algorithm::ES384,
+            "PS256" => Algorithm::PS256,
+            "PS384" => Algorithm::PS384,
+            "PS512" => Algorithm::PS512,
+            "EDDSA" => Algorithm::EdDSA,
+            _ => {
+                return Err(anyhow::anyhow!(format!(
+                    "Unsupported JWT algorithm: {}",
+                    alg
+                )))
+            }
+        };
+        parsed.push(parsed_alg);
+    }
+    Ok(parsed)
+}
+
+pub(crate) fn build_jwt_auth(auth: &AuthConfig) -> anyhow::Result<JwtAuth> {
+    let issuer = auth
+        .issuer
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("auth.issuer is required for jwt auth"))?;
+
+    if auth.jwt_keys.is_empty() {
+        let secret = auth
+            .jwt_secret
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("auth.jwt_secret is required for jwt auth"))?;
+        return Ok(JwtAuth::new(secret).with_issuer(issuer));
+    }
+
+    let mut keys = Vec::new();
+    for key in &auth.jwt_keys {
+        if key.kid.is_empty() {
+            return Err(anyhow::anyhow!("auth.jwt_keys entries need a kid"));
         }
-        Ok(parsed)
+        let algorithm = parse_algorithms(std::slice::from_ref(&key.algorithm))?[0];
+        let jwt_key = match (&key.secret, &key.private_key_path) {
+            (Some(secret), None) => JwtKey::hmac(Some(key.kid.clone()), algorithm, secret)?,
+            (None, Some(path)) => {
+                let pem = std::fs::read(path).map_err(|e| {
+                    anyhow::anyhow!("Failed to read JWT key '{}' from {}: {}", key.kid, path, e)
+                })?;
+                JwtKey::from_pem(key.kid.clone(), algorithm, &pem)?
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "JWT key '{}' needs exactly one of secret or private_key_path",
+                    key.kid
+                ))
+            }
+        };
+        keys.push(if key.retired { jwt_key.retired() } else { jwt_key });
     }
 
+    Ok(JwtAuth::with_keys(keys).with_issuer(issuer))
+}
+
+async fn build_auth_provider(auth: &AuthConfig) -> anyhow::Result<Arc<dyn AuthProvider>> {
+
     match auth.auth_type {
         AuthType::None => Err(anyhow::anyhow!(
             "auth.type is none but features.auth is enabled"
         )),
         AuthType::Static => {
-            let token = auth
-                .token
-                .clone()
-                .ok_or_else(|| anyhow::anyhow!("auth.token is required for static auth"))?;
-            Ok(Arc::new(StaticTokenAuth::new(token)))
+            if auth.token.is_none() && auth.tokens.is_empty() {
+                return Err(anyhow::anyhow!("auth.token or auth.tokens is required for static auth"));
+            }
+            let mut provider = StaticTokenAuth::new(auth.token.clone().unwrap_or_default());
+            for token in &auth.tokens {
+                provider = provider.with_labeled_token(&token.label, &token.token, token.scopes.clone());
+            }
+            Ok(Arc::new(provider))
         }
-        AuthType::Jwt => {
-            let secret = auth
-                .jwt_secret
-                .clone()
-                .ok_or_else(|| anyhow::anyhow!("auth.jwt_secret is required for jwt auth"))?;
-            let issuer = auth
-                .issuer
-                .clone()
-                .ok_or_else(|| anyhow::anyhow!("auth.issuer is required for jwt auth"))?;
-            Ok(Arc::new(JwtAuth::new(secret).with_issuer(issuer)))
+        AuthType::Jwt => Ok(Arc::new(build_jwt_auth(auth)?)),
+        AuthType::AnonymousReadonly => {
+            let mut provider = AnonymousReadonlyAuth::new();
+            if let Some(token) = auth.token.clone() {
+                provider = provider.with_operator_token(token);
+            }
+            Ok(Arc::new(provider))
         }
         AuthType::OAuth => {
             let client_id = auth
@@ -244,3 +744,191 @@ async fn build_auth_provider(auth: &AuthConfig) -> anyhow::Result<Arc<dyn AuthPr
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_router_builds_and_routes_path_params() {
+        let mut config = Config::default();
+        config.features.auth = false;
+        let access = create_access_control(&config.server.access).unwrap();
+        let server = HttpServer::new(config, Arc::new(ServerManager::new()));
+        let app = server.create_router(access).await.unwrap();
+
+        let request = Request::get("/servers/missing")
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_ne!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_upstream_keeps_content_limits() {
+        use base64::Engine;
+
+        // Streamable HTTP upstream answering with chunked bodies, which
+        // would be streamed through
+        let upstream = Router::new().route(
+            "/mcp",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                let image = base64::engine::general_purpose::STANDARD.encode(vec![7u8; 4096]);
+                let result = match request["method"].as_str() {
+                    Some("tools/call") => serde_json::json!({
+                        "content": [{ "type": "image", "data": image, "mimeType": "image/png" }]
+                    }),
+                    _ => serde_json::json!({}),
+                };
+                let line = format!(
+                    "{}\n",
+                    serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                );
+                Body::from_stream(futures::stream::iter([Ok::<_, std::io::Error>(line)]))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await });
+
+        let manager = Arc::new(ServerManager::new());
+        manager
+            .add_server(crate::config::McpServerConfig {
+                name: "images".to_string(),
+                url: Some(format!("http://{}/mcp", addr)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let mut config = Config::default();
+        config.features.auth = false;
+        config.content.max_content_bytes = 1024;
+        config.streaming.max_in_memory_bytes = 16;
+        // URI mediation would buffer the result regardless
+        config.resources.mediate_file_uris = false;
+        let access = create_access_control(&config.server.access).unwrap();
+        let app = HttpServer::new(config, manager).create_router(access).await.unwrap();
+
+        let call = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": { "name": "screenshot", "arguments": {} }
+        });
+        let request = Request::post("/mcp/images")
+            .header("content-type", "application/json")
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::from(call.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let block = &response["result"]["content"][0];
+        assert_eq!(block["type"], "text");
+        assert!(block["text"].as_str().unwrap().contains("exceeds the 1024 byte limit"));
+    }
+
+    #[tokio::test]
+    async fn test_preset_limited_role_cannot_name_other_servers() {
+        let upstream = Router::new().route(
+            "/mcp",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": {} }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await });
+
+        let manager = Arc::new(ServerManager::new());
+        manager
+            .add_server(crate::config::McpServerConfig {
+                name: "github".to_string(),
+                url: Some(format!("http://{}/mcp", addr)),
+                tags: vec!["dev".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let mut config = Config::default();
+        config.auth.auth_type = AuthType::Static;
+        config.auth.token = Some("admin-token".to_string());
+        config.auth.tokens = vec![crate::config::StaticTokenConfig {
+            label: "bob".to_string(),
+            token: "analyst-token".to_string(),
+            scopes: vec!["analyst".to_string()],
+        }];
+        config.presets = vec![PresetConfig {
+            name: "research".to_string(),
+            tags: vec!["research".to_string()],
+            description: None,
+        }];
+        config.rbac.enabled = true;
+        config.rbac.roles.insert(
+            "analyst".to_string(),
+            crate::config::RoleConfig {
+                permissions: Vec::new(),
+                presets: vec!["research".to_string()],
+            },
+        );
+        let access = create_access_control(&config.server.access).unwrap();
+        let app = HttpServer::new(config, manager).create_router(access).await.unwrap();
+
+        let post = |uri: &str, token: &str, body: serde_json::Value| {
+            Request::post(uri)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let call = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": { "name": "create_issue", "arguments": {} }
+        });
+        let request = post("/mcp/github", "analyst-token", call.clone());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let invoke = serde_json::json!({ "server": "github", "tool": "create_issue" });
+        let request = post("/tools/invoke", "analyst-token", invoke);
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Roles without a preset list reach every server
+        let response = app.oneshot(post("/mcp/github", "admin-token", call)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_dry_run_call_refused() {
+        let mut config = Config::default();
+        config.auth.auth_type = AuthType::AnonymousReadonly;
+        let access = create_access_control(&config.server.access).unwrap();
+        let app = HttpServer::new(config, Arc::new(ServerManager::new()))
+            .create_router(access)
+            .await
+            .unwrap();
+
+        let call = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": { "name": "github.create_issue", "arguments": {} }
+        });
+        let request = Request::post("/mcp")
+            .header("content-type", "application/json")
+            .header(crate::core::dry_run::DRY_RUN_HEADER, "true")
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+            .body(Body::from(call.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "READ_ONLY_ACCESS");
+    }
+}
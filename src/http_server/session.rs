@@ -0,0 +1,381 @@
+//! Downstream MCP sessions
+//!
+//! A session is created when a client initializes over `/mcp` and is
+//! identified by the `Mcp-Session-Id` header from then on. Sessions are
+//! written to the state backend under `sessions/`, so another node or a
+//! restarted proxy sharing the backend picks them up on first use.
+
+use crate::cloud::StateBackend;
+use crate::core::{ServerManager, SessionRoute};
+use crate::utils::errors::McpResult;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Header carrying the session ID (Streamable HTTP transport)
+pub const MCP_SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// State backend key prefix for sessions
+const KEY_PREFIX: &str = "sessions/";
+
+/// `last_seen` is only written back this often, to keep requests cheap
+const TOUCH_INTERVAL_SECONDS: i64 = 60;
+
+/// How often expired sessions are purged
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Downstream transport sessions are established over
+const STREAMABLE_HTTP: &str = "streamable_http";
+
+fn default_transport() -> String {
+    STREAMABLE_HTTP.to_string()
+}
+
+/// State negotiated when a client initialized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpSession {
+    pub id: String,
+    /// Downstream transport the client connected with
+    #[serde(default = "default_transport")]
+    pub transport: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    #[serde(default)]
+    pub client_info: Value,
+    #[serde(default)]
+    pub client_capabilities: Value,
+    #[serde(default)]
+    pub server_capabilities: Value,
+    /// Preset limiting which servers the session is routed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Authenticated user that created the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Requests made in the session; persisted with `last_seen`, so it can
+    /// lag slightly on other nodes
+    #[serde(default)]
+    pub request_count: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl McpSession {
+    /// Build a session from an `initialize` request and its result
+    pub fn from_initialize(
+        params: Option<&Value>,
+        result: Option<&Value>,
+        preset: Option<String>,
+        identity: Option<String>,
+    ) -> Self {
+        let field = |value: Option<&Value>, name: &str| {
+            value.and_then(|v| v.get(name)).cloned().unwrap_or(Value::Null)
+        };
+        let protocol_version = result
+            .or(params)
+            .and_then(|v| v.get("protocolVersion"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        let now = Utc::now();
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            transport: default_transport(),
+            protocol_version,
+            client_info: field(params, "clientInfo"),
+            client_capabilities: field(params, "capabilities"),
+            server_capabilities: field(result, "capabilities"),
+            preset,
+            identity,
+            request_count: 1,
+            created_at: now,
+            last_seen: now,
+        }
+    }
+
+    /// Routing info for requests made in this session
+    pub fn route(&self) -> SessionRoute<'_> {
+        let mut params = serde_json::json!({
+            "capabilities": self.client_capabilities,
+            "clientInfo": self.client_info,
+        });
+        if let Some(version) = &self.protocol_version {
+            params["protocolVersion"] = Value::String(version.clone());
+        }
+        SessionRoute {
+            id: &self.id,
+            initialize_params: params,
+        }
+    }
+
+    fn is_expired(&self, idle_timeout: chrono::Duration, now: DateTime<Utc>) -> bool {
+        now - self.last_seen > idle_timeout
+    }
+}
+
+/// Session IDs are generated UUIDs; anything else can't be a valid key
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn key(id: &str) -> String {
+    format!("{}{}", KEY_PREFIX, id)
+}
+
+/// Sessions kept in the state backend with a local cache
+pub struct SessionStore {
+    backend: Arc<dyn StateBackend>,
+    cache: DashMap<String, McpSession>,
+    idle_timeout: chrono::Duration,
+    /// Stops dedicated upstream processes when sessions end
+    server_manager: Option<Arc<ServerManager>>,
+}
+
+impl SessionStore {
+    pub fn new(backend: Arc<dyn StateBackend>, idle_timeout: Duration) -> Self {
+        Self {
+            backend,
+            cache: DashMap::new(),
+            idle_timeout: chrono::Duration::from_std(idle_timeout)
+                .unwrap_or(chrono::Duration::MAX),
+            server_manager: None,
+        }
+    }
+
+    /// Tie the lifetime of per-session upstream processes to sessions
+    pub fn with_server_manager(mut self, server_manager: Arc<ServerManager>) -> Self {
+        self.server_manager = Some(server_manager);
+        self
+    }
+
+    /// Store a new session
+    pub async fn insert(&self, session: McpSession) -> McpResult<()> {
+        self.backend
+            .set(&key(&session.id), serde_json::to_vec(&session)?)
+            .await?;
+        debug!("Created MCP session {}", session.id);
+        self.cache.insert(session.id.clone(), session);
+        Ok(())
+    }
+
+    /// Look up a live session, restoring it from the backend if needed
+    ///
+    /// Expired sessions are removed and reported as missing.
+    pub async fn get(&self, id: &str) -> McpResult<Option<McpSession>> {
+        if !is_valid_id(id) {
+            return Ok(None);
+        }
+
+        let session = match self.cache.get(id).map(|s| s.clone()) {
+            Some(session) => session,
+            None => match self.backend.get(&key(id)).await? {
+                Some(data) => {
+                    let session: McpSession = serde_json::from_slice(&data)?;
+                    info!("Restored MCP session {} from state backend", id);
+                    self.cache.insert(id.to_string(), session.clone());
+                    session
+                }
+                None => return Ok(None),
+            },
+        };
+
+        if session.is_expired(self.idle_timeout, Utc::now()) {
+            self.remove(id).await?;
+            return Ok(None);
+        }
+        Ok(Some(session))
+    }
+
+    /// Resume a session for a request
+    ///
+    /// A session is only handed back to the identity that created it, so a
+    /// leaked session ID can't be used from another account.
+    pub async fn resume(&self, id: &str, identity: Option<&str>) -> McpResult<Option<McpSession>> {
+        let Some(session) = self.get(id).await? else {
+            return Ok(None);
+        };
+        if session.identity.as_deref() != identity {
+            warn!("Rejected MCP session {} presented by a different identity", id);
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let (session, persist) = {
+            let Some(mut entry) = self.cache.get_mut(id) else {
+                return Ok(Some(session));
+            };
+            entry.request_count += 1;
+            let persist = (now - entry.last_seen).num_seconds() >= TOUCH_INTERVAL_SECONDS;
+            if persist {
+                entry.last_seen = now;
+            }
+            (entry.clone(), persist)
+        };
+
+        if persist {
+            self.backend
+                .set(&key(id), serde_json::to_vec(&session)?)
+                .await?;
+        }
+        Ok(Some(session))
+    }
+
+    /// Delete a session, returning whether it existed
+    pub async fn remove(&self, id: &str) -> McpResult<bool> {
+        if !is_valid_id(id) {
+            return Ok(false);
+        }
+        let cached = self.cache.remove(id).is_some();
+        let stored = self.backend.get(&key(id)).await?.is_some();
+        if stored {
+            self.backend.delete(&key(id)).await?;
+        }
+        if let Some(server_manager) = &self.server_manager {
+            server_manager.end_session(id).await;
+        }
+        Ok(cached || stored)
+    }
+
+    /// Every live session in the backend
+    pub async fn list(&self) -> McpResult<Vec<McpSession>> {
+        let now = Utc::now();
+        let mut sessions = Vec::new();
+        for key in self.backend.list(KEY_PREFIX).await? {
+            let Some(data) = self.backend.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<McpSession>(&data) {
+                Ok(session) if !session.is_expired(self.idle_timeout, now) => {
+                    // The local copy has the latest request count
+                    let session = match self.cache.get(&session.id) {
+                        Some(cached) => cached.clone(),
+                        None => session,
+                    };
+                    sessions.push(session);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Ignoring unreadable session {}: {}", key, e),
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Remove expired sessions, returning how many were removed
+    ///
+    /// Dedicated upstream processes are stopped for every session that is
+    /// no longer live, including ones another node ended. In a cluster only
+    /// the leader deletes expired sessions from the backend.
+    pub async fn purge_expired(&self) -> McpResult<usize> {
+        let now = Utc::now();
+        let leader = crate::cloud::leader::is_leader().await;
+        let mut purged = 0;
+        let mut live = HashSet::new();
+        for key in self.backend.list(KEY_PREFIX).await? {
+            let id = &key[KEY_PREFIX.len()..];
+            let expired = match self.backend.get(&key).await? {
+                Some(data) => serde_json::from_slice::<McpSession>(&data)
+                    .map(|session| session.is_expired(self.idle_timeout, now))
+                    .unwrap_or(true),
+                None => continue,
+            };
+            if expired {
+                self.cache.remove(id);
+                if leader {
+                    self.backend.delete(&key).await?;
+                    purged += 1;
+                }
+            } else {
+                live.insert(id.to_string());
+            }
+        }
+
+        self.cache.retain(|id, _| live.contains(id));
+        if let Some(server_manager) = &self.server_manager {
+            server_manager.retain_sessions(&live).await;
+        }
+        Ok(purged)
+    }
+
+    /// Periodically purge expired sessions in the background
+    pub fn spawn_sweeper(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                match self.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(n) => debug!("Purged {} expired MCP session(s)", n),
+                    Err(e) => warn!("Failed to purge expired MCP sessions: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::{FileBackend, InMemoryBackend};
+    use serde_json::json;
+
+    fn session(identity: Option<&str>) -> McpSession {
+        McpSession::from_initialize(
+            Some(&json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": { "roots": {} },
+                "clientInfo": { "name": "test", "version": "1.0" },
+            })),
+            Some(&json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": { "tools": {} },
+            })),
+            Some("dev".to_string()),
+            identity.map(|s| s.to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sessions_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = session(Some("alice"));
+        let id = original.id.clone();
+
+        let store = SessionStore::new(Arc::new(FileBackend::new(dir.path())), Duration::from_secs(60));
+        store.insert(original.clone()).await.unwrap();
+        drop(store);
+
+        // A fresh store over the same backend stands in for a restarted proxy
+        let store = SessionStore::new(Arc::new(FileBackend::new(dir.path())), Duration::from_secs(60));
+        let restored = store.resume(&id, Some("alice")).await.unwrap().unwrap();
+        assert_eq!(restored.request_count, 2);
+        assert_eq!(restored.client_capabilities, original.client_capabilities);
+        assert_eq!(restored.protocol_version.as_deref(), Some("2025-03-26"));
+        assert_eq!(restored.client_info["name"], "test");
+        assert_eq!(store.list().await.unwrap().len(), 1);
+
+        assert!(store.resume(&id, Some("mallory")).await.unwrap().is_none());
+        assert!(store.resume(&id, None).await.unwrap().is_none());
+        assert!(store.remove(&id).await.unwrap());
+        assert!(store.get(&id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_purged() {
+        let store = SessionStore::new(Arc::new(InMemoryBackend::new()), Duration::from_secs(60));
+        let mut stale = session(None);
+        stale.last_seen = Utc::now() - chrono::Duration::seconds(120);
+        let live = session(None);
+        store.insert(stale.clone()).await.unwrap();
+        store.insert(live.clone()).await.unwrap();
+
+        assert_eq!(store.purge_expired().await.unwrap(), 1);
+        assert!(store.get(&stale.id).await.unwrap().is_none());
+        assert!(store.get(&live.id).await.unwrap().is_some());
+        assert!(store.get("../escape").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,550 @@
+//! SPIFFE workload identity
+//!
+//! Streams X.509-SVIDs and trust bundles from the SPIFFE Workload API
+//! (e.g. a SPIRE agent), serves the SVID as the TLS certificate and checks
+//! inbound mTLS peers against the bundle of the trust domain their SPIFFE
+//! ID claims and the configured SPIFFE ID policy.
+//! Rotations pushed by the agent take effect without a restart.
+
+use crate::config::SpiffeConfig;
+use crate::http_server::tls::{certified_key_from_der, CertStore};
+use crate::utils::errors::{McpError, McpResult};
+use parking_lot::RwLock;
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{CertificateError, DigitallySignedStruct, DistinguishedName, RootCertStore, SignatureScheme};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::codec::Streaming;
+use tracing::{info, warn};
+use x509_parser::extensions::GeneralName;
+
+/// Delay between Workload API reconnection attempts
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Environment variable naming the Workload API socket
+const ENDPOINT_SOCKET_ENV: &str = "SPIFFE_ENDPOINT_SOCKET";
+
+/// SPIFFE ID of an authenticated mTLS peer, added to request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerSpiffeId(pub String);
+
+/// Workload API messages (spiffe/go-spiffe workload.proto)
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct X509SvidRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct X509SvidResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub svids: Vec<X509Svid>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub crl: Vec<Vec<u8>>,
+    #[prost(map = "string, bytes", tag = "3")]
+    pub federated_bundles: HashMap<String, Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct X509Svid {
+    #[prost(string, tag = "1")]
+    pub spiffe_id: String,
+    /// ASN.1 DER certificate chain, leaf first
+    #[prost(bytes = "vec", tag = "2")]
+    pub x509_svid: Vec<u8>,
+    /// PKCS#8 DER private key
+    #[prost(bytes = "vec", tag = "3")]
+    pub x509_svid_key: Vec<u8>,
+    /// ASN.1 DER CA certificates of our trust domain
+    #[prost(bytes = "vec", tag = "4")]
+    pub bundle: Vec<u8>,
+    #[prost(string, tag = "5")]
+    pub hint: String,
+}
+
+/// Which peers may connect, by SPIFFE ID
+#[derive(Debug, Clone, Default)]
+pub struct SpiffePolicy {
+    allowed_trust_domains: Vec<String>,
+    allowed_ids: Vec<String>,
+}
+
+impl SpiffePolicy {
+    pub fn new(config: &SpiffeConfig) -> Self {
+        Self {
+            allowed_trust_domains: config.allowed_trust_domains.clone(),
+            allowed_ids: config.allowed_ids.clone(),
+        }
+    }
+
+    /// Check a peer ID; with no trust domains configured only our own is allowed
+    pub fn is_allowed(&self, id: &str, own_trust_domain: &str) -> bool {
+        let Some(trust_domain) = trust_domain_of(id) else {
+            return false;
+        };
+
+        let trust_domain_allowed = if self.allowed_trust_domains.is_empty() {
+            trust_domain == own_trust_domain
+        } else {
+            self.allowed_trust_domains.iter().any(|td| td == trust_domain)
+        };
+
+        trust_domain_allowed
+            && (self.allowed_ids.is_empty()
+                || self.allowed_ids.iter().any(|pattern| id_matches(pattern, id)))
+    }
+}
+
+fn id_matches(pattern: &str, id: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => id
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/')),
+        None => pattern == id,
+    }
+}
+
+/// Trust domain of a SPIFFE ID (`spiffe://<trust-domain>/<path>`)
+pub fn trust_domain_of(id: &str) -> Option<&str> {
+    let rest = id.strip_prefix("spiffe://")?;
+    let trust_domain = rest.split('/').next()?;
+    (!trust_domain.is_empty()).then_some(trust_domain)
+}
+
+/// Extract the SPIFFE ID from a certificate's URI SAN
+pub fn spiffe_id_of(der: &[u8]) -> Option<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+    let san = cert.subject_alternative_name().ok()??;
+    san.value.general_names.iter().find_map(|name| match name {
+        GeneralName::URI(uri) if uri.starts_with("spiffe://") => Some(uri.to_string()),
+        _ => None,
+    })
+}
+
+/// Split concatenated DER certificates
+fn split_der_certificates(mut data: &[u8]) -> McpResult<Vec<CertificateDer<'static>>> {
+    let mut certs = Vec::new();
+    while !data.is_empty() {
+        let (rest, _) = x509_parser::parse_x509_certificate(data)
+            .map_err(|e| McpError::TransportError(format!("Invalid SVID certificate: {}", e)))?;
+        let len = data.len() - rest.len();
+        certs.push(CertificateDer::from(data[..len].to_vec()));
+        data = rest;
+    }
+    Ok(certs)
+}
+
+/// Trust domain of a federated bundle key, given as a name or SPIFFE ID
+fn bundle_trust_domain(key: &str) -> &str {
+    key.strip_prefix("spiffe://").unwrap_or(key).trim_end_matches('/')
+}
+
+#[derive(Clone)]
+struct TrustState {
+    /// One verifier per trust domain, so a CA only vouches for its own
+    verifiers: HashMap<String, Arc<dyn ClientCertVerifier>>,
+    trust_domain: String,
+}
+
+/// Serving identity and peer verifier backed by the Workload API
+pub struct SpiffeIdentity {
+    certs: Arc<CertStore>,
+    trust: RwLock<Option<TrustState>>,
+    policy: SpiffePolicy,
+    require_client_cert: bool,
+    provider: Arc<CryptoProvider>,
+}
+
+impl std::fmt::Debug for SpiffeIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpiffeIdentity")
+            .field("trust_domain", &self.trust.read().as_ref().map(|t| t.trust_domain.clone()))
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl SpiffeIdentity {
+    pub fn new(config: &SpiffeConfig, certs: Arc<CertStore>) -> Self {
+        Self {
+            certs,
+            trust: RwLock::new(None),
+            policy: SpiffePolicy::new(config),
+            require_client_cert: config.require_client_cert,
+            provider: Arc::new(rustls::crypto::ring::default_provider()),
+        }
+    }
+
+    /// Install the default SVID and rebuild the peer verifier from the bundles
+    pub fn apply(&self, update: X509SvidResponse) -> McpResult<()> {
+        let svid = update
+            .svids
+            .into_iter()
+            .next()
+            .ok_or_else(|| McpError::TransportError("Workload API returned no SVIDs".to_string()))?;
+
+        let trust_domain = trust_domain_of(&svid.spiffe_id)
+            .ok_or_else(|| {
+                McpError::TransportError(format!("Invalid SPIFFE ID '{}'", svid.spiffe_id))
+            })?
+            .to_string();
+
+        let bundles = std::iter::once((trust_domain.as_str(), &svid.bundle)).chain(
+            update
+                .federated_bundles
+                .iter()
+                .map(|(key, bundle)| (bundle_trust_domain(key), bundle))
+                // Our own bundle comes with the SVID
+                .filter(|(domain, _)| *domain != trust_domain),
+        );
+        let mut verifiers = HashMap::new();
+        for (domain, bundle) in bundles {
+            let mut roots = RootCertStore::empty();
+            for ca in split_der_certificates(bundle)? {
+                roots
+                    .add(ca)
+                    .map_err(|e| McpError::TransportError(format!("Invalid bundle CA: {}", e)))?;
+            }
+            let verifier =
+                WebPkiClientVerifier::builder_with_provider(Arc::new(roots), self.provider.clone())
+                    .build()
+                    .map_err(|e| {
+                        McpError::ConfigError(format!("Invalid trust bundle for {}: {}", domain, e))
+                    })?;
+            verifiers.insert(domain.to_string(), verifier);
+        }
+
+        let chain = split_der_certificates(&svid.x509_svid)?;
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(svid.x509_svid_key));
+        self.certs.set_certificate(certified_key_from_der(chain, key)?);
+
+        *self.trust.write() = Some(TrustState {
+            verifiers,
+            trust_domain,
+        });
+
+        info!("Loaded X.509-SVID {}", svid.spiffe_id);
+        Ok(())
+    }
+
+    /// Fetch the first SVID, then keep following rotations in the background
+    pub async fn start(self: Arc<Self>, config: &SpiffeConfig) -> McpResult<()> {
+        let socket = config
+            .workload_api_socket
+            .clone()
+            .or_else(|| std::env::var(ENDPOINT_SOCKET_ENV).ok())
+            .ok_or_else(|| {
+                McpError::ConfigError(format!(
+                    "server.spiffe.workload_api_socket is not set and {} is empty",
+                    ENDPOINT_SOCKET_ENV
+                ))
+            })?;
+
+        let mut stream = fetch_x509_svids(&socket).await?;
+        let first = stream
+            .message()
+            .await
+            .map_err(status_error)?
+            .ok_or_else(|| McpError::TransportError("Workload API closed the stream".to_string()))?;
+        self.apply(first)?;
+
+        tokio::spawn(self.watch(socket, stream));
+        Ok(())
+    }
+
+    async fn watch(self: Arc<Self>, socket: String, mut stream: Streaming<X509SvidResponse>) {
+        loop {
+            match stream.message().await {
+                Ok(Some(update)) => {
+                    if let Err(e) = self.apply(update) {
+                        warn!("Ignoring invalid SVID update: {}", e);
+                    }
+                    continue;
+                }
+                Ok(None) => warn!("Workload API stream ended, reconnecting"),
+                Err(e) => warn!("Workload API stream failed: {}, reconnecting", e),
+            }
+
+            stream = loop {
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                match fetch_x509_svids(&socket).await {
+                    Ok(stream) => break stream,
+                    Err(e) => warn!("Failed to reconnect to Workload API: {}", e),
+                }
+            };
+        }
+    }
+}
+
+impl ClientCertVerifier for SpiffeIdentity {
+    fn client_auth_mandatory(&self) -> bool {
+        self.require_client_cert
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let trust = self
+            .trust
+            .read()
+            .clone()
+            .ok_or_else(|| rustls::Error::General("SPIFFE trust bundle not loaded".to_string()))?;
+
+        let rejected = rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure);
+        let id = spiffe_id_of(end_entity.as_ref()).ok_or(rejected.clone())?;
+
+        // The chain must lead to the bundle of the domain the ID claims
+        let verifier = trust_domain_of(&id)
+            .and_then(|domain| trust.verifiers.get(domain))
+            .ok_or_else(|| {
+                warn!("Rejected mTLS peer {}: no trust bundle for its trust domain", id);
+                rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer)
+            })?;
+        let verified = verifier.verify_client_cert(end_entity, intermediates, now)?;
+
+        if !self.policy.is_allowed(&id, &trust.trust_domain) {
+            warn!("Rejected mTLS peer {} by SPIFFE policy", id);
+            return Err(rejected);
+        }
+
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn status_error(status: tonic::Status) -> McpError {
+    McpError::TransportError(format!("Workload API error: {}", status.message()))
+}
+
+/// Open a FetchX509SVID stream on the Workload API socket
+#[cfg(unix)]
+async fn fetch_x509_svids(socket: &str) -> McpResult<Streaming<X509SvidResponse>> {
+    use hyper::http::uri::PathAndQuery;
+    use tonic::metadata::MetadataValue;
+
+    let path = socket
+        .strip_prefix("unix://")
+        .or_else(|| socket.strip_prefix("unix:"))
+        .unwrap_or(socket)
+        .to_string();
+
+    let channel = tonic::transport::Endpoint::from_static("http://localhost")
+        .connect_with_connector(tower::service_fn(move |_: hyper::Uri| {
+            let path = path.clone();
+            async move {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+            }
+        }))
+        .await
+        .map_err(|e| McpError::TransportError(format!("Workload API connect failed: {}", e)))?;
+
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| McpError::TransportError(format!("Workload API unavailable: {}", e)))?;
+
+    let mut request = tonic::Request::new(X509SvidRequest {});
+    request
+        .metadata_mut()
+        .insert("workload.spiffe.io", MetadataValue::from_static("true"));
+
+    let codec = tonic::codec::ProstCodec::<X509SvidRequest, X509SvidResponse>::default();
+    let response = grpc
+        .server_streaming(
+            request,
+            PathAndQuery::from_static("/SpiffeWorkloadAPI/FetchX509SVID"),
+            codec,
+        )
+        .await
+        .map_err(status_error)?;
+
+    Ok(response.into_inner())
+}
+
+#[cfg(not(unix))]
+async fn fetch_x509_svids(_socket: &str) -> McpResult<Streaming<X509SvidResponse>> {
+    Err(McpError::ConfigError(
+        "The SPIFFE Workload API is only supported on unix platforms".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(trust_domains: &[&str], ids: &[&str]) -> SpiffePolicy {
+        SpiffePolicy::new(&SpiffeConfig {
+            allowed_trust_domains: trust_domains.iter().map(|s| s.to_string()).collect(),
+            allowed_ids: ids.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_trust_domain_of() {
+        assert_eq!(trust_domain_of("spiffe://example.org/ns/app"), Some("example.org"));
+        assert_eq!(trust_domain_of("spiffe:///ns/app"), None);
+        assert_eq!(trust_domain_of("https://example.org"), None);
+    }
+
+    #[test]
+    fn test_policy_defaults_to_own_trust_domain() {
+        let policy = policy(&[], &[]);
+        assert!(policy.is_allowed("spiffe://example.org/app", "example.org"));
+        assert!(!policy.is_allowed("spiffe://other.org/app", "example.org"));
+    }
+
+    #[test]
+    fn test_policy_id_patterns() {
+        let policy = policy(&["example.org", "partner.org"], &["spiffe://example.org/ns/prod/*"]);
+        assert!(policy.is_allowed("spiffe://example.org/ns/prod/api", "example.org"));
+        assert!(!policy.is_allowed("spiffe://example.org/ns/prod-canary/api", "example.org"));
+        assert!(!policy.is_allowed("spiffe://partner.org/ns/prod/api", "example.org"));
+    }
+
+    #[test]
+    fn test_spiffe_id_from_certificate() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.subject_alt_names = vec![rcgen::SanType::URI(
+            "spiffe://example.org/workload".try_into().unwrap(),
+        )];
+        let cert = params.self_signed(&key).unwrap();
+
+        assert_eq!(
+            spiffe_id_of(cert.der()).as_deref(),
+            Some("spiffe://example.org/workload")
+        );
+
+        let der = [cert.der().as_ref(), cert.der().as_ref()].concat();
+        assert_eq!(split_der_certificates(&der).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_apply_svid_update() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        params.subject_alt_names = vec![rcgen::SanType::URI(
+            "spiffe://example.org/proxy".try_into().unwrap(),
+        )];
+        let cert = params.self_signed(&key).unwrap();
+
+        let certs = Arc::new(CertStore::new());
+        let identity = SpiffeIdentity::new(&SpiffeConfig::default(), certs.clone());
+        identity
+            .apply(X509SvidResponse {
+                svids: vec![X509Svid {
+                    spiffe_id: "spiffe://example.org/proxy".to_string(),
+                    x509_svid: cert.der().to_vec(),
+                    x509_svid_key: key.serialize_der(),
+                    bundle: cert.der().to_vec(),
+                    hint: String::new(),
+                }],
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(certs.has_certificate());
+        assert!(identity.trust.read().is_some());
+    }
+
+    /// CA of `domain`
+    fn ca(domain: &str) -> (rcgen::Certificate, rcgen::KeyPair) {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        params.distinguished_name.push(rcgen::DnType::CommonName, domain);
+        (params.self_signed(&key).unwrap(), key)
+    }
+
+    /// Certificate for `id` issued by `ca`
+    fn issue(ca: &(rcgen::Certificate, rcgen::KeyPair), id: &str) -> (Vec<u8>, rcgen::KeyPair) {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.subject_alt_names = vec![rcgen::SanType::URI(id.try_into().unwrap())];
+        params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ClientAuth];
+        let cert = params.signed_by(&key, &ca.0, &ca.1).unwrap();
+        (cert.der().to_vec(), key)
+    }
+
+    #[test]
+    fn test_peers_verify_against_their_own_trust_domain() {
+        let own = ca("example.org");
+        let partner = ca("partner.org");
+        let (svid, svid_key) = issue(&own, "spiffe://example.org/proxy");
+        let (partner_peer, _) = issue(&partner, "spiffe://partner.org/api");
+        // The partner's CA vouching for an ID in our trust domain
+        let (forged, _) = issue(&partner, "spiffe://example.org/admin");
+
+        let config = SpiffeConfig {
+            allowed_trust_domains: vec!["example.org".to_string(), "partner.org".to_string()],
+            ..Default::default()
+        };
+        let identity = SpiffeIdentity::new(&config, Arc::new(CertStore::new()));
+        identity
+            .apply(X509SvidResponse {
+                svids: vec![X509Svid {
+                    spiffe_id: "spiffe://example.org/proxy".to_string(),
+                    x509_svid: svid,
+                    x509_svid_key: svid_key.serialize_der(),
+                    bundle: own.0.der().to_vec(),
+                    hint: String::new(),
+                }],
+                federated_bundles: HashMap::from([(
+                    "spiffe://partner.org".to_string(),
+                    partner.0.der().to_vec(),
+                )]),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let now = UnixTime::now();
+        let verify = |der: Vec<u8>| identity.verify_client_cert(&CertificateDer::from(der), &[], now);
+        assert!(verify(partner_peer).is_ok());
+        assert!(verify(forged).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+//! TLS termination
+//!
+//! Serves the router over rustls. Certificates come from static
+//! `cert_path`/`key_path` files, the ACME manager or the SPIFFE Workload
+//! API; the latter two swap them into the shared [`CertStore`] on renewal
+//! without a restart. ACME TLS-ALPN-01 validation handshakes are answered
+//! with challenge certificates from a [`ChallengeSource`], looked up once
+//! the ClientHello has been read.
+
+use crate::http_server::middleware::AccessControl;
+use crate::http_server::spiffe::{spiffe_id_of, PeerSpiffeId};
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder as ConnectionBuilder;
+use parking_lot::RwLock;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::danger::ClientCertVerifier;
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_rustls::server::StartHandshake;
+use tokio_rustls::{LazyConfigAcceptor, TlsAcceptor};
+use tower::Service;
+use tracing::{debug, warn};
+
+/// ALPN protocol used by the ACME TLS-ALPN-01 challenge
+pub const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+
+/// Certificates currently served by the TLS listener
+#[derive(Default)]
+pub struct CertStore {
+    current: RwLock<Option<Arc<CertifiedKey>>>,
+}
+
+impl std::fmt::Debug for CertStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertStore")
+            .field("has_certificate", &self.current.read().is_some())
+            .finish()
+    }
+}
+
+impl CertStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the serving certificate
+    pub fn set_certificate(&self, key: Arc<CertifiedKey>) {
+        *self.current.write() = Some(key);
+    }
+
+    /// Whether a serving certificate is loaded
+    pub fn has_certificate(&self) -> bool {
+        self.current.read().is_some()
+    }
+}
+
+impl ResolvesServerCert for CertStore {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        // Validation handshakes only ever get a challenge certificate
+        if is_acme_hello(&client_hello) {
+            return None;
+        }
+
+        self.current.read().clone()
+    }
+}
+
+/// Pending TLS-ALPN-01 challenge certificates, wherever they were published
+#[async_trait]
+pub trait ChallengeSource: Send + Sync {
+    /// Certificate answering the challenge for `domain`, if one is pending
+    async fn challenge_certificate(&self, domain: &str) -> Option<Arc<CertifiedKey>>;
+}
+
+/// Resolver serving a single challenge certificate
+#[derive(Debug)]
+struct ChallengeCertificate(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for ChallengeCertificate {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+fn is_acme_hello(client_hello: &ClientHello<'_>) -> bool {
+    client_hello
+        .alpn()
+        .is_some_and(|mut protocols| protocols.any(|p| p == ACME_TLS_ALPN))
+}
+
+/// Build a rustls certified key from PEM-encoded certificate chain and key
+pub fn certified_key_from_pem(cert_pem: &[u8], key_pem: &[u8]) -> McpResult<Arc<CertifiedKey>> {
+    let certs = rustls_pemfile::certs(&mut &cert_pem[..])
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| McpError::ConfigError(format!("Invalid certificate PEM: {}", e)))?;
+    if certs.is_empty() {
+        return Err(McpError::ConfigError("No certificates found in PEM".to_string()));
+    }
+
+    let key = rustls_pemfile::private_key(&mut &key_pem[..])
+        .map_err(|e| McpError::ConfigError(format!("Invalid private key PEM: {}", e)))?
+        .ok_or_else(|| McpError::ConfigError("No private key found in PEM".to_string()))?;
+
+    certified_key_from_der(certs, key)
+}
+
+/// Build a rustls certified key from DER-encoded parts
+pub fn certified_key_from_der(
+    certs: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> McpResult<Arc<CertifiedKey>> {
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(|e| McpError::ConfigError(format!("Unsupported private key: {}", e)))?;
+    Ok(Arc::new(CertifiedKey::new(certs, signing_key)))
+}
+
+/// Load a static certificate and key from disk
+pub async fn load_certified_key(cert_path: &str, key_path: &str) -> McpResult<Arc<CertifiedKey>> {
+    let cert_pem = tokio::fs::read(shellexpand::tilde(cert_path).as_ref()).await?;
+    let key_pem = tokio::fs::read(shellexpand::tilde(key_path).as_ref()).await?;
+    certified_key_from_pem(&cert_pem, &key_pem)
+}
+
+/// Build a TLS acceptor that resolves certificates from the store
+pub fn build_acceptor(store: Arc<CertStore>) -> McpResult<TlsAcceptor> {
+    let mut config = server_config_builder()?
+        .with_no_client_auth()
+        .with_cert_resolver(store);
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Build a TLS acceptor that also authenticates clients with the verifier
+pub fn build_mtls_acceptor(
+    store: Arc<CertStore>,
+    verifier: Arc<dyn ClientCertVerifier>,
+) -> McpResult<TlsAcceptor> {
+    let mut config = server_config_builder()?
+        .with_client_cert_verifier(verifier)
+        .with_cert_resolver(store);
+
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn server_config_builder(
+) -> McpResult<rustls::ConfigBuilder<rustls::ServerConfig, rustls::WantsVerifier>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| McpError::ConfigError(format!("TLS configuration error: {}", e)))
+}
+
+/// Finish a TLS-ALPN-01 validation handshake with the pending challenge
+/// certificate for the requested domain
+async fn answer_challenge(
+    start: StartHandshake<tokio::net::TcpStream>,
+    challenges: &dyn ChallengeSource,
+) -> McpResult<()> {
+    let domain = start
+        .client_hello()
+        .server_name()
+        .ok_or_else(|| McpError::InvalidRequest("TLS-ALPN-01 handshake without SNI".to_string()))?
+        .to_string();
+    let key = challenges
+        .challenge_certificate(&domain)
+        .await
+        .ok_or_else(|| McpError::InvalidRequest(format!("No pending challenge for {}", domain)))?;
+
+    let mut config = server_config_builder()?
+        .with_no_client_auth()
+        .with_cert_resolver(Arc::new(ChallengeCertificate(key)));
+    config.alpn_protocols = vec![ACME_TLS_ALPN.to_vec()];
+
+    // Validation ends once the handshake completes
+    start.into_stream(Arc::new(config)).await?;
+    Ok(())
+}
+
+/// Accept TLS connections and serve the router on each
+///
+/// A PROXY header from a trusted proxy is read before the handshake. With
+/// `challenges`, ACME TLS-ALPN-01 validation handshakes are answered from
+/// it instead of being served.
+pub async fn serve_tls(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    challenges: Option<Arc<dyn ChallengeSource>>,
+    app: Router,
+    access: Arc<AccessControl>,
+) -> McpResult<()> {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let challenges = challenges.clone();
+        let app = app.clone();
+        let access = access.clone();
+
+        tokio::spawn(async move {
+            let peer = match access.resolve_peer(&mut stream, peer).await {
+                Ok(peer) => peer,
+                Err(e) => {
+                    debug!("Dropping connection from {}: {}", peer, e);
+                    return;
+                }
+            };
+
+            let accepted = match &challenges {
+                Some(challenges) => {
+                    let start = match LazyConfigAcceptor::new(Default::default(), stream).await {
+                        Ok(start) => start,
+                        Err(e) => {
+                            debug!("TLS handshake with {} failed: {}", peer, e);
+                            return;
+                        }
+                    };
+                    if is_acme_hello(&start.client_hello()) {
+                        match answer_challenge(start, challenges.as_ref()).await {
+                            Ok(()) => debug!("Answered ACME TLS-ALPN-01 challenge from {}", peer),
+                            Err(e) => debug!("ACME TLS-ALPN-01 challenge from {} failed: {}", peer, e),
+                        }
+                        return;
+                    }
+                    start.into_stream(acceptor.config().clone()).await
+                }
+                None => acceptor.accept(stream).await,
+            };
+            let tls_stream = match accepted {
+                Ok(s) => s,
+                Err(e) => {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+
+            let peer_id = tls_stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(|cert| spiffe_id_of(cert.as_ref()))
+                .map(PeerSpiffeId);
+
+            let service = hyper::service::service_fn(move |mut request: hyper::Request<hyper::body::Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                if let Some(peer_id) = &peer_id {
+                    request.extensions_mut().insert(peer_id.clone());
+                }
+                app.clone().call(request)
+            });
+
+            if let Err(e) = ConnectionBuilder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(tls_stream), service)
+                .await
+            {
+                debug!("Connection from {} closed with error: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Parse a socket address for the TLS listener
+pub fn listen_addr(host: &str, port: u16) -> McpResult<SocketAddr> {
+    let ip = host
+        .parse::<std::net::IpAddr>()
+        .map_err(|e| McpError::ConfigError(format!("Invalid host '{}': {}", host, e)))?;
+    Ok(SocketAddr::from((ip, port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn self_signed(domain: &str) -> Arc<CertifiedKey> {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec![domain.to_string()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+        certified_key_from_pem(cert.pem().as_bytes(), key.serialize_pem().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_certified_key_from_pem() {
+        let key = self_signed("example.com");
+        assert_eq!(key.cert.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_pem_rejected() {
+        assert!(certified_key_from_pem(b"not a cert", b"not a key").is_err());
+    }
+
+    #[test]
+    fn test_cert_store() {
+        let store = CertStore::new();
+        assert!(!store.has_certificate());
+
+        store.set_certificate(self_signed("example.com"));
+        assert!(store.has_certificate());
+    }
+
+    #[test]
+    fn test_build_acceptor() {
+        let store = Arc::new(CertStore::new());
+        assert!(build_acceptor(store).is_ok());
+    }
+}
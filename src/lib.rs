@@ -8,10 +8,14 @@ pub mod cloud;
 pub mod compat;
 pub mod config;
 pub mod core;
+pub mod events;
 pub mod http_server;
+pub mod plugins;
+pub mod providers;
 pub mod registry;
 pub mod runtime;
 pub mod sandbox;
+pub mod skills;
 pub mod transport;
 pub mod utils;
 
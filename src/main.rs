@@ -1,11 +1,15 @@
 use clap::Parser;
 use supermcp::cli::args::{
-    Cli, ImportArgs, ImportSource, McpCommand, PresetCommand,
-    RegistryCommand, RuntimeCommand,
+    AuditCommand, BundleCommand, CapabilitiesCommand, Cli, GenerateCommand, ImportArgs, ImportSource,
+    ConfigCommand, McpCommand, PresetCommand, RegistryCommand, RuntimeCommand, SandboxCommand, SessionsCommand,
+    SkillCommand, TagCommand,
 };
+use supermcp::cli::exit::{self, ExitStatus};
+use supermcp::cli::output::OutputFormat;
 use supermcp::config::ConfigManager;
 use supermcp::core::ServerManager;
 use supermcp::http_server::HttpServer;
+use supermcp::utils::errors::McpResult;
 use std::sync::Arc;
 use tracing::{error, info};
 
@@ -23,12 +27,48 @@ async fn main() -> anyhow::Result<()> {
             info!("Starting Super MCP server on {}:{}", args.host, args.port);
             info!("Config file: {}", args.config);
 
+            // The SCM expects a started service to connect promptly
+            #[cfg(windows)]
+            let service_events = args
+                .windows_service
+                .then(supermcp::cli::win_service::start_dispatcher);
+
+            // Config in object storage is loaded from a local copy that
+            // is refreshed when the object changes
+            let remote_config = if supermcp::cloud::is_object_url(&args.config) {
+                let remote = Arc::new(supermcp::cloud::RemoteConfig::open(&args.config)?);
+                remote.fetch().await?;
+                Some(remote)
+            } else {
+                None
+            };
+
+            // Config in etcd or Consul is loaded from a local copy, then
+            // every change of the key is applied as it is watched
+            let kv_config = if supermcp::cloud::is_kv_url(&args.config) {
+                let source = Arc::new(supermcp::cloud::KvConfigSource::open(&args.config)?);
+                let (path, current) = source.fetch_to_cache().await?;
+                Some((source, path, current.revision))
+            } else {
+                None
+            };
+
             // Expand tilde in config path
-            let config_path = shellexpand::tilde(&args.config).to_string();
+            let config_path = match (&remote_config, &kv_config) {
+                (Some(remote), _) => remote.local_path().display().to_string(),
+                (_, Some((_, path, _))) => path.display().to_string(),
+                _ => shellexpand::tilde(&args.config).to_string(),
+            };
 
             // Load configuration
-            let config_manager = ConfigManager::new(&config_path).await?;
+            let config_manager = Arc::new(ConfigManager::new(&config_path).await?);
             let mut config = config_manager.get_config();
+            if let Some(remote) = &remote_config {
+                remote.clone().spawn_refresh(&config.config_source);
+            }
+            if let Some((source, _, revision)) = kv_config {
+                source.spawn_watch(config_manager.clone(), revision);
+            }
 
             // Override with CLI args
             config.server.host = args.host;
@@ -40,20 +80,151 @@ async fn main() -> anyhow::Result<()> {
                 config.lazy_loading.mode = lazy_mode.into();
             }
 
-            // Create server manager
-            let server_manager = Arc::new(ServerManager::new());
+            // Per-tenant keys sealing audit records and artifacts at rest
+            if let Some(keys) = supermcp::cloud::tenant_keys::TenantKeys::from_config(&config)? {
+                supermcp::cloud::tenant_keys::install(Arc::new(keys));
+            }
+
+            // Audit log for security and lifecycle events
+            if config.features.audit_logging {
+                let audit_config = supermcp::audit::AuditConfig {
+                    path: shellexpand::tilde(&config.audit.path).to_string().into(),
+                    format: match config.audit.format {
+                        supermcp::config::LogFormat::Json => supermcp::audit::logger::LogFormat::Json,
+                        supermcp::config::LogFormat::Pretty => supermcp::audit::logger::LogFormat::Pretty,
+                    },
+                    max_size_mb: config.audit.max_size_mb,
+                    max_files: config.audit.max_files,
+                    log_to_stdout: false,
+                };
+                match supermcp::audit::AuditLogger::new(audit_config).await {
+                    Ok(mut logger) => {
+                        if config.tenant_keys.encrypt_audit {
+                            if let Some(keys) = supermcp::cloud::tenant_keys::global() {
+                                logger = logger.with_tenant_keys(keys.clone());
+                            }
+                        }
+                        supermcp::audit::install_global(Arc::new(logger));
+                    }
+                    Err(e) => tracing::warn!("Audit log disabled: {}", e),
+                }
+            }
+
+            // Key pair for encrypted federation
+            if let Some(private_key) = &config.federation.private_key {
+                let key = supermcp::transport::envelope::FederationKey::from_base64(private_key)?;
+                info!("Federation public key: {}", key.public_base64());
+                supermcp::transport::envelope::install_identity(Arc::new(key));
+            }
+
+            // Corporate proxy for registry, OAuth and remote servers
+            supermcp::transport::http_client::install_proxy(config.proxy.clone());
+
+            // Singleton background jobs run on the elected leader only
+            if config.cluster.enabled {
+                let cluster = Arc::new(supermcp::cloud::ClusterManager::new(
+                    supermcp::cloud::ClusterConfig::from_config(&config.cluster)?,
+                ));
+                cluster.init().await?;
+                info!("Cluster node {} started", cluster.node_id());
+                supermcp::cloud::leader::install(cluster);
+            }
+
+            // Tokens for stdio servers calling back into the proxy, before
+            // any server is spawned
+            if let Some(delegator) = supermcp::auth::delegation::Delegator::from_config(&config)? {
+                supermcp::auth::delegation::install(Arc::new(delegator));
+            }
+
+            // Outbound webhooks for proxy events
+            if let Some(notifier) = supermcp::events::Notifier::from_config(&config.notifications) {
+                supermcp::events::install_global(notifier);
+            }
+
+            // Per-tenant usage reports for billing
+            if config.usage.enabled {
+                let meter = Arc::new(supermcp::events::usage::UsageMeter::new(config.usage.clone()));
+                meter.spawn();
+                supermcp::events::usage::install_global(meter);
+            }
 
-            // Add configured servers
-            for server_config in config.servers.clone() {
+            // Load shedding, installed before pools are created
+            if config.backpressure.enabled {
+                supermcp::core::backpressure::install_global(Arc::new(
+                    supermcp::core::backpressure::Backpressure::new(config.backpressure.clone()),
+                ));
+            }
+
+            // Trace sandboxed servers' files and connections, before any
+            // server is spawned
+            if config.ebpf.enabled {
+                supermcp::sandbox::observer::start(&config.ebpf).await?;
+            }
+
+            // Approved tool catalogs for drift detection
+            let drift = supermcp::core::drift::DriftMonitor::from_config(&config.drift).await?;
+
+            // Tool schemas of servers with pinned_schemas
+            let schema_pins = supermcp::core::schema_lock::SchemaPins::from_config(&config).await?;
+
+            // Create server manager
+            let server_manager = Arc::new(
+                ServerManager::new()
+                    .with_chaos(&config.chaos)
+                    .with_slos(&config.slos)
+                    .with_scheduling(&config.scheduling)
+                    .with_drift(drift.clone())
+                    .with_schema_pins(schema_pins),
+            );
+
+            // Add configured servers; read replicas forward to nodes running them
+            let servers = if config.cluster.enabled && config.cluster.read_replica {
+                info!("Running as a read replica without upstream servers");
+                Vec::new()
+            } else {
+                config.servers.clone()
+            };
+            for server_config in servers {
+                if !server_config.enabled {
+                    info!("Skipping disabled server: {}", server_config.name);
+                    continue;
+                }
                 info!("Configuring server: {}", server_config.name);
                 if let Err(e) = server_manager.add_server(server_config).await {
                     error!("Failed to add server: {}", e);
                 }
             }
 
+            if let Some(drift) = drift {
+                drift.spawn(server_manager.clone());
+            }
+
+            // Pick up servers added to editor configs while running
+            supermcp::cli::import_watch::ImportWatcher::spawn(
+                config.import.watch.clone(),
+                server_manager.clone(),
+            );
+
+            #[cfg(windows)]
+            if let Some(events) = service_events {
+                supermcp::cli::win_service::spawn_control_loop(
+                    events,
+                    server_manager.clone(),
+                    config.servers.iter().filter(|s| s.enabled).cloned().collect(),
+                );
+            }
+
             // Create and run HTTP server
-            let http_server = HttpServer::new(config, server_manager);
-            http_server.run().await?;
+            let mut http_server = HttpServer::new(config, server_manager).with_config_manager(config_manager);
+            if let Some(remote) = remote_config {
+                http_server = http_server.with_remote_config(remote);
+            }
+            let result = http_server.run().await;
+            #[cfg(windows)]
+            if args.windows_service {
+                supermcp::cli::win_service::report_exit(&result);
+            }
+            result?;
         }
         Cli::Mcp(args) => {
             match args.command {
@@ -70,8 +241,8 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
-                McpCommand::List => {
-                    if let Err(e) = supermcp::cli::mcp::list(&args.config).await {
+                McpCommand::List { output } => {
+                    if let Err(e) = supermcp::cli::mcp::list(&args.config, output).await {
                         eprintln!("Error: {}", e);
                         std::process::exit(1);
                     }
@@ -101,6 +272,40 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
+                McpCommand::Instantiate { template, params, name } => {
+                    if let Err(e) = supermcp::cli::mcp::instantiate(&args.config, &template, params, name.as_deref()).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                McpCommand::Enable { pattern } => {
+                    if let Err(e) = supermcp::cli::mcp::set_enabled(&args.config, &pattern, true).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                McpCommand::Disable { pattern } => {
+                    if let Err(e) = supermcp::cli::mcp::set_enabled(&args.config, &pattern, false).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                McpCommand::Restart { pattern, url, token } => {
+                    if let Err(e) = supermcp::cli::mcp::restart(&args.config, &pattern, url.as_deref(), token.as_deref()).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                McpCommand::Tag { command } => {
+                    let result = match command {
+                        TagCommand::Add { pattern, tags } => supermcp::cli::mcp::tag(&args.config, &pattern, &tags, true).await,
+                        TagCommand::Remove { pattern, tags } => supermcp::cli::mcp::tag(&args.config, &pattern, &tags, false).await,
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
         }
         Cli::Preset(args) => {
@@ -111,8 +316,8 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
-                PresetCommand::List => {
-                    if let Err(e) = supermcp::cli::preset::list(&args.config).await {
+                PresetCommand::List { output } => {
+                    if let Err(e) = supermcp::cli::preset::list(&args.config, output).await {
                         eprintln!("Error: {}", e);
                         std::process::exit(1);
                     }
@@ -123,11 +328,8 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
-                PresetCommand::Test { name } => {
-                    if let Err(e) = supermcp::cli::preset::test(&args.config, &name).await {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    }
+                PresetCommand::Test { name, quiet } => {
+                    exit::finish(supermcp::cli::preset::test(&args.config, &name, quiet).await, quiet);
                 }
                 PresetCommand::Remove { name } => {
                     if let Err(e) = supermcp::cli::preset::remove(&args.config, &name).await {
@@ -139,8 +341,8 @@ async fn main() -> anyhow::Result<()> {
         }
         Cli::Registry(args) => {
             match args.command {
-                RegistryCommand::Search { query } => {
-                    if let Err(e) = supermcp::cli::registry::search(&args.config, &query).await {
+                RegistryCommand::Search { query, output } => {
+                    if let Err(e) = supermcp::cli::registry::search(&args.config, &query, output).await {
                         eprintln!("Error: {}", e);
                         std::process::exit(1);
                     }
@@ -170,24 +372,45 @@ async fn main() -> anyhow::Result<()> {
                 args.binary.as_deref(),
                 args.config.as_deref(),
                 args.manager.as_deref(),
+                args.user,
                 args.uninstall,
             ).await {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-        Cli::Validate(args) => {
-            if let Err(e) = validate_config(&args.config, &args.format).await {
-                eprintln!("Validation failed: {}", e);
+        Cli::Service(args) => {
+            if let Err(e) = supermcp::cli::install::service(
+                args.command,
+                args.manager.as_deref(),
+                args.user,
+            ) {
+                eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
+        Cli::Validate(args) => {
+            exit::finish(validate_config(&args.config, &args.format, args.quiet).await, args.quiet);
+        }
         Cli::Migrate(args) => {
             if let Err(e) = migrate_config(&args.input, args.output.as_deref(), &args.format, args.dry_run).await {
                 eprintln!("Migration failed: {}", e);
                 std::process::exit(1);
             }
         }
+        Cli::Init(args) => {
+            if let Err(e) = supermcp::cli::init::run(&args.config, args.force, args.yes).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::MigrateConfig(args) => {
+            let target = args.to.unwrap_or(supermcp::config::CURRENT_CONFIG_VERSION);
+            if let Err(e) = migrate_config_version(&args.config, target, args.dry_run).await {
+                eprintln!("Migration failed: {}", e);
+                std::process::exit(1);
+            }
+        }
         Cli::Guide => {
             supermcp::compat::MigrationHelper::print_migration_guide();
             println!();
@@ -222,8 +445,9 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
-                RuntimeCommand::List { json } => {
-                    if let Err(e) = supermcp::cli::runtime::list(&args.config, json).await {
+                RuntimeCommand::List { output, json } => {
+                    let output = if json { OutputFormat::Json } else { output };
+                    if let Err(e) = supermcp::cli::runtime::list(&args.config, output).await {
                         eprintln!("Error: {}", e);
                         std::process::exit(1);
                     }
@@ -240,11 +464,8 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
-                RuntimeCommand::Validate => {
-                    if let Err(e) = supermcp::cli::runtime::validate(&args.config).await {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    }
+                RuntimeCommand::Validate { quiet } => {
+                    exit::finish(supermcp::cli::runtime::validate(&args.config, quiet).await, quiet);
                 }
                 RuntimeCommand::Exec { runtime, script, file } => {
                     if script.is_none() && file.is_none() {
@@ -256,6 +477,33 @@ async fn main() -> anyhow::Result<()> {
                         std::process::exit(1);
                     }
                 }
+                RuntimeCommand::Freeze { name, lock_dir } => {
+                    if let Err(e) = supermcp::cli::runtime::freeze(&args.config, &name, lock_dir).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                RuntimeCommand::Verify { name } => {
+                    if let Err(e) = supermcp::cli::runtime::verify(&args.config, &name).await {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        Cli::Skill(args) => {
+            let result = match args.command {
+                SkillCommand::Install { source, reference, force } => {
+                    supermcp::cli::skill::install(&args.config, &source, reference, force).await
+                }
+                SkillCommand::List { json } => supermcp::cli::skill::list(&args.config, json).await,
+                SkillCommand::Remove { name } => supermcp::cli::skill::remove(&args.config, &name).await,
+                SkillCommand::Info { name } => supermcp::cli::skill::info(&args.config, &name).await,
+                SkillCommand::Validate { path } => supermcp::cli::skill::validate(&path).await,
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
             }
         }
         Cli::Call(args) => {
@@ -268,6 +516,30 @@ async fn main() -> anyhow::Result<()> {
                 args.skill.as_deref(),
                 args.env,
                 args.json,
+                args.dry_run,
+            ).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Repl(args) => {
+            if let Err(e) = supermcp::cli::repl::execute(
+                args.config.as_deref(),
+                args.stdio.as_deref(),
+                args.http_url.as_deref(),
+                args.skill.as_deref(),
+                args.env,
+                args.json,
+            ).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Run(args) => {
+            if let Err(e) = supermcp::cli::pipeline::execute(
+                args.config.as_deref(),
+                &args.pipeline,
+                args.output.as_deref(),
             ).await {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -283,31 +555,200 @@ async fn main() -> anyhow::Result<()> {
                 args.schema,
                 args.json,
                 args.all,
+                args.watch.then_some(args.interval),
             ).await {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
         Cli::Providers(args) => {
+            let output = if args.json { OutputFormat::Json } else { args.output };
             if let Err(e) = supermcp::cli::call::list_providers(
                 args.config.as_deref(),
-                args.json,
+                output,
             ).await {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
-        Cli::Import(args) => {
-            if let Err(e) = handle_import(args).await {
+        Cli::Bundle(args) => {
+            let result = match args.command {
+                BundleCommand::Create { config, output, binary, env_overrides, self_extracting } => {
+                    supermcp::cli::bundle::create(&config, &output, binary.as_deref(), &env_overrides, self_extracting).await
+                }
+                BundleCommand::Unpack { archive, dest, env } => {
+                    supermcp::cli::bundle::unpack(&archive, &dest, env.as_deref()).await
+                }
+                BundleCommand::Apply { dir, env } => supermcp::cli::bundle::apply(&dir, env.as_deref()),
+            };
+            if let Err(e) = result {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
         }
+        Cli::Generate(args) => {
+            let output = args.output.as_deref();
+            let result = match args.command {
+                GenerateCommand::Dockerfile => {
+                    supermcp::cli::generate::generate_dockerfile(&args.config, output).await
+                }
+                GenerateCommand::Compose => {
+                    supermcp::cli::generate::generate_compose(&args.config, output).await
+                }
+                GenerateCommand::K8s {
+                    namespace,
+                    image,
+                    replicas,
+                    hpa_max_replicas,
+                    hpa_cpu_percent,
+                    helm,
+                } => {
+                    let options = supermcp::cli::generate::K8sOptions {
+                        namespace,
+                        image,
+                        replicas,
+                        hpa_max_replicas,
+                        hpa_cpu_percent,
+                    };
+                    supermcp::cli::generate::generate_k8s(&args.config, output, &options, helm).await
+                }
+                GenerateCommand::FederationKey => {
+                    supermcp::cli::generate::generate_federation_key(output).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Capabilities(args) => {
+            let result = match args.command {
+                CapabilitiesCommand::Export { output, key } => {
+                    supermcp::cli::capabilities::export(&args.config, output.as_deref(), key.as_deref()).await
+                }
+                CapabilitiesCommand::Compare { baseline, other, trusted_key, json, check } => {
+                    supermcp::cli::capabilities::compare(
+                        &args.config,
+                        &baseline,
+                        other.as_deref(),
+                        trusted_key.as_deref(),
+                        json,
+                        check,
+                    )
+                    .await
+                }
+                CapabilitiesCommand::Lock { output } => {
+                    supermcp::cli::capabilities::lock(&args.config, output.as_deref()).await
+                }
+                CapabilitiesCommand::Keygen { output } => supermcp::cli::capabilities::keygen(&output).await,
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Audit(args) => {
+            let result = match args.command {
+                AuditCommand::Decrypt { path, tenant } => {
+                    supermcp::cli::audit::decrypt(&args.config, path.as_deref(), tenant.as_deref()).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Sessions(args) => {
+            let url = args.url.as_deref();
+            let token = args.token.as_deref();
+            let result = match args.command {
+                SessionsCommand::List { json } => {
+                    supermcp::cli::sessions::list(&args.config, url, token, json).await
+                }
+                SessionsCommand::Kill { id } => {
+                    supermcp::cli::sessions::kill(&args.config, url, token, &id).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Sandbox(args) => {
+            let url = args.url.as_deref();
+            let token = args.token.as_deref();
+            let result = match args.command {
+                SandboxCommand::Learn { server, duration, json } => {
+                    supermcp::cli::sandbox::learn(&args.config, url, token, &server, &duration, json).await
+                }
+                SandboxCommand::Elevate { server, network, filesystem, gpu, duration, reason } => {
+                    let request = supermcp::sandbox::elevation::ElevationRequest {
+                        network,
+                        filesystem,
+                        gpu,
+                        duration_secs: 0,
+                        reason,
+                    };
+                    supermcp::cli::sandbox::elevate(&args.config, url, token, &server, &duration, request).await
+                }
+                SandboxCommand::Revoke { server } => {
+                    supermcp::cli::sandbox::revoke(&args.config, url, token, &server).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Config(args) => {
+            let url = args.url.as_deref();
+            let token = args.token.as_deref();
+            let result = match args.command {
+                ConfigCommand::Diff { file, json } => {
+                    supermcp::cli::config::diff(&args.config, url, token, &file, json).await
+                }
+                ConfigCommand::Apply { file, dry_run, json } => {
+                    supermcp::cli::config::apply(&args.config, url, token, &file, dry_run, json).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Cli::Import(args) => {
+            let quiet = args.quiet;
+            exit::finish(handle_import(args).await, quiet);
+        }
     }
 
     Ok(())
 }
 
+/// Upgrade a Super MCP config file in place to schema version `target`
+async fn migrate_config_version(config: &str, target: u32, dry_run: bool) -> anyhow::Result<()> {
+    let path = std::path::PathBuf::from(shellexpand::tilde(config).to_string());
+    let report = supermcp::config::migration::migrate_file(&path, target, dry_run).await?;
+
+    if report.from == report.to {
+        println!("{} is already at config version {}", path.display(), report.to);
+        return Ok(());
+    }
+    if dry_run {
+        println!("=== Dry Run - version {} -> {} ===\n", report.from, report.to);
+        println!("{}", report.content);
+    } else {
+        println!("Migrated {} from config version {} to {}", path.display(), report.from, report.to);
+        if let Some(backup) = &report.backup {
+            println!("  Backup: {}", backup.display());
+        }
+    }
+    for change in &report.applied {
+        println!("  - {}", change);
+    }
+    Ok(())
+}
+
 async fn migrate_config(
     input: &str,
     output: Option<&str>,
@@ -380,53 +821,60 @@ async fn migrate_config(
     Ok(())
 }
 
-async fn validate_config(config_path: &str, format: &str) -> anyhow::Result<()> {
+async fn validate_config(config_path: &str, format: &str, quiet: bool) -> McpResult<ExitStatus> {
     use supermcp::config::validation::ConfigValidator;
     use serde_json::json;
 
     let path = shellexpand::tilde(config_path).to_string();
 
-    let validator = ConfigValidator::new();
-    let result = validator.validate_file(&path).await;
+    // A file that cannot be read or parsed is a config failure; one that
+    // parses but breaks the rules is a validation failure
+    let result = match tokio::fs::read_to_string(&path).await {
+        Err(e) => Err((ExitStatus::Config, vec![format!("Failed to read file: {}", e)])),
+        Ok(content) => match content.parse::<toml::Table>() {
+            Err(e) => Err((ExitStatus::Config, vec![format!("Failed to parse TOML: {}", e)])),
+            Ok(_) => ConfigValidator::new().validate_toml(&content).map_err(|errors| {
+                (ExitStatus::Invalid, errors.iter().map(|e| e.to_string()).collect())
+            }),
+        },
+    };
+    let (status, errors) = match result {
+        Ok(()) => (ExitStatus::Ok, Vec::new()),
+        Err(failure) => failure,
+    };
+
+    if quiet {
+        exit::print_result(status, json!({ "path": path, "errors": errors }));
+        return Ok(status);
+    }
 
     match format {
         "json" => {
-            let output = match &result {
-                Ok(_) => json!({
-                    "valid": true,
-                    "path": path,
-                    "errors": []
-                }),
-                Err(errors) => json!({
-                    "valid": false,
-                    "path": path,
-                    "errors": errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
-                }),
-            };
+            let output = json!({
+                "valid": status == ExitStatus::Ok,
+                "path": path,
+                "errors": errors,
+            });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
         _ => {
-            match result {
-                Ok(_) => {
-                    println!("Configuration is valid: {}", path);
-                }
-                Err(errors) => {
-                    println!("Configuration is invalid: {}", path);
-                    println!("\nErrors:");
-                    for error in errors {
-                        println!("  - {}", error);
-                    }
-                    std::process::exit(1);
+            if status == ExitStatus::Ok {
+                println!("Configuration is valid: {}", path);
+            } else {
+                println!("Configuration is invalid: {}", path);
+                println!("\nErrors:");
+                for error in errors {
+                    println!("  - {}", error);
                 }
             }
         }
     }
 
-    Ok(())
+    Ok(status)
 }
 
 
-async fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
+async fn handle_import(args: ImportArgs) -> McpResult<ExitStatus> {
     use supermcp::cli::discover;
     use serde_json::json;
 
@@ -443,21 +891,31 @@ async fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
         ImportSource::Gemini => discover::discover_gemini().await?,
         ImportSource::Qwen => discover::discover_qwen().await?,
         ImportSource::GithubCopilot => discover::discover_github_copilot().await?,
+        ImportSource::Jetbrains => discover::discover_jetbrains().await?,
+        ImportSource::Zed => discover::discover_zed().await?,
     };
 
     if mcps.is_empty() {
-        if args.json {
+        if args.quiet {
+            exit::print_result(ExitStatus::Empty, json!({"imported": [], "count": 0, "dry_run": args.dry_run}));
+        } else if args.json {
             println!("{}", json!({"imported": [], "count": 0}));
         } else {
             println!("No MCP servers found from {:?}.", args.source);
         }
-        return Ok(());
+        return Ok(ExitStatus::Empty);
     }
 
     // Import into config
     let imported = discover::import_discovered(&args.config, mcps.clone(), args.dry_run).await?;
 
-    if args.json {
+    if args.quiet {
+        exit::print_result(ExitStatus::Ok, json!({
+            "imported": imported,
+            "count": imported.len(),
+            "dry_run": args.dry_run,
+        }));
+    } else if args.json {
         println!("{}", json!({
             "imported": imported,
             "count": imported.len(),
@@ -468,6 +926,7 @@ async fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
                     "source": m.source,
                     "command": m.command,
                     "args": m.args,
+                    "url": m.remote.as_ref().map(|r| &r.url),
                 })
             }).collect::<Vec<_>>(),
         }));
@@ -489,7 +948,8 @@ async fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
                     // It was renamed
                     imported.iter().find(|i| i.starts_with(&format!("{}-", server.name))).unwrap_or(&server.name)
                 };
-                println!("    - {} ({})", name, server.command);
+                let target = server.remote.as_ref().map_or(&server.command, |r| &r.url);
+                println!("    - {} ({})", name, target);
             }
         }
 
@@ -501,5 +961,5 @@ async fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
         }
     }
 
-    Ok(())
+    Ok(ExitStatus::Ok)
 }
@@ -0,0 +1,63 @@
+//! Provider plugins
+//!
+//! Third-party provider types ship as WASM components implementing the
+//! `supermcp:provider` interface in `wit/provider.wit`. Each `[[plugins]]`
+//! entry is loaded at startup and registered like any other provider.
+//! Plugins get no host imports: they can only compute on the JSON they are
+//! given, within their memory and fuel limits.
+//!
+//! Loading components requires the `wasm-plugins` feature.
+
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+use crate::config::PluginConfig;
+use crate::core::provider::Provider;
+use crate::utils::errors::{McpError, McpResult};
+
+/// Load a configured plugin as a provider
+pub async fn load(config: &PluginConfig) -> McpResult<Box<dyn Provider>> {
+    if config.name.is_empty() || config.path.is_empty() {
+        return Err(McpError::ConfigError(
+            "Plugins need both a name and a path".to_string(),
+        ));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    {
+        let provider = wasm::WasmPluginProvider::load(config).await?;
+        Ok(Box::new(provider))
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    Err(McpError::ConfigError(format!(
+        "Plugin '{}' needs supermcp built with the wasm-plugins feature",
+        config.name
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_requires_name_and_path() {
+        let config = PluginConfig {
+            name: "graphql".to_string(),
+            ..Default::default()
+        };
+        assert!(load(&config).await.is_err());
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_load_missing_component() {
+        let config = PluginConfig {
+            name: "graphql".to_string(),
+            path: "/nonexistent/graphql.wasm".to_string(),
+            ..Default::default()
+        };
+        let err = load(&config).await.err().unwrap();
+        assert!(err.to_string().contains("graphql"));
+    }
+}
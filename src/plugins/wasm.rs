@@ -0,0 +1,198 @@
+//! Wasmtime host for provider plugins
+//!
+//! Every call runs in a fresh instance with its own store, so a plugin
+//! can't keep state between calls or leak memory across them. The
+//! component is compiled once when the plugin loads.
+
+use crate::config::PluginConfig;
+use crate::core::provider::{parse_mcp_schema, Provider, ProviderType, Tool, ToolResult};
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{debug, info};
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+wasmtime::component::bindgen!({
+    path: "wit/provider.wit",
+    world: "provider-plugin",
+});
+
+/// Per-call store data
+struct PluginState {
+    limits: StoreLimits,
+}
+
+/// Compiled plugin shared with blocking call threads
+struct PluginHost {
+    name: String,
+    engine: Engine,
+    component: Component,
+    linker: Linker<PluginState>,
+    config_json: String,
+    max_memory_bytes: usize,
+    max_fuel: u64,
+}
+
+impl PluginHost {
+    /// Instantiate and configure a fresh instance, then run `f` on it
+    fn call<T>(
+        &self,
+        f: impl FnOnce(&ProviderPlugin, &mut Store<PluginState>) -> wasmtime::Result<Result<T, String>>,
+    ) -> Result<T, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, PluginState { limits });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.max_fuel).map_err(|e| e.to_string())?;
+
+        let plugin = ProviderPlugin::instantiate(&mut store, &self.component, &self.linker)
+            .map_err(|e| format!("failed to instantiate: {}", e))?;
+
+        plugin
+            .supermcp_provider_tools()
+            .call_configure(&mut store, &self.config_json)
+            .map_err(|e| format!("configure trapped: {}", e))?
+            .map_err(|e| format!("configure failed: {}", e))?;
+
+        f(&plugin, &mut store).map_err(|e| format!("plugin trapped: {}", e))?
+    }
+}
+
+/// Provider backed by a WASM component
+pub struct WasmPluginProvider {
+    host: Arc<PluginHost>,
+    path: PathBuf,
+    tools: Vec<Tool>,
+}
+
+impl WasmPluginProvider {
+    /// Compile the component and fetch its tool list
+    pub async fn load(config: &PluginConfig) -> McpResult<Self> {
+        let path = PathBuf::from(shellexpand::tilde(&config.path).to_string());
+
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config)
+            .map_err(|e| McpError::InternalError(format!("Failed to create WASM engine: {}", e)))?;
+
+        let component = {
+            let engine = engine.clone();
+            let component_path = path.clone();
+            tokio::task::spawn_blocking(move || Component::from_file(&engine, &component_path))
+                .await
+                .map_err(|e| McpError::InternalError(e.to_string()))?
+                .map_err(|e| {
+                    McpError::ConfigError(format!(
+                        "Failed to load plugin '{}' from {}: {}",
+                        config.name,
+                        path.display(),
+                        e
+                    ))
+                })?
+        };
+
+        let host = Arc::new(PluginHost {
+            name: config.name.clone(),
+            linker: Linker::new(&engine),
+            engine,
+            component,
+            config_json: serde_json::to_string(&config.config)?,
+            max_memory_bytes: (config.max_memory_mb as usize).saturating_mul(1024 * 1024),
+            max_fuel: config.max_fuel,
+        });
+
+        let plugin_tools = {
+            let host = host.clone();
+            tokio::task::spawn_blocking(move || {
+                host.call(|plugin, store| plugin.supermcp_provider_tools().call_list_tools(store))
+            })
+            .await
+            .map_err(|e| McpError::InternalError(e.to_string()))?
+            .map_err(|e| {
+                McpError::ConfigError(format!("Plugin '{}' failed to list tools: {}", config.name, e))
+            })?
+        };
+
+        let tools = plugin_tools
+            .into_iter()
+            .map(|tool| {
+                let schema: Value = serde_json::from_str(&tool.input_schema).unwrap_or(Value::Null);
+                Tool {
+                    name: format!("{}.{}", config.name, tool.name),
+                    description: tool.description,
+                    provider: config.name.clone(),
+                    provider_type: ProviderType::Plugin,
+                    parameters: parse_mcp_schema(&serde_json::json!({ "inputSchema": schema })),
+                    metadata: HashMap::from([("inputSchema".to_string(), schema)]),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        info!("Loaded plugin {} with {} tool(s)", config.name, tools.len());
+        Ok(Self { host, path, tools })
+    }
+}
+
+#[async_trait]
+impl Provider for WasmPluginProvider {
+    fn name(&self) -> &str {
+        &self.host.name
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Plugin
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    async fn list_tools(&self) -> McpResult<Vec<Tool>> {
+        Ok(self.tools.clone())
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> McpResult<ToolResult> {
+        let tool_name = name
+            .strip_prefix(&format!("{}.", self.host.name))
+            .unwrap_or(name)
+            .to_string();
+
+        if !self.tools.iter().any(|t| t.name == format!("{}.{}", self.host.name, tool_name)) {
+            return Ok(ToolResult::error(format!("Tool '{}' not found in plugin", tool_name)));
+        }
+
+        debug!("Calling plugin tool {}.{}", self.host.name, tool_name);
+        let host = self.host.clone();
+        let arguments = arguments.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            host.call(|plugin, store| {
+                plugin
+                    .supermcp_provider_tools()
+                    .call_call_tool(store, &tool_name, &arguments)
+            })
+        })
+        .await
+        .map_err(|e| McpError::InternalError(e.to_string()))?;
+
+        match result {
+            Ok(output) => {
+                let data = serde_json::from_str(&output).unwrap_or(Value::String(output));
+                ToolResult::success(data)
+            }
+            Err(e) => Ok(ToolResult::error(e)),
+        }
+    }
+
+    fn metadata(&self) -> HashMap<String, Value> {
+        HashMap::from([(
+            "path".to_string(),
+            Value::String(self.path.to_string_lossy().to_string()),
+        )])
+    }
+}
@@ -0,0 +1,302 @@
+//! Command provider: CLI utilities exposed as tools
+//!
+//! Each tool is an argv template. Arguments are substituted into the
+//! template without a shell, the command runs in the provider's sandbox,
+//! and stdout is returned as text, JSON or a list of lines.
+
+use crate::config::{
+    CommandOutputFormat, CommandProviderConfig, CommandToolConfig, McpServerConfig,
+};
+use crate::core::provider::{Provider, ProviderType, Tool, ToolResult};
+use crate::providers::{parameter_schemas, resolve_arguments};
+use crate::utils::errors::{McpError, McpResult};
+use crate::utils::process::output_with_timeout;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+/// Provider running configured commands
+pub struct CommandProvider {
+    config: CommandProviderConfig,
+}
+
+impl CommandProvider {
+    pub fn new(config: CommandProviderConfig) -> McpResult<Self> {
+        for tool in &config.tools {
+            if tool.command.is_empty() {
+                return Err(McpError::ConfigError(format!(
+                    "Command tool {}.{} has an empty command",
+                    config.name, tool.name
+                )));
+            }
+            for element in &tool.command {
+                for name in placeholders(element) {
+                    if !tool.arguments.iter().any(|a| a.name == name) {
+                        return Err(McpError::ConfigError(format!(
+                            "Command tool {}.{} references undeclared argument '{}'",
+                            config.name, tool.name, name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(Self { config })
+    }
+
+    fn find_tool(&self, name: &str) -> Option<&CommandToolConfig> {
+        let tool_name = name
+            .strip_prefix(&format!("{}.", self.config.name))
+            .unwrap_or(name);
+        self.config.tools.iter().find(|t| t.name == tool_name)
+    }
+
+    async fn run(&self, tool: &CommandToolConfig, argv: Vec<String>) -> McpResult<ToolResult> {
+        let mut argv = argv.into_iter();
+        let server = McpServerConfig {
+            name: format!("{}.{}", self.config.name, tool.name),
+            command: argv.next().unwrap_or_default(),
+            args: argv.collect(),
+            env: self.config.env.clone(),
+            sandbox: self.config.sandbox.clone(),
+            ..Default::default()
+        };
+        debug!("Running command tool {}: {} {}", server.name, server.command, server.args.join(" "));
+
+        let sandbox = crate::sandbox::create_sandbox(&server);
+        let child = sandbox.spawn(&server).await?;
+
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let Some(output) = output_with_timeout(child, None, timeout).await? else {
+            return Ok(ToolResult::error(format!(
+                "Command timed out after {}s",
+                self.config.timeout_seconds
+            )));
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if !output.status.success() {
+            return Ok(ToolResult::error(format!(
+                "Command exited with code {}: {}",
+                output.status.code().unwrap_or(-1),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        parse_output(tool.output, &stdout)
+    }
+}
+
+/// Placeholder names in a template element
+fn placeholders(element: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = element;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else {
+            break;
+        };
+        names.push(&rest[start + 1..start + 1 + len]);
+        rest = &rest[start + 2 + len..];
+    }
+    names
+}
+
+/// String form of an argument value
+fn value_to_arg(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Substitute tool arguments into the argv template
+///
+/// A whole-element placeholder bound to an array expands to one element
+/// per item. Values that would land in their own element and start with
+/// `-` are refused unless the template puts `--` before them, so callers
+/// can't smuggle in options.
+pub fn render_argv(tool: &CommandToolConfig, arguments: &Value) -> Result<Vec<String>, String> {
+    let values: HashMap<&str, Value> = resolve_arguments(&tool.arguments, arguments)?
+        .into_iter()
+        .filter_map(|(arg, value)| Some((arg.name.as_str(), value?)))
+        .collect();
+
+    let mut argv = Vec::new();
+    let mut after_separator = false;
+    for (i, element) in tool.command.iter().enumerate() {
+        let names = placeholders(element);
+        if names.iter().any(|name| !values.contains_key(name)) {
+            continue;
+        }
+
+        let whole = element.strip_prefix('{').and_then(|e| e.strip_suffix('}'));
+        let rendered = match whole.and_then(|name| values.get(name)) {
+            Some(Value::Array(items)) => items.iter().map(value_to_arg).collect(),
+            Some(value) => vec![value_to_arg(value)],
+            None => {
+                let mut rendered = element.clone();
+                for name in names {
+                    rendered = rendered.replace(&format!("{{{}}}", name), &value_to_arg(&values[name]));
+                }
+                vec![rendered]
+            }
+        };
+
+        if whole.is_some() && !after_separator && i > 0 {
+            if let Some(flag) = rendered.iter().find(|a| a.starts_with('-')) {
+                return Err(format!("Argument value '{}' looks like an option", flag));
+            }
+        }
+        if element == "--" {
+            after_separator = true;
+        }
+        argv.extend(rendered);
+    }
+
+    Ok(argv)
+}
+
+/// Turn stdout into a tool result according to the output format
+fn parse_output(format: CommandOutputFormat, stdout: &str) -> McpResult<ToolResult> {
+    let data = match format {
+        CommandOutputFormat::Text => Value::String(stdout.to_string()),
+        CommandOutputFormat::Json => match serde_json::from_str(stdout) {
+            Ok(value) => value,
+            Err(e) => return Ok(ToolResult::error(format!("Command output is not JSON: {}", e))),
+        },
+        CommandOutputFormat::Lines => Value::Array(
+            stdout
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| Value::String(l.to_string()))
+                .collect(),
+        ),
+    };
+
+    Ok(ToolResult::success(data)?.with_content(vec![json!({ "type": "text", "text": stdout })]))
+}
+
+#[async_trait]
+impl Provider for CommandProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Command
+    }
+
+    async fn is_available(&self) -> bool {
+        self.config
+            .tools
+            .iter()
+            .all(|tool| which::which(&tool.command[0]).is_ok())
+    }
+
+    async fn list_tools(&self) -> McpResult<Vec<Tool>> {
+        Ok(self
+            .config
+            .tools
+            .iter()
+            .map(|tool| Tool {
+                name: format!("{}.{}", self.config.name, tool.name),
+                description: tool.description.clone(),
+                provider: self.config.name.clone(),
+                provider_type: ProviderType::Command,
+                parameters: parameter_schemas(&tool.arguments),
+                metadata: HashMap::new(),
+            })
+            .collect())
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> McpResult<ToolResult> {
+        let Some(tool) = self.find_tool(name) else {
+            return Ok(ToolResult::error(format!("Tool '{}' not found in provider", name)));
+        };
+
+        match render_argv(tool, &arguments) {
+            Ok(argv) => self.run(tool, argv).await,
+            Err(e) => Ok(ToolResult::error(e)),
+        }
+    }
+
+    fn metadata(&self) -> HashMap<String, Value> {
+        let mut metadata = HashMap::new();
+        if let Some(description) = &self.config.description {
+            metadata.insert("description".to_string(), Value::String(description.clone()));
+        }
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolArgumentConfig;
+
+    fn argument(name: &str, arg_type: &str, required: bool) -> ToolArgumentConfig {
+        ToolArgumentConfig {
+            name: name.to_string(),
+            arg_type: arg_type.to_string(),
+            description: None,
+            required,
+            default: None,
+        }
+    }
+
+    fn tool(command: &[&str]) -> CommandToolConfig {
+        CommandToolConfig {
+            name: "grep".to_string(),
+            description: None,
+            command: command.iter().map(|s| s.to_string()).collect(),
+            arguments: vec![
+                argument("pattern", "string", true),
+                argument("files", "array", true),
+                argument("max", "integer", false),
+            ],
+            output: CommandOutputFormat::Lines,
+        }
+    }
+
+    #[test]
+    fn test_render_argv() {
+        let tool = tool(&["grep", "--max-count={max}", "-e", "{pattern}", "--", "{files}"]);
+        let argv = render_argv(&tool, &json!({ "pattern": "todo", "files": ["a.rs", "b.rs"] })).unwrap();
+        assert_eq!(argv, vec!["grep", "-e", "todo", "--", "a.rs", "b.rs"]);
+
+        let argv = render_argv(&tool, &json!({ "pattern": "x", "files": ["-a"], "max": 3 })).unwrap();
+        assert_eq!(argv, vec!["grep", "--max-count=3", "-e", "x", "--", "-a"]);
+    }
+
+    #[test]
+    fn test_render_argv_rejects_bad_input() {
+        let tool = tool(&["grep", "{pattern}", "{files}"]);
+        assert!(render_argv(&tool, &json!({ "pattern": "x" })).is_err());
+        assert!(render_argv(&tool, &json!({ "pattern": 1, "files": [] })).is_err());
+        assert!(render_argv(&tool, &json!({ "pattern": "--exec=sh", "files": [] })).is_err());
+    }
+
+    #[test]
+    fn test_undeclared_placeholder() {
+        let config = CommandProviderConfig {
+            name: "text".to_string(),
+            description: None,
+            env: HashMap::new(),
+            sandbox: Default::default(),
+            timeout_seconds: 5,
+            tools: vec![tool(&["grep", "{unknown}"])],
+        };
+        assert!(CommandProvider::new(config).is_err());
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let result = parse_output(CommandOutputFormat::Lines, "a\n\nb\n").unwrap();
+        assert_eq!(result.data, Some(json!(["a", "b"])));
+
+        let result = parse_output(CommandOutputFormat::Json, "{\"ok\": true}").unwrap();
+        assert_eq!(result.data, Some(json!({ "ok": true })));
+
+        assert!(!parse_output(CommandOutputFormat::Json, "nope").unwrap().success);
+    }
+}
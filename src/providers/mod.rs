@@ -0,0 +1,79 @@
+//! Built-in tool providers configured under `[[providers]]`
+
+pub mod command;
+#[cfg(feature = "sql")]
+pub mod sql;
+
+pub use command::CommandProvider;
+
+use crate::config::{ProviderConfig, ToolArgumentConfig};
+use crate::core::provider::{ParameterSchema, Provider};
+use crate::utils::errors::McpResult;
+use serde_json::Value;
+
+/// Build the provider described by a `[[providers]]` entry
+pub fn load(config: &ProviderConfig) -> McpResult<Box<dyn Provider>> {
+    match config {
+        ProviderConfig::Command(config) => Ok(Box::new(CommandProvider::new((**config).clone())?)),
+        #[cfg(feature = "sql")]
+        ProviderConfig::Sql(config) => Ok(Box::new(sql::SqlProvider::new((**config).clone())?)),
+        #[cfg(not(feature = "sql"))]
+        ProviderConfig::Sql(config) => Err(crate::utils::errors::McpError::ConfigError(format!(
+            "SQL provider '{}' needs supermcp built with the sql feature",
+            config.name
+        ))),
+    }
+}
+
+/// Parameter schemas for declared tool arguments
+pub(crate) fn parameter_schemas(declared: &[ToolArgumentConfig]) -> Vec<ParameterSchema> {
+    declared
+        .iter()
+        .map(|arg| ParameterSchema {
+            name: arg.name.clone(),
+            description: arg.description.clone(),
+            required: arg.required,
+            param_type: arg.arg_type.clone(),
+            default: arg.default.clone(),
+        })
+        .collect()
+}
+
+/// Match call arguments to their declarations, in declaration order
+///
+/// Omitted arguments take their default; still-missing optional arguments
+/// are `None`. Fails on missing required arguments and type mismatches.
+pub(crate) fn resolve_arguments<'a>(
+    declared: &'a [ToolArgumentConfig],
+    arguments: &Value,
+) -> Result<Vec<(&'a ToolArgumentConfig, Option<Value>)>, String> {
+    let mut resolved = Vec::with_capacity(declared.len());
+    for arg in declared {
+        let value = arguments
+            .get(&arg.name)
+            .filter(|v| !v.is_null())
+            .or(arg.default.as_ref());
+        match value {
+            Some(value) if !type_matches(&arg.arg_type, value) => {
+                return Err(format!("Argument '{}' must be of type {}", arg.name, arg.arg_type));
+            }
+            None if arg.required => return Err(format!("Missing required argument: {}", arg.name)),
+            value => resolved.push((arg, value.cloned())),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Check an argument value against its declared type
+fn type_matches(arg_type: &str, value: &Value) -> bool {
+    match arg_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "array" => value
+            .as_array()
+            .is_some_and(|items| !items.iter().any(|i| i.is_array() || i.is_object())),
+        _ => true,
+    }
+}
@@ -0,0 +1,489 @@
+//! SQL provider: read-only queries against Postgres, MySQL or SQLite
+//!
+//! Each configured statement becomes a tool taking its bind parameters.
+//! Read-only access is enforced twice: statements must be a single
+//! SELECT-style query, and every pooled connection is switched to
+//! read-only mode when it opens. Results are capped by row count and
+//! serialized size.
+//!
+//! Columns are decoded through sqlx's `Any` driver, which handles
+//! integers, floats, booleans, text and blobs; cast other types (dates,
+//! numerics, JSON) to text in the statement.
+
+use crate::config::{SqlProviderConfig, SqlQueryConfig, ToolArgumentConfig};
+use crate::core::provider::{Provider, ProviderType, Tool, ToolResult};
+use crate::providers::{parameter_schemas, resolve_arguments};
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde_json::{json, Map, Value};
+use sqlx::any::{AnyPoolOptions, AnyRow, AnyTypeInfoKind};
+use sqlx::{AnyPool, Column, Executor, Row, ValueRef};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
+
+/// Name of the ad-hoc query tool
+const ADHOC_TOOL: &str = "query";
+
+/// Leading keywords of statements that only read
+const READ_ONLY_KEYWORDS: &[&str] = &["select", "with", "values", "table", "explain", "show", "describe", "desc"];
+
+/// Database backend, from the URL scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl Backend {
+    fn from_url(url: &str) -> McpResult<Self> {
+        let scheme = url.split(':').next().unwrap_or_default();
+        match scheme {
+            "postgres" | "postgresql" => Ok(Backend::Postgres),
+            "mysql" | "mariadb" => Ok(Backend::MySql),
+            "sqlite" => Ok(Backend::Sqlite),
+            _ => Err(McpError::ConfigError(format!(
+                "Unsupported database URL scheme '{}'",
+                scheme
+            ))),
+        }
+    }
+
+    /// Statement switching a session to read-only
+    fn read_only_statement(&self) -> &'static str {
+        match self {
+            Backend::Postgres => "SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY",
+            Backend::MySql => "SET SESSION TRANSACTION READ ONLY",
+            Backend::Sqlite => "PRAGMA query_only = ON",
+        }
+    }
+
+    /// Statement opening the read-only transaction each query runs in
+    ///
+    /// Session settings are rolled back with it, so a query cannot turn
+    /// the pooled connection read-write for later ones.
+    fn begin_read_only(&self) -> &'static str {
+        match self {
+            Backend::Postgres | Backend::MySql => "START TRANSACTION READ ONLY",
+            Backend::Sqlite => "BEGIN",
+        }
+    }
+}
+
+/// Whether `sql` is a single statement that only reads
+///
+/// Comments and string literals are skipped; one trailing `;` is allowed.
+/// Any `INTO` clause is refused: MySQL's `INTO OUTFILE`/`DUMPFILE` writes
+/// to the server's disk and `INTO @var` sets session state, neither of
+/// which the read-only transaction prevents.
+pub fn is_read_only_statement(sql: &str) -> bool {
+    let mut code = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut statements = 0;
+    let mut after_semicolon = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                code.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                code.push(' ');
+            }
+            '\'' | '"' | '`' => {
+                for inner in chars.by_ref() {
+                    if inner == c {
+                        break;
+                    }
+                }
+                code.push(' ');
+            }
+            ';' => {
+                statements += 1;
+                after_semicolon = true;
+            }
+            c if !c.is_whitespace() && after_semicolon => return false,
+            c => code.push(c),
+        }
+    }
+
+    let mut words = code
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '@')
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase);
+    let first = words.next().unwrap_or_default();
+    statements <= 1
+        && READ_ONLY_KEYWORDS.contains(&first.as_str())
+        && !words.any(|word| word == "into")
+}
+
+/// Provider running read-only SQL
+pub struct SqlProvider {
+    config: SqlProviderConfig,
+    backend: Backend,
+    pool: AnyPool,
+    adhoc: SqlQueryConfig,
+}
+
+impl SqlProvider {
+    /// Validate the statements and open a lazy connection pool
+    pub fn new(config: SqlProviderConfig) -> McpResult<Self> {
+        let url = match (&config.url, &config.url_env) {
+            (Some(url), _) => url.clone(),
+            (None, Some(var)) => std::env::var(var).map_err(|_| {
+                McpError::ConfigError(format!(
+                    "SQL provider '{}': environment variable {} is not set",
+                    config.name, var
+                ))
+            })?,
+            (None, None) => {
+                return Err(McpError::ConfigError(format!(
+                    "SQL provider '{}' needs url or url_env",
+                    config.name
+                )))
+            }
+        };
+
+        for query in &config.queries {
+            if !is_read_only_statement(&query.sql) {
+                return Err(McpError::ConfigError(format!(
+                    "SQL provider '{}': query '{}' is not a single read-only statement",
+                    config.name, query.name
+                )));
+            }
+        }
+
+        let backend = Backend::from_url(&url)?;
+        let url = match backend {
+            Backend::Sqlite if !url.contains("mode=") => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                format!("{}{}mode=ro", url, separator)
+            }
+            _ => url,
+        };
+
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.timeout_seconds))
+            .after_connect(move |conn, _meta| {
+                Box::pin(async move {
+                    conn.execute(backend.read_only_statement()).await?;
+                    Ok(())
+                })
+            })
+            .connect_lazy(&url)
+            .map_err(|e| {
+                McpError::ConfigError(format!("SQL provider '{}': {}", config.name, e))
+            })?;
+
+        let adhoc = SqlQueryConfig {
+            name: ADHOC_TOOL.to_string(),
+            description: Some("Run a read-only SQL query".to_string()),
+            sql: String::new(),
+            parameters: vec![ToolArgumentConfig {
+                name: "sql".to_string(),
+                arg_type: "string".to_string(),
+                description: Some("A single SELECT statement".to_string()),
+                required: true,
+                default: None,
+            }],
+        };
+
+        Ok(Self {
+            config,
+            backend,
+            pool,
+            adhoc,
+        })
+    }
+
+    fn find_query(&self, name: &str) -> Option<&SqlQueryConfig> {
+        let tool_name = name
+            .strip_prefix(&format!("{}.", self.config.name))
+            .unwrap_or(name);
+        if self.config.allow_adhoc && tool_name == ADHOC_TOOL {
+            return Some(&self.adhoc);
+        }
+        self.config.queries.iter().find(|q| q.name == tool_name)
+    }
+
+    /// Run a statement, stopping at the row and byte limits
+    async fn run(&self, sql: &str, binds: Vec<(&ToolArgumentConfig, Option<Value>)>) -> McpResult<ToolResult> {
+        let mut query = sqlx::query(sql);
+        for (arg, value) in binds {
+            query = match (arg.arg_type.as_str(), value) {
+                (_, None) => query.bind(None::<String>),
+                ("integer", Some(v)) => query.bind(v.as_i64()),
+                ("number", Some(v)) => query.bind(v.as_f64()),
+                ("boolean", Some(v)) => query.bind(v.as_bool()),
+                (_, Some(Value::String(s))) => query.bind(s),
+                (_, Some(v)) => query.bind(v.to_string()),
+            };
+        }
+
+        let mut conn = self.pool.acquire().await.map_err(sql_error)?;
+        conn.execute(self.backend.begin_read_only()).await.map_err(sql_error)?;
+        let fetched = self.fetch(query, &mut conn).await;
+        // A connection that cannot roll back is closed rather than reused
+        if conn.execute("ROLLBACK").await.is_err() {
+            drop(conn.detach());
+        }
+        let (columns, rows, truncated) = fetched?;
+
+        let data = json!({
+            "columns": columns,
+            "rows": rows,
+            "row_count": rows.len(),
+            "truncated": truncated,
+        });
+        let text = serde_json::to_string(&data)?;
+        Ok(ToolResult::success(data)?.with_content(vec![json!({ "type": "text", "text": text })]))
+    }
+}
+
+impl SqlProvider {
+    /// Rows of a query up to the row and byte limits, and whether any
+    /// were left out
+    async fn fetch<'q>(
+        &self,
+        query: sqlx::query::Query<'q, sqlx::Any, sqlx::any::AnyArguments<'q>>,
+        conn: &mut sqlx::AnyConnection,
+    ) -> McpResult<(Vec<String>, Vec<Value>, bool)> {
+        let mut columns: Vec<String> = Vec::new();
+        let mut rows = Vec::new();
+        let mut bytes = 0;
+        let mut truncated = false;
+
+        let mut stream = query.fetch(conn);
+        while let Some(row) = stream.try_next().await.map_err(sql_error)? {
+            if columns.is_empty() {
+                columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+            }
+            if rows.len() >= self.config.max_rows {
+                truncated = true;
+                break;
+            }
+
+            let row = row_to_json(&row)?;
+            bytes += row.to_string().len();
+            if bytes > self.config.max_bytes {
+                truncated = true;
+                break;
+            }
+            rows.push(row);
+        }
+        Ok((columns, rows, truncated))
+    }
+}
+
+fn sql_error(e: sqlx::Error) -> McpError {
+    McpError::ToolExecutionError(format!("SQL error: {}", e))
+}
+
+/// Convert a row to a JSON object keyed by column name
+fn row_to_json(row: &AnyRow) -> McpResult<Value> {
+    let mut object = Map::new();
+    for (i, column) in row.columns().iter().enumerate() {
+        let raw = row.try_get_raw(i).map_err(sql_error)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().kind() {
+                AnyTypeInfoKind::Bool => json!(row.try_get::<bool, _>(i).map_err(sql_error)?),
+                AnyTypeInfoKind::SmallInt | AnyTypeInfoKind::Integer | AnyTypeInfoKind::BigInt => {
+                    json!(row.try_get::<i64, _>(i).map_err(sql_error)?)
+                }
+                AnyTypeInfoKind::Real | AnyTypeInfoKind::Double => {
+                    json!(row.try_get::<f64, _>(i).map_err(sql_error)?)
+                }
+                AnyTypeInfoKind::Blob => {
+                    use base64::Engine;
+                    let blob = row.try_get::<Vec<u8>, _>(i).map_err(sql_error)?;
+                    json!(base64::engine::general_purpose::STANDARD.encode(blob))
+                }
+                _ => json!(row.try_get::<String, _>(i).map_err(sql_error)?),
+            }
+        };
+        object.insert(column.name().to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+#[async_trait]
+impl Provider for SqlProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Sql
+    }
+
+    async fn is_available(&self) -> bool {
+        self.pool.acquire().await.is_ok()
+    }
+
+    async fn list_tools(&self) -> McpResult<Vec<Tool>> {
+        let adhoc = self.config.allow_adhoc.then_some(&self.adhoc);
+        Ok(self
+            .config
+            .queries
+            .iter()
+            .chain(adhoc)
+            .map(|query| Tool {
+                name: format!("{}.{}", self.config.name, query.name),
+                description: query.description.clone(),
+                provider: self.config.name.clone(),
+                provider_type: ProviderType::Sql,
+                parameters: parameter_schemas(&query.parameters),
+                metadata: HashMap::new(),
+            })
+            .collect())
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> McpResult<ToolResult> {
+        let Some(query) = self.find_query(name) else {
+            return Ok(ToolResult::error(format!("Tool '{}' not found in provider", name)));
+        };
+
+        let binds = match resolve_arguments(&query.parameters, &arguments) {
+            Ok(binds) => binds,
+            Err(e) => return Ok(ToolResult::error(e)),
+        };
+
+        let (sql, binds) = if query.name == ADHOC_TOOL && query.sql.is_empty() {
+            let sql = arguments.get("sql").and_then(|s| s.as_str()).unwrap_or_default();
+            if !is_read_only_statement(sql) {
+                return Ok(ToolResult::error("Only single read-only statements are allowed"));
+            }
+            (sql, Vec::new())
+        } else {
+            (query.sql.as_str(), binds)
+        };
+
+        debug!("Running SQL tool {}.{}", self.config.name, query.name);
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        match tokio::time::timeout(timeout, self.run(sql, binds)).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(e)) => Ok(ToolResult::error(e.to_string())),
+            Err(_) => Ok(ToolResult::error(format!(
+                "Query timed out after {}s",
+                self.config.timeout_seconds
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(url: &str, sql: &str) -> SqlProviderConfig {
+        SqlProviderConfig {
+            name: "db".to_string(),
+            description: None,
+            url: Some(url.to_string()),
+            url_env: None,
+            queries: vec![SqlQueryConfig {
+                name: "items".to_string(),
+                description: None,
+                sql: sql.to_string(),
+                parameters: vec![ToolArgumentConfig {
+                    name: "min".to_string(),
+                    arg_type: "integer".to_string(),
+                    description: None,
+                    required: true,
+                    default: None,
+                }],
+            }],
+            allow_adhoc: true,
+            max_rows: 2,
+            max_bytes: 1024,
+            timeout_seconds: 5,
+            max_connections: 1,
+        }
+    }
+
+    #[test]
+    fn test_read_only_statements() {
+        assert!(is_read_only_statement("SELECT * FROM t WHERE id = $1"));
+        assert!(is_read_only_statement("  with x as (select 1) select * from x;"));
+        assert!(is_read_only_statement("-- comment\nSELECT 'a; DROP TABLE t'"));
+        assert!(!is_read_only_statement("DELETE FROM t"));
+        assert!(!is_read_only_statement("SELECT 1; DROP TABLE t"));
+        assert!(!is_read_only_statement("/* SELECT */ UPDATE t SET x = 1"));
+        assert!(!is_read_only_statement(""));
+    }
+
+    #[test]
+    fn test_read_only_rejects_into() {
+        assert!(!is_read_only_statement("SELECT * FROM t INTO OUTFILE '/tmp/t.csv'"));
+        assert!(!is_read_only_statement("SELECT body FROM t LIMIT 1 INTO DUMPFILE '/tmp/x'"));
+        assert!(!is_read_only_statement("SELECT id INTO @id FROM t"));
+        assert!(!is_read_only_statement("select * into copy from t"));
+        assert!(is_read_only_statement("SELECT 'into' AS \"into\" FROM t"));
+        assert!(is_read_only_statement("SELECT into_date FROM t"));
+    }
+
+    #[test]
+    fn test_rejects_write_queries() {
+        assert!(SqlProvider::new(config("sqlite::memory:", "DELETE FROM items")).is_err());
+        assert!(SqlProvider::new(config("redis://localhost", "SELECT 1")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_query_with_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+
+        sqlx::any::install_default_drivers();
+        let setup = AnyPoolOptions::new().connect(&url).await.unwrap();
+        setup
+            .execute("CREATE TABLE items (id INTEGER, name TEXT); INSERT INTO items VALUES (1, 'a'), (2, 'b'), (3, 'c');")
+            .await
+            .unwrap();
+        setup.close().await;
+
+        let provider = SqlProvider::new(config(
+            &format!("sqlite://{}", path.display()),
+            "SELECT id, name FROM items WHERE id >= ? ORDER BY id",
+        ))
+        .unwrap();
+
+        let result = provider.call_tool("db.items", json!({ "min": 1 })).await.unwrap();
+        let data = result.data.unwrap();
+        assert_eq!(data["rows"][0], json!({ "id": 1, "name": "a" }));
+        assert_eq!(data["row_count"], 2);
+        assert_eq!(data["truncated"], true);
+
+        // The connection is read-only even for ad-hoc SQL that slips past the check
+        let result = provider
+            .call_tool("db.query", json!({ "sql": "WITH x AS (SELECT 1) DELETE FROM items" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        let result = provider
+            .call_tool("db.query", json!({ "sql": "DROP TABLE items" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+}
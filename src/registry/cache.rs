@@ -1,9 +1,13 @@
-//! Local caching for registry data
+//! Caching for registry data
+//!
+//! The cache lives in a local directory, or in object storage when
+//! `cache_dir` is an `s3://`, `gs://` or `az://` URL so a fleet shares one
+//! copy of the registry.
+use crate::cloud::Location;
 use crate::registry::types::{RegistryEntry, RegistryConfig};
 use crate::utils::errors::{McpError, McpResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use tracing::{debug, info};
 
@@ -14,48 +18,35 @@ struct CachedData {
     last_updated: SystemTime,
 }
 
+/// Name of the cache object
+pub(crate) const CACHE_FILE: &str = "registry.json";
+
 /// Registry cache manager
 pub struct RegistryCache {
-    cache_dir: PathBuf,
+    location: Location,
     ttl: Duration,
 }
 
 impl RegistryCache {
-    pub fn new(config: &RegistryConfig) -> Self {
-        Self {
-            cache_dir: config.cache_dir.clone(),
+    pub fn new(config: &RegistryConfig) -> McpResult<Self> {
+        Ok(Self {
+            location: Location::parse(&config.cache_dir.to_string_lossy())?,
             ttl: Duration::from_secs(config.cache_ttl_hours * 3600),
-        }
-    }
-
-    /// Ensure cache directory exists
-    async fn ensure_cache_dir(&self) -> McpResult<()> {
-        if !self.cache_dir.exists() {
-            tokio::fs::create_dir_all(&self.cache_dir)
-                .await
-                .map_err(|e| McpError::InternalError(format!("Failed to create cache dir: {}", e)))?;
-        }
-        Ok(())
-    }
-
-    fn cache_file_path(&self) -> PathBuf {
-        self.cache_dir.join("registry.json")
+        })
     }
 
     /// Load cached entries if they exist and are not expired
     pub async fn load(&self) -> McpResult<Option<HashMap<String, RegistryEntry>>> {
-        self.ensure_cache_dir().await?;
-
-        let cache_file = self.cache_file_path();
-        if !cache_file.exists() {
-            return Ok(None);
-        }
-
-        let content = tokio::fs::read_to_string(&cache_file)
+        let Some(content) = self
+            .location
+            .read(CACHE_FILE)
             .await
-            .map_err(|e| McpError::InternalError(format!("Failed to read cache: {}", e)))?;
+            .map_err(|e| McpError::InternalError(format!("Failed to read cache: {}", e)))?
+        else {
+            return Ok(None);
+        };
 
-        let cached: CachedData = serde_json::from_str(&content)
+        let cached: CachedData = serde_json::from_slice(&content)
             .map_err(|e| McpError::InternalError(format!("Failed to parse cache: {}", e)))?;
 
         // Check if cache is expired
@@ -73,18 +64,16 @@ impl RegistryCache {
 
     /// Save entries to cache
     pub async fn save(&self, entries: &HashMap<String, RegistryEntry>) -> McpResult<()> {
-        self.ensure_cache_dir().await?;
-
         let cached = CachedData {
             entries: entries.clone(),
             last_updated: SystemTime::now(),
         };
 
-        let content = serde_json::to_string_pretty(&cached)
+        let content = serde_json::to_vec_pretty(&cached)
             .map_err(|e| McpError::InternalError(format!("Failed to serialize cache: {}", e)))?;
 
-        let cache_file = self.cache_file_path();
-        tokio::fs::write(&cache_file, content)
+        self.location
+            .write(CACHE_FILE, content)
             .await
             .map_err(|e| McpError::InternalError(format!("Failed to write cache: {}", e)))?;
 
@@ -94,12 +83,9 @@ impl RegistryCache {
 
     /// Clear the cache
     pub async fn clear(&self) -> McpResult<()> {
-        let cache_file = self.cache_file_path();
-        if cache_file.exists() {
-            tokio::fs::remove_file(&cache_file)
-                .await
-                .map_err(|e| McpError::InternalError(format!("Failed to clear cache: {}", e)))?;
-        }
-        Ok(())
+        self.location
+            .remove(CACHE_FILE)
+            .await
+            .map_err(|e| McpError::InternalError(format!("Failed to clear cache: {}", e)))
     }
 }
@@ -1,6 +1,7 @@
 //! Registry HTTP client for MCP server discovery
 use crate::registry::cache::RegistryCache;
 use crate::registry::types::{RegistryConfig, RegistryEntry, SearchResults};
+use crate::transport::http_client::apply_proxy;
 use crate::utils::errors::{McpError, McpResult};
 use reqwest::Client;
 use std::collections::HashMap;
@@ -16,10 +17,9 @@ pub struct RegistryClient {
 
 impl RegistryClient {
     pub fn new(config: RegistryConfig) -> McpResult<Self> {
-        let cache = RegistryCache::new(&config);
+        let cache = RegistryCache::new(&config)?;
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+        let client = apply_proxy(Client::builder().timeout(Duration::from_secs(30)), &config.proxy)?
             .build()
             .map_err(|e| McpError::TransportError(format!("Failed to build HTTP client: {}", e)))?;
 
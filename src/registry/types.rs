@@ -32,6 +32,9 @@ pub struct RegistryConfig {
     pub url: String,
     pub cache_dir: std::path::PathBuf,
     pub cache_ttl_hours: u64,
+    /// Proxy for registry requests
+    #[serde(default)]
+    pub proxy: crate::config::ProxyConfig,
 }
 
 impl Default for RegistryConfig {
@@ -42,6 +45,7 @@ impl Default for RegistryConfig {
                 .unwrap_or_else(|| std::path::PathBuf::from(".cache"))
                 .join("super-mcp/registry"),
             cache_ttl_hours: 24,
+            proxy: crate::config::ProxyConfig::default(),
         }
     }
 }
@@ -0,0 +1,383 @@
+//! Deno runtime
+//!
+//! Deno denies file, network and environment access unless granted on the
+//! command line. The grants are generated from the same `SandboxConfig`
+//! the OS sandbox uses, so TypeScript servers stay confined even on
+//! platforms where OS sandboxing isn't available.
+
+use crate::config::{FilesystemAccess, McpServerConfig, SandboxConfig};
+use crate::runtime::types::{
+    ExecutionResult, ResourceLimits, RuntimeConfig, RuntimeError, RuntimeType,
+};
+use crate::utils::process::output_with_timeout;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Deno subcommands that accept permission flags
+const PERMISSION_SUBCOMMANDS: &[&str] = &["run", "serve"];
+
+/// Deno permission flags granting what `sandbox` allows
+///
+/// `env_keys` are the variables the process may read; they are only used
+/// when the sandbox doesn't inherit the parent environment.
+pub fn permission_flags(sandbox: &SandboxConfig, env_keys: &[String]) -> Vec<String> {
+    let mut flags = vec!["--no-prompt".to_string()];
+
+    match &sandbox.filesystem {
+        FilesystemAccess::Simple(s) if s == "full" => {
+            flags.push("--allow-read".to_string());
+            flags.push("--allow-write".to_string());
+        }
+        FilesystemAccess::Simple(_) => {
+            flags.push("--allow-read".to_string());
+            if !sandbox.writable.is_empty() {
+                flags.push(format!("--allow-write={}", sandbox.writable.join(",")));
+            }
+        }
+        FilesystemAccess::Paths(paths) if !paths.is_empty() => {
+            let paths = paths.join(",");
+            flags.push(format!("--allow-read={}", paths));
+            flags.push(format!("--allow-write={}", paths));
+        }
+        FilesystemAccess::Paths(_) => {}
+    }
+
+    if sandbox.network {
+        flags.push("--allow-net".to_string());
+    }
+
+    if sandbox.env_inherit {
+        flags.push("--allow-env".to_string());
+    } else if !env_keys.is_empty() {
+        flags.push(format!("--allow-env={}", env_keys.join(",")));
+    }
+
+    flags
+}
+
+/// Whether an argument already grants or denies Deno permissions
+fn is_permission_flag(arg: &str) -> bool {
+    arg == "-A" || arg.starts_with("--allow-") || arg.starts_with("--deny-")
+}
+
+/// Insert permission flags into a `deno run`/`deno serve` server command
+///
+/// Commands that aren't Deno, servers with sandboxing disabled, and
+/// commands that already carry explicit permission flags are left as-is.
+pub fn apply_permission_flags(config: &mut McpServerConfig) {
+    let is_deno = Path::new(&config.command)
+        .file_stem()
+        .is_some_and(|stem| stem == "deno");
+    if !is_deno || !config.sandbox.enabled {
+        return;
+    }
+
+    let Some(subcommand) = config.args.first() else {
+        return;
+    };
+    if !PERMISSION_SUBCOMMANDS.contains(&subcommand.as_str()) {
+        return;
+    }
+
+    if config.args.iter().any(|arg| is_permission_flag(arg)) {
+        debug!(
+            "Server {} sets its own Deno permissions, not deriving them from the sandbox",
+            config.name
+        );
+        return;
+    }
+
+    let mut env_keys: Vec<String> = config.env.keys().cloned().collect();
+    env_keys.sort();
+
+    let flags = permission_flags(&config.sandbox, &env_keys);
+    debug!("Deno permissions for server {}: {}", config.name, flags.join(" "));
+    config.args.splice(1..1, flags);
+}
+
+/// Deno runtime implementation
+#[derive(Debug)]
+pub struct DenoRuntime {
+    name: String,
+    working_dir: PathBuf,
+    packages: Vec<String>,
+    env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+}
+
+impl DenoRuntime {
+    /// Create a new Deno runtime from configuration
+    pub fn new(name: String, config: RuntimeConfig) -> Self {
+        let working_dir = config
+            .working_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::cache_dir()
+                    .unwrap_or_else(|| PathBuf::from("/tmp"))
+                    .join(format!("super-mcp/deno-{}", name))
+            });
+
+        Self {
+            name,
+            working_dir,
+            packages: config.packages,
+            env: config.env,
+            resource_limits: config.resource_limits,
+        }
+    }
+
+    /// Find the deno executable
+    fn find_deno(&self) -> Result<PathBuf, RuntimeError> {
+        if let Ok(cmd) = std::env::var("DENO_COMMAND") {
+            if let Ok(path) = which::which(&cmd) {
+                return Ok(path);
+            }
+        }
+
+        which::which("deno")
+            .map_err(|_| RuntimeError::RuntimeNotFound("deno not found in PATH".to_string()))
+    }
+
+    /// Module cache private to this runtime
+    fn deno_dir(&self) -> PathBuf {
+        self.working_dir.join(".deno")
+    }
+
+    /// Pre-fetch `npm:`/`jsr:` packages into the runtime's module cache
+    async fn install(&self) -> Result<(), RuntimeError> {
+        tokio::fs::create_dir_all(&self.working_dir).await?;
+
+        if self.packages.is_empty() {
+            return Ok(());
+        }
+
+        info!("Caching packages for Deno runtime {}: {:?}", self.name, self.packages);
+
+        let mut cmd = Command::new(self.find_deno()?);
+        cmd.arg("cache")
+            .args(&self.packages)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .env("DENO_DIR", self.deno_dir())
+            .env("NO_COLOR", "1");
+
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| RuntimeError::InstallError(e.to_string()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(RuntimeError::InstallError(stderr.trim().to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Server config that runs `deno run <args>` with permissions derived
+    /// from this runtime's resource limits
+    pub fn server_config(&self, server_name: &str, args: Vec<String>) -> McpServerConfig {
+        let sandbox = self.resource_limits.to_sandbox_config(&self.working_dir);
+
+        let mut env_keys: Vec<String> = self.env.keys().cloned().collect();
+        env_keys.sort();
+        let flags = permission_flags(&sandbox, &env_keys);
+
+        let mut env = self.env.clone();
+        env.insert("DENO_DIR".to_string(), self.deno_dir().to_string_lossy().to_string());
+        env.insert("DENO_NO_UPDATE_CHECK".to_string(), "1".to_string());
+        env.insert("NO_COLOR".to_string(), "1".to_string());
+
+        let command = self
+            .find_deno()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "deno".to_string());
+
+        McpServerConfig {
+            name: server_name.to_string(),
+            command,
+            args: ["run".to_string()].into_iter().chain(flags).chain(args).collect(),
+            env,
+            sandbox,
+            ..Default::default()
+        }
+    }
+
+    /// Run a script file under Deno and the OS sandbox
+    async fn run_script(&self, script_path: &Path) -> Result<ExecutionResult, RuntimeError> {
+        let start_time = Instant::now();
+
+        let config = self.server_config(
+            &self.name,
+            vec![script_path.to_string_lossy().to_string()],
+        );
+        let sandbox = crate::sandbox::create_sandbox(&config);
+
+        debug!("Executing script with deno: {} {}", config.command, config.args.join(" "));
+
+        let child = sandbox
+            .spawn(&config)
+            .await
+            .map_err(|e| RuntimeError::ExecutionError(format!("Failed to spawn deno process: {}", e)))?;
+
+        let timeout = Duration::from_secs(self.resource_limits.timeout_seconds);
+        let output = output_with_timeout(child, None, timeout)
+            .await?
+            .ok_or(RuntimeError::Timeout(self.resource_limits.timeout_seconds))?;
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+
+        Ok(ExecutionResult {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            exit_code: output.status.code().unwrap_or(-1),
+            execution_time_ms,
+            output_value: None,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::runtime::types::Runtime for DenoRuntime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn runtime_type(&self) -> RuntimeType {
+        RuntimeType::NodeDeno
+    }
+
+    async fn validate(&self) -> Result<(), RuntimeError> {
+        self.find_deno().map(|_| ())
+    }
+
+    fn resource_limits(&self) -> &ResourceLimits {
+        &self.resource_limits
+    }
+
+    async fn execute(
+        &self,
+        script: &str,
+        _input: Option<Value>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        self.install().await?;
+
+        let script_path = self
+            .working_dir
+            .join(format!("script_{}.ts", uuid::Uuid::new_v4()));
+        tokio::fs::write(&script_path, script).await?;
+
+        let result = self.run_script(&script_path).await;
+        let _ = tokio::fs::remove_file(&script_path).await;
+        result
+    }
+
+    async fn execute_file(
+        &self,
+        path: &Path,
+        _input: Option<Value>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        self.install().await?;
+        self.run_script(path).await
+    }
+
+    async fn install_packages(&self) -> Result<(), RuntimeError> {
+        self.install().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(filesystem: FilesystemAccess, network: bool) -> SandboxConfig {
+        SandboxConfig {
+            filesystem,
+            network,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_readonly_without_network() {
+        let flags = permission_flags(
+            &sandbox(FilesystemAccess::Simple("readonly".to_string()), false),
+            &["API_KEY".to_string()],
+        );
+        assert_eq!(flags, vec!["--no-prompt", "--allow-read", "--allow-env=API_KEY"]);
+    }
+
+    #[test]
+    fn test_readonly_with_writable_paths() {
+        let mut config = sandbox(FilesystemAccess::Simple("readonly".to_string()), false);
+        config.writable = vec!["/data".to_string(), "/out".to_string()];
+        let flags = permission_flags(&config, &[]);
+        assert_eq!(flags, vec!["--no-prompt", "--allow-read", "--allow-write=/data,/out"]);
+    }
+
+    #[test]
+    fn test_paths_and_network() {
+        let flags = permission_flags(
+            &sandbox(
+                FilesystemAccess::Paths(vec!["/data".to_string(), "/tmp".to_string()]),
+                true,
+            ),
+            &[],
+        );
+        assert_eq!(
+            flags,
+            vec![
+                "--no-prompt",
+                "--allow-read=/data,/tmp",
+                "--allow-write=/data,/tmp",
+                "--allow-net",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_to_deno_server() {
+        let mut config = McpServerConfig {
+            name: "ts".to_string(),
+            command: "/usr/local/bin/deno".to_string(),
+            args: vec!["run".to_string(), "server.ts".to_string()],
+            ..Default::default()
+        };
+        apply_permission_flags(&mut config);
+        assert_eq!(config.args, vec!["run", "--no-prompt", "--allow-read", "server.ts"]);
+    }
+
+    #[test]
+    fn test_apply_respects_explicit_permissions() {
+        let args = vec!["run".to_string(), "-A".to_string(), "server.ts".to_string()];
+        let mut config = McpServerConfig {
+            command: "deno".to_string(),
+            args: args.clone(),
+            ..Default::default()
+        };
+        apply_permission_flags(&mut config);
+        assert_eq!(config.args, args);
+
+        let mut config = McpServerConfig {
+            command: "node".to_string(),
+            args: vec!["run".to_string()],
+            ..Default::default()
+        };
+        apply_permission_flags(&mut config);
+        assert_eq!(config.args, vec!["run"]);
+    }
+
+    #[test]
+    fn test_deno_alias() {
+        let parsed: RuntimeType = serde_json::from_value(serde_json::json!("deno")).unwrap();
+        assert_eq!(parsed, RuntimeType::NodeDeno);
+    }
+}
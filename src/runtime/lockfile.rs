@@ -0,0 +1,254 @@
+//! Dependency lockfiles for runtimes
+//!
+//! A frozen runtime keeps its package manifest and lockfile in a lock
+//! directory next to the config file. Installs copy both into the working
+//! directory and run the package manager in frozen mode, so every host
+//! gets the same dependency tree.
+
+use crate::runtime::types::{RuntimeError, RuntimeType};
+use std::path::{Path, PathBuf};
+
+/// Manifest and lockfile names used by a runtime's package manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockFiles {
+    pub manifest: &'static str,
+    pub lockfile: &'static str,
+}
+
+impl LockFiles {
+    /// Files for a runtime type, or `None` if it doesn't support locking
+    pub fn for_runtime(runtime_type: &RuntimeType) -> Option<Self> {
+        let lockfile = match runtime_type {
+            RuntimeType::NodeNpm => "package-lock.json",
+            RuntimeType::NodePnpm => "pnpm-lock.yaml",
+            RuntimeType::NodeBun => "bun.lock",
+            RuntimeType::PythonUv => "uv.lock",
+            RuntimeType::PythonWasm | RuntimeType::NodeDeno => return None,
+        };
+        let manifest = match runtime_type {
+            RuntimeType::PythonUv => "pyproject.toml",
+            _ => "package.json",
+        };
+        Some(Self { manifest, lockfile })
+    }
+
+    /// Copy the manifest and lockfile from `working_dir` into `lock_dir`
+    pub async fn save(&self, working_dir: &Path, lock_dir: &Path) -> Result<(), RuntimeError> {
+        tokio::fs::create_dir_all(lock_dir).await?;
+        for name in [self.manifest, self.lockfile] {
+            let source = working_dir.join(name);
+            if !source.exists() {
+                return Err(RuntimeError::InstallError(format!(
+                    "package manager did not produce {}",
+                    name
+                )));
+            }
+            tokio::fs::copy(&source, lock_dir.join(name)).await?;
+        }
+        Ok(())
+    }
+
+    /// Copy a frozen manifest and lockfile into `working_dir`
+    ///
+    /// Returns `false` when `lock_dir` has no lockfile yet.
+    pub async fn restore(&self, lock_dir: &Path, working_dir: &Path) -> Result<bool, RuntimeError> {
+        if !lock_dir.join(self.lockfile).exists() {
+            return Ok(false);
+        }
+
+        tokio::fs::create_dir_all(working_dir).await?;
+        for name in [self.manifest, self.lockfile] {
+            tokio::fs::copy(lock_dir.join(name), working_dir.join(name)).await?;
+        }
+        Ok(true)
+    }
+
+    /// Configured packages that the frozen manifest doesn't declare
+    ///
+    /// Python packages match by normalized name and, when the configured
+    /// package has one, by version specifier; npm packages by name.
+    pub fn missing_packages(&self, manifest: &str, packages: &[String]) -> Result<Vec<String>, RuntimeError> {
+        let declared: Vec<String> = if self.manifest == "pyproject.toml" {
+            let doc: toml::Value = toml::from_str(manifest).map_err(|e| {
+                RuntimeError::ValidationError(format!("invalid {}: {}", self.manifest, e))
+            })?;
+            doc.get("project")
+                .and_then(|p| p.get("dependencies"))
+                .and_then(|d| d.as_array())
+                .map(|deps| deps.iter().filter_map(|d| d.as_str().map(String::from)).collect())
+                .unwrap_or_default()
+        } else {
+            let doc: serde_json::Value = serde_json::from_str(manifest)?;
+            doc.get("dependencies")
+                .and_then(|d| d.as_object())
+                .map(|deps| deps.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+
+        let missing = packages
+            .iter()
+            .filter(|package| {
+                if self.manifest == "pyproject.toml" {
+                    let (name, specifier) = python_requirement(package);
+                    !declared.iter().map(|d| python_requirement(d)).any(|(n, s)| {
+                        n == name && (specifier.is_empty() || s == specifier)
+                    })
+                } else {
+                    let wanted = npm_package_name(package);
+                    !declared.iter().any(|d| d == wanted)
+                }
+            })
+            .cloned()
+            .collect();
+        Ok(missing)
+    }
+}
+
+/// Package name from an npm spec such as `zod@3.22.4` or `@scope/pkg@1`
+pub fn npm_package_name(spec: &str) -> &str {
+    // Skip the leading '@' of scoped packages
+    match spec.get(1..).and_then(|rest| rest.find('@')) {
+        Some(i) => &spec[..i + 1],
+        None => spec,
+    }
+}
+
+/// Normalized name and version specifier of a PEP 508 requirement
+///
+/// Names follow PEP 503 (`Foo_Bar` is `foo-bar`). The specifier has its
+/// whitespace removed and clauses sorted, so `>= 1, <2` and `<2,>=1` are
+/// the same; extras and environment markers are left out.
+pub fn python_requirement(spec: &str) -> (String, String) {
+    let spec = spec.split(';').next().unwrap_or(spec).trim();
+    let end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(spec.len());
+    let (name, rest) = spec.split_at(end);
+
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, after)| after),
+        None => rest,
+    };
+    let rest = rest.trim().trim_start_matches('(').trim_end_matches(')');
+    let mut clauses: Vec<String> = rest
+        .split(',')
+        .map(|clause| clause.split_whitespace().collect())
+        .filter(|clause: &String| !clause.is_empty())
+        .collect();
+    clauses.sort();
+    (normalized, clauses.join(","))
+}
+
+/// Default lock directory for a runtime: `runtimes/<name>` beside the
+/// config file
+pub fn default_lock_dir(config_path: &Path, runtime_name: &str) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("runtimes")
+        .join(runtime_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npm_package_name() {
+        assert_eq!(npm_package_name("zod@3.22.4"), "zod");
+        assert_eq!(npm_package_name("@scope/pkg@1.0.0"), "@scope/pkg");
+        assert_eq!(npm_package_name("@scope/pkg"), "@scope/pkg");
+        assert_eq!(npm_package_name("lodash"), "lodash");
+    }
+
+    #[test]
+    fn test_missing_node_packages() {
+        let files = LockFiles::for_runtime(&RuntimeType::NodePnpm).unwrap();
+        let manifest = r#"{"dependencies": {"zod": "^3.22.4"}}"#;
+        let missing = files
+            .missing_packages(manifest, &["zod@3.22.4".to_string(), "chalk".to_string()])
+            .unwrap();
+        assert_eq!(missing, vec!["chalk"]);
+    }
+
+    #[test]
+    fn test_missing_python_packages() {
+        let files = LockFiles::for_runtime(&RuntimeType::PythonUv).unwrap();
+        let manifest = "[project]\nname = \"x\"\nversion = \"0\"\ndependencies = [\"httpx==0.27.0\"]\n";
+        let missing = files
+            .missing_packages(manifest, &["httpx==0.27.0".to_string(), "rich==13.7.1".to_string()])
+            .unwrap();
+        assert_eq!(missing, vec!["rich==13.7.1"]);
+
+        let manifest = r#"
+[project]
+name = "x"
+version = "0"
+dependencies = ["HTTPX == 0.27.0", "Rich_Text[extra] >=13, <14 ; python_version >= '3.10'"]
+"#;
+        let missing = files
+            .missing_packages(
+                manifest,
+                &[
+                    "httpx==0.27.0".to_string(),
+                    "rich-text<14,>=13".to_string(),
+                    "httpx".to_string(),
+                    "rich.text".to_string(),
+                    "httpx==0.28.0".to_string(),
+                    "anyio".to_string(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(missing, vec!["httpx==0.28.0", "anyio"]);
+    }
+
+    #[test]
+    fn test_python_requirement() {
+        assert_eq!(python_requirement("Foo__Bar.baz"), ("foo-bar-baz".to_string(), String::new()));
+        assert_eq!(
+            python_requirement("mcp[cli] (>= 1.2, <2); python_version >= '3.10'"),
+            ("mcp".to_string(), "<2,>=1.2".to_string())
+        );
+        assert_eq!(
+            python_requirement("pkg @ https://example.com/pkg-1.0.whl"),
+            ("pkg".to_string(), "@https://example.com/pkg-1.0.whl".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        let lock = dir.path().join("lock");
+        let files = LockFiles::for_runtime(&RuntimeType::NodeNpm).unwrap();
+
+        assert!(!files.restore(&lock, &work).await.unwrap());
+
+        tokio::fs::create_dir_all(&work).await.unwrap();
+        tokio::fs::write(work.join("package.json"), "{}").await.unwrap();
+        tokio::fs::write(work.join("package-lock.json"), "{}").await.unwrap();
+        files.save(&work, &lock).await.unwrap();
+
+        let other = dir.path().join("other");
+        assert!(files.restore(&lock, &other).await.unwrap());
+        assert!(other.join("package-lock.json").exists());
+    }
+
+    #[test]
+    fn test_default_lock_dir() {
+        let dir = default_lock_dir(Path::new("/etc/supermcp/config.toml"), "tools");
+        assert_eq!(dir, PathBuf::from("/etc/supermcp/runtimes/tools"));
+    }
+}
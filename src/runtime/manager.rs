@@ -111,6 +111,10 @@ impl RuntimeManager {
                     config.clone(),
                 ))
             }
+            RuntimeType::PythonUv => Arc::new(crate::runtime::python_uv::PythonUvRuntime::new(
+                config.name.clone(),
+                config.clone(),
+            )),
             RuntimeType::NodePnpm => Arc::new(crate::runtime::node::NodeRuntimeImpl::new(
                 config.name.clone(),
                 config.clone(),
@@ -123,6 +127,10 @@ impl RuntimeManager {
                 config.name.clone(),
                 config.clone(),
             )),
+            RuntimeType::NodeDeno => Arc::new(crate::runtime::deno::DenoRuntime::new(
+                config.name.clone(),
+                config.clone(),
+            )),
         };
 
         self.register(config, runtime);
@@ -277,9 +285,11 @@ impl RuntimeInfo {
     pub fn type_name(&self) -> String {
         match self.runtime_type {
             RuntimeType::PythonWasm => "Python (WASM)".to_string(),
+            RuntimeType::PythonUv => "Python (uv)".to_string(),
             RuntimeType::NodePnpm => "Node.js (pnpm)".to_string(),
             RuntimeType::NodeNpm => "Node.js (npm)".to_string(),
             RuntimeType::NodeBun => "Node.js (bun)".to_string(),
+            RuntimeType::NodeDeno => "Deno".to_string(),
         }
     }
 }
@@ -297,6 +307,7 @@ pub fn create_default_runtime_manager() -> RuntimeManager {
         env: HashMap::new(),
         resource_limits: crate::runtime::types::ResourceLimits::default(),
         enabled: true,
+        lock_dir: None,
     };
 
     let _ = manager.register_auto(python_config);
@@ -315,6 +326,7 @@ pub fn create_default_runtime_manager() -> RuntimeManager {
             env: HashMap::new(),
             resource_limits: crate::runtime::types::ResourceLimits::default(),
             enabled: true,
+            lock_dir: None,
         };
 
         let _ = manager.register_auto(config);
@@ -3,12 +3,19 @@
 //! This module provides support for executing scripts using different runtimes
 //! with maximum sandboxing:
 //! - Python via WASM (Pyodide-like)
+//! - Python in uv-managed virtualenvs
 //! - Node.js via pnpm, npm, or bun
+//! - Deno, with permission flags derived from the sandbox config
 
+pub mod deno;
+pub mod lockfile;
 pub mod manager;
 pub mod node;
+pub mod python_uv;
 pub mod python_wasm;
+pub mod tools;
 pub mod types;
 
 pub use manager::RuntimeManager;
+pub use tools::RuntimeTools;
 pub use types::{RuntimeConfig, ResourceLimits, RuntimeType};
@@ -3,13 +3,15 @@
 //! This module provides JavaScript/TypeScript execution via pnpm, npm, or bun
 //! with sandboxing support including process isolation and resource limits.
 
+use crate::runtime::lockfile::LockFiles;
 use crate::runtime::types::{
     ExecutionResult, ResourceLimits, RuntimeConfig, RuntimeError, RuntimeType,
 };
+use crate::utils::process::output_with_timeout;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use tokio::fs;
 use std::process::Stdio;
@@ -51,6 +53,23 @@ impl NodeRuntime {
         }
     }
 
+    /// Arguments for an install that fails instead of changing the lockfile
+    pub fn frozen_install_args(&self) -> Vec<&'static str> {
+        match self {
+            NodeRuntime::Npm => vec!["ci"],
+            NodeRuntime::Pnpm | NodeRuntime::Bun => vec!["install", "--frozen-lockfile"],
+        }
+    }
+
+    /// Runtime type for this variant
+    pub fn runtime_type(&self) -> RuntimeType {
+        match self {
+            NodeRuntime::Pnpm => RuntimeType::NodePnpm,
+            NodeRuntime::Npm => RuntimeType::NodeNpm,
+            NodeRuntime::Bun => RuntimeType::NodeBun,
+        }
+    }
+
     /// Get package manager specific execution command
     pub fn exec_command(&self, script: &str) -> Vec<String> {
         match self {
@@ -92,6 +111,8 @@ pub struct NodeRuntimeConfig {
     pub resource_limits: ResourceLimits,
     /// Script timeout
     pub timeout: Duration,
+    /// Frozen manifest and lockfile directory
+    pub lock_dir: Option<PathBuf>,
 }
 
 impl NodeRuntimeConfig {
@@ -106,6 +127,7 @@ impl NodeRuntimeConfig {
             env: HashMap::new(),
             resource_limits: ResourceLimits::default(),
             timeout: Duration::from_secs(30),
+            lock_dir: None,
         }
     }
 }
@@ -140,6 +162,7 @@ impl NodeRuntimeImpl {
         node_config.env = runtime_config.env;
         node_config.resource_limits = runtime_config.resource_limits.clone();
         node_config.timeout = Duration::from_secs(node_config.resource_limits.timeout_seconds);
+        node_config.lock_dir = runtime_config.lock_dir.map(PathBuf::from);
 
         Self {
             name: name.clone(),
@@ -207,8 +230,25 @@ impl NodeRuntimeImpl {
         Ok(())
     }
 
-    /// Install required packages
+    /// Install required packages, from the frozen lockfile when there is one
     async fn install_packages(&self) -> Result<(), RuntimeError> {
+        if let Some(lock_dir) = &self.config.lock_dir {
+            let files = self.lock_files();
+            if files.restore(lock_dir, &self.config.working_dir).await? {
+                info!(
+                    "Installing packages for {} runtime from {}",
+                    self.config.runtime.command(),
+                    files.lockfile
+                );
+                return self.run_package_manager(&self.config.runtime.frozen_install_args()).await;
+            }
+        }
+
+        self.add_packages().await
+    }
+
+    /// Resolve and add the configured packages, updating the lockfile
+    async fn add_packages(&self) -> Result<(), RuntimeError> {
         if self.config.packages.is_empty() {
             return Ok(());
         }
@@ -221,12 +261,17 @@ impl NodeRuntimeImpl {
 
         self.init_working_dir().await?;
 
-        let cmd = self.find_executable()?;
         let mut args = vec!["add", "--save"];
         args.extend(self.config.packages.iter().map(|s| s.as_str()));
+        self.run_package_manager(&args).await
+    }
+
+    /// Run the package manager in the working directory
+    async fn run_package_manager(&self, args: &[&str]) -> Result<(), RuntimeError> {
+        let cmd = self.find_executable()?;
 
         let mut cmd = Command::new(&cmd);
-        cmd.args(&args)
+        cmd.args(args)
             .current_dir(&self.config.working_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -257,6 +302,11 @@ impl NodeRuntimeImpl {
         Ok(())
     }
 
+    fn lock_files(&self) -> LockFiles {
+        LockFiles::for_runtime(&self.config.runtime.runtime_type())
+            .expect("node runtimes support lockfiles")
+    }
+
     /// Execute a JavaScript/TypeScript script
     async fn execute_script(
         &self,
@@ -303,7 +353,6 @@ impl NodeRuntimeImpl {
         // Apply resource limits
         #[cfg(target_os = "linux")]
         {
-            use std::os::unix::process::CommandExt;
             if self.config.resource_limits.max_memory_mb > 0 {
                 // Memory limits are typically applied via cgroups
                 // This is a best-effort attempt
@@ -311,7 +360,7 @@ impl NodeRuntimeImpl {
         }
 
         // Spawn the process
-        let mut child = match cmd.spawn() {
+        let child = match cmd.spawn() {
             Ok(child) => child,
             Err(e) => {
                 return Err(RuntimeError::ExecutionError(format!(
@@ -322,15 +371,13 @@ impl NodeRuntimeImpl {
             }
         };
 
-        // Wait for completion with timeout
-        let _result = tokio::time::timeout(self.config.timeout, child.wait()).await;
+        // Wait for completion, killing the process at the timeout
+        let output = output_with_timeout(child, None, self.config.timeout)
+            .await?
+            .ok_or(RuntimeError::Timeout(self.config.timeout.as_secs()))?;
 
         let execution_time_ms = start_time.elapsed().as_millis() as u64;
 
-        let output = child.wait_with_output().await.map_err(|e| {
-            RuntimeError::Io(std::io::Error::other(e.to_string()))
-        })?;
-
         // Parse stdout as JSON if possible
         let output_value: Option<Value> = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))
             .ok()
@@ -354,11 +401,7 @@ impl crate::runtime::types::Runtime for NodeRuntimeImpl {
     }
 
     fn runtime_type(&self) -> RuntimeType {
-        match self.config.runtime {
-            NodeRuntime::Pnpm => RuntimeType::NodePnpm,
-            NodeRuntime::Npm => RuntimeType::NodeNpm,
-            NodeRuntime::Bun => RuntimeType::NodeBun,
-        }
+        self.config.runtime.runtime_type()
     }
 
     async fn validate(&self) -> Result<(), RuntimeError> {
@@ -394,6 +437,40 @@ impl crate::runtime::types::Runtime for NodeRuntimeImpl {
     async fn install_packages(&self) -> Result<(), RuntimeError> {
         self.install_packages().await
     }
+
+    async fn freeze(&self, lock_dir: &Path) -> Result<(), RuntimeError> {
+        // Start from a clean manifest so the lock reflects only `packages`
+        let files = self.lock_files();
+        for name in [files.manifest, files.lockfile] {
+            let _ = fs::remove_file(self.config.working_dir.join(name)).await;
+        }
+
+        self.init_working_dir().await?;
+        self.add_packages().await?;
+        files.save(&self.config.working_dir, lock_dir).await
+    }
+
+    async fn verify(&self, lock_dir: &Path) -> Result<(), RuntimeError> {
+        let files = self.lock_files();
+        if !files.restore(lock_dir, &self.config.working_dir).await? {
+            return Err(RuntimeError::ValidationError(format!(
+                "no {} in {}",
+                files.lockfile,
+                lock_dir.display()
+            )));
+        }
+
+        let manifest = fs::read_to_string(lock_dir.join(files.manifest)).await?;
+        let missing = files.missing_packages(&manifest, &self.config.packages)?;
+        if !missing.is_empty() {
+            return Err(RuntimeError::ValidationError(format!(
+                "lockfile is missing packages: {}",
+                missing.join(", ")
+            )));
+        }
+
+        self.run_package_manager(&self.config.runtime.frozen_install_args()).await
+    }
 }
 
 /// Check if a Node.js runtime is available
@@ -0,0 +1,445 @@
+//! Python runtime backed by uv
+//!
+//! Each runtime config gets its own uv-managed virtualenv with its pinned
+//! packages installed. Scripts and servers run from that virtualenv under
+//! the platform sandbox, with network access off unless the runtime's
+//! resource limits allow it. A frozen runtime is installed from its
+//! `uv.lock` with `uv sync` instead.
+
+use crate::config::McpServerConfig;
+use crate::runtime::lockfile::LockFiles;
+use crate::runtime::types::{
+    ExecutionResult, ResourceLimits, RuntimeConfig, RuntimeError, RuntimeType,
+};
+use crate::utils::process::output_with_timeout;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Marker recording which packages the virtualenv was built with
+const PACKAGES_MARKER: &str = ".supermcp-packages";
+
+const LOCK_FILES: LockFiles = LockFiles {
+    manifest: "pyproject.toml",
+    lockfile: "uv.lock",
+};
+
+/// uv-backed Python runtime
+#[derive(Debug)]
+pub struct PythonUvRuntime {
+    name: String,
+    project_dir: PathBuf,
+    venv_dir: PathBuf,
+    lock_dir: Option<PathBuf>,
+    packages: Vec<String>,
+    env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+}
+
+impl PythonUvRuntime {
+    /// Create a new uv runtime from configuration
+    pub fn new(name: String, config: RuntimeConfig) -> Self {
+        let project_dir = config
+            .working_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::cache_dir()
+                    .unwrap_or_else(|| PathBuf::from("/tmp"))
+                    .join(format!("super-mcp/uv-{}", name))
+            });
+
+        Self {
+            name,
+            venv_dir: project_dir.join(".venv"),
+            project_dir,
+            lock_dir: config.lock_dir.map(PathBuf::from),
+            packages: config.packages,
+            env: config.env,
+            resource_limits: config.resource_limits,
+        }
+    }
+
+    /// Find the uv executable
+    fn find_uv(&self) -> Result<PathBuf, RuntimeError> {
+        if let Ok(cmd) = std::env::var("UV_COMMAND") {
+            if let Ok(path) = which::which(&cmd) {
+                return Ok(path);
+            }
+        }
+
+        which::which("uv")
+            .map_err(|_| RuntimeError::RuntimeNotFound("uv not found in PATH".to_string()))
+    }
+
+    /// Python interpreter inside the virtualenv
+    pub fn venv_python(&self) -> PathBuf {
+        venv_python(&self.venv_dir)
+    }
+
+    /// Create the virtualenv if it doesn't exist yet
+    async fn ensure_venv(&self) -> Result<(), RuntimeError> {
+        if self.venv_python().exists() {
+            return Ok(());
+        }
+
+        info!("Creating uv virtualenv for runtime {} at {:?}", self.name, self.venv_dir);
+        let uv = self.find_uv()?;
+        self.run_uv(&uv, &["venv".as_ref(), "--quiet".as_ref(), self.venv_dir.as_os_str()])
+            .await
+            .map_err(|e| RuntimeError::InstallError(format!("uv venv failed: {}", e)))
+    }
+
+    /// Run a uv subcommand, returning stderr on failure
+    async fn run_uv(&self, uv: &Path, args: &[&std::ffi::OsStr]) -> Result<(), String> {
+        let mut cmd = Command::new(uv);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .env("NO_COLOR", "1");
+
+        debug!("Running: {:?}", cmd);
+
+        let output = cmd.output().await.map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+
+    /// Create the virtualenv and install the pinned packages into it
+    async fn install(&self) -> Result<(), RuntimeError> {
+        if let Some(lock_dir) = &self.lock_dir {
+            if LOCK_FILES.restore(lock_dir, &self.project_dir).await? {
+                return self.sync_locked(false).await;
+            }
+        }
+
+        if let Some(unpinned) = self.packages.iter().find(|p| !is_pinned(p)) {
+            return Err(RuntimeError::ValidationError(format!(
+                "Package '{}' must be pinned to an exact version (e.g. 'name==1.2.3')",
+                unpinned
+            )));
+        }
+
+        self.ensure_venv().await?;
+
+        let marker = self.venv_dir.join(PACKAGES_MARKER);
+        let wanted = self.packages.join("\n");
+        if tokio::fs::read_to_string(&marker).await.ok().as_deref() == Some(wanted.as_str()) {
+            debug!("Packages for runtime {} are up to date", self.name);
+            return Ok(());
+        }
+
+        if !self.packages.is_empty() {
+            info!("Installing packages for uv runtime {}: {:?}", self.name, self.packages);
+
+            let uv = self.find_uv()?;
+            let python = self.venv_python();
+            let mut args: Vec<&std::ffi::OsStr> =
+                vec!["pip".as_ref(), "install".as_ref(), "--python".as_ref(), python.as_os_str()];
+            args.extend(self.packages.iter().map(|p| std::ffi::OsStr::new(p.as_str())));
+
+            self.run_uv(&uv, &args)
+                .await
+                .map_err(RuntimeError::InstallError)?;
+        }
+
+        tokio::fs::write(&marker, wanted).await?;
+        info!("Packages installed successfully");
+        Ok(())
+    }
+
+    /// Install exactly what `uv.lock` in the project directory pins
+    ///
+    /// With `check`, uv also fails if the lock is out of date with
+    /// `pyproject.toml` rather than trusting it as-is.
+    async fn sync_locked(&self, check: bool) -> Result<(), RuntimeError> {
+        let lock = tokio::fs::read_to_string(self.project_dir.join(LOCK_FILES.lockfile)).await?;
+        let marker = self.venv_dir.join(PACKAGES_MARKER);
+        if !check && tokio::fs::read_to_string(&marker).await.ok() == Some(lock.clone()) {
+            debug!("Packages for runtime {} are up to date", self.name);
+            return Ok(());
+        }
+
+        info!("Installing packages for uv runtime {} from uv.lock", self.name);
+        let uv = self.find_uv()?;
+        let mode = if check { "--locked" } else { "--frozen" };
+        self.run_uv(
+            &uv,
+            &[
+                "sync".as_ref(),
+                mode.as_ref(),
+                "--no-install-project".as_ref(),
+                "--project".as_ref(),
+                self.project_dir.as_os_str(),
+            ],
+        )
+        .await
+        .map_err(RuntimeError::InstallError)?;
+
+        tokio::fs::write(&marker, lock).await?;
+        Ok(())
+    }
+
+    /// `pyproject.toml` declaring the configured packages
+    fn pyproject(&self) -> Result<String, RuntimeError> {
+        let project_name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+            .collect();
+
+        let mut project = toml::Table::new();
+        project.insert("name".into(), format!("supermcp-runtime-{}", project_name).into());
+        project.insert("version".into(), "0.0.0".into());
+        project.insert(
+            "dependencies".into(),
+            toml::Value::Array(self.packages.iter().cloned().map(toml::Value::String).collect()),
+        );
+
+        let mut uv = toml::Table::new();
+        uv.insert("package".into(), false.into());
+        let mut tool = toml::Table::new();
+        tool.insert("uv".into(), uv.into());
+
+        let mut doc = toml::Table::new();
+        doc.insert("project".into(), project.into());
+        doc.insert("tool".into(), tool.into());
+
+        toml::to_string(&doc).map_err(|e| RuntimeError::ValidationError(e.to_string()))
+    }
+
+    /// Server config that runs `args` with the virtualenv's Python under
+    /// this runtime's sandbox, e.g. `["-m", "my_mcp_server"]`
+    pub fn server_config(&self, server_name: &str, args: Vec<String>) -> McpServerConfig {
+        let mut env = self.env.clone();
+        env.insert(
+            "VIRTUAL_ENV".to_string(),
+            self.venv_dir.to_string_lossy().to_string(),
+        );
+        env.insert("PYTHONDONTWRITEBYTECODE".to_string(), "1".to_string());
+
+        McpServerConfig {
+            name: server_name.to_string(),
+            command: self.venv_python().to_string_lossy().to_string(),
+            args,
+            env,
+            sandbox: self.resource_limits.to_sandbox_config(&self.project_dir),
+            ..Default::default()
+        }
+    }
+
+    /// Run a script file from the virtualenv under the sandbox
+    async fn run_script(&self, script_path: &Path) -> Result<ExecutionResult, RuntimeError> {
+        let start_time = Instant::now();
+
+        let config = self.server_config(
+            &self.name,
+            vec![script_path.to_string_lossy().to_string()],
+        );
+        let sandbox = crate::sandbox::create_sandbox(&config);
+
+        debug!("Executing Python script at {:?} with uv runtime {}", script_path, self.name);
+
+        let child = sandbox
+            .spawn(&config)
+            .await
+            .map_err(|e| RuntimeError::ExecutionError(format!("Failed to spawn Python process: {}", e)))?;
+
+        let timeout = Duration::from_secs(self.resource_limits.timeout_seconds);
+        let output = output_with_timeout(child, None, timeout)
+            .await?
+            .ok_or(RuntimeError::Timeout(self.resource_limits.timeout_seconds))?;
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+
+        Ok(ExecutionResult {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            exit_code: output.status.code().unwrap_or(-1),
+            execution_time_ms,
+            output_value: None,
+        })
+    }
+}
+
+/// Whether a requirement specifier pins an exact version or artifact
+pub fn is_pinned(spec: &str) -> bool {
+    let spec = spec.split(';').next().unwrap_or(spec);
+    spec.contains("==") || spec.contains(" @ ")
+}
+
+/// Python interpreter path inside a virtualenv
+fn venv_python(venv_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv_dir.join("Scripts").join("python.exe")
+    } else {
+        venv_dir.join("bin").join("python")
+    }
+}
+
+#[async_trait]
+impl crate::runtime::types::Runtime for PythonUvRuntime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn runtime_type(&self) -> RuntimeType {
+        RuntimeType::PythonUv
+    }
+
+    async fn validate(&self) -> Result<(), RuntimeError> {
+        self.find_uv().map(|_| ())
+    }
+
+    fn resource_limits(&self) -> &ResourceLimits {
+        &self.resource_limits
+    }
+
+    async fn execute(
+        &self,
+        script: &str,
+        _input: Option<Value>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        self.install().await?;
+
+        let script_path = self
+            .project_dir
+            .join(format!("script_{}.py", uuid::Uuid::new_v4()));
+        tokio::fs::write(&script_path, script).await?;
+
+        let result = self.run_script(&script_path).await;
+        let _ = tokio::fs::remove_file(&script_path).await;
+        result
+    }
+
+    async fn execute_file(
+        &self,
+        path: &Path,
+        _input: Option<Value>,
+    ) -> Result<ExecutionResult, RuntimeError> {
+        self.install().await?;
+        self.run_script(path).await
+    }
+
+    async fn install_packages(&self) -> Result<(), RuntimeError> {
+        self.install().await
+    }
+
+    async fn freeze(&self, lock_dir: &Path) -> Result<(), RuntimeError> {
+        tokio::fs::create_dir_all(&self.project_dir).await?;
+        tokio::fs::write(self.project_dir.join(LOCK_FILES.manifest), self.pyproject()?).await?;
+
+        info!("Locking packages for uv runtime {}", self.name);
+        let uv = self.find_uv()?;
+        self.run_uv(&uv, &["lock".as_ref(), "--project".as_ref(), self.project_dir.as_os_str()])
+            .await
+            .map_err(RuntimeError::InstallError)?;
+
+        LOCK_FILES.save(&self.project_dir, lock_dir).await
+    }
+
+    async fn verify(&self, lock_dir: &Path) -> Result<(), RuntimeError> {
+        if !LOCK_FILES.restore(lock_dir, &self.project_dir).await? {
+            return Err(RuntimeError::ValidationError(format!(
+                "no {} in {}",
+                LOCK_FILES.lockfile,
+                lock_dir.display()
+            )));
+        }
+
+        let manifest = tokio::fs::read_to_string(lock_dir.join(LOCK_FILES.manifest)).await?;
+        let missing = LOCK_FILES.missing_packages(&manifest, &self.packages)?;
+        if !missing.is_empty() {
+            return Err(RuntimeError::ValidationError(format!(
+                "lockfile is missing packages: {}",
+                missing.join(", ")
+            )));
+        }
+
+        self.sync_locked(true).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilesystemAccess;
+    use crate::runtime::types::RuntimeFilesystemAccess;
+
+    fn runtime(limits: ResourceLimits) -> PythonUvRuntime {
+        PythonUvRuntime::new(
+            "tools".to_string(),
+            RuntimeConfig {
+                name: "tools".to_string(),
+                type_: RuntimeType::PythonUv,
+                working_dir: Some("/tmp/uv-tools".to_string()),
+                resource_limits: limits,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_is_pinned() {
+        assert!(is_pinned("httpx==0.27.0"));
+        assert!(is_pinned("mcp[cli]==1.2.0; python_version >= '3.10'"));
+        assert!(is_pinned("pkg @ https://example.com/pkg-1.0.whl"));
+        assert!(!is_pinned("httpx"));
+        assert!(!is_pinned("httpx>=0.27"));
+        assert!(!is_pinned("httpx; python_version == '3.11'"));
+    }
+
+    #[test]
+    fn test_server_config_runs_from_venv_without_network() {
+        let runtime = runtime(ResourceLimits::default());
+        let config = runtime.server_config("srv", vec!["-m".to_string(), "srv".to_string()]);
+
+        assert_eq!(PathBuf::from(&config.command), venv_python(Path::new("/tmp/uv-tools/.venv")));
+        assert_eq!(
+            config.env.get("VIRTUAL_ENV").map(PathBuf::from),
+            Some(PathBuf::from("/tmp/uv-tools/.venv"))
+        );
+        assert!(config.sandbox.enabled);
+        assert!(!config.sandbox.network);
+    }
+
+    #[test]
+    fn test_network_follows_resource_limits() {
+        let runtime = runtime(ResourceLimits {
+            network_access: true,
+            filesystem: RuntimeFilesystemAccess::None,
+            ..Default::default()
+        });
+        let sandbox = runtime.server_config("srv", vec![]).sandbox;
+
+        assert!(sandbox.network);
+        assert!(matches!(sandbox.filesystem, FilesystemAccess::Paths(ref p) if p == &["/tmp/uv-tools"]));
+    }
+
+    #[test]
+    fn test_pyproject_declares_packages() {
+        let mut runtime = runtime(ResourceLimits::default());
+        runtime.packages = vec!["httpx==0.27.0".to_string()];
+
+        let pyproject = runtime.pyproject().unwrap();
+        let missing = LOCK_FILES
+            .missing_packages(&pyproject, &runtime.packages)
+            .unwrap();
+        assert!(missing.is_empty());
+        assert!(pyproject.contains("supermcp-runtime-tools"));
+    }
+
+    #[test]
+    fn test_runtime_type_serialization() {
+        let value = serde_json::to_value(RuntimeType::PythonUv).unwrap();
+        assert_eq!(value, "python_uv");
+    }
+}
@@ -7,6 +7,7 @@
 use crate::runtime::types::{
     ExecutionResult, ResourceLimits, RuntimeConfig, RuntimeError, RuntimeType,
 };
+use crate::utils::process::output_with_timeout;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -147,15 +148,26 @@ impl PythonWasmRuntime {
         #[cfg(target_os = "linux")]
         {
             if self.config.resource_limits.max_memory_mb > 0 {
-                use std::os::unix::process::CommandExt;
-                // Note: This requires the process to have CAP_SYS_RESOURCE or be root
-                // In practice, memory limits are better applied via cgroups
-                cmd.memory_max(self.config.resource_limits.max_memory_mb * 1024 * 1024);
+                // Caps address space rather than resident memory; cgroups
+                // would be exact but need delegation
+                let limit = libc::rlimit {
+                    rlim_cur: (self.config.resource_limits.max_memory_mb * 1024 * 1024) as libc::rlim_t,
+                    rlim_max: (self.config.resource_limits.max_memory_mb * 1024 * 1024) as libc::rlim_t,
+                };
+                // SAFETY: the hook only calls setrlimit
+                unsafe {
+                    cmd.pre_exec(move || {
+                        if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
             }
         }
 
         // Spawn the process
-        let mut child = match cmd.spawn() {
+        let child = match cmd.spawn() {
             Ok(child) => child,
             Err(e) => {
                 return Err(RuntimeError::ExecutionError(format!(
@@ -165,19 +177,16 @@ impl PythonWasmRuntime {
             }
         };
 
-        // Wait for completion with timeout
+        // Wait for completion, killing the process at the timeout
         let timeout = Duration::from_secs(self.config.resource_limits.timeout_seconds);
-        let _result = tokio::time::timeout(timeout, child.wait()).await;
-
-        let execution_time_ms = start_time.elapsed().as_millis() as u64;
-
-        let output = child.wait_with_output().await.map_err(|e| {
-            RuntimeError::Io(std::io::Error::other(e.to_string()))
-        })?;
+        let output = output_with_timeout(child, None, timeout).await;
 
         // Clean up temp file
         let _ = tokio::fs::remove_file(&script_path).await;
 
+        let output = output?.ok_or(RuntimeError::Timeout(self.config.resource_limits.timeout_seconds))?;
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+
         Ok(ExecutionResult {
             success: output.status.success(),
             stdout: String::from_utf8_lossy(&output.stdout).to_string(),
@@ -0,0 +1,332 @@
+//! Runtimes exposed as MCP tools
+//!
+//! When `runtime_tools.enabled` is set, each selected runtime appears in
+//! the proxy's tool list as `runtime_exec_<name>`. Calls run the script
+//! under that runtime's sandbox and resource limits, and every execution
+//! is written to the audit log.
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::config::Config;
+use crate::core::dry_run;
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::runtime::manager::RuntimeManager;
+use crate::runtime::types::{ExecutionResult, RuntimeError};
+use crate::utils::errors::{McpError, McpResult};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Prefix of runtime tool names
+pub const TOOL_PREFIX: &str = "runtime_exec_";
+
+/// Extra time allowed past a runtime's own timeout before giving up
+const TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+
+/// Longest script excerpt kept in audit events
+const AUDIT_SCRIPT_CHARS: usize = 1024;
+
+/// Tool provider backed by the configured runtimes
+pub struct RuntimeTools {
+    manager: RuntimeManager,
+    max_script_bytes: usize,
+}
+
+impl RuntimeTools {
+    /// Build the provider, or `None` unless runtime tools are enabled
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        let tools = &config.runtime_tools;
+        if !tools.enabled {
+            return None;
+        }
+
+        let manager = RuntimeManager::new();
+        for runtime in &config.runtimes {
+            if !runtime.enabled {
+                continue;
+            }
+            if !tools.runtimes.is_empty() && !tools.runtimes.contains(&runtime.name) {
+                continue;
+            }
+            if let Err(e) = manager.register_auto(runtime.clone()) {
+                warn!("Failed to expose runtime {} as a tool: {}", runtime.name, e);
+            }
+        }
+
+        for name in &tools.runtimes {
+            if !manager.contains(name) {
+                warn!("runtime_tools lists unknown or disabled runtime '{}'", name);
+            }
+        }
+
+        info!("Exposing {} runtime(s) as MCP tools", manager.len());
+        Some(Arc::new(Self {
+            manager,
+            max_script_bytes: tools.max_script_bytes,
+        }))
+    }
+
+    /// MCP tool definitions, one per exposed runtime
+    pub fn tools(&self) -> Vec<Value> {
+        let mut runtimes = self.manager.all();
+        runtimes.sort_by(|a, b| a.name().cmp(b.name()));
+
+        runtimes
+            .iter()
+            .filter_map(|runtime| {
+                let info = self.manager.info(runtime.name())?;
+                let limits = &info.resource_limits;
+                Some(json!({
+                    "name": format!("{}{}", TOOL_PREFIX, info.name),
+                    "description": format!(
+                        "Run a {} script in the sandboxed '{}' runtime (timeout {}s, {} MB memory, network {})",
+                        info.type_name(),
+                        info.name,
+                        limits.timeout_seconds,
+                        limits.max_memory_mb,
+                        if limits.network_access { "allowed" } else { "blocked" },
+                    ),
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "script": {
+                                "type": "string",
+                                "description": "Source code to execute"
+                            },
+                            "input": {
+                                "description": "Optional input passed to the script"
+                            }
+                        },
+                        "required": ["script"]
+                    }
+                }))
+            })
+            .collect()
+    }
+
+    /// Whether a tool name belongs to an exposed runtime
+    pub fn handles(&self, tool_name: &str) -> bool {
+        tool_name
+            .strip_prefix(TOOL_PREFIX)
+            .is_some_and(|name| self.manager.contains(name))
+    }
+
+    /// Answer a `tools/call` for a runtime tool, if it is one
+    pub async fn handle_request(&self, request: &JsonRpcRequest) -> McpResult<Option<JsonRpcResponse>> {
+        if request.method != "tools/call" {
+            return Ok(None);
+        }
+
+        let params = request.params.as_ref();
+        let Some(tool_name) = params.and_then(|p| p.get("name")).and_then(|n| n.as_str()) else {
+            return Ok(None);
+        };
+        if !self.handles(tool_name) {
+            return Ok(None);
+        }
+
+        let id = request
+            .id
+            .clone()
+            .ok_or_else(|| McpError::InvalidRequest("tools/call requires an id".to_string()))?;
+        let result = self
+            .call(tool_name, params.and_then(|p| p.get("arguments")))
+            .await?;
+        Ok(Some(JsonRpcResponse::success(id, result)))
+    }
+
+    /// Append runtime tools to an upstream `tools/list` response
+    pub fn extend_tool_list(&self, response: &mut JsonRpcResponse) {
+        let Some(result) = response.result.as_mut() else {
+            return;
+        };
+        if let Some(tools) = result.get_mut("tools").and_then(|t| t.as_array_mut()) {
+            tools.extend(self.tools());
+        }
+    }
+
+    /// Run a runtime tool, returning an MCP `tools/call` result
+    ///
+    /// Script failures and timeouts are reported as tool errors
+    /// (`isError`), not protocol errors.
+    pub async fn call(&self, tool_name: &str, arguments: Option<&Value>) -> McpResult<Value> {
+        let runtime_name = tool_name
+            .strip_prefix(TOOL_PREFIX)
+            .filter(|name| self.manager.contains(name))
+            .ok_or_else(|| McpError::ToolExecutionError(format!("Unknown tool: {}", tool_name)))?;
+        if dry_run::is_dry_run() {
+            let schema = self.tools().into_iter().find(|tool| tool["name"] == tool_name);
+            let arguments = arguments.cloned().unwrap_or_else(|| json!({}));
+            let report = dry_run::report(
+                "runtime",
+                tool_name,
+                &arguments,
+                schema.as_ref().and_then(|tool| tool.get("inputSchema")),
+            );
+            return Ok(dry_run::result(report));
+        }
+
+        let script = arguments
+            .and_then(|a| a.get("script"))
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| McpError::InvalidRequest("Missing required argument: script".to_string()))?;
+        if script.len() > self.max_script_bytes {
+            return Err(McpError::InvalidRequest(format!(
+                "Script is {} bytes, limit is {}",
+                script.len(),
+                self.max_script_bytes
+            )));
+        }
+        let input = arguments.and_then(|a| a.get("input")).cloned();
+
+        let timeout_seconds = self
+            .manager
+            .info(runtime_name)
+            .map(|info| info.resource_limits.timeout_seconds)
+            .unwrap_or(30);
+        let deadline = Duration::from_secs(timeout_seconds) + TIMEOUT_GRACE;
+
+        let result = match tokio::time::timeout(
+            deadline,
+            self.manager.execute(runtime_name, script, input),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(RuntimeError::Timeout(timeout_seconds)),
+        };
+
+        audit_execution(runtime_name, script, &result);
+
+        Ok(match result {
+            Ok(execution) => tool_result(&execution),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+}
+
+/// Format an execution as an MCP tool result
+fn tool_result(execution: &ExecutionResult) -> Value {
+    let mut text = execution.stdout.clone();
+    if !execution.stderr.is_empty() {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str("--- stderr ---\n");
+        text.push_str(&execution.stderr);
+    }
+
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": !execution.success,
+        "structuredContent": {
+            "exitCode": execution.exit_code,
+            "stdout": execution.stdout,
+            "stderr": execution.stderr,
+            "executionTimeMs": execution.execution_time_ms,
+            "output": execution.output_value,
+        }
+    })
+}
+
+fn audit_execution(runtime: &str, script: &str, result: &Result<ExecutionResult, RuntimeError>) {
+    let excerpt: String = script.chars().take(AUDIT_SCRIPT_CHARS).collect();
+    let mut details = json!({
+        "runtime": runtime,
+        "script_bytes": script.len(),
+        "script": excerpt,
+    });
+
+    let event = match result {
+        Ok(execution) => {
+            details["exit_code"] = json!(execution.exit_code);
+            details["execution_time_ms"] = json!(execution.execution_time_ms);
+            let event = AuditEvent::new(AuditEventType::RuntimeExec);
+            if execution.success {
+                event
+            } else {
+                event.with_error(format!("exited with code {}", execution.exit_code))
+            }
+        }
+        Err(e) => AuditEvent::new(AuditEventType::RuntimeExec).with_error(e.to_string()),
+    };
+
+    audit::record(event.with_server_name(runtime).with_details(details));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RuntimeToolsConfig;
+    use crate::runtime::types::{RuntimeConfig, RuntimeType};
+
+    fn config(enabled: bool, runtimes: Vec<String>) -> Config {
+        Config {
+            runtimes: vec![
+                RuntimeConfig {
+                    name: "py".to_string(),
+                    type_: RuntimeType::PythonUv,
+                    ..Default::default()
+                },
+                RuntimeConfig {
+                    name: "js".to_string(),
+                    type_: RuntimeType::NodeNpm,
+                    ..Default::default()
+                },
+                RuntimeConfig {
+                    name: "off".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                },
+            ],
+            runtime_tools: RuntimeToolsConfig {
+                enabled,
+                runtimes,
+                max_script_bytes: 16,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        assert!(RuntimeTools::from_config(&Config::default()).is_none());
+        assert!(RuntimeTools::from_config(&config(false, vec![])).is_none());
+    }
+
+    #[test]
+    fn test_tool_list_respects_allowlist() {
+        let tools = RuntimeTools::from_config(&config(true, vec![])).unwrap();
+        let names: Vec<String> = tools
+            .tools()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["runtime_exec_js", "runtime_exec_py"]);
+        assert!(!tools.handles("runtime_exec_off"));
+
+        let tools = RuntimeTools::from_config(&config(true, vec!["py".to_string()])).unwrap();
+        assert!(tools.handles("runtime_exec_py"));
+        assert!(!tools.handles("runtime_exec_js"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_script() {
+        let tools = RuntimeTools::from_config(&config(true, vec![])).unwrap();
+        let err = tools
+            .call("runtime_exec_py", Some(&json!({ "script": "x".repeat(17) })))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("limit"));
+    }
+
+    #[tokio::test]
+    async fn test_ignores_other_tools() {
+        let tools = RuntimeTools::from_config(&config(true, vec![])).unwrap();
+        let request = JsonRpcRequest::new("tools/call", Some(json!({ "name": "read_file" })));
+        assert!(tools.handle_request(&request).await.unwrap().is_none());
+    }
+}
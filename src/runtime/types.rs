@@ -13,16 +13,22 @@ use std::collections::HashMap;
 pub enum RuntimeType {
     /// Python via WASM (Pyodide-like)
     PythonWasm,
+    /// Python in a uv-managed virtualenv
+    PythonUv,
     /// Node.js via pnpm
     NodePnpm,
     /// Node.js via npm
     NodeNpm,
     /// Node.js via bun
     NodeBun,
+    /// TypeScript/JavaScript via Deno, with permission flags derived from
+    /// the sandbox settings
+    #[serde(alias = "deno")]
+    NodeDeno,
 }
 
 /// Resource limits for runtime execution
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct ResourceLimits {
     /// Maximum memory in megabytes
@@ -49,6 +55,33 @@ impl Default for ResourceLimits {
     }
 }
 
+impl ResourceLimits {
+    /// Sandbox settings enforcing these limits; `work_dir` stays accessible
+    /// when filesystem access is restricted to paths
+    pub fn to_sandbox_config(&self, work_dir: &std::path::Path) -> crate::config::SandboxConfig {
+        use crate::config::FilesystemAccess as SandboxFilesystem;
+
+        let work_dir = work_dir.to_string_lossy().to_string();
+        let filesystem = match &self.filesystem {
+            RuntimeFilesystemAccess::None => SandboxFilesystem::Paths(vec![work_dir]),
+            RuntimeFilesystemAccess::ReadOnly => SandboxFilesystem::Simple("readonly".to_string()),
+            RuntimeFilesystemAccess::ReadWrite => SandboxFilesystem::Simple("full".to_string()),
+            RuntimeFilesystemAccess::Paths(paths) => {
+                SandboxFilesystem::Paths(paths.iter().cloned().chain([work_dir]).collect())
+            }
+        };
+
+        crate::config::SandboxConfig {
+            enabled: true,
+            network: self.network_access,
+            filesystem,
+            max_memory_mb: self.max_memory_mb,
+            max_cpu_percent: self.max_cpu_percent,
+            ..Default::default()
+        }
+    }
+}
+
 /// File system access levels for runtime execution
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -74,7 +107,7 @@ pub struct RuntimeConfig {
     pub name: String,
     /// Runtime type
     pub type_: RuntimeType,
-    /// Packages to install (for Node.js)
+    /// Packages to install (Node.js, or pinned requirements for uv)
     pub packages: Vec<String>,
     /// Working directory for script execution
     pub working_dir: Option<String>,
@@ -84,6 +117,9 @@ pub struct RuntimeConfig {
     pub resource_limits: ResourceLimits,
     /// Whether this runtime is enabled
     pub enabled: bool,
+    /// Directory holding the frozen manifest and lockfile; when it contains
+    /// a lockfile, installs reproduce it exactly
+    pub lock_dir: Option<String>,
 }
 
 impl Default for RuntimeConfig {
@@ -96,6 +132,7 @@ impl Default for RuntimeConfig {
             env: HashMap::new(),
             resource_limits: ResourceLimits::default(),
             enabled: true,
+            lock_dir: None,
         }
     }
 }
@@ -148,6 +185,25 @@ pub trait Runtime: Send + Sync {
 
     /// Install required packages for this runtime
     async fn install_packages(&self) -> Result<(), RuntimeError>;
+
+    /// Resolve packages and write the manifest and lockfile to `lock_dir`
+    async fn freeze(&self, lock_dir: &std::path::Path) -> Result<(), RuntimeError> {
+        let _ = lock_dir;
+        Err(RuntimeError::ValidationError(format!(
+            "runtime '{}' does not support lockfiles",
+            self.name()
+        )))
+    }
+
+    /// Check that the lockfile in `lock_dir` matches the configured
+    /// packages and installs cleanly
+    async fn verify(&self, lock_dir: &std::path::Path) -> Result<(), RuntimeError> {
+        let _ = lock_dir;
+        Err(RuntimeError::ValidationError(format!(
+            "runtime '{}' does not support lockfiles",
+            self.name()
+        )))
+    }
 }
 
 /// Runtime execution error
@@ -0,0 +1,724 @@
+//! File access brokered by the proxy
+//!
+//! A server with `[servers.sandbox.broker]` enabled keeps a sandbox without
+//! broad filesystem access; instead the proxy lists `fs_read_<server>` and
+//! `fs_write_<server>` tools that read and write files on its behalf. Each
+//! call is opened beneath one of the server's read or write paths, so
+//! neither `..` nor a symlink can lead outside them, and written to the
+//! audit log and, when configured, the server's journal file. That only
+//! holds while the server cannot reach those files itself, so its sandbox
+//! must grant no filesystem access beyond the broker's write paths (see
+//! [`confines`]).
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::config::{Config, FileBrokerConfig, FilesystemAccess, SandboxConfig, SandboxType};
+use crate::core::dry_run;
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::utils::errors::{McpError, McpResult};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+
+/// Prefix of broker read tool names
+pub const READ_PREFIX: &str = "fs_read_";
+
+/// Prefix of broker write tool names
+pub const WRITE_PREFIX: &str = "fs_write_";
+
+/// Brokered file access of one server
+struct Policy {
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+    max_bytes: usize,
+    journal: Option<PathBuf>,
+}
+
+/// Whether a call reads or writes
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn tool(self) -> &'static str {
+        match self {
+            Access::Read => "fs_read",
+            Access::Write => "fs_write",
+        }
+    }
+}
+
+/// Whether a sandbox keeps its server from going around the broker
+///
+/// The sandbox must be on and grant either no filesystem access
+/// (`filesystem = []`) or only paths beneath the broker's write paths,
+/// which the server could write through the broker anyway.
+pub fn confines(sandbox: &SandboxConfig) -> bool {
+    if !sandbox.enabled || matches!(sandbox.sandbox_type, SandboxType::None) {
+        return false;
+    }
+    let FilesystemAccess::Paths(paths) = &sandbox.filesystem else {
+        return false;
+    };
+    let expand = |path: &str| PathBuf::from(shellexpand::tilde(path).to_string());
+    let roots: Vec<PathBuf> = sandbox.broker.write.iter().map(|root| expand(root)).collect();
+    paths.iter().map(|path| expand(path)).all(|path| {
+        !path.components().any(|part| part == std::path::Component::ParentDir)
+            && roots.iter().any(|root| path.starts_with(root))
+    })
+}
+
+/// Tool provider reading and writing files for brokered servers
+pub struct FileBroker {
+    policies: BTreeMap<String, Policy>,
+    /// Serializes journal appends
+    journal: tokio::sync::Mutex<()>,
+}
+
+impl FileBroker {
+    /// Build the broker, or `None` unless some server has it enabled
+    ///
+    /// Servers whose sandbox doesn't [confine](confines) them get no broker.
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        let policies: BTreeMap<String, Policy> = config
+            .servers
+            .iter()
+            .filter(|server| server.enabled && server.sandbox.broker.enabled)
+            .filter(|server| {
+                let confined = confines(&server.sandbox);
+                if !confined {
+                    warn!(
+                        "Not brokering files for {}: its sandbox allows filesystem access around the broker",
+                        server.name
+                    );
+                }
+                confined
+            })
+            .map(|server| (server.name.clone(), Policy::new(&server.sandbox.broker)))
+            .collect();
+        if policies.is_empty() {
+            return None;
+        }
+        info!("Brokering file access for {} server(s)", policies.len());
+        Some(Arc::new(Self {
+            policies,
+            journal: tokio::sync::Mutex::new(()),
+        }))
+    }
+
+    /// Brokered servers
+    pub fn servers(&self) -> impl Iterator<Item = &str> {
+        self.policies.keys().map(String::as_str)
+    }
+
+    /// MCP tool definitions of one server's broker
+    pub fn tools_for(&self, server: &str) -> Vec<Value> {
+        let Some(policy) = self.policies.get(server) else {
+            return Vec::new();
+        };
+        let roots = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let readable: Vec<PathBuf> = policy.read.iter().chain(&policy.write).cloned().collect();
+        let mut tools = vec![json!({
+            "name": format!("{}{}", READ_PREFIX, server),
+            "description": format!(
+                "Read a file for the '{}' server, under {} (at most {} bytes)",
+                server,
+                roots(&readable),
+                policy.max_bytes,
+            ),
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the file" }
+                },
+                "required": ["path"]
+            }
+        })];
+        if !policy.write.is_empty() {
+            tools.push(json!({
+                "name": format!("{}{}", WRITE_PREFIX, server),
+                "description": format!(
+                    "Write a file for the '{}' server, under {} (at most {} bytes)",
+                    server,
+                    roots(&policy.write),
+                    policy.max_bytes,
+                ),
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Absolute path of the file" },
+                        "content": { "type": "string" },
+                        "encoding": {
+                            "type": "string",
+                            "enum": ["utf8", "base64"],
+                            "description": "Encoding of content, utf8 by default"
+                        },
+                        "append": { "type": "boolean", "description": "Append instead of replacing" }
+                    },
+                    "required": ["path", "content"]
+                }
+            }));
+        }
+        tools
+    }
+
+    /// MCP tool definitions of the brokered servers among `visible`
+    pub fn tools(&self, visible: impl Fn(&str) -> bool) -> Vec<Value> {
+        self.servers()
+            .filter(|server| visible(server))
+            .flat_map(|server| self.tools_for(server))
+            .collect()
+    }
+
+    fn parse_tool<'a>(&self, tool_name: &'a str) -> Option<(Access, &'a str)> {
+        let (access, server) = match tool_name.strip_prefix(READ_PREFIX) {
+            Some(server) => (Access::Read, server),
+            None => (Access::Write, tool_name.strip_prefix(WRITE_PREFIX)?),
+        };
+        let policy = self.policies.get(server)?;
+        (access == Access::Read || !policy.write.is_empty()).then_some((access, server))
+    }
+
+    /// Whether a tool name belongs to the broker
+    pub fn handles(&self, tool_name: &str) -> bool {
+        self.parse_tool(tool_name).is_some()
+    }
+
+    /// The server a broker tool acts for
+    pub fn server_of<'a>(&self, tool_name: &'a str) -> Option<&'a str> {
+        self.parse_tool(tool_name).map(|(_, server)| server)
+    }
+
+    /// Answer a `tools/call` for a broker tool, if it is one
+    ///
+    /// Only tools of servers in `visible`, the servers the caller may reach,
+    /// are answered.
+    pub async fn handle_request(
+        &self,
+        request: &JsonRpcRequest,
+        identity: Option<&str>,
+        visible: impl Fn(&str) -> bool,
+    ) -> McpResult<Option<JsonRpcResponse>> {
+        if request.method != "tools/call" {
+            return Ok(None);
+        }
+        let params = request.params.as_ref();
+        let Some(tool_name) = params.and_then(|p| p.get("name")).and_then(|n| n.as_str()) else {
+            return Ok(None);
+        };
+        if !self.server_of(tool_name).is_some_and(visible) {
+            return Ok(None);
+        }
+        let id = request
+            .id
+            .clone()
+            .ok_or_else(|| McpError::InvalidRequest("tools/call requires an id".to_string()))?;
+        let result = self
+            .call(tool_name, params.and_then(|p| p.get("arguments")), identity)
+            .await?;
+        Ok(Some(JsonRpcResponse::success(id, result)))
+    }
+
+    /// Append broker tools of the servers in `visible` to an upstream
+    /// `tools/list` response
+    pub fn extend_tool_list(&self, response: &mut JsonRpcResponse, visible: impl Fn(&str) -> bool) {
+        let Some(result) = response.result.as_mut() else {
+            return;
+        };
+        if let Some(tools) = result.get_mut("tools").and_then(|t| t.as_array_mut()) {
+            tools.extend(self.tools(visible));
+        }
+    }
+
+    /// Run a broker tool, returning an MCP `tools/call` result
+    ///
+    /// Refused and failed calls are tool errors (`isError`), so the model
+    /// sees why.
+    pub async fn call(
+        &self,
+        tool_name: &str,
+        arguments: Option<&Value>,
+        identity: Option<&str>,
+    ) -> McpResult<Value> {
+        let (access, server) = self
+            .parse_tool(tool_name)
+            .ok_or_else(|| McpError::ToolExecutionError(format!("Unknown tool: {}", tool_name)))?;
+        let policy = &self.policies[server];
+        if dry_run::is_dry_run() {
+            let schema = self.tools_for(server).into_iter().find(|tool| tool["name"] == tool_name);
+            let arguments = arguments.cloned().unwrap_or_else(|| json!({}));
+            let report = dry_run::report(
+                server,
+                tool_name,
+                &arguments,
+                schema.as_ref().and_then(|tool| tool.get("inputSchema")),
+            );
+            return Ok(dry_run::result(report));
+        }
+
+        let path = arguments
+            .and_then(|a| a.get("path"))
+            .and_then(|p| p.as_str())
+            .ok_or_else(|| McpError::InvalidRequest("Missing required argument: path".to_string()))?;
+        let outcome = match access {
+            Access::Read => policy.read_file(path).await,
+            Access::Write => policy.write_file(path, arguments).await,
+        };
+        self.journal(server, policy, access, path, identity, &outcome).await;
+
+        Ok(match outcome {
+            Ok((_, content)) => content,
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+
+    /// Audit a call, and append it to the server's journal
+    async fn journal(
+        &self,
+        server: &str,
+        policy: &Policy,
+        access: Access,
+        path: &str,
+        identity: Option<&str>,
+        outcome: &McpResult<(Vec<u8>, Value)>,
+    ) {
+        let mut entry = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "server": server,
+            "tool": access.tool(),
+            "path": path,
+            "identity": identity,
+            "allowed": outcome.is_ok(),
+        });
+        let event = AuditEvent::new(AuditEventType::FileBrokerAccess).with_server_name(server);
+        let event = match outcome {
+            Ok((bytes, _)) => {
+                entry["bytes"] = json!(bytes.len());
+                entry["sha256"] = json!(sha256(bytes));
+                event
+            }
+            Err(e) => {
+                entry["error"] = json!(e.to_string());
+                event.with_error(e.to_string())
+            }
+        };
+        audit::record(event.with_details(entry.clone()));
+
+        let Some(journal) = &policy.journal else {
+            return;
+        };
+        let _guard = self.journal.lock().await;
+        let appended = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(journal)
+                .await?;
+            file.write_all(format!("{}\n", entry).as_bytes()).await
+        };
+        if let Err(e) = appended.await {
+            warn!("Failed to journal file broker call to {:?}: {}", journal, e);
+        }
+    }
+}
+
+impl Policy {
+    fn new(config: &FileBrokerConfig) -> Self {
+        let paths = |paths: &[String]| {
+            paths
+                .iter()
+                .map(|path| PathBuf::from(shellexpand::tilde(path).to_string()))
+                .collect()
+        };
+        Self {
+            read: paths(&config.read),
+            write: paths(&config.write),
+            max_bytes: config.max_bytes,
+            journal: config
+                .journal
+                .as_ref()
+                .map(|journal| PathBuf::from(shellexpand::tilde(journal).to_string())),
+        }
+    }
+
+    /// Open `path` for `access` beneath one of the brokered roots
+    ///
+    /// The path is matched against the roots without touching the
+    /// filesystem, then opened relative to the root's directory, so a `..`
+    /// or a symlink swapped in at any point cannot lead outside it.
+    fn open(&self, path: &str, access: Access, append: bool) -> McpResult<(PathBuf, std::fs::File)> {
+        let path = PathBuf::from(shellexpand::tilde(path).to_string());
+        if !path.is_absolute() {
+            return Err(McpError::InvalidRequest(format!(
+                "{} is not an absolute path",
+                path.display()
+            )));
+        }
+        let denied = || {
+            McpError::SandboxDenied(format!(
+                "{} is outside the paths brokered for {}",
+                path.display(),
+                match access {
+                    Access::Read => "reading",
+                    Access::Write => "writing",
+                }
+            ))
+        };
+
+        let roots: Vec<&PathBuf> = match access {
+            Access::Read => self.read.iter().chain(&self.write).collect(),
+            Access::Write => self.write.iter().collect(),
+        };
+        let (root, relative) = roots
+            .into_iter()
+            .flat_map(|root| std::iter::once(root.clone()).chain(std::fs::canonicalize(root)))
+            .find_map(|root| {
+                let relative = path.strip_prefix(&root).ok()?.to_path_buf();
+                (!relative.as_os_str().is_empty()).then_some((root, relative))
+            })
+            .ok_or_else(denied)?;
+
+        let file = open_beneath(&root, &relative, access, append).map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => denied(),
+            _ => McpError::InvalidRequest(format!("{}: {}", path.display(), e)),
+        })?;
+        if !file.metadata()?.is_file() {
+            return Err(McpError::InvalidRequest(format!(
+                "{} is not a regular file",
+                path.display()
+            )));
+        }
+        Ok((root.join(relative), file))
+    }
+
+    async fn read_file(&self, path: &str) -> McpResult<(Vec<u8>, Value)> {
+        let (resolved, file) = self.open(path, Access::Read, false)?;
+        let size = file.metadata()?.len();
+        let too_large = |size: u64| {
+            McpError::InvalidRequest(format!("{} is {} bytes, limit is {}", path, size, self.max_bytes))
+        };
+        if size > self.max_bytes as u64 {
+            return Err(too_large(size));
+        }
+        let mut bytes = Vec::new();
+        tokio::fs::File::from_std(file)
+            .take(self.max_bytes as u64 + 1)
+            .read_to_end(&mut bytes)
+            .await?;
+        if bytes.len() > self.max_bytes {
+            return Err(too_large(bytes.len() as u64));
+        }
+        let (text, encoding) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (text.to_string(), "utf8"),
+            Err(_) => (STANDARD.encode(&bytes), "base64"),
+        };
+        let result = json!({
+            "content": [{ "type": "text", "text": text }],
+            "structuredContent": {
+                "path": resolved,
+                "bytes": bytes.len(),
+                "encoding": encoding,
+            }
+        });
+        Ok((bytes, result))
+    }
+
+    async fn write_file(&self, path: &str, arguments: Option<&Value>) -> McpResult<(Vec<u8>, Value)> {
+        let content = arguments
+            .and_then(|a| a.get("content"))
+            .and_then(|c| c.as_str())
+            .ok_or_else(|| McpError::InvalidRequest("Missing required argument: content".to_string()))?;
+        let bytes = match arguments.and_then(|a| a.get("encoding")).and_then(|e| e.as_str()) {
+            None | Some("utf8") => content.as_bytes().to_vec(),
+            Some("base64") => STANDARD
+                .decode(content)
+                .map_err(|e| McpError::InvalidRequest(format!("Invalid base64 content: {}", e)))?,
+            Some(other) => {
+                return Err(McpError::InvalidRequest(format!("Unknown encoding '{}'", other)));
+            }
+        };
+        if bytes.len() > self.max_bytes {
+            return Err(McpError::InvalidRequest(format!(
+                "Content is {} bytes, limit is {}",
+                bytes.len(),
+                self.max_bytes
+            )));
+        }
+
+        let append = arguments
+            .and_then(|a| a.get("append"))
+            .and_then(|a| a.as_bool())
+            .unwrap_or(false);
+        let (resolved, file) = self.open(path, Access::Write, append)?;
+        let mut file = tokio::fs::File::from_std(file);
+        file.write_all(&bytes).await?;
+
+        let result = json!({
+            "content": [{
+                "type": "text",
+                "text": format!("Wrote {} bytes to {}", bytes.len(), resolved.display()),
+            }],
+            "structuredContent": { "path": resolved, "bytes": bytes.len() }
+        });
+        Ok((bytes, result))
+    }
+}
+
+/// Open `relative` beneath the directory `root`
+///
+/// `openat2` with `RESOLVE_BENEATH` refuses any resolution that leaves
+/// `root`, and `O_NOFOLLOW` a final symlink; both surface as
+/// `PermissionDenied`. `O_NONBLOCK` keeps a FIFO from stalling the open
+/// until it is rejected as not a regular file.
+#[cfg(target_os = "linux")]
+fn open_beneath(root: &Path, relative: &Path, access: Access, append: bool) -> std::io::Result<std::fs::File> {
+    use std::os::fd::{AsRawFd, FromRawFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let dir = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_DIRECTORY)
+        .open(root)?;
+    let relative = std::ffi::CString::new(relative.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let flags = libc::O_CLOEXEC
+        | libc::O_NOFOLLOW
+        | libc::O_NOCTTY
+        | libc::O_NONBLOCK
+        | match access {
+            Access::Read => libc::O_RDONLY,
+            Access::Write if append => libc::O_WRONLY | libc::O_CREAT | libc::O_APPEND,
+            Access::Write => libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+        };
+    // SAFETY: open_how is plain data, all-zero is its default
+    let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+    how.flags = flags as u64;
+    // openat2 refuses a mode unless the file may be created
+    how.mode = if access == Access::Write { 0o666 } else { 0 };
+    how.resolve = libc::RESOLVE_BENEATH | libc::RESOLVE_NO_MAGICLINKS;
+    // SAFETY: the path is NUL-terminated and both pointers outlive the call
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_openat2,
+            dir.as_raw_fd(),
+            relative.as_ptr(),
+            &how as *const libc::open_how,
+            std::mem::size_of::<libc::open_how>(),
+        )
+    };
+    if fd < 0 {
+        let e = std::io::Error::last_os_error();
+        return Err(match e.raw_os_error() {
+            Some(libc::EXDEV) | Some(libc::ELOOP) => std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "path leaves the brokered directory",
+            ),
+            _ => e,
+        });
+    }
+    // SAFETY: openat2 returned a new descriptor owned by nobody else
+    Ok(unsafe { std::fs::File::from_raw_fd(fd as i32) })
+}
+
+/// Open `relative` beneath the directory `root`
+///
+/// Without `openat2` the containment check and the open are separate
+/// steps; a final symlink is still refused where `O_NOFOLLOW` exists.
+#[cfg(not(target_os = "linux"))]
+fn open_beneath(root: &Path, relative: &Path, access: Access, append: bool) -> std::io::Result<std::fs::File> {
+    let path = root.join(relative);
+    let parent = path.parent().unwrap_or(root);
+    if !std::fs::canonicalize(parent)?.starts_with(std::fs::canonicalize(root)?) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "path leaves the brokered directory",
+        ));
+    }
+    let mut options = std::fs::OpenOptions::new();
+    match access {
+        Access::Read => options.read(true),
+        Access::Write => options.create(true).write(true).append(append).truncate(!append),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK);
+    }
+    options.open(path)
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::McpServerConfig;
+    use std::path::Path;
+
+    fn broker(dir: &Path, journal: &Path) -> Arc<FileBroker> {
+        let mut server = McpServerConfig {
+            name: "notes".to_string(),
+            ..Default::default()
+        };
+        server.sandbox.broker = FileBrokerConfig {
+            enabled: true,
+            read: vec![dir.join("docs").to_string_lossy().into_owned()],
+            write: vec![dir.join("out").to_string_lossy().into_owned()],
+            max_bytes: 16,
+            journal: Some(journal.to_string_lossy().into_owned()),
+        };
+        server.sandbox.filesystem = FilesystemAccess::Paths(Vec::new());
+        FileBroker::from_config(&Config {
+            servers: vec![server],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unconfined_servers_not_brokered() {
+        let mut server = McpServerConfig {
+            name: "notes".to_string(),
+            ..Default::default()
+        };
+        server.sandbox.broker.enabled = true;
+        server.sandbox.broker.write = vec!["/srv/notes".to_string()];
+        let config = |server: &McpServerConfig| Config {
+            servers: vec![server.clone()],
+            ..Default::default()
+        };
+
+        // The default read-only sandbox still reads the whole host
+        assert!(FileBroker::from_config(&config(&server)).is_none());
+
+        server.sandbox.filesystem = FilesystemAccess::Paths(vec!["/srv/notes/cache".to_string()]);
+        assert!(FileBroker::from_config(&config(&server)).is_some());
+
+        server.sandbox.filesystem = FilesystemAccess::Paths(vec!["/srv/notes/../etc".to_string()]);
+        assert!(FileBroker::from_config(&config(&server)).is_none());
+
+        server.sandbox.filesystem = FilesystemAccess::Paths(Vec::new());
+        server.sandbox.enabled = false;
+        assert!(FileBroker::from_config(&config(&server)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_broker_enforces_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["docs", "out", "secret"] {
+            std::fs::create_dir(dir.path().join(sub)).unwrap();
+        }
+        std::fs::write(dir.path().join("docs/a.txt"), "hello").unwrap();
+        std::fs::write(dir.path().join("secret/key"), "hunter2").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("secret/key"), dir.path().join("docs/link")).unwrap();
+        let journal = dir.path().join("journal.jsonl");
+        let broker = broker(dir.path(), &journal);
+
+        assert!(broker.handles("fs_read_notes"));
+        assert!(broker.handles("fs_write_notes"));
+        assert!(!broker.handles("fs_read_other"));
+
+        let path = |name: &str| json!({ "path": dir.path().join(name) });
+        let read = broker.call("fs_read_notes", Some(&path("docs/a.txt")), None).await.unwrap();
+        assert_eq!(read["content"][0]["text"], "hello");
+
+        for denied in ["secret/key", "docs/link"] {
+            let result = broker.call("fs_read_notes", Some(&path(denied)), None).await.unwrap();
+            assert_eq!(result["isError"], true, "{}", denied);
+        }
+
+        let mut write = path("out/b.txt");
+        write["content"] = json!("written");
+        let result = broker.call("fs_write_notes", Some(&write), Some("alice")).await.unwrap();
+        assert!(result.get("isError").is_none());
+        assert_eq!(std::fs::read_to_string(dir.path().join("out/b.txt")).unwrap(), "written");
+
+        let mut outside = path("docs/c.txt");
+        outside["content"] = json!("nope");
+        let result = broker.call("fs_write_notes", Some(&outside), None).await.unwrap();
+        assert_eq!(result["isError"], true);
+
+        write["content"] = json!("x".repeat(17));
+        let result = broker.call("fs_write_notes", Some(&write), None).await.unwrap();
+        assert_eq!(result["isError"], true);
+
+        let entries: Vec<Value> = std::fs::read_to_string(&journal)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[3]["identity"], "alice");
+        assert_eq!(entries[3]["bytes"], 7);
+        assert_eq!(entries[4]["allowed"], false);
+    }
+
+    #[tokio::test]
+    async fn test_broker_stays_beneath_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["docs", "out", "secret"] {
+            std::fs::create_dir(dir.path().join(sub)).unwrap();
+        }
+        std::fs::write(dir.path().join("secret/key"), "hunter2").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("secret"), dir.path().join("out/escape")).unwrap();
+        let broker = broker(dir.path(), &dir.path().join("journal.jsonl"));
+
+        let read = json!({ "path": format!("{}/docs/../secret/key", dir.path().display()) });
+        let result = broker.call("fs_read_notes", Some(&read), None).await.unwrap();
+        assert_eq!(result["isError"], true);
+
+        #[cfg(unix)]
+        {
+            let write = json!({ "path": dir.path().join("out/escape/key"), "content": "owned" });
+            let result = broker.call("fs_write_notes", Some(&write), None).await.unwrap();
+            assert_eq!(result["isError"], true);
+            assert_eq!(std::fs::read_to_string(dir.path().join("secret/key")).unwrap(), "hunter2");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broker_follows_visible_servers() {
+        let dir = tempfile::tempdir().unwrap();
+        let broker = broker(dir.path(), &dir.path().join("journal.jsonl"));
+        let request = JsonRpcRequest::new(
+            "tools/call",
+            Some(json!({ "name": "fs_read_notes", "arguments": { "path": "/etc/hostname" } })),
+        );
+
+        let hidden = broker.handle_request(&request, None, |_| false).await.unwrap();
+        assert!(hidden.is_none());
+        let shown = broker.handle_request(&request, None, |server| server == "notes").await.unwrap();
+        assert!(shown.is_some());
+
+        let mut response = JsonRpcResponse::success(crate::core::protocol::RequestId::Number(2), json!({ "tools": [] }));
+        broker.extend_tool_list(&mut response, |_| false);
+        assert_eq!(response.result.as_ref().unwrap()["tools"], json!([]));
+        broker.extend_tool_list(&mut response, |server| server == "notes");
+        assert_eq!(response.result.as_ref().unwrap()["tools"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,454 @@
+//! Disk space and inode limits on a sandboxed server's writable paths
+//!
+//! On Linux, when every writable path is a directory the proxy created for
+//! the server, the paths are tagged with a project ID allocated to it and
+//! given project quota limits, so the kernel refuses writes past them
+//! (ext4 and XFS, mounted with project quotas; needs `chattr`, `findmnt`
+//! and `setquota`). The limits, tags and ID are removed when the server
+//! stops. Paths that existed already, such as `/tmp`, may be shared and are
+//! never retagged. Elsewhere, or when that fails, usage is measured
+//! periodically and `tools/call` requests are refused while the server is
+//! over its limits. Either way the measured usage is shown in the server's
+//! status.
+
+use crate::config::{FilesystemAccess, McpServerConfig};
+use crate::utils::errors::{McpError, McpResult};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How often usage is measured
+const MEASURE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Project IDs are taken from this range, clear of common manual ones
+#[cfg(target_os = "linux")]
+const PROJECT_ID_BASE: u32 = 100_000;
+/// Servers that can hold a project ID at once
+#[cfg(target_os = "linux")]
+const MAX_PROJECTS: u32 = 100_000;
+
+/// Space and inodes used under the writable paths
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub bytes: u64,
+    pub inodes: u64,
+}
+
+/// Usage against the limits, for status output
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskQuotaReport {
+    pub used_bytes: u64,
+    pub quota_bytes: Option<u64>,
+    pub inodes: u64,
+    pub max_inodes: Option<u64>,
+    /// `project-quota` when the kernel enforces the limits, else `monitor`
+    pub enforcement: &'static str,
+    pub exceeded: bool,
+}
+
+/// Disk limits of one server
+pub struct DiskQuota {
+    server: String,
+    paths: Vec<PathBuf>,
+    max_bytes: Option<u64>,
+    max_inodes: Option<u64>,
+    usage: Mutex<DiskUsage>,
+    kernel_enforced: AtomicBool,
+    /// Project quota to remove when the server stops
+    #[cfg(target_os = "linux")]
+    project: Mutex<Option<Project>>,
+}
+
+/// A project ID and what was done with it
+#[cfg(target_os = "linux")]
+struct Project {
+    id: u32,
+    /// Paths tagged with the ID
+    tagged: Vec<PathBuf>,
+    /// Filesystems holding limits for the ID
+    mounts: Vec<String>,
+}
+
+impl DiskQuota {
+    /// Limits of a sandboxed server with a quota on explicit writable paths
+    pub fn from_config(config: &McpServerConfig) -> Option<Arc<Self>> {
+        let sandbox = &config.sandbox;
+        if !sandbox.enabled || (sandbox.disk_quota_mb.is_none() && sandbox.max_inodes.is_none()) {
+            return None;
+        }
+        let FilesystemAccess::Paths(paths) = &sandbox.filesystem else {
+            return None;
+        };
+        Some(Arc::new(Self {
+            server: config.name.clone(),
+            paths: paths
+                .iter()
+                .map(|path| PathBuf::from(shellexpand::tilde(path).to_string()))
+                .collect(),
+            max_bytes: sandbox.disk_quota_mb.map(|mb| mb * 1024 * 1024),
+            max_inodes: sandbox.max_inodes,
+            usage: Mutex::new(DiskUsage::default()),
+            kernel_enforced: AtomicBool::new(false),
+            #[cfg(target_os = "linux")]
+            project: Mutex::new(None),
+        }))
+    }
+
+    /// Apply the limits, then keep measuring usage while the quota is alive
+    pub async fn start(self: &Arc<Self>) {
+        match self.apply_project_quota().await {
+            Ok(()) => {
+                self.kernel_enforced.store(true, Ordering::Relaxed);
+                info!("Project quota set on the writable paths of {}", self.server);
+            }
+            Err(e) => warn!(
+                "No project quota for {} ({}), enforcing its disk limits by monitoring",
+                self.server, e
+            ),
+        }
+        self.measure().await;
+
+        let quota = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MEASURE_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(quota) = Weak::upgrade(&quota) else {
+                    return;
+                };
+                quota.measure().await;
+            }
+        });
+    }
+
+    /// Remove the project quota, if one was set
+    pub async fn stop(&self) {
+        self.release_project_quota().await;
+    }
+
+    async fn measure(&self) {
+        let paths = self.paths.clone();
+        match tokio::task::spawn_blocking(move || measure(&paths)).await {
+            Ok(usage) => {
+                debug!("{} uses {} bytes in {} inodes", self.server, usage.bytes, usage.inodes);
+                *self.usage.lock() = usage;
+            }
+            Err(e) => warn!("Measuring disk usage of {} failed: {}", self.server, e),
+        }
+    }
+
+    /// Whether the last measurement is past either limit
+    pub fn exceeded(&self) -> bool {
+        let usage = *self.usage.lock();
+        self.max_bytes.is_some_and(|max| usage.bytes > max)
+            || self.max_inodes.is_some_and(|max| usage.inodes > max)
+    }
+
+    /// Refuse a tool call while over the limits, unless the kernel is
+    /// enforcing them already
+    pub fn check(&self) -> McpResult<()> {
+        if !self.kernel_enforced.load(Ordering::Relaxed) && self.exceeded() {
+            return Err(McpError::SandboxDenied(format!(
+                "{} is over its disk quota",
+                self.server
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn report(&self) -> DiskQuotaReport {
+        let usage = *self.usage.lock();
+        DiskQuotaReport {
+            used_bytes: usage.bytes,
+            quota_bytes: self.max_bytes,
+            inodes: usage.inodes,
+            max_inodes: self.max_inodes,
+            enforcement: if self.kernel_enforced.load(Ordering::Relaxed) {
+                "project-quota"
+            } else {
+                "monitor"
+            },
+            exceeded: self.exceeded(),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn apply_project_quota(&self) -> McpResult<()> {
+        let registry = Registry::open()?;
+        let mut owned = registry.owned(&self.server);
+        for path in &self.paths {
+            if std::fs::symlink_metadata(path).is_err() {
+                tokio::fs::create_dir_all(path).await?;
+                owned.push(path.clone());
+                registry.set_owned(&self.server, &owned)?;
+            }
+        }
+        if let Some(shared) = self.paths.iter().find(|path| !owned.contains(path)) {
+            return Err(McpError::SandboxError(format!(
+                "{} was not created for {} and may be shared",
+                shared.display(),
+                self.server
+            )));
+        }
+
+        let mut project = Project {
+            id: registry.allocate(&self.server)?,
+            tagged: Vec::new(),
+            mounts: Vec::new(),
+        };
+        let id = project.id.to_string();
+        let blocks_kb = self.max_bytes.map_or(0, |bytes| bytes / 1024).to_string();
+        let inodes = self.max_inodes.unwrap_or(0).to_string();
+        let result = async {
+            for path in &self.paths {
+                let path_str = path.to_string_lossy();
+                // The directory is the server's alone, so all of it joins the
+                // project and new files under it inherit the tag
+                project.tagged.push(path.clone());
+                run("chattr", &["-R", "+P", "-p", &id, &path_str]).await?;
+                let mount = run("findmnt", &["-n", "-o", "TARGET", "--target", &path_str]).await?;
+                let mount = mount.trim().to_string();
+                if !project.mounts.contains(&mount) {
+                    project.mounts.push(mount.clone());
+                }
+                run("setquota", &["-P", &id, "0", &blocks_kb, "0", &inodes, &mount]).await?;
+            }
+            Ok(())
+        }
+        .await;
+
+        *self.project.lock() = Some(project);
+        if result.is_err() {
+            self.release_project_quota().await;
+        }
+        result
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn apply_project_quota(&self) -> McpResult<()> {
+        Err(McpError::SandboxError(
+            "project quotas are only available on Linux".to_string(),
+        ))
+    }
+
+    /// Lift the limits, untag the paths and give the project ID back
+    #[cfg(target_os = "linux")]
+    async fn release_project_quota(&self) {
+        let Some(project) = self.project.lock().take() else {
+            return;
+        };
+        self.kernel_enforced.store(false, Ordering::Relaxed);
+
+        let id = project.id.to_string();
+        for mount in &project.mounts {
+            if let Err(e) = run("setquota", &["-P", &id, "0", "0", "0", "0", mount]).await {
+                warn!("Failed to remove the project quota of {} on {}: {}", self.server, mount, e);
+            }
+        }
+        for path in &project.tagged {
+            if let Err(e) = run("chattr", &["-R", "-P", "-p", "0", &path.to_string_lossy()]).await {
+                warn!("Failed to untag {} of {}: {}", path.display(), self.server, e);
+            }
+        }
+        match Registry::open() {
+            Ok(registry) => registry.release(project.id),
+            Err(e) => warn!("Failed to release project ID {} of {}: {}", id, self.server, e),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn release_project_quota(&self) {}
+}
+
+/// Project IDs in use and the directories created for each server, kept in
+/// the state directory so other proxies on the host and later runs see them
+#[cfg(target_os = "linux")]
+struct Registry {
+    root: PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+impl Registry {
+    fn open() -> McpResult<Self> {
+        let root = dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("supermcp/quota");
+        Self::at(root)
+    }
+
+    fn at(root: PathBuf) -> McpResult<Self> {
+        std::fs::create_dir_all(root.join("ids"))?;
+        Ok(Self { root })
+    }
+
+    fn owned_file(&self, server: &str) -> PathBuf {
+        use base64::Engine;
+        let name = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(server);
+        self.root.join(format!("{}.dirs.json", name))
+    }
+
+    /// Directories created for `server` that still exist
+    fn owned(&self, server: &str) -> Vec<PathBuf> {
+        std::fs::read(self.owned_file(server))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Vec<PathBuf>>(&bytes).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .collect()
+    }
+
+    fn set_owned(&self, server: &str, paths: &[PathBuf]) -> McpResult<()> {
+        std::fs::write(self.owned_file(server), serde_json::to_vec(paths)?)?;
+        Ok(())
+    }
+
+    /// A project ID held by no other server, or the one `server` was left
+    /// holding by an unclean stop
+    fn allocate(&self, server: &str) -> McpResult<u32> {
+        let ids = self.root.join("ids");
+        for entry in std::fs::read_dir(&ids)?.flatten() {
+            if std::fs::read_to_string(entry.path()).is_ok_and(|holder| holder == server) {
+                if let Some(id) = entry.file_name().to_str().and_then(|id| id.parse().ok()) {
+                    return Ok(id);
+                }
+            }
+        }
+
+        for id in PROJECT_ID_BASE..PROJECT_ID_BASE + MAX_PROJECTS {
+            // Creating the file claims the ID, even against other processes
+            let claimed = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(ids.join(id.to_string()));
+            match claimed {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    file.write_all(server.as_bytes())?;
+                    return Ok(id);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(McpError::SandboxError("No project IDs are free".to_string()))
+    }
+
+    fn release(&self, id: u32) {
+        let _ = std::fs::remove_file(self.root.join("ids").join(id.to_string()));
+    }
+}
+
+/// Run a quota tool, returning its stdout
+#[cfg(target_os = "linux")]
+async fn run(program: &str, args: &[&str]) -> McpResult<String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| McpError::SandboxError(format!("{} unavailable: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(McpError::SandboxError(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Space and inodes used under `paths`, not following symlinks
+pub fn measure(paths: &[PathBuf]) -> DiskUsage {
+    fn walk(path: &Path, usage: &mut DiskUsage) {
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return;
+        };
+        usage.inodes += 1;
+        usage.bytes += metadata.len();
+        if metadata.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    walk(&entry.path(), usage);
+                }
+            }
+        }
+    }
+
+    let mut usage = DiskUsage::default();
+    for path in paths {
+        walk(path, &mut usage);
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_exceeded_by_measured_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a"), vec![0u8; 600 * 1024]).unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/b"), vec![0u8; 600 * 1024]).unwrap();
+
+        let usage = measure(&[dir.path().to_path_buf()]);
+        assert_eq!(usage.inodes, 4);
+        assert!(usage.bytes >= 1200 * 1024);
+
+        let mut config = McpServerConfig {
+            name: "writer".to_string(),
+            ..Default::default()
+        };
+        config.sandbox.filesystem =
+            FilesystemAccess::Paths(vec![dir.path().to_string_lossy().to_string()]);
+        config.sandbox.disk_quota_mb = Some(1);
+        let quota = DiskQuota::from_config(&config).unwrap();
+        assert!(!quota.exceeded());
+
+        *quota.usage.lock() = usage;
+        assert!(quota.exceeded());
+        assert!(matches!(quota.check(), Err(McpError::SandboxDenied(_))));
+        assert_eq!(quota.report().enforcement, "monitor");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_project_ids_are_unique_and_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Registry::at(dir.path().to_path_buf()).unwrap();
+
+        let a = registry.allocate("a").unwrap();
+        let b = registry.allocate("b").unwrap();
+        assert_ne!(a, b);
+        // A server left holding an ID gets the same one back
+        assert_eq!(registry.allocate("a").unwrap(), a);
+
+        registry.release(a);
+        let c = registry.allocate("c").unwrap();
+        assert_eq!(c, a);
+        assert_ne!(registry.allocate("a").unwrap(), c);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_only_created_directories_are_owned() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Registry::at(dir.path().join("registry")).unwrap();
+        let created = dir.path().join("created");
+        std::fs::create_dir(&created).unwrap();
+
+        assert!(registry.owned("writer").is_empty());
+        registry
+            .set_owned("writer", &[created.clone(), dir.path().join("gone")])
+            .unwrap();
+        assert_eq!(registry.owned("writer"), [created]);
+        assert!(registry.owned("other").is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+//! Loading the eBPF observer program with aya and reading its events
+
+use crate::utils::errors::{McpError, McpResult};
+use aya::maps::{HashMap as BpfHashMap, MapData, RingBuf};
+use aya::programs::{BtfTracePoint, TracePoint};
+use aya::{Btf, Ebpf};
+use parking_lot::Mutex;
+use std::path::Path;
+use tokio::io::unix::AsyncFd;
+
+/// Syscall tracepoints of the program, as (program, category, tracepoint)
+const TRACEPOINTS: &[(&str, &str, &str)] = &[
+    ("on_openat", "syscalls", "sys_enter_openat"),
+    ("on_connect", "syscalls", "sys_enter_connect"),
+];
+
+/// BTF tracepoints keeping its set of watched processes current
+const BTF_TRACEPOINTS: &[(&str, &str)] = &[
+    ("on_fork", "sched_process_fork"),
+    ("on_exit", "sched_process_exit"),
+];
+
+fn error(e: impl std::fmt::Display) -> McpError {
+    McpError::SandboxError(format!("eBPF observer: {}", e))
+}
+
+/// The loaded and attached program
+pub struct ObserverProgram {
+    /// Owns the attached programs; dropping it detaches them
+    _bpf: Mutex<Ebpf>,
+    watched: Mutex<BpfHashMap<MapData, u32, u32>>,
+}
+
+impl ObserverProgram {
+    /// Load the compiled `observer.bpf.o` and attach it, returning its
+    /// event ring buffer
+    pub fn load(path: &Path) -> McpResult<(Self, RingBuf<MapData>)> {
+        let mut bpf = Ebpf::load_file(path).map_err(error)?;
+        let btf = Btf::from_sys_fs().map_err(error)?;
+
+        for (name, category, tracepoint) in TRACEPOINTS {
+            let program: &mut TracePoint = bpf
+                .program_mut(name)
+                .ok_or_else(|| error(format!("{} has no program {}", path.display(), name)))?
+                .try_into()
+                .map_err(error)?;
+            program.load().map_err(error)?;
+            program.attach(category, tracepoint).map_err(error)?;
+        }
+        for (name, event) in BTF_TRACEPOINTS {
+            let program: &mut BtfTracePoint = bpf
+                .program_mut(name)
+                .ok_or_else(|| error(format!("{} has no program {}", path.display(), name)))?
+                .try_into()
+                .map_err(error)?;
+            program.load(event, &btf).map_err(error)?;
+            program.attach().map_err(error)?;
+        }
+
+        let map = |bpf: &mut Ebpf, name: &str| {
+            bpf.take_map(name)
+                .ok_or_else(|| error(format!("{} has no map {}", path.display(), name)))
+        };
+        let watched = BpfHashMap::try_from(map(&mut bpf, "WATCHED")?).map_err(error)?;
+        let events = RingBuf::try_from(map(&mut bpf, "EVENTS")?).map_err(error)?;
+        Ok((
+            Self {
+                _bpf: Mutex::new(bpf),
+                watched: Mutex::new(watched),
+            },
+            events,
+        ))
+    }
+
+    /// Watch a process, and the processes it forks from now on, as `server`
+    pub fn watch(&self, pid: u32, server: u32) -> McpResult<()> {
+        self.watched.lock().insert(pid, server, 0).map_err(error)
+    }
+}
+
+/// Pass every event in the ring buffer to `handle` as it arrives
+pub async fn read_events(events: RingBuf<MapData>, mut handle: impl FnMut(&[u8])) -> McpResult<()> {
+    let mut events = AsyncFd::new(events).map_err(error)?;
+    loop {
+        let mut guard = events.readable_mut().await.map_err(error)?;
+        let ring = guard.get_inner_mut();
+        while let Some(item) = ring.next() {
+            handle(&item);
+        }
+        guard.clear_ready();
+    }
+}
@@ -0,0 +1,247 @@
+//! Time-limited sandbox elevation
+//!
+//! An elevation grant restarts a server with more than its sandbox allows,
+//! e.g. network for 15 minutes, and restarts it under its own config again
+//! once the grant expires or is revoked. Changes made to the server's
+//! config while it is elevated are lost at that point.
+//!
+//! Granted paths are added to what the server may already write; a
+//! read-only server stays read-only everywhere else.
+
+use crate::config::{ElevationConfig, FilesystemAccess, McpServerConfig};
+use crate::sandbox::learn;
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+static GRANTS: Lazy<DashMap<String, Arc<Grant>>> = Lazy::new(DashMap::new);
+
+/// The active elevation grant of a server
+pub fn grant(server: &str) -> Option<Arc<Grant>> {
+    GRANTS.get(server).map(|grant| grant.clone())
+}
+
+/// What a server is temporarily allowed on top of its sandbox
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElevationRequest {
+    /// Allow network access
+    pub network: bool,
+    /// Paths to allow writing under
+    pub filesystem: Vec<String>,
+    /// Allow GPU devices
+    pub gpu: bool,
+    /// How long the grant lasts
+    pub duration_secs: u64,
+    /// Why it is needed, for the audit log
+    pub reason: Option<String>,
+}
+
+impl ElevationRequest {
+    fn is_empty(&self) -> bool {
+        !self.network && self.filesystem.is_empty() && !self.gpu
+    }
+}
+
+/// A granted elevation, as reported by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct ElevationGrant {
+    pub server: String,
+    #[serde(flatten)]
+    pub request: ElevationRequest,
+    pub granted_by: Option<String>,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An active grant
+pub struct Grant {
+    info: ElevationGrant,
+    original: McpServerConfig,
+    revoked: Notify,
+}
+
+impl Grant {
+    /// Register a grant for a server running under `config`
+    pub fn start(
+        config: McpServerConfig,
+        request: ElevationRequest,
+        granted_by: Option<String>,
+        limits: &ElevationConfig,
+    ) -> McpResult<Arc<Self>> {
+        let name = config.name.clone();
+        if !limits.enabled {
+            return Err(McpError::InvalidRequest("Sandbox elevation is disabled".to_string()));
+        }
+        if request.is_empty() {
+            return Err(McpError::InvalidRequest(
+                "Request network, gpu or filesystem paths to elevate".to_string(),
+            ));
+        }
+        if request.duration_secs == 0 || request.duration_secs > limits.max_duration_secs {
+            return Err(McpError::InvalidRequest(format!(
+                "Elevation lasts between 1 and {} seconds",
+                limits.max_duration_secs
+            )));
+        }
+        if !config.sandbox.enabled {
+            return Err(McpError::InvalidRequest(format!("{} is not sandboxed", name)));
+        }
+        if learn::session(&name).is_some_and(|session| !session.finished()) {
+            return Err(McpError::InvalidRequest(format!("{} is being learned", name)));
+        }
+
+        let granted_at = Utc::now();
+        let grant = Arc::new(Self {
+            info: ElevationGrant {
+                server: name.clone(),
+                expires_at: granted_at + chrono::Duration::seconds(request.duration_secs as i64),
+                request,
+                granted_by,
+                granted_at,
+            },
+            original: config,
+            revoked: Notify::new(),
+        });
+        match GRANTS.entry(name) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => Err(McpError::InvalidRequest(format!(
+                "{} is already elevated until {}",
+                entry.key(),
+                entry.get().info.expires_at
+            ))),
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(grant.clone());
+                Ok(grant)
+            }
+        }
+    }
+
+    /// The server's own config, restored when the grant ends
+    pub fn original(&self) -> &McpServerConfig {
+        &self.original
+    }
+
+    /// The server's config while elevated
+    pub fn elevated(&self) -> McpServerConfig {
+        elevate(&self.original, &self.info.request)
+    }
+
+    /// What was granted, to whom and until when
+    pub fn info(&self) -> &ElevationGrant {
+        &self.info
+    }
+
+    /// Wait until the grant expires or is revoked; true if revoked
+    pub async fn expire(&self) -> bool {
+        let remaining = (self.info.expires_at - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        tokio::select! {
+            _ = tokio::time::sleep(remaining) => false,
+            _ = self.revoked.notified() => true,
+        }
+    }
+
+    /// End the grant early
+    pub fn revoke(&self) {
+        self.revoked.notify_one();
+    }
+
+    /// Forget the grant, once the server runs under its own config again
+    pub fn end(&self) {
+        GRANTS.remove_if(&self.info.server, |_, grant| std::ptr::eq(grant.as_ref(), self));
+    }
+}
+
+/// `config` with the requested access added to its sandbox
+pub fn elevate(config: &McpServerConfig, request: &ElevationRequest) -> McpServerConfig {
+    let mut config = config.clone();
+    let sandbox = &mut config.sandbox;
+    sandbox.network |= request.network;
+    sandbox.allow_gpu |= request.gpu;
+    let writable = match &mut sandbox.filesystem {
+        FilesystemAccess::Paths(paths) => paths,
+        FilesystemAccess::Simple(access) if access == "full" => return config,
+        FilesystemAccess::Simple(_) => &mut sandbox.writable,
+    };
+    for path in &request.filesystem {
+        if !writable.contains(path) {
+            writable.push(path.clone());
+        }
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::FilesystemConstraint;
+
+    fn server(name: &str) -> McpServerConfig {
+        McpServerConfig {
+            name: name.to_string(),
+            command: "npx".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_elevate() {
+        let mut config = server("fetcher");
+        config.sandbox.filesystem = FilesystemAccess::Paths(vec!["/srv/data".to_string()]);
+        let request = ElevationRequest {
+            network: true,
+            filesystem: vec!["/srv/data".to_string(), "/tmp/export".to_string()],
+            ..Default::default()
+        };
+
+        let elevated = elevate(&config, &request);
+        assert!(elevated.sandbox.network);
+        assert!(!elevated.sandbox.allow_gpu);
+        assert!(matches!(
+            &elevated.sandbox.filesystem,
+            FilesystemAccess::Paths(paths) if paths == &["/srv/data", "/tmp/export"]
+        ));
+
+        // A read-only server keeps reading everywhere
+        let readonly = elevate(&server("reader"), &request);
+        assert!(matches!(
+            &readonly.sandbox.filesystem,
+            FilesystemAccess::Simple(access) if access == "readonly"
+        ));
+        assert_eq!(readonly.sandbox.writable, request.filesystem);
+        assert!(matches!(
+            FilesystemConstraint::from_config(&readonly.sandbox),
+            FilesystemConstraint::ReadOnlyExcept(paths) if paths == request.filesystem
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_grant_limits() {
+        let limits = ElevationConfig {
+            enabled: true,
+            max_duration_secs: 60,
+        };
+        let request = |duration_secs| ElevationRequest {
+            network: true,
+            duration_secs,
+            ..Default::default()
+        };
+
+        assert!(Grant::start(server("elevated"), ElevationRequest::default(), None, &limits).is_err());
+        assert!(Grant::start(server("elevated"), request(61), None, &limits).is_err());
+        assert!(Grant::start(server("elevated"), request(0), None, &limits).is_err());
+
+        let grant = Grant::start(server("elevated"), request(60), Some("ops".to_string()), &limits).unwrap();
+        assert!(Grant::start(server("elevated"), request(60), None, &limits).is_err());
+        assert!(grant.elevated().sandbox.network);
+
+        grant.revoke();
+        assert!(grant.expire().await);
+        grant.end();
+        assert!(super::grant("elevated").is_none());
+    }
+}
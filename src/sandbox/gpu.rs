@@ -0,0 +1,129 @@
+//! GPU passthrough for sandboxed servers
+//!
+//! GPU device nodes are denied like any other path unless a server sets
+//! `allow_gpu`. Then the NVIDIA and DRI devices are granted read-write in
+//! the sandbox, and `CUDA_VISIBLE_DEVICES`/`NVIDIA_VISIBLE_DEVICES` name the
+//! GPUs it may use. Listing `gpus` limits both to those indices.
+
+use std::path::Path;
+
+/// NVIDIA control devices every GPU process needs
+const NVIDIA_CONTROL: &[&str] = &[
+    "nvidiactl",
+    "nvidia-uvm",
+    "nvidia-uvm-tools",
+    "nvidia-modeset",
+    "nvidia-caps",
+];
+
+/// DRI render nodes are numbered from 128
+const DRI_RENDER_BASE: u32 = 128;
+
+/// Device paths to grant for GPUs `selected`, or all GPUs when empty
+pub fn device_paths(selected: &[u32]) -> Vec<String> {
+    device_paths_in(Path::new("/dev"), selected)
+}
+
+fn device_paths_in(dev: &Path, selected: &[u32]) -> Vec<String> {
+    let wanted = |index: u32| selected.is_empty() || selected.contains(&index);
+    let mut paths = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dev) else {
+        return paths;
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    for name in names {
+        let gpu = name
+            .strip_prefix("nvidia")
+            .and_then(|index| index.parse::<u32>().ok());
+        if gpu.is_some_and(wanted) || NVIDIA_CONTROL.contains(&name.as_str()) {
+            paths.push(dev.join(&name).to_string_lossy().into_owned());
+        }
+    }
+
+    let dri = dev.join("dri");
+    if let Ok(entries) = std::fs::read_dir(&dri) {
+        let mut nodes: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| {
+                let index = match (name.strip_prefix("card"), name.strip_prefix("renderD")) {
+                    (Some(card), _) => card.parse::<u32>().ok(),
+                    (_, Some(render)) => render
+                        .parse::<u32>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(DRI_RENDER_BASE)),
+                    _ => None,
+                };
+                index.is_some_and(wanted)
+            })
+            .collect();
+        nodes.sort();
+        paths.extend(nodes.into_iter().map(|name| dri.join(name).to_string_lossy().into_owned()));
+    }
+    paths
+}
+
+/// Environment naming the GPUs a server may use
+///
+/// With no selection the parent's own visibility settings are kept.
+pub fn env(selected: &[u32]) -> Vec<(&'static str, String)> {
+    let visible = if selected.is_empty() {
+        match std::env::var("CUDA_VISIBLE_DEVICES") {
+            Ok(visible) => visible,
+            Err(_) => "all".to_string(),
+        }
+    } else {
+        selected
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut env = vec![("NVIDIA_VISIBLE_DEVICES", visible.clone())];
+    if visible != "all" {
+        env.push(("CUDA_VISIBLE_DEVICES", visible));
+    }
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_gpu_devices() {
+        let dev = tempfile::tempdir().unwrap();
+        for name in ["nvidia0", "nvidia1", "nvidiactl", "nvidia-uvm", "null"] {
+            std::fs::write(dev.path().join(name), "").unwrap();
+        }
+        std::fs::create_dir(dev.path().join("dri")).unwrap();
+        for name in ["card0", "card1", "renderD128", "renderD129"] {
+            std::fs::write(dev.path().join("dri").join(name), "").unwrap();
+        }
+
+        let names = |paths: Vec<String>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.strip_prefix(&*dev.path().to_string_lossy()).unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(device_paths_in(dev.path(), &[1])),
+            ["/nvidia-uvm", "/nvidia1", "/nvidiactl", "/dri/card1", "/dri/renderD129"]
+        );
+        assert_eq!(device_paths_in(dev.path(), &[]).len(), 8);
+
+        assert_eq!(
+            env(&[0, 2]),
+            [
+                ("NVIDIA_VISIBLE_DEVICES", "0,2".to_string()),
+                ("CUDA_VISIBLE_DEVICES", "0,2".to_string())
+            ]
+        );
+    }
+}
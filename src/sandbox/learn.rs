@@ -0,0 +1,491 @@
+//! Learning a server's sandbox profile from what it does
+//!
+//! A learning session restarts a stdio server for a while under a
+//! permissive sandbox: the whole filesystem, network, and seccomp in audit
+//! mode. Meanwhile its process tree is sampled for the files it has open
+//! or mapped and the remote ends of its TCP sockets, and [`syscall_audit`]
+//! counts the syscalls outside the allow-list. The report suggests the
+//! tightest `[servers.sandbox]` block that lets the server do the same.
+//! Files are sampled, so ones held open only briefly can be missed,
+//! unless the eBPF [`observer`](super::observer) is running and reports
+//! every open and connect.
+
+use crate::config::{FilesystemAccess, McpServerConfig, SandboxConfig, SeccompMode};
+use crate::sandbox::observer::Activity;
+use crate::sandbox::syscall_audit;
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the server's processes are sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Paths every sandboxed server can read already, or that are not files
+const IMPLICIT_PATHS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/usr",
+    "/etc/ld.so.cache",
+    "/etc/ld.so.conf",
+    "/etc/ld.so.conf.d",
+    "/proc",
+    "/sys",
+    "/dev",
+];
+
+/// `/proc/net/tcp` states of connections the server made
+const TCP_ESTABLISHED: &str = "01";
+const TCP_SYN_SENT: &str = "02";
+
+static SESSIONS: Lazy<DashMap<String, Arc<LearningSession>>> = Lazy::new(DashMap::new);
+
+/// The running or last learning session of a server
+pub fn session(server: &str) -> Option<Arc<LearningSession>> {
+    SESSIONS.get(server).map(|session| session.clone())
+}
+
+/// The config a server runs under while it is being learned
+pub fn permissive(config: &McpServerConfig) -> McpServerConfig {
+    let mut config = config.clone();
+    let sandbox = &mut config.sandbox;
+    sandbox.enabled = true;
+    sandbox.network = true;
+    sandbox.filesystem = FilesystemAccess::Simple("full".to_string());
+    sandbox.seccomp_mode = SeccompMode::Audit;
+    config
+}
+
+/// What a server was seen doing
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Observation {
+    /// Files and directories it had open or mapped
+    pub paths: BTreeSet<String>,
+    /// Those of them open for writing
+    pub written: BTreeSet<String>,
+    /// Remote `address:port` of its TCP connections
+    pub hosts: BTreeSet<String>,
+}
+
+impl Observation {
+    fn merge(&mut self, other: Observation) {
+        self.paths.extend(other.paths);
+        self.written.extend(other.written);
+        self.hosts.extend(other.hosts);
+    }
+}
+
+/// A learning session's findings, for `/admin/v1/servers/{name}/learn`
+#[derive(Debug, Clone, Serialize)]
+pub struct LearnReport {
+    pub server: String,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub finished: bool,
+    /// Whether any process of the server was found to sample; without
+    /// one there is nothing to suggest a sandbox from
+    pub processes_seen: bool,
+    #[serde(flatten)]
+    pub observed: Observation,
+    /// Attempts per syscall number outside the allow-list
+    pub syscalls: BTreeMap<i64, u64>,
+    pub suggested: SandboxConfig,
+    /// `suggested` as a block to paste into the config
+    pub suggested_toml: String,
+}
+
+/// Learning of one server's sandbox profile
+pub struct LearningSession {
+    /// The server's config before the session, restored at its end
+    config: McpServerConfig,
+    started_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    observed: Mutex<Observation>,
+    processes_seen: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl LearningSession {
+    /// Begin learning a server, unless a session for it is running
+    pub fn start(config: McpServerConfig, duration: Duration) -> McpResult<Arc<Self>> {
+        if !cfg!(target_os = "linux") {
+            return Err(McpError::InvalidRequest(
+                "Sandbox learning is only available on Linux".to_string(),
+            ));
+        }
+        if config.url.is_some() || config.named_pipe.is_some() || config.ssh.is_some() {
+            return Err(McpError::InvalidRequest(format!(
+                "{} is not a stdio server; only those are sandboxed",
+                config.name
+            )));
+        }
+        if duration.is_zero() {
+            return Err(McpError::InvalidRequest(
+                "Learning needs a duration".to_string(),
+            ));
+        }
+        let duration = chrono::Duration::from_std(duration)
+            .map_err(|_| McpError::InvalidRequest("Learning duration is too long".to_string()))?;
+
+        let started_at = Utc::now();
+        let session = Arc::new(Self {
+            config,
+            started_at,
+            ends_at: started_at + duration,
+            observed: Mutex::new(Observation::default()),
+            processes_seen: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        });
+        match SESSIONS.entry(session.config.name.clone()) {
+            dashmap::mapref::entry::Entry::Occupied(entry) if !entry.get().finished() => {
+                Err(McpError::InvalidRequest(format!(
+                    "{} is already being learned until {}",
+                    entry.key(),
+                    entry.get().ends_at.to_rfc3339()
+                )))
+            }
+            entry => {
+                entry.insert(session.clone());
+                Ok(session)
+            }
+        }
+    }
+
+    /// The server's own config, to restore once done
+    pub fn original(&self) -> &McpServerConfig {
+        &self.config
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// End the session early, or mark it ended
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Add something the server was seen doing outside of sampling
+    pub fn record(&self, activity: &Activity) {
+        let mut observed = self.observed.lock();
+        match activity {
+            Activity::Open { path, written } => {
+                if *written {
+                    observed.written.insert(path.clone());
+                }
+                observed.paths.insert(path.clone());
+            }
+            Activity::Connect { endpoint } => {
+                observed.hosts.insert(endpoint.clone());
+            }
+        }
+    }
+
+    /// Sample the server's processes until the session ends
+    pub async fn observe(&self) {
+        let name = self.config.name.clone();
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        while !self.finished() && Utc::now() < self.ends_at {
+            interval.tick().await;
+            let name = name.clone();
+            let sampled = tokio::task::spawn_blocking(move || {
+                let pids = syscall_audit::global().processes(&name);
+                (!pids.is_empty()).then(|| sample(&pids))
+            })
+            .await;
+            if let Ok(Some(sampled)) = sampled {
+                self.processes_seen.store(true, Ordering::Relaxed);
+                self.observed.lock().merge(sampled);
+            }
+        }
+    }
+
+    pub fn report(&self) -> LearnReport {
+        let observed = self.observed.lock().clone();
+        let syscalls = syscall_audit::global().report(&self.config);
+        let suggested = suggest(&self.config.sandbox, &observed, &syscalls.suggested.seccomp_allow);
+        LearnReport {
+            server: self.config.name.clone(),
+            started_at: self.started_at,
+            ends_at: self.ends_at,
+            finished: self.finished(),
+            processes_seen: self.processes_seen.load(Ordering::Relaxed),
+            suggested_toml: render(&self.config.name, &suggested),
+            observed,
+            syscalls: syscalls.syscalls,
+            suggested,
+        }
+    }
+}
+
+/// The server's sandbox narrowed to what was observed
+fn suggest(current: &SandboxConfig, observed: &Observation, seccomp_allow: &[i64]) -> SandboxConfig {
+    let mut sandbox = current.clone();
+    sandbox.enabled = true;
+    sandbox.network = !observed.hosts.is_empty();
+    sandbox.filesystem = FilesystemAccess::Paths(granted_paths(&observed.paths));
+    sandbox.allow_gpu = observed
+        .paths
+        .iter()
+        .any(|path| path.starts_with("/dev/nvidia") || path.starts_with("/dev/dri/"));
+    if !sandbox.allow_gpu {
+        sandbox.gpus.clear();
+    }
+    sandbox.seccomp_mode = SeccompMode::Enforce;
+    sandbox.seccomp_allow = seccomp_allow.to_vec();
+    sandbox
+}
+
+/// Directories to grant for the paths used: a file's directory, or the
+/// directory itself, leaving out what is granted anyway and what is
+/// covered by another entry
+fn granted_paths(paths: &BTreeSet<String>) -> Vec<String> {
+    let implicit = |path: &str| {
+        IMPLICIT_PATHS
+            .iter()
+            .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+    };
+    let dirs: BTreeSet<String> = paths
+        .iter()
+        .filter(|path| !implicit(path))
+        .filter_map(|path| {
+            if Path::new(path).is_dir() {
+                return Some(path.clone());
+            }
+            let parent = Path::new(path).parent()?.to_string_lossy().into_owned();
+            (!implicit(&parent)).then_some(parent)
+        })
+        .collect();
+
+    let mut granted: Vec<String> = Vec::new();
+    for dir in dirs {
+        // Sorted, so a covering directory comes before what it covers
+        let covered = granted
+            .iter()
+            .any(|parent| parent == "/" || dir.starts_with(&format!("{}/", parent)));
+        if !covered {
+            granted.push(dir);
+        }
+    }
+    granted
+}
+
+/// The suggested sandbox as a config block
+fn render(server: &str, sandbox: &SandboxConfig) -> String {
+    #[derive(Serialize)]
+    struct Server<'a> {
+        name: &'a str,
+        sandbox: &'a SandboxConfig,
+    }
+    #[derive(Serialize)]
+    struct Servers<'a> {
+        servers: [Server<'a>; 1],
+    }
+    toml::to_string(&Servers {
+        servers: [Server { name: server, sandbox }],
+    })
+    .unwrap_or_default()
+}
+
+/// Files and connections of some processes right now
+fn sample(pids: &[u32]) -> Observation {
+    let mut observed = Observation::default();
+    for pid in pids {
+        let proc = format!("/proc/{}", pid);
+        if let Ok(exe) = std::fs::read_link(format!("{}/exe", proc)) {
+            observed.paths.insert(exe.to_string_lossy().into_owned());
+        }
+        if let Ok(maps) = std::fs::read_to_string(format!("{}/maps", proc)) {
+            observed.paths.extend(mapped_files(&maps));
+        }
+
+        let mut sockets = HashSet::new();
+        let Ok(fds) = std::fs::read_dir(format!("{}/fd", proc)) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy().into_owned();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|inode| inode.strip_suffix(']'))
+            {
+                sockets.insert(inode.to_string());
+                continue;
+            }
+            if !target.starts_with('/') {
+                continue;
+            }
+            let fdinfo = format!("{}/fdinfo/{}", proc, fd.file_name().to_string_lossy());
+            if std::fs::read_to_string(fdinfo).is_ok_and(|info| opened_for_writing(&info)) {
+                observed.written.insert(target.clone());
+            }
+            observed.paths.insert(target);
+        }
+
+        if !sockets.is_empty() {
+            for table in ["tcp", "tcp6"] {
+                if let Ok(table) = std::fs::read_to_string(format!("{}/net/{}", proc, table)) {
+                    observed.hosts.extend(remote_endpoints(&table, &sockets));
+                }
+            }
+        }
+    }
+    observed
+}
+
+/// Files mapped into a process, from `/proc/<pid>/maps`
+fn mapped_files(maps: &str) -> impl Iterator<Item = String> + '_ {
+    maps.lines().filter_map(|line| {
+        // address perms offset dev inode path, the path possibly with spaces
+        let path = line.splitn(6, char::is_whitespace).nth(5)?.trim();
+        let path = path.strip_suffix(" (deleted)").unwrap_or(path);
+        path.starts_with('/').then(|| path.to_string())
+    })
+}
+
+/// Whether an `fdinfo` entry's `flags` grant writing
+fn opened_for_writing(fdinfo: &str) -> bool {
+    fdinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("flags:"))
+        .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+        .is_some_and(|flags| flags & 0o3 != 0)
+}
+
+/// Remote ends of the connections in a `/proc/net/tcp{,6}` table made
+/// through the given socket inodes
+fn remote_endpoints<'a>(
+    table: &'a str,
+    sockets: &'a HashSet<String>,
+) -> impl Iterator<Item = String> + 'a {
+    table.lines().skip(1).filter_map(move |line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (remote, state, inode) = (fields.get(2)?, fields.get(3)?, fields.get(9)?);
+        if !sockets.contains(*inode) || (*state != TCP_ESTABLISHED && *state != TCP_SYN_SENT) {
+            return None;
+        }
+        let (address, port) = remote.split_once(':')?;
+        let port = u16::from_str_radix(port, 16).ok()?;
+        // The kernel prints each 32-bit word of the address in host order
+        let word = |i: usize| {
+            u32::from_str_radix(address.get(i * 8..i * 8 + 8)?, 16)
+                .ok()
+                .map(u32::to_ne_bytes)
+        };
+        match address.len() {
+            8 => Some(format!("{}:{}", Ipv4Addr::from(word(0)?), port)),
+            32 => {
+                let mut bytes = [0u8; 16];
+                for i in 0..4 {
+                    bytes[i * 4..i * 4 + 4].copy_from_slice(&word(i)?);
+                }
+                let address = Ipv6Addr::from(bytes);
+                Some(match address.to_ipv4_mapped() {
+                    Some(v4) => format!("{}:{}", v4, port),
+                    None => format!("[{}]:{}", address, port),
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_parsing() {
+        let maps = "7f00-7f01 r-xp 00000000 08:01 1234 /usr/lib/libc.so.6\n\
+                    7f01-7f02 rw-p 00000000 00:00 0 [heap]\n\
+                    7f02-7f03 r--p 00000000 08:01 99 /opt/my app/data.bin (deleted)\n";
+        assert_eq!(
+            mapped_files(maps).collect::<Vec<_>>(),
+            ["/usr/lib/libc.so.6", "/opt/my app/data.bin"]
+        );
+
+        assert!(opened_for_writing("pos:\t0\nflags:\t0100002\nmnt_id:\t25\n"));
+        assert!(!opened_for_writing("pos:\t0\nflags:\t02100000\nmnt_id:\t25\n"));
+
+        let sockets = HashSet::from(["4242".to_string()]);
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+                   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4241\n\
+                   1: 0100007F:C350 0100007F:01BB 01 00000000:00000000 00:00000000 00000000  1000        0 4242\n";
+        let remote: Vec<String> = remote_endpoints(tcp, &sockets).collect();
+        if cfg!(target_endian = "little") {
+            assert_eq!(remote, ["127.0.0.1:443"]);
+        }
+    }
+
+    #[test]
+    fn test_suggested_sandbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data");
+        std::fs::create_dir(&data).unwrap();
+        let root = dir.path().to_string_lossy().into_owned();
+        let observed = Observation {
+            paths: BTreeSet::from([
+                "/usr/lib/libc.so.6".to_string(),
+                "/dev/null".to_string(),
+                format!("{}/tool", root),
+                data.to_string_lossy().into_owned(),
+                format!("{}/data/cache.db", root),
+            ]),
+            hosts: BTreeSet::from(["93.184.216.34:443".to_string()]),
+            ..Default::default()
+        };
+
+        let sandbox = suggest(&SandboxConfig::default(), &observed, &[165]);
+        assert!(sandbox.network);
+        assert!(!sandbox.allow_gpu);
+        assert_eq!(sandbox.seccomp_mode, SeccompMode::Enforce);
+        assert_eq!(sandbox.seccomp_allow, [165]);
+        let FilesystemAccess::Paths(paths) = &sandbox.filesystem else {
+            panic!("expected explicit paths");
+        };
+        assert_eq!(paths, &[root]);
+
+        let toml = render("files", &sandbox);
+        assert!(toml.contains("[servers.sandbox]"));
+        assert!(toml.contains("seccomp_allow = [165]"));
+    }
+
+    /// A server spawned under the permissive sandbox is found and sampled
+    #[tokio::test]
+    async fn test_session_samples_server() {
+        let mut config = McpServerConfig {
+            name: "learned".to_string(),
+            command: "sleep".to_string(),
+            args: vec!["10".to_string()],
+            ..Default::default()
+        };
+        config.sandbox.env_inherit = true;
+        let session = LearningSession::start(config, Duration::from_secs(1)).unwrap();
+        assert!(!session.report().processes_seen);
+
+        let permissive = permissive(session.original());
+        let mut child = crate::sandbox::create_sandbox(&permissive)
+            .spawn(&permissive)
+            .await
+            .unwrap();
+        session.observe().await;
+        let _ = child.kill().await;
+
+        let report = session.report();
+        assert!(report.processes_seen);
+        assert!(report.observed.paths.iter().any(|path| path.ends_with("/sleep")));
+    }
+}
@@ -0,0 +1,106 @@
+//! Resource limit settings shared by the sandbox implementations
+
+use crate::utils::errors::{McpError, McpResult};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Highest `io.weight` cgroups v2 accepts
+pub const MAX_IO_WEIGHT: u16 = 10_000;
+
+/// cgroups v2 directory that server cgroups are created under
+pub const CGROUP_BASE: &str = "/sys/fs/cgroup/super-mcp";
+
+/// CPUs named by a cpuset list such as `"0-3,8"`, in ascending order
+pub fn parse_cpu_list(spec: &str) -> Result<Vec<usize>, String> {
+    let mut cpus = BTreeSet::new();
+    for part in spec.split(',').map(str::trim) {
+        let cpu = |s: &str| {
+            s.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid CPU '{}' in cpuset '{}'", s.trim(), spec))
+        };
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (cpu(first)?, cpu(last)?);
+                if first > last {
+                    return Err(format!("Invalid CPU range '{}' in cpuset '{}'", part, spec));
+                }
+                cpus.extend(first..=last);
+            }
+            None => {
+                cpus.insert(cpu(part)?);
+            }
+        }
+    }
+    Ok(cpus.into_iter().collect())
+}
+
+/// Cap open files per process and processes of the user, for the calling
+/// process and whatever it execs or forks
+///
+/// Runs in `pre_exec`, so it makes no allocations.
+#[cfg(unix)]
+pub fn apply_rlimits(max_open_files: Option<u64>, max_processes: Option<u64>) -> std::io::Result<()> {
+    let set = |resource, limit: u64| {
+        let limit = libc::rlimit {
+            rlim_cur: limit as libc::rlim_t,
+            rlim_max: limit as libc::rlim_t,
+        };
+        // SAFETY: setrlimit only reads the struct passed to it
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    if let Some(limit) = max_open_files {
+        set(libc::RLIMIT_NOFILE, limit)?;
+    }
+    if let Some(limit) = max_processes {
+        set(libc::RLIMIT_NPROC, limit)?;
+    }
+    Ok(())
+}
+
+/// Move a process into its server's cgroup, creating it with the given
+/// controller files written (e.g. `("io.weight", "default 50")`)
+///
+/// Controllers are delegated from the root first, best effort, as they may
+/// already be enabled or be unavailable.
+pub fn join_cgroup(server: &str, pid: u32, settings: &[(&str, String)]) -> McpResult<PathBuf> {
+    let base = PathBuf::from(CGROUP_BASE);
+    let path = base.join(server);
+    std::fs::create_dir_all(&path)
+        .map_err(|e| McpError::SandboxError(format!("Failed to create cgroup: {}", e)))?;
+
+    for parent in [PathBuf::from("/sys/fs/cgroup"), base] {
+        let control = parent.join("cgroup.subtree_control");
+        for (file, _) in settings {
+            let controller = file.split('.').next().unwrap_or_default();
+            if let Err(e) = std::fs::write(&control, format!("+{}", controller)) {
+                debug!("Could not enable {} in {:?}: {}", controller, control, e);
+            }
+        }
+    }
+    for (file, value) in settings {
+        std::fs::write(path.join(file), value)
+            .map_err(|e| McpError::SandboxError(format!("Failed to set {}: {}", file, e)))?;
+    }
+    std::fs::write(path.join("cgroup.procs"), pid.to_string())
+        .map_err(|e| McpError::SandboxError(format!("Failed to join cgroup: {}", e)))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8").unwrap(), vec![0, 1, 2, 3, 8]);
+        assert_eq!(parse_cpu_list("2, 1,2").unwrap(), vec![1, 2]);
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("0-x").is_err());
+    }
+}
@@ -14,16 +14,15 @@
 pub use linux_full::{LinuxSandboxFull as LinuxSandbox, SandboxAvailabilityReport, NamespaceSupport, create_best_effort_sandbox};
 
 // Submodules
+#[path = "linux_seccomp.rs"]
 mod linux_seccomp;
+#[path = "linux_landlock.rs"]
 mod linux_landlock;
+#[path = "linux_full.rs"]
 mod linux_full;
 
-use crate::config::McpServerConfig;
-use crate::sandbox::traits::{FilesystemConstraint, Sandbox, SandboxConstraints};
-use crate::utils::errors::McpResult;
-use async_trait::async_trait;
-use tokio::process::Child;
-
 // Re-export key functions for convenience
-pub use linux_seccomp::{apply_seccomp_filter, apply_restrictive_seccomp, is_seccomp_available};
-pub use linux_landlock::{apply_landlock_restrictions, is_landlock_available, get_landlock_status};
+pub use linux_seccomp::{apply_seccomp_filter, apply_restrictive_seccomp, get_seccomp_mode, is_seccomp_available};
+pub use linux_landlock::{
+    apply_landlock_restrictions, apply_landlock_with_rights, get_landlock_status, is_landlock_available,
+};
@@ -15,7 +15,7 @@ use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use std::path::PathBuf;
 use tokio::process::Child;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
 /// Advanced Linux sandbox configuration
 #[derive(Debug, Clone)]
@@ -63,7 +63,6 @@ impl Default for AdvancedLinuxSandboxConfig {
 pub struct AdvancedLinuxSandbox {
     constraints: SandboxConstraints,
     config: AdvancedLinuxSandboxConfig,
-    cgroup_path: Option<PathBuf>,
 }
 
 impl AdvancedLinuxSandbox {
@@ -71,18 +70,7 @@ impl AdvancedLinuxSandbox {
     pub fn from_config(server_config: &McpServerConfig) -> Self {
         let constraints = SandboxConstraints {
             network: server_config.sandbox.network,
-            filesystem: match &server_config.sandbox.filesystem {
-                crate::config::FilesystemAccess::Simple(s) if s == "readonly" => {
-                    FilesystemConstraint::ReadOnly
-                }
-                crate::config::FilesystemAccess::Simple(s) if s == "full" => {
-                    FilesystemConstraint::Full
-                }
-                crate::config::FilesystemAccess::Paths(paths) => {
-                    FilesystemConstraint::Paths(paths.clone())
-                }
-                _ => FilesystemConstraint::ReadOnly,
-            },
+            filesystem: FilesystemConstraint::from_config(&server_config.sandbox),
             env_inherit: server_config.sandbox.env_inherit,
             max_memory_mb: server_config.sandbox.max_memory_mb,
             max_cpu_percent: server_config.sandbox.max_cpu_percent,
@@ -96,7 +84,6 @@ impl AdvancedLinuxSandbox {
         Self {
             constraints,
             config: sandbox_config,
-            cgroup_path: None,
         }
     }
 
@@ -115,11 +102,12 @@ impl AdvancedLinuxSandbox {
 
     /// Setup cgroups for resource limits
     #[cfg(target_os = "linux")]
-    async fn setup_cgroups(&self, cgroup_name: &str) -> McpResult<PathBuf> {
+    async fn setup_cgroups(&self, server_config: &McpServerConfig) -> McpResult<PathBuf> {
         use std::fs;
         
-        let cgroup_base = PathBuf::from("/sys/fs/cgroup/super-mcp");
-        let cgroup_path = cgroup_base.join(cgroup_name);
+        let cgroup_base = PathBuf::from(crate::sandbox::limits::CGROUP_BASE);
+        let cgroup_path = cgroup_base.join(&server_config.name);
+        let sandbox = &server_config.sandbox;
 
         // Create cgroup directory
         tokio::fs::create_dir_all(&cgroup_path).await.map_err(|e| {
@@ -137,220 +125,73 @@ impl AdvancedLinuxSandbox {
         let cpu_max_path = cgroup_path.join("cpu.max");
         if cpu_max_path.exists() {
             // cpu.max format: "quota period" (e.g., "50000 100000" for 50%)
-            let quota = (self.constraints.max_cpu_percent as u64 * 1000) as u64;
+            let quota = self.constraints.max_cpu_percent as u64 * 1000;
             let period = 100000u64;
             fs::write(&cpu_max_path, format!("{} {}", quota, period)).map_err(|e| {
                 McpError::SandboxError(format!("Failed to set CPU limit: {}", e))
             })?;
         }
 
-        // Enable memory accounting
-        let memory_stat_path = cgroup_path.join("memory.stat");
-        if memory_stat_path.exists() {
-            debug!("Cgroup memory accounting enabled");
-        }
-
-        info!("Created cgroup at {:?}", cgroup_path);
-        Ok(cgroup_path)
-    }
-
-    #[cfg(not(target_os = "linux"))]
-    async fn setup_cgroups(&self, _cgroup_name: &str) -> McpResult<PathBuf> {
-        Err(McpError::SandboxError("cgroups only available on Linux".to_string()))
-    }
-
-    /// Cleanup cgroups
-    #[cfg(target_os = "linux")]
-    async fn cleanup_cgroups(&self, cgroup_path: &PathBuf) -> McpResult<()> {
-        // Kill all processes in the cgroup
-        let procs_path = cgroup_path.join("cgroup.procs");
-        if procs_path.exists() {
-            if let Ok(procs) = std::fs::read_to_string(&procs_path) {
-                for pid in procs.lines() {
-                    if let Ok(pid) = pid.parse::<i32>() {
-                        let _ = nix::sys::signal::kill(
-                            nix::unistd::Pid::from_raw(pid),
-                            nix::sys::signal::SIGKILL,
-                        );
+        // Pinning and IO weight need their controllers delegated to our
+        // cgroups; best effort, as the controllers may already be enabled
+        if sandbox.cpuset.is_some() || sandbox.io_weight.is_some() {
+            for parent in [PathBuf::from("/sys/fs/cgroup"), cgroup_base.clone()] {
+                let control = parent.join("cgroup.subtree_control");
+                for controller in ["+cpuset", "+io"] {
+                    if let Err(e) = fs::write(&control, controller) {
+                        debug!("Could not enable {} in {:?}: {}", controller, control, e);
                     }
                 }
             }
         }
 
-        // Remove cgroup directory
-        tokio::fs::remove_dir(cgroup_path).await.map_err(|e| {
-            McpError::SandboxError(format!("Failed to remove cgroup: {}", e))
-        })?;
-
-        Ok(())
-    }
-
-    /// Build namespace flags for clone syscall
-    #[cfg(target_os = "linux")]
-    fn build_namespace_flags(&self) -> nix::sched::CloneFlags {
-        use nix::sched::CloneFlags;
-
-        let mut flags = CloneFlags::empty();
-
-        if self.config.use_user_namespace {
-            flags |= CloneFlags::CLONE_NEWUSER;
-        }
-        if self.config.use_pid_namespace {
-            flags |= CloneFlags::CLONE_NEWPID;
-        }
-        if self.config.use_network_namespace {
-            flags |= CloneFlags::CLONE_NEWNET;
-        }
-        if self.config.use_mount_namespace {
-            flags |= CloneFlags::CLONE_NEWNS;
-        }
-        if self.config.use_ipc_namespace {
-            flags |= CloneFlags::CLONE_NEWIPC;
+        // Cap processes and threads
+        if let Some(max) = sandbox.max_processes {
+            let pids_max_path = cgroup_path.join("pids.max");
+            if pids_max_path.exists() {
+                fs::write(&pids_max_path, max.to_string()).map_err(|e| {
+                    McpError::SandboxError(format!("Failed to set process limit: {}", e))
+                })?;
+            } else {
+                warn!("pids.max is unavailable, {} is limited by RLIMIT_NPROC only", server_config.name);
+            }
         }
 
-        flags
-    }
-
-    /// Setup user namespace mapping
-    #[cfg(target_os = "linux")]
-    fn setup_uid_map(&self, pid: u32) -> McpResult<()> {
-        use std::fs;
-        
-        // Map current user to root (0) inside namespace
-        let uid_map = format!("0 {} 1\n", std::process::id());
-        let uid_map_path = format!("/proc/{}/uid_map", pid);
-        
-        fs::write(&uid_map_path, uid_map).map_err(|e| {
-            McpError::SandboxError(format!("Failed to write uid_map: {}", e))
-        })?;
-
-        // Disable setgroups (required for unprivileged user namespaces)
-        let setgroups_path = format!("/proc/{}/setgroups", pid);
-        let _ = fs::write(&setgroups_path, "deny");
-
-        // Map groups
-        let gid_map = format!("0 {} 1\n", std::process::id());
-        let gid_map_path = format!("/proc/{}/gid_map", pid);
-        
-        fs::write(&gid_map_path, gid_map).map_err(|e| {
-            McpError::SandboxError(format!("Failed to write gid_map: {}", e))
-        })?;
-
-        Ok(())
-    }
+        // Pin to CPUs
+        if let Some(cpus) = &sandbox.cpuset {
+            fs::write(cgroup_path.join("cpuset.cpus"), cpus).map_err(|e| {
+                McpError::SandboxError(format!("Failed to set cpuset {}: {}", cpus, e))
+            })?;
+        }
 
-    /// Generate seccomp filter
-    #[cfg(target_os = "linux")]
-    fn generate_seccomp_filter(&self) -> McpResult<seccompiler::BpfProgram> {
-        use seccompiler::*;
-
-        let mut rules = BTreeMap::new();
-
-        // Allow basic syscalls
-        let allowed_syscalls = vec![
-            "read", "write", "open", "close", "stat", "fstat", "lstat",
-            "poll", "lseek", "mmap", "mprotect", "munmap", "brk",
-            "rt_sigaction", "rt_sigprocmask", "rt_sigreturn", "ioctl",
-            "pread64", "pwrite64", "readv", "writev", "access", "pipe",
-            "select", "sched_yield", "mremap", "msync", "mincore",
-            "madvise", "shmget", "shmat", "shmctl", "dup", "dup2",
-            "pause", "nanosleep", "getitimer", "alarm", "setitimer",
-            "getpid", "sendfile", "socket", "connect", "accept",
-            "sendto", "recvfrom", "sendmsg", "recvmsg", "shutdown",
-            "bind", "listen", "getsockname", "getpeername", "socketpair",
-            "setsockopt", "getsockopt", "clone", "fork", "vfork",
-            "execve", "exit", "wait4", "kill", "uname", "semget",
-            "semop", "semctl", "shmdt", "msgget", "msgsnd", "msgrcv",
-            "msgctl", "fcntl", "flock", "fsync", "fdatasync", "truncate",
-            "ftruncate", "getdents", "getcwd", "chdir", "fchdir",
-            "rename", "mkdir", "rmdir", "creat", "link", "unlink",
-            "symlink", "readlink", "chmod", "fchmod", "chown", "fchown",
-            "lchown", "umask", "gettimeofday", "getrlimit", "getrusage",
-            "sysinfo", "times", "ptrace", "getuid", "syslog", "getgid",
-            "setuid", "setgid", "geteuid", "getegid", "setpgid",
-            "getppid", "getpgrp", "setsid", "setreuid", "setregid",
-            "getgroups", "setgroups", "setresuid", "getresuid",
-            "setresgid", "getresgid", "getpgid", "setfsuid", "setfsgid",
-            "getsid", "capget", "capset", "rt_sigpending", "rt_sigtimedwait",
-            "rt_sigqueueinfo", "rt_sigsuspend", "sigaltstack", "utime",
-            "mknod", "uselib", "personality", "ustat", "statfs", "fstatfs",
-            "sysfs", "getpriority", "setpriority", "sched_setparam",
-            "sched_getparam", "sched_setscheduler", "sched_getscheduler",
-            "sched_get_priority_max", "sched_get_priority_min",
-            "sched_rr_get_interval", "mlock", "munlock", "mlockall",
-            "munlockall", "vhangup", "modify_ldt", "pivot_root",
-            "_sysctl", "prctl", "arch_prctl", "adjtimex", "setrlimit",
-            "chroot", "sync", "acct", "settimeofday", "mount", "umount2",
-            "swapon", "swapoff", "reboot", "sethostname", "setdomainname",
-            "iopl", "ioperm", "create_module", "init_module", "delete_module",
-            "get_kernel_syms", "query_module", "quotactl", "nfsservctl",
-            "getpmsg", "putpmsg", "afs_syscall", "tuxcall", "security",
-            "gettid", "readahead", "setxattr", "lsetxattr", "fsetxattr",
-            "getxattr", "lgetxattr", "fgetxattr", "listxattr", "llistxattr",
-            "flistxattr", "removexattr", "lremovexattr", "fremovexattr",
-            "tkill", "time", "futex", "sched_setaffinity", "sched_getaffinity",
-            "set_thread_area", "io_setup", "io_destroy", "io_getevents",
-            "io_submit", "io_cancel", "get_thread_area", "lookup_dcookie",
-            "epoll_create", "epoll_ctl_old", "epoll_wait_old", "remap_file_pages",
-            "getdents64", "set_tid_address", "restart_syscall", "semtimedop",
-            "fadvise64", "timer_create", "timer_settime", "timer_gettime",
-            "timer_getoverrun", "timer_delete", "clock_settime", "clock_gettime",
-            "clock_getres", "clock_nanosleep", "exit_group", "epoll_wait",
-            "epoll_ctl", "tgkill", "utimes", "vserver", "mbind",
-            "set_mempolicy", "get_mempolicy", "mq_open", "mq_unlink",
-            "mq_timedsend", "mq_timedreceive", "mq_notify", "mq_getsetattr",
-            "kexec_load", "waitid", "add_key", "request_key", "keyctl",
-            "ioprio_set", "ioprio_get", "inotify_init", "inotify_add_watch",
-            "inotify_rm_watch", "migrate_pages", "openat", "mkdirat",
-            "mknodat", "fchownat", "futimesat", "newfstatat", "unlinkat",
-            "renameat", "linkat", "symlinkat", "readlinkat", "fchmodat",
-            "faccessat", "pselect6", "ppoll", "unshare", "set_robust_list",
-            "get_robust_list", "splice", "tee", "sync_file_range", "vmsplice",
-            "move_pages", "utimensat", "epoll_pwait", "signalfd", "timerfd_create",
-            "eventfd", "fallocate", "timerfd_settime", "timerfd_gettime",
-            "accept4", "signalfd4", "eventfd2", "epoll_create1", "dup3",
-            "pipe2", "inotify_init1", "preadv", "pwritev", "rt_tgsigqueueinfo",
-            "perf_event_open", "recvmmsg", "fanotify_init", "fanotify_mark",
-            "prlimit64", "name_to_handle_at", "open_by_handle_at",
-            "clock_adjtime", "syncfs", "sendmmsg", "setns", "getcpu",
-            "process_vm_readv", "process_vm_writev", "kcmp", "finit_module",
-            "sched_setattr", "sched_getattr", "renameat2", "seccomp",
-            "getrandom", "memfd_create", "kexec_file_load", "bpf",
-            "execveat", "userfaultfd", "membarrier", "mlock2",
-            "copy_file_range", "preadv2", "pwritev2", "pkey_mprotect",
-            "pkey_alloc", "pkey_free", "statx", "io_pgetevents", "rseq",
-        ];
-
-        for syscall in allowed_syscalls {
-            rules.insert(
-                syscall.to_string(),
-                SeccompRule::new(vec![], SeccompAction::Allow),
-            );
+        // Weight disk IO against other servers
+        if let Some(weight) = sandbox.io_weight {
+            let io_weight_path = cgroup_path.join("io.weight");
+            if io_weight_path.exists() {
+                fs::write(&io_weight_path, format!("default {}", weight)).map_err(|e| {
+                    McpError::SandboxError(format!("Failed to set IO weight: {}", e))
+                })?;
+            } else {
+                warn!(
+                    "io.weight is unavailable, {} runs without an IO weight",
+                    server_config.name
+                );
+            }
         }
 
-        // Deny dangerous syscalls
-        let denied_syscalls = vec![
-            "open_by_handle_at", // Can bypass directory restrictions
-            "ptrace",            // Process debugging
-            "process_vm_writev", // Cross-process memory write
-        ];
-
-        for syscall in denied_syscalls {
-            rules.insert(
-                syscall.to_string(),
-                SeccompRule::new(vec![], SeccompAction::Errno(1)), // EPERM
-            );
+        // Enable memory accounting
+        let memory_stat_path = cgroup_path.join("memory.stat");
+        if memory_stat_path.exists() {
+            debug!("Cgroup memory accounting enabled");
         }
 
-        let filter = SeccompFilter::new(
-            rules,
-            SeccompAction::Trap, // Default action for unknown syscalls
-            SeccompArch::X86_64,
-        ).map_err(|e| McpError::SandboxError(format!("Failed to create seccomp filter: {:?}", e)))?;
+        info!("Created cgroup at {:?}", cgroup_path);
+        Ok(cgroup_path)
+    }
 
-        filter.compile().map_err(|e| {
-            McpError::SandboxError(format!("Failed to compile seccomp filter: {:?}", e))
-        })
+    #[cfg(not(target_os = "linux"))]
+    async fn setup_cgroups(&self, _server_config: &McpServerConfig) -> McpResult<PathBuf> {
+        Err(McpError::SandboxError("cgroups only available on Linux".to_string()))
     }
 }
 
@@ -365,7 +206,7 @@ impl Sandbox for AdvancedLinuxSandbox {
 
         // Setup cgroups if enabled
         let cgroup_path = if self.config.use_cgroups {
-            Some(self.setup_cgroups(&config.name).await?)
+            Some(self.setup_cgroups(config).await?)
         } else {
             None
         };
@@ -387,6 +228,12 @@ impl Sandbox for AdvancedLinuxSandbox {
             cmd.env_clear();
         }
 
+        if config.sandbox.allow_gpu {
+            for (key, value) in crate::sandbox::gpu::env(&config.sandbox.gpus) {
+                cmd.env(key, value);
+            }
+        }
+
         for (key, value) in &config.env {
             cmd.env(key, value);
         }
@@ -395,6 +242,12 @@ impl Sandbox for AdvancedLinuxSandbox {
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
 
+        let (max_open_files, max_processes) = (config.sandbox.max_open_files, config.sandbox.max_processes);
+        // SAFETY: the hook only calls setrlimit
+        unsafe {
+            cmd.pre_exec(move || crate::sandbox::limits::apply_rlimits(max_open_files, max_processes));
+        }
+
         let child = cmd.spawn().map_err(|e| {
             McpError::SandboxError(format!("Failed to spawn sandboxed process: {}", e))
         })?;
@@ -444,6 +297,7 @@ mod tests {
                 max_cpu_percent: 25,
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let sandbox = AdvancedLinuxSandbox::from_config(&server_config);
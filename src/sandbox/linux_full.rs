@@ -8,14 +8,15 @@
 //! The sandbox is designed to be defense-in-depth, with multiple layers
 //! of protection.
 
-use crate::config::McpServerConfig;
+use super::{linux_landlock, linux_seccomp};
+use crate::config::{McpServerConfig, SeccompMode};
+use crate::sandbox::{gpu, observer, syscall_audit};
+use crate::sandbox::limits::{apply_rlimits, join_cgroup, parse_cpu_list};
 use crate::sandbox::traits::{FilesystemConstraint, Sandbox, SandboxConstraints};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
-use nix::sched::{unshare, CloneFlags};
-use nix::unistd::{Gid, Group, Uid, User};
-use nix::unistd::setgroups;
-use std::os::unix::process::CommandExt;
+use nix::sched::{sched_setaffinity, unshare, CloneFlags, CpuSet};
+use nix::unistd::{setgid, setgroups, setuid, Pid, Uid, User};
 use tokio::process::{Child, Command};
 use tracing::{debug, info, warn};
 
@@ -27,170 +28,161 @@ use tracing::{debug, info, warn};
 /// - Landlock for filesystem access control
 pub struct LinuxSandboxFull {
     constraints: SandboxConstraints,
+    /// Kernel features the layers are applied with
+    available: SandboxAvailabilityReport,
 }
 
 impl LinuxSandboxFull {
     /// Create a new Linux sandbox with the given constraints
     pub fn new(constraints: SandboxConstraints) -> Self {
-        Self { constraints }
+        Self {
+            constraints,
+            available: Self::check_availability(),
+        }
+    }
+
+    /// Apply only the layers `available` reports, as on a kernel without
+    /// the others
+    pub fn with_availability(mut self, available: SandboxAvailabilityReport) -> Self {
+        self.available = available;
+        self
     }
 
     /// Create a sandbox from an MCP server configuration
     pub fn from_config(config: &McpServerConfig) -> Self {
-        let filesystem = match &config.sandbox.filesystem {
-            crate::sandbox::traits::FilesystemConstraint::Full => FilesystemConstraint::Full,
-            crate::sandbox::traits::FilesystemConstraint::ReadOnly => FilesystemConstraint::ReadOnly,
-            crate::sandbox::traits::FilesystemConstraint::Paths(paths) => {
-                FilesystemConstraint::Paths(paths.clone())
-            }
-        };
-
-        Self {
-            constraints: SandboxConstraints {
-                network: config.sandbox.network,
-                filesystem,
-                env_inherit: config.sandbox.env_inherit,
-                max_memory_mb: config.sandbox.max_memory_mb,
-                max_cpu_percent: config.sandbox.max_cpu_percent,
-            },
-        }
+        let filesystem = FilesystemConstraint::from_config(&config.sandbox);
+
+        Self::new(SandboxConstraints {
+            network: config.sandbox.network,
+            filesystem,
+            env_inherit: config.sandbox.env_inherit,
+            max_memory_mb: config.sandbox.max_memory_mb,
+            max_cpu_percent: config.sandbox.max_cpu_percent,
+        })
     }
 
-    /// Apply Linux namespaces for isolation
+    /// Linux namespaces for isolation
     ///
-    /// This function uses unshare() to create new namespaces for:
     /// - Mount (CLONE_NEWNS): Filesystem isolation
-    /// - PID (CLONE_NEWPID): Process ID isolation
     /// - IPC (CLONE_NEWIPC): Inter-process communication isolation
     /// - Network (CLONE_NEWNET): Network isolation (if network disabled)
     /// - UTS (CLONE_NEWUTS): Hostname isolation
-    fn apply_namespaces(&self) -> Result<(), nix::Error> {
-        let mut flags = CloneFlags::empty();
-
-        // Mount namespace for filesystem isolation
-        flags |= CloneFlags::CLONE_NEWNS;
-        debug!("Enabling mount namespace (CLONE_NEWNS)");
-
-        // PID namespace for process isolation
-        flags |= CloneFlags::CLONE_NEWPID;
-        debug!("Enabling PID namespace (CLONE_NEWPID)");
-
-        // IPC namespace for System V IPC and POSIX message queue isolation
-        flags |= CloneFlags::CLONE_NEWIPC;
-        debug!("Enabling IPC namespace (CLONE_NEWIPC)");
-
-        // Network namespace (if network is disabled)
+    /// - User (CLONE_NEWUSER): unless root, so the others need no privileges
+    ///
+    /// No PID namespace: unshare only moves the process's children into
+    /// it, and the namespace dies with the first of them to exit.
+    fn namespace_flags(&self) -> CloneFlags {
+        let mut flags = CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWIPC | CloneFlags::CLONE_NEWUTS;
         if !self.constraints.network {
             flags |= CloneFlags::CLONE_NEWNET;
-            debug!("Enabling network namespace (CLONE_NEWNET)");
         }
-
-        // UTS namespace for hostname isolation
-        flags |= CloneFlags::CLONE_NEWUTS;
-        debug!("Enabling UTS namespace (CLONE_NEWUTS)");
-
-        unshare(flags)?;
-        info!("Successfully applied Linux namespaces");
-        Ok(())
+        if !Uid::current().is_root() {
+            flags |= CloneFlags::CLONE_NEWUSER;
+        }
+        flags
     }
 
     /// Prepare the pre_exec closure for the child process
     ///
     /// This closure runs in the child process before exec() and sets up
-    /// all sandboxing mechanisms.
-    fn prepare_pre_exec(&self) -> impl FnMut() -> Result<(), std::io::Error> + Send + Clone {
+    /// all sandboxing mechanisms. The child of a multi-threaded process may
+    /// not allocate or take locks, so the seccomp program, Landlock ruleset
+    /// and user to drop to are all prepared here, and the closure only
+    /// makes syscalls. Seccomp and Landlock are best effort: on a kernel
+    /// without them the server runs with the remaining layers.
+    fn prepare_pre_exec(
+        &self,
+        config: &McpServerConfig,
+        cpus: Option<CpuSet>,
+    ) -> impl FnMut() -> Result<(), std::io::Error> + Send + Sync + 'static {
         let network = self.constraints.network;
-        let filesystem = self.constraints.filesystem.clone();
+        let namespaces = self.namespace_flags();
+        let max_open_files = config.sandbox.max_open_files;
+        let max_processes = config.sandbox.max_processes;
+
+        let seccomp = if self.available.seccomp {
+            linux_seccomp::seccomp_program(config.sandbox.seccomp_mode, &config.sandbox.seccomp_allow)
+                .map_err(|e| warn!("Cannot build seccomp filter for {}: {}", config.name, e))
+                .ok()
+        } else {
+            warn!("seccomp is unavailable, {} runs without a syscall filter", config.name);
+            None
+        };
 
-        move || {
-            debug!("Setting up sandbox in pre_exec hook");
+        let devices = if config.sandbox.allow_gpu {
+            gpu::device_paths(&config.sandbox.gpus)
+        } else {
+            Vec::new()
+        };
+        let mut landlock = match &self.constraints.filesystem {
+            FilesystemConstraint::Full => None,
+            _ if !self.available.landlock => {
+                warn!("Landlock is unavailable, {} runs without filesystem restrictions", config.name);
+                None
+            }
+            FilesystemConstraint::ReadOnly => {
+                linux_landlock::landlock_ruleset(&["/".to_string()], true, &devices)
+                    .map_err(|e| warn!("Cannot build Landlock ruleset for {}: {}", config.name, e))
+                    .ok()
+            }
+            FilesystemConstraint::ReadOnlyExcept(writable) => {
+                let writable: Vec<_> = devices.iter().chain(writable).cloned().collect();
+                linux_landlock::landlock_ruleset(&["/".to_string()], true, &writable)
+                    .map_err(|e| warn!("Cannot build Landlock ruleset for {}: {}", config.name, e))
+                    .ok()
+            }
+            FilesystemConstraint::Paths(paths) => linux_landlock::landlock_ruleset(paths, false, &devices)
+                .map_err(|e| warn!("Cannot build Landlock ruleset for {}: {}", config.name, e))
+                .ok(),
+        };
 
-            // Apply namespaces first
-            let mut flags = CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWPID;
+        // Drop privileges if running as root
+        let unprivileged = if Uid::current().is_root() {
+            match User::from_name("nobody") {
+                Ok(Some(user)) => {
+                    debug!("Dropping privileges of {} to uid {}", config.name, user.uid);
+                    Some((user.uid, user.gid))
+                }
+                _ => {
+                    warn!("Could not find unprivileged user 'nobody' - running as root");
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
-            if !network {
-                flags |= CloneFlags::CLONE_NEWNET;
+        move || {
+            // Pin to the configured CPUs; children inherit the affinity
+            if let Some(cpus) = &cpus {
+                sched_setaffinity(Pid::from_raw(0), cpus)?;
             }
 
-            flags |= CloneFlags::CLONE_NEWIPC | CloneFlags::CLONE_NEWUTS;
+            // Cap descriptors and processes against fd exhaustion and fork bombs
+            apply_rlimits(max_open_files, max_processes)?;
 
-            if let Err(e) = unshare(flags) {
-                eprintln!("Failed to unshare namespaces: {}", e);
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("Namespace error: {}", e),
-                ));
-            }
-            debug!("Namespaces applied successfully");
-
-            // Apply seccomp filter
-            if let Err(e) = super::linux_seccomp::apply_seccomp_filter() {
-                eprintln!("Failed to apply seccomp: {}", e);
-                // Continue without seccomp rather than fail entirely
-                // This provides defense-in-depth without breaking functionality
-            } else {
-                debug!("seccomp filter applied successfully");
+            // Namespaces are optional isolation, except the network one
+            // when the server must not have network access
+            if let Err(e) = unshare(namespaces) {
+                if !network {
+                    return Err(e.into());
+                }
             }
 
-            // Apply Landlock restrictions
-            let paths = match &filesystem {
-                FilesystemConstraint::Paths(p) => p.clone(),
-                FilesystemConstraint::ReadOnly => vec!["/".to_string()],
-                FilesystemConstraint::Full => vec!["/".to_string()],
-            };
-            let read_only = matches!(filesystem, FilesystemConstraint::ReadOnly);
-
-            if let Err(e) = super::linux_landlock::apply_landlock_restrictions(paths, read_only) {
-                eprintln!("Failed to apply Landlock: {}", e);
-                // Continue without Landlock rather than fail entirely
-            } else {
-                debug!("Landlock restrictions applied successfully");
+            if let Some(ruleset) = landlock.take() {
+                let _ = linux_landlock::restrict_self(ruleset);
             }
 
-            // Drop privileges if running as root
-            if Uid::current().is_root() {
-                warn!("Running as root - attempting to drop privileges");
-
-                // Try to find an unprivileged user to run as
-                let unprivileged_user = User::from_name("nobody")
-                    .or_else(|_| User::from_name("nobody"))
-                    .ok()
-                    .flatten();
-
-                if let Some(user) = unprivileged_user {
-                    // Create a vector of supplementary groups for the user
-                    let supplementary_groups: Vec<Gid> = user.groups
-                        .iter()
-                        .filter(|g| g.name != "nogroup")
-                        .map(|g| g.gid)
-                        .collect();
-
-                    // Set supplementary groups first
-                    if !supplementary_groups.is_empty() {
-                        if let Err(e) = setgroups(&supplementary_groups) {
-                            warn!("Failed to set supplementary groups: {}", e);
-                        }
-                    }
-
-                    // Drop group privileges
-                    if let Err(e) = nix::unistd::setgid(Gid::from_raw(user.gid)) {
-                        warn!("Failed to set group ID: {}", e);
-                    } else {
-                        debug!("Successfully set GID to {}", user.gid);
-                    }
-
-                    // Drop user privileges
-                    if let Err(e) = nix::unistd::setuid(Uid::from_raw(user.uid)) {
-                        warn!("Failed to set user ID: {}", e);
-                    } else {
-                        debug!("Successfully set UID to {}", user.uid);
-                    }
-                } else {
-                    warn!("Could not find unprivileged user 'nobody' - running as root");
-                }
+            if let Some((uid, gid)) = unprivileged {
+                setgroups(&[])?;
+                setgid(gid)?;
+                setuid(uid)?;
             }
 
-            info!("Sandbox setup complete in pre_exec hook");
+            // Last, so the filter need not allow the setup above
+            if let Some(program) = &seccomp {
+                let _ = seccompiler::apply_filter(program);
+            }
             Ok(())
         }
     }
@@ -200,8 +192,8 @@ impl LinuxSandboxFull {
     /// Returns a report of which sandboxing features are available.
     pub fn check_availability() -> SandboxAvailabilityReport {
         SandboxAvailabilityReport {
-            seccomp: super::linux_seccomp::is_seccomp_available(),
-            landlock: super::linux_landlock::is_landlock_available(),
+            seccomp: linux_seccomp::is_seccomp_available(),
+            landlock: linux_landlock::is_landlock_available(),
             namespaces: Self::check_namespace_support(),
         }
     }
@@ -292,24 +284,74 @@ impl Sandbox for LinuxSandboxFull {
             cmd.env_clear();
         }
 
+        // Name the granted GPUs; the server's own env may override this
+        if config.sandbox.allow_gpu {
+            for (key, value) in gpu::env(&config.sandbox.gpus) {
+                cmd.env(key, value);
+            }
+        }
+
         // Apply custom environment variables
         for (key, value) in &config.env {
             debug!("Setting environment variable: {}=...", key);
             cmd.env(key, value);
         }
 
+        // Resolve the CPU set here; pre_exec must not allocate
+        let cpus = match &config.sandbox.cpuset {
+            Some(spec) => {
+                let mut cpus = CpuSet::new();
+                for cpu in parse_cpu_list(spec).map_err(McpError::ConfigError)? {
+                    cpus.set(cpu).map_err(|e| {
+                        McpError::SandboxError(format!("Cannot pin to CPU {}: {}", cpu, e))
+                    })?;
+                }
+                Some(cpus)
+            }
+            None => None,
+        };
         // Set up pre_exec hook for sandboxing
         // This runs in the child process before exec()
+        let pre_exec = self.prepare_pre_exec(config, cpus);
+        // SAFETY: the hook only makes syscalls, see prepare_pre_exec
         unsafe {
-            let pre_exec = self.prepare_pre_exec();
             cmd.pre_exec(pre_exec);
         }
 
         // Spawn the process
-        let child = cmd.spawn().map_err(|e| {
-            McpError::SandboxError(format!("Failed to spawn sandboxed process: {}", e))
+        let child = cmd.spawn().map_err(|e| match e.kind() {
+            // Landlock refusing to exec the command outside the allowed paths
+            std::io::ErrorKind::PermissionDenied => McpError::SandboxDenied(format!(
+                "{} is not executable under the sandbox policy: {}",
+                config.command, e
+            )),
+            _ => McpError::SandboxError(format!("Failed to spawn sandboxed process: {}", e)),
         })?;
 
+        // IO weight and the process cap are cgroup settings, applied once
+        // the child exists
+        let mut cgroup = Vec::new();
+        if let Some(weight) = config.sandbox.io_weight {
+            cgroup.push(("io.weight", format!("default {}", weight)));
+        }
+        if let Some(max) = config.sandbox.max_processes {
+            cgroup.push(("pids.max", max.to_string()));
+        }
+        if let (false, Some(pid)) = (cgroup.is_empty(), child.id()) {
+            if let Err(e) = join_cgroup(&config.name, pid, &cgroup) {
+                warn!("Cannot apply cgroup limits to {}: {}", config.name, e);
+            }
+        }
+
+        if let Some(pid) = child.id() {
+            observer::watch(&config.name, pid);
+        }
+
+        // Attribute the syscalls the kernel logs to this server
+        if let (SeccompMode::Audit, Some(pid)) = (config.sandbox.seccomp_mode, child.id()) {
+            syscall_audit::global().register(&config.name, pid);
+        }
+
         info!(
             "Successfully spawned sandboxed process with PID {:?}",
             child.id()
@@ -342,6 +384,134 @@ pub fn create_best_effort_sandbox(config: &McpServerConfig) -> Box<dyn Sandbox>
         Box::new(LinuxSandboxFull::from_config(config))
     } else {
         warn!("No sandboxing features available, using no-op sandbox");
-        Box::new(super::none::NoSandbox::new())
+        Box::new(crate::sandbox::none::NoSandbox::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &str) -> McpServerConfig {
+        let mut config = McpServerConfig {
+            name: name.to_string(),
+            command: "sleep".to_string(),
+            args: vec!["10".to_string()],
+            ..Default::default()
+        };
+        config.sandbox.enabled = true;
+        config.sandbox.env_inherit = true;
+        config
+    }
+
+    /// A line of a file under `/proc/<pid>`, once the child has exec'd
+    async fn proc_line(child: &Child, file: &str, prefix: &str) -> String {
+        let pid = child.id().unwrap();
+        for _ in 0..50 {
+            let exe = std::fs::read_link(format!("/proc/{}/exe", pid)).unwrap_or_default();
+            if exe.ends_with("sleep") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        std::fs::read_to_string(format!("/proc/{}/{}", pid, file))
+            .unwrap()
+            .lines()
+            .find(|line| line.starts_with(prefix))
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_spawn_pins_cpus() {
+        let mut config = server("pinned");
+        config.sandbox.cpuset = Some("0".to_string());
+
+        let mut child = LinuxSandboxFull::from_config(&config).spawn(&config).await.unwrap();
+        let allowed = proc_line(&child, "status", "Cpus_allowed_list:").await;
+        let _ = child.kill().await;
+        assert_eq!(allowed.split_whitespace().nth(1), Some("0"));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_limits_open_files() {
+        let mut config = server("capped");
+        config.sandbox.max_open_files = Some(64);
+
+        let mut child = LinuxSandboxFull::from_config(&config).spawn(&config).await.unwrap();
+        let limit = proc_line(&child, "limits", "Max open files").await;
+        let _ = child.kill().await;
+        assert_eq!(limit.split_whitespace().nth(3), Some("64"));
+        assert_eq!(limit.split_whitespace().nth(4), Some("64"));
+    }
+
+    /// Seccomp filters stacked on a process, this one's included
+    fn seccomp_filters(status: &str) -> usize {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("Seccomp_filters:"))
+            .map_or(0, |count| count.trim().parse().unwrap())
+    }
+
+    /// Server that tries to write `file`, then sleeps
+    ///
+    /// The directory is opened up, as servers started by root run as nobody.
+    /// It must lie outside `/tmp`, which Landlock leaves writable.
+    fn writer(name: &str, file: &std::path::Path) -> McpServerConfig {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = file.parent().unwrap();
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+
+        let mut config = server(name);
+        config.command = "sh".to_string();
+        config.args = vec![
+            "-c".to_string(),
+            format!("echo x > {}; exec sleep 10", file.display()),
+        ];
+        config.sandbox.max_open_files = Some(64);
+        config
+    }
+
+    #[tokio::test]
+    async fn test_default_sandbox_applies_available_layers() {
+        let dir = tempfile::tempdir_in("/dev/shm").unwrap();
+        let file = dir.path().join("written");
+        let config = writer("default", &file);
+        let available = LinuxSandboxFull::check_availability();
+
+        let mut child = crate::sandbox::create_sandbox(&config).spawn(&config).await.unwrap();
+        let limit = proc_line(&child, "limits", "Max open files").await;
+        let status = std::fs::read_to_string(format!("/proc/{}/status", child.id().unwrap())).unwrap();
+        let _ = child.kill().await;
+
+        assert_eq!(limit.split_whitespace().nth(3), Some("64"));
+        let own = seccomp_filters(&std::fs::read_to_string("/proc/self/status").unwrap());
+        assert_eq!(seccomp_filters(&status) > own, available.seccomp);
+        if available.landlock {
+            assert!(!file.exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_without_landlock_or_seccomp() {
+        let dir = tempfile::tempdir_in("/dev/shm").unwrap();
+        let file = dir.path().join("written");
+        let config = writer("bare-kernel", &file);
+
+        let sandbox = LinuxSandboxFull::from_config(&config).with_availability(SandboxAvailabilityReport {
+            seccomp: false,
+            landlock: false,
+            namespaces: NamespaceSupport::Full,
+        });
+        let mut child = sandbox.spawn(&config).await.unwrap();
+        let limit = proc_line(&child, "limits", "Max open files").await;
+        let status = std::fs::read_to_string(format!("/proc/{}/status", child.id().unwrap())).unwrap();
+        let _ = child.kill().await;
+
+        // The other layers still apply
+        assert_eq!(limit.split_whitespace().nth(3), Some("64"));
+        let own = seccomp_filters(&std::fs::read_to_string("/proc/self/status").unwrap());
+        assert_eq!(seccomp_filters(&status), own);
+        assert!(file.exists());
     }
 }
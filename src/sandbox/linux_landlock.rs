@@ -5,17 +5,42 @@
 //! create security sandboxes that can restrict access to the filesystem.
 
 use landlock::{
-    Access, AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreated, RulesetStatus,
+    Access, AccessFs, BitFlags, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr, RulesetStatus, ABI,
 };
 use std::path::Path;
 use tracing::{debug, info, warn};
 
+/// Landlock ABI whose access rights are handled; older kernels enforce
+/// what they support
+const LANDLOCK_ABI: ABI = ABI::V2;
+
+/// Read-only paths every sandboxed process needs to run its command and
+/// link it
+const SYSTEM_PATHS: &[&str] = &[
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib64",
+    "/usr",
+    "/etc/ld.so.cache",
+    "/etc/ld.so.conf",
+    "/etc/ld.so.conf.d",
+];
+
+/// `landlock_create_ruleset` flag returning the supported ABI version
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+
+/// Scratch locations writable unless the process has the whole filesystem
+const SCRATCH_PATHS: &[&str] = &["/tmp", "/var/tmp", "/dev/null"];
+
 /// Apply Landlock restrictions to limit filesystem access
 ///
 /// # Arguments
 ///
 /// * `allowed_paths` - List of paths that should be accessible
 /// * `read_only` - If true, all paths are granted read-only access
+/// * `devices` - Paths granted read-write regardless, e.g. GPU device nodes
 ///
 /// # Returns
 ///
@@ -25,127 +50,104 @@ use tracing::{debug, info, warn};
 /// # Example
 ///
 /// ```rust,no_run
-/// use super_mcp::sandbox::linux_landlock::apply_landlock_restrictions;
+/// use supermcp::sandbox::linux::apply_landlock_restrictions;
 ///
 /// let paths = vec!["/tmp/workdir".to_string()];
-/// apply_landlock_restrictions(paths, false).expect("Failed to apply Landlock");
+/// apply_landlock_restrictions(paths, false, &[]).expect("Failed to apply Landlock");
 /// ```
 pub fn apply_landlock_restrictions(
     allowed_paths: Vec<String>,
     read_only: bool,
+    devices: &[String],
 ) -> Result<(), Box<dyn std::error::Error>> {
+    restrict_self(landlock_ruleset(&allowed_paths, read_only, devices)?)
+}
+
+/// Build the ruleset [`apply_landlock_restrictions`] enforces
+///
+/// Opening the paths allocates, so a sandbox builds the ruleset before
+/// forking and only calls [`restrict_self`] in the child.
+pub fn landlock_ruleset(
+    allowed_paths: &[String],
+    read_only: bool,
+    devices: &[String],
+) -> Result<RulesetCreated, Box<dyn std::error::Error>> {
     info!(
-        "Applying Landlock restrictions: {} paths, read_only={}",
+        "Building Landlock ruleset: {} paths, read_only={}",
         allowed_paths.len(),
         read_only
     );
 
-    // Create a ruleset with filesystem access rights
-    let mut ruleset = Ruleset::default();
+    let mut ruleset = Ruleset::new()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+        .create()?;
 
     // Determine access rights based on read_only flag
     let access_rights = if read_only {
-        AccessFs::from_all(Access::Read)
+        AccessFs::from_read(LANDLOCK_ABI)
     } else {
-        AccessFs::from_all(Access::ReadWrite)
+        AccessFs::from_all(LANDLOCK_ABI)
     };
-
     debug!("Landlock access rights: {:?}", access_rights);
 
-    // Add allowed paths
-    let mut valid_paths = 0;
-    for path_str in allowed_paths {
-        let path = Path::new(&path_str);
-        if path.exists() {
-            match PathFd::new(path) {
-                Ok(path_fd) => {
-                    let beneath = PathBeneath::new(path_fd, access_rights);
-                    match ruleset.add_rule(beneath) {
-                        Ok(_) => {
-                            debug!("Added Landlock rule for path: {}", path_str);
-                            valid_paths += 1;
-                        }
-                        Err(e) => {
-                            warn!("Failed to add Landlock rule for {}: {}", path_str, e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to open path {} for Landlock: {}", path_str, e);
-                }
-            }
-        } else {
-            warn!("Path does not exist, skipping Landlock rule: {}", path_str);
-        }
+    let valid_paths = allowed_paths
+        .iter()
+        .filter(|path| add_rule(&mut ruleset, path, access_rights))
+        .count();
+    if valid_paths == 0 {
+        warn!("No valid paths were added to Landlock ruleset");
     }
 
-    // Also allow read-only access to system libraries (required for dynamic linking)
-    let system_paths = ["/lib", "/lib64", "/usr/lib", "/usr/lib64"];
-    for path_str in &system_paths {
-        let path = Path::new(path_str);
-        if path.exists() {
-            match PathFd::new(path) {
-                Ok(path_fd) => {
-                    let beneath = PathBeneath::new(path_fd, AccessFs::from_all(Access::Read));
-                    match ruleset.add_rule(beneath) {
-                        Ok(_) => debug!("Added Landlock rule for system path: {}", path_str),
-                        Err(e) => {
-                            warn!("Failed to add Landlock rule for {}: {}", path_str, e)
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to open system path {}: {}", path_str, e);
-                }
-            }
-        }
+    // Devices the sandbox passes through, such as GPUs; files only get
+    // the file rights of what is asked
+    for device in devices {
+        add_rule(&mut ruleset, device, AccessFs::from_all(LANDLOCK_ABI));
     }
-
-    // Allow read-only access to essential system directories
-    let essential_paths = ["/etc/ld.so.cache", "/etc/ld.so.conf", "/etc/ld.so.conf.d"];
-    for path_str in &essential_paths {
-        let path = Path::new(path_str);
-        if path.exists() {
-            match PathFd::new(path) {
-                Ok(path_fd) => {
-                    let beneath = PathBeneath::new(path_fd, AccessFs::from_all(Access::Read));
-                    match ruleset.add_rule(beneath) {
-                        Ok(_) => debug!("Added Landlock rule for essential path: {}", path_str),
-                        Err(e) => {
-                            warn!("Failed to add Landlock rule for {}: {}", path_str, e)
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to open essential path {}: {}", path_str, e);
-                }
-            }
-        }
+    for path in SYSTEM_PATHS {
+        add_rule(&mut ruleset, path, AccessFs::from_read(LANDLOCK_ABI));
     }
-
-    if valid_paths == 0 {
-        warn!("No valid paths were added to Landlock ruleset");
+    for path in SCRATCH_PATHS {
+        add_rule(&mut ruleset, path, AccessFs::from_all(LANDLOCK_ABI));
     }
+    Ok(ruleset)
+}
 
-    // Restrict the process
-    info!("Applying Landlock self-restriction");
-    let status = ruleset.restrict_self()?;
-
-    match status.ruleset {
-        RulesetStatus::FullyEnforced => {
-            info!("Landlock fully enforced");
-            Ok(())
+/// Allow `access` beneath `path`, if it exists; true when added
+fn add_rule(ruleset: &mut RulesetCreated, path: &str, access: BitFlags<AccessFs>) -> bool {
+    if !Path::new(path).exists() {
+        debug!("Path does not exist, skipping Landlock rule: {}", path);
+        return false;
+    }
+    let rule = match PathFd::new(path) {
+        Ok(path_fd) => PathBeneath::new(path_fd, access),
+        Err(e) => {
+            warn!("Failed to open path {} for Landlock: {}", path, e);
+            return false;
         }
-        RulesetStatus::PartiallyEnforced => {
-            warn!("Landlock only partially enforced - some restrictions may not be active");
-            Ok(())
+    };
+    match ruleset.add_rule(rule) {
+        Ok(_) => {
+            debug!("Added Landlock rule for path: {}", path);
+            true
         }
-        RulesetStatus::NotEnforced => {
-            Err("Landlock not supported on this system".into())
+        Err(e) => {
+            warn!("Failed to add Landlock rule for {}: {}", path, e);
+            false
         }
     }
 }
 
+/// Enforce a ruleset on the calling thread and what it execs
+///
+/// Makes no allocations unless it fails, so it is safe in `pre_exec`.
+pub fn restrict_self(ruleset: RulesetCreated) -> Result<(), Box<dyn std::error::Error>> {
+    let status = ruleset.restrict_self()?;
+    match status.ruleset {
+        RulesetStatus::FullyEnforced | RulesetStatus::PartiallyEnforced => Ok(()),
+        RulesetStatus::NotEnforced => Err("Landlock not supported on this system".into()),
+    }
+}
+
 /// Check if Landlock is available on this system
 ///
 /// Returns true if Landlock is supported by the kernel and can be used.
@@ -164,7 +166,17 @@ pub fn get_landlock_status() -> (bool, bool) {
 }
 
 fn landlock_supported_by_kernel() -> bool {
-    if std::path::Path::new("/proc/self/attr/landlock").exists() {
+    // Asking for the ABI version works without securityfs mounted
+    // SAFETY: the version query takes no attributes
+    let version = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    if version > 0 {
         return true;
     }
 
@@ -179,44 +191,57 @@ fn landlock_supported_by_kernel() -> bool {
 ///
 /// This is a more flexible version that allows specifying exact access rights.
 pub fn apply_landlock_with_rights(
-    allowed_paths: Vec<(String, AccessFs)>,
+    allowed_paths: Vec<(String, BitFlags<AccessFs>)>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!(
         "Applying Landlock restrictions with custom rights: {} paths",
         allowed_paths.len()
     );
 
-    let mut ruleset = Ruleset::default();
-
-    for (path_str, access_rights) in allowed_paths {
-        let path = Path::new(&path_str);
-        if path.exists() {
-            match PathFd::new(path) {
-                Ok(path_fd) => {
-                    let beneath = PathBeneath::new(path_fd, access_rights);
-                    match ruleset.add_rule(beneath) {
-                        Ok(_) => debug!("Added Landlock rule for: {}", path_str),
-                        Err(e) => warn!("Failed to add rule for {}: {}", path_str, e),
-                    }
-                }
-                Err(e) => warn!("Failed to open {}: {}", path_str, e),
-            }
-        } else {
-            warn!("Path does not exist: {}", path_str);
-        }
+    let mut ruleset = Ruleset::new()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+        .create()?;
+    for (path, access_rights) in &allowed_paths {
+        add_rule(&mut ruleset, path, *access_rights);
     }
+    restrict_self(ruleset)
+}
 
-    let status = ruleset.restrict_self()?;
-
-    match status.ruleset {
-        RulesetStatus::FullyEnforced => {
-            info!("Landlock fully enforced with custom rights");
-            Ok(())
-        }
-        RulesetStatus::PartiallyEnforced => {
-            warn!("Landlock partially enforced with custom rights");
-            Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+
+    /// Passed-through devices are writable under a path-restricted ruleset
+    #[test]
+    fn test_devices_are_granted() {
+        if !is_landlock_available() {
+            eprintln!("Skipping: Landlock is unavailable");
+            return;
         }
-        RulesetStatus::NotEnforced => Err("Landlock not supported".into()),
+        // Not under /tmp, which the ruleset allows anyway
+        let dir = tempfile::tempdir_in(".").unwrap();
+        let device = dir.path().join("renderD128");
+        let other = dir.path().join("other");
+        std::fs::write(&device, "").unwrap();
+        std::fs::write(&other, "").unwrap();
+
+        let write = |path: &Path| {
+            let mut ruleset = landlock_ruleset(&[], false, &[device.to_string_lossy().into_owned()]).ok();
+            let mut cmd = std::process::Command::new("/bin/sh");
+            cmd.arg("-c").arg(format!("echo x > {}", path.display()));
+            // SAFETY: restrict_self only makes syscalls
+            unsafe {
+                cmd.pre_exec(move || {
+                    if let Some(ruleset) = ruleset.take() {
+                        restrict_self(ruleset).map_err(|_| std::io::Error::from(std::io::ErrorKind::Other))?;
+                    }
+                    Ok(())
+                });
+            }
+            cmd.stderr(std::process::Stdio::null()).status().unwrap().success()
+        };
+        assert!(write(&device));
+        assert!(!write(&other));
     }
 }
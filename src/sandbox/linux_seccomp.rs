@@ -3,16 +3,28 @@
 //! This module provides syscall filtering using seccomp-bpf to restrict
 //! which system calls sandboxed processes can make.
 
-use seccompiler::{
-    BpfProgram, SeccompAction, SeccompCondition, SeccompFilter, SeccompRule, SeccompCmpArgLen,
-    SeccompCmpOp,
-};
+use crate::config::SeccompMode;
+use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, SeccompRule};
 
 /// Apply a seccomp filter that allows basic operations but blocks dangerous syscalls
 ///
 /// This uses an allow-list approach, permitting only essential syscalls
-/// and denying everything else with EPERM.
-pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
+/// plus `extra` and denying everything else with EPERM. In audit mode the
+/// rest are allowed but logged by the kernel instead.
+pub fn apply_seccomp_filter(
+    mode: SeccompMode,
+    extra: &[i64],
+) -> Result<(), Box<dyn std::error::Error>> {
+    seccompiler::apply_filter(&seccomp_program(mode, extra)?)?;
+    Ok(())
+}
+
+/// Compile the filter [`apply_seccomp_filter`] installs, so a sandbox can
+/// build it before forking and only load it in the child
+pub fn seccomp_program(
+    mode: SeccompMode,
+    extra: &[i64],
+) -> Result<BpfProgram, Box<dyn std::error::Error>> {
     // Define allowed syscalls with their conditions
     let mut rules: Vec<(i64, Vec<SeccompRule>)> = vec![
         // File operations
@@ -30,7 +42,6 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_preadv, vec![]),
         (libc::SYS_pwritev, vec![]),
         (libc::SYS_dup, vec![]),
-        (libc::SYS_dup2, vec![]),
         (libc::SYS_dup3, vec![]),
 
         // Directory operations
@@ -79,15 +90,12 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_wait4, vec![]),
         (libc::SYS_clone, vec![]),
         (libc::SYS_clone3, vec![]),
-        (libc::SYS_fork, vec![]),
-        (libc::SYS_vfork, vec![]),
         (libc::SYS_execve, vec![]),
         (libc::SYS_execveat, vec![]),
         (libc::SYS_waitid, vec![]),
 
         // Signal handling
         (libc::SYS_sigaltstack, vec![]),
-        (libc::SYS_signalfd, vec![]),
         (libc::SYS_signalfd4, vec![]),
         (libc::SYS_restart_syscall, vec![]),
 
@@ -104,13 +112,10 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_epoll_ctl, vec![]),
         (libc::SYS_epoll_pwait, vec![]),
         (libc::SYS_epoll_pwait2, vec![]),
-        (libc::SYS_poll, vec![]),
         (libc::SYS_ppoll, vec![]),
-        (libc::SYS_select, vec![]),
         (libc::SYS_pselect6, vec![]),
 
         // Pipes and FIFOs
-        (libc::SYS_pipe, vec![]),
         (libc::SYS_pipe2, vec![]),
         (libc::SYS_tee, vec![]),
         (libc::SYS_splice, vec![]),
@@ -127,7 +132,6 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_fadvise64, vec![]),
 
         // Eventfd
-        (libc::SYS_eventfd, vec![]),
         (libc::SYS_eventfd2, vec![]),
 
         // Timerfd
@@ -136,7 +140,6 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_timerfd_gettime, vec![]),
 
         // Inotify (for file watching)
-        (libc::SYS_inotify_init, vec![]),
         (libc::SYS_inotify_init1, vec![]),
         (libc::SYS_inotify_add_watch, vec![]),
         (libc::SYS_inotify_rm_watch, vec![]),
@@ -225,46 +228,92 @@ pub fn apply_seccomp_filter() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_io_uring_setup, vec![]),
         (libc::SYS_io_uring_enter, vec![]),
         (libc::SYS_io_uring_register, vec![]),
+
+        // Threads, the dynamic loader and the C runtime
+        (libc::SYS_futex, vec![]),
+        (libc::SYS_set_tid_address, vec![]),
+        (libc::SYS_set_robust_list, vec![]),
+        (libc::SYS_get_robust_list, vec![]),
+        (libc::SYS_rseq, vec![]),
+        (libc::SYS_madvise, vec![]),
+        (libc::SYS_membarrier, vec![]),
+        (libc::SYS_msync, vec![]),
+        (libc::SYS_mincore, vec![]),
+        (libc::SYS_sched_yield, vec![]),
+        (libc::SYS_sched_getaffinity, vec![]),
+        (libc::SYS_getresuid, vec![]),
+        (libc::SYS_getresgid, vec![]),
+        (libc::SYS_getpgid, vec![]),
+        (libc::SYS_setpgid, vec![]),
+        (libc::SYS_getsid, vec![]),
+        (libc::SYS_setsid, vec![]),
+        (libc::SYS_rt_sigsuspend, vec![]),
+        (libc::SYS_getitimer, vec![]),
+        (libc::SYS_setitimer, vec![]),
+        (libc::SYS_pidfd_open, vec![]),
+        (libc::SYS_close_range, vec![]),
+        (libc::SYS_statx, vec![]),
+        (libc::SYS_statfs, vec![]),
+        (libc::SYS_fstatfs, vec![]),
+        (libc::SYS_flock, vec![]),
+        (libc::SYS_truncate, vec![]),
+        (libc::SYS_fchown, vec![]),
+        (libc::SYS_fchownat, vec![]),
+        (libc::SYS_utimensat, vec![]),
+        (libc::SYS_linkat, vec![]),
+        (libc::SYS_memfd_create, vec![]),
+        (libc::SYS_copy_file_range, vec![]),
+        (libc::SYS_sendfile, vec![]),
+        (libc::SYS_getpriority, vec![]),
+        (libc::SYS_sched_getparam, vec![]),
+        (libc::SYS_sched_getscheduler, vec![]),
     ];
+    // Syscalls x86_64 has alongside their *at and 2 forms
+    #[cfg(target_arch = "x86_64")]
+    rules.extend([
+        (libc::SYS_arch_prctl, vec![]),
+        (libc::SYS_open, vec![]),
+        (libc::SYS_stat, vec![]),
+        (libc::SYS_lstat, vec![]),
+        (libc::SYS_access, vec![]),
+        (libc::SYS_readlink, vec![]),
+        (libc::SYS_getdents, vec![]),
+        (libc::SYS_mkdir, vec![]),
+        (libc::SYS_rmdir, vec![]),
+        (libc::SYS_unlink, vec![]),
+        (libc::SYS_rename, vec![]),
+        (libc::SYS_dup2, vec![]),
+        (libc::SYS_poll, vec![]),
+        (libc::SYS_select, vec![]),
+        (libc::SYS_pipe, vec![]),
+        (libc::SYS_epoll_create, vec![]),
+        (libc::SYS_epoll_wait, vec![]),
+        (libc::SYS_eventfd, vec![]),
+        (libc::SYS_inotify_init, vec![]),
+        (libc::SYS_signalfd, vec![]),
+        (libc::SYS_getpgrp, vec![]),
+        (libc::SYS_alarm, vec![]),
+        (libc::SYS_fork, vec![]),
+        (libc::SYS_vfork, vec![]),
+    ]);
+    for syscall in extra {
+        if !rules.iter().any(|(allowed, _)| allowed == syscall) {
+            rules.push((*syscall, vec![]));
+        }
+    }
 
-    // Create the filter with EPERM as the default action for denied syscalls
+    // EPERM for denied syscalls, or SECCOMP_RET_LOG when auditing
+    let mismatch = match mode {
+        SeccompMode::Enforce => SeccompAction::Errno(libc::EPERM as u32),
+        SeccompMode::Audit => SeccompAction::Log,
+    };
     let filter = SeccompFilter::new(
-        rules,
-        SeccompAction::Errno(libc::EPERM), // Deny with EPERM
+        rules.into_iter().collect(),
+        mismatch,
         SeccompAction::Allow,
         std::env::consts::ARCH.try_into()?,
     )?;
-
-    let program = filter.compile()?;
-
-    // Load the seccomp filter
-    unsafe {
-        // Enable no_new_privs (required for seccomp)
-        let result = libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
-        if result < 0 {
-            return Err(format!(
-                "prctl(PR_SET_NO_NEW_PRIVS) failed: {}",
-                std::io::Error::last_os_error()
-            )
-            .into());
-        }
-
-        // Apply the seccomp filter
-        let result = libc::prctl(
-            libc::PR_SET_SECCOMP,
-            libc::SECCOMP_MODE_FILTER,
-            program.as_ptr() as *const _,
-        );
-        if result < 0 {
-            return Err(format!(
-                "prctl(PR_SET_SECCOMP) failed: {}",
-                std::io::Error::last_os_error()
-            )
-            .into());
-        }
-    }
-
-    Ok(())
+    Ok(filter.try_into()?)
 }
 
 /// Apply a restrictive seccomp filter for network-disabled mode
@@ -287,7 +336,6 @@ pub fn apply_restrictive_seccomp() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_readv, vec![]),
         (libc::SYS_writev, vec![]),
         (libc::SYS_dup, vec![]),
-        (libc::SYS_dup2, vec![]),
         (libc::SYS_dup3, vec![]),
 
         // Directory operations
@@ -326,8 +374,6 @@ pub fn apply_restrictive_seccomp() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_wait4, vec![]),
         (libc::SYS_clone, vec![]),
         (libc::SYS_clone3, vec![]),
-        (libc::SYS_fork, vec![]),
-        (libc::SYS_vfork, vec![]),
         (libc::SYS_execve, vec![]),
         (libc::SYS_execveat, vec![]),
         (libc::SYS_waitid, vec![]),
@@ -349,13 +395,10 @@ pub fn apply_restrictive_seccomp() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_epoll_ctl, vec![]),
         (libc::SYS_epoll_pwait, vec![]),
         (libc::SYS_epoll_pwait2, vec![]),
-        (libc::SYS_poll, vec![]),
         (libc::SYS_ppoll, vec![]),
-        (libc::SYS_select, vec![]),
         (libc::SYS_pselect6, vec![]),
 
         // Pipes and FIFOs
-        (libc::SYS_pipe, vec![]),
         (libc::SYS_pipe2, vec![]),
         (libc::SYS_tee, vec![]),
         (libc::SYS_splice, vec![]),
@@ -369,7 +412,6 @@ pub fn apply_restrictive_seccomp() -> Result<(), Box<dyn std::error::Error>> {
         (libc::SYS_ftruncate, vec![]),
 
         // Eventfd
-        (libc::SYS_eventfd, vec![]),
         (libc::SYS_eventfd2, vec![]),
 
         // Timerfd
@@ -413,38 +455,13 @@ pub fn apply_restrictive_seccomp() -> Result<(), Box<dyn std::error::Error>> {
     ];
 
     let filter = SeccompFilter::new(
-        rules,
-        SeccompAction::Errno(libc::EPERM),
+        rules.into_iter().collect(),
+        SeccompAction::Errno(libc::EPERM as u32),
         SeccompAction::Allow,
         std::env::consts::ARCH.try_into()?,
     )?;
-
-    let program = filter.compile()?;
-
-    unsafe {
-        let result = libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
-        if result < 0 {
-            return Err(format!(
-                "prctl(PR_SET_NO_NEW_PRIVS) failed: {}",
-                std::io::Error::last_os_error()
-            )
-            .into());
-        }
-
-        let result = libc::prctl(
-            libc::PR_SET_SECCOMP,
-            libc::SECCOMP_MODE_FILTER,
-            program.as_ptr() as *const _,
-        );
-        if result < 0 {
-            return Err(format!(
-                "prctl(PR_SET_SECCOMP) failed: {}",
-                std::io::Error::last_os_error()
-            )
-            .into());
-        }
-    }
-
+    let program: BpfProgram = filter.try_into()?;
+    seccompiler::apply_filter(&program)?;
     Ok(())
 }
 
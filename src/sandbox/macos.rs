@@ -3,12 +3,35 @@
 //! This module implements sandboxing using macOS's Seatbelt (sandbox) system.
 //! Seatbelt uses a profile-based system to restrict process capabilities.
 
-use crate::config::McpServerConfig;
+use crate::config::{MacosAccessConfig, McpServerConfig};
 use crate::sandbox::traits::{FilesystemConstraint, Sandbox, SandboxConstraints};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use tokio::process::Child;
 
+/// Services sending Apple events goes through
+const APPLE_EVENT_SERVICES: &[&str] = &["com.apple.coreservices.appleevents"];
+
+/// The pasteboard server behind the clipboard
+const CLIPBOARD_SERVICES: &[&str] = &["com.apple.pasteboard.1"];
+
+/// Services screen capture APIs talk to
+const SCREEN_CAPTURE_SERVICES: &[&str] = &[
+    "com.apple.windowserver.active",
+    "com.apple.replayd",
+    "com.apple.screencaptureui.agent",
+];
+
+/// `path` with a leading `~/` expanded to the home directory
+fn expand_home(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|h| h.join(rest).to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+        None => path.to_string(),
+    }
+}
+
 /// macOS Seatbelt sandbox
 pub struct MacOSSandbox {
     constraints: SandboxConstraints,
@@ -20,24 +43,13 @@ impl MacOSSandbox {
     pub fn from_config(config: &McpServerConfig) -> Self {
         let constraints = SandboxConstraints {
             network: config.sandbox.network,
-            filesystem: match &config.sandbox.filesystem {
-                crate::config::FilesystemAccess::Simple(s) if s == "readonly" => {
-                    FilesystemConstraint::ReadOnly
-                }
-                crate::config::FilesystemAccess::Simple(s) if s == "full" => {
-                    FilesystemConstraint::Full
-                }
-                crate::config::FilesystemAccess::Paths(paths) => {
-                    FilesystemConstraint::Paths(paths.clone())
-                }
-                _ => FilesystemConstraint::ReadOnly,
-            },
+            filesystem: FilesystemConstraint::from_config(&config.sandbox),
             env_inherit: config.sandbox.env_inherit,
             max_memory_mb: config.sandbox.max_memory_mb,
             max_cpu_percent: config.sandbox.max_cpu_percent,
         };
 
-        let profile = Self::generate_profile(&constraints);
+        let profile = Self::generate_profile(&constraints, &config.sandbox.macos);
 
         Self {
             constraints,
@@ -46,7 +58,7 @@ impl MacOSSandbox {
     }
 
     /// Generate a Seatbelt sandbox profile based on constraints
-    fn generate_profile(constraints: &SandboxConstraints) -> String {
+    fn generate_profile(constraints: &SandboxConstraints, access: &MacosAccessConfig) -> String {
         let mut rules = vec![
             "(version 1)".to_string(),
             "(deny default)".to_string(),
@@ -79,21 +91,21 @@ impl MacOSSandbox {
                 rules.push("(allow file-write* (subpath \"/tmp\"))".to_string());
                 rules.push("(allow file-write* (subpath \"/var/tmp\"))".to_string());
             }
+            FilesystemConstraint::ReadOnlyExcept(paths) => {
+                rules.push("(allow file-read*)".to_string());
+                // Allow writing to temp directories and the given paths
+                rules.push("(allow file-write* (subpath \"/tmp\"))".to_string());
+                rules.push("(allow file-write* (subpath \"/var/tmp\"))".to_string());
+                for path in paths {
+                    rules.push(format!("(allow file-write* (subpath \"{}\"))", expand_home(path)));
+                }
+            }
             FilesystemConstraint::Paths(paths) => {
                 // Allow specific paths
                 for path in paths {
-                    // Expand home directory
-                    let expanded = if path.starts_with("~/") {
-                        dirs::home_dir()
-                            .map(|h| h.join(&path[2..]).to_string_lossy().to_string())
-                            .unwrap_or_else(|| path.clone())
-                    } else {
-                        path.clone()
-                    };
-
                     rules.push(format!(
                         "(allow file-read* file-write* (subpath \"{}\"))",
-                        expanded
+                        expand_home(path)
                     ));
                 }
                 // Also allow temp directory
@@ -121,6 +133,24 @@ impl MacOSSandbox {
         // Allow sysctl read
         rules.push("(allow sysctl-read)".to_string());
 
+        // Automation access, stated either way so the profile shows what
+        // the server was granted
+        let grant = |allowed: bool| if allowed { "allow" } else { "deny" };
+        rules.push(format!("({} appleevent-send)", grant(access.apple_events)));
+        for (allowed, services) in [
+            (access.apple_events, APPLE_EVENT_SERVICES),
+            (access.clipboard, CLIPBOARD_SERVICES),
+            (access.screen_capture, SCREEN_CAPTURE_SERVICES),
+        ] {
+            for service in services {
+                rules.push(format!(
+                    "({} mach-lookup (global-name \"{}\"))",
+                    grant(allowed),
+                    service
+                ));
+            }
+        }
+
         rules.join("\n")
     }
 
@@ -170,6 +200,13 @@ impl Sandbox for MacOSSandbox {
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
 
+        // Cap descriptors and processes; Seatbelt has no such limits
+        let (max_open_files, max_processes) = (config.sandbox.max_open_files, config.sandbox.max_processes);
+        // SAFETY: the hook only calls setrlimit
+        unsafe {
+            cmd.pre_exec(move || crate::sandbox::limits::apply_rlimits(max_open_files, max_processes));
+        }
+
         // Spawn the process
         let child = cmd.spawn().map_err(|e| {
             McpError::SandboxError(format!("Failed to spawn sandboxed process: {}", e))
@@ -197,12 +234,28 @@ mod tests {
             max_cpu_percent: 50,
         };
 
-        let profile = MacOSSandbox::generate_profile(&constraints);
+        let profile = MacOSSandbox::generate_profile(&constraints, &MacosAccessConfig::default());
         
         assert!(profile.contains("(version 1)"));
         assert!(profile.contains("(deny default)"));
         assert!(profile.contains("(allow file-read*)"));
         assert!(profile.contains("(deny network*)"));
+        assert!(profile.contains("(deny appleevent-send)"));
+        assert!(profile.contains("(deny mach-lookup (global-name \"com.apple.pasteboard.1\"))"));
+    }
+
+    #[test]
+    fn test_generate_profile_with_clipboard() {
+        let access = MacosAccessConfig {
+            clipboard: true,
+            ..Default::default()
+        };
+
+        let profile = MacOSSandbox::generate_profile(&SandboxConstraints::default(), &access);
+
+        assert!(profile.contains("(allow mach-lookup (global-name \"com.apple.pasteboard.1\"))"));
+        assert!(profile.contains("(deny mach-lookup (global-name \"com.apple.replayd\"))"));
+        assert!(profile.contains("(deny appleevent-send)"));
     }
 
     #[test]
@@ -215,7 +268,7 @@ mod tests {
             max_cpu_percent: 50,
         };
 
-        let profile = MacOSSandbox::generate_profile(&constraints);
+        let profile = MacOSSandbox::generate_profile(&constraints, &MacosAccessConfig::default());
         
         assert!(profile.contains("(allow network-outbound)"));
         assert!(profile.contains("(allow file-read* file-write*)"));
@@ -231,7 +284,7 @@ mod tests {
             max_cpu_percent: 50,
         };
 
-        let profile = MacOSSandbox::generate_profile(&constraints);
+        let profile = MacOSSandbox::generate_profile(&constraints, &MacosAccessConfig::default());
         
         assert!(profile.contains("/tmp/test"));
     }
@@ -1,4 +1,14 @@
+pub mod broker;
+pub mod disk_quota;
+#[cfg(all(target_os = "linux", feature = "ebpf"))]
+pub mod ebpf;
+pub mod elevation;
+pub mod gpu;
+pub mod learn;
+pub mod limits;
 pub mod none;
+pub mod observer;
+pub mod syscall_audit;
 pub mod traits;
 
 #[cfg(target_os = "linux")]
@@ -0,0 +1,338 @@
+//! Runtime file and network observation of sandboxed servers
+//!
+//! With `[ebpf] enabled = true` the eBPF program in `ebpf/observer.bpf.c`
+//! traces `openat` and `connect` in every sandboxed server's process tree
+//! as they happen, where sandbox learning otherwise only samples what is
+//! open at the moment. The first time a server opens a path or connects
+//! to an endpoint is written to the audit log, and a running learning
+//! session of the server is given every event.
+//!
+//! Needs supermcp built with the `ebpf` feature, a kernel with BTF, and
+//! `CAP_BPF` plus `CAP_PERFMON` (or root).
+
+use crate::audit::{self, AuditEvent, AuditEventType};
+use crate::config::EbpfConfig;
+use crate::sandbox::learn;
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+const EVENT_OPEN: u32 = 1;
+const EVENT_CONNECT: u32 = 2;
+
+/// Size of `struct event` before its data
+const HEADER_LEN: usize = 24;
+
+/// Access mode bits of open flags; read-only is 0
+const O_ACCMODE: u32 = 0o3;
+
+/// `dirfd` meaning the current directory
+const AT_FDCWD: i32 = -100;
+
+const AF_UNIX: u16 = 1;
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+
+static OBSERVER: OnceCell<Arc<Observer>> = OnceCell::new();
+
+/// Something a server was seen doing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Activity {
+    /// Opened a path, or connected to a Unix socket at it
+    Open { path: String, written: bool },
+    /// Connected to `address:port`
+    Connect { endpoint: String },
+}
+
+/// One event from the eBPF program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub pid: u32,
+    pub server: u32,
+    pub dirfd: i32,
+    pub activity: Activity,
+}
+
+/// Decode a `struct event`; Unix socket and relative paths are left as
+/// the process gave them
+pub fn parse_event(bytes: &[u8]) -> Option<Event> {
+    let word = |i: usize| Some(u32::from_ne_bytes(bytes.get(i * 4..i * 4 + 4)?.try_into().ok()?));
+    let (pid, server, kind, flags) = (word(0)?, word(1)?, word(2)?, word(3)?);
+    let dirfd = word(4)? as i32;
+    let len = word(5)? as usize;
+    let data = bytes.get(HEADER_LEN..HEADER_LEN + len)?;
+
+    let activity = match kind {
+        EVENT_OPEN => Activity::Open {
+            path: String::from_utf8_lossy(data).into_owned(),
+            written: flags & O_ACCMODE != 0,
+        },
+        EVENT_CONNECT => sockaddr(data)?,
+        _ => return None,
+    };
+    Some(Event {
+        pid,
+        server,
+        dirfd,
+        activity,
+    })
+}
+
+/// The endpoint a `struct sockaddr` names
+fn sockaddr(data: &[u8]) -> Option<Activity> {
+    let family = u16::from_ne_bytes(data.get(0..2)?.try_into().ok()?);
+    let port = || Some(u16::from_be_bytes(data.get(2..4)?.try_into().ok()?));
+    let endpoint = match family {
+        AF_INET => {
+            let address: [u8; 4] = data.get(4..8)?.try_into().ok()?;
+            format!("{}:{}", Ipv4Addr::from(address), port()?)
+        }
+        AF_INET6 => {
+            let address: [u8; 16] = data.get(8..24)?.try_into().ok()?;
+            let address = Ipv6Addr::from(address);
+            match address.to_ipv4_mapped() {
+                Some(v4) => format!("{}:{}", v4, port()?),
+                None => format!("[{}]:{}", address, port()?),
+            }
+        }
+        AF_UNIX => {
+            let path = data.get(2..)?;
+            let path = &path[..path.iter().position(|b| *b == 0).unwrap_or(path.len())];
+            // Abstract sockets have no path
+            if path.is_empty() {
+                return None;
+            }
+            return Some(Activity::Open {
+                path: String::from_utf8_lossy(path).into_owned(),
+                written: true,
+            });
+        }
+        _ => return None,
+    };
+    Some(Activity::Connect { endpoint })
+}
+
+/// Start observing, for the rest of the process's life
+pub async fn start(config: &EbpfConfig) -> McpResult<()> {
+    let observer = Observer::load(config)?;
+    if OBSERVER.set(observer).is_err() {
+        return Err(McpError::SandboxError("The eBPF observer is already running".to_string()));
+    }
+    info!("eBPF observer watching sandboxed servers");
+    Ok(())
+}
+
+/// Observe `pid` and its descendants as `server`, when observing
+pub fn watch(server: &str, pid: u32) {
+    if let Some(observer) = OBSERVER.get() {
+        if let Err(e) = observer.watch(server, pid) {
+            warn!("Cannot observe {} (pid {}): {}", server, pid, e);
+        }
+    }
+}
+
+/// Decodes events of the eBPF program and passes them on
+pub struct Observer {
+    #[cfg(all(target_os = "linux", feature = "ebpf"))]
+    program: super::ebpf::ObserverProgram,
+    /// Server of each id given to the program
+    servers: DashMap<u32, String>,
+    ids: DashMap<String, u32>,
+    next_id: AtomicU32,
+    /// What each server has been seen doing, so each is audited once
+    seen: DashMap<String, HashSet<String>>,
+    audit: bool,
+}
+
+impl Observer {
+    #[cfg(all(target_os = "linux", feature = "ebpf"))]
+    fn load(config: &EbpfConfig) -> McpResult<Arc<Self>> {
+        let (program, events) =
+            super::ebpf::ObserverProgram::load(Path::new(&shellexpand::tilde(&config.program)))?;
+        let observer = Arc::new(Self::new(config, program));
+        let reader = observer.clone();
+        tokio::spawn(async move {
+            let result = super::ebpf::read_events(events, |bytes| match parse_event(bytes) {
+                Some(event) => reader.handle(event),
+                None => tracing::debug!("Skipping a malformed eBPF event"),
+            })
+            .await;
+            if let Err(e) = result {
+                warn!("Stopped reading eBPF events: {}", e);
+            }
+        });
+        Ok(observer)
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "ebpf")))]
+    fn load(_config: &EbpfConfig) -> McpResult<Arc<Self>> {
+        Err(McpError::ConfigError(
+            "The eBPF observer needs supermcp built for Linux with the ebpf feature".to_string(),
+        ))
+    }
+
+    #[cfg(all(target_os = "linux", feature = "ebpf"))]
+    fn new(config: &EbpfConfig, program: super::ebpf::ObserverProgram) -> Self {
+        Self {
+            program,
+            servers: DashMap::new(),
+            ids: DashMap::new(),
+            next_id: AtomicU32::new(1),
+            seen: DashMap::new(),
+            audit: config.audit,
+        }
+    }
+
+    #[cfg(all(test, not(all(target_os = "linux", feature = "ebpf"))))]
+    fn new(config: &EbpfConfig) -> Self {
+        Self {
+            servers: DashMap::new(),
+            ids: DashMap::new(),
+            next_id: AtomicU32::new(1),
+            seen: DashMap::new(),
+            audit: config.audit,
+        }
+    }
+
+    fn watch(&self, server: &str, pid: u32) -> McpResult<()> {
+        let id = *self.ids.entry(server.to_string()).or_insert_with(|| {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            self.servers.insert(id, server.to_string());
+            id
+        });
+        #[cfg(all(target_os = "linux", feature = "ebpf"))]
+        self.program.watch(pid, id)?;
+        #[cfg(not(all(target_os = "linux", feature = "ebpf")))]
+        let _ = (pid, id);
+        Ok(())
+    }
+
+    /// Audit an event and give it to the server's learning session
+    pub fn handle(&self, mut event: Event) {
+        let Some(server) = self.servers.get(&event.server).map(|name| name.clone()) else {
+            return;
+        };
+        if let Activity::Open { path, .. } = &mut event.activity {
+            if let Some(absolute) = resolve(event.pid, event.dirfd, path) {
+                *path = absolute;
+            }
+        }
+
+        if let Some(session) = learn::session(&server).filter(|session| !session.finished()) {
+            session.record(&event.activity);
+        }
+
+        let key = match &event.activity {
+            Activity::Open { path, .. } => path.clone(),
+            Activity::Connect { endpoint } => endpoint.clone(),
+        };
+        let first = self.seen.entry(server.clone()).or_default().insert(key);
+        if self.audit && first {
+            let details = match &event.activity {
+                Activity::Open { path, written } => {
+                    serde_json::json!({ "path": path, "written": written, "pid": event.pid })
+                }
+                Activity::Connect { endpoint } => {
+                    serde_json::json!({ "endpoint": endpoint, "pid": event.pid })
+                }
+            };
+            audit::record(
+                AuditEvent::new(AuditEventType::SandboxObserved)
+                    .with_server_name(&server)
+                    .with_details(details),
+            );
+        }
+    }
+}
+
+/// Absolute form of a path the process opened relative to a directory
+fn resolve(pid: u32, dirfd: i32, path: &str) -> Option<String> {
+    if path.starts_with('/') {
+        return None;
+    }
+    let base = if dirfd == AT_FDCWD {
+        format!("/proc/{}/cwd", pid)
+    } else {
+        format!("/proc/{}/fd/{}", pid, dirfd)
+    };
+    let base = std::fs::read_link(base).ok()?;
+    Some(Path::new(&base).join(path).to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: u32, flags: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for word in [42, 1, kind, flags, AT_FDCWD as u32, data.len() as u32] {
+            bytes.extend_from_slice(&word.to_ne_bytes());
+        }
+        bytes.extend_from_slice(data);
+        bytes.resize(HEADER_LEN + 256, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let open = parse_event(&event(EVENT_OPEN, 0o1101, b"/tmp/out.log")).unwrap();
+        assert_eq!((open.pid, open.server, open.dirfd), (42, 1, AT_FDCWD));
+        assert_eq!(
+            open.activity,
+            Activity::Open {
+                path: "/tmp/out.log".to_string(),
+                written: true
+            }
+        );
+
+        let mut inet = AF_INET.to_ne_bytes().to_vec();
+        inet.extend_from_slice(&443u16.to_be_bytes());
+        inet.extend_from_slice(&[93, 184, 216, 34]);
+        inet.resize(16, 0);
+        assert_eq!(
+            parse_event(&event(EVENT_CONNECT, 0, &inet)).unwrap().activity,
+            Activity::Connect {
+                endpoint: "93.184.216.34:443".to_string()
+            }
+        );
+
+        let mut unix = AF_UNIX.to_ne_bytes().to_vec();
+        unix.extend_from_slice(b"/run/docker.sock\0");
+        assert_eq!(
+            parse_event(&event(EVENT_CONNECT, 0, &unix)).unwrap().activity,
+            Activity::Open {
+                path: "/run/docker.sock".to_string(),
+                written: true
+            }
+        );
+
+        assert!(parse_event(&event(9, 0, b"")).is_none());
+        assert!(parse_event(&[0; 8]).is_none());
+    }
+
+    /// Sandboxed servers are handed to the observer as they are spawned
+    #[cfg(all(target_os = "linux", not(feature = "ebpf")))]
+    #[tokio::test]
+    async fn test_spawned_server_is_watched() {
+        let observer = Arc::new(Observer::new(&EbpfConfig::default()));
+        assert!(OBSERVER.set(observer.clone()).is_ok());
+
+        let mut config = crate::config::McpServerConfig {
+            name: "observed".to_string(),
+            command: "sleep".to_string(),
+            args: vec!["10".to_string()],
+            ..Default::default()
+        };
+        config.sandbox.enabled = true;
+        config.sandbox.env_inherit = true;
+        let mut child = crate::sandbox::create_sandbox(&config).spawn(&config).await.unwrap();
+        let _ = child.kill().await;
+        assert!(observer.ids.contains_key("observed"));
+    }
+}
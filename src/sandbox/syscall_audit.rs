@@ -0,0 +1,263 @@
+//! Syscall audit for servers running with `seccomp_mode = "audit"`
+//!
+//! In audit mode the seccomp filter lets syscalls outside its allow-list
+//! through and the kernel logs each one (`SECCOMP_RET_LOG`, audit record
+//! type 1326). Without auditd running the records land in the kernel log,
+//! read here from `/dev/kmsg` (needs `CAP_SYSLOG` or
+//! `kernel.dmesg_restrict = 0`). Each is matched to a server by process
+//! ancestry and counted, and the report suggests the `seccomp_allow` list
+//! for an enforcing filter that passes exactly what the server used.
+
+use crate::config::{McpServerConfig, SeccompMode};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, info, warn};
+
+/// Audit record type of seccomp decisions
+const AUDIT_SECCOMP: &str = "type=1326";
+
+/// `code` of a record logged by `SECCOMP_RET_LOG`
+const SECCOMP_RET_LOG: &str = "code=0x7ffc0000";
+
+/// How far up the process tree a record is matched to a server
+const MAX_ANCESTRY: usize = 64;
+
+static AUDITOR: Lazy<SyscallAuditor> = Lazy::new(SyscallAuditor::default);
+
+/// The process-wide auditor
+pub fn global() -> &'static SyscallAuditor {
+    &AUDITOR
+}
+
+/// Syscalls attempted by audited servers outside the allow-list
+#[derive(Default)]
+pub struct SyscallAuditor {
+    /// Server of each audited process tree, by the root's PID
+    roots: DashMap<u32, String>,
+    /// Attempts per syscall number, per server
+    syscalls: DashMap<String, BTreeMap<i64, u64>>,
+    reader_started: AtomicBool,
+}
+
+/// Syscall settings for a server's enforcing filter
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SeccompProfile {
+    pub seccomp_mode: SeccompMode,
+    pub seccomp_allow: Vec<i64>,
+}
+
+/// What a server was seen attempting, for `/admin/v1/servers/{name}/syscalls`
+#[derive(Debug, Clone, Serialize)]
+pub struct SyscallReport {
+    pub server: String,
+    pub seccomp_mode: SeccompMode,
+    /// Architecture the syscall numbers belong to
+    pub arch: &'static str,
+    /// Attempts per syscall number outside the allow-list
+    pub syscalls: BTreeMap<i64, u64>,
+    /// Enforcing settings allowing the configured and the seen syscalls
+    pub suggested: SeccompProfile,
+}
+
+impl SyscallAuditor {
+    /// Attribute syscalls logged for `pid` and its descendants to `server`
+    pub fn register(&self, server: &str, pid: u32) {
+        self.roots
+            .retain(|pid, _| std::path::Path::new(&format!("/proc/{}", pid)).exists());
+        self.roots.insert(pid, server.to_string());
+        if !self.reader_started.swap(true, Ordering::SeqCst) {
+            if let Err(e) = std::thread::Builder::new()
+                .name("syscall-audit".to_string())
+                .spawn(|| read_kernel_log(global()))
+            {
+                warn!("Failed to start the syscall audit reader: {}", e);
+            }
+        }
+    }
+
+    /// Count a logged syscall against the server `pid` belongs to
+    pub fn record(&self, pid: u32, syscall: i64) {
+        let Some(server) = self.server_of(pid) else {
+            return;
+        };
+        debug!("{} attempted syscall {}", server, syscall);
+        *self.syscalls.entry(server).or_default().entry(syscall).or_default() += 1;
+    }
+
+    /// Live processes of an audited server: its roots and their descendants
+    pub fn processes(&self, server: &str) -> Vec<u32> {
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| self.server_of(*pid).is_some_and(|name| name == server))
+            .collect()
+    }
+
+    fn server_of(&self, pid: u32) -> Option<String> {
+        let mut pid = pid;
+        for _ in 0..MAX_ANCESTRY {
+            if let Some(server) = self.roots.get(&pid) {
+                return Some(server.clone());
+            }
+            pid = parent_pid(pid)?;
+        }
+        None
+    }
+
+    pub fn report(&self, config: &McpServerConfig) -> SyscallReport {
+        let syscalls = self
+            .syscalls
+            .get(&config.name)
+            .map(|syscalls| syscalls.clone())
+            .unwrap_or_default();
+        let mut allow = config.sandbox.seccomp_allow.clone();
+        allow.extend(syscalls.keys());
+        allow.sort_unstable();
+        allow.dedup();
+        SyscallReport {
+            server: config.name.clone(),
+            seccomp_mode: config.sandbox.seccomp_mode,
+            arch: std::env::consts::ARCH,
+            syscalls,
+            suggested: SeccompProfile {
+                seccomp_mode: SeccompMode::Enforce,
+                seccomp_allow: allow,
+            },
+        }
+    }
+}
+
+/// Count seccomp records from the kernel log, from now on
+fn read_kernel_log(auditor: &SyscallAuditor) {
+    let mut kmsg = match std::fs::File::open("/dev/kmsg") {
+        Ok(kmsg) => kmsg,
+        Err(e) => {
+            warn!("Cannot read the kernel log, audited syscalls go unreported: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = kmsg.seek(SeekFrom::End(0)) {
+        debug!("Cannot skip earlier kernel log records: {}", e);
+    }
+    info!("Reading audited syscalls from the kernel log");
+    for line in BufReader::new(kmsg).lines() {
+        match line {
+            Ok(line) => {
+                if let Some((pid, syscall)) = parse_record(&line) {
+                    auditor.record(pid, syscall);
+                }
+            }
+            // Records were overwritten before being read
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => continue,
+            Err(e) => {
+                warn!("Stopped reading the kernel log: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// PID and syscall number of a `SECCOMP_RET_LOG` record
+pub fn parse_record(line: &str) -> Option<(u32, i64)> {
+    if !line.contains(AUDIT_SECCOMP) || !line.contains(SECCOMP_RET_LOG) {
+        return None;
+    }
+    let field = |key: &str| {
+        line.split_whitespace()
+            .find_map(|part| part.strip_prefix(key)?.strip_prefix('='))
+    };
+    Some((field("pid")?.parse().ok()?, field("syscall")?.parse().ok()?))
+}
+
+/// Parent of a process, from `/proc/<pid>/stat`
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces and parentheses; the state and
+    // parent follow the last `)`
+    let (_, rest) = stat.rsplit_once(')')?;
+    let parent = rest.split_whitespace().nth(1)?.parse().ok()?;
+    (parent != 0).then_some(parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_suggests_seen_syscalls() {
+        let line = "12,3456,789012,-;audit: type=1326 audit(1760000000.123:42): auid=1000 \
+                    uid=1000 gid=1000 ses=3 pid=4321 comm=\"node\" exe=\"/usr/bin/node\" \
+                    sig=0 arch=c000003e syscall=165 compat=0 ip=0x7f00 code=0x7ffc0000";
+        assert_eq!(parse_record(line), Some((4321, 165)));
+        assert_eq!(parse_record(&line.replace("0x7ffc0000", "0x50000")), None);
+        assert_eq!(parse_record("6,1,2,-;eth0: link up"), None);
+
+        let auditor = SyscallAuditor::default();
+        let pid = std::process::id();
+        auditor.roots.insert(pid, "files".to_string());
+        auditor.record(pid, 165);
+        auditor.record(pid, 165);
+        auditor.record(pid, 12);
+        auditor.record(u32::MAX, 99);
+
+        let mut config = McpServerConfig {
+            name: "files".to_string(),
+            ..Default::default()
+        };
+        config.sandbox.seccomp_mode = SeccompMode::Audit;
+        config.sandbox.seccomp_allow = vec![12, 300];
+        let report = auditor.report(&config);
+        assert_eq!(report.syscalls, BTreeMap::from([(12, 1), (165, 2)]));
+        assert_eq!(report.suggested.seccomp_mode, SeccompMode::Enforce);
+        assert_eq!(report.suggested.seccomp_allow, vec![12, 165, 300]);
+    }
+
+    /// An audited server spawned through the platform sandbox has the
+    /// syscalls outside its allow-list reported
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_audited_server_is_reported() {
+        let logged = std::fs::read_to_string("/proc/sys/kernel/seccomp/actions_logged")
+            .is_ok_and(|actions| actions.split_whitespace().any(|action| action == "log"));
+        if !logged || std::fs::File::open("/dev/kmsg").is_err() {
+            eprintln!("Skipping: seccomp log records are not readable here");
+            return;
+        }
+
+        let mut config = McpServerConfig {
+            name: "audited".to_string(),
+            command: "/bin/sh".to_string(),
+            // `nice` calls setpriority, which the filter does not allow; it
+            // execs in place, so the record names the server's own process
+            args: vec!["-c".to_string(), "sleep 1; exec nice -n 1 sleep 10".to_string()],
+            ..Default::default()
+        };
+        config.sandbox.enabled = true;
+        config.sandbox.env_inherit = true;
+        config.sandbox.seccomp_mode = SeccompMode::Audit;
+
+        let mut child = crate::sandbox::create_sandbox(&config).spawn(&config).await.unwrap();
+        let mut report = global().report(&config);
+        for _ in 0..50 {
+            if report.syscalls.contains_key(&libc::SYS_setpriority) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            report = global().report(&config);
+        }
+        let _ = child.kill().await;
+        assert!(
+            report.syscalls.contains_key(&libc::SYS_setpriority),
+            "setpriority not reported: {:?}",
+            report.syscalls
+        );
+        assert!(report.suggested.seccomp_allow.contains(&libc::SYS_setpriority));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::McpServerConfig;
+use crate::config::{FilesystemAccess, McpServerConfig, SandboxConfig};
 use crate::utils::errors::McpResult;
 use async_trait::async_trait;
 use tokio::process::Child;
@@ -17,9 +17,23 @@ pub struct SandboxConstraints {
 pub enum FilesystemConstraint {
     Full,
     ReadOnly,
+    /// Read everywhere, write only under these paths
+    ReadOnlyExcept(Vec<String>),
     Paths(Vec<String>),
 }
 
+impl FilesystemConstraint {
+    /// The access a server's `filesystem` and `writable` settings grant
+    pub fn from_config(sandbox: &SandboxConfig) -> Self {
+        match &sandbox.filesystem {
+            FilesystemAccess::Simple(s) if s == "full" => Self::Full,
+            FilesystemAccess::Paths(paths) => Self::Paths(paths.clone()),
+            _ if sandbox.writable.is_empty() => Self::ReadOnly,
+            _ => Self::ReadOnlyExcept(sandbox.writable.clone()),
+        }
+    }
+}
+
 /// Trait for sandbox implementations
 #[async_trait]
 pub trait Sandbox: Send + Sync {
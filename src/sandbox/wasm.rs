@@ -73,18 +73,7 @@ impl WasmSandbox {
     pub fn from_config(server_config: &McpServerConfig) -> Self {
         let constraints = SandboxConstraints {
             network: server_config.sandbox.network,
-            filesystem: match &server_config.sandbox.filesystem {
-                crate::config::FilesystemAccess::Simple(s) if s == "readonly" => {
-                    FilesystemConstraint::ReadOnly
-                }
-                crate::config::FilesystemAccess::Simple(s) if s == "full" => {
-                    FilesystemConstraint::Full
-                }
-                crate::config::FilesystemAccess::Paths(paths) => {
-                    FilesystemConstraint::Paths(paths.clone())
-                }
-                _ => FilesystemConstraint::ReadOnly,
-            },
+            filesystem: FilesystemConstraint::from_config(&server_config.sandbox),
             env_inherit: server_config.sandbox.env_inherit,
             max_memory_mb: server_config.sandbox.max_memory_mb,
             max_cpu_percent: server_config.sandbox.max_cpu_percent,
@@ -370,6 +359,7 @@ mod tests {
                 max_memory_mb: 256,
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let sandbox = WasmSandbox::from_config(&server_config);
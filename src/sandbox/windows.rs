@@ -23,18 +23,7 @@ impl WindowsSandbox {
     pub fn from_config(config: &McpServerConfig) -> Self {
         let constraints = SandboxConstraints {
             network: config.sandbox.network,
-            filesystem: match &config.sandbox.filesystem {
-                crate::config::FilesystemAccess::Simple(s) if s == "readonly" => {
-                    FilesystemConstraint::ReadOnly
-                }
-                crate::config::FilesystemAccess::Simple(s) if s == "full" => {
-                    FilesystemConstraint::Full
-                }
-                crate::config::FilesystemAccess::Paths(paths) => {
-                    FilesystemConstraint::Paths(paths.clone())
-                }
-                _ => FilesystemConstraint::ReadOnly,
-            },
+            filesystem: FilesystemConstraint::from_config(&config.sandbox),
             env_inherit: config.sandbox.env_inherit,
             max_memory_mb: config.sandbox.max_memory_mb,
             max_cpu_percent: config.sandbox.max_cpu_percent,
@@ -201,6 +190,7 @@ mod tests {
             tags: vec![],
             description: None,
             sandbox: Default::default(),
+            ..Default::default()
         };
 
         let sandbox = WindowsSandbox::from_config(&config);
@@ -0,0 +1,413 @@
+//! Managed skill installation
+//!
+//! Each installed skill lives in `<directory>/<name>/` alongside an install
+//! record noting where it came from, so skills can be listed, upgraded and
+//! removed without touching hand-managed skill directories.
+
+use crate::config::SkillsConfig;
+use crate::skills::manifest::SkillManifest;
+use crate::utils::errors::{McpError, McpResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// Install record kept next to SKILL.md
+const RECORD_FILE: &str = ".supermcp-skill.json";
+
+/// Where a skill is installed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillSource {
+    /// Local directory containing SKILL.md
+    Path(PathBuf),
+    /// Git repository, optionally at a branch or tag
+    Git { url: String, reference: Option<String> },
+    /// Name to look up in the registry
+    Registry(String),
+}
+
+impl SkillSource {
+    /// Classify an install argument
+    pub fn parse(spec: &str, reference: Option<String>) -> Self {
+        let is_git = spec.starts_with("git@")
+            || spec.starts_with("git+")
+            || spec.starts_with("ssh://")
+            || spec.ends_with(".git")
+            || ((spec.starts_with("https://") || spec.starts_with("http://"))
+                && !spec.ends_with(".md"));
+
+        if is_git {
+            let url = spec.strip_prefix("git+").unwrap_or(spec).to_string();
+            return SkillSource::Git { url, reference };
+        }
+
+        let path = PathBuf::from(shellexpand::tilde(spec).to_string());
+        if path.exists() || spec.starts_with('.') || spec.starts_with('/') || spec.starts_with('~') {
+            return SkillSource::Path(path);
+        }
+
+        SkillSource::Registry(spec.to_string())
+    }
+}
+
+impl std::fmt::Display for SkillSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillSource::Path(path) => write!(f, "{}", path.display()),
+            SkillSource::Git { url, reference: Some(r) } => write!(f, "{}#{}", url, r),
+            SkillSource::Git { url, reference: None } => write!(f, "{}", url),
+            SkillSource::Registry(name) => write!(f, "registry:{}", name),
+        }
+    }
+}
+
+/// How and when a skill was installed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// A skill in the managed directory
+#[derive(Debug, Clone)]
+pub struct InstalledSkill {
+    pub manifest: SkillManifest,
+    pub record: Option<InstallRecord>,
+    pub path: PathBuf,
+}
+
+/// Installs, lists and removes skills under a managed directory
+pub struct SkillManager {
+    directory: PathBuf,
+}
+
+impl SkillManager {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn from_config(config: &SkillsConfig) -> Self {
+        Self::new(shellexpand::tilde(&config.directory).to_string())
+    }
+
+    /// Managed skills directory
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Install a skill, replacing an installed copy with a different
+    /// version or revision
+    ///
+    /// Registry sources must be resolved to a git source first. Returns
+    /// the new skill and the copy it replaced, if any.
+    pub async fn install(
+        &self,
+        source: &SkillSource,
+        force: bool,
+    ) -> McpResult<(InstalledSkill, Option<InstalledSkill>)> {
+        tokio::fs::create_dir_all(&self.directory).await?;
+        let staging = self
+            .directory
+            .join(format!(".staging-{}", uuid::Uuid::new_v4()));
+
+        let result = self.install_staged(source, &staging, force).await;
+        if staging.exists() {
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+        }
+        result
+    }
+
+    async fn install_staged(
+        &self,
+        source: &SkillSource,
+        staging: &Path,
+        force: bool,
+    ) -> McpResult<(InstalledSkill, Option<InstalledSkill>)> {
+        let revision = match source {
+            SkillSource::Path(path) => {
+                copy_dir(path.clone(), staging.to_path_buf()).await?;
+                None
+            }
+            SkillSource::Git { url, reference } => {
+                Some(git_clone(url, reference.as_deref(), staging).await?)
+            }
+            SkillSource::Registry(name) => {
+                return Err(McpError::ConfigError(format!(
+                    "Registry skill '{}' must be resolved to a repository before installing",
+                    name
+                )))
+            }
+        };
+
+        let manifest = SkillManifest::load(staging).await?;
+        let destination = self.directory.join(&manifest.name);
+        let previous = self.get(&manifest.name).await.ok().flatten();
+
+        if let Some(existing) = &previous {
+            let same_version = existing.manifest.version == manifest.version;
+            let same_revision =
+                existing.record.as_ref().and_then(|r| r.revision.clone()) == revision;
+            if same_version && same_revision && !force {
+                return Err(McpError::ConfigError(format!(
+                    "Skill '{}' {} is already installed (use --force to reinstall)",
+                    manifest.name,
+                    manifest.version.as_deref().unwrap_or("(unversioned)")
+                )));
+            }
+        }
+
+        let record = InstallRecord {
+            source: source.to_string(),
+            revision,
+            installed_at: Utc::now(),
+        };
+        tokio::fs::write(
+            staging.join(RECORD_FILE),
+            serde_json::to_string_pretty(&record)?,
+        )
+        .await?;
+
+        if destination.exists() {
+            tokio::fs::remove_dir_all(&destination).await?;
+        }
+        tokio::fs::rename(staging, &destination).await?;
+        info!("Installed skill {} to {}", manifest.name, destination.display());
+
+        Ok((
+            InstalledSkill {
+                manifest,
+                record: Some(record),
+                path: destination,
+            },
+            previous,
+        ))
+    }
+
+    /// Look up an installed skill
+    pub async fn get(&self, name: &str) -> McpResult<Option<InstalledSkill>> {
+        let path = self.directory.join(name);
+        if !path.join("SKILL.md").exists() {
+            return Ok(None);
+        }
+        read_installed(path).await.map(Some)
+    }
+
+    /// All installed skills, sorted by name
+    pub async fn list(&self) -> McpResult<Vec<InstalledSkill>> {
+        let mut skills = Vec::new();
+        if !self.directory.exists() {
+            return Ok(skills);
+        }
+
+        let mut entries = tokio::fs::read_dir(&self.directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if hidden || !path.join("SKILL.md").exists() {
+                continue;
+            }
+            match read_installed(path).await {
+                Ok(skill) => skills.push(skill),
+                Err(e) => debug!("Skipping invalid skill in {:?}: {}", entry.file_name(), e),
+            }
+        }
+
+        skills.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+        Ok(skills)
+    }
+
+    /// Remove an installed skill
+    pub async fn remove(&self, name: &str) -> McpResult<()> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(McpError::ConfigError(format!("Invalid skill name '{}'", name)));
+        }
+
+        let path = self.directory.join(name);
+        if !path.join("SKILL.md").exists() {
+            return Err(McpError::ConfigError(format!("Skill '{}' is not installed", name)));
+        }
+
+        tokio::fs::remove_dir_all(&path).await?;
+        info!("Removed skill {}", name);
+        Ok(())
+    }
+}
+
+async fn read_installed(path: PathBuf) -> McpResult<InstalledSkill> {
+    let manifest = SkillManifest::load(&path).await?;
+    let record = match tokio::fs::read_to_string(path.join(RECORD_FILE)).await {
+        Ok(content) => serde_json::from_str(&content).ok(),
+        Err(_) => None,
+    };
+    Ok(InstalledSkill {
+        manifest,
+        record,
+        path,
+    })
+}
+
+/// Shallow-clone a repository and return the checked-out commit
+async fn git_clone(url: &str, reference: Option<&str>, destination: &Path) -> McpResult<String> {
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(reference) = reference {
+        clone.args(["--branch", reference]);
+    }
+    // Nothing after `--` is taken as an option, whatever the URL holds
+    clone.arg("--").arg(url).arg(destination);
+    run_git(clone, "clone").await?;
+
+    let mut rev_parse = Command::new("git");
+    rev_parse.arg("-C").arg(destination).args(["rev-parse", "HEAD"]);
+    let revision = run_git(rev_parse, "rev-parse").await?;
+
+    tokio::fs::remove_dir_all(destination.join(".git")).await?;
+    Ok(revision)
+}
+
+async fn run_git(mut command: Command, action: &str) -> McpResult<String> {
+    let output = command
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await
+        .map_err(|e| McpError::InstallError(format!("Failed to run git {}: {}", action, e)))?;
+
+    if !output.status.success() {
+        return Err(McpError::InstallError(format!(
+            "git {} failed: {}",
+            action,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Recursively copy a skill directory, leaving out VCS metadata
+async fn copy_dir(source: PathBuf, destination: PathBuf) -> McpResult<()> {
+    fn copy(source: &Path, destination: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(destination)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            let target = destination.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                copy(&entry.path(), &target)?;
+            } else {
+                std::fs::copy(entry.path(), target)?;
+            }
+        }
+        Ok(())
+    }
+
+    if !source.join("SKILL.md").exists() {
+        return Err(McpError::ConfigError(format!(
+            "No SKILL.md found in {}",
+            source.display()
+        )));
+    }
+
+    tokio::task::spawn_blocking(move || copy(&source, &destination))
+        .await
+        .map_err(|e| McpError::InternalError(e.to_string()))??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn write_skill(dir: &Path, version: &str) {
+        tokio::fs::create_dir_all(dir.join("scripts")).await.unwrap();
+        tokio::fs::write(
+            dir.join("SKILL.md"),
+            format!("---\nname: demo\ndescription: Demo skill\nversion: {}\n---\n", version),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(dir.join("scripts/run.sh"), "echo hi").await.unwrap();
+    }
+
+    #[test]
+    fn test_source_parsing() {
+        assert!(matches!(
+            SkillSource::parse("https://github.com/acme/skills", None),
+            SkillSource::Git { .. }
+        ));
+        assert!(matches!(
+            SkillSource::parse("git@github.com:acme/skills.git", Some("v1".to_string())),
+            SkillSource::Git { reference: Some(_), .. }
+        ));
+        assert!(matches!(SkillSource::parse("./my-skill", None), SkillSource::Path(_)));
+        assert_eq!(
+            SkillSource::parse("pdf-tools", None),
+            SkillSource::Registry("pdf-tools".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_list_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("src");
+        write_skill(&source, "1.0.0").await;
+
+        let manager = SkillManager::new(dir.path().join("managed"));
+        let (skill, previous) = manager
+            .install(&SkillSource::Path(source.clone()), false)
+            .await
+            .unwrap();
+        assert_eq!(skill.manifest.name, "demo");
+        assert!(previous.is_none());
+        assert!(skill.path.join("scripts/run.sh").exists());
+
+        // Same version again needs --force
+        assert!(manager
+            .install(&SkillSource::Path(source.clone()), false)
+            .await
+            .is_err());
+
+        write_skill(&source, "1.1.0").await;
+        let (skill, previous) = manager
+            .install(&SkillSource::Path(source), false)
+            .await
+            .unwrap();
+        assert_eq!(skill.manifest.version.as_deref(), Some("1.1.0"));
+        assert_eq!(previous.unwrap().manifest.version.as_deref(), Some("1.0.0"));
+
+        let listed = manager.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].record.is_some());
+
+        manager.remove("demo").await.unwrap();
+        assert!(manager.list().await.unwrap().is_empty());
+        assert!(manager.remove("../etc").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_skill_not_installed() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("src");
+        tokio::fs::create_dir_all(&source).await.unwrap();
+        tokio::fs::write(source.join("SKILL.md"), "# No frontmatter\n").await.unwrap();
+
+        let manager = SkillManager::new(dir.path().join("managed"));
+        assert!(manager.install(&SkillSource::Path(source), false).await.is_err());
+        assert!(manager.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_git_url_is_never_an_option() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("pwned");
+        let url = format!("--upload-pack=touch {}", marker.display());
+
+        assert!(git_clone(&url, None, &dir.path().join("clone")).await.is_err());
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,202 @@
+//! SKILL.md frontmatter parsing and validation
+
+use crate::core::command::Version;
+use crate::runtime::types::ResourceLimits;
+use crate::utils::errors::{McpError, McpResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Longest allowed skill name
+const MAX_NAME_LEN: usize = 64;
+
+/// Longest allowed skill description
+const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// Metadata from the YAML frontmatter at the top of SKILL.md
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillManifest {
+    /// Lowercase, hyphenated identifier; also the install directory name
+    pub name: String,
+    /// What the skill does and when to use it
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_yaml::Value>,
+    /// Sandbox limits applied when the skill's scripts run
+    #[serde(default)]
+    pub resources: ResourceLimits,
+}
+
+impl SkillManifest {
+    /// Parse and validate the frontmatter of a SKILL.md document
+    pub fn parse(content: &str) -> McpResult<Self> {
+        let frontmatter = frontmatter(content).ok_or_else(|| {
+            McpError::ConfigError(
+                "SKILL.md must start with YAML frontmatter delimited by '---'".to_string(),
+            )
+        })?;
+
+        let manifest: Self = serde_yaml::from_str(frontmatter)
+            .map_err(|e| McpError::ConfigError(format!("Invalid SKILL.md frontmatter: {}", e)))?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Like [`parse`](Self::parse), but `None` for SKILL.md files without
+    /// frontmatter
+    pub fn parse_optional(content: &str) -> McpResult<Option<Self>> {
+        if frontmatter(content).is_none() {
+            return Ok(None);
+        }
+        Self::parse(content).map(Some)
+    }
+
+    /// Read and validate `<dir>/SKILL.md`
+    pub async fn load(dir: &Path) -> McpResult<Self> {
+        let path = dir.join("SKILL.md");
+        let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+            McpError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Check the fields against the skill format's constraints
+    pub fn validate(&self) -> McpResult<()> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= MAX_NAME_LEN
+            && self
+                .name
+                .split('-')
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+        if !valid_name {
+            return Err(McpError::ConfigError(format!(
+                "Invalid skill name '{}': use up to {} lowercase letters, digits and single hyphens",
+                self.name, MAX_NAME_LEN
+            )));
+        }
+
+        let description = self.description.trim();
+        if description.is_empty() {
+            return Err(McpError::ConfigError(format!(
+                "Skill '{}' is missing a description",
+                self.name
+            )));
+        }
+        if description.len() > MAX_DESCRIPTION_LEN {
+            return Err(McpError::ConfigError(format!(
+                "Skill '{}' description exceeds {} characters",
+                self.name, MAX_DESCRIPTION_LEN
+            )));
+        }
+
+        let resources = &self.resources;
+        if resources.max_memory_mb == 0
+            || resources.timeout_seconds == 0
+            || !(1..=100).contains(&resources.max_cpu_percent)
+        {
+            return Err(McpError::ConfigError(format!(
+                "Skill '{}' resources need non-zero max_memory_mb and timeout_seconds and max_cpu_percent between 1 and 100",
+                self.name
+            )));
+        }
+
+        if let Some(version) = &self.version {
+            self.parsed_version().ok_or_else(|| {
+                McpError::ConfigError(format!(
+                    "Skill '{}' has invalid version '{}'",
+                    self.name, version
+                ))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Release part of the version (`1.2.0` of `1.2.0-beta.1`)
+    pub fn parsed_version(&self) -> Option<Version> {
+        let version = self.version.as_deref()?;
+        let release = version.split(['-', '+']).next().unwrap_or(version);
+        release.parse().ok()
+    }
+}
+
+/// Text between the opening and closing `---` lines
+fn frontmatter(content: &str) -> Option<&str> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let rest = content
+        .strip_prefix("---\r\n")
+        .or_else(|| content.strip_prefix("---\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some(&rest[..offset]);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKILL: &str = "---\nname: pdf-tools\ndescription: Extract text from PDFs\nversion: 1.2.0\nmetadata:\n  author: someone\n---\n\n# PDF tools\n";
+
+    #[test]
+    fn test_parse_frontmatter() {
+        let manifest = SkillManifest::parse(SKILL).unwrap();
+        assert_eq!(manifest.name, "pdf-tools");
+        assert_eq!(manifest.version.as_deref(), Some("1.2.0"));
+        assert!(manifest.metadata.contains_key("author"));
+    }
+
+    #[test]
+    fn test_missing_frontmatter() {
+        assert!(SkillManifest::parse("# Just markdown\n").is_err());
+        assert!(SkillManifest::parse("---\nname: x\n").is_err());
+    }
+
+    #[test]
+    fn test_name_rules() {
+        for name in ["PDF", "pdf_tools", "-pdf", "pdf--tools", ""] {
+            let content = format!("---\nname: \"{}\"\ndescription: d\n---\n", name);
+            assert!(SkillManifest::parse(&content).is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn test_version_rules() {
+        let content = "---\nname: a\ndescription: d\nversion: 2.0.0-beta.1\n---\n";
+        let manifest = SkillManifest::parse(content).unwrap();
+        assert_eq!(manifest.parsed_version(), Some("2.0.0".parse().unwrap()));
+
+        let content = "---\nname: a\ndescription: d\nversion: latest\n---\n";
+        assert!(SkillManifest::parse(content).is_err());
+    }
+
+    #[test]
+    fn test_resources() {
+        let manifest = SkillManifest::parse(SKILL).unwrap();
+        assert_eq!(manifest.resources, ResourceLimits::default());
+
+        let content = "---\nname: a\ndescription: d\nresources:\n  max_memory_mb: 128\n  network_access: true\n---\n";
+        let manifest = SkillManifest::parse(content).unwrap();
+        assert_eq!(manifest.resources.max_memory_mb, 128);
+        assert!(manifest.resources.network_access);
+
+        let content = "---\nname: a\ndescription: d\nresources:\n  max_cpu_percent: 0\n---\n";
+        assert!(SkillManifest::parse(content).is_err());
+        assert!(SkillManifest::parse_optional("# No frontmatter").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_description_required() {
+        let content = "---\nname: a\ndescription: \"  \"\n---\n";
+        assert!(SkillManifest::parse(content).is_err());
+    }
+}
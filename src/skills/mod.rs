@@ -0,0 +1,11 @@
+//! Managed agent skills
+//!
+//! Skills are directories holding a SKILL.md (YAML frontmatter plus
+//! instructions) and optional scripts. Installed skills live under
+//! `skills.directory` and are picked up by `supermcp call --skill`.
+
+pub mod manager;
+pub mod manifest;
+
+pub use manager::{InstallRecord, InstalledSkill, SkillManager, SkillSource};
+pub use manifest::SkillManifest;
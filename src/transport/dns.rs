@@ -0,0 +1,359 @@
+//! DNS resolution for upstream HTTP clients
+//!
+//! Used in place of reqwest's resolver when `[servers.http.dns]` changes
+//! anything. Lookups go to the system resolver or, with `nameservers`, are
+//! sent as plain UDP queries for A and AAAA records. Results can be cached,
+//! and an expired entry is kept as a fallback for when resolution flakes.
+//!
+//! Address order decides how connections are dialled: hyper races the
+//! family of the first address against the other one after 300 ms, so
+//! returning a single family turns that off.
+
+use crate::config::{IpPreference, UpstreamDnsConfig};
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tracing::{debug, warn};
+
+/// Lifetime of cached system resolver answers without `ttl_secs`
+const SYSTEM_TTL: Duration = Duration::from_secs(60);
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+struct CachedLookup {
+    addrs: Vec<IpAddr>,
+    expires: Instant,
+}
+
+/// Resolver applying an [`UpstreamDnsConfig`]; clones share the cache
+#[derive(Clone)]
+pub struct UpstreamResolver {
+    config: UpstreamDnsConfig,
+    nameservers: Vec<SocketAddr>,
+    cache: Arc<DashMap<String, CachedLookup>>,
+}
+
+impl UpstreamResolver {
+    pub fn new(config: &UpstreamDnsConfig) -> McpResult<Self> {
+        let nameservers = config
+            .nameservers
+            .iter()
+            .map(|ns| parse_nameserver(ns))
+            .collect::<McpResult<_>>()?;
+        Ok(Self {
+            config: config.clone(),
+            nameservers,
+            cache: Arc::new(DashMap::new()),
+        })
+    }
+
+    /// Addresses for a host, filtered and ordered for dialling
+    pub async fn lookup(&self, host: &str) -> McpResult<Vec<IpAddr>> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self.dialable(vec![ip], host);
+        }
+
+        let cached = self.config.cache.then(|| self.cache.get(host)).flatten();
+        if let Some(entry) = cached.as_ref().filter(|entry| entry.expires > Instant::now()) {
+            return self.dialable(entry.addrs.clone(), host);
+        }
+        let stale = cached.map(|entry| entry.addrs.clone());
+
+        let addrs = match self.resolve_uncached(host).await {
+            Ok((addrs, ttl)) => {
+                let ttl = self.config.ttl_secs.map(Duration::from_secs).unwrap_or(ttl);
+                if let Some(expires) = Instant::now().checked_add(ttl).filter(|_| self.config.cache) {
+                    self.cache.insert(
+                        host.to_string(),
+                        CachedLookup {
+                            addrs: addrs.clone(),
+                            expires,
+                        },
+                    );
+                }
+                addrs
+            }
+            Err(e) => match stale {
+                Some(addrs) => {
+                    warn!("Resolving {} failed, using the expired cached answer: {}", host, e);
+                    addrs
+                }
+                None => return Err(e),
+            },
+        };
+
+        self.dialable(addrs, host)
+    }
+
+    fn dialable(&self, addrs: Vec<IpAddr>, host: &str) -> McpResult<Vec<IpAddr>> {
+        let ordered = order(addrs, self.config.ip_preference, self.config.happy_eyeballs);
+        if ordered.is_empty() {
+            return Err(McpError::TransportError(format!(
+                "No addresses for {} match ip_preference {:?}",
+                host, self.config.ip_preference
+            )));
+        }
+        Ok(ordered)
+    }
+
+    /// Uncached answer and how long it may be cached
+    async fn resolve_uncached(&self, host: &str) -> McpResult<(Vec<IpAddr>, Duration)> {
+        if self.nameservers.is_empty() {
+            let addrs = tokio::net::lookup_host((host, 0))
+                .await
+                .map_err(|e| McpError::TransportError(format!("Failed to resolve {}: {}", host, e)))?
+                .map(|addr| addr.ip())
+                .collect();
+            return Ok((addrs, SYSTEM_TTL));
+        }
+
+        let mut types = Vec::new();
+        if self.config.ip_preference != IpPreference::Ipv6Only {
+            types.push(TYPE_A);
+        }
+        if self.config.ip_preference != IpPreference::Ipv4Only {
+            types.push(TYPE_AAAA);
+        }
+        let answers = futures::future::join_all(types.into_iter().map(|qtype| self.query(host, qtype))).await;
+
+        let mut addrs = Vec::new();
+        let mut ttl = u32::MAX;
+        let mut error = None;
+        for answer in answers {
+            match answer {
+                Ok(records) => {
+                    for (ip, record_ttl) in records {
+                        addrs.push(ip);
+                        ttl = ttl.min(record_ttl);
+                    }
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        match error {
+            Some(e) if addrs.is_empty() => Err(e),
+            _ if addrs.is_empty() => Err(McpError::TransportError(format!("No addresses for {}", host))),
+            _ => Ok((addrs, Duration::from_secs(ttl.into()))),
+        }
+    }
+
+    /// Ask each nameserver in turn for one record type
+    async fn query(&self, host: &str, qtype: u16) -> McpResult<Vec<(IpAddr, u32)>> {
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let mut last_error = None;
+        for nameserver in &self.nameservers {
+            let id = rand::random::<u16>();
+            let query = encode_query(id, host, qtype)?;
+            let attempt = async {
+                let bind: SocketAddr = if nameserver.is_ipv4() {
+                    (Ipv4Addr::UNSPECIFIED, 0).into()
+                } else {
+                    (Ipv6Addr::UNSPECIFIED, 0).into()
+                };
+                let socket = UdpSocket::bind(bind).await?;
+                socket.connect(nameserver).await?;
+                socket.send(&query).await?;
+                let mut buf = [0u8; 1232];
+                loop {
+                    let len = socket.recv(&mut buf).await?;
+                    // Ignore stray datagrams for other queries
+                    if let Some(records) = decode_response(id, &buf[..len]) {
+                        return Ok::<_, std::io::Error>(records);
+                    }
+                }
+            };
+            match tokio::time::timeout(timeout, attempt).await {
+                Ok(Ok(records)) => return records,
+                Ok(Err(e)) => last_error = Some(format!("{}: {}", nameserver, e)),
+                Err(_) => last_error = Some(format!("{}: timed out", nameserver)),
+            }
+            debug!("DNS query for {} failed: {:?}", host, last_error);
+        }
+        Err(McpError::TransportError(format!(
+            "Failed to resolve {}: {}",
+            host,
+            last_error.unwrap_or_default()
+        )))
+    }
+}
+
+impl Resolve for UpstreamResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+/// "1.1.1.1", "1.1.1.1:5353" or "[::1]:53"
+pub fn parse_nameserver(nameserver: &str) -> McpResult<SocketAddr> {
+    nameserver
+        .parse::<SocketAddr>()
+        .or_else(|_| nameserver.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| McpError::ConfigError(format!("Invalid nameserver {:?}", nameserver)))
+}
+
+/// Filter and order addresses by preference; without happy eyeballs only
+/// the first address's family is kept
+fn order(mut addrs: Vec<IpAddr>, preference: IpPreference, happy_eyeballs: bool) -> Vec<IpAddr> {
+    match preference {
+        IpPreference::Auto => {}
+        IpPreference::Ipv4First => addrs.sort_by_key(|ip| ip.is_ipv6()),
+        IpPreference::Ipv6First => addrs.sort_by_key(|ip| ip.is_ipv4()),
+        IpPreference::Ipv4Only => addrs.retain(IpAddr::is_ipv4),
+        IpPreference::Ipv6Only => addrs.retain(IpAddr::is_ipv6),
+    }
+    if !happy_eyeballs {
+        if let Some(first) = addrs.first().copied() {
+            addrs.retain(|ip| ip.is_ipv4() == first.is_ipv4());
+        }
+    }
+    addrs
+}
+
+/// A recursive query for one record type
+fn encode_query(id: u16, host: &str, qtype: u16) -> McpResult<Vec<u8>> {
+    let mut query = Vec::with_capacity(host.len() + 18);
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired, one question
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in host.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(McpError::TransportError(format!("Invalid host name {:?}", host)));
+        }
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&qtype.to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    Ok(query)
+}
+
+/// Skip a possibly compressed name, returning the offset after it
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)?;
+        match len {
+            0 => return Some(pos + 1),
+            len if len & 0xC0 == 0xC0 => return Some(pos + 2),
+            len => pos += 1 + len as usize,
+        }
+    }
+}
+
+fn read_u16(packet: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]))
+}
+
+/// A and AAAA records with their TTLs from a response to query `id`
+///
+/// Returns `None` for packets that are not that response, and an error
+/// when the nameserver reported one.
+fn decode_response(id: u16, packet: &[u8]) -> Option<McpResult<Vec<(IpAddr, u32)>>> {
+    if read_u16(packet, 0)? != id || packet.get(2)? & 0x80 == 0 {
+        return None;
+    }
+    let rcode = packet.get(3)? & 0x0F;
+    // NXDOMAIN and friends; NOERROR with no records just yields nothing
+    if rcode != 0 {
+        return Some(Err(McpError::TransportError(format!(
+            "Nameserver answered with error code {}",
+            rcode
+        ))));
+    }
+
+    let questions = read_u16(packet, 4)?;
+    let answers = read_u16(packet, 6)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(packet, pos)? + 4;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(packet, pos)?;
+        let rtype = read_u16(packet, pos)?;
+        let ttl = u32::from_be_bytes(packet.get(pos + 4..pos + 8)?.try_into().ok()?);
+        let len = read_u16(packet, pos + 8)? as usize;
+        let data = packet.get(pos + 10..pos + 10 + len)?;
+        match (rtype, len) {
+            (TYPE_A, 4) => records.push((IpAddr::from(<[u8; 4]>::try_from(data).ok()?), ttl)),
+            (TYPE_AAAA, 16) => records.push((IpAddr::from(<[u8; 16]>::try_from(data).ok()?), ttl)),
+            // CNAMEs in the chain are followed by the nameserver
+            _ => {}
+        }
+        pos += 10 + len;
+    }
+    Some(Ok(records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ips(addrs: &[&str]) -> Vec<IpAddr> {
+        addrs.iter().map(|a| a.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_order() {
+        let addrs = ips(&["2001:db8::1", "192.0.2.1", "2001:db8::2", "192.0.2.2"]);
+        assert_eq!(
+            order(addrs.clone(), IpPreference::Ipv4First, true),
+            ips(&["192.0.2.1", "192.0.2.2", "2001:db8::1", "2001:db8::2"])
+        );
+        assert_eq!(
+            order(addrs.clone(), IpPreference::Ipv6Only, true),
+            ips(&["2001:db8::1", "2001:db8::2"])
+        );
+        assert_eq!(
+            order(addrs.clone(), IpPreference::Auto, false),
+            ips(&["2001:db8::1", "2001:db8::2"])
+        );
+        assert_eq!(order(addrs.clone(), IpPreference::Auto, true), addrs);
+    }
+
+    #[test]
+    fn test_decode_response() {
+        let query = encode_query(0x1234, "mcp.example.com", TYPE_A).unwrap();
+        let mut response = query.clone();
+        // Response flag, one answer
+        response[2] |= 0x80;
+        response[7] = 1;
+        // Pointer to the question name, A, IN, TTL 300, 4 bytes
+        response.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 1, 44, 0, 4, 192, 0, 2, 7]);
+
+        let records = decode_response(0x1234, &response).unwrap().unwrap();
+        assert_eq!(records, vec![("192.0.2.7".parse().unwrap(), 300)]);
+        assert!(decode_response(0x4321, &response).is_none());
+
+        response[3] = 3;
+        assert!(decode_response(0x1234, &response).unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_literal_and_preference() {
+        let resolver = UpstreamResolver::new(&UpstreamDnsConfig {
+            ip_preference: IpPreference::Ipv6Only,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(resolver.lookup("127.0.0.1").await.is_err());
+        assert_eq!(resolver.lookup("::1").await.unwrap(), ips(&["::1"]));
+        assert!(UpstreamResolver::new(&UpstreamDnsConfig {
+            nameservers: vec!["dns.example".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
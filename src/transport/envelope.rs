@@ -0,0 +1,419 @@
+//! Encrypted payloads between federated super-mcp instances
+//!
+//! Each instance has a static X25519 key pair. The sending instance puts
+//! its public key in `X-SuperMCP-Key`; both sides derive one key per
+//! direction with HKDF-SHA256 from the shared secret, salted with the two
+//! public keys. Bodies are sealed as `nonce || ciphertext || tag` with
+//! ChaCha20-Poly1305 and a random nonce.
+//!
+//! Every sealed request carries a [`Binding`] in `X-SuperMCP-Envelope`: a
+//! timestamp and a fresh ID, authenticated together with the method and
+//! path as associated data. The receiver refuses stale and repeated IDs.
+//! Responses are a sequence of length-prefixed frames, each bound to the
+//! request ID, the status, its position and whether it is the last, so
+//! streamed bodies are sealed as they are produced and cannot be cut short
+//! or spliced into another exchange.
+
+use crate::utils::errors::{McpError, McpResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Header carrying the sender's public key
+pub const KEY_HEADER: &str = "x-supermcp-key";
+
+/// Header carrying the [`Binding`] of a sealed request
+pub const BINDING_HEADER: &str = "x-supermcp-envelope";
+
+/// Largest clock difference accepted on a sealed request, in seconds
+pub const MAX_SKEW_SECS: i64 = 300;
+
+/// Size of the length prefix of a response frame
+const FRAME_LEN_BYTES: usize = 4;
+
+/// Content type of sealed bodies
+pub const SEALED_CONTENT_TYPE: &str = "application/vnd.supermcp.sealed";
+
+/// Message direction, which selects the derived key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+impl Direction {
+    fn info(self) -> &'static [u8] {
+        match self {
+            Direction::Request => b"supermcp federation v1 request",
+            Direction::Response => b"supermcp federation v1 response",
+        }
+    }
+}
+
+/// Static X25519 key pair of this instance
+pub struct FederationKey {
+    secret: [u8; 32],
+    public: [u8; 32],
+}
+
+impl FederationKey {
+    /// New random key pair
+    pub fn generate() -> McpResult<Self> {
+        let mut secret = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .map_err(|_| McpError::InternalError("Failed to generate key".to_string()))?;
+        Ok(Self::from_secret(secret))
+    }
+
+    /// Key pair from a base64 private key
+    pub fn from_base64(private_key: &str) -> McpResult<Self> {
+        Ok(Self::from_secret(decode_key(private_key)?))
+    }
+
+    fn from_secret(secret: [u8; 32]) -> Self {
+        let public = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+        Self { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public
+    }
+
+    pub fn private_base64(&self) -> String {
+        STANDARD.encode(self.secret)
+    }
+
+    pub fn public_base64(&self) -> String {
+        STANDARD.encode(self.public)
+    }
+
+    /// AEAD key for messages between `client` and `server` in `direction`
+    fn aead_key(
+        &self,
+        peer: &[u8; 32],
+        client: &[u8; 32],
+        server: &[u8; 32],
+        direction: Direction,
+    ) -> McpResult<LessSafeKey> {
+        let shared = MontgomeryPoint(*peer).mul_clamped(self.secret).to_bytes();
+        // Low-order peer points give an all-zero secret
+        if shared == [0u8; 32] {
+            return Err(McpError::AuthError("Invalid federation public key".to_string()));
+        }
+
+        let salt = [client.as_slice(), server.as_slice()].concat();
+        let info = [direction.info()];
+        let prk = Salt::new(HKDF_SHA256, &salt).extract(&shared);
+        let okm = prk
+            .expand(&info, &CHACHA20_POLY1305)
+            .map_err(|_| McpError::InternalError("Key derivation failed".to_string()))?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+}
+
+/// What one sealed exchange is bound to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    /// Unix time the request was sealed
+    pub timestamp: i64,
+    /// Random ID, never reused by the sender
+    pub id: String,
+    method: String,
+    path: String,
+}
+
+impl Binding {
+    /// Fresh binding for a request to `path` (with any query)
+    pub fn new(method: &str, path: &str) -> Self {
+        Self {
+            timestamp: Utc::now().timestamp(),
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Binding received in [`BINDING_HEADER`] on a request to `method` `path`
+    pub fn parse(header: &str, method: &str, path: &str) -> McpResult<Self> {
+        let invalid = || McpError::AuthError("Invalid envelope binding".to_string());
+        let (timestamp, id) = header.trim().split_once('.').ok_or_else(invalid)?;
+        if id.is_empty() || id.len() > 64 || !id.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            timestamp: timestamp.parse().map_err(|_| invalid())?,
+            id: id.to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    /// Value of [`BINDING_HEADER`]
+    pub fn header_value(&self) -> String {
+        format!("{}.{}", self.timestamp, self.id)
+    }
+
+    fn request_aad(&self) -> Vec<u8> {
+        format!("request\n{}\n{}\n{}\n{}", self.method, self.path, self.timestamp, self.id).into_bytes()
+    }
+
+    fn frame_aad(&self, status: u16, seq: u64, last: bool) -> Vec<u8> {
+        format!("response\n{}\n{}\n{}\n{}", self.id, status, seq, last).into_bytes()
+    }
+}
+
+/// IDs of recently accepted sealed requests
+///
+/// Entries are kept for the skew window; older requests are refused by
+/// their timestamp instead.
+#[derive(Default)]
+pub struct ReplayCache {
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl ReplayCache {
+    /// Accept `binding` once, if its timestamp is within [`MAX_SKEW_SECS`]
+    pub fn check(&self, binding: &Binding) -> McpResult<()> {
+        let now = Utc::now().timestamp();
+        if (now - binding.timestamp).abs() > MAX_SKEW_SECS {
+            return Err(McpError::AuthError(
+                "Sealed request is outside the allowed clock skew".to_string(),
+            ));
+        }
+        let mut seen = self.seen.lock();
+        seen.retain(|_, timestamp| now - *timestamp <= MAX_SKEW_SECS);
+        if seen.insert(binding.id.clone(), binding.timestamp).is_some() {
+            return Err(McpError::AuthError("Sealed request was replayed".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Seals a response body frame by frame
+pub struct FrameSealer {
+    envelope: Arc<Envelope>,
+    binding: Binding,
+    status: u16,
+    seq: u64,
+}
+
+impl FrameSealer {
+    pub fn new(envelope: Arc<Envelope>, binding: Binding, status: u16) -> Self {
+        Self { envelope, binding, status, seq: 0 }
+    }
+
+    /// Next frame, `len || nonce || ciphertext || tag`
+    pub fn seal(&mut self, chunk: &[u8], last: bool) -> McpResult<Vec<u8>> {
+        let aad = self.binding.frame_aad(self.status, self.seq, last);
+        let sealed = self.envelope.seal(Direction::Response, &aad, chunk)?;
+        self.seq += 1;
+        let mut frame = (sealed.len() as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(&sealed);
+        Ok(frame)
+    }
+}
+
+/// Sealing and opening on one client/server link, from one side
+pub struct Envelope {
+    key: Arc<FederationKey>,
+    peer: [u8; 32],
+    /// This side opened the link (sends requests)
+    client: bool,
+}
+
+impl Envelope {
+    /// Link from a client instance to a server whose key is pinned
+    pub fn client(key: Arc<FederationKey>, server: [u8; 32]) -> Self {
+        Self { key, peer: server, client: true }
+    }
+
+    /// Link from a server instance to a client that sent `client`
+    pub fn server(key: Arc<FederationKey>, client: [u8; 32]) -> Self {
+        Self { key, peer: client, client: false }
+    }
+
+    /// This side's public key, sent in [`KEY_HEADER`]
+    pub fn public_key_base64(&self) -> String {
+        self.key.public_base64()
+    }
+
+    fn aead_key(&self, direction: Direction) -> McpResult<LessSafeKey> {
+        let own = self.key.public_key();
+        let (client, server) = if self.client {
+            (own, self.peer)
+        } else {
+            (self.peer, own)
+        };
+        self.key.aead_key(&self.peer, &client, &server, direction)
+    }
+
+    fn seal(&self, direction: Direction, aad: &[u8], plaintext: &[u8]) -> McpResult<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| McpError::InternalError("Failed to generate nonce".to_string()))?;
+
+        let mut sealed = plaintext.to_vec();
+        self.aead_key(direction)?
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut sealed)
+            .map_err(|_| McpError::InternalError("Encryption failed".to_string()))?;
+
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    fn open(&self, direction: Direction, aad: &[u8], sealed: &[u8]) -> McpResult<Vec<u8>> {
+        if sealed.len() < NONCE_LEN + CHACHA20_POLY1305.tag_len() {
+            return Err(McpError::AuthError("Sealed payload is truncated".to_string()));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| McpError::AuthError("Invalid nonce".to_string()))?;
+
+        let mut buffer = ciphertext.to_vec();
+        let plaintext = self
+            .aead_key(direction)?
+            .open_in_place(nonce, Aad::from(aad), &mut buffer)
+            .map_err(|_| McpError::AuthError("Sealed payload failed authentication".to_string()))?;
+        Ok(plaintext.to_vec())
+    }
+
+    /// Seal a request body under `binding`
+    pub fn seal_request(&self, binding: &Binding, plaintext: &[u8]) -> McpResult<Vec<u8>> {
+        self.seal(Direction::Request, &binding.request_aad(), plaintext)
+    }
+
+    /// Open a request body sealed under `binding`
+    pub fn open_request(&self, binding: &Binding, sealed: &[u8]) -> McpResult<Vec<u8>> {
+        self.open(Direction::Request, &binding.request_aad(), sealed)
+    }
+
+    /// Open a framed response to the request sealed under `binding`
+    pub fn open_response(&self, binding: &Binding, status: u16, mut frames: &[u8]) -> McpResult<Vec<u8>> {
+        let truncated = || McpError::AuthError("Sealed response is truncated".to_string());
+        let mut plaintext = Vec::new();
+        for seq in 0.. {
+            if frames.len() < FRAME_LEN_BYTES {
+                return Err(truncated());
+            }
+            let (len, rest) = frames.split_at(FRAME_LEN_BYTES);
+            let len = u32::from_be_bytes(len.try_into().expect("4-byte prefix")) as usize;
+            if rest.len() < len {
+                return Err(truncated());
+            }
+            let (frame, rest) = rest.split_at(len);
+            frames = rest;
+
+            let last = frames.is_empty();
+            let aad = binding.frame_aad(status, seq, last);
+            plaintext.extend(self.open(Direction::Response, &aad, frame)?);
+            if last {
+                break;
+            }
+        }
+        Ok(plaintext)
+    }
+}
+
+/// Decode a base64 X25519 key
+pub fn decode_key(key: &str) -> McpResult<[u8; 32]> {
+    STANDARD
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| McpError::ConfigError("X25519 keys are 32 bytes in base64".to_string()))
+}
+
+static IDENTITY: OnceCell<Arc<FederationKey>> = OnceCell::new();
+
+/// Install this process's key pair, used by federated transports
+pub fn install_identity(key: Arc<FederationKey>) -> bool {
+    IDENTITY.set(key).is_ok()
+}
+
+/// This process's key pair, if `federation.private_key` is configured
+pub fn identity() -> Option<Arc<FederationKey>> {
+    IDENTITY.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link() -> (Envelope, Envelope) {
+        let client_key = Arc::new(FederationKey::generate().unwrap());
+        let server_key = Arc::new(FederationKey::generate().unwrap());
+        let client = Envelope::client(client_key.clone(), server_key.public_key());
+        let server = Envelope::server(server_key, client_key.public_key());
+        (client, server)
+    }
+
+    #[test]
+    fn test_round_trip_and_tamper() {
+        let (client, server) = link();
+        let binding = Binding::new("POST", "/tools/invoke");
+        let sealed = client.seal_request(&binding, b"{\"tool\":\"echo\"}").unwrap();
+        let received = Binding::parse(&binding.header_value(), "POST", "/tools/invoke").unwrap();
+        assert_eq!(server.open_request(&received, &sealed).unwrap(), b"{\"tool\":\"echo\"}");
+        // Keys are per direction
+        assert!(server.open(Direction::Response, &received.request_aad(), &sealed).is_err());
+
+        // The request is bound to its method and path
+        let moved = Binding::parse(&binding.header_value(), "POST", "/admin/v1/config").unwrap();
+        assert!(server.open_request(&moved, &sealed).is_err());
+
+        let server = Arc::new(server);
+        let mut sealer = FrameSealer::new(server.clone(), received.clone(), 200);
+        let mut reply = sealer.seal(b"o", false).unwrap();
+        reply.extend(sealer.seal(b"k", false).unwrap());
+        reply.extend(sealer.seal(b"", true).unwrap());
+        assert_eq!(client.open_response(&binding, 200, &reply).unwrap(), b"ok");
+
+        // Bound to the status, the request and complete
+        assert!(client.open_response(&binding, 403, &reply).is_err());
+        assert!(client.open_response(&Binding::new("POST", "/tools/invoke"), 200, &reply).is_err());
+        let cut = reply.len() - 4 - NONCE_LEN - CHACHA20_POLY1305.tag_len();
+        assert!(client.open_response(&binding, 200, &reply[..cut]).is_err());
+
+        let mut tampered = reply.clone();
+        tampered[10] ^= 1;
+        assert!(client.open_response(&binding, 200, &tampered).is_err());
+
+        // A third instance pretending to be the client can't read replies
+        let other = Envelope::client(Arc::new(FederationKey::generate().unwrap()), client.peer);
+        assert!(other.open_response(&binding, 200, &reply).is_err());
+    }
+
+    #[test]
+    fn test_replays_are_refused() {
+        let cache = ReplayCache::default();
+        let binding = Binding::new("GET", "/tools");
+        cache.check(&binding).unwrap();
+        assert!(cache.check(&binding).is_err());
+
+        let stale = Binding {
+            timestamp: binding.timestamp - MAX_SKEW_SECS - 1,
+            ..Binding::new("GET", "/tools")
+        };
+        assert!(cache.check(&stale).is_err());
+        assert!(Binding::parse("12.not-an-id!", "GET", "/tools").is_err());
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let key = FederationKey::generate().unwrap();
+        let restored = FederationKey::from_base64(&key.private_base64()).unwrap();
+        assert_eq!(restored.public_base64(), key.public_base64());
+        assert_eq!(decode_key(&key.public_base64()).unwrap(), key.public_key());
+        assert!(decode_key("c2hvcnQ=").is_err());
+    }
+}
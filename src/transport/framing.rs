@@ -0,0 +1,267 @@
+//! Message framing for byte-stream transports
+//!
+//! Reads JSON-RPC messages delimited either by newlines or by LSP-style
+//! `Content-Length` headers, buffering partial reads and discarding
+//! messages above a size limit instead of growing without bound.
+
+use crate::config::StdioFraming;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tracing::warn;
+
+/// Maximum length of a single header line
+const MAX_HEADER_LINE: usize = 8 * 1024;
+
+enum Line {
+    Complete(Vec<u8>),
+    Oversized(usize),
+}
+
+/// Reads framed messages from an async byte stream
+pub struct MessageReader<R> {
+    reader: BufReader<R>,
+    framing: StdioFraming,
+    max_message_bytes: usize,
+    detected: Option<StdioFraming>,
+}
+
+impl<R: AsyncRead + Unpin> MessageReader<R> {
+    pub fn new(reader: R, framing: StdioFraming, max_message_bytes: usize) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            framing,
+            max_message_bytes,
+            detected: None,
+        }
+    }
+
+    /// Framing observed on the most recent message
+    pub fn detected(&self) -> Option<StdioFraming> {
+        self.detected
+    }
+
+    /// Read the next message body, or `None` at end of stream
+    pub async fn next_message(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        loop {
+            let line = match self.read_line(self.max_message_bytes).await? {
+                None => return Ok(None),
+                Some(Line::Oversized(len)) => {
+                    warn!(
+                        "Discarded {} byte message exceeding limit of {} bytes",
+                        len, self.max_message_bytes
+                    );
+                    continue;
+                }
+                Some(Line::Complete(line)) => line,
+            };
+
+            let line = line.trim_ascii();
+            if line.is_empty() {
+                continue;
+            }
+
+            if self.framing != StdioFraming::Newline && header_name(line).is_some() {
+                self.detected = Some(StdioFraming::ContentLength);
+                match self.read_framed_body(line.to_vec()).await? {
+                    Some(body) => return Ok(Some(body)),
+                    None => continue,
+                }
+            }
+
+            if self.framing == StdioFraming::ContentLength {
+                warn!("Ignoring unframed output: {}", String::from_utf8_lossy(line));
+                continue;
+            }
+
+            self.detected = Some(StdioFraming::Newline);
+            return Ok(Some(line.to_vec()));
+        }
+    }
+
+    /// Read the remaining headers and the body they describe
+    async fn read_framed_body(&mut self, first_header: Vec<u8>) -> std::io::Result<Option<Vec<u8>>> {
+        let mut content_length = content_length(&first_header);
+
+        loop {
+            match self.read_line(MAX_HEADER_LINE).await? {
+                None => return Ok(None),
+                Some(Line::Oversized(_)) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Oversized message header",
+                    ))
+                }
+                Some(Line::Complete(line)) => {
+                    let line = line.trim_ascii();
+                    if line.is_empty() {
+                        break;
+                    }
+                    content_length = content_length.or_else(|| self::content_length(line));
+                }
+            }
+        }
+
+        let Some(len) = content_length else {
+            warn!("Framed message without Content-Length header");
+            return Ok(None);
+        };
+
+        if len > self.max_message_bytes {
+            warn!(
+                "Discarded {} byte message exceeding limit of {} bytes",
+                len, self.max_message_bytes
+            );
+            let mut discard = (&mut self.reader).take(len as u64);
+            tokio::io::copy(&mut discard, &mut tokio::io::sink()).await?;
+            return Ok(None);
+        }
+
+        let mut body = vec![0u8; len];
+        self.reader.read_exact(&mut body).await?;
+        Ok(Some(body))
+    }
+
+    /// Read up to and including the next newline, without buffering more
+    /// than `limit` bytes of it
+    async fn read_line(&mut self, limit: usize) -> std::io::Result<Option<Line>> {
+        let mut line = Vec::new();
+        let mut total = 0usize;
+
+        loop {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                return Ok(match total {
+                    0 => None,
+                    _ if total > limit => Some(Line::Oversized(total)),
+                    _ => Some(Line::Complete(line)),
+                });
+            }
+
+            let (consumed, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+
+            total += consumed;
+            if total <= limit + 1 {
+                line.extend_from_slice(&available[..consumed]);
+            } else {
+                line = Vec::new();
+            }
+            self.reader.consume(consumed);
+
+            if done {
+                return Ok(Some(if total > limit + 1 {
+                    Line::Oversized(total)
+                } else {
+                    Line::Complete(line)
+                }));
+            }
+        }
+    }
+}
+
+/// Header name if the line looks like `Name: value`
+fn header_name(line: &[u8]) -> Option<&[u8]> {
+    let colon = line.iter().position(|&b| b == b':')?;
+    let name = &line[..colon];
+    let is_header = name.eq_ignore_ascii_case(b"content-length")
+        || name.eq_ignore_ascii_case(b"content-type");
+    is_header.then_some(name)
+}
+
+fn content_length(line: &[u8]) -> Option<usize> {
+    let name = header_name(line)?;
+    if !name.eq_ignore_ascii_case(b"content-length") {
+        return None;
+    }
+    std::str::from_utf8(&line[name.len() + 1..])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Encode a message for the wire
+pub fn encode_message(json: &str, framing: StdioFraming) -> Vec<u8> {
+    match framing {
+        StdioFraming::ContentLength => {
+            let mut out = format!("Content-Length: {}\r\n\r\n", json.len()).into_bytes();
+            out.extend_from_slice(json.as_bytes());
+            out
+        }
+        StdioFraming::Auto | StdioFraming::Newline => {
+            let mut out = json.as_bytes().to_vec();
+            out.push(b'\n');
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read_all(input: &[u8], framing: StdioFraming, max: usize) -> Vec<String> {
+        let mut reader = MessageReader::new(input, framing, max);
+        let mut messages = Vec::new();
+        while let Some(message) = reader.next_message().await.unwrap() {
+            messages.push(String::from_utf8(message).unwrap());
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_newline_framing() {
+        let messages = read_all(b"{\"a\":1}\n\n{\"b\":2}\r\n", StdioFraming::Auto, 1024).await;
+        assert_eq!(messages, vec!["{\"a\":1}", "{\"b\":2}"]);
+    }
+
+    #[tokio::test]
+    async fn test_content_length_framing() {
+        let input = b"Content-Length: 7\r\nContent-Type: application/json\r\n\r\n{\"a\":1}Content-Length: 7\r\n\r\n{\"b\":2}";
+        let messages = read_all(input, StdioFraming::Auto, 1024).await;
+        assert_eq!(messages, vec!["{\"a\":1}", "{\"b\":2}"]);
+    }
+
+    #[tokio::test]
+    async fn test_auto_detection_records_framing() {
+        let input: &[u8] = b"Content-Length: 2\r\n\r\n{}";
+        let mut reader = MessageReader::new(input, StdioFraming::Auto, 1024);
+        reader.next_message().await.unwrap();
+        assert_eq!(reader.detected(), Some(StdioFraming::ContentLength));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_messages_skipped() {
+        let long = format!("{{\"x\":\"{}\"}}\n{{\"ok\":true}}\n", "a".repeat(100));
+        let messages = read_all(long.as_bytes(), StdioFraming::Newline, 32).await;
+        assert_eq!(messages, vec!["{\"ok\":true}"]);
+
+        let framed = format!("Content-Length: 100\r\n\r\n{}Content-Length: 2\r\n\r\n{{}}", "a".repeat(100));
+        let messages = read_all(framed.as_bytes(), StdioFraming::ContentLength, 32).await;
+        assert_eq!(messages, vec!["{}"]);
+    }
+
+    #[tokio::test]
+    async fn test_partial_reads() {
+        let (mut tx, rx) = tokio::io::duplex(4);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            tx.write_all(b"Content-Length: 11\r\n\r\n{\"id\":123}\n").await.unwrap();
+        });
+
+        let mut reader = MessageReader::new(rx, StdioFraming::Auto, 1024);
+        let message = reader.next_message().await.unwrap().unwrap();
+        assert_eq!(message, b"{\"id\":123}\n");
+        writer.await.unwrap();
+    }
+
+    #[test]
+    fn test_encode_message() {
+        assert_eq!(encode_message("{}", StdioFraming::Newline), b"{}\n");
+        assert_eq!(
+            encode_message("{}", StdioFraming::ContentLength),
+            b"Content-Length: 2\r\n\r\n{}"
+        );
+    }
+}
@@ -0,0 +1,441 @@
+//! Shared HTTP clients for upstream MCP servers
+//!
+//! Remote transports draw their `reqwest::Client` from a process-wide pool
+//! keyed by the tuning settings, so servers with identical settings share
+//! connection pools (including multiplexed HTTP/2 connections) instead of
+//! each building an ad-hoc client. Per-upstream connection metrics are
+//! recorded here as well.
+//!
+//! Servers with `[servers.http.dns]` settings resolve through
+//! [`crate::transport::dns`].
+//!
+//! TLS and proxy settings are part of the key, except that a server
+//! overriding the TLS server name gets a client of its own, pinned to its
+//! address. Other outbound clients (registry, OAuth) use the global proxy
+//! via [`apply_proxy`].
+
+use crate::config::{
+    ProxyConfig, UpstreamDnsConfig, UpstreamHttpConfig, UpstreamHttpVersion, UpstreamTlsConfig,
+};
+use crate::transport::dns::UpstreamResolver;
+use crate::utils::errors::{McpError, McpResult};
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+use url::{Host, Url};
+
+type ClientKey = (UpstreamHttpConfig, UpstreamTlsConfig, ProxyConfig);
+
+static CLIENTS: Lazy<DashMap<ClientKey, reqwest::Client>> = Lazy::new(DashMap::new);
+
+static PROXY: OnceCell<ProxyConfig> = OnceCell::new();
+
+static METRICS: Lazy<DashMap<String, Arc<UpstreamMetrics>>> = Lazy::new(DashMap::new);
+
+/// Convert configured upstream headers, rejecting invalid names or values
+pub fn header_map(headers: &HashMap<String, String>) -> McpResult<HeaderMap> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| McpError::ConfigError(format!("Invalid header name {:?}: {}", name, e)))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|e| McpError::ConfigError(format!("Invalid value for header {}: {}", name, e)))?;
+        value.set_sensitive(true);
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Install the global `[proxy]` settings
+pub fn install_proxy(proxy: ProxyConfig) -> bool {
+    PROXY.set(proxy).is_ok()
+}
+
+/// The global `[proxy]` settings, if installed
+pub fn global_proxy() -> ProxyConfig {
+    PROXY.get().cloned().unwrap_or_default()
+}
+
+/// Route a client through a proxy, bypassing it for `no_proxy` hosts
+///
+/// Without a proxy URL the builder keeps reqwest's default of honouring
+/// the proxy environment variables.
+pub fn apply_proxy(
+    builder: reqwest::ClientBuilder,
+    proxy: &ProxyConfig,
+) -> McpResult<reqwest::ClientBuilder> {
+    let Some(url) = &proxy.url else {
+        return Ok(builder);
+    };
+    let target = reqwest::Proxy::all(url)
+        .map_err(|e| McpError::ConfigError(format!("Invalid proxy URL: {}", e)))?
+        .no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(",")));
+    Ok(builder.proxy(target))
+}
+
+/// Get (or build) the shared client for the given settings
+pub fn shared_client(
+    config: &UpstreamHttpConfig,
+    tls: &UpstreamTlsConfig,
+    proxy: &ProxyConfig,
+) -> McpResult<reqwest::Client> {
+    let key = (config.clone(), tls.clone(), proxy.clone());
+    if let Some(client) = CLIENTS.get(&key) {
+        return Ok(client.clone());
+    }
+
+    let builder = apply_proxy(apply_tls(tuned_builder(config)?, tls)?, proxy)?;
+    Ok(CLIENTS.entry(key).or_insert(build(builder)?).clone())
+}
+
+/// Client for an upstream endpoint
+///
+/// This is the shared client unless `tls.server_name` is set, in which case
+/// `endpoint` is rewritten to use that name (see [`pin_server_name`]).
+pub async fn upstream_client(
+    config: &UpstreamHttpConfig,
+    tls: &UpstreamTlsConfig,
+    proxy: &ProxyConfig,
+    endpoint: &mut Url,
+) -> McpResult<reqwest::Client> {
+    if tls.server_name.is_none() {
+        return shared_client(config, tls, proxy);
+    }
+    let builder = pin_server_name(tuned_builder(config)?, tls, endpoint).await?;
+    build(apply_proxy(apply_tls(builder, tls)?, proxy)?)
+}
+
+fn read_pem(path: &str) -> McpResult<Vec<u8>> {
+    std::fs::read(shellexpand::tilde(path).as_ref())
+        .map_err(|e| McpError::ConfigError(format!("Failed to read {}: {}", path, e)))
+}
+
+/// Add the CA bundle, client certificate and verification settings
+pub fn apply_tls(
+    mut builder: reqwest::ClientBuilder,
+    tls: &UpstreamTlsConfig,
+) -> McpResult<reqwest::ClientBuilder> {
+    if let Some(path) = &tls.ca_bundle {
+        let certs = reqwest::Certificate::from_pem_bundle(&read_pem(path)?)
+            .map_err(|e| McpError::ConfigError(format!("Invalid CA bundle {}: {}", path, e)))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    match (&tls.client_cert, &tls.client_key) {
+        (Some(cert), Some(key)) => {
+            let mut pem = read_pem(cert)?;
+            pem.push(b'\n');
+            pem.extend(read_pem(key)?);
+            let identity = reqwest::Identity::from_pem(&pem)
+                .map_err(|e| McpError::ConfigError(format!("Invalid client certificate {}: {}", cert, e)))?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(McpError::ConfigError(
+                "tls.client_cert and tls.client_key must be set together".to_string(),
+            ))
+        }
+    }
+
+    if tls.insecure_skip_verify {
+        warn!("TLS certificate verification is disabled for an upstream server; never use insecure_skip_verify in production");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder)
+}
+
+/// Send `tls.server_name` as SNI while connecting to `endpoint`'s address
+///
+/// reqwest takes the TLS server name from the URL, so `endpoint`'s host is
+/// replaced with the name and the name is resolved to where the original
+/// host points. The Host header carries the name too.
+pub async fn pin_server_name(
+    builder: reqwest::ClientBuilder,
+    tls: &UpstreamTlsConfig,
+    endpoint: &mut Url,
+) -> McpResult<reqwest::ClientBuilder> {
+    let Some(name) = &tls.server_name else {
+        return Ok(builder);
+    };
+    let host = match endpoint.host() {
+        Some(Host::Domain(domain)) => domain.to_string(),
+        Some(Host::Ipv4(ip)) => ip.to_string(),
+        Some(Host::Ipv6(ip)) => ip.to_string(),
+        None => return Err(McpError::TransportError(format!("URL has no host: {}", endpoint))),
+    };
+    let port = endpoint.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| McpError::TransportError(format!("Failed to resolve {}: {}", host, e)))?
+        .collect();
+    endpoint
+        .set_host(Some(name))
+        .map_err(|e| McpError::ConfigError(format!("Invalid tls.server_name {:?}: {}", name, e)))?;
+    Ok(builder.resolve_to_addrs(name, &addrs))
+}
+
+fn build(builder: reqwest::ClientBuilder) -> McpResult<reqwest::Client> {
+    builder
+        .build()
+        .map_err(|e| McpError::TransportError(e.to_string()))
+}
+
+fn tuned_builder(config: &UpstreamHttpConfig) -> McpResult<reqwest::ClientBuilder> {
+    let mut builder = reqwest::Client::builder()
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .read_timeout(Duration::from_secs(config.request_timeout_secs));
+
+    if config.tcp_keepalive_secs > 0 {
+        builder = builder.tcp_keepalive(Duration::from_secs(config.tcp_keepalive_secs));
+    }
+
+    builder = match config.http_version {
+        UpstreamHttpVersion::Auto => builder,
+        UpstreamHttpVersion::Http1 => builder.http1_only(),
+        UpstreamHttpVersion::Http2 => builder.http2_prior_knowledge(),
+    };
+
+    if config.http_version != UpstreamHttpVersion::Http1 {
+        builder = builder.http2_adaptive_window(true);
+        if config.http2_keep_alive_interval_secs > 0 {
+            builder = builder
+                .http2_keep_alive_interval(Duration::from_secs(
+                    config.http2_keep_alive_interval_secs,
+                ))
+                .http2_keep_alive_timeout(Duration::from_secs(
+                    config.http2_keep_alive_timeout_secs,
+                ))
+                .http2_keep_alive_while_idle(true);
+        }
+    }
+
+    if config.dns != UpstreamDnsConfig::default() {
+        builder = builder.dns_resolver(Arc::new(UpstreamResolver::new(&config.dns)?));
+    }
+
+    Ok(builder)
+}
+
+/// Connection metrics for a single upstream endpoint
+#[derive(Debug, Default)]
+pub struct UpstreamMetrics {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    http1_responses: AtomicU64,
+    http2_responses: AtomicU64,
+    total_latency_ms: AtomicU64,
+}
+
+impl UpstreamMetrics {
+    /// Record a completed HTTP exchange
+    pub fn record_response(&self, version: reqwest::Version, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.total_latency_ms
+            .fetch_add(latency.as_millis() as u64, Ordering::Relaxed);
+        if version == reqwest::Version::HTTP_2 {
+            self.http2_responses.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.http1_responses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a request that failed before a response arrived
+    pub fn record_failure(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self, upstream: &str) -> UpstreamMetricsSnapshot {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let failures = self.failures.load(Ordering::Relaxed);
+        let completed = requests.saturating_sub(failures);
+        let total_latency_ms = self.total_latency_ms.load(Ordering::Relaxed);
+
+        UpstreamMetricsSnapshot {
+            upstream: upstream.to_string(),
+            requests,
+            failures,
+            http1_responses: self.http1_responses.load(Ordering::Relaxed),
+            http2_responses: self.http2_responses.load(Ordering::Relaxed),
+            average_latency_ms: if completed == 0 {
+                0.0
+            } else {
+                total_latency_ms as f64 / completed as f64
+            },
+        }
+    }
+}
+
+/// Point-in-time view of an upstream's connection metrics
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamMetricsSnapshot {
+    pub upstream: String,
+    pub requests: u64,
+    pub failures: u64,
+    pub http1_responses: u64,
+    pub http2_responses: u64,
+    pub average_latency_ms: f64,
+}
+
+/// Get the metrics handle for an endpoint, keyed by scheme, host and port
+pub fn metrics_for(endpoint: &Url) -> Arc<UpstreamMetrics> {
+    let key = format!(
+        "{}://{}:{}",
+        endpoint.scheme(),
+        endpoint.host_str().unwrap_or(""),
+        endpoint.port_or_known_default().unwrap_or(0)
+    );
+    METRICS.entry(key).or_default().clone()
+}
+
+/// Snapshot metrics for every upstream contacted so far
+pub fn upstream_metrics_snapshot() -> Vec<UpstreamMetricsSnapshot> {
+    let mut snapshots: Vec<_> = METRICS
+        .iter()
+        .map(|entry| entry.value().snapshot(entry.key()))
+        .collect();
+    snapshots.sort_by(|a, b| a.upstream.cmp(&b.upstream));
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_client_reused_for_same_settings() {
+        let config = UpstreamHttpConfig {
+            pool_max_idle_per_host: 3,
+            ..Default::default()
+        };
+        let tls = UpstreamTlsConfig::default();
+        let proxy = ProxyConfig::default();
+        shared_client(&config, &tls, &proxy).unwrap();
+        shared_client(&config, &tls, &proxy).unwrap();
+        assert!(CLIENTS.contains_key(&(config, tls, proxy)));
+    }
+
+    #[test]
+    fn test_apply_proxy() {
+        let proxy = ProxyConfig {
+            url: Some("socks5://127.0.0.1:1080".to_string()),
+            no_proxy: vec!["localhost".to_string(), "10.0.0.0/8".to_string()],
+        };
+        assert!(apply_proxy(reqwest::Client::builder(), &proxy).unwrap().build().is_ok());
+
+        let proxy = ProxyConfig {
+            url: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            apply_proxy(reqwest::Client::builder(), &proxy),
+            Err(McpError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_client_cert_needs_key() {
+        let tls = UpstreamTlsConfig {
+            client_cert: Some("client.pem".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            apply_tls(reqwest::Client::builder(), &tls),
+            Err(McpError::ConfigError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pin_server_name() {
+        let tls = UpstreamTlsConfig {
+            server_name: Some("mcp.internal".to_string()),
+            ..Default::default()
+        };
+        let mut endpoint: Url = "https://127.0.0.1:8443/mcp".parse().unwrap();
+        let builder = pin_server_name(reqwest::Client::builder(), &tls, &mut endpoint)
+            .await
+            .unwrap();
+        assert!(builder.build().is_ok());
+        assert_eq!(endpoint.as_str(), "https://mcp.internal:8443/mcp");
+    }
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let url: Url = "https://metrics-test.example.com/mcp".parse().unwrap();
+        let metrics = metrics_for(&url);
+        metrics.record_response(reqwest::Version::HTTP_2, Duration::from_millis(40));
+        metrics.record_response(reqwest::Version::HTTP_11, Duration::from_millis(20));
+        metrics.record_failure();
+
+        let snapshot = upstream_metrics_snapshot()
+            .into_iter()
+            .find(|s| s.upstream == "https://metrics-test.example.com:443")
+            .unwrap();
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.failures, 1);
+        assert_eq!(snapshot.http2_responses, 1);
+        assert_eq!(snapshot.http1_responses, 1);
+        assert_eq!(snapshot.average_latency_ms, 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_spares_streamed_bodies() {
+        use axum::{body::Body, routing::get, Router};
+        use futures::stream;
+
+        // One chunk every 400ms, so the whole body takes longer than the
+        // timeout but no single read does
+        let trickle = |stall: Duration| {
+            Body::from_stream(stream::unfold(0, move |n| async move {
+                if n == 5 {
+                    return None;
+                }
+                tokio::time::sleep(if n == 2 { stall } else { Duration::from_millis(400) }).await;
+                Some((Ok::<_, std::io::Error>(bytes::Bytes::from("chunk\n")), n + 1))
+            }))
+        };
+        let app = Router::new()
+            .route("/slow", get(move || async move { trickle(Duration::from_millis(400)) }))
+            .route("/stalled", get(move || async move { trickle(Duration::from_secs(3)) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = UpstreamHttpConfig {
+            request_timeout_secs: 1,
+            ..Default::default()
+        };
+        let client = build(tuned_builder(&config).unwrap()).unwrap();
+
+        let body = client
+            .get(format!("http://{}/slow", addr))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body.lines().count(), 5);
+
+        let stalled = client
+            .get(format!("http://{}/stalled", addr))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await;
+        assert!(stalled.unwrap_err().is_timeout());
+    }
+}
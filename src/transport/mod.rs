@@ -1,11 +1,22 @@
+pub mod dns;
+pub mod envelope;
+pub mod framing;
+pub mod http_client;
+pub mod named_pipe;
+pub mod nats;
 pub mod sse;
+pub mod ssh;
 pub mod stdio;
 pub mod streamable;
+pub mod supermcp;
 pub mod traits;
 pub mod websocket;
 
+pub use http_client::{upstream_metrics_snapshot, UpstreamMetricsSnapshot};
+pub use named_pipe::NamedPipeTransport;
 pub use sse::SseTransport;
 pub use stdio::StdioTransport;
 pub use streamable::StreamableHttpTransport;
-pub use traits::{Transport, TransportFactory};
+pub use supermcp::SuperMcpTransport;
+pub use traits::{ByteStream, Transport, TransportFactory, TransportResponse};
 pub use websocket::WebSocketTransport;
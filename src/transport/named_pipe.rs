@@ -0,0 +1,240 @@
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
+use crate::core::SharedRequestIdGenerator;
+use crate::transport::traits::Transport;
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{oneshot, Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+type PipeWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Named pipe transport for MCP servers listening on `\\.\pipe\<name>`
+///
+/// Messages are newline-delimited JSON, as with stdio.
+pub struct NamedPipeTransport {
+    writer: Arc<Mutex<PipeWriter>>,
+    pending: Arc<DashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
+    is_connected: Arc<RwLock<bool>>,
+    request_id_gen: SharedRequestIdGenerator,
+}
+
+impl NamedPipeTransport {
+    /// Connect to a named pipe, waiting while all pipe instances are busy
+    #[cfg(windows)]
+    pub async fn connect(path: &str) -> McpResult<Self> {
+        use tokio::net::windows::named_pipe::ClientOptions;
+
+        // All pipe instances are in use; retry until one frees up
+        const ERROR_PIPE_BUSY: i32 = 231;
+
+        let path = normalize_pipe_path(path);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+
+        let client = loop {
+            match ClientOptions::new().open(&path) {
+                Ok(client) => break client,
+                Err(e)
+                    if e.raw_os_error() == Some(ERROR_PIPE_BUSY)
+                        && tokio::time::Instant::now() < deadline =>
+                {
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+                Err(e) => {
+                    return Err(McpError::TransportError(format!(
+                        "Failed to connect to named pipe {}: {}",
+                        path, e
+                    )))
+                }
+            }
+        };
+
+        info!("Connected to named pipe {}", path);
+        Ok(Self::from_stream(client))
+    }
+
+    #[cfg(not(windows))]
+    pub async fn connect(path: &str) -> McpResult<Self> {
+        Err(McpError::ConfigError(format!(
+            "Named pipe transport is only available on Windows: {}",
+            path
+        )))
+    }
+
+    /// Build a transport over an already connected duplex stream
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+
+        let transport = Self {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+            pending: Arc::new(DashMap::new()),
+            is_connected: Arc::new(RwLock::new(true)),
+            request_id_gen: SharedRequestIdGenerator::new(),
+        };
+
+        transport.start_reader(reader);
+        transport
+    }
+
+    fn start_reader<R>(&self, reader: R)
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        let pending = self.pending.clone();
+        let is_connected = self.is_connected.clone();
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!("Received: {}", line);
+
+                match serde_json::from_str::<JsonRpcResponse>(&line) {
+                    Ok(response) => match response.id.clone() {
+                        Some(id) => {
+                            if let Some((_, tx)) = pending.remove(&id) {
+                                let _ = tx.send(response);
+                            } else {
+                                warn!("Received response with unknown id: {:?}", id);
+                            }
+                        }
+                        None => debug!("Received response without id, ignoring"),
+                    },
+                    Err(e) => warn!("Failed to parse response: {}", e),
+                }
+            }
+
+            info!("Named pipe reader task ended");
+            *is_connected.write().await = false;
+            pending.clear();
+        });
+    }
+
+    async fn write_line(&self, json: &str) -> McpResult<()> {
+        let mut writer = self.writer.lock().await;
+        writer.write_all(json.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+/// Accept bare pipe names as well as full `\\.\pipe\` paths
+pub fn normalize_pipe_path(path: &str) -> String {
+    if path.starts_with(r"\\") {
+        path.to_string()
+    } else {
+        format!(r"\\.\pipe\{}", path)
+    }
+}
+
+#[async_trait]
+impl Transport for NamedPipeTransport {
+    async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        if !self.is_connected().await {
+            return Err(McpError::TransportError("Transport not connected".to_string()));
+        }
+
+        let mut request = request;
+        if request.id.is_none() {
+            request.id = Some(self.request_id_gen.next_id());
+        }
+        let request_id = request
+            .id
+            .clone()
+            .ok_or_else(|| McpError::InvalidRequest("Missing request id".to_string()))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(request_id.clone(), tx);
+
+        let json = serde_json::to_string(&request)?;
+        debug!("Sending: {}", json);
+
+        if let Err(e) = self.write_line(&json).await {
+            self.pending.remove(&request_id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(std::time::Duration::from_secs(30), rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(McpError::TransportError("Response channel closed".to_string())),
+            Err(_) => {
+                self.pending.remove(&request_id);
+                Err(McpError::UpstreamTimeout(30000))
+            }
+        }
+    }
+
+    async fn send_notification(&self, request: JsonRpcRequest) -> McpResult<()> {
+        if !self.is_connected().await {
+            return Err(McpError::TransportError("Transport not connected".to_string()));
+        }
+
+        let mut request = request;
+        request.id = None;
+
+        let json = serde_json::to_string(&request)?;
+        debug!("Sending notification: {}", json);
+        self.write_line(&json).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        *self.is_connected.read().await
+    }
+
+    async fn close(&self) -> McpResult<()> {
+        let mut writer = self.writer.lock().await;
+        if let Err(e) = writer.shutdown().await {
+            debug!("Failed to shut down named pipe: {}", e);
+        }
+
+        *self.is_connected.write().await = false;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_pipe_path() {
+        assert_eq!(normalize_pipe_path("supermcp"), r"\\.\pipe\supermcp");
+        assert_eq!(normalize_pipe_path(r"\\.\pipe\supermcp"), r"\\.\pipe\supermcp");
+    }
+
+    #[tokio::test]
+    async fn test_request_response_over_stream() {
+        let (client, server) = tokio::io::duplex(4096);
+        let transport = NamedPipeTransport::from_stream(client);
+
+        tokio::spawn(async move {
+            let (reader, mut writer) = tokio::io::split(server);
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": {} });
+                writer
+                    .write_all(format!("{}\n", response).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let response = transport
+            .send_request(JsonRpcRequest::new("ping", None))
+            .await
+            .unwrap();
+        assert!(response.result.is_some());
+
+        transport.close().await.unwrap();
+        assert!(!transport.is_connected().await);
+    }
+}
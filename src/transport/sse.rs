@@ -1,4 +1,5 @@
 //! SSE (Server-Sent Events) transport for MCP communication
+use crate::config::{ProxyConfig, UpstreamTlsConfig};
 use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
 use crate::core::SharedRequestIdGenerator;
 use crate::transport::traits::Transport;
@@ -6,7 +7,9 @@ use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use futures::stream::StreamExt;
-use reqwest::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use crate::transport::http_client::{apply_proxy, apply_tls, global_proxy, header_map, pin_server_name};
+use reqwest::header::{HeaderMap, ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{oneshot, RwLock};
 use tracing::{debug, error, info};
@@ -16,7 +19,10 @@ use url::Url;
 pub struct SseTransport {
     endpoint: Url,
     client: reqwest::Client,
+    headers: HeaderMap,
     session_id: Arc<RwLock<Option<String>>>,
+    /// Where to POST messages, as announced by an `endpoint` event
+    post_endpoint: Arc<RwLock<Option<Url>>>,
     pending: Arc<DashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
     is_connected: Arc<RwLock<bool>>,
     request_id_gen: SharedRequestIdGenerator,
@@ -24,20 +30,42 @@ pub struct SseTransport {
 
 impl SseTransport {
     pub async fn new(endpoint: impl Into<String>) -> McpResult<Self> {
-        let endpoint = endpoint
+        Self::with_headers(endpoint, &HashMap::new()).await
+    }
+
+    /// Create a transport that sends extra headers with every request
+    pub async fn with_headers(
+        endpoint: impl Into<String>,
+        headers: &HashMap<String, String>,
+    ) -> McpResult<Self> {
+        Self::with_tls(endpoint, headers, &UpstreamTlsConfig::default(), &global_proxy()).await
+    }
+
+    /// Create a transport with custom TLS and proxy settings
+    pub async fn with_tls(
+        endpoint: impl Into<String>,
+        headers: &HashMap<String, String>,
+        tls: &UpstreamTlsConfig,
+        proxy: &ProxyConfig,
+    ) -> McpResult<Self> {
+        let headers = header_map(headers)?;
+        let mut endpoint = endpoint
             .into()
             .parse::<Url>()
             .map_err(|e| McpError::TransportError(format!("Invalid URL: {}", e)))?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+        let builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
+        let builder = pin_server_name(builder, tls, &mut endpoint).await?;
+        let client = apply_proxy(apply_tls(builder, tls)?, proxy)?
             .build()
             .map_err(|e| McpError::TransportError(e.to_string()))?;
 
         let transport = Self {
             endpoint,
             client,
+            headers,
             session_id: Arc::new(RwLock::new(None)),
+            post_endpoint: Arc::new(RwLock::new(None)),
             pending: Arc::new(DashMap::new()),
             is_connected: Arc::new(RwLock::new(false)),
             request_id_gen: SharedRequestIdGenerator::new(),
@@ -56,6 +84,7 @@ impl SseTransport {
         let response = self
             .client
             .get(self.endpoint.clone())
+            .headers(self.headers.clone())
             .header(ACCEPT, "text/event-stream")
             .header(CACHE_CONTROL, "no-cache")
             .send()
@@ -88,12 +117,39 @@ impl SseTransport {
     async fn start_reader(&self, response: reqwest::Response) {
         let pending = self.pending.clone();
         let is_connected = self.is_connected.clone();
+        let post_endpoint = self.post_endpoint.clone();
+        let base = self.endpoint.clone();
 
         tokio::spawn(async move {
             let mut stream = response.bytes_stream();
             let mut buffer = String::new();
+            let mut event_name = String::new();
             let mut event_data = String::new();
 
+            let dispatch = |event_name: &str, event_data: &str| {
+                let payload = event_data.trim_end_matches('\n');
+                if event_name == "endpoint" {
+                    return base.join(payload).ok();
+                }
+                match serde_json::from_str::<JsonRpcResponse>(payload) {
+                    Ok(response) => {
+                        if let Some(id) = response.id.clone() {
+                            if let Some((_, tx)) = pending.remove(&id) {
+                                let _ = tx.send(response);
+                            } else {
+                                debug!("Received SSE response with unknown id: {:?}", id);
+                            }
+                        } else {
+                            debug!("Received SSE response without id, ignoring");
+                        }
+                    }
+                    Err(e) => {
+                        debug!("Failed to parse SSE data: {}", e);
+                    }
+                }
+                None
+            };
+
             while let Some(chunk) = stream.next().await {
                 match chunk {
                     Ok(bytes) => {
@@ -110,29 +166,19 @@ impl SseTransport {
 
                             if line.is_empty() {
                                 if !event_data.is_empty() {
-                                    let payload = event_data.trim_end_matches('\n');
-                                    match serde_json::from_str::<JsonRpcResponse>(payload) {
-                                        Ok(response) => {
-                                            if let Some(id) = response.id.clone() {
-                                                if let Some((_, tx)) = pending.remove(&id) {
-                                                    let _ = tx.send(response);
-                                                } else {
-                                                    debug!("Received SSE response with unknown id: {:?}", id);
-                                                }
-                                            } else {
-                                                debug!("Received SSE response without id, ignoring");
-                                            }
-                                        }
-                                        Err(e) => {
-                                            debug!("Failed to parse SSE data: {}", e);
-                                        }
+                                    if let Some(url) = dispatch(&event_name, &event_data) {
+                                        debug!("SSE server posts messages to {}", url);
+                                        *post_endpoint.write().await = Some(url);
                                     }
                                     event_data.clear();
                                 }
+                                event_name.clear();
                                 continue;
                             }
 
-                            if let Some(data) = line.strip_prefix("data:") {
+                            if let Some(name) = line.strip_prefix("event:") {
+                                event_name = name.trim().to_string();
+                            } else if let Some(data) = line.strip_prefix("data:") {
                                 let data = data.trim_start();
                                 event_data.push_str(data);
                                 event_data.push('\n');
@@ -147,23 +193,7 @@ impl SseTransport {
             }
 
             if !event_data.is_empty() {
-                let payload = event_data.trim_end_matches('\n');
-                match serde_json::from_str::<JsonRpcResponse>(payload) {
-                    Ok(response) => {
-                        if let Some(id) = response.id.clone() {
-                            if let Some((_, tx)) = pending.remove(&id) {
-                                let _ = tx.send(response);
-                            } else {
-                                debug!("Received SSE response with unknown id: {:?}", id);
-                            }
-                        } else {
-                            debug!("Received SSE response without id, ignoring");
-                        }
-                    }
-                    Err(e) => {
-                        debug!("Failed to parse SSE data: {}", e);
-                    }
-                }
+                dispatch(&event_name, &event_data);
             }
 
             info!("SSE reader task ended");
@@ -172,6 +202,19 @@ impl SseTransport {
         });
     }
 
+    /// Where to POST a message, and the headers to send with it
+    async fn message_target(&self) -> (Url, HeaderMap) {
+        let url = match self.post_endpoint.read().await.clone() {
+            Some(url) => url,
+            None => {
+                let session_id = self.session_id.read().await.clone();
+                self.build_request_url(session_id)
+            }
+        };
+        let headers = headers_for(&self.endpoint, &url, &self.headers);
+        (url, headers)
+    }
+
     fn build_request_url(&self, session_id: Option<String>) -> Url {
         let mut url = self.endpoint.clone();
 
@@ -208,12 +251,12 @@ impl Transport for SseTransport {
         let json = serde_json::to_string(&request)?;
         debug!("Sending SSE request: {}", json);
 
-        let session_id = self.session_id.read().await.clone();
-        let url = self.build_request_url(session_id);
+        let (url, headers) = self.message_target().await;
 
         let response = self
             .client
             .post(url)
+            .headers(headers)
             .header(CONTENT_TYPE, "application/json")
             .header(ACCEPT, "application/json")
             .body(json)
@@ -235,7 +278,7 @@ impl Transport for SseTransport {
             Ok(Err(_)) => Err(McpError::TransportError("Response channel closed".to_string())),
             Err(_) => {
                 self.pending.remove(&request_id);
-                Err(McpError::Timeout(30000))
+                Err(McpError::UpstreamTimeout(30000))
             }
         }
     }
@@ -253,12 +296,12 @@ impl Transport for SseTransport {
         let json = serde_json::to_string(&request)?;
         debug!("Sending SSE notification: {}", json);
 
-        let session_id = self.session_id.read().await.clone();
-        let url = self.build_request_url(session_id);
+        let (url, headers) = self.message_target().await;
 
         let response = self
             .client
             .post(url)
+            .headers(headers)
             .header(CONTENT_TYPE, "application/json")
             .body(json)
             .send()
@@ -293,3 +336,42 @@ impl Transport for SseTransport {
         Ok(())
     }
 }
+
+/// The configured headers, if `target` is on the SSE endpoint's origin
+///
+/// They often carry credentials, which a server must not be able to
+/// redirect elsewhere through its `endpoint` event.
+fn headers_for(endpoint: &Url, target: &Url, headers: &HeaderMap) -> HeaderMap {
+    if endpoint.origin() == target.origin() {
+        headers.clone()
+    } else {
+        debug!("Not sending configured headers to cross-origin {}", target);
+        HeaderMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_stay_on_origin() {
+        let endpoint: Url = "https://mcp.example.com/sse".parse().unwrap();
+        let headers = header_map(&HashMap::from([(
+            "Authorization".to_string(),
+            "Bearer secret".to_string(),
+        )]))
+        .unwrap();
+
+        let same = endpoint.join("/messages?session_id=1").unwrap();
+        assert_eq!(headers_for(&endpoint, &same, &headers).len(), 1);
+        for other in [
+            "https://evil.example.net/messages",
+            "http://mcp.example.com/messages",
+            "https://mcp.example.com:8443/messages",
+        ] {
+            let other: Url = other.parse().unwrap();
+            assert!(headers_for(&endpoint, &other, &headers).is_empty());
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Stdio servers on remote hosts over SSH
+//!
+//! An SSH server is a stdio server whose local process is the system `ssh`
+//! client: the configured command and env run on the remote host and
+//! JSON-RPC flows over the session's stdin/stdout. Host keys are always
+//! verified, either against a pinned key or the user's known_hosts.
+//!
+//! The env often carries secrets, so it never appears in an argv, where
+//! `ps` on either host would show it. It is written to the session's stdin
+//! first, and a small remote wrapper exports it before running the server
+//! on the rest of the stream.
+
+use crate::config::{McpServerConfig, ServerType, SshConfig};
+use crate::utils::errors::{McpError, McpResult};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Remote wrapper: exports the `KEY=value` lines sent first on stdin, up
+/// to a blank line, then runs the server on the rest of the session
+const ENV_READER: &str =
+    r#"while IFS= read -r line && [ -n "$line" ]; do export "$line"; done; exec "$@""#;
+
+/// Rewrite an SSH server's config into the local `ssh` invocation
+///
+/// Other server types are left untouched.
+pub fn apply_ssh_command(config: &mut McpServerConfig) -> McpResult<()> {
+    if config.server_type != ServerType::Ssh {
+        return Ok(());
+    }
+    let ssh = config.ssh.clone().ok_or_else(|| {
+        McpError::ConfigError(format!("Server '{}' has type ssh but no [ssh] section", config.name))
+    })?;
+    if ssh.host.is_empty() {
+        return Err(McpError::ConfigError(format!("Server '{}' has no ssh.host", config.name)));
+    }
+    // A leading dash would be read as an ssh option, e.g. -oProxyCommand=...
+    for value in std::iter::once(&ssh.host).chain(ssh.user.as_ref()) {
+        if value.starts_with('-') || value.chars().any(char::is_whitespace) {
+            return Err(McpError::ConfigError(format!(
+                "Server '{}' has an invalid SSH host or user: {}",
+                config.name, value
+            )));
+        }
+    }
+
+    if config.sandbox.enabled && !config.sandbox.network {
+        return Err(McpError::ConfigError(format!(
+            "Server '{}' is sandboxed without network access, which its ssh client needs; \
+             set sandbox.network = true",
+            config.name
+        )));
+    }
+    // Refuse env that can't be sent now rather than on every spawn
+    env_preamble(&config.env)?;
+
+    let known_hosts = match &ssh.host_key {
+        Some(key) => Some(write_known_hosts(&config.name, &ssh, key)?),
+        None => None,
+    };
+    let remote = remote_command(&config.command, &config.args);
+    let args = ssh_args(&ssh, known_hosts.as_ref(), remote);
+    let mut keys: Vec<_> = config.env.keys().map(String::as_str).collect();
+    keys.sort();
+    debug!(
+        "Server {} runs on {} via: ssh {} (env sent on stdin: {})",
+        config.name,
+        ssh.host,
+        args.join(" "),
+        keys.join(", ")
+    );
+
+    // The env goes to the remote side; locally ssh only needs the agent
+    let mut local_env = HashMap::new();
+    if ssh.use_agent {
+        if let Ok(sock) = std::env::var("SSH_AUTH_SOCK") {
+            local_env.insert("SSH_AUTH_SOCK".to_string(), sock);
+        }
+    }
+
+    // The result is a plain local command, so resolving it again (on
+    // restart or update) leaves it as-is
+    config.server_type = ServerType::Local;
+    config.command = "ssh".to_string();
+    config.args = args;
+    config.stdio.local_env = Some(local_env);
+    config.requires.clear();
+    Ok(())
+}
+
+/// Arguments for the local `ssh` client
+fn ssh_args(ssh: &SshConfig, known_hosts: Option<&PathBuf>, remote: String) -> Vec<String> {
+    let mut args = vec![
+        "-T".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        format!("ConnectTimeout={}", ssh.connect_timeout_secs),
+        "-o".to_string(),
+        "StrictHostKeyChecking=yes".to_string(),
+        "-p".to_string(),
+        ssh.port.to_string(),
+    ];
+
+    if let Some(path) = known_hosts {
+        let path = path.display().to_string();
+        args.extend([
+            "-o".to_string(),
+            format!("UserKnownHostsFile={}", path),
+            "-o".to_string(),
+            format!("GlobalKnownHostsFile={}", path),
+        ]);
+    }
+    if let Some(identity) = &ssh.identity_file {
+        args.extend([
+            "-i".to_string(),
+            shellexpand::tilde(identity).to_string(),
+            "-o".to_string(),
+            "IdentitiesOnly=yes".to_string(),
+        ]);
+    }
+    if !ssh.use_agent {
+        args.extend(["-o".to_string(), "IdentityAgent=none".to_string()]);
+    }
+
+    args.push(match &ssh.user {
+        Some(user) => format!("{}@{}", user, ssh.host),
+        None => ssh.host.clone(),
+    });
+    args.push("--".to_string());
+    args.push(remote);
+    args
+}
+
+/// Shell command line run by the remote login shell
+fn remote_command(command: &str, args: &[String]) -> String {
+    let mut words = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        shell_words::quote(ENV_READER).into_owned(),
+        "sh".to_string(),
+    ];
+    words.push(shell_words::quote(command).into_owned());
+    words.extend(args.iter().map(|arg| shell_words::quote(arg).into_owned()));
+    words.join(" ")
+}
+
+/// Env as read by [`ENV_READER`]: sorted `KEY=value` lines and a blank line
+pub fn env_preamble(env: &HashMap<String, String>) -> McpResult<String> {
+    let mut vars: Vec<_> = env.iter().collect();
+    vars.sort();
+    let mut preamble = String::new();
+    for (key, value) in vars {
+        let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(McpError::ConfigError(format!(
+                "Invalid environment variable name for SSH server: {}",
+                key
+            )));
+        }
+        if value.contains(['\n', '\0']) {
+            return Err(McpError::ConfigError(format!(
+                "Environment variable {} of an SSH server can't contain newlines",
+                key
+            )));
+        }
+        preamble.push_str(&format!("{}={}\n", key, value));
+    }
+    preamble.push('\n');
+    Ok(preamble)
+}
+
+/// Directory of pinned known_hosts files, private to this user
+///
+/// It lives under the user's state dir rather than the shared temp dir,
+/// and an existing one is only used if it is a real directory owned by
+/// this user and closed to everyone else.
+fn known_hosts_dir() -> McpResult<PathBuf> {
+    let parent = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .ok_or_else(|| McpError::ConfigError("No state directory for SSH known_hosts".to_string()))?
+        .join("supermcp");
+    std::fs::create_dir_all(&parent)?;
+    let dir = parent.join("ssh");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+        match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        let meta = std::fs::symlink_metadata(&dir)?;
+        // SAFETY: geteuid has no preconditions
+        let uid = unsafe { libc::geteuid() };
+        if !meta.file_type().is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+            return Err(McpError::ConfigError(format!(
+                "{} must be a directory owned by this user with mode 0700",
+                dir.display()
+            )));
+        }
+    }
+    #[cfg(not(unix))]
+    std::fs::create_dir_all(&dir)?;
+
+    Ok(dir)
+}
+
+/// Write the pinned host key where only this server's ssh reads it
+fn write_known_hosts(name: &str, ssh: &SshConfig, host_key: &str) -> McpResult<PathBuf> {
+    let dir = known_hosts_dir()?;
+
+    let file_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}.known_hosts", file_name));
+
+    let host = if ssh.port == 22 {
+        ssh.host.clone()
+    } else {
+        format!("[{}]:{}", ssh.host, ssh.port)
+    };
+
+    // A fresh file each time, never following a link planted in its place
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600).custom_flags(libc::O_NOFOLLOW);
+    }
+    let mut file = options.open(&path)?;
+    std::io::Write::write_all(&mut file, format!("{} {}\n", host, host_key.trim()).as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh_server() -> McpServerConfig {
+        McpServerConfig {
+            name: "build box".to_string(),
+            server_type: ServerType::Ssh,
+            command: "uvx".to_string(),
+            args: vec!["mcp-server-git".to_string(), "--repository".to_string(), "/srv/my repo".to_string()],
+            env: HashMap::from([("GIT_TOKEN".to_string(), "a'b c".to_string())]),
+            sandbox: crate::config::SandboxConfig {
+                network: true,
+                ..Default::default()
+            },
+            ssh: Some(SshConfig {
+                host: "build.internal".to_string(),
+                port: 2222,
+                user: Some("mcp".to_string()),
+                host_key: Some("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIexample".to_string()),
+                use_agent: false,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_ssh_command() {
+        let mut config = ssh_server();
+        apply_ssh_command(&mut config).unwrap();
+
+        assert_eq!(config.command, "ssh");
+        assert_eq!(config.server_type, ServerType::Local);
+        assert_eq!(config.stdio.local_env, Some(HashMap::new()));
+        assert!(!config.args.iter().any(|a| a.contains("GIT_TOKEN") || a.contains("a'b")));
+        assert!(config.args.contains(&"StrictHostKeyChecking=yes".to_string()));
+        assert!(config.args.contains(&"IdentityAgent=none".to_string()));
+        assert!(config.args.iter().any(|a| a.starts_with("UserKnownHostsFile=")));
+
+        let (remote, rest) = config.args.split_last().unwrap();
+        assert_eq!(rest.last().map(String::as_str), Some("--"));
+        assert_eq!(rest[rest.len() - 2], "mcp@build.internal");
+        assert_eq!(
+            shell_words::split(remote).unwrap(),
+            vec!["sh", "-c", ENV_READER, "sh", "uvx", "mcp-server-git", "--repository", "/srv/my repo"]
+        );
+
+        let path = config
+            .args
+            .iter()
+            .find_map(|a| a.strip_prefix("UserKnownHostsFile="))
+            .unwrap();
+        assert!(path.ends_with("supermcp/ssh/build_box.known_hosts"));
+        let known_hosts = std::fs::read_to_string(path).unwrap();
+        assert!(known_hosts.starts_with("[build.internal]:2222 ssh-ed25519 "));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let dir = std::path::Path::new(path).parent().unwrap();
+            assert_eq!(std::fs::metadata(dir).unwrap().permissions().mode() & 0o777, 0o700);
+            assert_eq!(std::fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_apply_ssh_command_rejects_bad_env() {
+        let mut config = ssh_server();
+        config.env.insert("X; rm -rf /".to_string(), "1".to_string());
+        assert!(apply_ssh_command(&mut config).is_err());
+
+        let mut config = ssh_server();
+        config.env.insert("MULTI".to_string(), "a\nB=1".to_string());
+        assert!(apply_ssh_command(&mut config).is_err());
+    }
+
+    #[test]
+    fn test_apply_ssh_command_needs_network() {
+        let mut config = ssh_server();
+        config.sandbox.network = false;
+        assert!(matches!(apply_ssh_command(&mut config), Err(McpError::ConfigError(_))));
+
+        config.sandbox.enabled = false;
+        apply_ssh_command(&mut config).unwrap();
+        assert!(!config.sandbox.network);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_env_reader_exports_preamble() {
+        use std::io::Write;
+
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", ENV_READER, "sh", "sh", "-c", r#"printf '%s\n' "$GIT_TOKEN"; cat"#])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let env = ssh_server().env;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(env_preamble(&env).unwrap().as_bytes()).unwrap();
+        stdin.write_all(b"{\"jsonrpc\":\"2.0\"}\n").unwrap();
+        drop(stdin);
+
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "a'b c\n{\"jsonrpc\":\"2.0\"}\n");
+    }
+
+    #[test]
+    fn test_apply_ssh_command_rejects_option_hosts() {
+        let mut config = ssh_server();
+        config.ssh.as_mut().unwrap().host = "-oProxyCommand=touch /tmp/x".to_string();
+        assert!(apply_ssh_command(&mut config).is_err());
+    }
+
+    #[test]
+    fn test_local_servers_untouched() {
+        let mut config = McpServerConfig {
+            command: "uvx".to_string(),
+            ..Default::default()
+        };
+        apply_ssh_command(&mut config).unwrap();
+        assert_eq!(config.command, "uvx");
+    }
+}
@@ -1,12 +1,16 @@
+use crate::config::{StdioConfig, StdioFraming};
 use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
 use crate::core::SharedRequestIdGenerator;
 use crate::sandbox::Sandbox;
+use crate::transport::framing::{encode_message, MessageReader};
 use crate::transport::traits::Transport;
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
 use tokio::process::{Child, ChildStdin, ChildStdout};
 use tokio::sync::{oneshot, Mutex, RwLock};
 use tracing::{debug, error, info, warn};
@@ -18,8 +22,22 @@ pub struct StdioTransport {
     pending: Arc<DashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
     is_connected: Arc<RwLock<bool>>,
     request_id_gen: SharedRequestIdGenerator,
+    framing: StdioFraming,
+    /// Set once an auto-framed server is seen sending Content-Length frames,
+    /// or answers nothing until probed with them
+    content_length_detected: Arc<AtomicBool>,
+    /// Set once the server has sent any message
+    received_any: Arc<AtomicBool>,
+    /// How long an auto-framed server may leave the first request unanswered
+    /// before it is resent with Content-Length framing
+    probe: Option<Duration>,
+    /// Set once the framing probe has been sent
+    probed: AtomicBool,
 }
 
+/// How long a request waits for its response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl StdioTransport {
     pub async fn new(
         command: impl Into<String>,
@@ -27,6 +45,22 @@ impl StdioTransport {
         env: std::collections::HashMap<String, String>,
         sandbox: Arc<dyn Sandbox>,
     ) -> McpResult<Self> {
+        Self::with_config(command, args, env, sandbox, &StdioConfig::default()).await
+    }
+
+    /// Spawn the server with explicit framing settings
+    pub async fn with_config(
+        command: impl Into<String>,
+        args: Vec<String>,
+        env: std::collections::HashMap<String, String>,
+        sandbox: Arc<dyn Sandbox>,
+        stdio: &StdioConfig,
+    ) -> McpResult<Self> {
+        // Env meant for a remote process goes on stdin, ahead of any message
+        let (env, remote_env) = match &stdio.local_env {
+            Some(local) => (local.clone(), Some(env)),
+            None => (env, None),
+        };
         let config = crate::config::McpServerConfig {
             name: "temp".to_string(),
             command: command.into(),
@@ -35,14 +69,19 @@ impl StdioTransport {
             tags: vec![],
             description: None,
             sandbox: crate::config::SandboxConfig::default(),
+            ..Default::default()
         };
 
         let mut child = sandbox.spawn(&config).await?;
 
-        let stdin = child
+        let mut stdin = child
             .stdin
             .take()
             .ok_or_else(|| McpError::TransportError("Failed to open stdin".to_string()))?;
+        if let Some(env) = remote_env {
+            stdin.write_all(crate::transport::ssh::env_preamble(&env)?.as_bytes()).await?;
+            stdin.flush().await?;
+        }
 
         let stdout = child
             .stdout
@@ -55,26 +94,47 @@ impl StdioTransport {
             pending: Arc::new(DashMap::new()),
             is_connected: Arc::new(RwLock::new(true)),
             request_id_gen: SharedRequestIdGenerator::new(),
+            framing: stdio.framing,
+            content_length_detected: Arc::new(AtomicBool::new(false)),
+            received_any: Arc::new(AtomicBool::new(false)),
+            probe: (stdio.framing == StdioFraming::Auto && stdio.probe_ms > 0)
+                .then(|| Duration::from_millis(stdio.probe_ms)),
+            probed: AtomicBool::new(false),
         };
 
         // Start response reader task
-        transport.start_reader(stdout).await;
+        transport
+            .start_reader(stdout, stdio.max_message_bytes)
+            .await;
 
         Ok(transport)
     }
 
-    async fn start_reader(&self, stdout: ChildStdout) {
+    async fn start_reader(&self, stdout: ChildStdout, max_message_bytes: usize) {
         let pending = self.pending.clone();
         let is_connected = self.is_connected.clone();
+        let content_length_detected = self.content_length_detected.clone();
+        let received_any = self.received_any.clone();
+        let mut reader = MessageReader::new(stdout, self.framing, max_message_bytes);
 
         tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            loop {
+                let message = match reader.next_message().await {
+                    Ok(Some(message)) => message,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to read from stdout: {}", e);
+                        break;
+                    }
+                };
+                debug!("Received: {}", String::from_utf8_lossy(&message));
+                received_any.store(true, Ordering::Relaxed);
 
-            while let Ok(Some(line)) = lines.next_line().await {
-                debug!("Received: {}", line);
+                if reader.detected() == Some(StdioFraming::ContentLength) {
+                    content_length_detected.store(true, Ordering::Relaxed);
+                }
 
-                match serde_json::from_str::<JsonRpcResponse>(&line) {
+                match serde_json::from_slice::<JsonRpcResponse>(&message) {
                     Ok(response) => {
                         if let Some(id) = response.id.clone() {
                             if let Some((_, tx)) = pending.remove(&id) {
@@ -97,6 +157,21 @@ impl StdioTransport {
             pending.clear();
         });
     }
+
+    /// Write a message using the configured (or detected) framing
+    async fn write_message(&self, json: &str) -> McpResult<()> {
+        let framing = match self.framing {
+            StdioFraming::Auto if self.content_length_detected.load(Ordering::Relaxed) => {
+                StdioFraming::ContentLength
+            }
+            framing => framing,
+        };
+
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(&encode_message(json, framing)).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -122,29 +197,39 @@ impl Transport for StdioTransport {
         debug!("Sending: {}", json);
 
         // Write request
-        {
-            let mut stdin = self.stdin.lock().await;
-            if let Err(e) = stdin.write_all(json.as_bytes()).await {
-                self.pending.remove(&request_id);
-                return Err(McpError::Io(e));
-            }
-            if let Err(e) = stdin.write_all(b"\n").await {
-                self.pending.remove(&request_id);
-                return Err(McpError::Io(e));
+        if let Err(e) = self.write_message(&json).await {
+            self.pending.remove(&request_id);
+            return Err(e);
+        }
+
+        // Wait for response, probing a silent auto-framed server once
+        let mut rx = rx;
+        let mut remaining = REQUEST_TIMEOUT;
+        if let Some(probe) = self.probe.filter(|_| !self.received_any.load(Ordering::Relaxed)) {
+            if let Ok(response) = tokio::time::timeout(probe, &mut rx).await {
+                return response
+                    .map_err(|_| McpError::TransportError("Response channel closed".to_string()));
             }
-            if let Err(e) = stdin.flush().await {
-                self.pending.remove(&request_id);
-                return Err(McpError::Io(e));
+            remaining = remaining.saturating_sub(probe);
+            if !self.received_any.load(Ordering::Relaxed) && !self.probed.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "No reply after {}ms to a newline-framed request, sending it with Content-Length framing",
+                    probe.as_millis()
+                );
+                self.content_length_detected.store(true, Ordering::Relaxed);
+                if let Err(e) = self.write_message(&json).await {
+                    self.pending.remove(&request_id);
+                    return Err(e);
+                }
             }
         }
 
-        // Wait for response
-        match tokio::time::timeout(std::time::Duration::from_secs(30), rx).await {
+        match tokio::time::timeout(remaining, rx).await {
             Ok(Ok(response)) => Ok(response),
             Ok(Err(_)) => Err(McpError::TransportError("Response channel closed".to_string())),
             Err(_) => {
                 self.pending.remove(&request_id);
-                Err(McpError::Timeout(30000))
+                Err(McpError::UpstreamTimeout(REQUEST_TIMEOUT.as_millis() as u64))
             }
         }
     }
@@ -160,12 +245,7 @@ impl Transport for StdioTransport {
         let json = serde_json::to_string(&request)?;
         debug!("Sending notification: {}", json);
 
-        let mut stdin = self.stdin.lock().await;
-        stdin.write_all(json.as_bytes()).await?;
-        stdin.write_all(b"\n").await?;
-        stdin.flush().await?;
-
-        Ok(())
+        self.write_message(&json).await
     }
 
     async fn is_connected(&self) -> bool {
@@ -191,3 +271,60 @@ impl Transport for StdioTransport {
         Ok(())
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::sandbox::NoSandbox;
+
+    /// Answers Content-Length framed requests only, ignoring newline ones
+    const FRAMED_ONLY_SERVER: &str = r#"
+import json, sys
+stdin = sys.stdin.buffer
+while True:
+    line = stdin.readline()
+    if not line:
+        break
+    if not line.lower().startswith(b"content-length:"):
+        continue
+    length = int(line.split(b":")[1])
+    stdin.readline()
+    request = json.loads(stdin.read(length))
+    body = json.dumps({"jsonrpc": "2.0", "id": request["id"], "result": {}}).encode()
+    sys.stdout.buffer.write(b"Content-Length: %d\r\n\r\n" % len(body) + body)
+    sys.stdout.buffer.flush()
+"#;
+
+    #[tokio::test]
+    async fn test_silent_server_is_probed_with_content_length() {
+        let stdio = StdioConfig {
+            probe_ms: 300,
+            ..Default::default()
+        };
+        let transport = StdioTransport::with_config(
+            "python3",
+            vec!["-c".to_string(), FRAMED_ONLY_SERVER.to_string()],
+            Default::default(),
+            Arc::new(NoSandbox::new()),
+            &stdio,
+        )
+        .await
+        .unwrap();
+
+        let response = transport
+            .send_request(JsonRpcRequest::new("initialize", None))
+            .await
+            .unwrap();
+        assert!(response.result.is_some());
+        assert!(transport.probed.load(Ordering::Relaxed));
+
+        // Later requests go out framed straight away
+        let started = std::time::Instant::now();
+        transport
+            .send_request(JsonRpcRequest::new("tools/list", None))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_millis(300));
+        transport.close().await.unwrap();
+    }
+}
@@ -6,12 +6,17 @@
 
 use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
 use crate::core::SharedRequestIdGenerator;
-use crate::transport::traits::Transport;
+use crate::config::{InitializeOverrides, ProxyConfig, UpstreamHttpConfig, UpstreamTlsConfig};
+use crate::transport::http_client::{
+    global_proxy, header_map, metrics_for, upstream_client, UpstreamMetrics,
+};
+use crate::transport::traits::{should_stream, Transport, TransportResponse};
 use crate::utils::errors::{McpError, McpResult};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use futures::StreamExt;
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::sync::{oneshot, RwLock};
@@ -22,55 +27,106 @@ use url::Url;
 pub struct StreamableHttpTransport {
     endpoint: Url,
     client: reqwest::Client,
+    headers: HeaderMap,
+    metrics: Arc<UpstreamMetrics>,
     session_id: Arc<RwLock<Option<String>>>,
     pending: Arc<DashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
     is_connected: Arc<RwLock<bool>>,
     request_id_gen: SharedRequestIdGenerator,
+    /// Deadline for each POST to be answered with headers
+    request_timeout: std::time::Duration,
 }
 
 impl StreamableHttpTransport {
     pub async fn new(endpoint: impl Into<String>) -> McpResult<Self> {
-        let endpoint = endpoint
+        Self::with_config(endpoint, &UpstreamHttpConfig::default()).await
+    }
+
+    /// Create a transport using the shared client for the given tuning
+    pub async fn with_config(
+        endpoint: impl Into<String>,
+        http_config: &UpstreamHttpConfig,
+    ) -> McpResult<Self> {
+        Self::with_headers(endpoint, http_config, &HashMap::new()).await
+    }
+
+    /// Create a transport that sends extra headers with every request
+    pub async fn with_headers(
+        endpoint: impl Into<String>,
+        http_config: &UpstreamHttpConfig,
+        headers: &HashMap<String, String>,
+    ) -> McpResult<Self> {
+        Self::with_initialize(endpoint, http_config, headers, &InitializeOverrides::default()).await
+    }
+
+    /// Create a transport whose handshake announces what `initialize` says
+    pub async fn with_initialize(
+        endpoint: impl Into<String>,
+        http_config: &UpstreamHttpConfig,
+        headers: &HashMap<String, String>,
+        initialize: &InitializeOverrides,
+    ) -> McpResult<Self> {
+        Self::with_tls(
+            endpoint,
+            http_config,
+            &UpstreamTlsConfig::default(),
+            &global_proxy(),
+            headers,
+            initialize,
+        )
+        .await
+    }
+
+    /// Create a transport with custom TLS and proxy settings
+    pub async fn with_tls(
+        endpoint: impl Into<String>,
+        http_config: &UpstreamHttpConfig,
+        tls: &UpstreamTlsConfig,
+        proxy: &ProxyConfig,
+        headers: &HashMap<String, String>,
+        initialize: &InitializeOverrides,
+    ) -> McpResult<Self> {
+        let headers = header_map(headers)?;
+        let mut endpoint = endpoint
             .into()
             .parse::<Url>()
             .map_err(|e| McpError::TransportError(format!("Invalid URL: {}", e)))?;
 
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .pool_max_idle_per_host(10)
-            .build()
-            .map_err(|e| McpError::TransportError(e.to_string()))?;
+        let metrics = metrics_for(&endpoint);
+        let client = upstream_client(http_config, tls, proxy, &mut endpoint).await?;
 
         let transport = Self {
             endpoint,
             client,
+            headers,
+            metrics,
             session_id: Arc::new(RwLock::new(None)),
             pending: Arc::new(DashMap::new()),
             is_connected: Arc::new(RwLock::new(false)),
             request_id_gen: SharedRequestIdGenerator::new(),
+            request_timeout: std::time::Duration::from_secs(http_config.request_timeout_secs),
         };
 
         // Initialize connection
-        transport.initialize().await?;
+        transport.initialize(initialize).await?;
 
         Ok(transport)
     }
 
-    async fn initialize(&self) -> McpResult<()> {
+    async fn initialize(&self, overrides: &InitializeOverrides) -> McpResult<()> {
         info!("Initializing Streamable HTTP transport: {}", self.endpoint);
 
         // Send initialize request to establish session
-        let mut init_request = JsonRpcRequest::new(
-            "initialize",
-            Some(serde_json::json!({
-                "protocolVersion": "2024-11-05",
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "super-mcp",
-                    "version": env!("CARGO_PKG_VERSION")
-                }
-            })),
-        );
+        let mut params = serde_json::json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "super-mcp",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        });
+        overrides.apply(&mut params);
+        let mut init_request = JsonRpcRequest::new("initialize", Some(params));
 
         if init_request.id.is_none() {
             init_request.id = Some(self.request_id_gen.next_id());
@@ -85,15 +141,23 @@ impl StreamableHttpTransport {
 
         let json = serde_json::to_string(&init_request)?;
 
-        let response = self
-            .client
-            .post(self.endpoint.clone())
-            .header(CONTENT_TYPE, "application/json")
-            .header(ACCEPT, "application/x-ndjson")
-            .body(json)
-            .send()
+        let response = match self
+            .send(
+                "Initialize",
+                self.client
+                    .post(self.endpoint.clone())
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(ACCEPT, "application/x-ndjson")
+                    .body(json),
+            )
             .await
-            .map_err(|e| McpError::TransportError(format!("Initialize failed: {}", e)))?;
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.pending.remove(&request_id);
+                return Err(e);
+            }
+        };
 
         if !response.status().is_success() {
             self.pending.remove(&request_id);
@@ -122,7 +186,7 @@ impl StreamableHttpTransport {
             Ok(Err(_)) => Err(McpError::TransportError("Initialize response channel closed".to_string())),
             Err(_) => {
                 self.pending.remove(&request_id);
-                Err(McpError::Timeout(30000))
+                Err(McpError::UpstreamTimeout(30000))
             }
         }
     }
@@ -171,6 +235,29 @@ impl StreamableHttpTransport {
         });
     }
 
+    /// Send an HTTP request, recording upstream connection metrics
+    ///
+    /// The deadline covers getting the response headers only; the body is
+    /// a stream of messages that may stay open much longer.
+    async fn send(&self, what: &str, request: reqwest::RequestBuilder) -> McpResult<reqwest::Response> {
+        let started = std::time::Instant::now();
+        let sent = request.headers(self.headers.clone()).send();
+        match tokio::time::timeout(self.request_timeout, sent).await {
+            Ok(Ok(response)) => {
+                self.metrics.record_response(response.version(), started.elapsed());
+                Ok(response)
+            }
+            Ok(Err(e)) => {
+                self.metrics.record_failure();
+                Err(McpError::TransportError(format!("{} failed: {}", what, e)))
+            }
+            Err(_) => {
+                self.metrics.record_failure();
+                Err(McpError::UpstreamTimeout(self.request_timeout.as_millis() as u64))
+            }
+        }
+    }
+
     fn build_request_url(&self, session_id: Option<String>) -> Url {
         let mut url = self.endpoint.clone();
 
@@ -181,13 +268,12 @@ impl StreamableHttpTransport {
 
         url
     }
-}
 
-#[async_trait]
-impl Transport for StreamableHttpTransport {
-    async fn send_request(&self,
+    /// POST a request upstream, registering its pending slot first
+    async fn post_request(
+        &self,
         request: JsonRpcRequest,
-    ) -> McpResult<JsonRpcResponse> {
+    ) -> McpResult<(RequestId, oneshot::Receiver<JsonRpcResponse>, reqwest::Response)> {
         if !self.is_connected().await {
             return Err(McpError::TransportError("Transport not connected".to_string()));
         }
@@ -210,15 +296,23 @@ impl Transport for StreamableHttpTransport {
         let session_id = self.session_id.read().await.clone();
         let url = self.build_request_url(session_id);
 
-        let response = self
-            .client
-            .post(url)
-            .header(CONTENT_TYPE, "application/json")
-            .header(ACCEPT, "application/x-ndjson")
-            .body(json)
-            .send()
+        let response = match self
+            .send(
+                "Request",
+                self.client
+                    .post(url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(ACCEPT, "application/x-ndjson")
+                    .body(json),
+            )
             .await
-            .map_err(|e| McpError::TransportError(format!("Request failed: {}", e)))?;
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.pending.remove(&request_id);
+                return Err(e);
+            }
+        };
 
         if !response.status().is_success() {
             self.pending.remove(&request_id);
@@ -228,19 +322,77 @@ impl Transport for StreamableHttpTransport {
             )));
         }
 
-        // Start reader for this response stream
-        self.start_reader(response).await;
+        Ok((request_id, rx, response))
+    }
 
-        // Wait for response via channel
+    /// Wait for the reader task to deliver the response for `request_id`
+    async fn await_response(
+        &self,
+        request_id: &RequestId,
+        rx: oneshot::Receiver<JsonRpcResponse>,
+    ) -> McpResult<JsonRpcResponse> {
         match tokio::time::timeout(std::time::Duration::from_secs(30), rx).await {
             Ok(Ok(response)) => Ok(response),
             Ok(Err(_)) => Err(McpError::TransportError("Response channel closed".to_string())),
             Err(_) => {
-                self.pending.remove(&request_id);
-                Err(McpError::Timeout(30000))
+                self.pending.remove(request_id);
+                Err(McpError::UpstreamTimeout(30000))
             }
         }
     }
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    async fn send_request(&self,
+        request: JsonRpcRequest,
+    ) -> McpResult<JsonRpcResponse> {
+        let (request_id, rx, response) = self.post_request(request).await?;
+
+        // Start reader for this response stream
+        self.start_reader(response).await;
+
+        // Wait for response via channel
+        self.await_response(&request_id, rx).await
+    }
+
+    async fn send_request_streaming(
+        &self,
+        request: JsonRpcRequest,
+        max_in_memory_bytes: usize,
+    ) -> McpResult<TransportResponse> {
+        let (request_id, rx, response) = self.post_request(request).await?;
+
+        if !should_stream(response.content_length(), max_in_memory_bytes) {
+            self.start_reader(response).await;
+            return self
+                .await_response(&request_id, rx)
+                .await
+                .map(TransportResponse::Buffered);
+        }
+
+        // The body is handed to the caller untouched, so nothing will
+        // complete the pending slot.
+        self.pending.remove(&request_id);
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/x-ndjson")
+            .to_string();
+        debug!(
+            "Streaming upstream body for {:?} ({})",
+            request_id, content_type
+        );
+
+        let body = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(std::io::Error::other))
+            .boxed();
+
+        Ok(TransportResponse::Streamed { content_type, body })
+    }
 
     async fn send_notification(&self,
         request: JsonRpcRequest,
@@ -259,14 +411,15 @@ impl Transport for StreamableHttpTransport {
         let url = self.build_request_url(session_id);
 
         let response = self
-            .client
-            .post(url)
-            .header(CONTENT_TYPE, "application/json")
-            .header(ACCEPT, "application/x-ndjson")
-            .body(json)
-            .send()
-            .await
-            .map_err(|e| McpError::TransportError(format!("Notification failed: {}", e)))?;
+            .send(
+                "Notification",
+                self.client
+                    .post(url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .header(ACCEPT, "application/x-ndjson")
+                    .body(json),
+            )
+            .await?;
 
         if !response.status().is_success() {
             return Err(McpError::TransportError(format!(
@@ -295,7 +448,9 @@ impl Transport for StreamableHttpTransport {
             let _ = self
                 .client
                 .delete(self.endpoint.clone())
+                .headers(self.headers.clone())
                 .query(&[("session_id", id)])
+                .timeout(self.request_timeout)
                 .send()
                 .await;
         }
@@ -0,0 +1,507 @@
+//! Federation with another super-mcp instance
+//!
+//! A `type = "supermcp"` server points at another proxy's HTTP API instead
+//! of an MCP server. The remote's aggregated tools are re-exported one
+//! namespace level deeper: tool `create_issue` of the remote's `github`
+//! server is listed as `github.create_issue`.
+//!
+//! Every federated request carries the instances it already passed through
+//! in `X-SuperMCP-Via`. An instance that finds itself in that chain refuses
+//! the request, so cyclic topologies fail fast instead of recursing.
+//!
+//! With `peer_public_key` set, bodies are also encrypted end to end (see
+//! [`crate::transport::envelope`]).
+
+use crate::config::{UpstreamHttpConfig, UpstreamTlsConfig};
+use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
+use crate::core::request_id::{current_correlation_id, REQUEST_ID_HEADER};
+use crate::transport::envelope::{
+    self, Binding, Envelope, BINDING_HEADER, KEY_HEADER, SEALED_CONTENT_TYPE,
+};
+use crate::transport::http_client::{global_proxy, header_map, shared_client};
+use crate::transport::traits::Transport;
+use crate::utils::errors::{McpError, McpResult};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Header listing the instances a request passed through, oldest first
+pub const VIA_HEADER: &str = "x-supermcp-via";
+
+/// Longest chain of instances a request may cross
+pub const MAX_HOPS: usize = 8;
+
+/// Separator between the remote server name and its tool name
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+/// How long a health check result is trusted
+const HEALTH_TTL: Duration = Duration::from_secs(15);
+
+/// Largest response body read from a federated instance
+const MAX_RESPONSE_BYTES: usize = 32 * 1024 * 1024;
+
+/// JSON-RPC "method not found"
+const METHOD_NOT_FOUND: i32 = -32601;
+
+static INSTANCE_ID: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().to_string());
+
+tokio::task_local! {
+    static VIA_CHAIN: Vec<String>;
+}
+
+/// Random ID of this process, as it appears in `X-SuperMCP-Via`
+pub fn instance_id() -> &'static str {
+    &INSTANCE_ID
+}
+
+/// Split an `X-SuperMCP-Via` header value
+pub fn parse_via(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Run `f` as part of a request that came through `chain`
+///
+/// Federated requests made inside `f` extend the chain rather than
+/// starting a new one.
+pub async fn with_via_chain<F: Future>(chain: Vec<String>, f: F) -> F::Output {
+    VIA_CHAIN.scope(chain, f).await
+}
+
+/// Chain to send upstream: the incoming chain plus this instance
+fn outgoing_via() -> String {
+    let mut chain = VIA_CHAIN.try_with(Clone::clone).unwrap_or_default();
+    chain.push(instance_id().to_string());
+    chain.join(",")
+}
+
+/// Transport to another super-mcp instance
+pub struct SuperMcpTransport {
+    base: String,
+    client: reqwest::Client,
+    headers: HeaderMap,
+    /// Encryption to the remote's pinned key
+    envelope: Option<Envelope>,
+    /// Exported tool name to (remote server, remote tool)
+    tools: DashMap<String, (String, String)>,
+    healthy: AtomicBool,
+    /// Deadline for each call, body included
+    request_timeout: Duration,
+    checked_at: Mutex<Option<Instant>>,
+}
+
+impl SuperMcpTransport {
+    /// Connect, failing if the remote instance isn't healthy
+    ///
+    /// With `peer_public_key`, payloads are encrypted to that key using the
+    /// installed [`envelope::identity`].
+    pub async fn connect(
+        endpoint: impl Into<String>,
+        http_config: &UpstreamHttpConfig,
+        headers: &HashMap<String, String>,
+        peer_public_key: Option<&str>,
+    ) -> McpResult<Self> {
+        let endpoint = endpoint.into();
+        url::Url::parse(&endpoint).map_err(|e| McpError::TransportError(format!("Invalid URL: {}", e)))?;
+
+        let envelope = match peer_public_key {
+            Some(peer) => {
+                let key = envelope::identity().ok_or_else(|| {
+                    McpError::ConfigError(format!(
+                        "federation.private_key is needed to encrypt requests to {}",
+                        endpoint
+                    ))
+                })?;
+                Some(Envelope::client(key, envelope::decode_key(peer)?))
+            }
+            None => None,
+        };
+
+        let transport = Self {
+            base: endpoint.trim_end_matches('/').to_string(),
+            client: shared_client(http_config, &UpstreamTlsConfig::default(), &global_proxy())?,
+            headers: header_map(headers)?,
+            envelope,
+            tools: DashMap::new(),
+            healthy: AtomicBool::new(false),
+            request_timeout: Duration::from_secs(http_config.request_timeout_secs),
+            checked_at: Mutex::new(None),
+        };
+
+        if !transport.check_health().await {
+            return Err(McpError::TransportError(format!(
+                "Federated super-mcp at {} is not healthy",
+                transport.base
+            )));
+        }
+        info!("Federating super-mcp at {}", transport.base);
+        Ok(transport)
+    }
+
+    /// Call an API path, sealing the exchange when encryption is configured
+    ///
+    /// `/health` is answered outside the remote's API layers, so it stays
+    /// in the clear.
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> McpResult<Value> {
+        let envelope = self.envelope.as_ref().filter(|_| path != "/health");
+        let binding = envelope.map(|_| Binding::new(method.as_str(), path));
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.base, path))
+            .headers(self.headers.clone())
+            .header(VIA_HEADER, outgoing_via())
+            .timeout(self.request_timeout);
+        // The remote instance logs under the same correlation ID
+        if let Some(id) = current_correlation_id() {
+            request = request.header(REQUEST_ID_HEADER, &*id);
+        }
+        match (envelope.zip(binding.as_ref()), body) {
+            // Body-less requests are sealed too, to authenticate the binding
+            (Some((envelope, binding)), body) => {
+                let plaintext = body.map(|body| serde_json::to_vec(&body)).transpose()?;
+                let sealed = envelope.seal_request(binding, &plaintext.unwrap_or_default())?;
+                request = request
+                    .header(KEY_HEADER, envelope.public_key_base64())
+                    .header(BINDING_HEADER, binding.header_value())
+                    .header(CONTENT_TYPE, SEALED_CONTENT_TYPE)
+                    .body(sealed);
+            }
+            (None, Some(body)) => request = request.json(&body),
+            (None, None) => {}
+        }
+
+        let failed = |e: reqwest::Error| {
+            self.healthy.store(false, Ordering::Relaxed);
+            McpError::TransportError(format!("Federated request failed: {}", e))
+        };
+        let mut response = request.send().await.map_err(failed)?;
+
+        let status = response.status();
+        let sealed = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .is_some_and(|t| t.as_bytes() == SEALED_CONTENT_TYPE.as_bytes());
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(failed)? {
+            if bytes.len() + chunk.len() > MAX_RESPONSE_BYTES {
+                return Err(McpError::TransportError(format!(
+                    "Federated super-mcp at {} answered with more than {} bytes",
+                    self.base, MAX_RESPONSE_BYTES
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let body: Value = match envelope.zip(binding.as_ref()) {
+            Some((envelope, binding)) if sealed => {
+                serde_json::from_slice(&envelope.open_response(binding, status.as_u16(), &bytes)?)
+                    .unwrap_or(Value::Null)
+            }
+            // Refusals by the remote's encryption layer are in the clear;
+            // nothing unauthenticated is passed on
+            Some(_) => {
+                return Err(McpError::AuthError(format!(
+                    "Federated super-mcp at {} answered {} without encryption",
+                    self.base, status
+                )));
+            }
+            None => serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        };
+        if !status.is_success() {
+            let message = body
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or_else(|| status.canonical_reason().unwrap_or("error"));
+            return Err(McpError::TransportError(format!(
+                "Federated super-mcp returned {}: {}",
+                status, message
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Refresh the cached health of the remote instance
+    async fn check_health(&self) -> bool {
+        let healthy = match self.call(Method::GET, "/health", None).await {
+            Ok(body) => body.get("status").and_then(|s| s.as_str()) == Some("healthy"),
+            Err(e) => {
+                debug!("Health check of {} failed: {}", self.base, e);
+                false
+            }
+        };
+        self.healthy.store(healthy, Ordering::Relaxed);
+        *self.checked_at.lock() = Some(Instant::now());
+        healthy
+    }
+
+    async fn list_tools(&self) -> McpResult<Value> {
+        let body = self.call(Method::GET, "/tools", None).await?;
+        if let Some(error) = body.get("error").and_then(|e| e.as_str()) {
+            return Err(McpError::TransportError(format!("Federated tool list failed: {}", error)));
+        }
+
+        let mut exported = Vec::new();
+        for tool in body.get("tools").and_then(|t| t.as_array()).into_iter().flatten() {
+            let (Some(server), Some(name)) = (
+                tool.get("server").and_then(|s| s.as_str()),
+                tool.get("name").and_then(|n| n.as_str()),
+            ) else {
+                continue;
+            };
+            let exported_name = format!("{}{}{}", server, NAMESPACE_SEPARATOR, name);
+            self.tools
+                .insert(exported_name.clone(), (server.to_string(), name.to_string()));
+            exported.push(json!({
+                "name": exported_name,
+                "description": tool.get("description").cloned().unwrap_or(json!("")),
+                "inputSchema": tool.get("inputSchema").cloned().unwrap_or(json!({})),
+            }));
+        }
+        Ok(json!({ "tools": exported }))
+    }
+
+    /// Call a re-exported tool; JSON-RPC errors from the remote are returned as `Ok(Err)`
+    async fn call_tool(&self, params: Option<&Value>) -> McpResult<Result<Value, (i32, String)>> {
+        let name = params
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| McpError::InvalidRequest("tools/call needs a tool name".to_string()))?;
+        let (server, tool) = match self.tools.get(name) {
+            Some(entry) => entry.value().clone(),
+            None => name
+                .split_once(NAMESPACE_SEPARATOR)
+                .map(|(server, tool)| (server.to_string(), tool.to_string()))
+                .ok_or_else(|| {
+                    McpError::InvalidRequest(format!(
+                        "Federated tool names look like <server>{}<tool>: {}",
+                        NAMESPACE_SEPARATOR, name
+                    ))
+                })?,
+        };
+
+        let arguments = params
+            .and_then(|p| p.get("arguments"))
+            .cloned()
+            .unwrap_or(json!({}));
+        let body = self
+            .call(
+                Method::POST,
+                "/tools/invoke",
+                Some(json!({ "server": server, "tool": tool, "arguments": arguments })),
+            )
+            .await?;
+
+        // The invoke API answers with the tool result or {"error", "code"}
+        match (body.get("error"), body.get("content")) {
+            (Some(error), None) => {
+                let code = body
+                    .get("code")
+                    .and_then(|c| c.as_i64())
+                    .unwrap_or(-32603) as i32;
+                Ok(Err((code, error.as_str().unwrap_or("Federated tool call failed").to_string())))
+            }
+            _ => Ok(Ok(body)),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for SuperMcpTransport {
+    async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse> {
+        let id = request.id.clone().unwrap_or(RequestId::Number(0));
+
+        let result = match request.method.as_str() {
+            "initialize" => {
+                let version = request
+                    .params
+                    .as_ref()
+                    .and_then(|p| p.get("protocolVersion"))
+                    .cloned()
+                    .unwrap_or(json!("2024-11-05"));
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "super-mcp", "version": env!("CARGO_PKG_VERSION") },
+                })
+            }
+            "ping" => {
+                if !self.check_health().await {
+                    return Err(McpError::TransportError(format!(
+                        "Federated super-mcp at {} is not healthy",
+                        self.base
+                    )));
+                }
+                json!({})
+            }
+            "tools/list" => self.list_tools().await?,
+            "tools/call" => match self.call_tool(request.params.as_ref()).await? {
+                Ok(result) => result,
+                Err((code, message)) => return Ok(JsonRpcResponse::error(id, code, message)),
+            },
+            method => {
+                return Ok(JsonRpcResponse::error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("Method not found: {} (federated super-mcp servers only expose tools)", method),
+                ));
+            }
+        };
+
+        self.healthy.store(true, Ordering::Relaxed);
+        Ok(JsonRpcResponse::success(id, result))
+    }
+
+    async fn send_notification(&self, request: JsonRpcRequest) -> McpResult<()> {
+        // The remote proxy manages its own upstream sessions
+        debug!("Not forwarding {} to federated super-mcp", request.method);
+        Ok(())
+    }
+
+    async fn is_connected(&self) -> bool {
+        let stale = self
+            .checked_at
+            .lock()
+            .is_none_or(|checked| checked.elapsed() > HEALTH_TTL);
+        if stale {
+            let healthy = self.check_health().await;
+            if !healthy {
+                warn!("Federated super-mcp at {} is unhealthy", self.base);
+            }
+            return healthy;
+        }
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    async fn close(&self) -> McpResult<()> {
+        self.healthy.store(false, Ordering::Relaxed);
+        self.tools.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        routing::{get, post},
+        Json, Router,
+    };
+
+    async fn remote() -> String {
+        let app = Router::new()
+            .route("/health", get(|| async { Json(json!({ "status": "healthy" })) }))
+            .route(
+                "/tools",
+                get(|| async {
+                    Json(json!({
+                        "count": 1,
+                        "tools": [{ "name": "echo", "description": "Echo", "inputSchema": {}, "server": "util" }],
+                    }))
+                }),
+            )
+            .route(
+                "/tools/invoke",
+                post(|Json(body): Json<Value>| async move {
+                    if body["tool"] == "echo" {
+                        Json(json!({ "content": [{ "type": "text", "text": body["arguments"]["text"] }] }))
+                    } else {
+                        Json(json!({ "error": "no such tool", "code": -32602 }))
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_tools_are_namespaced() {
+        let transport = SuperMcpTransport::connect(remote().await, &UpstreamHttpConfig::default(), &HashMap::new(), None)
+            .await
+            .unwrap();
+
+        let list = transport
+            .send_request(JsonRpcRequest::new("tools/list", None))
+            .await
+            .unwrap();
+        assert_eq!(list.result.unwrap()["tools"][0]["name"], "util.echo");
+
+        let call = transport
+            .send_request(JsonRpcRequest::new(
+                "tools/call",
+                Some(json!({ "name": "util.echo", "arguments": { "text": "hi" } })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(call.result.unwrap()["content"][0]["text"], "hi");
+
+        let missing = transport
+            .send_request(JsonRpcRequest::new("tools/call", Some(json!({ "name": "util.nope" }))))
+            .await
+            .unwrap();
+        assert_eq!(missing.error.unwrap().code, -32602);
+
+        let resources = transport
+            .send_request(JsonRpcRequest::new("resources/list", None))
+            .await
+            .unwrap();
+        assert_eq!(resources.error.unwrap().code, METHOD_NOT_FOUND);
+        assert!(transport.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_refusals_are_errors() {
+        let app = Router::new().route(
+            "/tools",
+            get(|| async {
+                (
+                    axum::http::StatusCode::FORBIDDEN,
+                    Json(json!({ "message": "forged by a middlebox" })),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let key = std::sync::Arc::new(envelope::FederationKey::generate().unwrap());
+        let peer = envelope::FederationKey::generate().unwrap().public_key();
+        let transport = SuperMcpTransport {
+            base: format!("http://{}", addr),
+            client: reqwest::Client::new(),
+            headers: HeaderMap::new(),
+            envelope: Some(Envelope::client(key, peer)),
+            tools: DashMap::new(),
+            healthy: AtomicBool::new(true),
+            request_timeout: Duration::from_secs(5),
+            checked_at: Mutex::new(None),
+        };
+        let error = transport.list_tools().await.unwrap_err();
+        assert!(matches!(error, McpError::AuthError(_)), "{}", error);
+        assert!(!error.to_string().contains("middlebox"));
+    }
+
+    #[tokio::test]
+    async fn test_via_chain_extends_incoming() {
+        assert_eq!(outgoing_via(), instance_id());
+
+        let chain = parse_via(" a, b ,,");
+        assert_eq!(chain, vec!["a", "b"]);
+        let via = with_via_chain(chain, async { outgoing_via() }).await;
+        assert_eq!(via, format!("a,b,{}", instance_id()));
+    }
+}
@@ -1,6 +1,47 @@
 use crate::core::protocol::{JsonRpcRequest, JsonRpcResponse};
 use crate::utils::errors::McpResult;
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+
+/// Raw upstream body chunks forwarded without buffering
+pub type ByteStream = BoxStream<'static, Result<Bytes, std::io::Error>>;
+
+/// Response from an upstream that may be forwarded without buffering
+pub enum TransportResponse {
+    /// Fully parsed JSON-RPC response
+    Buffered(JsonRpcResponse),
+    /// Upstream body passed through as-is (SSE or chunked NDJSON)
+    Streamed {
+        /// Content type reported by the upstream
+        content_type: String,
+        /// Body chunks in arrival order
+        body: ByteStream,
+    },
+}
+
+impl std::fmt::Debug for TransportResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Buffered(response) => f.debug_tuple("Buffered").field(response).finish(),
+            Self::Streamed { content_type, .. } => f
+                .debug_struct("Streamed")
+                .field("content_type", content_type)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Decide whether an upstream body should be streamed instead of buffered.
+///
+/// Bodies without a known length (chunked or SSE) are always streamed; bodies
+/// with a declared length are streamed only above the in-memory threshold.
+pub fn should_stream(content_length: Option<u64>, max_in_memory_bytes: usize) -> bool {
+    match content_length {
+        Some(len) => len > max_in_memory_bytes as u64,
+        None => true,
+    }
+}
 
 /// Transport for MCP communication
 #[async_trait]
@@ -8,6 +49,19 @@ pub trait Transport: Send + Sync {
     /// Send a request and wait for response
     async fn send_request(&self, request: JsonRpcRequest) -> McpResult<JsonRpcResponse>;
 
+    /// Send a request, allowing large or open-ended upstream bodies to be
+    /// forwarded as a stream instead of being parsed into memory.
+    ///
+    /// Transports that multiplex responses over a shared channel fall back
+    /// to buffering.
+    async fn send_request_streaming(
+        &self,
+        request: JsonRpcRequest,
+        _max_in_memory_bytes: usize,
+    ) -> McpResult<TransportResponse> {
+        self.send_request(request).await.map(TransportResponse::Buffered)
+    }
+
     /// Send a notification (no response expected)
     async fn send_notification(&self, request: JsonRpcRequest) -> McpResult<()>;
 
@@ -22,3 +76,20 @@ pub trait Transport: Send + Sync {
 pub trait TransportFactory: Send + Sync {
     fn create(&self) -> Box<dyn Transport>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_stream_unknown_length() {
+        assert!(should_stream(None, 1024));
+    }
+
+    #[test]
+    fn test_should_stream_threshold() {
+        assert!(!should_stream(Some(1024), 1024));
+        assert!(should_stream(Some(1025), 1024));
+        assert!(!should_stream(Some(0), 0));
+    }
+}
@@ -174,7 +174,7 @@ impl Transport for WebSocketTransport {
             Ok(Err(_)) => Err(McpError::TransportError("Response channel closed".to_string())),
             Err(_) => {
                 self.pending.remove(&request_id);
-                Err(McpError::Timeout(30000))
+                Err(McpError::UpstreamTimeout(30000))
             }
         }
     }
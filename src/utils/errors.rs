@@ -1,11 +1,112 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
+use serde_json::{json, Value};
 use thiserror::Error;
 
+/// Stable, machine-readable error codes
+///
+/// Sent as `error` in HTTP error bodies and as `error.data.code` in
+/// JSON-RPC errors. Codes are only ever added, never renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    ServerNotFound,
+    SandboxError,
+    SandboxDenied,
+    TransportError,
+    UpstreamTimeout,
+    AuthenticationError,
+    AuthorizationError,
+    QuotaExceeded,
+    ConfigError,
+    Timeout,
+    InvalidRequest,
+    InternalError,
+    IoError,
+    SerializationError,
+    InstallError,
+    ToolExecutionError,
+    Overloaded,
+    ConsentRequired,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ServerNotFound => "SERVER_NOT_FOUND",
+            Self::SandboxError => "SANDBOX_ERROR",
+            Self::SandboxDenied => "SANDBOX_DENIED",
+            Self::TransportError => "TRANSPORT_ERROR",
+            Self::UpstreamTimeout => "UPSTREAM_TIMEOUT",
+            Self::AuthenticationError => "AUTHENTICATION_ERROR",
+            Self::AuthorizationError => "AUTHORIZATION_ERROR",
+            Self::QuotaExceeded => "QUOTA_EXCEEDED",
+            Self::ConfigError => "CONFIG_ERROR",
+            Self::Timeout => "TIMEOUT",
+            Self::InvalidRequest => "INVALID_REQUEST",
+            Self::InternalError => "INTERNAL_ERROR",
+            Self::IoError => "IO_ERROR",
+            Self::SerializationError => "SERIALIZATION_ERROR",
+            Self::InstallError => "INSTALL_ERROR",
+            Self::ToolExecutionError => "TOOL_EXECUTION_ERROR",
+            Self::Overloaded => "OVERLOADED",
+            Self::ConsentRequired => "CONSENT_REQUIRED",
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ServerNotFound => StatusCode::NOT_FOUND,
+            Self::AuthenticationError => StatusCode::UNAUTHORIZED,
+            Self::AuthorizationError | Self::SandboxDenied | Self::ConsentRequired => {
+                StatusCode::FORBIDDEN
+            }
+            Self::InvalidRequest => StatusCode::BAD_REQUEST,
+            Self::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+            Self::Timeout | Self::UpstreamTimeout => StatusCode::GATEWAY_TIMEOUT,
+            Self::TransportError => StatusCode::BAD_GATEWAY,
+            Self::Overloaded => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// JSON-RPC error code; anything without a standard code is a
+    /// generic server error (-32000) told apart by `data.code`
+    pub fn json_rpc_code(&self) -> i32 {
+        match self {
+            Self::InvalidRequest => -32600,
+            Self::InternalError
+            | Self::IoError
+            | Self::SerializationError
+            | Self::ConfigError
+            | Self::InstallError => -32603,
+            _ => -32000,
+        }
+    }
+
+    /// Whether the same request may succeed if tried again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::TransportError
+                | Self::UpstreamTimeout
+                | Self::Timeout
+                | Self::QuotaExceeded
+                | Self::Overloaded
+        )
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum McpError {
     #[error("server not found: {0}")]
@@ -14,6 +115,10 @@ pub enum McpError {
     #[error("sandbox error: {0}")]
     SandboxError(String),
 
+    /// The sandbox policy refused the operation
+    #[error("denied by sandbox: {0}")]
+    SandboxDenied(String),
+
     #[error("transport error: {0}")]
     TransportError(String),
 
@@ -29,6 +134,13 @@ pub enum McpError {
     #[error("timeout after {0}ms")]
     Timeout(u64),
 
+    /// An upstream MCP server did not answer in time
+    #[error("upstream timed out after {0}ms")]
+    UpstreamTimeout(u64),
+
+    #[error("quota exceeded: {0}")]
+    QuotaExceeded(String),
+
     #[error("invalid request: {0}")]
     InvalidRequest(String),
 
@@ -46,6 +158,15 @@ pub enum McpError {
 
     #[error("tool execution error: {0}")]
     ToolExecutionError(String),
+
+    /// The proxy or an upstream pool is saturated; retry after this many
+    /// seconds
+    #[error("overloaded, retry after {0}s")]
+    Overloaded(u64),
+
+    /// The tool (`server.tool`) needs the caller's consent first
+    #[error("consent required to call {0}")]
+    ConsentRequired(String),
 }
 
 impl From<anyhow::Error> for McpError {
@@ -61,34 +182,55 @@ impl From<dialoguer::Error> for McpError {
 }
 
 impl McpError {
-    pub fn status_code(&self) -> StatusCode {
+    /// Position of this error in the taxonomy
+    pub fn code(&self) -> ErrorCode {
         match self {
-            Self::ServerNotFound(_) => StatusCode::NOT_FOUND,
-            Self::AuthError(_) => StatusCode::UNAUTHORIZED,
-            Self::AuthorizationError(_) => StatusCode::FORBIDDEN,
-            Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
-            Self::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
-            Self::TransportError(_) => StatusCode::BAD_GATEWAY,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ServerNotFound(_) => ErrorCode::ServerNotFound,
+            Self::SandboxError(_) => ErrorCode::SandboxError,
+            Self::SandboxDenied(_) => ErrorCode::SandboxDenied,
+            Self::TransportError(_) => ErrorCode::TransportError,
+            Self::AuthError(_) => ErrorCode::AuthenticationError,
+            Self::AuthorizationError(_) => ErrorCode::AuthorizationError,
+            Self::ConfigError(_) => ErrorCode::ConfigError,
+            Self::Timeout(_) => ErrorCode::Timeout,
+            Self::UpstreamTimeout(_) => ErrorCode::UpstreamTimeout,
+            Self::QuotaExceeded(_) => ErrorCode::QuotaExceeded,
+            Self::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            Self::InternalError(_) => ErrorCode::InternalError,
+            Self::Io(_) => ErrorCode::IoError,
+            Self::Serialization(_) => ErrorCode::SerializationError,
+            Self::InstallError(_) => ErrorCode::InstallError,
+            Self::ToolExecutionError(_) => ErrorCode::ToolExecutionError,
+            Self::Overloaded(_) => ErrorCode::Overloaded,
+            Self::ConsentRequired(_) => ErrorCode::ConsentRequired,
         }
     }
 
+    pub fn status_code(&self) -> StatusCode {
+        self.code().status_code()
+    }
+
     pub fn error_code(&self) -> &'static str {
-        match self {
-            Self::ServerNotFound(_) => "SERVER_NOT_FOUND",
-            Self::SandboxError(_) => "SANDBOX_ERROR",
-            Self::TransportError(_) => "TRANSPORT_ERROR",
-            Self::AuthError(_) => "AUTHENTICATION_ERROR",
-            Self::AuthorizationError(_) => "AUTHORIZATION_ERROR",
-            Self::ConfigError(_) => "CONFIG_ERROR",
-            Self::Timeout(_) => "TIMEOUT",
-            Self::InvalidRequest(_) => "INVALID_REQUEST",
-            Self::InternalError(_) => "INTERNAL_ERROR",
-            Self::Io(_) => "IO_ERROR",
-            Self::Serialization(_) => "SERIALIZATION_ERROR",
-            Self::InstallError(_) => "INSTALL_ERROR",
-            Self::ToolExecutionError(_) => "TOOL_EXECUTION_ERROR",
+        self.code().as_str()
+    }
+
+    /// Payload for JSON-RPC `error.data`
+    pub fn data(&self) -> Value {
+        let code = self.code();
+        let mut data = json!({
+            "code": code,
+            "retryable": code.is_retryable(),
+        });
+        if let Self::Timeout(ms) | Self::UpstreamTimeout(ms) = self {
+            data["timeout_ms"] = json!(ms);
+        }
+        if let Self::Overloaded(secs) = self {
+            data["retry_after_secs"] = json!(secs);
         }
+        if let Self::ConsentRequired(tool) = self {
+            data["tool"] = json!(tool);
+        }
+        data
     }
 }
 
@@ -98,9 +240,14 @@ impl IntoResponse for McpError {
         let body = Json(json!({
             "error": self.error_code(),
             "message": self.to_string(),
+            "retryable": self.code().is_retryable(),
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Self::Overloaded(secs) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, secs.into());
+        }
+        response
     }
 }
 
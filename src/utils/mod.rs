@@ -1,7 +1,9 @@
 pub mod errors;
 pub mod metrics;
+pub mod process;
+pub mod request_trace;
 pub mod shutdown;
 
-pub use errors::{McpError, McpResult};
+pub use errors::{ErrorCode, McpError, McpResult};
 pub use metrics::{MetricsCollector, SharedMetrics, metrics_middleware};
 pub use shutdown::{ShutdownCoordinator, ShutdownGuard};
@@ -0,0 +1,118 @@
+//! Running short-lived child processes to completion
+//!
+//! Output is read while the process runs, so one writing more than a pipe
+//! holds can't stall waiting for a reader, and a process that runs too long,
+//! or whose caller gives up on it, is killed rather than left behind.
+
+use std::process::Output;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Child;
+
+/// Kills the child if it is dropped before it has exited
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        if let Ok(None) = self.0.try_wait() {
+            let _ = self.0.start_kill();
+        }
+    }
+}
+
+async fn read_all(pipe: Option<impl AsyncRead + Unpin>) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        pipe.read_to_end(&mut buf).await?;
+    }
+    Ok(buf)
+}
+
+/// Feed `input` to the child's stdin, then collect its output and status
+///
+/// Returns `None` once `timeout` passes, after killing the child. Stdin is
+/// closed after `input` is written, or at once without any.
+pub async fn output_with_timeout(
+    mut child: Child,
+    input: Option<&[u8]>,
+    timeout: Duration,
+) -> std::io::Result<Option<Output>> {
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let mut child = KillOnDrop(child);
+
+    let run = async {
+        let write = async {
+            if let (Some(mut stdin), Some(input)) = (stdin, input) {
+                // Processes that ignore their input may exit before reading it
+                let _ = stdin.write_all(input).await;
+            }
+        };
+        let (_, stdout, stderr, status) =
+            tokio::join!(write, read_all(stdout), read_all(stderr), child.0.wait());
+        Ok(Output {
+            status: status?,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    };
+
+    let result = tokio::time::timeout(timeout, run).await;
+    match result {
+        Ok(output) => output.map(Some),
+        Err(_) => {
+            child.0.kill().await?;
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    fn spawn(script: &str) -> Child {
+        Command::new("sh")
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_output_larger_than_a_pipe() {
+        let child = spawn("head -c 1000000 /dev/zero; head -c 1000000 /dev/zero >&2");
+        let output = output_with_timeout(child, None, Duration::from_secs(10))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!((output.stdout.len(), output.stderr.len()), (1_000_000, 1_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_input_is_passed_on_stdin() {
+        let output = output_with_timeout(spawn("cat"), Some(b"hello"), Duration::from_secs(10))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(output.stdout, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_the_child() {
+        let child = spawn("exec sleep 30");
+        let pid = child.id().unwrap() as libc::pid_t;
+        let output = output_with_timeout(child, None, Duration::from_millis(300))
+            .await
+            .unwrap();
+        assert!(output.is_none());
+        // SAFETY: signal 0 only checks that the process exists
+        assert_eq!(unsafe { libc::kill(pid, 0) }, -1);
+    }
+}
@@ -0,0 +1,272 @@
+//! Phase timing for slow request traces
+//!
+//! With `[slow_requests]` enabled, every HTTP request runs inside a
+//! [`RequestTrace`]. Code on the request path marks phases (auth, routing,
+//! pool checkout, upstream, serialization) with [`phase`]; nested phases
+//! form stacks like `routing;upstream`. Requests slower than the threshold
+//! are logged with their request ID and kept for the admin API, including
+//! each stack's self time in folded flamegraph format.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static TRACE: Arc<RequestTrace>;
+}
+
+/// Name of the root frame in folded stacks
+const ROOT: &str = "request";
+
+/// Timing of one phase of a request
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    /// Enclosing phases and this one, joined with `;`
+    pub stack: String,
+    /// Start, relative to the start of the request
+    pub offset_ms: f64,
+    pub duration_ms: f64,
+    /// Duration minus nested phases
+    pub self_ms: f64,
+}
+
+struct OpenPhase {
+    seq: u64,
+    name: &'static str,
+    started: Instant,
+    nested: Duration,
+}
+
+#[derive(Default)]
+struct TraceState {
+    open: Vec<OpenPhase>,
+    next_seq: u64,
+    phases: Vec<PhaseTiming>,
+}
+
+/// Phases recorded so far for one request
+pub struct RequestTrace {
+    id: String,
+    started: Instant,
+    state: Mutex<TraceState>,
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl RequestTrace {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            started: Instant::now(),
+            state: Mutex::new(TraceState::default()),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn open(&self, name: &'static str) -> u64 {
+        let mut state = self.state.lock();
+        state.next_seq += 1;
+        let seq = state.next_seq;
+        state.open.push(OpenPhase {
+            seq,
+            name,
+            started: Instant::now(),
+            nested: Duration::ZERO,
+        });
+        seq
+    }
+
+    fn close(&self, seq: u64) {
+        let mut state = self.state.lock();
+        let Some(pos) = state.open.iter().rposition(|p| p.seq == seq) else {
+            return;
+        };
+        let phase = state.open.remove(pos);
+        let duration = phase.started.elapsed();
+
+        let stack = state.open[..pos]
+            .iter()
+            .map(|p| p.name)
+            .chain(std::iter::once(phase.name))
+            .collect::<Vec<_>>()
+            .join(";");
+        if let Some(parent) = pos.checked_sub(1).and_then(|i| state.open.get_mut(i)) {
+            parent.nested += duration;
+        }
+        state.phases.push(PhaseTiming {
+            stack,
+            offset_ms: ms(phase.started.duration_since(self.started)),
+            duration_ms: ms(duration),
+            self_ms: ms(duration.saturating_sub(phase.nested)),
+        });
+    }
+
+    /// Finished phases, in the order they started
+    pub fn phases(&self) -> Vec<PhaseTiming> {
+        let mut phases = self.state.lock().phases.clone();
+        phases.sort_by(|a, b| a.offset_ms.total_cmp(&b.offset_ms));
+        phases
+    }
+}
+
+/// Folded stacks (`request;routing;upstream 1234`) with self time in
+/// microseconds, the input format of flamegraph tools
+pub fn folded(phases: &[PhaseTiming], total: Duration) -> Vec<String> {
+    let top_level: f64 = phases
+        .iter()
+        .filter(|p| !p.stack.contains(';'))
+        .map(|p| p.duration_ms)
+        .sum();
+    let root_self = (ms(total) - top_level).max(0.0);
+
+    std::iter::once(format!("{} {}", ROOT, (root_self * 1000.0).round() as u64))
+        .chain(phases.iter().map(|p| {
+            format!("{};{} {}", ROOT, p.stack, (p.self_ms * 1000.0).round() as u64)
+        }))
+        .collect()
+}
+
+/// Run `f` as the request traced by `trace`
+pub async fn traced<F: Future>(trace: Arc<RequestTrace>, f: F) -> F::Output {
+    TRACE.scope(trace, f).await
+}
+
+/// ID of the traced request this task is serving, if any
+pub fn current_id() -> Option<String> {
+    TRACE.try_with(|trace| trace.id.clone()).ok()
+}
+
+/// Ends its phase when dropped
+#[must_use = "the phase ends when the guard is dropped"]
+pub struct PhaseGuard {
+    trace: Option<Arc<RequestTrace>>,
+    seq: u64,
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        if let Some(trace) = &self.trace {
+            trace.close(self.seq);
+        }
+    }
+}
+
+/// Start a phase of the current request; a no-op outside traced requests
+pub fn phase(name: &'static str) -> PhaseGuard {
+    let trace = TRACE.try_with(Arc::clone).ok();
+    let seq = trace.as_ref().map_or(0, |trace| trace.open(name));
+    PhaseGuard { trace, seq }
+}
+
+/// A request that took longer than the threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowRequest {
+    pub id: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub started_at: DateTime<Utc>,
+    pub total_ms: f64,
+    pub phases: Vec<PhaseTiming>,
+    pub folded: Vec<String>,
+}
+
+/// Most recent slow requests
+pub struct SlowRequestLog {
+    threshold: Duration,
+    retain: usize,
+    entries: Mutex<VecDeque<SlowRequest>>,
+}
+
+impl SlowRequestLog {
+    pub fn new(threshold: Duration, retain: usize) -> Self {
+        Self {
+            threshold,
+            retain,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    pub fn record(&self, request: SlowRequest) {
+        let mut entries = self.entries.lock();
+        entries.push_front(request);
+        entries.truncate(self.retain);
+    }
+
+    /// Slow requests, newest first
+    pub fn list(&self) -> Vec<SlowRequest> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<SlowRequest> {
+        self.entries.lock().iter().find(|r| r.id == id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nested_phases() {
+        let trace = Arc::new(RequestTrace::new("req-1"));
+        traced(trace.clone(), async {
+            assert_eq!(current_id().as_deref(), Some("req-1"));
+            drop(phase("auth"));
+            let _routing = phase("routing");
+            {
+                let _upstream = phase("upstream");
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await;
+        assert!(current_id().is_none());
+
+        let phases = trace.phases();
+        let stacks: Vec<_> = phases.iter().map(|p| p.stack.as_str()).collect();
+        assert_eq!(stacks, vec!["auth", "routing", "routing;upstream"]);
+        assert!(phases[2].duration_ms >= 20.0);
+        assert!(phases[1].self_ms < phases[2].duration_ms);
+
+        let folded = folded(&phases, trace.elapsed());
+        assert!(folded[0].starts_with("request "));
+        assert!(folded[3].starts_with("request;routing;upstream "));
+    }
+
+    #[test]
+    fn test_log_keeps_newest() {
+        let log = SlowRequestLog::new(Duration::from_millis(100), 2);
+        for id in ["a", "b", "c"] {
+            log.record(SlowRequest {
+                id: id.to_string(),
+                method: "POST".to_string(),
+                path: "/mcp".to_string(),
+                status: 200,
+                started_at: Utc::now(),
+                total_ms: 150.0,
+                phases: Vec::new(),
+                folded: Vec::new(),
+            });
+        }
+        let ids: Vec<_> = log.list().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec!["c", "b"]);
+        assert!(log.get("a").is_none());
+    }
+}
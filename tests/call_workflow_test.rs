@@ -1,6 +1,7 @@
 //! Integration tests for the call command workflow
 
 use supermcp::cli::call::parse_function_style;
+use supermcp::cli::output::OutputFormat;
 use tempfile::TempDir;
 use tokio::fs;
 
@@ -22,6 +23,7 @@ async fn test_call_with_empty_config() {
         false,
         false,
         false,
+        None,
     )
     .await;
 
@@ -40,7 +42,7 @@ async fn test_list_providers_with_empty_config() {
     // Should not error with empty config
     let result = supermcp::cli::call::list_providers(
         Some(config_path.to_str().unwrap()),
-        false,
+        OutputFormat::Table,
     )
     .await;
 
@@ -1,6 +1,8 @@
 //! CLI command tests
 
 use supermcp::cli;
+use supermcp::cli::exit::ExitStatus;
+use supermcp::cli::output::OutputFormat;
 use tempfile::TempDir;
 use tokio::fs;
 
@@ -62,6 +64,29 @@ async fn test_mcp_add_duplicate() {
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_mcp_bulk_enable_and_tag() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let path = config_path.to_str().unwrap();
+
+    for name in ["github", "github-enterprise", "gitlab"] {
+        cli::mcp::add(path, name, "echo", None, None, Some(vec!["git".to_string()]), None)
+            .await
+            .unwrap();
+    }
+
+    cli::mcp::set_enabled(path, "github*", false).await.unwrap();
+    cli::mcp::tag(path, "git", &["vcs".to_string()], true).await.unwrap();
+    assert!(cli::mcp::set_enabled(path, "bitbucket*", false).await.is_err());
+
+    let content = fs::read_to_string(&config_path).await.unwrap();
+    let config: supermcp::Config = toml::from_str(&content).unwrap();
+    let enabled: Vec<bool> = config.servers.iter().map(|s| s.enabled).collect();
+    assert_eq!(enabled, vec![false, false, true]);
+    assert!(config.servers.iter().all(|s| s.tags == ["git", "vcs"]));
+}
+
 #[tokio::test]
 async fn test_mcp_list_empty() {
     let temp_dir = TempDir::new().unwrap();
@@ -73,7 +98,7 @@ async fn test_mcp_list_empty() {
         .unwrap();
 
     // Should not error even with no servers
-    cli::mcp::list(config_path.to_str().unwrap()).await.unwrap();
+    cli::mcp::list(config_path.to_str().unwrap(), OutputFormat::Table).await.unwrap();
 }
 
 #[tokio::test]
@@ -143,7 +168,7 @@ async fn test_preset_list_empty() {
         .await
         .unwrap();
 
-    cli::preset::list(config_path.to_str().unwrap()).await.unwrap();
+    cli::preset::list(config_path.to_str().unwrap(), OutputFormat::Table).await.unwrap();
 }
 
 #[tokio::test]
@@ -187,8 +212,9 @@ description = "Development"
 "#;
     fs::write(&config_path, config).await.unwrap();
 
-    // Should not error
-    cli::preset::test(config_path.to_str().unwrap(), "dev").await.unwrap();
+    let path = config_path.to_str().unwrap();
+    assert_eq!(cli::preset::test(path, "dev", false).await.unwrap(), ExitStatus::Ok);
+    assert_eq!(cli::preset::test(path, "prod", true).await.unwrap(), ExitStatus::NotFound);
 }
 
 #[test]
@@ -155,6 +155,7 @@ fn test_server_config_serialization() {
         port: 8080,
         cert_path: Some("/path/to/cert.pem".to_string()),
         key_path: Some("/path/to/key.pem".to_string()),
+        ..Default::default()
     };
     
     let json = serde_json::to_string(&config).unwrap();
@@ -60,6 +60,7 @@ async fn test_discovered_mcp_structure() {
         description: None,
         source_path: PathBuf::new(),
         auto_approve: Some(false),
+        remote: None,
     };
 
     assert_eq!(mcp.name, "test");
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
-use supermcp::utils::errors::McpError;
+use supermcp::core::protocol::{JsonRpcError, JsonRpcResponse, RequestId};
+use supermcp::utils::errors::{ErrorCode, McpError};
 
 #[test]
 fn test_error_status_codes() {
@@ -32,3 +33,37 @@ fn test_error_codes() {
         "SANDBOX_ERROR"
     );
 }
+
+#[test]
+fn test_taxonomy_codes() {
+    assert_eq!(McpError::UpstreamTimeout(30000).error_code(), "UPSTREAM_TIMEOUT");
+    assert_eq!(
+        McpError::SandboxDenied("test".to_string()).status_code(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        McpError::QuotaExceeded("test".to_string()).status_code(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        serde_json::to_value(ErrorCode::QuotaExceeded).unwrap(),
+        "QUOTA_EXCEEDED"
+    );
+}
+
+#[test]
+fn test_json_rpc_error_data() {
+    let response = JsonRpcResponse::from_error(RequestId::Number(7), &McpError::UpstreamTimeout(30000));
+    let error = response.error.unwrap();
+    assert_eq!(error.code, -32000);
+    assert_eq!(error.message, "upstream timed out after 30000ms");
+
+    let data = error.data.unwrap();
+    assert_eq!(data["code"], "UPSTREAM_TIMEOUT");
+    assert_eq!(data["retryable"], true);
+    assert_eq!(data["timeout_ms"], 30000);
+
+    let error = JsonRpcError::from(&McpError::InvalidRequest("bad".to_string()));
+    assert_eq!(error.code, -32600);
+    assert_eq!(error.data.unwrap()["retryable"], false);
+}
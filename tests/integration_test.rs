@@ -19,6 +19,7 @@ async fn test_full_config_workflow() {
             port: 3000,
             cert_path: None,
             key_path: None,
+            ..Default::default()
         },
         servers: vec![
             McpServerConfig {
@@ -29,6 +30,7 @@ async fn test_full_config_workflow() {
                 tags: vec!["filesystem".to_string()],
                 description: Some("Filesystem server".to_string()),
                 sandbox: Default::default(),
+                ..Default::default()
             }
         ],
         presets: vec![
@@ -0,0 +1,212 @@
+//! 1MCP API conformance tests
+//!
+//! Runs the request and response shapes documented in the migration guide
+//! against `one_mcp_routes`.
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use supermcp::compat::one_mcp_routes;
+use supermcp::config::PresetConfig;
+use supermcp::core::ServerManager;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    one_mcp_routes(
+        Arc::new(ServerManager::new()),
+        vec![PresetConfig {
+            name: "dev".to_string(),
+            tags: vec!["dev".to_string()],
+            description: Some("Development tools".to_string()),
+        }],
+    )
+}
+
+async fn call(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(body) => {
+            request = request.header("content-type", "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null)
+    };
+    (status, value)
+}
+
+fn assert_api_error(body: &Value, code: &str) {
+    assert_eq!(body["error"], code);
+    assert!(body["message"].is_string());
+}
+
+#[tokio::test]
+async fn test_health_and_info() {
+    let app = app();
+
+    let (status, body) = call(&app, Method::GET, "/v1/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "healthy");
+    assert!(body["version"].is_string());
+
+    let (status, body) = call(&app, Method::GET, "/v1/info", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["api_version"], "v1");
+    assert_eq!(body["compatible_with"], "1mcp");
+    assert_eq!(body["servers"], json!({ "total": 0, "healthy": 0 }));
+}
+
+#[tokio::test]
+async fn test_server_list_shape() {
+    let (status, body) = call(&app(), Method::GET, "/v1/servers", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "servers": [], "total": 0 }));
+}
+
+#[tokio::test]
+async fn test_unknown_server_is_404_everywhere() {
+    let app = app();
+    let cases = [
+        (Method::GET, "/v1/servers/missing", None),
+        (Method::PUT, "/v1/servers/missing", Some(json!({ "enabled": true }))),
+        (Method::DELETE, "/v1/servers/missing", None),
+        (Method::POST, "/v1/servers/missing/start", None),
+        (Method::POST, "/v1/servers/missing/stop", None),
+        (Method::POST, "/v1/servers/missing/restart", None),
+        (Method::GET, "/v1/servers/missing/status", None),
+    ];
+
+    for (method, uri, body) in cases {
+        let (status, body) = call(&app, method.clone(), uri, body).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{} {}", method, uri);
+        assert_api_error(&body, "SERVER_NOT_FOUND");
+    }
+}
+
+#[tokio::test]
+async fn test_create_server_rejects_malformed_body() {
+    let (status, _) = call(
+        &app(),
+        Method::POST,
+        "/v1/servers",
+        Some(json!({ "name": "no-command" })),
+    )
+    .await;
+    assert!(status.is_client_error());
+}
+
+#[tokio::test]
+async fn test_bulk_reports_per_server_results() {
+    let app = app();
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/v1/servers/bulk",
+        Some(json!({ "action": "stop", "servers": ["b", "a", "a"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["action"], "stop");
+    assert_eq!(body["succeeded"], 0);
+    assert_eq!(body["failed"], 2);
+    assert_eq!(body["results"][0]["name"], "a");
+    assert_eq!(body["results"][0]["success"], false);
+    assert!(body["results"][0]["error"].is_string());
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/v1/servers/bulk",
+        Some(json!({ "action": "restart" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"], json!([]));
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/v1/servers/bulk",
+        Some(json!({ "action": "explode" })),
+    )
+    .await;
+    assert!(status.is_client_error());
+}
+
+#[tokio::test]
+async fn test_preset_crud() {
+    let app = app();
+
+    let (status, body) = call(&app, Method::GET, "/v1/presets", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 1);
+    assert_eq!(
+        body["presets"][0],
+        json!({ "name": "dev", "description": "Development tools", "tags": ["dev"], "servers": [] })
+    );
+
+    let created = json!({ "name": "search", "tags": ["web", "search"] });
+    let (status, body) = call(&app, Method::POST, "/v1/presets", Some(created.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["name"], "search");
+    assert_eq!(body["tags"], json!(["web", "search"]));
+
+    let (status, body) = call(&app, Method::POST, "/v1/presets", Some(created)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_api_error(&body, "PRESET_EXISTS");
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/v1/presets",
+        Some(json!({ "name": "empty", "tags": [] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_api_error(&body, "INVALID_PRESET");
+
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        "/v1/presets/search",
+        Some(json!({ "tags": ["web"], "description": "Web search" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tags"], json!(["web"]));
+    assert_eq!(body["description"], "Web search");
+
+    let (status, body) = call(&app, Method::GET, "/v1/presets/search", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["description"], "Web search");
+
+    let (status, _) = call(&app, Method::DELETE, "/v1/presets/search", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    for method in [Method::GET, Method::DELETE] {
+        let (status, body) = call(&app, method, "/v1/presets/search", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_api_error(&body, "PRESET_NOT_FOUND");
+    }
+}
+
+#[tokio::test]
+async fn test_compat_header() {
+    let app = app().layer(axum::middleware::from_fn(supermcp::compat::one_mcp_compat_middleware));
+    let response = app
+        .oneshot(Request::builder().uri("/v1/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-super-mcp-compat"], "1mcp-v1");
+}
@@ -29,7 +29,7 @@ impl Provider for TestProvider {
     }
 
     fn provider_type(&self) -> ProviderType {
-        self.ptype.clone()
+        self.ptype
     }
 
     async fn is_available(&self) -> bool {
@@ -8,6 +8,7 @@ fn create_test_registry_config() -> RegistryConfig {
         url: "https://registry.modelcontextprotocol.io".to_string(),
         cache_dir: PathBuf::from("/tmp/super-mcp-test-registry"),
         cache_ttl_hours: 24,
+        ..Default::default()
     }
 }
 
@@ -22,6 +22,7 @@ async fn test_add_server() {
         tags: vec!["test".to_string()],
         description: Some("Test server".to_string()),
         sandbox: Default::default(),
+        ..Default::default()
     };
     
     let _result = manager.add_server(config).await;
@@ -53,6 +54,7 @@ async fn test_get_servers_by_tags() {
         tags: vec!["filesystem".to_string(), "local".to_string()],
         description: None,
         sandbox: Default::default(),
+        ..Default::default()
     };
 
     let config2 = McpServerConfig {
@@ -63,6 +65,7 @@ async fn test_get_servers_by_tags() {
         tags: vec!["network".to_string()],
         description: None,
         sandbox: Default::default(),
+        ..Default::default()
     };
     
     // Try to add servers (may fail in test environment)
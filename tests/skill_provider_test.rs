@@ -61,7 +61,42 @@ Arguments:
     let result = provider.call_tool("exec-skill.exec", serde_json::json!({"cmd": "echo hello"})).await;
 
     // Should either succeed or fail gracefully (depending on implementation)
-    assert!(result.is_ok() || result.unwrap().success == false);
+    assert!(result.is_ok() || !result.unwrap().success);
+}
+
+#[tokio::test]
+async fn test_skill_script_and_limits() {
+    let temp_dir = TempDir::new().unwrap();
+    let skill_dir = temp_dir.path().join("script-skill");
+    fs::create_dir_all(&skill_dir).await.unwrap();
+
+    let skill_content = r#"---
+name: script-skill
+description: Runs scripts
+resources:
+  max_memory_mb: 128
+  timeout_seconds: 5
+---
+
+### run
+Run the bundled script
+Script: ../outside.sh
+"#;
+
+    fs::write(skill_dir.join("SKILL.md"), skill_content).await.unwrap();
+    fs::write(temp_dir.path().join("outside.sh"), "echo escaped").await.unwrap();
+
+    let provider = SkillProvider::new("script-skill", skill_dir).await.unwrap();
+    assert_eq!(provider.limits().max_memory_mb, 128);
+    assert_eq!(provider.limits().timeout_seconds, 5);
+
+    let tools = provider.list_tools().await.unwrap();
+    assert_eq!(tools[0].description, Some("Run the bundled script".to_string()));
+    assert_eq!(tools[0].metadata["script"], "../outside.sh");
+
+    // Scripts outside the skill directory are refused
+    let result = provider.call_tool("script-skill.run", serde_json::json!({})).await.unwrap();
+    assert!(!result.success);
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 //! Tests for tools and providers commands
 
 use supermcp::cli::call::{list_providers, list_tools};
+use supermcp::cli::output::OutputFormat;
 use tempfile::TempDir;
 use tokio::fs;
 
@@ -22,6 +23,7 @@ async fn test_list_tools_with_empty_config() {
         false,
         false,
         false,
+        None,
     )
     .await;
 
@@ -47,6 +49,7 @@ async fn test_list_tools_with_invalid_provider() {
         false,
         false,
         false,
+        None,
     )
     .await;
 
@@ -65,7 +68,7 @@ async fn test_list_providers_with_empty_config() {
     // Should complete without error
     let result = list_providers(
         Some(config_path.to_str().unwrap()),
-        false,
+        OutputFormat::Table,
     )
     .await;
 
@@ -83,7 +86,7 @@ async fn test_list_providers_json_output() {
     // Should complete without error
     let result = list_providers(
         Some(config_path.to_str().unwrap()),
-        true, // json output
+        OutputFormat::Json,
     )
     .await;
 
@@ -108,6 +111,7 @@ async fn test_list_tools_json_output() {
         false,
         true, // json output
         false,
+        None,
     )
     .await;
 
@@ -132,6 +136,7 @@ async fn test_list_tools_all_flag() {
         false,
         false,
         true, // all
+        None,
     )
     .await;
 